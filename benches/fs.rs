@@ -1,7 +1,7 @@
 use criterion::{async_executor::AsyncExecutor, criterion_group, criterion_main, Criterion};
 use tempfile::NamedTempFile;
 
-criterion_group!(fs, read, write);
+criterion_group!(fs, read, read_fixed, write);
 criterion_main!(fs);
 
 struct CompleteIoRuntime;
@@ -54,6 +54,75 @@ fn read(c: &mut Criterion) {
     group.finish();
 }
 
+// Push the operation to the driver and wait for its completion.
+//
+// Like the runtime, the operation is leaked to the driver till it completes.
+fn read_with_driver<O: completeio::driver::OpCode + 'static>(
+    driver: &mut completeio::driver::Driver<'static>,
+    op: O,
+) -> O {
+    use completeio::driver::{CompleteIo, Entry, Operation};
+
+    let op = Box::leak(Box::new(op));
+    let op_ptr = op as *mut O;
+    driver
+        .try_push(Operation::new(op, 0))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    let mut entries = Vec::<Entry>::with_capacity(1);
+    while entries.is_empty() {
+        unsafe { driver.submit(None, &mut entries) }.unwrap();
+    }
+    entries.pop().unwrap().into_result().unwrap();
+    // SAFETY: the operation is completed and the driver doesn't reference it
+    *unsafe { Box::from_raw(op_ptr) }
+}
+
+fn read_fixed(c: &mut Criterion) {
+    use std::io::{IoSliceMut, Write};
+
+    use completeio::{
+        buf::IntoInner,
+        driver::{AsRawFd, CompleteIo, Driver, FixedBufRegistry},
+        op::{ReadAt, ReadAtFixed},
+    };
+
+    const BUF_LEN: usize = 65536;
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(&[1u8; BUF_LEN]).unwrap();
+    let file = std::fs::File::open(temp_file.path()).unwrap();
+
+    let mut group = c.benchmark_group("read_fixed");
+
+    group.bench_function("regular", |b| {
+        let mut driver = Driver::new().unwrap();
+        let fd = driver.attach(file.as_raw_fd()).unwrap();
+        let mut buffer = Vec::with_capacity(BUF_LEN);
+        b.iter(|| {
+            buffer.clear();
+            buffer = read_with_driver(&mut driver, ReadAt::new(fd, 0, std::mem::take(&mut buffer)))
+                .into_inner();
+        })
+    });
+
+    group.bench_function("fixed", |b| {
+        // registered buffers outlive the driver
+        let memory: &'static mut [u8] = Box::leak(vec![0u8; BUF_LEN].into_boxed_slice());
+        let bufs: &'static mut [IoSliceMut<'static>] =
+            Box::leak(Box::new([IoSliceMut::new(memory)]));
+        let mut driver = Driver::new().unwrap();
+        let registry: &'static FixedBufRegistry<'static> =
+            Box::leak(Box::new(driver.register_buffers(bufs).unwrap()));
+        let fd = driver.attach(file.as_raw_fd()).unwrap();
+        b.iter(|| {
+            let buffer = registry.check_out(0).unwrap();
+            read_with_driver(&mut driver, ReadAtFixed::new(fd, 0, buffer)).into_inner()
+        })
+    });
+
+    group.finish();
+}
+
 static CONTENT: &[u8] = include_bytes!("../Cargo.toml");
 
 fn write(c: &mut Criterion) {
@@ -87,7 +156,7 @@ fn write(c: &mut Criterion) {
         let temp_file = NamedTempFile::new().unwrap();
         b.to_async(CompleteIoRuntime).iter(|| async {
             let file = completeio::fs::File::create(temp_file.path()).unwrap();
-            let (res, _) = file.write_all_at(CONTENT, 0).await;
+            let (res, _) = file.write_all_at(0, CONTENT).await;
             res.unwrap();
         })
     });
@@ -1,6 +1,6 @@
 use criterion::{async_executor::AsyncExecutor, criterion_group, criterion_main, Criterion};
 
-criterion_group!(net, tcp, udp);
+criterion_group!(net, tcp, udp, send);
 criterion_main!(net);
 
 struct CompleteIoRuntime;
@@ -134,3 +134,25 @@ fn udp(c: &mut Criterion) {
 
     group.finish();
 }
+
+fn send(c: &mut Criterion) {
+    const SENDS: usize = 256;
+    static PACKET: &[u8] = &[1u8; 64];
+
+    let mut group = c.benchmark_group("send");
+
+    // back-to-back sends measure the overhead of the submission
+    group.bench_function("completeio", |b| {
+        let rx = completeio::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let tx = completeio::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        tx.connect(rx.local_addr().unwrap()).unwrap();
+        b.to_async(CompleteIoRuntime).iter(|| async {
+            for _ in 0..SENDS {
+                let (res, _) = tx.send(PACKET).await;
+                res.unwrap();
+            }
+        })
+    });
+
+    group.finish();
+}
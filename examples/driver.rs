@@ -7,11 +7,13 @@ use completeio::{
 };
 
 fn main() {
+    // the operation outlives the driver
+    let mut op;
     let mut driver = Driver::new().unwrap();
     let file = completeio::fs::File::open("Cargo.toml").unwrap();
     let fd = driver.attach(file.as_raw_fd()).unwrap();
 
-    let mut op = completeio::op::ReadAt::new(fd, 0, Vec::with_capacity(4096));
+    op = completeio::op::ReadAt::new(fd, 0, Vec::with_capacity(4096));
     let mut ops = VecDeque::from([(&mut op, 0).into()]);
     driver.push_queue(&mut ops);
 
@@ -23,6 +25,8 @@ fn main() {
     assert_eq!(entry.user_data(), 0);
 
     let n = entry.into_result().unwrap();
+    // the driver borrows the operation until it's dropped
+    drop(driver);
     let mut buffer = op.into_inner();
     unsafe {
         buffer.set_len(n);
@@ -0,0 +1,33 @@
+use std::time::Duration;
+
+use completeio::{net::TcpListener, signal::ctrl_c, task, time::interval};
+
+fn main() {
+    task::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        task::spawn_named("accept", async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                task::spawn_named("connection", async move {
+                    let (res, _) = stream.recv(Vec::with_capacity(1024)).await;
+                    res.unwrap();
+                })
+                .detach();
+            }
+        })
+        .detach();
+        task::spawn_named("tick", async {
+            let mut interval = interval(Duration::from_secs(1));
+            loop {
+                interval.tick().await;
+            }
+        })
+        .detach();
+
+        println!("press ctrl-c to dump the tasks");
+        ctrl_c().await.unwrap();
+        for task in task::dump_tasks() {
+            println!("{task:?}");
+        }
+    })
+}
@@ -0,0 +1,49 @@
+fn main() {
+    #[cfg(target_os = "linux")]
+    {
+        use std::time::{Duration, Instant};
+
+        use arrayvec::ArrayVec;
+        use completeio::{
+            driver::{CompleteIo, Driver, Entry, Operation},
+            op::Nop,
+        };
+
+        const SUBMISSIONS: u32 = 100_000;
+
+        // the cost of one submission of a nop, the driver enters the kernel every time
+        fn enter_cost(register_ring_fd: bool) -> Duration {
+            let mut ops = (0..SUBMISSIONS).map(|_| Nop::new()).collect::<Vec<_>>();
+            let mut driver = Driver::builder()
+                .register_ring_fd(register_ring_fd)
+                .build()
+                .unwrap();
+            assert_eq!(driver.capabilities().registered_ring, register_ring_fd);
+            let mut entries = ArrayVec::<Entry, 1>::new();
+            let start = Instant::now();
+            for op in &mut ops {
+                driver
+                    .try_push(Operation::new(op, 0))
+                    .unwrap_or_else(|_| panic!("queue is full"));
+                unsafe { driver.submit(None, &mut entries) }.unwrap();
+                entries.pop().unwrap().into_result().unwrap();
+            }
+            start.elapsed() / SUBMISSIONS
+        }
+
+        if !completeio::driver::probe()
+            .map(|capabilities| capabilities.kernel_version >= Some((5, 18, 0)))
+            .unwrap_or_default()
+        {
+            println!("the kernel doesn't support registered ring fds");
+            return;
+        }
+        // the kernel takes a reference of the plain fd when the file table is shared with
+        // another thread
+        let _thread = std::thread::spawn(std::thread::park);
+        // warm up
+        enter_cost(true);
+        println!("plain ring fd: {:?} per enter", enter_cost(false));
+        println!("registered ring fd: {:?} per enter", enter_cost(true));
+    }
+}
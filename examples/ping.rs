@@ -0,0 +1,109 @@
+//! Send ICMP echo requests to the address given as the argument, 127.0.0.1 by default.
+
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::{Duration, Instant},
+};
+
+use completeio::{
+    net::RawSocket,
+    time::{sleep, timeout},
+};
+use socket2::{Domain, Protocol, Type};
+
+const ECHO_REQUEST_V4: u8 = 8;
+const ECHO_REPLY_V4: u8 = 0;
+const ECHO_REQUEST_V6: u8 = 128;
+const ECHO_REPLY_V6: u8 = 129;
+const COUNT: u16 = 4;
+const INTERVAL: Duration = Duration::from_secs(1);
+
+fn checksum(packet: &[u8]) -> u16 {
+    let mut sum = packet
+        .chunks(2)
+        .map(|word| u32::from(u16::from_be_bytes([word[0], *word.get(1).unwrap_or(&0)])))
+        .sum::<u32>();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+fn echo_request(v6: bool, id: u16, seq: u16) -> Vec<u8> {
+    let ty = if v6 { ECHO_REQUEST_V6 } else { ECHO_REQUEST_V4 };
+    let mut packet = vec![ty, 0, 0, 0];
+    packet.extend_from_slice(&id.to_be_bytes());
+    packet.extend_from_slice(&seq.to_be_bytes());
+    packet.extend_from_slice(b"completeio ping");
+    // the kernel computes the checksum of ICMPv6 messages
+    if !v6 {
+        let sum = checksum(&packet);
+        packet[2..4].copy_from_slice(&sum.to_be_bytes());
+    }
+    packet
+}
+
+fn main() {
+    let ip: IpAddr = std::env::args()
+        .nth(1)
+        .map(|arg| arg.parse().expect("invalid IP address"))
+        .unwrap_or([127, 0, 0, 1].into());
+    let (domain, protocol) = match ip {
+        IpAddr::V4(_) => (Domain::IPV4, Protocol::ICMPV4),
+        IpAddr::V6(_) => (Domain::IPV6, Protocol::ICMPV6),
+    };
+
+    completeio::task::block_on(async {
+        let socket = match RawSocket::new(domain, protocol) {
+            Ok(socket) => socket,
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        };
+        let raw = socket.socket_type().unwrap() == Type::RAW;
+        let id = std::process::id() as u16;
+        let addr = SocketAddr::new(ip, 0);
+        for seq in 0..COUNT {
+            let start = Instant::now();
+            let request = echo_request(ip.is_ipv6(), id, seq);
+            socket.send_to(request, addr).await.0.unwrap();
+            let reply = timeout(INTERVAL, async {
+                loop {
+                    let (res, packet) = socket.recv_from(Vec::with_capacity(1500)).await;
+                    let (len, _) = res.unwrap();
+                    // IPv4 raw sockets receive the IP header
+                    let offset = if raw && !ip.is_ipv6() {
+                        usize::from(packet[0] & 0x0f) * 4
+                    } else {
+                        0
+                    };
+                    let message = &packet[offset..len];
+                    let reply_ty = if ip.is_ipv6() {
+                        ECHO_REPLY_V6
+                    } else {
+                        ECHO_REPLY_V4
+                    };
+                    // datagram sockets receive only the replies to their requests, the
+                    // identifier is replaced by the kernel
+                    let matches = message.len() >= 8
+                        && message[0] == reply_ty
+                        && (!raw || message[4..6] == id.to_be_bytes())
+                        && message[6..8] == seq.to_be_bytes();
+                    if matches {
+                        break message.len();
+                    }
+                }
+            })
+            .await;
+            match reply {
+                Ok(len) => println!(
+                    "{len} bytes from {ip}: icmp_seq={seq} time={:?}",
+                    start.elapsed()
+                ),
+                Err(_) => println!("request timeout for icmp_seq={seq}"),
+            }
+            sleep(INTERVAL.saturating_sub(start.elapsed())).await;
+        }
+    })
+}
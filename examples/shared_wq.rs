@@ -0,0 +1,39 @@
+fn main() {
+    #[cfg(target_os = "linux")]
+    {
+        use std::thread;
+
+        use arrayvec::ArrayVec;
+        use completeio::driver::{CompleteIo, Driver, Entry, Operation};
+
+        // the first driver starts the kernel worker pool
+        let mut driver = Driver::new().unwrap();
+        let token = driver.work_queue_token().unwrap();
+
+        // one driver per thread, the other drivers share the pool of the first one
+        let threads = (1..4)
+            .map(|i| {
+                let token = token.clone();
+                thread::spawn(move || {
+                    let mut op = completeio::op::Nop::new();
+                    let mut driver = Driver::builder().attach_wq(&token).build().unwrap();
+                    driver
+                        .try_push(Operation::new(&mut op, i))
+                        .unwrap_or_else(|_| panic!("queue is full"));
+                    let mut entries = ArrayVec::<Entry, 1>::new();
+                    unsafe { driver.submit(None, &mut entries) }.unwrap();
+                    let entry = entries.pop().unwrap();
+                    println!("driver {} completed {}", i, entry.user_data());
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        // the token is dangling after the driver is dropped
+        drop(driver);
+        let err = Driver::builder().attach_wq(&token).build().err().unwrap();
+        println!("attaching to the dropped driver fails: {err}");
+    }
+}
@@ -0,0 +1,95 @@
+//! UDP echo benchmark which receives datagrams with a single multishot receive
+//! operation into a ring of provided buffers.
+
+#[cfg(target_os = "linux")]
+fn main() {
+    use std::{net::UdpSocket, thread, time::Instant};
+
+    use bumpalo::Bump;
+    use completeio::{
+        driver::{AsRawFd, BufRing, CompleteIo, Driver, Entry, Operation},
+        op::RecvMultishot,
+    };
+
+    const GROUP_ID: u16 = 0;
+    const RING_ENTRIES: u16 = 256;
+    const DATAGRAM_LEN: usize = 1024;
+    const WINDOW: usize = 64;
+    const ROUNDS: usize = 1000;
+    const TOTAL: usize = WINDOW * ROUNDS;
+
+    let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+    server.connect(client.local_addr().unwrap()).unwrap();
+    client.connect(server.local_addr().unwrap()).unwrap();
+
+    let client = thread::spawn(move || {
+        let datagram = [1u8; DATAGRAM_LEN];
+        let mut buffer = [0u8; DATAGRAM_LEN];
+        let start = Instant::now();
+        for _ in 0..ROUNDS {
+            for _ in 0..WINDOW {
+                client.send(&datagram).unwrap();
+            }
+            for _ in 0..WINDOW {
+                let n = client.recv(&mut buffer).unwrap();
+                assert_eq!(n, DATAGRAM_LEN);
+            }
+        }
+        start.elapsed()
+    });
+
+    // the ring and the operations outlive the driver
+    let ring = BufRing::new(GROUP_ID, RING_ENTRIES, DATAGRAM_LEN).unwrap();
+    let arena = Bump::new();
+    let mut driver = Driver::new().unwrap();
+    driver.register_buf_ring(&ring).unwrap();
+    let fd = driver.attach(server.as_raw_fd()).unwrap();
+
+    let recv = arena.alloc(RecvMultishot::new(fd, GROUP_ID));
+    driver
+        .try_push(Operation::new(recv, 0))
+        .unwrap_or_else(|_| panic!("queue is full"));
+
+    let mut entries = Vec::<Entry>::new();
+    let mut echoed = 0;
+    while echoed < TOTAL {
+        unsafe { driver.submit(None, &mut entries) }.unwrap();
+        for entry in entries.drain(..) {
+            let more = entry.has_more();
+            match (entry.buffer_id(), entry.into_result()) {
+                (Some(buffer_id), Ok(len)) => {
+                    // the buffer goes back to the ring after the echo
+                    let buffer = unsafe { ring.take(buffer_id, len) };
+                    server.send(&buffer).unwrap();
+                    echoed += 1;
+                }
+                // the ring ran out of buffers, the datagrams wait in the socket
+                (_, Err(e)) if e.raw_os_error() == Some(libc::ENOBUFS) => {}
+                (_, res) => panic!("unexpected result {res:?}"),
+            }
+            if !more && echoed < TOTAL {
+                // the multishot operation finished, submit it again
+                let recv = arena.alloc(RecvMultishot::new(fd, GROUP_ID));
+                driver
+                    .try_push(Operation::new(recv, 0))
+                    .unwrap_or_else(|_| panic!("queue is full"));
+            }
+        }
+    }
+    let elapsed = client.join().unwrap();
+
+    driver.try_cancel(0).unwrap();
+    unsafe { driver.submit(None, &mut entries) }.unwrap();
+    driver.unregister_buf_ring(GROUP_ID).unwrap();
+
+    println!(
+        "echoed {TOTAL} datagrams of {DATAGRAM_LEN} bytes in {elapsed:?}, {:.0} datagrams/s",
+        TOTAL as f64 / elapsed.as_secs_f64()
+    );
+}
+
+#[cfg(not(target_os = "linux"))]
+fn main() {
+    println!("multishot receive with provided buffers needs io-uring");
+}
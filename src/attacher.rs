@@ -7,18 +7,23 @@ use once_cell::sync::OnceCell as OnceLock;
 
 use crate::{
     driver::{AsRawFd, Fd},
-    task::RUNTIME,
+    task::{
+        runtime::{Runtime, RuntimeRef},
+        RUNTIME,
+    },
 };
 
 /// Attach a handle to the driver of current thread.
 ///
 /// A handle can and only can attach once to one driver. However, the handle
 /// itself is Send & Sync. We mark it !Send & !Sync to warn users, making them
-/// ensure that they are using it in the correct thread.
-#[derive(Debug, Clone)]
+/// ensure that they are using it in the correct thread. The use on another thread
+/// fails with [`io::ErrorKind::InvalidInput`].
+#[derive(Debug)]
 pub struct Attacher {
-    // Make it thread safe and !Send & !Sync.
-    once: OnceLock<Fd>,
+    // Make it thread safe and !Send & !Sync. The fd is paired with the runtime it's attached
+    // to, the operations of the handle are submitted to it without the thread-local lookup.
+    once: OnceLock<(Fd, RuntimeRef)>,
 }
 
 impl Attacher {
@@ -29,8 +34,44 @@ impl Attacher {
     }
 
     pub fn attach(&self, source: &impl AsRawFd) -> io::Result<Fd> {
-        self.once
-            .get_or_try_init(|| RUNTIME.with(|runtime| runtime.attach(source.as_raw_fd())))
-            .map(|r| *r)
+        self.attach_runtime(source).map(|(fd, _)| fd)
+    }
+
+    /// Attach the handle, returning the runtime it's attached to as well.
+    pub fn attach_runtime(&self, source: &impl AsRawFd) -> io::Result<(Fd, &Runtime)> {
+        let (fd, runtime) = self.once.get_or_try_init(|| {
+            RUNTIME.with(|runtime| {
+                Ok::<_, io::Error>((runtime.attach(source.as_raw_fd())?, runtime.runtime_ref()))
+            })
+        })?;
+        Ok((*fd, runtime.get()?))
+    }
+
+    /// Detach the handle from the driver of current thread, it's attached again on the next use.
+    ///
+    /// The handle should be reset on the thread it's attached before moving it to another
+    /// runtime.
+    pub fn reset(&mut self, source: &impl AsRawFd) -> io::Result<()> {
+        if let Some((_, runtime)) = self.once.get() {
+            runtime.get()?.detach(source.as_raw_fd())?;
+            self.once.take();
+        }
+        Ok(())
+    }
+
+    /// Create attacher for a duplicated handle of the source.
+    ///
+    /// IOCP: the duplicated handle shares the completion port association with the source.
+    /// io-uring/kqueue: the duplicated fd is attached separately on the first use.
+    pub fn duplicate(&self, duplicated: &impl AsRawFd) -> Self {
+        #[cfg(target_os = "windows")]
+        if let Some((_, runtime)) = self.once.get() {
+            let once = OnceLock::new();
+            _ = once.set((Fd::from_raw(duplicated.as_raw_fd()), *runtime));
+            return Self { once };
+        }
+        #[cfg(not(target_os = "windows"))]
+        let _ = duplicated;
+        Self::new()
     }
 }
@@ -84,6 +84,8 @@ pub struct VectoredBufWrapper<'arena, T: 'arena> {
     buffers: Box<[T]>,
     io_slices: Box<[IoSlice<'arena>]>,
     io_slices_mut: Box<[IoSliceMut<'arena>]>,
+    // the leading slices of `io_slices` skipped by `advance`
+    written_slices: usize,
 }
 
 impl<T> IntoInner for VectoredBufWrapper<'_, T> {
@@ -114,13 +116,54 @@ impl<'arena, T: IoBufMut<'arena>> From<Box<[T]>> for VectoredBufWrapper<'arena,
             buffers,
             io_slices,
             io_slices_mut,
+            written_slices: 0,
         }
     }
 }
 
+impl<'arena, T: IoBuf<'arena>> VectoredBufWrapper<'arena, T> {
+    /// Returns the number of bytes left to be written from the buffers.
+    pub fn remaining(&self) -> usize {
+        self.io_slices[self.written_slices..]
+            .iter()
+            .map(|slice| slice.len())
+            .sum()
+    }
+
+    /// Skips `n` written bytes, the next vectored write starts in the middle of the partially
+    /// written buffer.
+    ///
+    /// It is used to resume a short write like
+    /// [`File::write_all_vectored_at`](crate::fs::File::write_all_vectored_at) does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than [`remaining`](Self::remaining).
+    pub fn advance(&mut self, mut n: usize) {
+        while let Some(slice) = self.io_slices.get_mut(self.written_slices) {
+            if n < slice.len() {
+                slice.advance(n);
+                return;
+            }
+            n -= slice.len();
+            self.written_slices += 1;
+        }
+        assert_eq!(n, 0, "advancing past the end of the buffers");
+    }
+
+    /// Restores the bytes skipped by [`advance`](Self::advance).
+    pub fn rewind(&mut self) {
+        for (slice, buf) in self.io_slices.iter_mut().zip(self.buffers.iter()) {
+            // SAFETY: buffer Unpin and could be self referenced
+            *slice = IoSlice::new(unsafe { &*(buf.as_slice() as *const _ as *const _) });
+        }
+        self.written_slices = 0;
+    }
+}
+
 impl<'arena, T: IoBuf<'arena>> AsIoSlices<'arena> for VectoredBufWrapper<'arena, T> {
     unsafe fn as_io_slices(&self) -> &[IoSlice<'_>] {
-        &self.io_slices
+        &self.io_slices[self.written_slices..]
     }
 }
 
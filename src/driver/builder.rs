@@ -0,0 +1,304 @@
+#[cfg(feature = "hooks")]
+use std::sync::Arc;
+use std::{io, time::Duration};
+
+#[cfg(feature = "hooks")]
+use crate::driver::DriverHooks;
+#[cfg(target_os = "linux")]
+use crate::driver::{BackendKind, WorkQueueToken};
+use crate::driver::{Driver, Hooks};
+
+/// Builder of [`Driver`] with configurable queue sizes.
+///
+/// ```
+/// use completeio::driver::{CompleteIo, Driver};
+///
+/// let driver = Driver::builder().sq_entries(64).build().unwrap();
+/// assert_eq!(driver.capacity_left(), 64);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DriverBuilder {
+    pub(crate) sq_entries: u32,
+    pub(crate) cq_entries: Option<u32>,
+    pub(crate) event_capacity: Option<usize>,
+    pub(crate) concurrency: u32,
+    pub(crate) files_to_register: u32,
+    pub(crate) sqpoll_idle: Option<Duration>,
+    pub(crate) sqpoll_cpu: Option<u32>,
+    pub(crate) coop_taskrun: bool,
+    pub(crate) defer_taskrun: bool,
+    pub(crate) single_issuer: bool,
+    pub(crate) blocking_threads: usize,
+    pub(crate) check_user_data: bool,
+    pub(crate) register_ring_fd: bool,
+    pub(crate) napi_busy_poll: Option<Duration>,
+    pub(crate) napi_prefer_busy_poll: bool,
+    pub(crate) hooks: Hooks,
+    #[cfg(target_os = "linux")]
+    pub(crate) attach_wq: Option<WorkQueueToken>,
+    #[cfg(target_os = "linux")]
+    pub(crate) cancel_any: bool,
+    #[cfg(target_os = "linux")]
+    pub(crate) backend: Option<BackendKind>,
+}
+
+impl Default for DriverBuilder {
+    fn default() -> Self {
+        Self {
+            sq_entries: 1024,
+            cq_entries: None,
+            event_capacity: None,
+            concurrency: 0,
+            files_to_register: 0,
+            sqpoll_idle: None,
+            sqpoll_cpu: None,
+            coop_taskrun: false,
+            defer_taskrun: false,
+            single_issuer: false,
+            blocking_threads: 4,
+            check_user_data: cfg!(debug_assertions),
+            register_ring_fd: true,
+            napi_busy_poll: None,
+            napi_prefer_busy_poll: false,
+            hooks: Hooks::default(),
+            #[cfg(target_os = "linux")]
+            attach_wq: None,
+            #[cfg(target_os = "linux")]
+            cancel_any: true,
+            #[cfg(target_os = "linux")]
+            backend: None,
+        }
+    }
+}
+
+impl DriverBuilder {
+    /// Create the builder with 1024 submission queue entries and without registered files.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of operations that could be pushed before submission.
+    ///
+    /// io-uring rounds the number up to a power of two.
+    pub fn sq_entries(mut self, entries: u32) -> Self {
+        self.sq_entries = entries;
+        self
+    }
+
+    /// Set the size of io-uring completion queue, it defaults to twice the submission queue
+    /// entries. The size limits the number of operations in flight.
+    ///
+    /// Other drivers ignore the value.
+    pub fn cq_entries(mut self, entries: u32) -> Self {
+        self.cq_entries = Some(entries);
+        self
+    }
+
+    /// Set the number of readiness or completion events received from kqueue or IOCP in one
+    /// poll, it defaults to the submission queue entries.
+    ///
+    /// io-uring driver ignores the value.
+    pub fn event_capacity(mut self, capacity: usize) -> Self {
+        self.event_capacity = Some(capacity);
+        self
+    }
+
+    /// Set the number of threads IOCP allows to process completions concurrently, zero means
+    /// the number of processors.
+    ///
+    /// Other drivers ignore the value.
+    pub fn concurrency(mut self, threads: u32) -> Self {
+        self.concurrency = threads;
+        self
+    }
+
+    /// Set the number of files to register, see
+    /// [`CompleteIo::register_files`](crate::driver::CompleteIo::register_files).
+    pub fn files_to_register(mut self, files: u32) -> Self {
+        self.files_to_register = files;
+        self
+    }
+
+    /// Enable io-uring submission queue polling by a kernel thread, which sleeps after `idle`
+    /// time without submissions.
+    ///
+    /// The driver enters the kernel only to wake up the sleeping thread or to wait for
+    /// completions. Linux before 5.11 requires `CAP_SYS_ADMIN` privilege and registered files
+    /// for operations. The driver creation fails with the permission error if the kernel
+    /// refuses the mode.
+    ///
+    /// The wakeups and waits enter the kernel with the registered ring fd, see
+    /// [`DriverBuilder::register_ring_fd`].
+    ///
+    /// Other drivers ignore the value.
+    pub fn sqpoll(mut self, idle: Duration) -> Self {
+        self.sqpoll_idle = Some(idle);
+        self
+    }
+
+    /// Bind the submission queue polling thread to `cpu`.
+    ///
+    /// The value is used together with [`DriverBuilder::sqpoll`].
+    pub fn sqpoll_cpu(mut self, cpu: u32) -> Self {
+        self.sqpoll_cpu = Some(cpu);
+        self
+    }
+
+    /// Set up io-uring with `IORING_SETUP_COOP_TASKRUN`, the kernel doesn't interrupt the
+    /// thread to run completions. Available since Linux 5.19.
+    ///
+    /// The flags which the kernel rejects are dropped, see
+    /// [`DriverCapabilities`](crate::driver::DriverCapabilities) for the applied ones. Other
+    /// drivers ignore the value.
+    pub fn coop_taskrun(mut self, enable: bool) -> Self {
+        self.coop_taskrun = enable;
+        self
+    }
+
+    /// Set up io-uring with `IORING_SETUP_DEFER_TASKRUN`, the completions are run only when the
+    /// driver waits for them. Available since Linux 6.1.
+    ///
+    /// The flag requires [`DriverBuilder::single_issuer`], which is set with it. The driver
+    /// can't be sent to other threads, so the kernel is always entered by the thread created
+    /// it. The completions are not received by
+    /// [`CompleteIo::peek_completed`](crate::driver::CompleteIo::peek_completed) and
+    /// [`CompleteIo::flush`](crate::driver::CompleteIo::flush) until the next submission.
+    ///
+    /// Other drivers ignore the value.
+    pub fn defer_taskrun(mut self, enable: bool) -> Self {
+        self.defer_taskrun = enable;
+        self
+    }
+
+    /// Set up io-uring with `IORING_SETUP_SINGLE_ISSUER`, only the thread created the driver
+    /// submits operations. Available since Linux 6.0.
+    ///
+    /// Other drivers ignore the value.
+    pub fn single_issuer(mut self, enable: bool) -> Self {
+        self.single_issuer = enable;
+        self
+    }
+
+    /// Set the maximum number of threads kqueue driver performs the blocking file operations on,
+    /// it defaults to 4. Zero makes the driver perform them on its thread.
+    ///
+    /// The operations like [`ReadAt`](crate::op::ReadAt) and [`WriteAt`](crate::op::WriteAt)
+    /// are not waiting for readiness of regular files. The threads perform them in parallel, so
+    /// the operations on the same file are ordered only if they are linked. The threads are
+    /// spawned on demand. NetBSD and OpenBSD don't support user events to receive the results,
+    /// so the pool is disabled.
+    ///
+    /// Other drivers ignore the value.
+    pub fn blocking_threads(mut self, threads: usize) -> Self {
+        self.blocking_threads = threads;
+        self
+    }
+
+    /// Reject the operations with the user defined data of an operation in flight, it's enabled
+    /// by default in debug builds.
+    ///
+    /// The driver keeps the set of the user defined data till the last entry of the operation is
+    /// consumed, so the multishot operations hold it till they finish.
+    pub fn check_user_data(mut self, enable: bool) -> Self {
+        self.check_user_data = enable;
+        self
+    }
+
+    /// Register the io-uring fd with `IORING_REGISTER_RING_FDS`, the kernel doesn't look up the
+    /// fd on every enter. It's enabled by default and skipped before Linux 5.18.
+    ///
+    /// The registration belongs to the thread created the driver, the driver enters the kernel
+    /// with the plain fd on other threads. The driver dropped on another thread can't remove the
+    /// registration, so the kernel keeps the ring till the creating thread exits.
+    ///
+    /// Other drivers ignore the value.
+    pub fn register_ring_fd(mut self, enable: bool) -> Self {
+        self.register_ring_fd = enable;
+        self
+    }
+
+    /// Busy poll the network devices of the sockets for `timeout` when the driver waits for
+    /// completions, the registration is issued after the ring setup. Available since Linux 6.9.
+    ///
+    /// The kernel without NAPI support doesn't fail the build, see
+    /// [`DriverCapabilities::napi_busy_poll`](crate::driver::DriverCapabilities::napi_busy_poll)
+    /// for the applied value and the driver methods to change it.
+    ///
+    /// Other drivers ignore the value.
+    pub fn napi_busy_poll(mut self, timeout: Duration) -> Self {
+        self.napi_busy_poll = Some(timeout);
+        self
+    }
+
+    /// Prefer busy polling to the interrupts of the network devices.
+    ///
+    /// The value is used together with [`DriverBuilder::napi_busy_poll`].
+    pub fn napi_prefer_busy_poll(mut self, enable: bool) -> Self {
+        self.napi_prefer_busy_poll = enable;
+        self
+    }
+
+    /// Set the callbacks of the driver events, see [`DriverHooks`].
+    #[cfg(feature = "hooks")]
+    pub fn hooks(mut self, hooks: Arc<dyn DriverHooks>) -> Self {
+        self.hooks = Hooks::new(hooks);
+        self
+    }
+
+    /// Share the kernel worker pool of the driver which created `token` instead of starting a
+    /// new one, the drivers are not oversubscribing the machine with the threads.
+    ///
+    /// The build fails with [`io::ErrorKind::InvalidInput`] if the driver is dropped.
+    #[cfg(target_os = "linux")]
+    pub fn attach_wq(mut self, token: &WorkQueueToken) -> Self {
+        self.attach_wq = Some(token.clone());
+        self
+    }
+
+    /// Cancel the operations with one request on shutdown, the operations are canceled one by
+    /// one if it's disabled as before Linux 5.19.
+    #[doc(hidden)]
+    #[cfg(target_os = "linux")]
+    pub fn cancel_any(mut self, enable: bool) -> Self {
+        self.cancel_any = enable;
+        self
+    }
+
+    /// Select the backend instead of probing io-uring, see
+    /// [`Driver::backend_kind`](crate::driver::Driver::backend_kind).
+    ///
+    /// By default the driver falls back to epoll when io-uring is unavailable. The build with
+    /// the selected backend fails if it's unavailable, and with [`io::ErrorKind::Unsupported`]
+    /// if it's not a Linux backend.
+    #[cfg(target_os = "linux")]
+    pub fn backend(mut self, kind: BackendKind) -> Self {
+        self.backend = Some(kind);
+        self
+    }
+
+    /// Create the driver.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::ErrorKind::InvalidInput`] if the submission queue is empty or io-uring
+    /// completion queue is smaller than the submission queue.
+    pub fn build<'arena>(&self) -> io::Result<Driver<'arena>> {
+        if self.sq_entries == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "submission queue entries should be nonzero",
+            ));
+        }
+        if matches!(self.cq_entries, Some(cq_entries) if cq_entries < self.sq_entries) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "completion queue entries should not be less than submission queue entries",
+            ));
+        }
+        Driver::from_builder(self)
+    }
+
+    pub(crate) fn event_capacity_or_default(&self) -> usize {
+        self.event_capacity.unwrap_or(self.sq_entries as usize)
+    }
+}
@@ -0,0 +1,95 @@
+use std::{io, time::Duration};
+
+/// Operations natively supported by the driver backend.
+///
+/// The operations which are not supported natively are either emulated in user space or
+/// unavailable. Obtained with [`Driver::capabilities`](crate::driver::Driver::capabilities) or
+/// [`probe`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DriverCapabilities {
+    /// One operation accepts connections continuously.
+    pub accept_multishot: bool,
+    /// Sends don't copy the data into the kernel.
+    pub send_zc: bool,
+    /// Files are opened asynchronously.
+    pub open_at: bool,
+    /// The kernel pins registered buffers for the fixed reads and writes.
+    pub fixed_buffers: bool,
+    /// The version of the running kernel as `(major, minor, patch)` if it is detected.
+    pub kernel_version: Option<(u32, u32, u32)>,
+    /// io-uring is set up with `IORING_SETUP_COOP_TASKRUN`.
+    ///
+    /// The setup flags are reported by the driver created with them, [`probe`] doesn't set
+    /// them.
+    pub coop_taskrun: bool,
+    /// io-uring is set up with `IORING_SETUP_DEFER_TASKRUN`.
+    pub defer_taskrun: bool,
+    /// io-uring is set up with `IORING_SETUP_SINGLE_ISSUER`.
+    pub single_issuer: bool,
+    /// The io-uring fd is registered, see
+    /// [`DriverBuilder::register_ring_fd`](crate::driver::DriverBuilder::register_ring_fd).
+    ///
+    /// [`probe`] reports whether the kernel supports the registration.
+    pub registered_ring: bool,
+    /// The busy poll timeout of the network devices if io-uring NAPI is registered.
+    pub napi_busy_poll: Option<Duration>,
+    /// io-uring NAPI prefers busy polling to the device interrupts.
+    pub napi_prefer_busy_poll: bool,
+}
+
+/// The backend of [`Driver`](crate::driver::Driver), returned by
+/// [`Driver::backend_kind`](crate::driver::Driver::backend_kind).
+///
+/// The backend is selected by the target OS, Linux falls back to epoll at runtime when io-uring
+/// is unavailable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BackendKind {
+    /// io-uring on Linux.
+    IoUring,
+    /// epoll on Linux when io-uring is unavailable.
+    Epoll,
+    /// kqueue on macOS and BSD.
+    Kqueue,
+    /// I/O completion ports on Windows.
+    Iocp,
+}
+
+/// Detect the capabilities of the driver backend without creating a driver.
+///
+/// io-uring driver creates a small ring to probe the supported opcodes, the capabilities of epoll
+/// are returned if io-uring is unavailable. Other drivers return the static capabilities of the
+/// backend.
+pub fn probe() -> io::Result<DriverCapabilities> {
+    crate::driver::probe_capabilities()
+}
+
+#[cfg(unix)]
+pub(crate) fn kernel_version() -> Option<(u32, u32, u32)> {
+    let mut uts = std::mem::MaybeUninit::<libc::utsname>::uninit();
+    // SAFETY: uname fills the structure on success
+    if unsafe { libc::uname(uts.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    // SAFETY: the structure is initialized
+    let uts = unsafe { uts.assume_init() };
+    // SAFETY: release is a null terminated string
+    let release = unsafe { std::ffi::CStr::from_ptr(uts.release.as_ptr()) };
+    parse_release(release.to_str().ok()?)
+}
+
+// parses versions like "6.1.0-13-amd64" or "23.1.0"
+#[cfg(unix)]
+fn parse_release(release: &str) -> Option<(u32, u32, u32)> {
+    let mut numbers = release.split('.').map(|part| {
+        let digits = part
+            .find(|c: char| !c.is_ascii_digit())
+            .map_or(part, |end| &part[..end]);
+        digits.parse::<u32>().ok()
+    });
+    let major = numbers.next()??;
+    let minor = numbers.next().flatten().unwrap_or(0);
+    let patch = numbers.next().flatten().unwrap_or(0);
+    Some((major, minor, patch))
+}
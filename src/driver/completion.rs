@@ -0,0 +1,57 @@
+use std::marker::PhantomData;
+
+use crate::driver::{DriverStats, Entry, Hooks, UserDataSet};
+
+/// Iterator of completed entries returned by
+/// [`CompleteIo::peek_completed`](crate::driver::CompleteIo::peek_completed).
+///
+/// Every entry is consumed from the driver buffer when it is returned.
+pub struct CompletionIter<'a, 'arena> {
+    // the entries in reverse order to pop them from the end
+    completed: &'a mut Vec<Entry>,
+    stats: &'a mut DriverStats,
+    user_data: &'a mut UserDataSet,
+    hooks: &'a Hooks,
+    _lifetime: PhantomData<&'arena ()>,
+}
+
+impl<'a> CompletionIter<'a, '_> {
+    pub(super) fn new(
+        completed: &'a mut Vec<Entry>,
+        stats: &'a mut DriverStats,
+        user_data: &'a mut UserDataSet,
+        hooks: &'a Hooks,
+    ) -> Self {
+        completed.reverse();
+        Self {
+            completed,
+            stats,
+            user_data,
+            hooks,
+            _lifetime: PhantomData,
+        }
+    }
+}
+
+impl Iterator for CompletionIter<'_, '_> {
+    type Item = Entry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.completed.pop()?;
+        self.stats.count_completed(&entry);
+        self.user_data.release(&entry);
+        self.hooks.completed(&entry);
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.completed.len(), Some(self.completed.len()))
+    }
+}
+
+impl Drop for CompletionIter<'_, '_> {
+    fn drop(&mut self) {
+        // restore the order of the entries which are not consumed
+        self.completed.reverse();
+    }
+}
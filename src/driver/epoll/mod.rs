@@ -0,0 +1,917 @@
+#[cfg(feature = "allocator_api")]
+use std::alloc::Allocator;
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{self, IoSliceMut},
+    os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd},
+    sync::Arc,
+    time::Duration,
+};
+
+#[cfg(feature = "time")]
+use crate::driver::time::TimerWheel;
+use crate::{
+    driver::{
+        capabilities::kernel_version,
+        completion,
+        link::{canceled_entry, Links},
+        op::CancelTarget,
+        op_timeout::OpTimeouts,
+        CancelError, CompleteIo, CompletionIter, CountCompleted, CountingExtend, DriverBuilder,
+        DriverCapabilities, DriverStats, DriverWaker, Entry, Fd, FdOrFixed, FixedBufRegistry,
+        FixedFd, FixedFdSlot, Hooks, OpCode, OpObject, Operation, PushError, UserDataSet,
+    },
+    syscall, vec_deque_alloc,
+};
+
+/// The readiness of a file which the operation waits for when the driver falls back to epoll,
+/// see [`OpCode::epoll_readiness`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Readiness {
+    /// The file is readable.
+    Readable(RawFd),
+    /// The file is writable.
+    Writable(RawFd),
+}
+
+/// Registered files of the epoll driver.
+///
+/// epoll has no registered files, the driver keeps the raw fds of
+/// [`CompleteIo::register_fd`] and [`CompleteIo::register_files`] to resolve the fixed fds of
+/// the operations.
+#[derive(Debug)]
+pub struct EpollFiles {
+    fds: Vec<RawFd>,
+    // registered file slots which are in use
+    allocated: Vec<bool>,
+    // the generation of the fixed fds accepted for every registered file slot
+    generation: Vec<u32>,
+}
+
+impl EpollFiles {
+    fn new(files_to_register: u32) -> Self {
+        Self {
+            fds: vec![-1; files_to_register as usize],
+            allocated: vec![false; files_to_register as usize],
+            generation: vec![0; files_to_register as usize],
+        }
+    }
+
+    /// The raw fd of the attached or registered file, `-1` if no file is registered with the
+    /// fixed fd.
+    pub fn raw_fd(&self, fd: FdOrFixed) -> RawFd {
+        match fd {
+            FdOrFixed::Fd(fd) => fd.as_raw_fd(),
+            FdOrFixed::Fixed(fixed_fd) => self
+                .fds
+                .get(fixed_fd.as_offset() as usize)
+                .copied()
+                .unwrap_or(-1),
+        }
+    }
+
+    fn set(&mut self, offset: u32, fds: &[RawFd]) -> io::Result<()> {
+        let offset = offset as usize;
+        let Some(files) = self.fds.get_mut(offset..offset + fds.len()) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "registered fixed file index is out of files_to_register range",
+            ));
+        };
+        files.copy_from_slice(fds);
+        Ok(())
+    }
+
+    fn remove(&mut self, offset: u32, len: u32) {
+        let range = offset as usize..(offset + len) as usize;
+        if let Some(files) = self.fds.get_mut(range.clone()) {
+            files.fill(-1);
+            self.allocated[range].fill(false);
+        }
+    }
+
+    // Find the first free range of `len` registered file slots
+    fn allocate(&mut self, len: usize) -> io::Result<u32> {
+        let mut offset = 0;
+        for (i, allocated) in self.allocated.iter().enumerate() {
+            if *allocated {
+                offset = i + 1;
+            } else if i + 1 - offset == len {
+                break;
+            }
+        }
+        if offset + len > self.allocated.len() {
+            return Err(io::Error::other(
+                "not enough free slots for registered files",
+            ));
+        }
+        self.allocated[offset..offset + len].fill(true);
+        Ok(u32::try_from(offset).expect("in range"))
+    }
+
+    // Fail the operation using the stale fixed fds
+    fn check(&self, op: &dyn OpCode) -> io::Result<()> {
+        for fixed_fd in op.fixed_fds().into_iter().flatten() {
+            match self.generation.get(fixed_fd.as_offset() as usize) {
+                Some(generation) if *generation != fixed_fd.generation => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "fixed fd is stale, the registered file is updated",
+                    ));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the registered files of `slot` from `offset` with `fds` like
+    /// [`FilesUpdate`](crate::op::FilesUpdate), the fixed fds got from the slot before are stale.
+    pub(in crate::driver) fn update(
+        &mut self,
+        slot: &FixedFdSlot,
+        offset: u32,
+        fds: &[RawFd],
+    ) -> io::Result<usize> {
+        let range = slot.offset as usize..(slot.offset + slot.len) as usize;
+        if !self
+            .allocated
+            .get(range.clone())
+            .is_some_and(|allocated| allocated.iter().all(|allocated| *allocated))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "updated files are not registered",
+            ));
+        }
+        self.set(offset, fds)?;
+        self.generation[range].fill(slot.generation);
+        Ok(fds.len())
+    }
+}
+
+// the driver runs the timers instead of operating
+#[cfg(feature = "time")]
+pub(in crate::driver) const TIMER_PENDING: usize = usize::MAX - 2;
+// the driver updates or removes the timer instead of operating
+#[cfg(feature = "time")]
+pub(in crate::driver) const TIMER_UPDATE_PENDING: usize = usize::MAX - 3;
+#[cfg(feature = "time")]
+pub(in crate::driver) const TIMER_REMOVE_PENDING: usize = usize::MAX - 4;
+
+#[cfg(feature = "time")]
+fn timer_found(found: bool) -> io::Result<usize> {
+    if found {
+        Ok(0)
+    } else {
+        Err(io::Error::from_raw_os_error(libc::ENOENT))
+    }
+}
+
+// the error of the operation which epoll driver doesn't emulate
+pub(in crate::driver) fn requires_io_uring(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{name} requires io-uring driver"),
+    )
+}
+
+// the key of the waker eventfd in the interest list, other keys are the file descriptors
+const WAKER_KEY: u64 = u64::MAX;
+
+// operations waiting for readiness of a file in the push order
+#[derive(Default)]
+struct Waiting<'arena> {
+    readers: VecDeque<OpObject<'arena>>,
+    writers: VecDeque<OpObject<'arena>>,
+    // the events of the one-shot registration, zero after it has fired
+    armed: u32,
+    // the file is in the interest list
+    registered: bool,
+}
+
+impl Waiting<'_> {
+    fn events(&self) -> u32 {
+        let mut events = 0;
+        if !self.readers.is_empty() {
+            events |= (libc::EPOLLIN | libc::EPOLLRDHUP) as u32;
+        }
+        if !self.writers.is_empty() {
+            events |= libc::EPOLLOUT as u32;
+        }
+        events
+    }
+
+    fn is_empty(&self) -> bool {
+        self.readers.is_empty() && self.writers.is_empty()
+    }
+}
+
+// Low-level driver based on epoll, it performs the operations in user space when their files
+// are ready
+pub struct Driver<'arena> {
+    epoll: OwnedFd,
+    // the capacity of the submission queue
+    entries: usize,
+    // submission queue
+    squeue: Vec<OpObject<'arena>>,
+    // operations waiting for readiness of their files
+    waiting: HashMap<RawFd, Waiting<'arena>>,
+    // files which waiting operations changed since the last wait
+    changed: Vec<RawFd>,
+    // epoll_wait ready events output
+    ready_events: Vec<libc::epoll_event>,
+    files: EpollFiles,
+    // linked operations waiting for their predecessors
+    links: Links<'arena>,
+    // timeouts of pushed operations
+    op_timeouts: OpTimeouts<()>,
+    // entries completed on push and the buffer used when there are linked operations or timeouts
+    completed: Vec<Entry>,
+    #[cfg(feature = "time")]
+    timers: TimerWheel,
+    // eventfd of the wakers, it's registered level-triggered
+    waker: Option<Arc<OwnedFd>>,
+    stats: DriverStats,
+    user_data: UserDataSet,
+    hooks: Hooks,
+}
+
+pub(in crate::driver) fn capabilities() -> DriverCapabilities {
+    DriverCapabilities {
+        kernel_version: kernel_version(),
+        ..DriverCapabilities::default()
+    }
+}
+
+fn epoll_ctl(epoll: RawFd, op: i32, fd: RawFd, events: u32, key: u64) -> io::Result<()> {
+    let mut event = libc::epoll_event { events, u64: key };
+    syscall!(epoll_ctl(epoll, op, fd, &mut event))?;
+    Ok(())
+}
+
+// SAFETY: the operations are accessed only by the thread owning the driver, as the kernel
+// accesses the operations pushed into io-uring driver regardless of the thread
+unsafe impl Send for Driver<'_> {}
+
+impl<'arena> Driver<'arena> {
+    pub(in crate::driver) fn from_builder(builder: &DriverBuilder) -> io::Result<Self> {
+        let entries = builder.sq_entries as usize;
+        let epoll = syscall!(epoll_create1(libc::EPOLL_CLOEXEC))?;
+        Ok(Self {
+            // SAFETY: the fd is just created
+            epoll: unsafe { OwnedFd::from_raw_fd(epoll) },
+            entries,
+            squeue: Vec::with_capacity(entries),
+            waiting: HashMap::new(),
+            changed: Vec::new(),
+            ready_events: Vec::with_capacity(builder.event_capacity_or_default().max(1)),
+            files: EpollFiles::new(builder.files_to_register),
+            links: Links::default(),
+            op_timeouts: OpTimeouts::default(),
+            completed: Vec::new(),
+            #[cfg(feature = "time")]
+            timers: TimerWheel::with_capacity(16),
+            waker: None,
+            stats: DriverStats::default(),
+            user_data: UserDataSet::new(builder.check_user_data),
+            hooks: builder.hooks.clone(),
+        })
+    }
+
+    pub(in crate::driver) fn stats(&self) -> DriverStats {
+        self.stats
+    }
+
+    pub(in crate::driver) fn reset_stats(&mut self) {
+        self.stats = DriverStats::default();
+    }
+
+    // the entries which could be output without entering the kernel
+    pub(in crate::driver) fn cq_ready(&self) -> usize {
+        self.completed.len()
+    }
+
+    // discard the pushed operations and remove their files from the interest list, the
+    // operations are performed in user space, so none of them is in flight
+    pub(in crate::driver) fn shutdown(&mut self) {
+        let epoll = self.epoll.as_raw_fd();
+        for (fd, waiting) in self.waiting.drain() {
+            if waiting.registered {
+                let _ = epoll_ctl(epoll, libc::EPOLL_CTL_DEL, fd, 0, 0);
+            }
+        }
+        self.changed.clear();
+        self.squeue.clear();
+        self.links = Links::default();
+        self.op_timeouts = OpTimeouts::default();
+        self.completed.clear();
+        self.user_data.clear();
+        #[cfg(feature = "time")]
+        {
+            self.timers = TimerWheel::with_capacity(16);
+        }
+    }
+
+    pub(in crate::driver) fn waker(&mut self) -> io::Result<DriverWaker> {
+        let fd = match &self.waker {
+            Some(fd) => fd.clone(),
+            None => {
+                let fd = syscall!(eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK))?;
+                // SAFETY: the fd is just created
+                let fd = Arc::new(unsafe { OwnedFd::from_raw_fd(fd) });
+                epoll_ctl(
+                    self.epoll.as_raw_fd(),
+                    libc::EPOLL_CTL_ADD,
+                    fd.as_raw_fd(),
+                    libc::EPOLLIN as u32,
+                    WAKER_KEY,
+                )?;
+                self.waker = Some(fd.clone());
+                fd
+            }
+        };
+        Ok(DriverWaker { fd })
+    }
+
+    // reset the counter of the nonblocking eventfd
+    fn reset_waker(&self) {
+        if let Some(fd) = &self.waker {
+            let mut data = 0u64;
+            let _ = syscall!(read(
+                fd.as_raw_fd(),
+                &mut data as *mut _ as *mut _,
+                std::mem::size_of::<u64>(),
+            ));
+        }
+    }
+
+    // operate pushed operations
+    fn operate_squeue(&mut self, entries: &mut impl Extend<Entry>) {
+        let mut squeue = std::mem::take(&mut self.squeue);
+        for op in squeue.drain(..) {
+            entries.extend(self.operate(op));
+        }
+        self.squeue = squeue;
+    }
+
+    // perform the operation, it waits for readiness if it would block
+    fn operate(&mut self, mut op: OpObject<'arena>) -> Option<Entry> {
+        let user_data = op.user_data();
+        let res = match self.files.check(op.opcode_ref()) {
+            Ok(()) => op.opcode().epoll_operate(&mut self.files),
+            Err(e) => Some(Err(e)),
+        };
+        match res {
+            // no result => the operation waits for readiness
+            None => {
+                self.wait(op);
+                None
+            }
+            #[cfg(feature = "time")]
+            Some(Ok(TIMER_PENDING)) => {
+                self.timers.insert(user_data, op.opcode_ref().timer_delay());
+                None
+            }
+            #[cfg(feature = "time")]
+            Some(Ok(TIMER_UPDATE_PENDING)) => {
+                let opcode = op.opcode_ref();
+                let updated = self
+                    .timers
+                    .update(opcode.timer_target(), opcode.timer_delay());
+                Some(Entry::new(user_data, timer_found(updated)))
+            }
+            #[cfg(feature = "time")]
+            Some(Ok(TIMER_REMOVE_PENDING)) => {
+                let removed = self.timers.cancel(op.opcode_ref().timer_target());
+                Some(Entry::new(user_data, timer_found(removed)))
+            }
+            Some(res) => Some(Entry::new(user_data, res)),
+        }
+    }
+
+    // queue the operation till its file is ready
+    fn wait(&mut self, op: OpObject<'arena>) {
+        let (fd, readable) = match op.opcode_ref().epoll_readiness(&self.files) {
+            Readiness::Readable(fd) => (fd, true),
+            Readiness::Writable(fd) => (fd, false),
+        };
+        let waiting = self.waiting.entry(fd).or_default();
+        if readable {
+            waiting.readers.push_back(op);
+        } else {
+            waiting.writers.push_back(op);
+        }
+        self.changed.push(fd);
+    }
+
+    // arm the one-shot registrations of the files with waiting operations, the files without
+    // them are removed from the interest list
+    fn update_interest(&mut self, entries: &mut impl Extend<Entry>) {
+        let epoll = self.epoll.as_raw_fd();
+        for fd in self.changed.drain(..) {
+            let Some(waiting) = self.waiting.get_mut(&fd) else {
+                continue;
+            };
+            let events = waiting.events();
+            if events == 0 {
+                if waiting.registered {
+                    // the closed file is removed by the kernel
+                    let _ = epoll_ctl(epoll, libc::EPOLL_CTL_DEL, fd, 0, 0);
+                }
+                self.waiting.remove(&fd);
+                continue;
+            }
+            if events == waiting.armed {
+                continue;
+            }
+            let (op, retry_op, retry_errno) = if waiting.registered {
+                (libc::EPOLL_CTL_MOD, libc::EPOLL_CTL_ADD, libc::ENOENT)
+            } else {
+                (libc::EPOLL_CTL_ADD, libc::EPOLL_CTL_MOD, libc::EEXIST)
+            };
+            let oneshot = events | libc::EPOLLONESHOT as u32;
+            let res = match epoll_ctl(epoll, op, fd, oneshot, fd as u64) {
+                Err(e) if e.raw_os_error() == Some(retry_errno) => {
+                    epoll_ctl(epoll, retry_op, fd, oneshot, fd as u64)
+                }
+                res => res,
+            };
+            match res {
+                Ok(()) => {
+                    waiting.armed = events;
+                    waiting.registered = true;
+                }
+                Err(e) => {
+                    // the file can't be polled or it's closed
+                    let waiting = self.waiting.remove(&fd).expect("waiting operations exist");
+                    let errno = e.raw_os_error().unwrap_or(libc::EINVAL);
+                    entries.extend(
+                        waiting
+                            .readers
+                            .into_iter()
+                            .chain(waiting.writers)
+                            .map(|op| {
+                                Entry::new(op.user_data(), Err(io::Error::from_raw_os_error(errno)))
+                            }),
+                    );
+                }
+            }
+        }
+    }
+
+    // wait for the ready files and perform their operations
+    fn check_readiness(
+        &mut self,
+        timeout: Option<Duration>,
+        entries: &mut impl Extend<Entry>,
+    ) -> io::Result<()> {
+        let timeout = match timeout {
+            None => -1,
+            // round up, so the timers are expired after the wait
+            Some(timeout) => timeout
+                .as_nanos()
+                .div_ceil(1_000_000)
+                .try_into()
+                .unwrap_or(i32::MAX),
+        };
+        self.ready_events.clear();
+        let ready = syscall!(epoll_wait(
+            self.epoll.as_raw_fd(),
+            self.ready_events.as_mut_ptr(),
+            self.ready_events.capacity() as _,
+            timeout,
+        ))?;
+        // SAFETY: epoll_wait initialized the events
+        unsafe { self.ready_events.set_len(ready as usize) };
+        for index in 0..self.ready_events.len() {
+            let event = self.ready_events[index];
+            let (key, events) = (event.u64, event.events);
+            if key == WAKER_KEY {
+                // the wait is interrupted by the waker
+                self.reset_waker();
+                continue;
+            }
+            let fd = key as RawFd;
+            let Some(waiting) = self.waiting.get_mut(&fd) else {
+                continue;
+            };
+            waiting.armed = 0;
+            self.changed.push(fd);
+            // the operations on the failed file complete with the error
+            let failed = events & (libc::EPOLLERR | libc::EPOLLHUP) as u32 != 0;
+            if failed || events & (libc::EPOLLIN | libc::EPOLLRDHUP) as u32 != 0 {
+                self.operate_ready(Readiness::Readable(fd), entries);
+            }
+            if failed || events & libc::EPOLLOUT as u32 != 0 {
+                self.operate_ready(Readiness::Writable(fd), entries);
+            }
+        }
+        Ok(())
+    }
+
+    // perform the operations waiting for the ready file till one of them would block
+    fn operate_ready(&mut self, readiness: Readiness, entries: &mut impl Extend<Entry>) {
+        let (fd, readable) = match readiness {
+            Readiness::Readable(fd) => (fd, true),
+            Readiness::Writable(fd) => (fd, false),
+        };
+        loop {
+            let Some(waiting) = self.waiting.get_mut(&fd) else {
+                return;
+            };
+            let queue = if readable {
+                &mut waiting.readers
+            } else {
+                &mut waiting.writers
+            };
+            let Some(mut op) = queue.pop_front() else {
+                return;
+            };
+            if let Some(res) = op.opcode().epoll_operate(&mut self.files) {
+                entries.extend(Some(Entry::new(op.user_data(), res)));
+                continue;
+            }
+            if op.opcode_ref().epoll_readiness(&self.files) == readiness {
+                // the file is not ready anymore, the order of the operations is kept
+                queue.push_front(op);
+                return;
+            }
+            // the operation waits for another file
+            self.wait(op);
+        }
+    }
+
+    #[inline]
+    fn push_op(&mut self, op: OpObject<'arena>) {
+        self.stats.submitted += 1;
+        self.hooks.pushed(op.user_data(), || op.opcode_ref().name());
+        if let Some(cancel) = op.opcode_ref().as_cancel() {
+            let res = self.cancel(&cancel.target);
+            self.completed.push(Entry::new(op.user_data(), res));
+            return;
+        }
+        if let Some(timeout) = op.timeout() {
+            self.op_timeouts.insert(op.user_data(), timeout, ());
+        }
+        if let Some(op) = self.links.push(op) {
+            self.squeue.push(op);
+        }
+    }
+
+    // cancel pushed operations matching the target
+    fn cancel(&mut self, target: &CancelTarget) -> io::Result<usize> {
+        let canceled = match target {
+            CancelTarget::UserData(user_data) => {
+                let user_data = *user_data;
+                let pushed = self.squeue.len();
+                self.squeue.retain(|op| op.user_data() != user_data);
+                let removed = pushed != self.squeue.len();
+                let removed = self.remove_waiting(user_data) || removed;
+                #[cfg(feature = "time")]
+                let removed = self.timers.remove(user_data) || removed;
+                if self.links.cancel(user_data) || removed {
+                    vec![user_data]
+                } else {
+                    Vec::new()
+                }
+            }
+            CancelTarget::Fd(fd) => {
+                let fd = self.files.raw_fd(*fd);
+                let canceled: Vec<_> = match self.waiting.get_mut(&fd) {
+                    Some(waiting) => waiting
+                        .readers
+                        .drain(..)
+                        .chain(waiting.writers.drain(..))
+                        .map(|op| op.user_data())
+                        .collect(),
+                    None => Vec::new(),
+                };
+                self.changed.push(fd);
+                for user_data in &canceled {
+                    self.links.cancel(*user_data);
+                }
+                canceled
+            }
+        };
+        if canceled.is_empty() {
+            return Err(io::Error::from_raw_os_error(libc::ENOENT));
+        }
+        for user_data in &canceled {
+            self.op_timeouts.remove(*user_data);
+            self.completed.push(canceled_entry(*user_data));
+        }
+        Ok(match target {
+            CancelTarget::UserData(_) => 0,
+            CancelTarget::Fd(_) => canceled.len(),
+        })
+    }
+
+    // remove the operation waiting for readiness, we assume cancellations are rare
+    fn remove_waiting(&mut self, user_data: usize) -> bool {
+        for (fd, waiting) in &mut self.waiting {
+            for queue in [&mut waiting.readers, &mut waiting.writers] {
+                if let Some(pos) = queue.iter().position(|op| op.user_data() == user_data) {
+                    let _ = queue.remove(pos);
+                    self.changed.push(*fd);
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    // remove the operation canceled on timeout, it completes with the timed out error
+    fn discard_op(&mut self, user_data: usize) {
+        if let Some(pos) = self
+            .squeue
+            .iter()
+            .position(|operation| operation.user_data() == user_data)
+        {
+            let _ = self.squeue.remove(pos);
+        }
+        self.remove_waiting(user_data);
+        self.links.cancel(user_data);
+        self.op_timeouts.remove(user_data);
+        #[cfg(feature = "time")]
+        self.timers.remove(user_data);
+    }
+
+    // timeouts of completed operations
+    fn remove_op_timeouts(&mut self, completed: &[Entry]) {
+        for entry in completed.iter().filter(|entry| !entry.has_more()) {
+            self.op_timeouts.remove(entry.user_data());
+        }
+    }
+
+    // cancel operations with expired deadlines
+    fn expire_op_timeouts(&mut self, completed: &mut Vec<Entry>) {
+        if self.op_timeouts.is_empty() {
+            return;
+        }
+        self.remove_op_timeouts(completed);
+        for (user_data, ()) in self.op_timeouts.expire() {
+            self.discard_op(user_data);
+            // as the operation canceled by io-uring linked timeout
+            completed.push(
+                Entry::new(user_data, Err(io::ErrorKind::TimedOut.into()))
+                    .with_raw_result(-libc::ECANCELED),
+            );
+        }
+    }
+
+    fn submit_entries(
+        &mut self,
+        timeout: Option<Duration>,
+        entries: &mut impl Extend<Entry>,
+    ) -> io::Result<()> {
+        if self.links.is_empty() && self.op_timeouts.is_empty() && self.completed.is_empty() {
+            return self.submit_impl(timeout, entries);
+        }
+        // don't wait when there are operations completed on push
+        let timeout = if self.completed.is_empty() {
+            self.op_timeouts.till_next_deadline_or_timeout(timeout)
+        } else {
+            Some(Duration::ZERO)
+        };
+        // expire timeouts and push successors of completed linked operations
+        let mut completed = std::mem::take(&mut self.completed);
+        let res = self.submit_impl(timeout, &mut completed);
+        self.expire_op_timeouts(&mut completed);
+        self.links
+            .complete_entries(&mut completed, &mut self.squeue);
+        entries.extend(completed.drain(..));
+        self.completed = completed;
+        res
+    }
+
+    fn submit_impl(
+        &mut self,
+        timeout: Option<Duration>,
+        entries: &mut impl Extend<Entry>,
+    ) -> io::Result<()> {
+        let mut entries = CountingExtend {
+            inner: entries,
+            count: 0,
+        };
+        self.operate_squeue(&mut entries);
+        self.update_interest(&mut entries);
+        // let the caller process the operations completed on submission
+        let timeout = if entries.count > 0 {
+            Some(Duration::ZERO)
+        } else {
+            timeout
+        };
+        #[cfg(feature = "time")]
+        let timeout = self.timers.till_next_timer_or_timeout(timeout);
+        let res = if timeout == Some(Duration::ZERO) && self.waiting.is_empty() {
+            Ok(())
+        } else {
+            self.check_readiness(timeout, &mut entries)
+        };
+        #[cfg(feature = "time")]
+        self.timers.expire_timers(&mut entries);
+        res
+    }
+}
+
+impl<'arena> CompleteIo<'arena> for Driver<'arena> {
+    #[inline]
+    fn attach(&mut self, fd: RawFd) -> io::Result<Fd> {
+        Ok(Fd::from_raw(fd))
+    }
+
+    fn detach(&mut self, fd: RawFd) -> io::Result<()> {
+        match self.waiting.get(&fd) {
+            Some(waiting) if !waiting.is_empty() => Err(io::Error::from_raw_os_error(libc::EBUSY)),
+            Some(_) => {
+                let waiting = self.waiting.remove(&fd).expect("checked above");
+                if waiting.registered {
+                    epoll_ctl(self.epoll.as_raw_fd(), libc::EPOLL_CTL_DEL, fd, 0, 0)?;
+                }
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn register_fd(&mut self, fd: RawFd, id: u32) -> io::Result<FixedFd> {
+        self.files.set(id, &[fd])?;
+        self.files.allocated[id as usize] = true;
+        Ok(FixedFd::new(id, self.files.generation[id as usize]))
+    }
+
+    #[inline]
+    fn unregister_fd(&mut self, fixed_fd: FixedFd) -> io::Result<()> {
+        self.files.remove(fixed_fd.as_offset(), 1);
+        Ok(())
+    }
+
+    fn register_files(&mut self, fds: &[RawFd]) -> io::Result<FixedFdSlot> {
+        let offset = self.files.allocate(fds.len())?;
+        self.files.set(offset, fds)?;
+        // the fixed fds of the new slot share the generation
+        let range = offset as usize..offset as usize + fds.len();
+        let generation = self.files.generation[range.clone()]
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or_default();
+        self.files.generation[range].fill(generation);
+        Ok(FixedFdSlot {
+            offset,
+            len: u32::try_from(fds.len()).expect("in range"),
+            generation,
+        })
+    }
+
+    fn register_files_update(
+        &mut self,
+        slot: &mut FixedFdSlot,
+        index: usize,
+        fds: &[RawFd],
+    ) -> io::Result<()> {
+        if index + fds.len() > slot.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "updated files are out of the slot",
+            ));
+        }
+        self.files.set(slot.offset + index as u32, fds)
+    }
+
+    #[inline]
+    fn unregister_files(&mut self, slot: FixedFdSlot) -> io::Result<()> {
+        self.files.remove(slot.offset, slot.len);
+        Ok(())
+    }
+
+    #[inline]
+    fn register_buffers<'a: 'arena>(
+        &mut self,
+        bufs: &'a mut [IoSliceMut<'a>],
+    ) -> io::Result<FixedBufRegistry<'a>> {
+        FixedBufRegistry::new(bufs)
+    }
+
+    #[inline]
+    fn unregister_buffers(&mut self, registry: &FixedBufRegistry<'_>) -> io::Result<()> {
+        registry.check_not_in_use()
+    }
+
+    #[inline]
+    fn try_cancel(&mut self, user_data: usize) -> Result<(), CancelError> {
+        match self.cancel(&CancelTarget::UserData(user_data)) {
+            Ok(_) => {
+                self.stats.canceled += 1;
+                self.hooks.canceled(user_data);
+                Ok(())
+            }
+            Err(e) if e.raw_os_error() == Some(libc::ENOENT) => Err(CancelError::NotFound),
+            Err(e) => Err(CancelError::Os(e)),
+        }
+    }
+
+    #[inline]
+    fn try_push<O: OpCode>(
+        &mut self,
+        op: Operation<'arena, O>,
+    ) -> Result<(), PushError<Operation<'arena, O>>> {
+        if self.capacity_left() == 0 {
+            return Err(PushError::QueueFull(op));
+        }
+        if !self.user_data.insert(op.user_data()) {
+            return Err(PushError::DuplicateUserData(op));
+        }
+        self.push_op(OpObject::from(op));
+        Ok(())
+    }
+
+    #[inline]
+    fn try_push_dyn(&mut self, op: OpObject<'arena>) -> Result<(), PushError<OpObject<'arena>>> {
+        if self.capacity_left() == 0 {
+            return Err(PushError::QueueFull(op));
+        }
+        if !self.user_data.insert(op.user_data()) {
+            return Err(PushError::DuplicateUserData(op));
+        }
+        self.push_op(op);
+        Ok(())
+    }
+
+    #[inline]
+    fn push_queue<#[cfg(feature = "allocator_api")] A: Allocator + Unpin + 'arena>(
+        &mut self,
+        ops_queue: &mut vec_deque_alloc!(OpObject<'arena>, A),
+    ) {
+        self.stats.max_queued = self.stats.max_queued.max(ops_queue.len());
+        let till = self.capacity_left().min(ops_queue.len());
+        for _ in 0..till {
+            // the operation with the duplicate data stays in the queue
+            if !self.user_data.insert(ops_queue[0].user_data()) {
+                break;
+            }
+            let op = ops_queue.pop_front().expect("in range");
+            self.push_op(op);
+        }
+    }
+
+    #[inline]
+    fn capacity_left(&self) -> usize {
+        self.entries.saturating_sub(self.squeue.len())
+    }
+
+    unsafe fn submit(
+        &mut self,
+        timeout: Option<Duration>,
+        entries: &mut impl Extend<Entry>,
+    ) -> io::Result<()> {
+        self.hooks.submitted();
+        // the completed operations release the data when the entries are extended
+        let mut user_data = std::mem::take(&mut self.user_data);
+        let hooks = self.hooks.clone();
+        let mut entries = CountCompleted::new(entries, &mut user_data, &hooks);
+        let res = self.submit_entries(timeout, &mut entries);
+        self.stats.completed += entries.completed();
+        self.user_data = user_data;
+        res
+    }
+
+    fn peek_completed(&mut self) -> CompletionIter<'_, 'arena> {
+        // the entries are returned after the successors of linked operations are pushed
+        let mut completed = std::mem::take(&mut self.completed);
+        self.remove_op_timeouts(&completed);
+        self.links
+            .complete_entries(&mut completed, &mut self.squeue);
+        self.completed = completed;
+        CompletionIter::epoll(completion::CompletionIter::new(
+            &mut self.completed,
+            &mut self.stats,
+            &mut self.user_data,
+            &self.hooks,
+        ))
+    }
+
+    unsafe fn flush(&mut self) -> io::Result<usize> {
+        self.hooks.submitted();
+        let submitted = self.squeue.len();
+        // the entries of completed operations are output by the next submit
+        let mut completed = std::mem::take(&mut self.completed);
+        self.operate_squeue(&mut completed);
+        self.update_interest(&mut completed);
+        self.completed = completed;
+        Ok(submitted)
+    }
+}
+
+impl Drop for Driver<'_> {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+impl AsRawFd for Driver<'_> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.epoll.as_raw_fd()
+    }
+}
@@ -0,0 +1,169 @@
+use std::{
+    cell::Cell,
+    io::{self, IoSliceMut},
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+    slice,
+};
+
+use crate::buf::{IoBuf, IoBufMut};
+
+/// Buffers registered in a driver with
+/// [`CompleteIo::register_buffers`](crate::driver::CompleteIo::register_buffers).
+///
+/// A buffer is checked out by its index for a [`ReadAtFixed`](crate::op::ReadAtFixed) or
+/// [`WriteAtFixed`](crate::op::WriteAtFixed) operation. The registry hands out every buffer once
+/// at a time, so the index stays valid and the memory is not shared while an operation is in
+/// flight.
+pub struct FixedBufRegistry<'arena> {
+    buffers: Vec<(NonNull<u8>, usize)>,
+    checked_out: Box<[Cell<bool>]>,
+    _lifetime: PhantomData<&'arena mut [u8]>,
+}
+
+impl<'arena> FixedBufRegistry<'arena> {
+    pub(crate) fn new(bufs: &'arena mut [IoSliceMut<'arena>]) -> io::Result<Self> {
+        if bufs.len() > usize::from(u16::MAX) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "too many buffers to register",
+            ));
+        }
+        let buffers = bufs
+            .iter_mut()
+            .map(|buf| (NonNull::new(buf.as_mut_ptr()).expect("not null"), buf.len()))
+            .collect::<Vec<_>>();
+        let checked_out = buffers.iter().map(|_| Cell::new(false)).collect();
+        Ok(Self {
+            buffers,
+            checked_out,
+            _lifetime: PhantomData,
+        })
+    }
+
+    /// The number of registered buffers.
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Returns `true` if no buffers are registered.
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+
+    /// Check out the buffer with `index`.
+    ///
+    /// The buffer is returned to the registry when [`FixedBuf`] is dropped. Returns `None` if the
+    /// index is out of range or the buffer is already checked out.
+    pub fn check_out(&self, index: u16) -> Option<FixedBuf<'_>> {
+        let checked_out = self.checked_out.get(usize::from(index))?;
+        if checked_out.replace(true) {
+            return None;
+        }
+        Some(FixedBuf {
+            registry: self,
+            index,
+            len: 0,
+        })
+    }
+
+    // Buffers could be unregistered only if no operation uses them
+    pub(crate) fn check_not_in_use(&self) -> io::Result<()> {
+        if self.checked_out.iter().any(Cell::get) {
+            Err(io::Error::other("registered buffer is checked out"))
+        } else {
+            Ok(())
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    pub(crate) fn as_raw_bufs(&self) -> &[(NonNull<u8>, usize)] {
+        &self.buffers
+    }
+}
+
+/// A registered buffer checked out from [`FixedBufRegistry`].
+///
+/// Like [`Vec`], it tracks the number of initialized bytes. Reads fill the rest of the buffer.
+pub struct FixedBuf<'a> {
+    registry: &'a FixedBufRegistry<'a>,
+    index: u16,
+    len: usize,
+}
+
+impl FixedBuf<'_> {
+    /// The index of the buffer in the registry.
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+
+    /// Forget the initialized bytes.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Append bytes from `data`, returning the number of bytes copied.
+    ///
+    /// Fewer bytes are copied if the buffer is full.
+    pub fn extend_from_slice(&mut self, data: &[u8]) -> usize {
+        let n = data.len().min(self.buf_capacity() - self.len);
+        // SAFETY: the checked out buffer is not shared and has enough space
+        unsafe {
+            std::ptr::copy_nonoverlapping(data.as_ptr(), self.as_buf_mut_ptr().add(self.len), n)
+        };
+        self.len += n;
+        n
+    }
+
+    fn raw_buf(&self) -> (NonNull<u8>, usize) {
+        self.registry.buffers[usize::from(self.index)]
+    }
+}
+
+impl Deref for FixedBuf<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: the initialized part of the checked out buffer
+        unsafe { slice::from_raw_parts(self.raw_buf().0.as_ptr(), self.len) }
+    }
+}
+
+impl DerefMut for FixedBuf<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY: the initialized part of the checked out buffer
+        unsafe { slice::from_raw_parts_mut(self.raw_buf().0.as_ptr(), self.len) }
+    }
+}
+
+unsafe impl<'a> IoBuf<'a> for FixedBuf<'a> {
+    fn as_buf_ptr(&self) -> *const u8 {
+        self.raw_buf().0.as_ptr()
+    }
+
+    fn buf_len(&self) -> usize {
+        self.len
+    }
+
+    fn buf_capacity(&self) -> usize {
+        self.raw_buf().1
+    }
+}
+
+unsafe impl<'a> IoBufMut<'a> for FixedBuf<'a> {
+    fn as_buf_mut_ptr(&mut self) -> *mut u8 {
+        self.raw_buf().0.as_ptr()
+    }
+
+    fn set_buf_init(&mut self, len: usize) {
+        debug_assert!(self.len + len <= self.buf_capacity());
+        self.len += len;
+    }
+}
+
+impl Drop for FixedBuf<'_> {
+    fn drop(&mut self) {
+        self.registry.checked_out[usize::from(self.index)].set(false);
+    }
+}
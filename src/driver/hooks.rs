@@ -0,0 +1,139 @@
+#[cfg(feature = "hooks")]
+use std::sync::Arc;
+use std::{any::type_name, fmt};
+
+use crate::driver::Entry;
+
+/// Callbacks of the driver events, set with
+/// [`DriverBuilder::hooks`](crate::driver::DriverBuilder::hooks).
+///
+/// The callbacks are called on the thread of the driver, they should be cheap as they delay the
+/// driver. The operations are identified by their user defined data.
+///
+/// ```
+/// use std::sync::{
+///     atomic::{AtomicUsize, Ordering},
+///     Arc,
+/// };
+///
+/// use arrayvec::ArrayVec;
+/// use completeio::{
+///     driver::{CompleteIo, Driver, DriverHooks, Entry, Operation},
+///     op::Nop,
+/// };
+///
+/// #[derive(Default)]
+/// struct Completions(AtomicUsize);
+///
+/// impl DriverHooks for Completions {
+///     fn on_complete(&self, _entry: &Entry) {
+///         self.0.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let completions = Arc::new(Completions::default());
+/// let mut op = Nop::new();
+/// let mut driver = Driver::builder()
+///     .hooks(completions.clone())
+///     .build()
+///     .unwrap();
+/// driver
+///     .try_push(Operation::new(&mut op, 0))
+///     .unwrap_or_else(|_| panic!("queue is full"));
+/// let mut entries = ArrayVec::<Entry, 1>::new();
+/// unsafe { driver.submit(None, &mut entries) }.unwrap();
+/// assert_eq!(completions.0.load(Ordering::Relaxed), 1);
+/// ```
+#[cfg(feature = "hooks")]
+pub trait DriverHooks: Send + Sync {
+    /// The operation is pushed to the driver, `name` is returned by `OpCode::name`.
+    fn on_push(&self, user_data: usize, name: &'static str) {
+        let _ = (user_data, name);
+    }
+
+    /// The driver submits the pushed operations and waits for completions.
+    fn on_submit(&self) {}
+
+    /// The entry of the operation is reaped by the driver.
+    ///
+    /// The entry is not reported for dropped entries of
+    /// [`CompletionIter`](crate::driver::CompletionIter) which are not consumed.
+    fn on_complete(&self, entry: &Entry) {
+        let _ = entry;
+    }
+
+    /// The cancellation of the operation is accepted by
+    /// [`CompleteIo::try_cancel`](crate::driver::CompleteIo::try_cancel).
+    fn on_cancel(&self, user_data: usize) {
+        let _ = user_data;
+    }
+}
+
+// the hooks of the driver, the calls are compiled out without `hooks` feature
+#[derive(Clone, Default)]
+pub(crate) struct Hooks {
+    #[cfg(feature = "hooks")]
+    hooks: Option<Arc<dyn DriverHooks>>,
+}
+
+impl Hooks {
+    #[cfg(feature = "hooks")]
+    pub(crate) fn new(hooks: Arc<dyn DriverHooks>) -> Self {
+        Self { hooks: Some(hooks) }
+    }
+
+    #[inline]
+    pub(crate) fn pushed(&self, user_data: usize, name: impl FnOnce() -> &'static str) {
+        #[cfg(feature = "hooks")]
+        if let Some(hooks) = &self.hooks {
+            hooks.on_push(user_data, name());
+        }
+        #[cfg(not(feature = "hooks"))]
+        let _ = (user_data, name);
+    }
+
+    #[inline]
+    pub(crate) fn submitted(&self) {
+        #[cfg(feature = "hooks")]
+        if let Some(hooks) = &self.hooks {
+            hooks.on_submit();
+        }
+    }
+
+    #[inline]
+    pub(crate) fn completed(&self, entry: &Entry) {
+        #[cfg(feature = "hooks")]
+        if let Some(hooks) = &self.hooks {
+            hooks.on_complete(entry);
+        }
+        #[cfg(not(feature = "hooks"))]
+        let _ = entry;
+    }
+
+    #[inline]
+    pub(crate) fn canceled(&self, user_data: usize) {
+        #[cfg(feature = "hooks")]
+        if let Some(hooks) = &self.hooks {
+            hooks.on_cancel(user_data);
+        }
+        #[cfg(not(feature = "hooks"))]
+        let _ = user_data;
+    }
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[cfg(feature = "hooks")]
+        let set = self.hooks.is_some();
+        #[cfg(not(feature = "hooks"))]
+        let set = false;
+        f.debug_struct("Hooks").field("set", &set).finish()
+    }
+}
+
+// the type name without the module path and the generic parameters, like `ReadAt`
+pub(crate) fn op_name<T: ?Sized>() -> &'static str {
+    let name = type_name::<T>();
+    let name = name.find('<').map_or(name, |end| &name[..end]);
+    name.rsplit("::").next().unwrap_or(name)
+}
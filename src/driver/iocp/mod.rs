@@ -1,20 +1,25 @@
 #[cfg(feature = "allocator_api")]
 use std::alloc::Allocator;
 use std::{
-    fmt, io,
+    collections::{HashMap, HashSet},
+    fmt,
+    io::{self, IoSliceMut},
     marker::PhantomData,
     os::windows::prelude::{
         AsRawHandle, AsRawSocket, FromRawHandle, FromRawSocket, IntoRawHandle, IntoRawSocket,
         OwnedHandle, RawHandle,
     },
+    ptr::{null, null_mut},
+    sync::Arc,
     task::Poll,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use windows_sys::Win32::{
     Foundation::{
-        RtlNtStatusToDosError, ERROR_HANDLE_EOF, ERROR_IO_INCOMPLETE, ERROR_NO_DATA,
-        FACILITY_NTWIN32, INVALID_HANDLE_VALUE, NTSTATUS, STATUS_PENDING, STATUS_SUCCESS,
+        RtlNtStatusToDosError, ERROR_BUSY, ERROR_HANDLE_EOF, ERROR_IO_INCOMPLETE, ERROR_NOT_FOUND,
+        ERROR_NO_DATA, ERROR_OPERATION_ABORTED, FACILITY_NTWIN32, HANDLE, INVALID_HANDLE_VALUE,
+        NTSTATUS, STATUS_PENDING, STATUS_SUCCESS,
     },
     Storage::FileSystem::SetFileCompletionNotificationModes,
     System::{
@@ -22,8 +27,8 @@ use windows_sys::Win32::{
         Threading::INFINITE,
         WindowsProgramming::{FILE_SKIP_COMPLETION_PORT_ON_SUCCESS, FILE_SKIP_SET_EVENT_ON_HANDLE},
         IO::{
-            CreateIoCompletionPort, GetQueuedCompletionStatusEx, PostQueuedCompletionStatus,
-            OVERLAPPED, OVERLAPPED_ENTRY,
+            CancelIoEx, CreateIoCompletionPort, GetQueuedCompletionStatusEx,
+            PostQueuedCompletionStatus, OVERLAPPED, OVERLAPPED_ENTRY,
         },
     },
 };
@@ -31,7 +36,15 @@ use windows_sys::Win32::{
 #[cfg(feature = "time")]
 use crate::driver::time::TimerWheel;
 use crate::{
-    driver::{CompleteIo, Entry, OpObject, Operation},
+    driver::{
+        link::{canceled_entry, Links},
+        op::CancelTarget,
+        op_name,
+        op_timeout::OpTimeouts,
+        BackendKind, CancelError, CompleteIo, CompletionIter, CountCompleted, DriverBuilder,
+        DriverCapabilities, DriverStats, Entry, FixedBufRegistry, Hooks, OpObject, Operation,
+        PushError, UserDataSet,
+    },
     syscall, vec_deque_alloc,
 };
 
@@ -114,7 +127,7 @@ pub struct Fd {
 
 impl Fd {
     #[inline]
-    const fn from_raw(raw_fd: RawFd) -> Self {
+    pub(crate) const fn from_raw(raw_fd: RawFd) -> Self {
         Self {
             raw_fd,
             _not_send_not_sync: PhantomData,
@@ -138,6 +151,30 @@ pub type FixedFd = Fd;
 /// FdOrFixed is aliased to attached fd
 pub type FdOrFixed = Fd;
 
+/// Registered files of
+/// [`CompleteIo::register_files`](crate::driver::CompleteIo::register_files).
+#[derive(Debug, PartialEq, Eq)]
+pub struct FixedFdSlot {
+    fds: Vec<FixedFd>,
+}
+
+impl FixedFdSlot {
+    /// The number of registered files in the slot.
+    pub fn len(&self) -> usize {
+        self.fds.len()
+    }
+
+    /// Returns `true` if the slot has no registered files.
+    pub fn is_empty(&self) -> bool {
+        self.fds.is_empty()
+    }
+
+    /// Get fixed fd of the registered file with `index` in the slot.
+    pub fn get(&self, index: usize) -> Option<FixedFd> {
+        self.fds.get(index).copied()
+    }
+}
+
 /// Invalid file descriptor value could be used as an initial value of uninitialized file descriptor
 pub const INVALID_FD: Fd = Fd::from_raw(unsafe { std::mem::transmute(INVALID_HANDLE_VALUE) });
 /// Invalid fixed file descriptor value could be used as an initial value of uninitialized fixed
@@ -156,15 +193,73 @@ pub trait OpCode {
     /// Return mut reference on OVERLAPPED structure
     fn overlapped(&mut self) -> &mut OVERLAPPED;
 
-    /// Only timers implement this method
+    /// Return the handle of the pending operation to cancel it.
+    ///
+    /// Synchronous operations don't implement this method.
+    fn handle(&self) -> Option<RawFd> {
+        None
+    }
+
+    /// The name of the operation reported to [`DriverHooks`](crate::driver::DriverHooks), it
+    /// defaults to the type name without the module path.
+    fn name(&self) -> &'static str {
+        op_name::<Self>()
+    }
+
+    /// Only [`Cancel`](crate::op::Cancel) implements this method, the driver completes it
+    /// instead of operating.
+    fn as_cancel(&self) -> Option<&crate::op::Cancel> {
+        None
+    }
+
+    /// Only timers and timer updates implement this method
     #[cfg(feature = "time")]
     fn timer_delay(&self) -> Duration {
         unimplemented!("operation is not a timer")
     }
+
+    /// Only timer updates and removals implement this method, returns user_data of the timer
+    #[cfg(feature = "time")]
+    fn timer_target(&self) -> usize {
+        unimplemented!("operation doesn't target a timer")
+    }
 }
 
 const DEFAULT_CAPACITY: usize = 1024;
 
+/// A handle of [`Driver`] to post completions into it with [`MsgRing`](crate::op::MsgRing).
+///
+/// The handle owns a duplicate of the completion port handle and could be sent to other threads.
+#[derive(Debug)]
+pub struct DriverHandle {
+    port: OwnedHandle,
+}
+
+impl DriverHandle {
+    pub(in crate::driver) fn as_raw_fd(&self) -> RawFd {
+        self.port.as_raw_handle()
+    }
+}
+
+/// A handle to wake up [`Driver`] waiting for completions from other threads.
+///
+/// The handle posts a completion packet, the interrupted wait returns without entries.
+#[derive(Debug, Clone)]
+pub struct DriverWaker {
+    port: Arc<OwnedHandle>,
+}
+
+impl DriverWaker {
+    /// Wake up the driver.
+    pub fn wake(&self) -> io::Result<()> {
+        syscall!(
+            BOOL,
+            PostQueuedCompletionStatus(self.port.as_raw_handle() as _, 0, WAKER_KEY, null_mut())
+        )?;
+        Ok(())
+    }
+}
+
 /// Low-level driver of IOCP.
 pub struct Driver<'arena> {
     port: OwnedHandle,
@@ -172,31 +267,160 @@ pub struct Driver<'arena> {
     // to protect undrained part of squeue from new pushes from processing of completed entries
     squeue_drained_till: usize,
     iocp_entries: Vec<OVERLAPPED_ENTRY>,
+    // linked operations waiting for their predecessors
+    links: Links<'arena>,
+    // handle and overlapped of the pending operations to cancel them
+    pending: HashMap<usize, (RawFd, *mut OVERLAPPED)>,
+    // timeouts of pushed operations
+    op_timeouts: OpTimeouts<()>,
+    // entries completed on push and the buffer used when there are linked operations or timeouts
+    completed: Vec<Entry>,
+    // operations canceled with CancelIoEx on timeout
+    timed_out: HashSet<usize>,
     #[cfg(feature = "time")]
     timers: TimerWheel,
+    stats: DriverStats,
+    user_data: UserDataSet,
+    hooks: Hooks,
     _lifetime: PhantomData<&'arena ()>,
 }
 
+// the kernel version is not detected
+fn capabilities() -> DriverCapabilities {
+    DriverCapabilities::default()
+}
+
+pub(crate) fn probe_capabilities() -> io::Result<DriverCapabilities> {
+    Ok(capabilities())
+}
+
 impl<'arena> Driver<'arena> {
     /// Create a new IOCP.
     pub fn new() -> io::Result<Self> {
         Self::with(DEFAULT_CAPACITY as _, 0)
     }
 
+    /// Create a handle to post completions into the driver from other threads.
+    pub fn handle(&self) -> io::Result<DriverHandle> {
+        Ok(DriverHandle {
+            port: self.port.try_clone()?,
+        })
+    }
+
+    /// Create a handle to wake up the driver from other threads.
+    pub fn waker(&mut self) -> io::Result<DriverWaker> {
+        Ok(DriverWaker {
+            port: Arc::new(self.port.try_clone()?),
+        })
+    }
+
     /// Create a new IOCP driver with specified entries.
     ///
     /// File registration is implemented as attachment.
-    pub fn with(entries: u32, _files_to_register: u32) -> io::Result<Self> {
-        let port = syscall!(BOOL, CreateIoCompletionPort(INVALID_HANDLE_VALUE, 0, 0, 0))?;
+    pub fn with(entries: u32, files_to_register: u32) -> io::Result<Self> {
+        Self::builder()
+            .sq_entries(entries)
+            .files_to_register(files_to_register)
+            .build()
+    }
+
+    /// Operations natively supported by the backend.
+    ///
+    /// IOCP driver emulates the operations in user space, so none of them is native.
+    pub fn capabilities(&self) -> DriverCapabilities {
+        capabilities()
+    }
+
+    /// The backend of the driver.
+    pub fn backend_kind(&self) -> BackendKind {
+        BackendKind::Iocp
+    }
+
+    /// Counters of the driver operations.
+    pub fn stats(&self) -> DriverStats {
+        self.stats
+    }
+
+    /// Reset the counters of the driver operations.
+    pub fn reset_stats(&mut self) {
+        self.stats = DriverStats::default();
+    }
+
+    /// Cancel the operations in flight and wait until the kernel completes them.
+    ///
+    /// The pending I/O is canceled with `CancelIoEx` for every handle. The entries of the
+    /// canceled operations are discarded. The driver waits without `timeout` till all operations
+    /// complete, [`io::ErrorKind::TimedOut`] is returned if the timeout passes first. The driver
+    /// is shut down on drop, so the kernel doesn't access the operation buffers after the driver
+    /// is gone.
+    pub fn shutdown(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        self.squeue.clear();
+        self.links = Links::default();
+        self.op_timeouts = OpTimeouts::default();
+        self.completed.clear();
+        self.user_data.clear();
+        self.timed_out.clear();
+        #[cfg(feature = "time")]
+        {
+            self.timers = TimerWheel::with_capacity(16);
+        }
+        let handles = self
+            .pending
+            .values()
+            .map(|&(handle, _)| handle)
+            .collect::<HashSet<_>>();
+        for handle in handles {
+            // the handle could be closed already
+            unsafe { CancelIoEx(handle as _, null()) };
+        }
+        while !self.pending.is_empty() {
+            let timeout = match deadline {
+                Some(deadline) => {
+                    let left = deadline.saturating_duration_since(Instant::now());
+                    if left.is_zero() {
+                        return Err(io::ErrorKind::TimedOut.into());
+                    }
+                    Some(left)
+                }
+                None => None,
+            };
+            self.poll_impl(timeout)?;
+            for iocp_entry in self.iocp_entries.drain(..).filter(|e| !is_wakeup(e)) {
+                self.pending
+                    .remove(&Self::create_entry(iocp_entry).user_data());
+            }
+        }
+        Ok(())
+    }
+
+    /// Create a builder of IOCP driver.
+    pub fn builder() -> DriverBuilder {
+        DriverBuilder::new()
+    }
+
+    pub(crate) fn from_builder(builder: &DriverBuilder) -> io::Result<Self> {
+        let port = syscall!(
+            BOOL,
+            CreateIoCompletionPort(INVALID_HANDLE_VALUE, 0, 0, builder.concurrency)
+        )?;
         let port = unsafe { OwnedHandle::from_raw_handle(port as _) };
-        let entries = entries as usize;
+        let entries = builder.sq_entries as usize;
         Ok(Self {
             port,
             squeue: Vec::with_capacity(entries),
             squeue_drained_till: entries,
-            iocp_entries: Vec::with_capacity(entries),
+            iocp_entries: Vec::with_capacity(builder.event_capacity_or_default()),
+            links: Links::default(),
+            pending: HashMap::new(),
+            op_timeouts: OpTimeouts::default(),
+            completed: Vec::new(),
+            timed_out: HashSet::new(),
             #[cfg(feature = "time")]
             timers: TimerWheel::with_capacity(16),
+            stats: DriverStats::default(),
+            user_data: UserDataSet::new(builder.check_user_data),
+            hooks: builder.hooks.clone(),
             _lifetime: PhantomData,
         })
     }
@@ -213,7 +437,7 @@ impl<'arena> Driver<'arena> {
             GetQueuedCompletionStatusEx(
                 self.port.as_raw_handle() as _,
                 self.iocp_entries.as_mut_ptr(),
-                self.iocp_entries.len() as _,
+                self.iocp_entries.capacity() as _,
                 &mut recv_count,
                 timeout,
                 0,
@@ -232,6 +456,16 @@ impl<'arena> Driver<'arena> {
     fn create_entry(iocp_entry: OVERLAPPED_ENTRY) -> Entry {
         let transferred = iocp_entry.dwNumberOfBytesTransferred;
         let overlapped_ptr = iocp_entry.lpOverlapped;
+        if overlapped_ptr.is_null() {
+            // the completion posted with MsgRing, a negative result is the error code
+            let result = transferred as i32;
+            let res = if result < 0 {
+                Err(io::Error::from_raw_os_error(-result))
+            } else {
+                Ok(result as _)
+            };
+            return Entry::new(iocp_entry.lpCompletionKey, res);
+        }
         let overlapped = unsafe { &*overlapped_ptr.cast::<Overlapped>() };
         let res = if matches!(
             overlapped.base.Internal as NTSTATUS,
@@ -247,6 +481,257 @@ impl<'arena> Driver<'arena> {
         };
         Entry::new(overlapped.user_data, res)
     }
+
+    #[inline]
+    fn push_op(&mut self, op: OpObject<'arena>) {
+        self.stats.submitted += 1;
+        self.hooks.pushed(op.user_data(), || op.opcode_ref().name());
+        if let Some(cancel) = op.opcode_ref().as_cancel() {
+            let res = self.cancel(&cancel.target);
+            self.completed.push(Entry::new(op.user_data(), res));
+            return;
+        }
+        if let Some(timeout) = op.timeout() {
+            self.op_timeouts.insert(op.user_data(), timeout, ());
+        }
+        if let Some(op) = self.links.push(op) {
+            self.squeue.push(op);
+        }
+    }
+
+    // cancel pushed operations matching the target
+    fn cancel(&mut self, target: &CancelTarget) -> io::Result<usize> {
+        let (queued, pending) = match target {
+            CancelTarget::UserData(user_data) => {
+                let user_data = *user_data;
+                let len = self.squeue.len();
+                self.squeue.retain(|op| op.user_data() != user_data);
+                let removed = len != self.squeue.len();
+                #[cfg(feature = "time")]
+                let removed = self.timers.remove(user_data) || removed;
+                let queued = if self.links.cancel(user_data) || removed {
+                    vec![user_data]
+                } else {
+                    Vec::new()
+                };
+                let pending = self
+                    .pending
+                    .get(&user_data)
+                    .map(|&(handle, overlapped)| vec![(handle, overlapped)])
+                    .unwrap_or_default();
+                (queued, pending)
+            }
+            CancelTarget::Fd(fd) => {
+                let fd = fd.as_raw_fd();
+                let mut queued = Vec::new();
+                self.squeue.retain(|op| {
+                    let matches = op.opcode_ref().handle() == Some(fd);
+                    if matches {
+                        queued.push(op.user_data());
+                    }
+                    !matches
+                });
+                for user_data in &queued {
+                    self.links.cancel(*user_data);
+                }
+                let pending = self
+                    .pending
+                    .values()
+                    .filter(|(handle, _)| *handle == fd)
+                    .copied()
+                    .collect();
+                (queued, pending)
+            }
+        };
+        for user_data in &queued {
+            self.op_timeouts.remove(*user_data);
+            self.completed.push(canceled_entry(*user_data));
+        }
+        // the canceled operations complete with ERROR_OPERATION_ABORTED
+        let canceled = pending
+            .into_iter()
+            .filter(|&(handle, overlapped)| unsafe { CancelIoEx(handle as _, overlapped) } != 0)
+            .count()
+            + queued.len();
+        match (target, canceled) {
+            (_, 0) => Err(io::Error::from_raw_os_error(ERROR_NOT_FOUND as _)),
+            (CancelTarget::UserData(_), _) => Ok(0),
+            (CancelTarget::Fd(_), canceled) => Ok(canceled),
+        }
+    }
+
+    // remove the operation canceled on timeout, it completes with the timed out error
+    fn discard_op(&mut self, user_data: usize) {
+        if let Some(pos) = self
+            .squeue
+            .iter()
+            .position(|operation| operation.user_data() == user_data)
+        {
+            // we assume cancellations are rare
+            let _ = self.squeue.remove(pos);
+        }
+        self.links.cancel(user_data);
+        self.op_timeouts.remove(user_data);
+        #[cfg(feature = "time")]
+        self.timers.remove(user_data);
+    }
+
+    // timeouts of completed operations, the operations canceled on timeout fail with TimedOut
+    fn remove_op_timeouts(&mut self, completed: &mut [Entry]) {
+        for entry in completed.iter_mut().filter(|entry| !entry.has_more()) {
+            let user_data = entry.user_data();
+            self.op_timeouts.remove(user_data);
+            let aborted = matches!(
+                &entry.result,
+                Err(e) if e.raw_os_error() == Some(ERROR_OPERATION_ABORTED as _)
+            );
+            if self.timed_out.remove(&user_data) && aborted {
+                entry.result = Err(io::ErrorKind::TimedOut.into());
+            }
+        }
+    }
+
+    // cancel operations with expired deadlines
+    fn expire_op_timeouts(&mut self, completed: &mut Vec<Entry>) {
+        if self.op_timeouts.is_empty() && self.timed_out.is_empty() {
+            return;
+        }
+        self.remove_op_timeouts(completed);
+        for (user_data, ()) in self.op_timeouts.expire() {
+            match self.pending.get(&user_data).copied() {
+                Some((handle, overlapped)) => {
+                    // the canceled operation completes with ERROR_OPERATION_ABORTED
+                    if unsafe { CancelIoEx(handle as _, overlapped) } != 0 {
+                        self.timed_out.insert(user_data);
+                    }
+                }
+                None => {
+                    // the operation is not started yet
+                    self.discard_op(user_data);
+                    completed.push(Entry::new(user_data, Err(io::ErrorKind::TimedOut.into())));
+                }
+            }
+        }
+    }
+
+    // operate pushed operations
+    unsafe fn operate_squeue(&mut self, entries: &mut impl Extend<Entry>) {
+        let oneshot_completed_iter =
+            self.squeue
+                .drain(..)
+                .enumerate()
+                .filter_map(|(idx, mut operation)| {
+                    let user_data = operation.user_data();
+                    // we require Unpin buffers - so no need to pin
+                    let op = operation.opcode();
+                    let result = op.operate(user_data);
+                    match result {
+                        #[cfg(feature = "time")]
+                        Poll::Ready(Ok(TIMER_PENDING)) => {
+                            self.timers.insert(user_data, op.timer_delay());
+                            None
+                        }
+                        #[cfg(feature = "time")]
+                        Poll::Ready(Ok(TIMER_UPDATE_PENDING)) => {
+                            let updated = self.timers.update(op.timer_target(), op.timer_delay());
+                            self.squeue_drained_till = idx + 1;
+                            Some(Entry::new(user_data, timer_found(updated)))
+                        }
+                        #[cfg(feature = "time")]
+                        Poll::Ready(Ok(TIMER_REMOVE_PENDING)) => {
+                            let removed = self.timers.cancel(op.timer_target());
+                            self.squeue_drained_till = idx + 1;
+                            Some(Entry::new(user_data, timer_found(removed)))
+                        }
+                        Poll::Ready(Ok(NOP_PENDING)) => {
+                            // the packet without overlapped completes with the completion key
+                            let res = syscall!(
+                                BOOL,
+                                PostQueuedCompletionStatus(
+                                    self.port.as_raw_handle() as _,
+                                    0,
+                                    user_data,
+                                    null_mut()
+                                )
+                            );
+                            res.err().map(|e| Entry::new(user_data, Err(e)))
+                        }
+                        Poll::Ready(result) => {
+                            self.squeue_drained_till = idx + 1;
+                            Some(Entry::new(user_data, result))
+                        }
+                        _ => {
+                            if let Some(handle) = op.handle() {
+                                let overlapped = op.overlapped() as *mut OVERLAPPED;
+                                self.pending.insert(user_data, (handle, overlapped));
+                            }
+                            None
+                        }
+                    }
+                });
+
+        entries.extend(oneshot_completed_iter);
+        self.squeue_drained_till = self.squeue.capacity();
+    }
+
+    unsafe fn submit_entries(
+        &mut self,
+        timeout: Option<Duration>,
+        entries: &mut impl Extend<Entry>,
+    ) -> io::Result<()> {
+        if self.links.is_empty() && self.op_timeouts.is_empty() && self.completed.is_empty() {
+            return self.submit_impl(timeout, entries);
+        }
+        // don't wait when there are operations completed on push
+        let timeout = if self.completed.is_empty() {
+            self.op_timeouts.till_next_deadline_or_timeout(timeout)
+        } else {
+            Some(Duration::ZERO)
+        };
+        // expire timeouts and push successors of completed linked operations
+        let mut completed = std::mem::take(&mut self.completed);
+        let res = self.submit_impl(timeout, &mut completed);
+        self.expire_op_timeouts(&mut completed);
+        self.links
+            .complete_entries(&mut completed, &mut self.squeue);
+        entries.extend(completed.drain(..));
+        self.completed = completed;
+        res
+    }
+
+    unsafe fn submit_impl(
+        &mut self,
+        timeout: Option<Duration>,
+        entries: &mut impl Extend<Entry>,
+    ) -> io::Result<()> {
+        self.operate_squeue(entries);
+
+        #[cfg(feature = "time")]
+        let timeout = self.timers.till_next_timer_or_timeout(timeout);
+
+        let res = self.poll_impl(timeout);
+        #[cfg(feature = "time")]
+        self.timers.expire_timers(entries);
+
+        entries.extend(
+            self.iocp_entries
+                .drain(..)
+                .filter(|e| !is_wakeup(e))
+                .map(|e| {
+                    let entry = Self::create_entry(e);
+                    self.pending.remove(&entry.user_data());
+                    entry
+                }),
+        );
+
+        res
+    }
+}
+
+// the packet posted by the waker
+#[inline]
+fn is_wakeup(iocp_entry: &OVERLAPPED_ENTRY) -> bool {
+    iocp_entry.lpOverlapped.is_null() && iocp_entry.lpCompletionKey == WAKER_KEY
 }
 
 /// # Safety
@@ -283,6 +768,51 @@ fn ntstatus_from_win32(x: i32) -> NTSTATUS {
 
 #[cfg(feature = "time")]
 const TIMER_PENDING: usize = usize::MAX - 2;
+// the driver posts the completion of `Nop` operation
+const NOP_PENDING: usize = usize::MAX - 3;
+// the driver updates or removes the timer instead of operating
+#[cfg(feature = "time")]
+const TIMER_UPDATE_PENDING: usize = usize::MAX - 4;
+#[cfg(feature = "time")]
+const TIMER_REMOVE_PENDING: usize = usize::MAX - 5;
+// the completion key of the packets posted by the wakers
+const WAKER_KEY: usize = usize::MAX - 6;
+
+// FILE_INFORMATION_CLASS of the completion port replacement, available since Windows 8.1
+const FILE_REPLACE_COMPLETION_INFORMATION: i32 = 61;
+
+#[repr(C)]
+struct FileCompletionInformation {
+    port: HANDLE,
+    key: usize,
+}
+
+#[repr(C)]
+struct IoStatusBlock {
+    // the union of the status and a pointer
+    status: *mut std::ffi::c_void,
+    information: usize,
+}
+
+#[link(name = "ntdll")]
+extern "system" {
+    fn NtSetInformationFile(
+        file_handle: HANDLE,
+        io_status_block: *mut IoStatusBlock,
+        file_information: *const std::ffi::c_void,
+        length: u32,
+        file_information_class: i32,
+    ) -> NTSTATUS;
+}
+
+#[cfg(feature = "time")]
+fn timer_found(found: bool) -> io::Result<usize> {
+    if found {
+        Ok(0)
+    } else {
+        Err(io::Error::from_raw_os_error(ERROR_NOT_FOUND as _))
+    }
+}
 
 impl<'arena> CompleteIo<'arena> for Driver<'arena> {
     #[inline]
@@ -299,6 +829,39 @@ impl<'arena> CompleteIo<'arena> for Driver<'arena> {
         Ok(Fd::from_raw(fd))
     }
 
+    fn detach(&mut self, fd: RawFd) -> io::Result<()> {
+        let pending = self.pending.values().any(|&(handle, _)| handle == fd)
+            || self
+                .squeue
+                .iter()
+                .any(|op| op.opcode_ref().handle() == Some(fd));
+        if pending {
+            return Err(io::Error::from_raw_os_error(ERROR_BUSY as _));
+        }
+        // the port is replaced with none
+        let info = FileCompletionInformation { port: 0, key: 0 };
+        let mut status = IoStatusBlock {
+            status: null_mut(),
+            information: 0,
+        };
+        let res = unsafe {
+            NtSetInformationFile(
+                fd as _,
+                &mut status,
+                &info as *const _ as *const _,
+                std::mem::size_of::<FileCompletionInformation>() as _,
+                FILE_REPLACE_COMPLETION_INFORMATION,
+            )
+        };
+        if res == STATUS_SUCCESS {
+            Ok(())
+        } else {
+            Err(io::Error::from_raw_os_error(
+                unsafe { RtlNtStatusToDosError(res) } as _,
+            ))
+        }
+    }
+
     #[inline]
     fn register_fd(&mut self, fd: RawFd, _id: u32) -> io::Result<FixedFd> {
         self.attach(fd)
@@ -309,40 +872,88 @@ impl<'arena> CompleteIo<'arena> for Driver<'arena> {
         Ok(())
     }
 
-    #[inline]
-    fn try_cancel(&mut self, user_data: usize) -> Result<(), ()> {
-        if let Some(pos) = self
-            .squeue
+    fn register_files(&mut self, fds: &[RawFd]) -> io::Result<FixedFdSlot> {
+        let fds = fds
             .iter()
-            .position(|operation| operation.user_data() == user_data)
-        {
-            // we assume cancellations are rare
-            let _ = self.squeue.remove(pos);
+            .map(|fd| self.register_fd(*fd, 0))
+            .collect::<io::Result<_>>()?;
+        Ok(FixedFdSlot { fds })
+    }
+
+    fn register_files_update(
+        &mut self,
+        slot: &mut FixedFdSlot,
+        index: usize,
+        fds: &[RawFd],
+    ) -> io::Result<()> {
+        if index + fds.len() > slot.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "updated files are out of the slot",
+            ));
+        }
+        for (fixed_fd, fd) in slot.fds[index..].iter_mut().zip(fds) {
+            *fixed_fd = self.register_fd(*fd, 0)?;
         }
         Ok(())
     }
 
+    #[inline]
+    fn unregister_files(&mut self, _slot: FixedFdSlot) -> io::Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn register_buffers<'a: 'arena>(
+        &mut self,
+        bufs: &'a mut [IoSliceMut<'a>],
+    ) -> io::Result<FixedBufRegistry<'a>> {
+        FixedBufRegistry::new(bufs)
+    }
+
+    #[inline]
+    fn unregister_buffers(&mut self, registry: &FixedBufRegistry<'_>) -> io::Result<()> {
+        registry.check_not_in_use()
+    }
+
+    #[inline]
+    fn try_cancel(&mut self, user_data: usize) -> Result<(), CancelError> {
+        match self.cancel(&CancelTarget::UserData(user_data)) {
+            Ok(_) => {
+                self.stats.canceled += 1;
+                self.hooks.canceled(user_data);
+                Ok(())
+            }
+            Err(e) if e.raw_os_error() == Some(ERROR_NOT_FOUND as _) => Err(CancelError::NotFound),
+            Err(e) => Err(CancelError::Os(e)),
+        }
+    }
+
     #[inline]
     fn try_push<O: OpCode>(
         &mut self,
         op: Operation<'arena, O>,
-    ) -> Result<(), Operation<'arena, O>> {
-        if self.capacity_left() > 0 {
-            self.squeue.push(OpObject::from(op));
-            Ok(())
-        } else {
-            Err(op)
+    ) -> Result<(), PushError<Operation<'arena, O>>> {
+        if self.capacity_left() == 0 {
+            return Err(PushError::QueueFull(op));
         }
+        if !self.user_data.insert(op.user_data()) {
+            return Err(PushError::DuplicateUserData(op));
+        }
+        self.push_op(OpObject::from(op));
+        Ok(())
     }
 
     #[inline]
-    fn try_push_dyn(&mut self, op: OpObject<'arena>) -> Result<(), OpObject<'arena>> {
-        if self.capacity_left() > 0 {
-            self.squeue.push(op);
-            Ok(())
-        } else {
-            Err(op)
+    fn try_push_dyn(&mut self, op: OpObject<'arena>) -> Result<(), PushError<OpObject<'arena>>> {
+        if self.capacity_left() == 0 {
+            return Err(PushError::QueueFull(op));
+        }
+        if !self.user_data.insert(op.user_data()) {
+            return Err(PushError::DuplicateUserData(op));
         }
+        self.push_op(op);
+        Ok(())
     }
 
     #[inline]
@@ -350,8 +961,16 @@ impl<'arena> CompleteIo<'arena> for Driver<'arena> {
         &mut self,
         ops_queue: &mut vec_deque_alloc!(OpObject<'arena>, A),
     ) {
+        self.stats.max_queued = self.stats.max_queued.max(ops_queue.len());
         let till = self.capacity_left().min(ops_queue.len());
-        self.squeue.extend(ops_queue.drain(..till));
+        for _ in 0..till {
+            // the operation with the duplicate data stays in the queue
+            if !self.user_data.insert(ops_queue[0].user_data()) {
+                break;
+            }
+            let op = ops_queue.pop_front().expect("in range");
+            self.push_op(op);
+        }
     }
 
     #[inline]
@@ -364,46 +983,47 @@ impl<'arena> CompleteIo<'arena> for Driver<'arena> {
         timeout: Option<Duration>,
         entries: &mut impl Extend<Entry>,
     ) -> io::Result<()> {
-        let oneshot_completed_iter =
-            self.squeue
-                .drain(..)
-                .enumerate()
-                .filter_map(|(idx, mut operation)| {
-                    let user_data = operation.user_data();
-                    // we require Unpin buffers - so no need to pin
-                    let op = operation.opcode();
-                    let result = op.operate(user_data);
-                    match result {
-                        #[cfg(feature = "time")]
-                        Poll::Ready(Ok(TIMER_PENDING)) => {
-                            self.timers.insert(user_data, op.timer_delay());
-                            None
-                        }
-                        Poll::Ready(result) => {
-                            self.squeue_drained_till = idx + 1;
-                            Some(Entry::new(user_data, result))
-                        }
-                        _ => None,
-                    }
-                });
-
-        entries.extend(oneshot_completed_iter);
-        self.squeue_drained_till = self.squeue.capacity();
-
-        #[cfg(feature = "time")]
-        let timeout = self.timers.till_next_timer_or_timeout(timeout);
+        self.hooks.submitted();
+        // the completed operations release the data when the entries are extended
+        let mut user_data = std::mem::take(&mut self.user_data);
+        let hooks = self.hooks.clone();
+        let mut entries = CountCompleted::new(entries, &mut user_data, &hooks);
+        let res = self.submit_entries(timeout, &mut entries);
+        self.stats.completed += entries.completed();
+        self.user_data = user_data;
+        res
+    }
 
-        let res = self.poll_impl(timeout);
-        #[cfg(feature = "time")]
-        self.timers.expire_timers(entries);
+    fn peek_completed(&mut self) -> CompletionIter<'_, 'arena> {
+        // the entries are returned after the successors of linked operations are pushed
+        let mut completed = std::mem::take(&mut self.completed);
+        self.remove_op_timeouts(&mut completed);
+        self.links
+            .complete_entries(&mut completed, &mut self.squeue);
+        self.completed = completed;
+        CompletionIter::new(
+            &mut self.completed,
+            &mut self.stats,
+            &mut self.user_data,
+            &self.hooks,
+        )
+    }
 
-        entries.extend(
-            self.iocp_entries
-                .drain(..)
-                .filter_map(|e| Some(Self::create_entry(e))),
-        );
+    unsafe fn flush(&mut self) -> io::Result<usize> {
+        self.hooks.submitted();
+        let submitted = self.squeue.len();
+        // the entries of completed operations are output by the next submit
+        let mut completed = std::mem::take(&mut self.completed);
+        self.operate_squeue(&mut completed);
+        self.completed = completed;
+        Ok(submitted)
+    }
+}
 
-        res
+impl Drop for Driver<'_> {
+    fn drop(&mut self) {
+        // the kernel writes to the buffers of the operations in flight
+        let _ = self.shutdown(None);
     }
 }
 
@@ -1,9 +1,12 @@
 #[cfg(feature = "once_cell_try")]
 use std::sync::OnceLock;
 use std::{
+    fs::OpenOptions,
     io,
     marker::PhantomData,
-    os::raw::c_void,
+    net::Shutdown,
+    os::{raw::c_void, windows::io::IntoRawHandle},
+    path::PathBuf,
     ptr::{copy, null, null_mut},
     task::Poll,
 };
@@ -11,6 +14,7 @@ use std::{
 #[cfg(not(feature = "once_cell_try"))]
 use once_cell::sync::OnceCell as OnceLock;
 use socket2::{Domain, Protocol, SockAddr, Socket, Type};
+use widestring::U16CString;
 use windows_sys::{
     core::GUID,
     Win32::{
@@ -19,28 +23,51 @@ use windows_sys::{
             ERROR_NO_DATA, ERROR_PIPE_CONNECTED,
         },
         Networking::WinSock::{
-            closesocket, getsockopt, setsockopt, socklen_t, WSAIoctl, WSARecv, WSARecvFrom,
-            WSASend, WSASendTo, INVALID_SOCKET, LPFN_ACCEPTEX, LPFN_CONNECTEX,
-            LPFN_GETACCEPTEXSOCKADDRS, SIO_GET_EXTENSION_FUNCTION_POINTER, SOCKADDR,
-            SOCKADDR_STORAGE, SOL_SOCKET, SO_ERROR, SO_UPDATE_ACCEPT_CONTEXT,
-            SO_UPDATE_CONNECT_CONTEXT, WSAENOTSOCK, WSAID_ACCEPTEX, WSAID_CONNECTEX,
-            WSAID_GETACCEPTEXSOCKADDRS,
+            bind, closesocket, getsockopt, listen, setsockopt, shutdown, socklen_t, WSAIoctl,
+            WSARecv, WSARecvFrom, WSASend, WSASendTo, INVALID_SOCKET, LPFN_ACCEPTEX,
+            LPFN_CONNECTEX, LPFN_GETACCEPTEXSOCKADDRS, LPFN_TRANSMITFILE, MSG_PEEK, SD_BOTH,
+            SD_RECEIVE, SD_SEND, SIO_GET_EXTENSION_FUNCTION_POINTER, SOCKADDR, SOCKADDR_STORAGE,
+            SOL_SOCKET, SO_ERROR, SO_UPDATE_ACCEPT_CONTEXT, SO_UPDATE_CONNECT_CONTEXT, WSABUF,
+            WSAENOTSOCK, WSAID_ACCEPTEX, WSAID_CONNECTEX, WSAID_GETACCEPTEXSOCKADDRS,
+            WSAID_TRANSMITFILE,
+        },
+        Storage::FileSystem::{
+            FileAllocationInfo, FileEndOfFileInfo, FileStandardInfo, FlushFileBuffers,
+            GetFileInformationByHandleEx, MoveFileExW, ReadFile, SetFileInformationByHandle,
+            WriteFile, FILE_ALLOCATION_INFO, FILE_END_OF_FILE_INFO, FILE_STANDARD_INFO,
+        },
+        System::{
+            Pipes::ConnectNamedPipe,
+            IO::{PostQueuedCompletionStatus, OVERLAPPED},
         },
-        Storage::FileSystem::{FlushFileBuffers, ReadFile, WriteFile},
-        System::{Pipes::ConnectNamedPipe, IO::OVERLAPPED},
     },
 };
 
 #[cfg(feature = "time")]
-use crate::driver::iocp::TIMER_PENDING;
+use crate::driver::iocp::{TIMER_PENDING, TIMER_REMOVE_PENDING, TIMER_UPDATE_PENDING};
 #[cfg(feature = "time")]
-pub use crate::driver::time::Timeout;
+pub use crate::driver::time::{Timeout, TimeoutRemove, TimeoutUpdate};
 use crate::{
     buf::{AsIoSlices, AsIoSlicesMut, BufWrapper, BufWrapperMut, IntoInner, IoBuf, IoBufMut},
-    driver::{iocp::Overlapped, Fd, FromRawFd, IntoRawFd, OpCode, RawFd},
+    driver::{
+        iocp::{Overlapped, NOP_PENDING},
+        DriverHandle, Fd, FixedBuf, FromRawFd, IntoRawFd, OpCode, RawFd,
+    },
+    op::{AcceptFlags, AllocateMode, RenameFlags, RwFlags, SyncRangeFlags},
     syscall,
 };
 
+fn check_rw_flags(rw_flags: RwFlags) -> io::Result<()> {
+    if rw_flags.contains(RwFlags::DSYNC) || rw_flags.contains(RwFlags::NOWAIT) {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "RwFlags::DSYNC and RwFlags::NOWAIT are not supported on this platform",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
 #[inline]
 unsafe fn winapi_result(transferred: u32) -> Poll<io::Result<usize>> {
     let error = GetLastError();
@@ -64,24 +91,16 @@ unsafe fn win32_result(res: i32, transferred: u32) -> Poll<io::Result<usize>> {
 }
 
 // read, write, send and recv functions may return immediately, indicate that
-// the task is completed, but the overlapped result is also posted to the IOCP.
-// To make our driver easy, simply return Pending and query the result later.
-
-#[inline]
-unsafe fn win32_pending_result(res: i32) -> Poll<io::Result<usize>> {
-    if res == 0 {
-        winapi_result(0)
-    } else {
-        Poll::Pending
-    }
-}
+// the task is completed. The attached handles skip the completion port on success,
+// so the result is returned without waiting for the packet. Read and write functions
+// store the number of bytes in the overlapped.
 
 #[inline]
 unsafe fn winsock_result(res: i32, transferred: u32) -> Poll<io::Result<usize>> {
     if res != 0 {
         winapi_result(transferred)
     } else {
-        Poll::Pending
+        Poll::Ready(Ok(transferred as _))
     }
 }
 
@@ -147,12 +166,16 @@ impl<'arena, T: IoBufMut<'arena>> OpCode for Read<'arena, T> {
             null_mut(),
             self.overlapped() as *mut _,
         );
-        win32_pending_result(res)
+        win32_result(res, self.overlapped.base.InternalHigh as _)
     }
 
     fn overlapped(&mut self) -> &mut OVERLAPPED {
         &mut self.overlapped.base
     }
+
+    fn handle(&self) -> Option<RawFd> {
+        Some(self.fd.as_raw_fd())
+    }
 }
 
 /// Read a file at specified position into specified buffer.
@@ -160,6 +183,7 @@ pub struct ReadAt<'arena, T: IoBufMut<'arena>> {
     fd: Fd,
     offset: usize,
     buffer: T,
+    rw_flags: RwFlags,
     overlapped: Overlapped,
     _lifetime: PhantomData<&'arena ()>,
 }
@@ -171,10 +195,21 @@ impl<'arena, T: IoBufMut<'arena>> ReadAt<'arena, T> {
             fd,
             offset,
             buffer,
+            rw_flags: RwFlags::empty(),
             overlapped: Overlapped::new(usize::MAX),
             _lifetime: PhantomData,
         }
     }
+
+    /// Set [`RwFlags`] of the operation.
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: `DSYNC` and `NOWAIT` fail with [`io::ErrorKind::Unsupported`], `HIPRI` is ignored.
+    pub fn with_rw_flags(mut self, rw_flags: RwFlags) -> Self {
+        self.rw_flags = rw_flags;
+        self
+    }
 }
 
 impl<'arena, T: IoBufMut<'arena>> IntoInner for ReadAt<'arena, T> {
@@ -187,6 +222,7 @@ impl<'arena, T: IoBufMut<'arena>> IntoInner for ReadAt<'arena, T> {
 
 impl<'arena, T: IoBufMut<'arena>> OpCode for ReadAt<'arena, T> {
     unsafe fn operate(&mut self, user_data: usize) -> Poll<io::Result<usize>> {
+        check_rw_flags(self.rw_flags)?;
         self.overlapped.user_data = user_data;
         self.overlapped().Anonymous.Anonymous.Offset = (self.offset & 0xFFFFFFFF) as _;
         #[cfg(target_pointer_width = "64")]
@@ -204,218 +240,1245 @@ impl<'arena, T: IoBufMut<'arena>> OpCode for ReadAt<'arena, T> {
             null_mut(),
             self.overlapped() as *mut _,
         );
-        win32_pending_result(res)
+        win32_result(res, self.overlapped.base.InternalHigh as _)
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        &mut self.overlapped.base
+    }
+
+    fn handle(&self) -> Option<RawFd> {
+        Some(self.fd.as_raw_fd())
+    }
+}
+
+/// Write a nonseekable file from specified buffer.
+pub struct Write<'arena, T: IoBuf<'arena>> {
+    fd: Fd,
+    buffer: T,
+    overlapped: Overlapped,
+    _lifetime: PhantomData<&'arena ()>,
+}
+
+impl<'arena, T: IoBuf<'arena>> Write<'arena, T> {
+    /// Create [`Write`].
+    pub fn new(fd: Fd, buffer: T) -> Self {
+        Self {
+            fd,
+            buffer,
+            overlapped: Overlapped::new(usize::MAX),
+            _lifetime: PhantomData,
+        }
+    }
+}
+
+impl<'arena, T: IoBuf<'arena>> IntoInner for Write<'arena, T> {
+    type Inner = T;
+
+    fn into_inner(self) -> Self::Inner {
+        self.buffer
+    }
+}
+
+impl<'arena, T: IoBuf<'arena>> OpCode for Write<'arena, T> {
+    unsafe fn operate(&mut self, user_data: usize) -> Poll<io::Result<usize>> {
+        self.overlapped.user_data = user_data;
+        // SAFETY: buffer is Unpin
+        let slice = self.buffer.as_slice();
+        let res = WriteFile(
+            self.fd.as_raw_fd() as _,
+            slice.as_ptr() as _,
+            slice.len() as _,
+            null_mut(),
+            self.overlapped() as *mut _,
+        );
+        win32_result(res, self.overlapped.base.InternalHigh as _)
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        &mut self.overlapped.base
+    }
+
+    fn handle(&self) -> Option<RawFd> {
+        Some(self.fd.as_raw_fd())
+    }
+}
+
+/// Write a file at specified position from specified buffer.
+pub struct WriteAt<'arena, T: IoBuf<'arena>> {
+    fd: Fd,
+    offset: usize,
+    buffer: T,
+    rw_flags: RwFlags,
+    overlapped: Overlapped,
+    _lifetime: PhantomData<&'arena ()>,
+}
+
+impl<'arena, T: IoBuf<'arena>> WriteAt<'arena, T> {
+    /// Create [`WriteAt`].
+    pub fn new(fd: Fd, offset: usize, buffer: T) -> Self {
+        Self {
+            fd,
+            offset,
+            buffer,
+            rw_flags: RwFlags::empty(),
+            overlapped: Overlapped::new(usize::MAX),
+            _lifetime: PhantomData,
+        }
+    }
+
+    /// Set [`RwFlags`] of the operation.
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: `DSYNC` and `NOWAIT` fail with [`io::ErrorKind::Unsupported`], `HIPRI` is ignored.
+    pub fn with_rw_flags(mut self, rw_flags: RwFlags) -> Self {
+        self.rw_flags = rw_flags;
+        self
+    }
+}
+
+impl<'arena, T: IoBuf<'arena>> IntoInner for WriteAt<'arena, T> {
+    type Inner = T;
+
+    fn into_inner(self) -> Self::Inner {
+        self.buffer
+    }
+}
+
+impl<'arena, T: IoBuf<'arena>> OpCode for WriteAt<'arena, T> {
+    unsafe fn operate(&mut self, user_data: usize) -> Poll<io::Result<usize>> {
+        check_rw_flags(self.rw_flags)?;
+        self.overlapped.user_data = user_data;
+        self.overlapped().Anonymous.Anonymous.Offset = (self.offset & 0xFFFFFFFF) as _;
+        #[cfg(target_pointer_width = "64")]
+        {
+            self.overlapped().Anonymous.Anonymous.OffsetHigh = (self.offset >> 32) as _;
+        }
+        // SAFETY: buffer is Unpin
+        let slice = self.buffer.as_slice();
+        let res = WriteFile(
+            self.fd.as_raw_fd() as _,
+            slice.as_ptr() as _,
+            slice.len() as _,
+            null_mut(),
+            self.overlapped() as *mut _,
+        );
+        win32_result(res, self.overlapped.base.InternalHigh as _)
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        &mut self.overlapped.base
+    }
+
+    fn handle(&self) -> Option<RawFd> {
+        Some(self.fd.as_raw_fd())
+    }
+}
+
+/// Read a file at specified position into scattered buffers.
+///
+/// ## Platform specific
+///
+/// * IOCP: `ReadFileScatter` requires unbuffered page-sized buffers, so only the first non-empty
+///   buffer is read into with `ReadFile`.
+pub struct ReadVectoredAtImpl<'arena, T: AsIoSlicesMut<'arena>> {
+    fd: Fd,
+    offset: usize,
+    buffer: T,
+    overlapped: Overlapped,
+    _lifetime: PhantomData<&'arena ()>,
+}
+
+impl<'arena, T: AsIoSlicesMut<'arena>> ReadVectoredAtImpl<'arena, T> {
+    /// Create [`ReadVectoredAt`](crate::op::ReadVectoredAt).
+    pub fn new(fd: Fd, offset: usize, buffer: T) -> Self {
+        Self {
+            fd,
+            offset,
+            buffer,
+            overlapped: Overlapped::new(usize::MAX),
+            _lifetime: PhantomData,
+        }
+    }
+}
+
+impl<'arena, T: AsIoSlicesMut<'arena>> IntoInner for ReadVectoredAtImpl<'arena, T> {
+    type Inner = T;
+
+    fn into_inner(self) -> Self::Inner {
+        self.buffer
+    }
+}
+
+impl<'arena, T: AsIoSlicesMut<'arena>> OpCode for ReadVectoredAtImpl<'arena, T> {
+    unsafe fn operate(&mut self, user_data: usize) -> Poll<io::Result<usize>> {
+        self.overlapped.user_data = user_data;
+        self.overlapped().Anonymous.Anonymous.Offset = (self.offset & 0xFFFFFFFF) as _;
+        #[cfg(target_pointer_width = "64")]
+        {
+            self.overlapped().Anonymous.Anonymous.OffsetHigh = (self.offset >> 32) as _;
+        }
+        let fd = self.fd.as_raw_fd() as _;
+        // SAFETY: IoSliceMut is Unpin
+        let slices = unsafe { self.buffer.as_io_slices_mut() };
+        let slice: &mut [u8] = slices
+            .iter_mut()
+            .find(|slice| !slice.is_empty())
+            .map_or(&mut [], |slice| &mut **slice);
+        let res = ReadFile(
+            fd,
+            slice.as_mut_ptr() as _,
+            slice.len() as _,
+            null_mut(),
+            &mut self.overlapped.base as *mut _,
+        );
+        win32_result(res, self.overlapped.base.InternalHigh as _)
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        &mut self.overlapped.base
+    }
+
+    fn handle(&self) -> Option<RawFd> {
+        Some(self.fd.as_raw_fd())
+    }
+}
+
+/// Write a file at specified position from scattered buffers.
+///
+/// ## Platform specific
+///
+/// * IOCP: `WriteFileGather` requires unbuffered page-sized buffers, so only the first non-empty
+///   buffer is written with `WriteFile`.
+pub struct WriteVectoredAtImpl<'arena, T: AsIoSlices<'arena>> {
+    fd: Fd,
+    offset: usize,
+    buffer: T,
+    overlapped: Overlapped,
+    _lifetime: PhantomData<&'arena ()>,
+}
+
+impl<'arena, T: AsIoSlices<'arena>> WriteVectoredAtImpl<'arena, T> {
+    /// Create [`WriteVectoredAt`](crate::op::WriteVectoredAt).
+    pub fn new(fd: Fd, offset: usize, buffer: T) -> Self {
+        Self {
+            fd,
+            offset,
+            buffer,
+            overlapped: Overlapped::new(usize::MAX),
+            _lifetime: PhantomData,
+        }
+    }
+}
+
+impl<'arena, T: AsIoSlices<'arena>> IntoInner for WriteVectoredAtImpl<'arena, T> {
+    type Inner = T;
+
+    fn into_inner(self) -> Self::Inner {
+        self.buffer
+    }
+}
+
+impl<'arena, T: AsIoSlices<'arena>> OpCode for WriteVectoredAtImpl<'arena, T> {
+    unsafe fn operate(&mut self, user_data: usize) -> Poll<io::Result<usize>> {
+        self.overlapped.user_data = user_data;
+        self.overlapped().Anonymous.Anonymous.Offset = (self.offset & 0xFFFFFFFF) as _;
+        #[cfg(target_pointer_width = "64")]
+        {
+            self.overlapped().Anonymous.Anonymous.OffsetHigh = (self.offset >> 32) as _;
+        }
+        // SAFETY: IoSlice is Unpin
+        let slices = unsafe { self.buffer.as_io_slices() };
+        let slice: &[u8] = slices
+            .iter()
+            .find(|slice| !slice.is_empty())
+            .map_or(&[], |slice| &**slice);
+        let res = WriteFile(
+            self.fd.as_raw_fd() as _,
+            slice.as_ptr() as _,
+            slice.len() as _,
+            null_mut(),
+            &mut self.overlapped.base as *mut _,
+        );
+        win32_result(res, self.overlapped.base.InternalHigh as _)
     }
 
     fn overlapped(&mut self) -> &mut OVERLAPPED {
         &mut self.overlapped.base
     }
+
+    fn handle(&self) -> Option<RawFd> {
+        Some(self.fd.as_raw_fd())
+    }
+}
+
+static CONNECT_EX: OnceLock<LPFN_CONNECTEX> = OnceLock::new();
+
+/// Connect to a remote address.
+pub struct Connect {
+    fd: Fd,
+    addr: SockAddr,
+    overlapped: Overlapped,
+}
+
+impl Connect {
+    /// Create [`Connect`]. `fd` should be bound.
+    pub fn new(fd: Fd, addr: SockAddr) -> Self {
+        Self {
+            fd,
+            addr,
+            overlapped: Overlapped::new(usize::MAX),
+        }
+    }
+
+    /// Post operation socket handling.
+    ///
+    /// Set SO_UPDATE_CONNECT_CONTEXT
+    pub fn on_connect(&self, result: io::Result<usize>) -> io::Result<()> {
+        let _ = result?;
+        self.update_context()?;
+        Ok(())
+    }
+
+    /// Set SO_UPDATE_CONNECT_CONTEXT
+    pub fn update_context(&self) -> io::Result<()> {
+        syscall!(
+            SOCKET,
+            setsockopt(
+                self.fd.as_raw_fd() as _,
+                SOL_SOCKET,
+                SO_UPDATE_CONNECT_CONTEXT,
+                null(),
+                0,
+            )
+        )?;
+        Ok(())
+    }
+}
+
+impl OpCode for Connect {
+    unsafe fn operate(&mut self, user_data: usize) -> Poll<io::Result<usize>> {
+        self.overlapped.user_data = user_data;
+        let connect_fn = CONNECT_EX
+            .get_or_try_init(|| get_wsa_fn(self.fd, WSAID_CONNECTEX))?
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::Unsupported, "cannot retrieve ConnectEx")
+            })?;
+        let mut sent = 0;
+        let res = connect_fn(
+            self.fd.as_raw_fd() as _,
+            // SAFETY: SockAddr is Unpin - https://docs.rs/socket2/latest/socket2/struct.SockAddr.html#impl-Unpin-for-SockAddr
+            self.addr.as_ptr(),
+            self.addr.len(),
+            null(),
+            0,
+            &mut sent,
+            &mut self.overlapped.base as *mut _,
+        );
+        win32_result(res, sent)
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        &mut self.overlapped.base
+    }
+
+    fn handle(&self) -> Option<RawFd> {
+        Some(self.fd.as_raw_fd())
+    }
+}
+
+/// Shut down the read, write, or both halves of a connected socket.
+pub struct ShutdownSocket {
+    fd: Fd,
+    how: Shutdown,
+}
+
+impl ShutdownSocket {
+    /// Create [`ShutdownSocket`].
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: it is synchronized operation, and calls `shutdown`.
+    pub fn new(fd: Fd, how: Shutdown) -> Self {
+        Self { fd, how }
+    }
+}
+
+impl OpCode for ShutdownSocket {
+    unsafe fn operate(&mut self, _user_data: usize) -> Poll<io::Result<usize>> {
+        let how = match self.how {
+            Shutdown::Read => SD_RECEIVE,
+            Shutdown::Write => SD_SEND,
+            Shutdown::Both => SD_BOTH,
+        };
+        Poll::Ready(syscall!(SOCKET, shutdown(self.fd.as_raw_fd() as _, how)).map(|_| 0))
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        unimplemented!("shutdown is synchonous")
+    }
+}
+
+/// Create a socket.
+///
+/// The result is the new socket, [`CreateSocket::on_create`] takes its ownership.
+pub struct CreateSocket {
+    domain: Domain,
+    ty: Type,
+    protocol: Option<Protocol>,
+}
+
+impl CreateSocket {
+    /// Create [`CreateSocket`].
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: it is synchronized operation, and calls `WSASocketW`.
+    pub fn new(domain: Domain, ty: Type, protocol: Option<Protocol>) -> Self {
+        Self {
+            domain,
+            ty,
+            protocol,
+        }
+    }
+
+    /// Post operation handling, returns the created socket.
+    pub fn on_create(&self, result: io::Result<usize>) -> io::Result<Socket> {
+        let fd = result?;
+        // SAFETY: the operation returned a new socket
+        Ok(unsafe { Socket::from_raw_fd(fd as _) })
+    }
+}
+
+impl OpCode for CreateSocket {
+    unsafe fn operate(&mut self, _user_data: usize) -> Poll<io::Result<usize>> {
+        let res = Socket::new(self.domain, self.ty, self.protocol);
+        Poll::Ready(res.map(|socket| socket.into_raw_fd() as _))
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        unimplemented!("CreateSocket is synchonous")
+    }
+}
+
+/// Bind a socket to an address.
+pub struct Bind {
+    fd: Fd,
+    addr: SockAddr,
+}
+
+impl Bind {
+    /// Create [`Bind`].
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: it is synchronized operation, and calls `bind`.
+    pub fn new(fd: Fd, addr: SockAddr) -> Self {
+        Self { fd, addr }
+    }
+}
+
+impl OpCode for Bind {
+    unsafe fn operate(&mut self, _user_data: usize) -> Poll<io::Result<usize>> {
+        let res = syscall!(
+            SOCKET,
+            bind(
+                self.fd.as_raw_fd() as _,
+                self.addr.as_ptr() as _,
+                self.addr.len()
+            )
+        );
+        Poll::Ready(res.map(|_| 0))
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        unimplemented!("Bind is synchonous")
+    }
+}
+
+/// Mark a socket as accepting connections.
+pub struct Listen {
+    fd: Fd,
+    backlog: i32,
+}
+
+impl Listen {
+    /// Create [`Listen`].
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: it is synchronized operation, and calls `listen`.
+    pub fn new(fd: Fd, backlog: i32) -> Self {
+        Self { fd, backlog }
+    }
+}
+
+impl OpCode for Listen {
+    unsafe fn operate(&mut self, _user_data: usize) -> Poll<io::Result<usize>> {
+        Poll::Ready(syscall!(SOCKET, listen(self.fd.as_raw_fd() as _, self.backlog)).map(|_| 0))
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        unimplemented!("Listen is synchonous")
+    }
+}
+
+/// Wait for a socket to become readable.
+pub struct PollReadable {
+    fd: Fd,
+    overlapped: Overlapped,
+}
+
+impl PollReadable {
+    /// Create [`PollReadable`].
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: zero-byte `WSARecv` peeking the data, the result is always 0.
+    pub fn new(fd: Fd) -> Self {
+        Self {
+            fd,
+            overlapped: Overlapped::new(usize::MAX),
+        }
+    }
+}
+
+impl OpCode for PollReadable {
+    unsafe fn operate(&mut self, user_data: usize) -> Poll<io::Result<usize>> {
+        self.overlapped.user_data = user_data;
+        let buffer = WSABUF {
+            len: 0,
+            buf: null_mut(),
+        };
+        let mut flags = MSG_PEEK as u32;
+        let mut received = 0;
+        let res = WSARecv(
+            self.fd.as_raw_fd() as _,
+            &buffer,
+            1,
+            &mut received,
+            &mut flags,
+            &mut self.overlapped.base as *mut _,
+            None,
+        );
+        winsock_result(res, received)
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        &mut self.overlapped.base
+    }
+
+    fn handle(&self) -> Option<RawFd> {
+        Some(self.fd.as_raw_fd())
+    }
+}
+
+/// Wait for a socket to become writable.
+pub struct PollWritable {
+    fd: Fd,
+    overlapped: Overlapped,
+}
+
+impl PollWritable {
+    /// Create [`PollWritable`].
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: zero-byte `WSASend`, the result is always 0.
+    pub fn new(fd: Fd) -> Self {
+        Self {
+            fd,
+            overlapped: Overlapped::new(usize::MAX),
+        }
+    }
+}
+
+impl OpCode for PollWritable {
+    unsafe fn operate(&mut self, user_data: usize) -> Poll<io::Result<usize>> {
+        self.overlapped.user_data = user_data;
+        let buffer = WSABUF {
+            len: 0,
+            buf: null_mut(),
+        };
+        let mut sent = 0;
+        let res = WSASend(
+            self.fd.as_raw_fd() as _,
+            &buffer,
+            1,
+            &mut sent,
+            0,
+            &mut self.overlapped.base as *mut _,
+            None,
+        );
+        winsock_result(res, sent)
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        &mut self.overlapped.base
+    }
+
+    fn handle(&self) -> Option<RawFd> {
+        Some(self.fd.as_raw_fd())
+    }
+}
+
+/// Read a file at specified position into a registered buffer.
+///
+/// ## Platform specific
+///
+/// * IOCP: the buffers are not registered in the kernel, it is the same as [`ReadAt`].
+pub type ReadAtFixed<'arena> = ReadAt<'arena, FixedBuf<'arena>>;
+
+/// Write a file at specified position from a registered buffer.
+///
+/// ## Platform specific
+///
+/// * IOCP: the buffers are not registered in the kernel, it is the same as [`WriteAt`].
+pub type WriteAtFixed<'arena> = WriteAt<'arena, FixedBuf<'arena>>;
+
+/// Sync data to the disk.
+pub struct Sync {
+    fd: Fd,
+    #[allow(dead_code)]
+    datasync: bool,
+}
+
+impl Sync {
+    /// Create [`Sync`].
+    ///
+    /// If `datasync` is `true`, the file metadata may not be synchronized.
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: it is synchronized operation, and calls `FlushFileBuffers`.
+    /// * io-uring: `fdatasync` if `datasync` specified, otherwise `fsync`.
+    /// * kqueue: it is synchronized `fdatasync` or `fsync`.
+    pub fn new(fd: Fd, datasync: bool) -> Self {
+        Self { fd, datasync }
+    }
+}
+
+impl OpCode for Sync {
+    unsafe fn operate(&mut self, _user_data: usize) -> Poll<io::Result<usize>> {
+        let res = FlushFileBuffers(self.fd.as_raw_fd() as _);
+        win32_result(res, 0)
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        unimplemented!("FlushFileBuffers is synchonous")
+    }
+}
+
+/// Sync a file range to the disk.
+pub struct SyncFileRange {
+    fd: Fd,
+}
+
+impl SyncFileRange {
+    /// Create [`SyncFileRange`] for the range of `nbytes` bytes starting from `offset`.
+    ///
+    /// Zero `nbytes` means the range till the end of file.
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: it falls back to synchronized `FlushFileBuffers` of the whole file.
+    pub fn new(fd: Fd, _offset: u64, _nbytes: u32, _flags: SyncRangeFlags) -> Self {
+        Self { fd }
+    }
+}
+
+impl OpCode for SyncFileRange {
+    unsafe fn operate(&mut self, _user_data: usize) -> Poll<io::Result<usize>> {
+        let res = FlushFileBuffers(self.fd.as_raw_fd() as _);
+        win32_result(res, 0)
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        unimplemented!("FlushFileBuffers is synchonous")
+    }
+}
+
+/// Manipulate the allocated disk space of a file.
+pub struct Fallocate {
+    fd: Fd,
+    offset: u64,
+    len: u64,
+    mode: AllocateMode,
+}
+
+impl Fallocate {
+    /// Create [`Fallocate`] for the range of `len` bytes starting from `offset`.
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: it is synchronized operation, and calls `SetFileInformationByHandle` to grow the
+    ///   allocation size and the end of file. [`AllocateMode::PUNCH_HOLE`] fails with
+    ///   [`io::ErrorKind::Unsupported`].
+    pub fn new(fd: Fd, offset: u64, len: u64, mode: AllocateMode) -> Self {
+        Self {
+            fd,
+            offset,
+            len,
+            mode,
+        }
+    }
+}
+
+impl OpCode for Fallocate {
+    unsafe fn operate(&mut self, _user_data: usize) -> Poll<io::Result<usize>> {
+        if self.mode.contains(AllocateMode::PUNCH_HOLE) {
+            return Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "punching holes is not supported by IOCP driver",
+            )));
+        }
+        let Some(end) = self
+            .offset
+            .checked_add(self.len)
+            .and_then(|end| i64::try_from(end).ok())
+        else {
+            return Poll::Ready(Err(io::ErrorKind::InvalidInput.into()));
+        };
+        let handle = self.fd.as_raw_fd() as _;
+        let mut info: FILE_STANDARD_INFO = std::mem::zeroed();
+        let res = GetFileInformationByHandleEx(
+            handle,
+            FileStandardInfo,
+            &mut info as *mut _ as _,
+            std::mem::size_of::<FILE_STANDARD_INFO>() as _,
+        );
+        if res == 0 {
+            return winapi_result(0);
+        }
+        // setting smaller allocation size truncates the file
+        if info.AllocationSize < end {
+            let allocation = FILE_ALLOCATION_INFO {
+                AllocationSize: end,
+            };
+            let res = SetFileInformationByHandle(
+                handle,
+                FileAllocationInfo,
+                &allocation as *const _ as _,
+                std::mem::size_of::<FILE_ALLOCATION_INFO>() as _,
+            );
+            if res == 0 {
+                return winapi_result(0);
+            }
+        }
+        if !self.mode.contains(AllocateMode::KEEP_SIZE) && info.EndOfFile < end {
+            let end_of_file = FILE_END_OF_FILE_INFO { EndOfFile: end };
+            let res = SetFileInformationByHandle(
+                handle,
+                FileEndOfFileInfo,
+                &end_of_file as *const _ as _,
+                std::mem::size_of::<FILE_END_OF_FILE_INFO>() as _,
+            );
+            return win32_result(res, 0);
+        }
+        Poll::Ready(Ok(0))
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        unimplemented!("SetFileInformationByHandle is synchonous")
+    }
+}
+
+/// Truncate or extend a file to the specified length.
+pub struct Truncate {
+    fd: Fd,
+    len: u64,
+}
+
+impl Truncate {
+    /// Create [`Truncate`].
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: it is synchronized operation, and calls `SetFileInformationByHandle`.
+    pub fn new(fd: Fd, len: u64) -> Self {
+        Self { fd, len }
+    }
+}
+
+impl OpCode for Truncate {
+    unsafe fn operate(&mut self, _user_data: usize) -> Poll<io::Result<usize>> {
+        let Ok(len) = i64::try_from(self.len) else {
+            return Poll::Ready(Err(io::ErrorKind::InvalidInput.into()));
+        };
+        let end_of_file = FILE_END_OF_FILE_INFO { EndOfFile: len };
+        let res = SetFileInformationByHandle(
+            self.fd.as_raw_fd() as _,
+            FileEndOfFileInfo,
+            &end_of_file as *const _ as _,
+            std::mem::size_of::<FILE_END_OF_FILE_INFO>() as _,
+        );
+        win32_result(res, 0)
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        unimplemented!("SetFileInformationByHandle is synchonous")
+    }
+}
+
+/// Open or create a file.
+///
+/// The completed operation returns the raw handle of the opened file.
+pub struct OpenAt {
+    path: PathBuf,
+    options: OpenOptions,
+}
+
+impl OpenAt {
+    /// Create [`OpenAt`].
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: it is synchronized operation, and calls `CreateFileW`. The caller should specify
+    ///   `FILE_FLAG_OVERLAPPED` to be able to attach the handle.
+    pub fn new(path: impl Into<PathBuf>, options: OpenOptions) -> Self {
+        Self {
+            path: path.into(),
+            options,
+        }
+    }
+}
+
+impl OpCode for OpenAt {
+    unsafe fn operate(&mut self, _user_data: usize) -> Poll<io::Result<usize>> {
+        Poll::Ready(
+            self.options
+                .open(&self.path)
+                .map(|file| file.into_raw_handle() as usize),
+        )
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        unimplemented!("CreateFileW is synchonous")
+    }
+}
+
+/// Create a directory.
+pub struct MkdirAt {
+    path: PathBuf,
+}
+
+impl MkdirAt {
+    /// Create [`MkdirAt`].
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: it is synchronized operation, and calls `CreateDirectoryW`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl OpCode for MkdirAt {
+    unsafe fn operate(&mut self, _user_data: usize) -> Poll<io::Result<usize>> {
+        Poll::Ready(std::fs::create_dir(&self.path).map(|_| 0))
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        unimplemented!("CreateDirectoryW is synchonous")
+    }
+}
+
+/// Remove a file or an empty directory.
+pub struct UnlinkAt {
+    path: PathBuf,
+    dir: bool,
+}
+
+impl UnlinkAt {
+    /// Create [`UnlinkAt`]. If `dir` is `true`, the empty directory is removed.
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: it is synchronized operation, and calls `DeleteFileW` or `RemoveDirectoryW`.
+    pub fn new(path: impl Into<PathBuf>, dir: bool) -> Self {
+        Self {
+            path: path.into(),
+            dir,
+        }
+    }
+}
+
+impl OpCode for UnlinkAt {
+    unsafe fn operate(&mut self, _user_data: usize) -> Poll<io::Result<usize>> {
+        let res = if self.dir {
+            std::fs::remove_dir(&self.path)
+        } else {
+            std::fs::remove_file(&self.path)
+        };
+        Poll::Ready(res.map(|_| 0))
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        unimplemented!("DeleteFileW is synchonous")
+    }
+}
+
+/// Rename a file.
+pub struct RenameAt {
+    old_path: PathBuf,
+    new_path: PathBuf,
+    flags: RenameFlags,
+}
+
+impl RenameAt {
+    /// Create [`RenameAt`].
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: it is synchronized operation, and calls `MoveFileExW`.
+    pub fn new(
+        old_path: impl Into<PathBuf>,
+        new_path: impl Into<PathBuf>,
+        flags: RenameFlags,
+    ) -> Self {
+        Self {
+            old_path: old_path.into(),
+            new_path: new_path.into(),
+            flags,
+        }
+    }
+}
+
+impl OpCode for RenameAt {
+    unsafe fn operate(&mut self, _user_data: usize) -> Poll<io::Result<usize>> {
+        if !self.flags.contains(RenameFlags::NOREPLACE) {
+            return Poll::Ready(std::fs::rename(&self.old_path, &self.new_path).map(|_| 0));
+        }
+        let to_wide = |path: &PathBuf| {
+            U16CString::from_os_str(path)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+        };
+        let (old_path, new_path) = match (to_wide(&self.old_path), to_wide(&self.new_path)) {
+            (Ok(old_path), Ok(new_path)) => (old_path, new_path),
+            (Err(e), _) | (_, Err(e)) => return Poll::Ready(Err(e)),
+        };
+        // without MOVEFILE_REPLACE_EXISTING the existing destination is not replaced
+        let res = MoveFileExW(old_path.as_ptr(), new_path.as_ptr(), 0);
+        win32_result(res, 0)
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        unimplemented!("MoveFileExW is synchonous")
+    }
+}
+
+/// Create a symbolic link.
+pub struct SymlinkAt {
+    target: PathBuf,
+    linkpath: PathBuf,
+    dir: bool,
+}
+
+impl SymlinkAt {
+    /// Create [`SymlinkAt`]. If `dir` is `true`, a directory symbolic link is created.
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: it is synchronized operation, and calls `CreateSymbolicLinkW`.
+    pub fn new(target: impl Into<PathBuf>, linkpath: impl Into<PathBuf>, dir: bool) -> Self {
+        Self {
+            target: target.into(),
+            linkpath: linkpath.into(),
+            dir,
+        }
+    }
+}
+
+impl OpCode for SymlinkAt {
+    unsafe fn operate(&mut self, _user_data: usize) -> Poll<io::Result<usize>> {
+        let res = if self.dir {
+            std::os::windows::fs::symlink_dir(&self.target, &self.linkpath)
+        } else {
+            std::os::windows::fs::symlink_file(&self.target, &self.linkpath)
+        };
+        Poll::Ready(res.map(|_| 0))
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        unimplemented!("CreateSymbolicLinkW is synchonous")
+    }
+}
+
+/// Create a hard link.
+pub struct LinkAt {
+    old_path: PathBuf,
+    new_path: PathBuf,
+}
+
+impl LinkAt {
+    /// Create [`LinkAt`].
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: it is synchronized operation, and calls `CreateHardLinkW`.
+    pub fn new(old_path: impl Into<PathBuf>, new_path: impl Into<PathBuf>) -> Self {
+        Self {
+            old_path: old_path.into(),
+            new_path: new_path.into(),
+        }
+    }
+}
+
+impl OpCode for LinkAt {
+    unsafe fn operate(&mut self, _user_data: usize) -> Poll<io::Result<usize>> {
+        Poll::Ready(std::fs::hard_link(&self.old_path, &self.new_path).map(|_| 0))
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        unimplemented!("CreateHardLinkW is synchonous")
+    }
+}
+
+/// Close a file handle or socket.
+pub struct Close {
+    fd: RawFd,
+}
+
+impl Close {
+    /// Create [`Close`].
+    ///
+    /// The operation takes ownership of `fd`. The caller should not use it
+    /// after the operation is pushed.
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: it is synchronized operation, and calls either `closesocket` or `CloseHandle`.
+    pub fn new(fd: RawFd) -> Self {
+        Self { fd }
+    }
+}
+
+impl OpCode for Close {
+    unsafe fn operate(&mut self, _user_data: usize) -> Poll<io::Result<usize>> {
+        Poll::Ready(close_raw_fd(self.fd))
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        unimplemented!("Close is synchonous")
+    }
 }
 
-/// Write a nonseekable file from specified buffer.
-pub struct Write<'arena, T: IoBuf<'arena>> {
-    fd: Fd,
-    buffer: T,
-    overlapped: Overlapped,
-    _lifetime: PhantomData<&'arena ()>,
+/// Cancel pushed operations.
+///
+/// The canceled operations complete with `ERROR_OPERATION_ABORTED`.
+pub struct Cancel {
+    pub(in crate::driver) target: CancelTarget,
 }
 
-impl<'arena, T: IoBuf<'arena>> Write<'arena, T> {
-    /// Create [`Write`].
-    pub fn new(fd: Fd, buffer: T) -> Self {
+pub(in crate::driver) enum CancelTarget {
+    UserData(usize),
+    Fd(Fd),
+}
+
+impl Cancel {
+    /// Create [`Cancel`] of the operation with `user_data`.
+    ///
+    /// It completes with `0` when the target is canceled and with `ERROR_NOT_FOUND` when the
+    /// target is not found, e.g. it is already completed.
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: the pending target is canceled with `CancelIoEx` on push.
+    pub fn new(user_data: usize) -> Self {
         Self {
-            fd,
-            buffer,
-            overlapped: Overlapped::new(usize::MAX),
-            _lifetime: PhantomData,
+            target: CancelTarget::UserData(user_data),
         }
     }
-}
-
-impl<'arena, T: IoBuf<'arena>> IntoInner for Write<'arena, T> {
-    type Inner = T;
 
-    fn into_inner(self) -> Self::Inner {
-        self.buffer
+    /// Create [`Cancel`] of all operations on `fd`.
+    ///
+    /// It completes with the number of canceled operations and with `ERROR_NOT_FOUND` when
+    /// nothing is canceled.
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: the pending operations are canceled with `CancelIoEx` on push.
+    pub fn fd(fd: Fd) -> Self {
+        Self {
+            target: CancelTarget::Fd(fd),
+        }
     }
 }
 
-impl<'arena, T: IoBuf<'arena>> OpCode for Write<'arena, T> {
-    unsafe fn operate(&mut self, user_data: usize) -> Poll<io::Result<usize>> {
-        self.overlapped.user_data = user_data;
-        // SAFETY: buffer is Unpin
-        let slice = self.buffer.as_slice();
-        let res = WriteFile(
-            self.fd.as_raw_fd() as _,
-            slice.as_ptr() as _,
-            slice.len() as _,
-            null_mut(),
-            self.overlapped() as *mut _,
-        );
-        win32_pending_result(res)
+impl OpCode for Cancel {
+    unsafe fn operate(&mut self, _user_data: usize) -> Poll<io::Result<usize>> {
+        unreachable!("Cancel is completed by the driver")
     }
 
     fn overlapped(&mut self) -> &mut OVERLAPPED {
-        &mut self.overlapped.base
+        unreachable!("Cancel is completed by the driver")
+    }
+
+    fn as_cancel(&self) -> Option<&Cancel> {
+        Some(self)
     }
 }
 
-/// Write a file at specified position from specified buffer.
-pub struct WriteAt<'arena, T: IoBuf<'arena>> {
-    fd: Fd,
-    offset: usize,
-    buffer: T,
-    overlapped: Overlapped,
-    _lifetime: PhantomData<&'arena ()>,
+/// Post a completion into another driver.
+pub struct MsgRing {
+    port: RawFd,
+    user_data: usize,
+    result: i32,
 }
 
-impl<'arena, T: IoBuf<'arena>> WriteAt<'arena, T> {
-    /// Create [`WriteAt`].
-    pub fn new(fd: Fd, offset: usize, buffer: T) -> Self {
+impl MsgRing {
+    /// Create [`MsgRing`] posting into the driver of `handle`.
+    ///
+    /// The target driver outputs an [`Entry`](crate::driver::Entry) with `user_data` and
+    /// `result`, a negative `result` is the error code. The operation itself completes with `0`.
+    ///
+    /// The handle should outlive the operation.
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: it is synchronized operation, and calls `PostQueuedCompletionStatus` with
+    ///   `user_data` as the completion key.
+    pub fn new(handle: &DriverHandle, user_data: usize, result: i32) -> Self {
         Self {
-            fd,
-            offset,
-            buffer,
-            overlapped: Overlapped::new(usize::MAX),
-            _lifetime: PhantomData,
+            port: handle.as_raw_fd(),
+            user_data,
+            result,
         }
     }
 }
 
-impl<'arena, T: IoBuf<'arena>> IntoInner for WriteAt<'arena, T> {
-    type Inner = T;
+impl OpCode for MsgRing {
+    unsafe fn operate(&mut self, _user_data: usize) -> Poll<io::Result<usize>> {
+        let res = syscall!(
+            BOOL,
+            PostQueuedCompletionStatus(
+                self.port as _,
+                self.result as _,
+                self.user_data,
+                null_mut()
+            )
+        );
+        Poll::Ready(res.map(|_| 0))
+    }
 
-    fn into_inner(self) -> Self::Inner {
-        self.buffer
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        unimplemented!("MsgRing is synchonous")
     }
 }
 
-impl<'arena, T: IoBuf<'arena>> OpCode for WriteAt<'arena, T> {
-    unsafe fn operate(&mut self, user_data: usize) -> Poll<io::Result<usize>> {
-        self.overlapped.user_data = user_data;
-        self.overlapped().Anonymous.Anonymous.Offset = (self.offset & 0xFFFFFFFF) as _;
-        #[cfg(target_pointer_width = "64")]
-        {
-            self.overlapped().Anonymous.Anonymous.OffsetHigh = (self.offset >> 32) as _;
-        }
-        // SAFETY: buffer is Unpin
-        let slice = self.buffer.as_slice();
-        let res = WriteFile(
-            self.fd.as_raw_fd() as _,
-            slice.as_ptr() as _,
-            slice.len() as _,
-            null_mut(),
-            self.overlapped() as *mut _,
-        );
-        win32_pending_result(res)
+/// No operation, it completes with `0`.
+///
+/// It measures the submission and completion overhead of a driver or checks that the driver is
+/// responsive.
+#[derive(Debug, Default)]
+pub struct Nop {}
+
+impl Nop {
+    /// Create [`Nop`].
+    ///
+    /// ## Platform specific
+    ///
+    /// * IOCP: the driver posts a completion packet into its port.
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl OpCode for Nop {
+    unsafe fn operate(&mut self, _user_data: usize) -> Poll<io::Result<usize>> {
+        Poll::Ready(Ok(NOP_PENDING))
     }
 
     fn overlapped(&mut self) -> &mut OVERLAPPED {
-        &mut self.overlapped.base
+        unimplemented!("Nop is completed by the driver")
     }
 }
 
-static CONNECT_EX: OnceLock<LPFN_CONNECTEX> = OnceLock::new();
+static TRANSMIT_FILE: OnceLock<LPFN_TRANSMITFILE> = OnceLock::new();
 
-/// Connect to a remote address.
-pub struct Connect {
-    fd: Fd,
-    addr: SockAddr,
+/// Send a file region to a socket.
+///
+/// The operation should be resubmitted till [`SendFile::on_transfer`] reports completion.
+pub struct SendFile {
+    file_fd: Fd,
+    socket_fd: Fd,
+    offset: u64,
+    len: usize,
+    sent: usize,
     overlapped: Overlapped,
 }
 
-impl Connect {
-    /// Create [`Connect`]. `fd` should be bound.
-    pub fn new(fd: Fd, addr: SockAddr) -> Self {
-        Self {
-            fd,
-            addr,
+impl SendFile {
+    /// Maximum number of bytes `TransmitFile` could send in one call.
+    const MAX_TRANSMIT: usize = i32::MAX as usize - 1;
+
+    /// Create [`SendFile`] to send `len` bytes of the file starting from `offset`.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: splices through an internal pipe owned by the operation.
+    /// * kqueue: `sendfile` syscall.
+    /// * IOCP: `TransmitFile`.
+    pub fn new(file_fd: Fd, socket_fd: Fd, offset: u64, len: usize) -> io::Result<Self> {
+        Ok(Self {
+            file_fd,
+            socket_fd,
+            offset,
+            len,
+            sent: 0,
             overlapped: Overlapped::new(usize::MAX),
-        }
+        })
     }
 
-    /// Post operation socket handling.
+    /// Post operation handling.
     ///
-    /// Set SO_UPDATE_CONNECT_CONTEXT
-    pub fn on_connect(&self, result: io::Result<usize>) -> io::Result<()> {
-        let _ = result?;
-        self.update_context()?;
-        Ok(())
+    /// Returns `true` when `len` bytes were sent or the end of file is reached.
+    pub fn on_transfer(&mut self, result: io::Result<usize>) -> io::Result<bool> {
+        let transferred = result?;
+        self.sent += transferred;
+        Ok(transferred == 0 || self.sent == self.len)
     }
 
-    /// Set SO_UPDATE_CONNECT_CONTEXT
-    pub fn update_context(&self) -> io::Result<()> {
-        syscall!(
-            SOCKET,
-            setsockopt(
-                self.fd.as_raw_fd() as _,
-                SOL_SOCKET,
-                SO_UPDATE_CONNECT_CONTEXT,
-                null(),
-                0,
-            )
-        )?;
-        Ok(())
+    /// Bytes sent to the socket so far.
+    pub fn sent(&self) -> usize {
+        self.sent
     }
 }
 
-impl OpCode for Connect {
+impl OpCode for SendFile {
     unsafe fn operate(&mut self, user_data: usize) -> Poll<io::Result<usize>> {
-        self.overlapped.user_data = user_data;
-        let connect_fn = CONNECT_EX
-            .get_or_try_init(|| get_wsa_fn(self.fd, WSAID_CONNECTEX))?
+        // the operation could be resubmitted
+        self.overlapped = Overlapped::new(user_data);
+        let offset = self.offset + self.sent as u64;
+        self.overlapped().Anonymous.Anonymous.Offset = (offset & 0xFFFFFFFF) as _;
+        self.overlapped().Anonymous.Anonymous.OffsetHigh = (offset >> 32) as _;
+
+        let transmit_fn = TRANSMIT_FILE
+            .get_or_try_init(|| get_wsa_fn(self.socket_fd, WSAID_TRANSMITFILE))?
             .ok_or_else(|| {
-                io::Error::new(io::ErrorKind::Unsupported, "cannot retrieve ConnectEx")
+                io::Error::new(io::ErrorKind::Unsupported, "cannot retrieve TransmitFile")
             })?;
-        let mut sent = 0;
-        let res = connect_fn(
-            self.fd.as_raw_fd() as _,
-            // SAFETY: SockAddr is Unpin - https://docs.rs/socket2/latest/socket2/struct.SockAddr.html#impl-Unpin-for-SockAddr
-            self.addr.as_ptr(),
-            self.addr.len(),
-            null(),
+        let len = (self.len - self.sent).min(Self::MAX_TRANSMIT);
+        let res = transmit_fn(
+            self.socket_fd.as_raw_fd() as _,
+            self.file_fd.as_raw_fd() as _,
+            len as _,
             0,
-            &mut sent,
             &mut self.overlapped.base as *mut _,
+            null(),
+            0,
         );
-        win32_result(res, sent)
+        // the number of transferred bytes is stored in the overlapped structure on completion
+        win32_result(res, self.overlapped.base.InternalHigh as _)
     }
 
     fn overlapped(&mut self) -> &mut OVERLAPPED {
         &mut self.overlapped.base
     }
-}
-
-/// Sync data to the disk.
-pub struct Sync {
-    fd: Fd,
-    #[allow(dead_code)]
-    datasync: bool,
-}
-
-impl Sync {
-    /// Create [`Sync`].
-    ///
-    /// If `datasync` is `true`, the file metadata may not be synchronized.
-    ///
-    /// ## Platform specific
-    ///
-    /// * IOCP: it is synchronized operation, and calls `FlushFileBuffers`.
-    /// * io-uring: `fdatasync` if `datasync` specified, otherwise `fsync`.
-    /// * kqueue: it is synchronized `fdatasync` or `fsync`.
-    pub fn new(fd: Fd, datasync: bool) -> Self {
-        Self { fd, datasync }
-    }
-}
-
-impl OpCode for Sync {
-    unsafe fn operate(&mut self, _user_data: usize) -> Poll<io::Result<usize>> {
-        let res = FlushFileBuffers(self.fd.as_raw_fd() as _);
-        win32_result(res, 0)
-    }
 
-    fn overlapped(&mut self) -> &mut OVERLAPPED {
-        unimplemented!("FlushFileBuffers is synchonous")
+    fn handle(&self) -> Option<RawFd> {
+        Some(self.socket_fd.as_raw_fd())
     }
 }
 
@@ -431,6 +1494,7 @@ pub struct Accept {
     accept_sock_opts: Option<AcceptSocketOpts>,
     addr: SockAddr,
     overlapped: Overlapped,
+    flags: AcceptFlags,
 }
 
 struct AcceptSocketOpts {
@@ -461,9 +1525,18 @@ impl Accept {
                 )
             },
             overlapped: Overlapped::new(usize::MAX),
+            flags: AcceptFlags::empty(),
         }
     }
 
+    /// Set flags of the accepted socket, they are applied in [`Accept::on_accept`].
+    ///
+    /// `CLOEXEC` makes the socket handle not inheritable.
+    pub fn with_flags(mut self, flags: AcceptFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
     /// Init existing [`Accept`] for new accept operation.
     pub fn init_with_socket_opts(
         &mut self,
@@ -495,17 +1568,25 @@ impl Accept {
                 )
             },
             overlapped: Overlapped::new(usize::MAX),
+            flags: AcceptFlags::empty(),
         }
     }
 
     /// Post operation socket handling.
     ///
     /// Set SO_UPDATE_ACCEPT_CONTEXT
+    /// Apply the accept flags.
     /// Get remote address.
     pub fn on_accept(&mut self, result: io::Result<usize>) -> io::Result<(Socket, &SockAddr)> {
         let _ = result?;
         let accept_sock = unsafe { Socket::from_raw_fd(self.accept_fd) };
         self.update_context()?;
+        if self.flags.contains(AcceptFlags::CLOEXEC) {
+            accept_sock.set_no_inherit(true)?;
+        }
+        if self.flags.contains(AcceptFlags::NONBLOCK) {
+            accept_sock.set_nonblocking(true)?;
+        }
         let addr = self.as_sockaddr()?;
         Ok((accept_sock, addr))
     }
@@ -597,6 +1678,10 @@ impl OpCode for Accept {
     fn overlapped(&mut self) -> &mut OVERLAPPED {
         &mut self.overlapped.base
     }
+
+    fn handle(&self) -> Option<RawFd> {
+        Some(self.fd.as_raw_fd())
+    }
 }
 
 /// Receive a single piece of data in a single buffer from remote.
@@ -612,6 +1697,12 @@ impl<'arena, T: IoBufMut<'arena>> Recv<'arena, T> {
             inner: RecvVectoredImpl::new(fd, BufWrapperMut::from(buffer)),
         }
     }
+
+    /// Set `MSG_*` flags like `MSG_PEEK` or `MSG_WAITALL` of the `WSARecv` call.
+    pub fn with_flags(mut self, flags: i32) -> Self {
+        self.inner.flags = flags as _;
+        self
+    }
 }
 
 impl<'arena, T: IoBufMut<'arena>> IntoInner for Recv<'arena, T> {
@@ -630,6 +1721,10 @@ impl<'arena, T: IoBufMut<'arena>> OpCode for Recv<'arena, T> {
     fn overlapped(&mut self) -> &mut OVERLAPPED {
         self.inner.overlapped()
     }
+
+    fn handle(&self) -> Option<RawFd> {
+        self.inner.handle()
+    }
 }
 
 /// Receive a single piece of data into scattered buffers from remote.
@@ -637,6 +1732,7 @@ pub struct RecvVectoredImpl<'arena, T: AsIoSlicesMut<'arena>> {
     fd: Fd,
     buffer: T,
     overlapped: Overlapped,
+    flags: u32,
     _lifetime: PhantomData<&'arena ()>,
 }
 
@@ -648,6 +1744,7 @@ impl<'arena, T: AsIoSlicesMut<'arena>> RecvVectoredImpl<'arena, T> {
             fd,
             buffer,
             overlapped: Overlapped::new(usize::MAX),
+            flags: 0,
             _lifetime: PhantomData,
         }
     }
@@ -667,7 +1764,7 @@ impl<'arena, T: AsIoSlicesMut<'arena>> OpCode for RecvVectoredImpl<'arena, T> {
         // SAFETY: IoSliceMut is Unpin
         let slices = unsafe { self.buffer.as_io_slices_mut() };
         let fd = self.fd.as_raw_fd();
-        let mut flags = 0;
+        let mut flags = self.flags;
         let mut received = 0;
         let res = WSARecv(
             fd as _,
@@ -684,6 +1781,10 @@ impl<'arena, T: AsIoSlicesMut<'arena>> OpCode for RecvVectoredImpl<'arena, T> {
     fn overlapped(&mut self) -> &mut OVERLAPPED {
         &mut self.overlapped.base
     }
+
+    fn handle(&self) -> Option<RawFd> {
+        Some(self.fd.as_raw_fd())
+    }
 }
 
 /// Send a single piece of data from a single buffer to remote.
@@ -699,6 +1800,12 @@ impl<'arena, T: IoBuf<'arena>> Send<'arena, T> {
             inner: SendVectoredImpl::new(fd, BufWrapper::from(buffer)),
         }
     }
+
+    /// Set `MSG_*` flags like `MSG_OOB` of the `WSASend` call.
+    pub fn with_flags(mut self, flags: i32) -> Self {
+        self.inner.flags = flags as _;
+        self
+    }
 }
 
 impl<'arena, T: IoBuf<'arena>> IntoInner for Send<'arena, T> {
@@ -717,13 +1824,26 @@ impl<'arena, T: IoBuf<'arena>> OpCode for Send<'arena, T> {
     fn overlapped(&mut self) -> &mut OVERLAPPED {
         self.inner.overlapped()
     }
+
+    fn handle(&self) -> Option<RawFd> {
+        self.inner.handle()
+    }
 }
 
+/// Send a single piece of data from a single buffer to remote without copying it into the
+/// kernel.
+///
+/// ## Platform specific
+///
+/// * IOCP: there is no zero-copy send, it is the same as [`Send`].
+pub type SendZc<'arena, T> = Send<'arena, T>;
+
 /// Send a single piece of data to remote using scattered buffers.
 pub struct SendVectoredImpl<'arena, T: AsIoSlices<'arena>> {
     fd: Fd,
     buffer: T,
     overlapped: Overlapped,
+    flags: u32,
     _lifetime: PhantomData<&'arena ()>,
 }
 
@@ -734,6 +1854,7 @@ impl<'arena, T: AsIoSlices<'arena>> SendVectoredImpl<'arena, T> {
             fd,
             buffer,
             overlapped: Overlapped::new(usize::MAX),
+            flags: 0,
             _lifetime: PhantomData,
         }
     }
@@ -758,7 +1879,7 @@ impl<'arena, T: AsIoSlices<'arena>> OpCode for SendVectoredImpl<'arena, T> {
             slices.as_ptr() as _,
             slices.len() as _,
             &mut sent,
-            0,
+            self.flags,
             &mut self.overlapped.base as *mut _,
             None,
         );
@@ -768,6 +1889,10 @@ impl<'arena, T: AsIoSlices<'arena>> OpCode for SendVectoredImpl<'arena, T> {
     fn overlapped(&mut self) -> &mut OVERLAPPED {
         &mut self.overlapped.base
     }
+
+    fn handle(&self) -> Option<RawFd> {
+        Some(self.fd.as_raw_fd())
+    }
 }
 
 /// Receive a single piece of data and source address using a single buffer.
@@ -783,6 +1908,12 @@ impl<'arena, T: IoBufMut<'arena>> RecvFrom<'arena, T> {
             inner: RecvMsgImpl::new(fd, BufWrapperMut::from(buffer)),
         }
     }
+
+    /// Set `MSG_*` flags of the `WSARecvFrom` call, see [`RecvMsgImpl::with_flags`].
+    pub fn with_flags(mut self, flags: i32) -> Self {
+        self.inner = self.inner.with_flags(flags);
+        self
+    }
 }
 
 impl<'arena, T: IoBufMut<'arena>> IntoInner for RecvFrom<'arena, T> {
@@ -802,6 +1933,10 @@ impl<'arena, T: IoBufMut<'arena>> OpCode for RecvFrom<'arena, T> {
     fn overlapped(&mut self) -> &mut OVERLAPPED {
         self.inner.overlapped()
     }
+
+    fn handle(&self) -> Option<RawFd> {
+        self.inner.handle()
+    }
 }
 
 /// Receive a single piece of data and source address using scattered buffers.
@@ -811,6 +1946,7 @@ pub struct RecvMsgImpl<'arena, T: AsIoSlicesMut<'arena>> {
     addr: SOCKADDR_STORAGE,
     addr_len: socklen_t,
     overlapped: Overlapped,
+    flags: u32,
     _lifetime: PhantomData<&'arena ()>,
 }
 
@@ -823,9 +1959,16 @@ impl<'arena, T: AsIoSlicesMut<'arena>> RecvMsgImpl<'arena, T> {
             addr: unsafe { std::mem::zeroed() },
             addr_len: std::mem::size_of::<SOCKADDR_STORAGE>() as _,
             overlapped: Overlapped::new(usize::MAX),
+            flags: 0,
             _lifetime: PhantomData,
         }
     }
+
+    /// Set `MSG_*` flags like `MSG_PEEK` of the `WSARecvFrom` call.
+    pub fn with_flags(mut self, flags: i32) -> Self {
+        self.flags = flags as _;
+        self
+    }
 }
 
 impl<'arena, T: AsIoSlicesMut<'arena>> IntoInner for RecvMsgImpl<'arena, T> {
@@ -844,7 +1987,7 @@ impl<'arena, T: AsIoSlicesMut<'arena>> OpCode for RecvMsgImpl<'arena, T> {
         let fd = self.fd.as_raw_fd();
         // SAFETY: buffer is Unpin, IoSliceMut is Unpin as well
         let slices = unsafe { self.buffer.as_io_slices_mut() };
-        let mut flags = 0;
+        let mut flags = self.flags;
         let mut received = 0;
         let res = WSARecvFrom(
             fd as _,
@@ -863,6 +2006,10 @@ impl<'arena, T: AsIoSlicesMut<'arena>> OpCode for RecvMsgImpl<'arena, T> {
     fn overlapped(&mut self) -> &mut OVERLAPPED {
         &mut self.overlapped.base
     }
+
+    fn handle(&self) -> Option<RawFd> {
+        Some(self.fd.as_raw_fd())
+    }
 }
 
 /// Send a single piece of data from a single buffer to the specified address.
@@ -878,6 +2025,12 @@ impl<'arena, T: IoBuf<'arena>> SendTo<'arena, T> {
             inner: SendMsgImpl::new(fd, BufWrapper::from(buffer), addr),
         }
     }
+
+    /// Set `MSG_*` flags of the `WSASendTo` call, see [`SendMsgImpl::with_flags`].
+    pub fn with_flags(mut self, flags: i32) -> Self {
+        self.inner = self.inner.with_flags(flags);
+        self
+    }
 }
 
 impl<'arena, T: IoBuf<'arena>> IntoInner for SendTo<'arena, T> {
@@ -896,6 +2049,10 @@ impl<'arena, T: IoBuf<'arena>> OpCode for SendTo<'arena, T> {
     fn overlapped(&mut self) -> &mut OVERLAPPED {
         self.inner.overlapped()
     }
+
+    fn handle(&self) -> Option<RawFd> {
+        self.inner.handle()
+    }
 }
 
 /// Send a single piece of data from scattered buffers to the specified address.
@@ -904,6 +2061,7 @@ pub struct SendMsgImpl<'arena, T: AsIoSlices<'arena>> {
     buffer: T,
     addr: SockAddr,
     overlapped: Overlapped,
+    flags: u32,
     _lifetime: PhantomData<&'arena ()>,
 }
 
@@ -915,9 +2073,16 @@ impl<'arena, T: AsIoSlices<'arena>> SendMsgImpl<'arena, T> {
             buffer,
             addr,
             overlapped: Overlapped::new(usize::MAX),
+            flags: 0,
             _lifetime: PhantomData,
         }
     }
+
+    /// Set `MSG_*` flags like `MSG_OOB` of the `WSASendTo` call.
+    pub fn with_flags(mut self, flags: i32) -> Self {
+        self.flags = flags as _;
+        self
+    }
 }
 
 impl<'arena, T: AsIoSlices<'arena>> IntoInner for SendMsgImpl<'arena, T> {
@@ -939,7 +2104,7 @@ impl<'arena, T: AsIoSlices<'arena>> OpCode for SendMsgImpl<'arena, T> {
             slices.as_ptr() as _,
             slices.len() as _,
             &mut sent,
-            0,
+            self.flags,
             self.addr.as_ptr(),
             self.addr.len(),
             &mut self.overlapped.base as *mut _,
@@ -951,6 +2116,10 @@ impl<'arena, T: AsIoSlices<'arena>> OpCode for SendMsgImpl<'arena, T> {
     fn overlapped(&mut self) -> &mut OVERLAPPED {
         &mut self.overlapped.base
     }
+
+    fn handle(&self) -> Option<RawFd> {
+        Some(self.fd.as_raw_fd())
+    }
 }
 
 /// Connect a named pipe server.
@@ -982,6 +2151,10 @@ impl OpCode for ConnectNamedPipe {
     fn overlapped(&mut self) -> &mut OVERLAPPED {
         &mut self.overlapped.base
     }
+
+    fn handle(&self) -> Option<RawFd> {
+        Some(self.fd.as_raw_fd())
+    }
 }
 
 #[cfg(feature = "time")]
@@ -995,9 +2168,43 @@ impl OpCode for Timeout {
     }
 
     #[cfg(feature = "time")]
+    fn timer_delay(&self) -> std::time::Duration {
+        self.delay()
+    }
+}
+
+#[cfg(feature = "time")]
+impl OpCode for TimeoutUpdate {
+    unsafe fn operate(&mut self, _user_data: usize) -> Poll<io::Result<usize>> {
+        Poll::Ready(Ok(TIMER_UPDATE_PENDING))
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        unimplemented!("not used for timers")
+    }
+
     fn timer_delay(&self) -> std::time::Duration {
         self.delay
     }
+
+    fn timer_target(&self) -> usize {
+        self.user_data
+    }
+}
+
+#[cfg(feature = "time")]
+impl OpCode for TimeoutRemove {
+    unsafe fn operate(&mut self, _user_data: usize) -> Poll<io::Result<usize>> {
+        Poll::Ready(Ok(TIMER_REMOVE_PENDING))
+    }
+
+    fn overlapped(&mut self) -> &mut OVERLAPPED {
+        unimplemented!("not used for timers")
+    }
+
+    fn timer_target(&self) -> usize {
+        self.user_data
+    }
 }
 
 fn get_sockopt_error(fd: RawFd) -> Result<(), i32> {
@@ -0,0 +1,211 @@
+use std::{
+    cell::Cell,
+    io,
+    ops::Deref,
+    ptr::NonNull,
+    slice,
+    sync::atomic::{AtomicU16, Ordering},
+};
+
+use io_uring::types::BufRingEntry;
+
+use crate::buf::IoBuf;
+
+/// A ring of provided buffers.
+///
+/// The kernel picks a buffer from the ring when an operation with buffer selection, like
+/// [`RecvMultishot`](crate::op::RecvMultishot), completes. The id of the picked buffer is
+/// returned by [`Entry::buffer_id`](crate::driver::Entry::buffer_id).
+///
+/// The ring should be registered with
+/// [`Driver::register_buf_ring`](super::Driver::register_buf_ring) before use.
+pub struct BufRing {
+    ring: NonNull<BufRingEntry>,
+    buffers: NonNull<u8>,
+    entries: u16,
+    buf_len: usize,
+    group_id: u16,
+    // local copy of the tail shared with the kernel
+    tail: Cell<u16>,
+}
+
+impl BufRing {
+    /// Allocate a ring of `entries` buffers of `buf_len` bytes for the buffer group `group_id`.
+    ///
+    /// All buffers are provided to the kernel initially.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::ErrorKind::InvalidInput`] if `entries` is not a power of two or is greater
+    /// than 32768.
+    pub fn new(group_id: u16, entries: u16, buf_len: usize) -> io::Result<Self> {
+        if !entries.is_power_of_two() || entries > 1 << 15 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "ring entries should be a power of two not greater than 32768",
+            ));
+        }
+        let ring_size = usize::from(entries) * std::mem::size_of::<BufRingEntry>();
+        // the kernel requires the ring to be page aligned
+        let ring = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                ring_size,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_ANONYMOUS | libc::MAP_PRIVATE,
+                -1,
+                0,
+            )
+        };
+        if ring == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        let buffers = vec![0u8; usize::from(entries) * buf_len].into_boxed_slice();
+        let buffers = Box::into_raw(buffers) as *mut u8;
+
+        let ring = Self {
+            ring: NonNull::new(ring as *mut BufRingEntry).expect("mapped"),
+            buffers: NonNull::new(buffers).expect("allocated"),
+            entries,
+            buf_len,
+            group_id,
+            tail: Cell::new(0),
+        };
+        for buffer_id in 0..entries {
+            ring.push(buffer_id);
+        }
+        ring.publish();
+        Ok(ring)
+    }
+
+    /// The buffer group id.
+    pub fn group_id(&self) -> u16 {
+        self.group_id
+    }
+
+    /// The number of buffers in the ring.
+    pub fn entries(&self) -> u16 {
+        self.entries
+    }
+
+    /// The length of each buffer.
+    pub fn buf_len(&self) -> usize {
+        self.buf_len
+    }
+
+    pub(super) fn as_ring_addr(&self) -> u64 {
+        self.ring.as_ptr() as u64
+    }
+
+    /// Take the buffer filled with `len` bytes by the kernel.
+    ///
+    /// The buffer is given back to the kernel when the returned guard is dropped.
+    ///
+    /// # Safety
+    ///
+    /// `buffer_id` should be returned by a completion entry of an operation using this ring and
+    /// the buffer should be taken once per completion entry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer_id` or `len` is out of range.
+    pub unsafe fn take(&self, buffer_id: u16, len: usize) -> ProvidedBuf<'_> {
+        assert!(buffer_id < self.entries, "buffer id is out of range");
+        assert!(len <= self.buf_len, "buffer length is out of range");
+        ProvidedBuf {
+            ring: self,
+            buffer_id,
+            len,
+        }
+    }
+
+    fn buffer_ptr(&self, buffer_id: u16) -> *mut u8 {
+        // SAFETY: the buffer id is within the allocated buffers
+        unsafe {
+            self.buffers
+                .as_ptr()
+                .add(usize::from(buffer_id) * self.buf_len)
+        }
+    }
+
+    // Write the buffer entry after the tail, it becomes visible to the kernel after `publish`.
+    fn push(&self, buffer_id: u16) {
+        let tail = self.tail.get();
+        let index = usize::from(tail & (self.entries - 1));
+        // SAFETY: the index is masked by the ring size
+        let entry = unsafe { &mut *self.ring.as_ptr().add(index) };
+        entry.set_addr(self.buffer_ptr(buffer_id) as u64);
+        entry.set_len(self.buf_len as u32);
+        entry.set_bid(buffer_id);
+        self.tail.set(tail.wrapping_add(1));
+    }
+
+    fn publish(&self) {
+        // SAFETY: the tail overlaps the first ring entry which lives as long as the ring
+        let tail = unsafe { &*(BufRingEntry::tail(self.ring.as_ptr()) as *const AtomicU16) };
+        tail.store(self.tail.get(), Ordering::Release);
+    }
+
+    fn recycle(&self, buffer_id: u16) {
+        self.push(buffer_id);
+        self.publish();
+    }
+}
+
+impl Drop for BufRing {
+    fn drop(&mut self) {
+        let ring_size = usize::from(self.entries) * std::mem::size_of::<BufRingEntry>();
+        unsafe {
+            libc::munmap(self.ring.as_ptr() as _, ring_size);
+            drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+                self.buffers.as_ptr(),
+                usize::from(self.entries) * self.buf_len,
+            )));
+        }
+    }
+}
+
+/// A buffer taken from [`BufRing`].
+///
+/// It is given back to the kernel on drop.
+pub struct ProvidedBuf<'a> {
+    ring: &'a BufRing,
+    buffer_id: u16,
+    len: usize,
+}
+
+impl ProvidedBuf<'_> {
+    /// The buffer id.
+    pub fn buffer_id(&self) -> u16 {
+        self.buffer_id
+    }
+}
+
+impl Deref for ProvidedBuf<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: the kernel doesn't write into the taken buffer
+        unsafe { slice::from_raw_parts(self.ring.buffer_ptr(self.buffer_id), self.len) }
+    }
+}
+
+unsafe impl<'a> IoBuf<'a> for ProvidedBuf<'a> {
+    fn as_buf_ptr(&self) -> *const u8 {
+        self.ring.buffer_ptr(self.buffer_id)
+    }
+
+    fn buf_len(&self) -> usize {
+        self.len
+    }
+
+    fn buf_capacity(&self) -> usize {
+        self.ring.buf_len
+    }
+}
+
+impl Drop for ProvidedBuf<'_> {
+    fn drop(&mut self) {
+        self.ring.recycle(self.buffer_id);
+    }
+}
@@ -2,22 +2,41 @@
 use std::alloc::Allocator;
 #[doc(no_inline)]
 pub use std::os::fd::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
-use std::{fmt, io, marker::PhantomData, time::Duration};
+use std::{
+    collections::{hash_map, HashMap, VecDeque},
+    fmt,
+    io::{self, IoSliceMut},
+    marker::PhantomData,
+    ops::Range,
+    os::fd::{BorrowedFd, OwnedFd},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Weak,
+    },
+    time::{Duration, Instant},
+};
 
 use io_uring::{
     cqueue,
     opcode::{self, AsyncCancel, FilesUpdate},
     register::SKIP_FILE,
     squeue,
-    types::{SubmitArgs, Timespec},
+    types::{self, CancelBuilder, Timespec},
     IoUring, Probe,
 };
 
 use crate::{
-    driver::{unix::IntoFdOrFixed, CompleteIo, Entry, OpObject, Operation},
-    vec_deque_alloc,
+    driver::{
+        capabilities::kernel_version, epoll::requires_io_uring, op_name, unix::IntoFdOrFixed,
+        CancelError, CompleteIo, CompletionIter, CountingExtend, DriverBuilder, DriverCapabilities,
+        DriverStats, Entry, EpollFiles, FixedBufRegistry, Hooks, OpObject, Operation, PushError,
+        Readiness, UserDataSet,
+    },
+    syscall, vec_deque_alloc,
 };
 
+mod buf_ring;
+pub use buf_ring::*;
 pub(crate) mod op;
 
 /// Attached file descriptor.
@@ -31,7 +50,7 @@ pub struct Fd {
 
 impl Fd {
     #[inline]
-    const fn from_raw(raw_fd: RawFd) -> Self {
+    pub(in crate::driver) const fn from_raw(raw_fd: RawFd) -> Self {
         Self {
             raw_fd,
             _not_send_not_sync: PhantomData,
@@ -39,7 +58,7 @@ impl Fd {
     }
 
     #[inline]
-    fn as_raw_fd(&self) -> RawFd {
+    pub(in crate::driver) fn as_raw_fd(&self) -> RawFd {
         self.raw_fd
     }
 }
@@ -51,23 +70,29 @@ impl fmt::Debug for Fd {
 }
 
 /// Fixed fd is offset in registered files array
+///
+/// The fixed fd got from a [`FixedFdSlot`] is stale after [`FilesUpdate`](crate::op::FilesUpdate)
+/// of the slot completes, the driver fails the operations using it with
+/// [`io::ErrorKind::InvalidInput`].
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct FixedFd {
     offset: u32,
+    pub(in crate::driver) generation: u32,
     _not_send_not_sync: PhantomData<*const ()>,
 }
 
 impl FixedFd {
     #[inline]
-    const fn from_offset(offset: u32) -> Self {
+    pub(in crate::driver) const fn new(offset: u32, generation: u32) -> Self {
         Self {
             offset,
+            generation,
             _not_send_not_sync: PhantomData,
         }
     }
 
     #[inline]
-    fn as_offset(&self) -> u32 {
+    pub(in crate::driver) fn as_offset(&self) -> u32 {
         self.offset
     }
 }
@@ -87,6 +112,16 @@ pub enum FdOrFixed {
     Fixed(FixedFd),
 }
 
+impl FdOrFixed {
+    #[inline]
+    pub(in crate::driver) fn fixed(self) -> Option<FixedFd> {
+        match self {
+            Self::Fd(_) => None,
+            Self::Fixed(fixed_fd) => Some(fixed_fd),
+        }
+    }
+}
+
 impl IntoFdOrFixed for Fd {
     type Target = FdOrFixed;
 
@@ -105,28 +140,285 @@ impl IntoFdOrFixed for FixedFd {
     }
 }
 
+/// A contiguous range of registered files allocated by
+/// [`CompleteIo::register_files`](crate::driver::CompleteIo::register_files).
+#[derive(Debug, PartialEq, Eq)]
+pub struct FixedFdSlot {
+    pub(in crate::driver) offset: u32,
+    pub(in crate::driver) len: u32,
+    // bumped by every FilesUpdate of the slot
+    pub(in crate::driver) generation: u32,
+}
+
+impl FixedFdSlot {
+    /// The number of registered files in the slot.
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// Returns `true` if the slot has no registered files.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get fixed fd of the registered file with `index` in the slot.
+    pub fn get(&self, index: usize) -> Option<FixedFd> {
+        let index = u32::try_from(index)
+            .ok()
+            .filter(|index| *index < self.len)?;
+        Some(FixedFd::new(self.offset + index, self.generation))
+    }
+}
+
 /// Invalid file descriptor value could be used as an initial value of uninitialized file descriptor
 pub const INVALID_FD: Fd = Fd::from_raw(-1);
 /// Invalid fixed file descriptor value could be used as an initial value of uninitialized fixed
 /// file descriptor
-pub const INVALID_FIXED_FD: FixedFd = FixedFd::from_offset(u32::MAX);
+pub const INVALID_FIXED_FD: FixedFd = FixedFd::new(u32::MAX, 0);
 
 /// Abstraction of io-uring operations.
 pub trait OpCode {
     /// Create submission entry.
     fn create_entry(&mut self) -> squeue::Entry;
+
+    /// Perform the operation synchronously when io-uring has no opcode for it or the operation
+    /// fails before submission.
+    ///
+    /// The driver calls it before creating submission entry. If the result is returned the
+    /// operation is completed without submission and [`OpCode::create_entry`] is not called.
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        None
+    }
+
+    /// Whether the kernel posts a notification CQE with `IORING_CQE_F_NOTIF` flag when it
+    /// releases the operation buffers.
+    ///
+    /// The driver calls it once per push before [`OpCode::create_entry`], the operation chooses
+    /// the opcode by the `capabilities` of the driver. The driver reports the completion with
+    /// the result of the first CQE only after the notification arrives, so the buffers could be
+    /// reused.
+    fn request_notification(&mut self, _capabilities: &DriverCapabilities) -> bool {
+        false
+    }
+
+    /// The registered files used by the operation.
+    ///
+    /// The driver fails the operation with [`io::ErrorKind::InvalidInput`] if a file is stale,
+    /// see [`FixedFd`].
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [None; 2]
+    }
+
+    /// The slot of the registered files replaced by the operation, its fixed fds got before are
+    /// stale when the operation completes.
+    fn files_update(&self) -> Option<&FixedFdSlot> {
+        None
+    }
+
+    /// The name of the operation reported to [`DriverHooks`](crate::driver::DriverHooks), it
+    /// defaults to the type name without the module path.
+    fn name(&self) -> &'static str {
+        op_name::<Self>()
+    }
+
+    /// Perform the operation when the driver falls back to epoll, `None` is returned if it would
+    /// block till the file of [`OpCode::epoll_readiness`] is ready.
+    ///
+    /// The operations which are not emulated fail with [`io::ErrorKind::Unsupported`].
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        Some(Err(requires_io_uring(self.name())))
+    }
+
+    /// The readiness the operation waits for when [`OpCode::epoll_operate`] would block.
+    fn epoll_readiness(&self, _files: &EpollFiles) -> Readiness {
+        unreachable!("operation completes in one shot")
+    }
+
+    /// Only [`Cancel`](crate::op::Cancel) implements this method, epoll driver completes it
+    /// instead of operating.
+    fn as_cancel(&self) -> Option<&crate::op::Cancel> {
+        None
+    }
+
+    /// Only timers and timer updates implement this method, epoll driver runs the timers in
+    /// user space.
+    #[cfg(feature = "time")]
+    fn timer_delay(&self) -> Duration {
+        unimplemented!("operation is not a timer")
+    }
+
+    /// Only timer updates and removals implement this method, returns user_data of the timer
+    #[cfg(feature = "time")]
+    fn timer_target(&self) -> usize {
+        unimplemented!("operation doesn't target a timer")
+    }
+}
+
+/// A handle of [`Driver`](crate::driver::Driver) to post completions into it with
+/// [`MsgRing`](crate::op::MsgRing).
+///
+/// The handle owns a duplicate of the ring fd and could be sent to other threads.
+#[derive(Debug)]
+pub struct DriverHandle {
+    fd: OwnedFd,
+}
+
+impl DriverHandle {
+    pub(in crate::driver) fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+/// A handle to wake up [`Driver`](crate::driver::Driver) waiting for completions from other
+/// threads.
+///
+/// The driver polls an eventfd, the interrupted wait returns without entries.
+#[derive(Debug, Clone)]
+pub struct DriverWaker {
+    pub(in crate::driver) fd: Arc<OwnedFd>,
+}
+
+impl DriverWaker {
+    /// Wake up the driver.
+    ///
+    /// The wakeups before the driver waits are coalesced into one.
+    pub fn wake(&self) -> io::Result<()> {
+        let data = 1u64;
+        syscall!(write(
+            self.fd.as_raw_fd(),
+            &data as *const _ as *const _,
+            std::mem::size_of::<u64>(),
+        ))?;
+        Ok(())
+    }
+}
+
+/// A token of [`Driver`](crate::driver::Driver) to share its kernel worker pool with the drivers
+/// built with [`DriverBuilder::attach_wq`].
+///
+/// The token doesn't keep the driver alive, the build fails if the driver is dropped.
+#[derive(Debug, Clone)]
+pub struct WorkQueueToken {
+    fd: Weak<OwnedFd>,
 }
 
-/// Low-level driver of io-uring.
+// Low-level driver of io-uring
 pub struct Driver<'arena> {
     inner: IoUring,
     squeue_buffer: Vec<squeue::Entry>,
+    // operations completed synchronously before submission
+    sync_completed: VecDeque<Entry>,
+    // operations waiting for the buffer release notification with the result of the first CQE
+    pending_notifications: HashMap<u64, Option<i32>>,
     files_update_fds: Vec<RawFd>,
     // in progress FilesUpdate state
     files_update_state: FilesUpdateState,
+    // registered file slots which are in use or wait for the async removal
+    files_allocated: Vec<bool>,
+    // registered file slots removed by the in progress update, they are freed when it completes
+    files_unregistering: Vec<bool>,
+    // the generation of the fixed fds accepted for every registered file slot
+    files_generation: Vec<u32>,
+    // FilesUpdate operations in flight and the slots with the generations they set
+    files_updates: HashMap<u64, (Range<u32>, u32)>,
+    // removals waiting for the in progress FilesUpdate to complete
+    deferred_files_removal: Vec<u32>,
+    // operations with linked timeouts and the CQEs seen so far
+    ops_with_timeout: HashMap<u64, LinkTimeout>,
+    // timespecs of linked timeouts are read by the kernel on submission, boxed to keep them in
+    // place while the vector grows
+    #[allow(clippy::vec_box)]
+    link_timespecs: Vec<Box<Timespec>>,
+    capabilities: DriverCapabilities,
+    // eventfd of the wakers and whether the multishot poll of it is pushed
+    waker: Option<Arc<OwnedFd>>,
+    waker_armed: bool,
+    // duplicate of the ring fd referenced by the work queue tokens
+    wq_fd: Option<Arc<OwnedFd>>,
+    // user_data of the operations submitted to the kernel and not completed yet with the number
+    // of them, it's tracked for shutdown whether the duplicate check is enabled or not
+    in_flight: HashMap<usize, usize>,
+    // completion entries expected from the pushed requests including the internal ones, an
+    // operation with a linked timeout or a notification posts two
+    cq_pending: usize,
+    // the operations are canceled one by one on shutdown, the kernel rejected the cancel
+    // request of any operation or it's disabled
+    cancel_one_by_one: bool,
+    cancel_any: bool,
+    // the shutdown cancel requests of single operations which are not completed yet
+    shutdown_cancels: usize,
+    // the kernel accepts the absolute deadlines
+    abs_timer: bool,
+    // the ring fd registered by the thread created the driver
+    registered_ring: Option<RegisteredRing>,
+    stats: DriverStats,
+    user_data: UserDataSet,
+    hooks: Hooks,
+    // the kernel counter of dropped completion entries when the stats are reset
+    cq_overflow_base: u32,
+    cq_overflow_observed: bool,
     _lifetime: PhantomData<&'arena ()>,
 }
 
+fn capabilities_from_probe(probe: &Probe) -> DriverCapabilities {
+    DriverCapabilities {
+        // multishot accept is available since Linux 5.19 as well as IORING_OP_SOCKET
+        accept_multishot: probe.is_supported(opcode::Socket::CODE),
+        send_zc: probe.is_supported(opcode::SendZc::CODE),
+        open_at: probe.is_supported(opcode::OpenAt::CODE),
+        fixed_buffers: probe.is_supported(opcode::ReadFixed::CODE),
+        kernel_version: kernel_version(),
+        ..DriverCapabilities::default()
+    }
+}
+
+// the driver can't be created on the running kernel
+fn unavailable(e: io::Error) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("io-uring is unavailable: {e}"),
+    )
+}
+
+pub(in crate::driver) fn probe_capabilities() -> io::Result<DriverCapabilities> {
+    let ring = IoUring::new(2).map_err(|e| match e.raw_os_error() {
+        Some(libc::ENOSYS) | Some(libc::EPERM) => unavailable(e),
+        _ => e,
+    })?;
+    let mut probe = Probe::new();
+    ring.submitter()
+        .register_probe(&mut probe)
+        .map_err(|e| match e.raw_os_error() {
+            Some(libc::EINVAL) => unavailable(e),
+            _ => e,
+        })?;
+    let registered_ring = RegisteredRing::register(&ring).map(|ring_fd| ring_fd.unregister(&ring));
+    Ok(DriverCapabilities {
+        registered_ring: registered_ring.is_some(),
+        ..capabilities_from_probe(&probe)
+    })
+}
+
+// the time the kernel waits for completions
+#[derive(Debug, Clone, Copy)]
+enum Wait {
+    // `None` waits without timeout
+    Timeout(Option<Duration>),
+    Deadline(Instant),
+}
+
+// the operation and its linked timeout complete with separate CQEs in any order
+#[derive(Debug)]
+enum LinkTimeout {
+    Armed,
+    // the timeout fired and canceled the operation
+    Expired,
+    // the timeout was canceled, the operation completed or failed before it fired
+    Disarmed,
+    // the operation is canceled, the timeout CQE tells the reason
+    Canceled(Entry),
+}
+
 #[derive(Debug, Clone, Copy)]
 enum FilesUpdateState {
     NoUpdateInProgress,
@@ -137,20 +429,116 @@ enum FilesUpdateState {
 }
 
 impl<'arena> Driver<'arena> {
+    const CANCEL_ALL_KEY: u64 = u64::MAX - 2;
+    const CANCEL_KEY: u64 = u64::MAX - 1;
     const FILES_UPDATE_KEY: u64 = u64::MAX;
+    // the linked timeout of the operation uses its user_data with the highest bit set
+    const LINK_TIMEOUT_FLAG: u64 = 1 << 63;
+    const SHUTDOWN_CANCEL_KEY: u64 = u64::MAX - 4;
+    const WAKER_KEY: u64 = u64::MAX - 3;
 
-    /// Create a new io-uring driver with 1024 entries and without registered files.
-    pub fn new() -> io::Result<Self> {
-        Self::with(1024, 0)
-    }
-
-    /// Create a new io-uring driver with specified entries and files to register.
-    pub fn with(entries: u32, files_to_register: u32) -> io::Result<Self> {
-        let inner = IoUring::new(entries)?;
+    pub(in crate::driver) fn from_builder(builder: &DriverBuilder) -> io::Result<Self> {
+        let entries = builder.sq_entries;
+        let files_to_register = builder.files_to_register;
+        // keep the ring of the shared work queue open during the setup
+        let attach_wq = match &builder.attach_wq {
+            Some(token) => Some(token.fd.upgrade().ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "the driver of the shared work queue is dropped",
+                )
+            })?),
+            None => None,
+        };
+        let mut coop_taskrun = builder.coop_taskrun;
+        let mut defer_taskrun = builder.defer_taskrun;
+        let mut single_issuer = builder.single_issuer || defer_taskrun;
+        let inner = loop {
+            let mut uring_builder = IoUring::builder();
+            if let Some(cq_entries) = builder.cq_entries {
+                uring_builder.setup_cqsize(cq_entries);
+            }
+            if let Some(idle) = builder.sqpoll_idle {
+                uring_builder.setup_sqpoll(idle.as_millis().try_into().unwrap_or(u32::MAX));
+                if let Some(cpu) = builder.sqpoll_cpu {
+                    uring_builder.setup_sqpoll_cpu(cpu);
+                }
+            }
+            if let Some(fd) = &attach_wq {
+                uring_builder.setup_attach_wq(fd.as_raw_fd());
+            }
+            if coop_taskrun {
+                uring_builder.setup_coop_taskrun();
+            }
+            if defer_taskrun {
+                uring_builder.setup_defer_taskrun();
+            }
+            if single_issuer {
+                uring_builder.setup_single_issuer();
+            }
+            match uring_builder.build(entries) {
+                Ok(inner) => break inner,
+                // the kernel rejects the unknown setup flags, drop the newest one and retry
+                Err(e) if e.raw_os_error() == Some(libc::EINVAL) && defer_taskrun => {
+                    defer_taskrun = false
+                }
+                Err(e) if e.raw_os_error() == Some(libc::EINVAL) && single_issuer => {
+                    single_issuer = false
+                }
+                Err(e) if e.raw_os_error() == Some(libc::EINVAL) && coop_taskrun => {
+                    coop_taskrun = false
+                }
+                // no io-uring before Linux 5.1 or it's disabled, seccomp filters of containers
+                // commonly deny it
+                Err(e)
+                    if e.raw_os_error() == Some(libc::ENOSYS)
+                        || (e.raw_os_error() == Some(libc::EPERM)
+                            && builder.sqpoll_idle.is_none()) =>
+                {
+                    return Err(unavailable(e))
+                }
+                Err(e) => return Err(e),
+            }
+        };
+        let submitter = inner.submitter();
+        let mut probe = Probe::new();
+        // the probe is available since Linux 5.6
+        submitter
+            .register_probe(&mut probe)
+            .map_err(|e| match e.raw_os_error() {
+                Some(libc::EINVAL) => unavailable(e),
+                _ => e,
+            })?;
+        let registered_ring = if builder.register_ring_fd {
+            RegisteredRing::register(&inner)
+        } else {
+            None
+        };
+        let mut capabilities = DriverCapabilities {
+            coop_taskrun,
+            defer_taskrun,
+            single_issuer,
+            registered_ring: registered_ring.is_some(),
+            ..capabilities_from_probe(&probe)
+        };
+        if let Some(busy_poll) = builder.napi_busy_poll {
+            match register_napi(&inner, busy_poll, builder.napi_prefer_busy_poll) {
+                Ok(()) => {
+                    capabilities.napi_busy_poll = Some(busy_poll);
+                    capabilities.napi_prefer_busy_poll = builder.napi_prefer_busy_poll;
+                }
+                // the driver works without busy polling
+                Err(e) if e.kind() == io::ErrorKind::Unsupported => {}
+                Err(e) => return Err(e),
+            }
+        }
+        op::ACCEPT_MULTISHOT_SUPPORTED.store(capabilities.accept_multishot, Ordering::Relaxed);
+        op::SOCKET_SUPPORTED.store(probe.is_supported(opcode::Socket::CODE), Ordering::Relaxed);
+        op::FUTEX_SUPPORTED.store(
+            probe.is_supported(opcode::FutexWait::CODE),
+            Ordering::Relaxed,
+        );
         let files_update_fds = if files_to_register > 0 {
-            let submitter = inner.submitter();
-            let mut probe = Probe::new();
-            submitter.register_probe(&mut probe)?;
             if probe.is_supported(opcode::Socket::CODE) {
                 // register_files_sparse available since Linux 5.19
                 submitter.register_files_sparse(files_to_register)?;
@@ -170,22 +558,404 @@ impl<'arena> Driver<'arena> {
         Ok(Self {
             inner,
             squeue_buffer: Vec::with_capacity(entries as usize),
+            sync_completed: VecDeque::new(),
+            pending_notifications: HashMap::new(),
             files_update_fds,
             files_update_state: FilesUpdateState::NoUpdateInProgress,
+            files_allocated: vec![false; files_to_register as usize],
+            files_unregistering: vec![false; files_to_register as usize],
+            files_generation: vec![0; files_to_register as usize],
+            files_updates: HashMap::new(),
+            deferred_files_removal: Vec::new(),
+            ops_with_timeout: HashMap::new(),
+            link_timespecs: Vec::new(),
+            capabilities,
+            waker: None,
+            waker_armed: false,
+            wq_fd: None,
+            in_flight: HashMap::new(),
+            cq_pending: 0,
+            cancel_one_by_one: false,
+            cancel_any: builder.cancel_any,
+            shutdown_cancels: 0,
+            abs_timer: true,
+            registered_ring,
+            stats: DriverStats::default(),
+            user_data: UserDataSet::new(builder.check_user_data),
+            hooks: builder.hooks.clone(),
+            cq_overflow_base: 0,
+            cq_overflow_observed: false,
             _lifetime: PhantomData,
         })
     }
 
+    /// Operations natively supported by the kernel, probed when the driver is created.
+    pub fn capabilities(&self) -> DriverCapabilities {
+        self.capabilities
+    }
+
+    /// Counters of the driver operations.
+    pub fn stats(&self) -> DriverStats {
+        DriverStats {
+            cq_overflows: self
+                .cq_overflow()
+                .wrapping_sub(self.cq_overflow_base)
+                .into(),
+            ..self.stats
+        }
+    }
+
+    /// Reset the counters of the driver operations.
+    pub fn reset_stats(&mut self) {
+        self.stats = DriverStats::default();
+        self.cq_overflow_base = self.cq_overflow();
+        self.cq_overflow_observed = false;
+    }
+
+    /// The number of entries which could be pushed to the submission queue.
+    pub fn sq_space_left(&self) -> usize {
+        // SAFETY: the submission queue is not borrowed by &self
+        let squeue = unsafe { self.inner.submission_shared() };
+        squeue.capacity() - squeue.len()
+    }
+
+    // the completion entries which could be posted without overflowing the completion queue
+    fn cq_space_left(&self) -> usize {
+        // SAFETY: the queues are not borrowed by &self
+        let squeue = unsafe { self.inner.submission_shared() };
+        // the kept completions are flushed before new operations are accepted
+        if squeue.cq_overflow() {
+            return 0;
+        }
+        let cq_capacity = unsafe { self.inner.completion_shared() }.capacity();
+        // FilesUpdate and the waker poll are pushed without the capacity check, the room is kept
+        // for them until they are pushed and counted
+        let reserved = usize::from(matches!(
+            self.files_update_state,
+            FilesUpdateState::NoUpdateInProgress
+        )) + usize::from(!self.waker_armed);
+        cq_capacity.saturating_sub(reserved + self.cq_pending)
+    }
+
+    /// The number of entries ready in the completion queue.
+    pub fn cq_ready(&self) -> usize {
+        // SAFETY: the completion queue is not borrowed by &self
+        unsafe { self.inner.completion_shared() }.len()
+    }
+
+    /// Whether the kernel reported the completion queue overflow with `IORING_SQ_CQ_OVERFLOW`
+    /// flag since the driver is created or the counters are reset.
+    pub fn cq_overflow_observed(&self) -> bool {
+        self.cq_overflow_observed
+    }
+
+    fn cq_overflow(&self) -> u32 {
+        // SAFETY: the completion queue is not borrowed by &self
+        unsafe { self.inner.completion_shared() }.overflow()
+    }
+
+    /// Cancel the operations in flight and wait until the kernel completes them.
+    ///
+    /// The entries of the canceled operations are discarded. The driver waits without `timeout`
+    /// till all operations complete, [`io::ErrorKind::TimedOut`] is returned if the timeout
+    /// passes first. The driver is shut down on drop, so the kernel doesn't access the operation
+    /// buffers after the driver is gone.
+    ///
+    /// Linux before 5.19 can't cancel all operations with one request, the driver cancels them
+    /// one by one.
+    ///
+    /// If the shutdown on drop fails, the process is aborted: the kernel could write to the
+    /// buffers of the operations after they are released.
+    pub fn shutdown(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        self.sync_completed.clear();
+        self.cancel_one_by_one = !self.cancel_any;
+        if !self.in_flight.is_empty() && self.cancel_any {
+            let squeue_entry = opcode::AsyncCancel2::new(CancelBuilder::any())
+                .build()
+                .user_data(Self::CANCEL_ALL_KEY);
+            self.push_shutdown_entry(&squeue_entry)?;
+        }
+        let mut completed = Vec::new();
+        // the registered files array is read by the kernel until FilesUpdate completes
+        while !self.in_flight.is_empty()
+            || !matches!(
+                self.files_update_state,
+                FilesUpdateState::NoUpdateInProgress
+            )
+        {
+            if self.cancel_one_by_one && self.shutdown_cancels == 0 {
+                // the previous requests are completed, the operations they couldn't cancel yet
+                // are canceled again
+                self.cancel_each_in_flight()?;
+            }
+            let timeout = match deadline {
+                Some(deadline) => {
+                    let left = deadline.saturating_duration_since(Instant::now());
+                    if left.is_zero() {
+                        return Err(io::ErrorKind::TimedOut.into());
+                    }
+                    Some(left)
+                }
+                None => None,
+            };
+            // SAFETY: pushed operations are borrowed for the 'arena lifetime
+            match unsafe { self.submit(timeout, &mut completed) } {
+                Err(e) if e.kind() != io::ErrorKind::Interrupted => return Err(e),
+                _ => completed.clear(),
+            }
+        }
+        self.user_data.clear();
+        Ok(())
+    }
+
+    // push the cancel request for every user_data in flight, one request cancels one of the
+    // operations with the same user_data
+    fn cancel_each_in_flight(&mut self) -> io::Result<()> {
+        let in_flight: Vec<_> = self.in_flight.keys().copied().collect();
+        for user_data in in_flight {
+            let squeue_entry = AsyncCancel::new(user_data as u64)
+                .build()
+                .user_data(Self::SHUTDOWN_CANCEL_KEY);
+            self.push_shutdown_entry(&squeue_entry)?;
+            self.shutdown_cancels += 1;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn complete_in_flight(&mut self, user_data: usize) {
+        if let hash_map::Entry::Occupied(mut count) = self.in_flight.entry(user_data) {
+            *count.get_mut() -= 1;
+            if *count.get() == 0 {
+                count.remove();
+            }
+        }
+    }
+
+    fn push_shutdown_entry(&mut self, squeue_entry: &squeue::Entry) -> io::Result<()> {
+        if unsafe { self.inner.submission().push(squeue_entry) }.is_err() {
+            // SAFETY: pushed operations are borrowed for the 'arena lifetime
+            unsafe { self.flush() }?;
+            unsafe { self.inner.submission().push(squeue_entry) }
+                .map_err(|_| io::Error::other("submission queue is full"))?;
+        }
+        self.cq_pending += 1;
+        Ok(())
+    }
+
+    /// Create a handle to post completions into the driver from other threads.
+    pub fn handle(&self) -> io::Result<DriverHandle> {
+        // SAFETY: the ring fd is valid while the driver lives
+        let fd = unsafe { BorrowedFd::borrow_raw(self.inner.as_raw_fd()) };
+        Ok(DriverHandle {
+            fd: fd.try_clone_to_owned()?,
+        })
+    }
+
+    /// Create a token to share the kernel worker pool of the driver with other drivers.
+    pub fn work_queue_token(&mut self) -> io::Result<WorkQueueToken> {
+        let fd = match &self.wq_fd {
+            Some(fd) => fd.clone(),
+            None => {
+                // SAFETY: the ring fd is valid while the driver is alive
+                let fd = unsafe { BorrowedFd::borrow_raw(self.inner.as_raw_fd()) };
+                let fd = Arc::new(fd.try_clone_to_owned()?);
+                self.wq_fd = Some(fd.clone());
+                fd
+            }
+        };
+        Ok(WorkQueueToken {
+            fd: Arc::downgrade(&fd),
+        })
+    }
+
+    /// Create a handle to wake up the driver from other threads.
+    ///
+    /// The first call creates an eventfd and pushes the multishot poll of it, the poll is not
+    /// reported as an operation.
+    pub fn waker(&mut self) -> io::Result<DriverWaker> {
+        let fd = match &self.waker {
+            Some(fd) => fd.clone(),
+            None => {
+                let fd = syscall!(eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK))?;
+                // SAFETY: the fd is just created
+                let fd = Arc::new(unsafe { OwnedFd::from_raw_fd(fd) });
+                self.waker = Some(fd.clone());
+                fd
+            }
+        };
+        self.arm_waker();
+        Ok(DriverWaker { fd })
+    }
+
+    // push the poll of the waker eventfd when it's not pushed yet
+    fn arm_waker(&mut self) {
+        let Some(fd) = &self.waker else {
+            return;
+        };
+        if self.waker_armed {
+            return;
+        }
+        let squeue_entry = opcode::PollAdd::new(types::Fd(fd.as_raw_fd()), libc::POLLIN as _)
+            .multi(true)
+            .build()
+            .user_data(Self::WAKER_KEY);
+        // the poll is pushed with the next submission if the queue is full
+        self.waker_armed = unsafe { self.inner.submission().push(&squeue_entry) }.is_ok();
+        if self.waker_armed {
+            self.cq_pending += 1;
+        }
+    }
+
+    /// Register the ring of provided buffers.
+    ///
+    /// Operations select buffers from the ring by its group id. Available since Linux 5.19.
+    pub fn register_buf_ring(&mut self, ring: &'arena BufRing) -> io::Result<()> {
+        // SAFETY: the ring outlives the driver
+        unsafe {
+            self.inner.submitter().register_buf_ring(
+                ring.as_ring_addr(),
+                ring.entries(),
+                ring.group_id(),
+            )
+        }
+    }
+
+    /// Unregister the ring of provided buffers with the group id.
+    pub fn unregister_buf_ring(&mut self, group_id: u16) -> io::Result<()> {
+        self.inner.submitter().unregister_buf_ring(group_id)
+    }
+
+    /// Busy poll the network devices of the sockets for `busy_poll` when the driver waits for
+    /// completions, the previous registration is replaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::ErrorKind::Unsupported`] before Linux 6.9 or if the kernel is built
+    /// without busy polling.
+    pub fn register_napi(&mut self, busy_poll: Duration, prefer_busy_poll: bool) -> io::Result<()> {
+        register_napi(&self.inner, busy_poll, prefer_busy_poll)?;
+        self.capabilities.napi_busy_poll = Some(busy_poll);
+        self.capabilities.napi_prefer_busy_poll = prefer_busy_poll;
+        Ok(())
+    }
+
+    /// Stop busy polling the network devices.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::ErrorKind::Unsupported`] before Linux 6.9 or if the kernel is built
+    /// without busy polling.
+    pub fn unregister_napi(&mut self) -> io::Result<()> {
+        let mut napi = Napi::default();
+        napi_result(syscall!(syscall(
+            libc::SYS_io_uring_register,
+            self.inner.as_raw_fd() as libc::c_long,
+            IORING_UNREGISTER_NAPI as libc::c_long,
+            &mut napi as *mut Napi,
+            1 as libc::c_long,
+        )))?;
+        self.capabilities.napi_busy_poll = None;
+        self.capabilities.napi_prefer_busy_poll = false;
+        Ok(())
+    }
+
     // Submit and wait for completions until `timeout` is passed
-    fn submit_impl(&mut self, timeout: Option<Duration>) -> io::Result<()> {
-        let res = match timeout {
-            None => self.inner.submit_and_wait(1),
-            Some(Duration::ZERO) => self.inner.submit(),
-            Some(duration) => {
+    fn sync_submission(&mut self) {
+        self.inner.submission().sync();
+
+        if let FilesUpdateState::Pushed = self.files_update_state {
+            self.files_update_state = FilesUpdateState::Submitted
+        }
+    }
+
+    fn observe_cq_overflow(&mut self) {
+        if self.inner.submission().cq_overflow() {
+            self.cq_overflow_observed = true;
+        }
+    }
+
+    // move the completions kept by the kernel into the drained completion queue, returns
+    // `false` if there are no kept completions
+    fn flush_cq_overflow(&mut self) -> bool {
+        if !self.inner.params().is_feature_nodrop() || !self.inner.submission().cq_overflow() {
+            return false;
+        }
+        self.cq_overflow_observed = true;
+        self.stats.cq_overflow_flushes += 1;
+        // SAFETY: no submission entries are passed
+        let res = unsafe { self.enter::<libc::sigset_t>(0, 0, IORING_ENTER_GETEVENTS, None) };
+        res.is_ok()
+    }
+
+    // the kernel has read the timespecs of the submitted linked timeouts
+    fn release_link_timespecs(&mut self) {
+        if !self.link_timespecs.is_empty() && self.inner.submission().is_empty() {
+            self.link_timespecs.clear();
+        }
+    }
+
+    fn submit_and_wait(
+        &mut self,
+        wait: Wait,
+        min_complete: usize,
+        completed: &mut impl Extend<Entry>,
+    ) -> io::Result<()> {
+        self.hooks.submitted();
+        self.arm_waker();
+        // Anyway we need to submit once, no matter there are entries in squeue.
+        self.sync_submission();
+
+        // the synchronously completed operations count towards the threshold
+        let want = min_complete.saturating_sub(self.sync_completed.len());
+        let wait = if want == 0 {
+            Wait::Timeout(Some(Duration::ZERO))
+        } else {
+            wait
+        };
+        let res = self.submit_impl(wait, want);
+        self.release_link_timespecs();
+        self.observe_cq_overflow();
+        // if new submission entries are pushed during completion, runtime has to submit
+        // and wait again
+        completed.extend(self.peek_completed());
+        res
+    }
+
+    fn submit_impl(&mut self, wait: Wait, want: usize) -> io::Result<()> {
+        let res = match wait {
+            Wait::Timeout(None) => self.enter_submit::<libc::sigset_t>(want, 0, None),
+            Wait::Timeout(Some(Duration::ZERO)) if self.capabilities.defer_taskrun => {
+                let to_submit = self.inner.submission().len() as _;
+                // the deferred completions are run only when the kernel is asked for them
+                unsafe { self.enter::<libc::sigset_t>(to_submit, 0, IORING_ENTER_GETEVENTS, None) }
+            }
+            Wait::Timeout(Some(Duration::ZERO)) => self.enter_submit::<libc::sigset_t>(0, 0, None),
+            Wait::Timeout(Some(duration)) => {
                 // Wait till timeout.
                 let timespec = timespec(duration);
-                let args = SubmitArgs::new().timespec(&timespec);
-                self.inner.submitter().submit_with_args(1, &args)
+                let args = GeteventsArg {
+                    sigmask: 0,
+                    sigmask_sz: 0,
+                    min_wait_usec: 0,
+                    ts: &timespec as *const Timespec as u64,
+                };
+                self.enter_submit(want, IORING_ENTER_EXT_ARG, Some(&args))
+            }
+            Wait::Deadline(deadline) => {
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                if timeout.is_zero() || !self.abs_timer {
+                    return self.submit_impl(Wait::Timeout(Some(timeout)), want);
+                }
+                match self.submit_with_abs_timer(timeout, want) {
+                    // the kernel rejects the unknown flag, wait with the time left
+                    Err(e) if e.raw_os_error() == Some(libc::EINVAL) => {
+                        self.abs_timer = false;
+                        return self.submit_impl(Wait::Deadline(deadline), want);
+                    }
+                    res => res,
+                }
             }
         };
         match res {
@@ -200,52 +970,302 @@ impl<'arena> Driver<'arena> {
         }
     }
 
-    fn complete_entries(&mut self, entries: &mut impl Extend<Entry>) {
+    // wait for the CLOCK_MONOTONIC time which is `timeout` later than now
+    fn submit_with_abs_timer(&mut self, timeout: Duration, want: usize) -> io::Result<usize> {
+        let mut now = std::mem::MaybeUninit::<libc::timespec>::uninit();
+        syscall!(clock_gettime(libc::CLOCK_MONOTONIC, now.as_mut_ptr()))?;
+        // SAFETY: clock_gettime fills the time on success
+        let now = unsafe { now.assume_init() };
+        let deadline = Duration::new(now.tv_sec as _, now.tv_nsec as _) + timeout;
+        let timespec = timespec(deadline);
+        let args = GeteventsArg {
+            sigmask: 0,
+            sigmask_sz: 0,
+            min_wait_usec: 0,
+            ts: &timespec as *const Timespec as u64,
+        };
+        let mut flags = IORING_ENTER_GETEVENTS | IORING_ENTER_EXT_ARG | IORING_ENTER_ABS_TIMER;
+        if self.inner.params().is_setup_sqpoll() && self.inner.submission().need_wakeup() {
+            flags |= IORING_ENTER_SQ_WAKEUP;
+        }
+        let to_submit = self.inner.submission().len() as _;
+        // SAFETY: the argument and the timespec outlive the call
+        unsafe { self.enter(to_submit, want as _, flags, Some(&args)) }
+    }
+
+    // submit the queued entries like `Submitter::submit_and_wait` of io-uring crate
+    fn enter_submit<T>(
+        &mut self,
+        want: usize,
+        mut flags: u32,
+        arg: Option<&T>,
+    ) -> io::Result<usize> {
+        let params = self.inner.params();
+        let (iopoll, sqpoll) = (params.is_setup_iopoll(), params.is_setup_sqpoll());
+        let submission = self.inner.submission();
+        let (len, cq_overflow, need_wakeup) = (
+            submission.len(),
+            submission.cq_overflow(),
+            submission.need_wakeup(),
+        );
+        drop(submission);
+        if want > 0 || iopoll || cq_overflow {
+            flags |= IORING_ENTER_GETEVENTS;
+        }
+        if sqpoll {
+            if need_wakeup {
+                flags |= IORING_ENTER_SQ_WAKEUP;
+            } else if want == 0 {
+                // the polling thread is awake and takes the entries itself
+                return Ok(len);
+            }
+        }
+        // SAFETY: the argument outlives the call
+        unsafe { self.enter(len as _, want as _, flags, arg) }
+    }
+
+    // io_uring_enter with the registered ring fd on the thread which registered it
+    unsafe fn enter<T>(
+        &self,
+        to_submit: u32,
+        min_complete: u32,
+        flags: u32,
+        arg: Option<&T>,
+    ) -> io::Result<usize> {
+        match &self.registered_ring {
+            Some(ring) if ring.thread == thread_id() => {
+                let arg = arg.map_or(std::ptr::null(), |arg| arg as *const T);
+                let res = syscall!(syscall(
+                    libc::SYS_io_uring_enter,
+                    ring.index as libc::c_long,
+                    to_submit as libc::c_long,
+                    min_complete as libc::c_long,
+                    (flags | IORING_ENTER_REGISTERED_RING) as libc::c_long,
+                    arg,
+                    std::mem::size_of::<T>(),
+                ))?;
+                Ok(res as _)
+            }
+            _ => self
+                .inner
+                .submitter()
+                .enter(to_submit, min_complete, flags, arg),
+        }
+    }
+
+    // the entry reported for the CQE, CQEs of internal requests are consumed by the driver
+    fn complete_entry(&mut self, entry: cqueue::Entry) -> Option<Entry> {
         const TIMER_EXPIRED: i32 = -libc::ETIME;
-        const NO_ENTRY: i32 = -libc::ENOENT;
-        const NOT_CANCELLABLE: i32 = -libc::EALREADY;
-
-        let completed_entries = self.inner.completion().filter_map(|entry| {
-            match entry.user_data() {
-                Self::FILES_UPDATE_KEY => {
-                    // async FilesUpdate operation has finished - reset files update state
-                    for f in self.files_update_fds.iter_mut() {
-                        *f = SKIP_FILE
+
+        // the multishot requests are counted once, the result and the notification twice
+        if !cqueue::more(entry.flags())
+            || self.pending_notifications.contains_key(&entry.user_data())
+        {
+            self.cq_pending = self.cq_pending.saturating_sub(1);
+        }
+        match entry.user_data() {
+            Self::FILES_UPDATE_KEY => {
+                // async FilesUpdate operation has finished - reset files update state
+                // the kernel updates the files in order and reports how many it processed
+                let updated = usize::try_from(entry.result()).unwrap_or(0);
+                for (i, f) in self.files_update_fds.iter_mut().enumerate() {
+                    if std::mem::take(&mut self.files_unregistering[i]) && i < updated {
+                        // the removed slot could be allocated again, the slot which failed to be
+                        // removed stays allocated
+                        self.files_allocated[i] = false;
                     }
-                    self.files_update_state = FilesUpdateState::NoUpdateInProgress;
-                    // we processed CQE
+                    *f = SKIP_FILE
+                }
+                self.files_update_state = FilesUpdateState::NoUpdateInProgress;
+                // we processed CQE
+                None
+            }
+            // https://man7.org/linux/man-pages/man3/io_uring_prep_cancel.3.html
+            // The result of the cancel request itself is not interesting - the cancelled
+            // operation completes with its own CQE.
+            Self::CANCEL_KEY => None,
+            Self::SHUTDOWN_CANCEL_KEY => {
+                self.shutdown_cancels -= 1;
+                None
+            }
+            Self::CANCEL_ALL_KEY => {
+                // Linux before 5.19 doesn't support IORING_ASYNC_CANCEL_ANY
+                if entry.result() == -libc::EINVAL {
+                    self.cancel_one_by_one = true;
+                }
+                None
+            }
+            Self::WAKER_KEY => {
+                if let Some(fd) = &self.waker {
+                    // reset the counter of the nonblocking eventfd
+                    let mut data = 0u64;
+                    let _ = syscall!(read(
+                        fd.as_raw_fd(),
+                        &mut data as *mut _ as *mut _,
+                        std::mem::size_of::<u64>(),
+                    ));
+                }
+                if !cqueue::more(entry.flags()) {
+                    self.waker_armed = false;
+                }
+                None
+            }
+            user_data if user_data & Self::LINK_TIMEOUT_FLAG != 0 => {
+                let user_data = user_data & !Self::LINK_TIMEOUT_FLAG;
+                let expired = entry.result() == TIMER_EXPIRED;
+                match self.ops_with_timeout.get_mut(&user_data) {
+                    Some(state @ LinkTimeout::Armed) => {
+                        *state = if expired {
+                            LinkTimeout::Expired
+                        } else {
+                            LinkTimeout::Disarmed
+                        };
+                        None
+                    }
+                    Some(LinkTimeout::Canceled(_)) => {
+                        let Some(LinkTimeout::Canceled(canceled)) =
+                            self.ops_with_timeout.remove(&user_data)
+                        else {
+                            unreachable!("the state is checked above")
+                        };
+                        Some(if expired {
+                            timed_out_entry(user_data).with_notification(canceled.is_notification())
+                        } else {
+                            canceled
+                        })
+                    }
+                    // the operation has completed or was canceled explicitly
+                    _ => None,
+                }
+            }
+            user_data if self.pending_notifications.contains_key(&user_data) => {
+                if entry.flags() & IORING_CQE_F_NOTIF != 0 {
+                    // the buffers are released - report the result of the first CQE
+                    let result = self
+                        .pending_notifications
+                        .remove(&user_data)
+                        .flatten()
+                        .expect("notification follows the result");
+                    let entry =
+                        Entry::new(user_data as _, cqe_result(result)).with_notification(true);
+                    self.complete_with_timeout(user_data, entry)
+                } else if cqueue::more(entry.flags()) {
+                    self.pending_notifications
+                        .insert(user_data, Some(entry.result()));
                     None
+                } else {
+                    // no notification follows
+                    self.cq_pending = self.cq_pending.saturating_sub(1);
+                    self.pending_notifications.remove(&user_data);
+                    self.complete_with_timeout(user_data, create_entry(entry))
                 }
-                _ => match entry.result() {
-                    // https://man7.org/linux/man-pages/man3/io_uring_prep_cancel.3.html
-                    // The specified timeout occurred and triggered the completion event.,
-                    TIMER_EXPIRED => Some(Entry::new(entry.user_data() as usize, Ok(0))),
-                    // The request identified by user_data could not be located.
-                    // This could be because it completed before the cancelation
-                    // request was issued, or if an invalid identifier is used.
-                    NO_ENTRY |
-                    // The execution state of the request has progressed far
-                    // enough that cancelation is no longer possible. This should
-                    // normally mean that it will complete shortly, either
-                    // successfully, or interrupted due to the cancelation.
-                    NOT_CANCELLABLE => None,
-                    _ => Some(create_entry(entry)),
-                },
-            }
-        });
-        entries.extend(completed_entries);
+            }
+            user_data if self.ops_with_timeout.contains_key(&user_data) => {
+                if cqueue::more(entry.flags()) {
+                    return Some(create_entry(entry));
+                }
+                self.complete_with_timeout(user_data, create_entry(entry))
+            }
+            _ => match entry.result() {
+                // The specified timeout occurred and triggered the completion event.,
+                TIMER_EXPIRED => Some(
+                    Entry::new(entry.user_data() as usize, Ok(0)).with_raw_result(TIMER_EXPIRED),
+                ),
+                _ => Some(create_entry(entry)),
+            },
+        }
+    }
+
+    // resolve the linked timeout of the completed operation, the operation canceled by the
+    // timeout completes with the timed out error
+    fn complete_with_timeout(&mut self, user_data: u64, entry: Entry) -> Option<Entry> {
+        if entry.raw_result() != -libc::ECANCELED {
+            // the CQE of the disarmed timeout is ignored
+            self.ops_with_timeout.remove(&user_data);
+            return Some(entry);
+        }
+        match self.ops_with_timeout.remove(&user_data) {
+            // the linked timeout cancels the operation
+            Some(LinkTimeout::Expired) => {
+                Some(timed_out_entry(user_data).with_notification(entry.is_notification()))
+            }
+            Some(LinkTimeout::Armed) => {
+                // wait for the timeout CQE
+                self.ops_with_timeout
+                    .insert(user_data, LinkTimeout::Canceled(entry));
+                None
+            }
+            _ => Some(entry),
+        }
     }
 
     #[inline]
-    fn register_fd_impl(&mut self, fd: RawFd, id: u32) -> io::Result<()> {
+    // Push the operation entry followed by its linked timeout
+    fn push_entry(
+        &mut self,
+        entry: squeue::Entry,
+        user_data: usize,
+        link: bool,
+        timeout: Option<Duration>,
+        cqes: usize,
+    ) -> Result<(), ()> {
+        let required = if timeout.is_some() { 2 } else { 1 };
+        if self.sq_space_left() < required || self.cq_space_left() < cqes {
+            return Err(());
+        }
+        let Some(timeout) = timeout else {
+            unsafe { self.inner.submission().push(&link_entry(entry, link)) }.map_err(|_| ())?;
+            self.cq_pending += cqes;
+            return Ok(());
+        };
+        let entries = [
+            entry.flags(squeue::Flags::IO_LINK),
+            self.link_timeout_entry(user_data, timeout, link),
+        ];
+        unsafe { self.inner.submission().push_multiple(&entries) }.expect("in capacity");
+        self.cq_pending += cqes;
+        Ok(())
+    }
+
+    fn link_timeout_entry(
+        &mut self,
+        user_data: usize,
+        timeout: Duration,
+        link: bool,
+    ) -> squeue::Entry {
+        let timespec = Box::new(timespec(timeout));
+        let entry = opcode::LinkTimeout::new(&*timespec)
+            .build()
+            .user_data(user_data as u64 | Self::LINK_TIMEOUT_FLAG);
+        self.link_timespecs.push(timespec);
+        self.ops_with_timeout
+            .insert(user_data as _, LinkTimeout::Armed);
+        // the successors of the operation are linked to the timeout
+        link_entry(entry, link)
+    }
+
+    fn check_files_range(&self, offset: u32, len: usize) {
         debug_assert!(
             self.files_update_fds.len() > 0,
             "files_to_register is nonzero"
         );
         debug_assert!(
-            (id as usize) < self.files_update_fds.len(),
+            offset as usize + len <= self.files_update_fds.len(),
             "registered fixed file index is within [0; files_to_register) range"
         );
+    }
+
+    fn register_files_impl(&mut self, offset: u32, fds: &[RawFd]) -> io::Result<()> {
+        self.check_files_range(offset, fds.len());
+        // the registration overrides the deferred removal
+        if !self.deferred_files_removal.is_empty() {
+            let range = offset..offset + fds.len() as u32;
+            self.deferred_files_removal.retain(|id| !range.contains(id));
+        }
+        if !matches!(self.files_update_state, FilesUpdateState::Submitted) {
+            // the removal isn't submitted yet, the registration overrides it in place
+            self.files_unregistering[offset as usize..offset as usize + fds.len()].fill(false);
+        }
 
         let is_squeue_full = unsafe { self.inner.submission_shared().is_full() };
 
@@ -253,28 +1273,161 @@ impl<'arena> Driver<'arena> {
             (true, _) | (false, FilesUpdateState::Submitted) => {
                 // fallback to synchronous registration when squeue is full or async files_update is
                 // not completed yet
-                self.inner.submitter().register_files_update(id, &[fd])?;
+                self.inner.submitter().register_files_update(offset, fds)?;
             }
             (false, FilesUpdateState::NoUpdateInProgress) => {
                 // set the initial file update
-                self.files_update_fds[id as usize] = fd;
-                // create and push update operation for all registered file descriptors
-                let len = u32::try_from(self.files_update_fds.len()).expect("in range");
-                let fds_ptr = self.files_update_fds.as_ptr();
-                let squeue_entry = FilesUpdate::new(fds_ptr, len)
-                    .build()
-                    .user_data(Self::FILES_UPDATE_KEY);
-                let mut squeue = self.inner.submission();
-                unsafe { squeue.push(&squeue_entry) }.expect("squeue is not full");
-                self.files_update_state = FilesUpdateState::Pushed;
+                self.set_files_update(offset, fds.iter().copied());
+                self.push_files_update();
             }
             (false, FilesUpdateState::Pushed) => {
                 // accumulate more updates in `files_update_fds`
-                self.files_update_fds[id as usize] = fd;
+                self.set_files_update(offset, fds.iter().copied());
             }
         }
         Ok(())
     }
+
+    // Registered files could be used by operations pushed before, so they are always removed
+    // asynchronously after the pushed operations.
+    fn unregister_files_impl(&mut self, offset: u32, len: u32) {
+        self.check_files_range(offset, len as usize);
+
+        let is_squeue_full = unsafe { self.inner.submission_shared().is_full() };
+
+        match (is_squeue_full, self.files_update_state) {
+            (false, FilesUpdateState::NoUpdateInProgress) => {
+                self.set_files_removal(offset, len);
+                self.push_files_update();
+            }
+            (_, FilesUpdateState::Pushed) => {
+                self.set_files_removal(offset, len);
+            }
+            // defer the removal till the in progress update completes
+            _ => self.deferred_files_removal.extend(offset..offset + len),
+        }
+    }
+
+    fn push_deferred_files_removal(&mut self) {
+        let is_squeue_full = unsafe { self.inner.submission_shared().is_full() };
+        if self.deferred_files_removal.is_empty()
+            || is_squeue_full
+            || !matches!(
+                self.files_update_state,
+                FilesUpdateState::NoUpdateInProgress
+            )
+        {
+            return;
+        }
+        for id in std::mem::take(&mut self.deferred_files_removal) {
+            self.set_files_removal(id, 1);
+        }
+        self.push_files_update();
+    }
+
+    #[inline]
+    fn set_files_removal(&mut self, offset: u32, len: u32) {
+        let range = offset as usize..(offset + len) as usize;
+        self.files_update_fds[range.clone()].fill(-1);
+        self.files_unregistering[range].fill(true);
+    }
+
+    #[inline]
+    fn set_files_update(&mut self, offset: u32, fds: impl Iterator<Item = RawFd>) {
+        for (f, fd) in self.files_update_fds[offset as usize..].iter_mut().zip(fds) {
+            *f = fd;
+        }
+    }
+
+    // Create and push update operation for all registered file descriptors
+    fn push_files_update(&mut self) {
+        let len = u32::try_from(self.files_update_fds.len()).expect("in range");
+        let fds_ptr = self.files_update_fds.as_ptr();
+        let squeue_entry = FilesUpdate::new(fds_ptr, len)
+            .build()
+            .user_data(Self::FILES_UPDATE_KEY);
+        let mut squeue = self.inner.submission();
+        unsafe { squeue.push(&squeue_entry) }.expect("squeue is not full");
+        self.cq_pending += 1;
+        self.files_update_state = FilesUpdateState::Pushed;
+    }
+
+    // Find the first free range of `len` registered file slots
+    fn allocate_files(&mut self, len: usize) -> io::Result<u32> {
+        let mut offset = 0;
+        for (i, allocated) in self.files_allocated.iter().enumerate() {
+            if *allocated {
+                offset = i + 1;
+            } else if i + 1 - offset == len {
+                break;
+            }
+        }
+        if offset + len > self.files_allocated.len() {
+            return Err(io::Error::other(
+                "not enough free slots for registered files",
+            ));
+        }
+        self.files_allocated[offset..offset + len].fill(true);
+        Ok(u32::try_from(offset).expect("in range"))
+    }
+
+    // Fail the operation using the stale fixed fds or updating the files which are not registered
+    fn check_files(&self, op: &dyn OpCode) -> io::Result<()> {
+        for fixed_fd in op.fixed_fds().into_iter().flatten() {
+            // the invalid fixed fd fails in the kernel
+            match self.files_generation.get(fixed_fd.as_offset() as usize) {
+                Some(generation) if *generation != fixed_fd.generation => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "fixed fd is stale, the registered file is updated",
+                    ));
+                }
+                _ => {}
+            }
+        }
+        if let Some(slot) = op.files_update() {
+            let range = slot.offset as usize..(slot.offset + slot.len) as usize;
+            if !self
+                .files_allocated
+                .get(range)
+                .is_some_and(|allocated| allocated.iter().all(|allocated| *allocated))
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "updated files are not registered",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn track_files_update(&mut self, user_data: usize, op: &dyn OpCode) {
+        if let Some(slot) = op.files_update() {
+            self.files_updates.insert(
+                user_data as _,
+                (slot.offset..slot.offset + slot.len, slot.generation),
+            );
+        }
+    }
+
+    // The fixed fds of the slot got before the completed FilesUpdate are stale
+    #[inline]
+    fn complete_files_update(&mut self, user_data: usize) {
+        if self.files_updates.is_empty() {
+            return;
+        }
+        let Some((range, generation)) = self.files_updates.remove(&(user_data as u64)) else {
+            return;
+        };
+        for slot_generation in &mut self.files_generation[range.start as usize..range.end as usize]
+        {
+            // the updates of the slot could complete out of order
+            if (generation.wrapping_sub(*slot_generation) as i32) > 0 {
+                *slot_generation = generation;
+            }
+        }
+    }
 }
 
 impl<'arena> CompleteIo<'arena> for Driver<'arena> {
@@ -283,40 +1436,184 @@ impl<'arena> CompleteIo<'arena> for Driver<'arena> {
         Ok(Fd::from_raw(fd))
     }
 
+    #[inline]
+    fn detach(&mut self, _fd: RawFd) -> io::Result<()> {
+        Ok(())
+    }
+
     #[inline]
     fn register_fd(&mut self, fd: RawFd, id: u32) -> io::Result<FixedFd> {
-        self.register_fd_impl(fd, id)
-            .map(|_| FixedFd::from_offset(id))
+        self.register_files_impl(id, &[fd])?;
+        self.files_allocated[id as usize] = true;
+        Ok(FixedFd::new(id, self.files_generation[id as usize]))
     }
 
     #[inline]
     fn unregister_fd(&mut self, fixed_fd: FixedFd) -> io::Result<()> {
-        self.register_fd_impl(-1, fixed_fd.as_offset())
+        self.unregister_files_impl(fixed_fd.as_offset(), 1);
+        Ok(())
+    }
+
+    fn register_files(&mut self, fds: &[RawFd]) -> io::Result<FixedFdSlot> {
+        let offset = self.allocate_files(fds.len())?;
+        if let Err(e) = self.register_files_impl(offset, fds) {
+            self.files_allocated[offset as usize..offset as usize + fds.len()].fill(false);
+            return Err(e);
+        }
+        // the fixed fds of the new slot share the generation
+        let range = offset as usize..offset as usize + fds.len();
+        let generation = self.files_generation[range.clone()]
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or_default();
+        self.files_generation[range].fill(generation);
+        Ok(FixedFdSlot {
+            offset,
+            len: u32::try_from(fds.len()).expect("in range"),
+            generation,
+        })
+    }
+
+    fn register_files_update(
+        &mut self,
+        slot: &mut FixedFdSlot,
+        index: usize,
+        fds: &[RawFd],
+    ) -> io::Result<()> {
+        if index + fds.len() > slot.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "updated files are out of the slot",
+            ));
+        }
+        self.register_files_impl(slot.offset + index as u32, fds)
+    }
+
+    fn unregister_files(&mut self, slot: FixedFdSlot) -> io::Result<()> {
+        self.unregister_files_impl(slot.offset, slot.len);
+        Ok(())
+    }
+
+    fn register_buffers<'a: 'arena>(
+        &mut self,
+        bufs: &'a mut [IoSliceMut<'a>],
+    ) -> io::Result<FixedBufRegistry<'a>> {
+        let registry = FixedBufRegistry::new(bufs)?;
+        let iovecs = registry
+            .as_raw_bufs()
+            .iter()
+            .map(|(ptr, len)| libc::iovec {
+                iov_base: ptr.as_ptr() as _,
+                iov_len: *len,
+            })
+            .collect::<Vec<_>>();
+        // SAFETY: the buffers outlive the driver
+        unsafe { self.inner.submitter().register_buffers(&iovecs) }?;
+        Ok(registry)
+    }
+
+    fn unregister_buffers(&mut self, registry: &FixedBufRegistry<'_>) -> io::Result<()> {
+        registry.check_not_in_use()?;
+        self.inner.submitter().unregister_buffers()
     }
 
     #[inline]
-    fn try_cancel(&mut self, user_data: usize) -> Result<(), ()> {
+    fn try_cancel(&mut self, user_data: usize) -> Result<(), CancelError> {
         let squeue_entry = AsyncCancel::new(user_data as u64)
             .build()
-            .user_data(user_data as u64);
-        unsafe { self.inner.submission().push(&squeue_entry) }.map_err(|_| ())
+            .user_data(Self::CANCEL_KEY);
+        if unsafe { self.inner.submission().push(&squeue_entry) }.is_err() {
+            // submit queued entries to make room for the request
+            // SAFETY: pushed operations are borrowed for the 'arena lifetime
+            unsafe { self.flush() }.map_err(CancelError::Os)?;
+            unsafe { self.inner.submission().push(&squeue_entry) }
+                .map_err(|_| CancelError::QueueFull)?;
+        }
+        self.cq_pending += 1;
+        // the canceled operation completes with the canceled error
+        self.ops_with_timeout.remove(&(user_data as u64));
+        self.stats.canceled += 1;
+        self.hooks.canceled(user_data);
+        Ok(())
     }
 
     #[inline]
     fn try_push<O: OpCode>(
         &mut self,
         mut op: Operation<'arena, O>,
-    ) -> Result<(), Operation<'arena, O>> {
+    ) -> Result<(), PushError<Operation<'arena, O>>> {
         let user_data = op.user_data();
+        if !self.user_data.insert(user_data) {
+            return Err(PushError::DuplicateUserData(op));
+        }
+        let result = match self.check_files(op.opcode()) {
+            Ok(()) => op.opcode().operate(),
+            Err(e) => Some(Err(e)),
+        };
+        if let Some(result) = result {
+            self.stats.submitted += 1;
+            self.hooks.pushed(user_data, || op.opcode().name());
+            self.sync_completed.push_back(Entry::new(user_data, result));
+            return Ok(());
+        }
+        let has_notification = op.opcode().request_notification(&self.capabilities);
         let squeue_entry = op.opcode().create_entry().user_data(user_data as _);
-        unsafe { self.inner.submission().push(&squeue_entry) }.map_err(|_| op)
+        let cqes = expected_cqes(op.timeout(), has_notification);
+        if self
+            .push_entry(squeue_entry, user_data, op.is_linked(), op.timeout(), cqes)
+            .is_err()
+        {
+            self.user_data.remove(user_data);
+            return Err(PushError::QueueFull(op));
+        }
+        self.stats.submitted += 1;
+        self.hooks.pushed(user_data, || op.opcode().name());
+        *self.in_flight.entry(user_data).or_default() += 1;
+        if has_notification {
+            self.pending_notifications.insert(user_data as _, None);
+        }
+        self.track_files_update(user_data, op.opcode());
+        Ok(())
     }
 
     #[inline]
-    fn try_push_dyn(&mut self, mut op: OpObject<'arena>) -> Result<(), OpObject<'arena>> {
+    fn try_push_dyn(
+        &mut self,
+        mut op: OpObject<'arena>,
+    ) -> Result<(), PushError<OpObject<'arena>>> {
         let user_data = op.user_data();
+        if !self.user_data.insert(user_data) {
+            return Err(PushError::DuplicateUserData(op));
+        }
+        let result = match self.check_files(op.opcode()) {
+            Ok(()) => op.opcode().operate(),
+            Err(e) => Some(Err(e)),
+        };
+        if let Some(result) = result {
+            self.stats.submitted += 1;
+            self.hooks.pushed(user_data, || op.opcode().name());
+            self.sync_completed.push_back(Entry::new(user_data, result));
+            return Ok(());
+        }
+        let has_notification = op.opcode().request_notification(&self.capabilities);
         let squeue_entry = op.opcode().create_entry().user_data(user_data as _);
-        unsafe { self.inner.submission().push(&squeue_entry) }.map_err(|_| op)
+        let cqes = expected_cqes(op.timeout(), has_notification);
+        if self
+            .push_entry(squeue_entry, user_data, op.is_linked(), op.timeout(), cqes)
+            .is_err()
+        {
+            self.user_data.remove(user_data);
+            return Err(PushError::QueueFull(op));
+        }
+        self.stats.submitted += 1;
+        self.hooks.pushed(user_data, || op.opcode().name());
+        *self.in_flight.entry(user_data).or_default() += 1;
+        if has_notification {
+            self.pending_notifications.insert(user_data as _, None);
+        }
+        self.track_files_update(user_data, op.opcode());
+        Ok(())
     }
 
     #[inline]
@@ -324,18 +1621,55 @@ impl<'arena> CompleteIo<'arena> for Driver<'arena> {
         &mut self,
         ops_queue: &mut vec_deque_alloc!(OpObject<'arena>, A),
     ) {
-        let to_drain = self.capacity_left().min(ops_queue.len());
-        if to_drain == 0 {
-            return;
-        };
-
-        let mut squeue = self.inner.submission();
-        let drain_iter = ops_queue.drain(..to_drain).map(|mut op| {
-            let user_data = op.user_data();
-            op.opcode().create_entry().user_data(user_data as _)
-        });
+        self.stats.max_queued = self.stats.max_queued.max(ops_queue.len());
+        let mut sq_space = self.sq_space_left();
+        let mut cq_space = self.cq_space_left();
         self.squeue_buffer.clear();
-        self.squeue_buffer.extend(drain_iter);
+        while let Some(op) = ops_queue.front_mut() {
+            // an operation with timeout takes two entries
+            let required = if op.timeout().is_some() { 2 } else { 1 };
+            let has_notification = op.opcode().request_notification(&self.capabilities);
+            let cqes = expected_cqes(op.timeout(), has_notification);
+            // the operation with the duplicate data stays in the queue
+            if sq_space < required || cq_space < cqes || !self.user_data.insert(op.user_data()) {
+                break;
+            }
+            sq_space -= required;
+            cq_space -= cqes;
+            let mut op = ops_queue.pop_front().expect("not empty");
+            self.stats.submitted += 1;
+            let user_data = op.user_data();
+            self.hooks.pushed(user_data, || op.opcode().name());
+            let result = match self.check_files(op.opcode()) {
+                Ok(()) => op.opcode().operate(),
+                Err(e) => Some(Err(e)),
+            };
+            match result {
+                Some(result) => self.sync_completed.push_back(Entry::new(user_data, result)),
+                None => {
+                    let squeue_entry = op.opcode().create_entry().user_data(user_data as _);
+                    *self.in_flight.entry(user_data).or_default() += 1;
+                    self.cq_pending += cqes;
+                    match op.timeout() {
+                        Some(timeout) => {
+                            self.squeue_buffer
+                                .push(squeue_entry.flags(squeue::Flags::IO_LINK));
+                            let timeout_entry =
+                                self.link_timeout_entry(user_data, timeout, op.is_linked());
+                            self.squeue_buffer.push(timeout_entry);
+                        }
+                        None => self
+                            .squeue_buffer
+                            .push(link_entry(squeue_entry, op.is_linked())),
+                    }
+                    if has_notification {
+                        self.pending_notifications.insert(user_data as _, None);
+                    }
+                    self.track_files_update(user_data, op.opcode());
+                }
+            }
+        }
+        let mut squeue = self.inner.submission();
         unsafe {
             squeue
                 .push_multiple(&self.squeue_buffer)
@@ -345,8 +1679,7 @@ impl<'arena> CompleteIo<'arena> for Driver<'arena> {
 
     #[inline]
     fn capacity_left(&self) -> usize {
-        let squeue = unsafe { self.inner.submission_shared() };
-        squeue.capacity() - squeue.len()
+        self.sq_space_left().min(self.cq_space_left())
     }
 
     unsafe fn submit(
@@ -354,36 +1687,292 @@ impl<'arena> CompleteIo<'arena> for Driver<'arena> {
         timeout: Option<Duration>,
         completed: &mut impl Extend<Entry>,
     ) -> io::Result<()> {
-        // Anyway we need to submit once, no matter there are entries in squeue.
-        self.inner.submission().sync();
+        self.submit_and_wait_min(timeout, 1, completed)
+    }
 
-        if let FilesUpdateState::Pushed = self.files_update_state {
-            self.files_update_state = FilesUpdateState::Submitted
+    unsafe fn submit_and_wait_min(
+        &mut self,
+        timeout: Option<Duration>,
+        min_complete: usize,
+        completed: &mut impl Extend<Entry>,
+    ) -> io::Result<()> {
+        self.submit_and_wait(Wait::Timeout(timeout), min_complete, completed)
+    }
+
+    unsafe fn submit_until(
+        &mut self,
+        deadline: Instant,
+        completed: &mut impl Extend<Entry>,
+    ) -> io::Result<bool> {
+        let mut completed = CountingExtend {
+            inner: completed,
+            count: 0,
+        };
+        loop {
+            match self.submit_and_wait(Wait::Deadline(deadline), 1, &mut completed) {
+                Ok(()) => {}
+                // the absolute deadline is passed to the kernel again
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {
+                    if completed.count == 0 {
+                        continue;
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+            return Ok(completed.count == 0 && Instant::now() >= deadline);
         }
+    }
 
-        let res = self.submit_impl(timeout);
-        // if new submission entries are pushed during completion, runtime has to submit
-        // and wait again
-        self.complete_entries(completed);
+    fn peek_completed(&mut self) -> CompletionIter<'_, 'arena> {
+        CompletionIter::io_uring(CqueueIter { driver: self })
+    }
+
+    unsafe fn flush(&mut self) -> io::Result<usize> {
+        self.hooks.submitted();
+        self.arm_waker();
+        self.sync_submission();
+        let res = match self.enter_submit::<libc::sigset_t>(0, 0, None) {
+            Ok(submitted) => Ok(submitted),
+            // the queued entries are submitted by the next call
+            Err(e) if matches!(e.raw_os_error(), Some(libc::EBUSY) | Some(libc::EAGAIN)) => Ok(0),
+            Err(e) => Err(e),
+        };
+        self.release_link_timespecs();
+        self.observe_cq_overflow();
         res
     }
 }
 
+impl Drop for Driver<'_> {
+    fn drop(&mut self) {
+        // the kernel writes to the buffers of the operations in flight
+        if self.shutdown(None).is_err() {
+            // the buffers could be written after they are released
+            std::process::abort();
+        }
+        if let Some(ring) = self.registered_ring.take() {
+            ring.unregister(&self.inner);
+        }
+    }
+}
+
 impl AsRawFd for Driver<'_> {
     fn as_raw_fd(&self) -> RawFd {
         self.inner.as_raw_fd()
     }
 }
 
+// every entry is consumed from the completion queue when it is returned
+pub(in crate::driver) struct CqueueIter<'a, 'arena> {
+    driver: &'a mut Driver<'arena>,
+}
+
+impl Iterator for CqueueIter<'_, '_> {
+    type Item = Entry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = match self.driver.sync_completed.pop_front() {
+            Some(entry) => entry,
+            None => {
+                let mut flushed = false;
+                loop {
+                    // the completion queue head is updated when the queue is dropped
+                    let Some(cqe) = self.driver.inner.completion().next() else {
+                        // the kept completions are returned after the drained ones
+                        if !flushed && self.driver.flush_cq_overflow() {
+                            flushed = true;
+                            continue;
+                        }
+                        return None;
+                    };
+                    if let Some(entry) = self.driver.complete_entry(cqe) {
+                        if !entry.has_more() {
+                            self.driver.complete_in_flight(entry.user_data());
+                            self.driver.complete_files_update(entry.user_data());
+                        }
+                        break entry;
+                    }
+                }
+            }
+        };
+        self.driver.stats.count_completed(&entry);
+        self.driver.user_data.release(&entry);
+        self.driver.hooks.completed(&entry);
+        Some(entry)
+    }
+}
+
+impl Drop for CqueueIter<'_, '_> {
+    fn drop(&mut self) {
+        self.driver.push_deferred_files_removal();
+    }
+}
+
+// not exported by io-uring crate
+const IORING_CQE_F_NOTIF: u32 = 1 << 3;
+const IORING_ENTER_GETEVENTS: u32 = 1 << 0;
+const IORING_ENTER_SQ_WAKEUP: u32 = 1 << 1;
+const IORING_ENTER_EXT_ARG: u32 = 1 << 3;
+const IORING_ENTER_ABS_TIMER: u32 = 1 << 5;
+const IORING_ENTER_REGISTERED_RING: u32 = 1 << 4;
+const IORING_REGISTER_RING_FDS: u32 = 20;
+const IORING_UNREGISTER_RING_FDS: u32 = 21;
+const IORING_REGISTER_NAPI: u32 = 27;
+const IORING_UNREGISTER_NAPI: u32 = 28;
+
+// struct io_uring_getevents_arg
+#[repr(C)]
+struct GeteventsArg {
+    sigmask: u64,
+    sigmask_sz: u32,
+    min_wait_usec: u32,
+    ts: u64,
+}
+
+// struct io_uring_rsrc_update
+#[repr(C)]
+struct RsrcUpdate {
+    offset: u32,
+    resv: u32,
+    data: u64,
+}
+
+// struct io_uring_napi, the zeroed operation registers the dynamic tracking of the devices
+#[repr(C)]
+#[derive(Default)]
+struct Napi {
+    busy_poll_to: u32,
+    prefer_busy_poll: u8,
+    opcode: u8,
+    pad: [u8; 2],
+    op_param: u32,
+    resv: u32,
+}
+
+fn register_napi(ring: &IoUring, busy_poll: Duration, prefer_busy_poll: bool) -> io::Result<()> {
+    let mut napi = Napi {
+        busy_poll_to: busy_poll.as_micros().try_into().unwrap_or(u32::MAX),
+        prefer_busy_poll: prefer_busy_poll.into(),
+        ..Napi::default()
+    };
+    napi_result(syscall!(syscall(
+        libc::SYS_io_uring_register,
+        ring.as_raw_fd() as libc::c_long,
+        IORING_REGISTER_NAPI as libc::c_long,
+        &mut napi as *mut Napi,
+        1 as libc::c_long,
+    )))
+}
+
+// the kernel rejects the unknown register opcode and NAPI without `CONFIG_NET_RX_BUSY_POLL`
+fn napi_result(res: io::Result<libc::c_long>) -> io::Result<()> {
+    match res {
+        Ok(_) => Ok(()),
+        Err(e) if e.raw_os_error() == Some(libc::EINVAL) => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("io-uring NAPI is unsupported: {e}"),
+        )),
+        Err(e) => Err(e),
+    }
+}
+
+// the index of the ring fd in the registered ring fds of the thread
+#[derive(Debug)]
+struct RegisteredRing {
+    index: u32,
+    thread: u64,
+}
+
+impl RegisteredRing {
+    // returns `None` if the kernel doesn't support the registration, it's available since
+    // Linux 5.18
+    fn register(ring: &IoUring) -> Option<Self> {
+        let mut update = RsrcUpdate {
+            // the kernel allocates the index
+            offset: u32::MAX,
+            resv: 0,
+            data: ring.as_raw_fd() as _,
+        };
+        let registered = syscall!(syscall(
+            libc::SYS_io_uring_register,
+            ring.as_raw_fd() as libc::c_long,
+            IORING_REGISTER_RING_FDS as libc::c_long,
+            &mut update as *mut RsrcUpdate,
+            1 as libc::c_long,
+        ))
+        .ok()?;
+        (registered == 1).then(|| Self {
+            index: update.offset,
+            thread: thread_id(),
+        })
+    }
+
+    // the index of other threads could refer to another ring, the registration of the thread
+    // is dropped when it exits
+    fn unregister(self, ring: &IoUring) {
+        if self.thread != thread_id() {
+            return;
+        }
+        let mut update = RsrcUpdate {
+            offset: self.index,
+            resv: 0,
+            data: 0,
+        };
+        let _ = syscall!(syscall(
+            libc::SYS_io_uring_register,
+            ring.as_raw_fd() as libc::c_long,
+            IORING_UNREGISTER_RING_FDS as libc::c_long,
+            &mut update as *mut RsrcUpdate,
+            1 as libc::c_long,
+        ));
+    }
+}
+
+// unique id of the current thread, cheaper than `thread::current().id()` on every enter
+fn thread_id() -> u64 {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+    thread_local! {
+        static ID: u64 = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    }
+    ID.with(|id| *id)
+}
+
+// successors of the linked entry start after it completes successfully
+#[inline]
+fn link_entry(entry: squeue::Entry, link: bool) -> squeue::Entry {
+    if link {
+        entry.flags(squeue::Flags::IO_LINK)
+    } else {
+        entry
+    }
+}
+
+// the completion entries posted by the operation, the multishot one is counted once
+fn expected_cqes(timeout: Option<Duration>, has_notification: bool) -> usize {
+    1 + usize::from(timeout.is_some()) + usize::from(has_notification)
+}
+
+// the operation canceled by its linked timeout
+fn timed_out_entry(user_data: u64) -> Entry {
+    Entry::new(user_data as _, Err(io::ErrorKind::TimedOut.into()))
+        .with_raw_result(-libc::ECANCELED)
+}
+
 #[inline]
 fn create_entry(entry: cqueue::Entry) -> Entry {
-    let result = entry.result();
-    let result = if result < 0 {
+    Entry::new(entry.user_data() as _, cqe_result(entry.result()))
+        .with_more(cqueue::more(entry.flags()))
+        .with_buffer_id(cqueue::buffer_select(entry.flags()))
+}
+
+#[inline]
+fn cqe_result(result: i32) -> io::Result<usize> {
+    if result < 0 {
         Err(io::Error::from_raw_os_error(-result))
     } else {
         Ok(result as _)
-    };
-    Entry::new(entry.user_data() as _, result)
+    }
 }
 
 #[inline]
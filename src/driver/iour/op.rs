@@ -1,6 +1,15 @@
 #[cfg(feature = "time")]
 use std::time::Duration;
+use std::{
+    ffi::CString,
+    io,
+    marker::PhantomData,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd},
+    sync::atomic::{AtomicBool, AtomicU32, Ordering},
+};
 
+#[cfg(feature = "time")]
+use boot_time::Instant;
 #[cfg(feature = "time")]
 use io_uring::types::{TimeoutFlags, Timespec};
 use io_uring::{
@@ -11,10 +20,17 @@ use io_uring::{
 use libc::sockaddr;
 use socket2::SockAddr;
 
+#[cfg(feature = "time")]
+use crate::driver::epoll::{TIMER_PENDING, TIMER_REMOVE_PENDING, TIMER_UPDATE_PENDING};
 pub use crate::driver::unix::op::*;
 use crate::{
     buf::{AsIoSlices, AsIoSlicesMut, BufWrapper, BufWrapperMut, IntoInner, IoBuf, IoBufMut},
-    driver::{unix::IntoFdOrFixed, Fd, FdOrFixed, IntoRawFd, OpCode},
+    driver::{
+        unix::IntoFdOrFixed, DriverCapabilities, EpollFiles, Fd, FdOrFixed, FixedBuf, FixedFd,
+        FixedFdSlot, IntoRawFd, OpCode, Readiness,
+    },
+    op::{AcceptFlags, AllocateMode, RenameFlags, RwFlags, SyncRangeFlags},
+    syscall,
 };
 
 macro_rules! apply_to_fd_or_fixed {
@@ -27,6 +43,81 @@ macro_rules! apply_to_fd_or_fixed {
 
 }
 
+macro_rules! with_fd_or_fixed {
+    ($fd:expr, | $target:ident | $body:expr) => {
+        match $fd {
+            FdOrFixed::Fd(fd) => {
+                let $target = types::Fd(fd.as_raw_fd());
+                $body
+            }
+            FdOrFixed::Fixed(fixed_fd) => {
+                let $target = types::Fixed(fixed_fd.as_offset());
+                $body
+            }
+        }
+    };
+}
+
+// Perform the call which could block with `O_NONBLOCK` set on the file, epoll driver waits for
+// readiness instead. The flag of the blocking file is restored after the call.
+fn nonblocking(
+    fd: RawFd,
+    call: impl FnOnce() -> Option<io::Result<usize>>,
+) -> Option<io::Result<usize>> {
+    let flags = match syscall!(fcntl(fd, libc::F_GETFL)) {
+        Ok(flags) => flags,
+        Err(e) => return Some(Err(e)),
+    };
+    if flags & libc::O_NONBLOCK != 0 {
+        return call();
+    }
+    if let Err(e) = syscall!(fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK)) {
+        return Some(Err(e));
+    }
+    let res = call();
+    let _ = syscall!(fcntl(fd, libc::F_SETFL, flags));
+    res
+}
+
+// The receive or send with `MSG_DONTWAIT` flag completes with the would block error as in
+// io-uring
+fn dont_wait(flags: i32, res: Option<io::Result<usize>>) -> Option<io::Result<usize>> {
+    if flags & libc::MSG_DONTWAIT != 0 {
+        Some(res.unwrap_or_else(|| Err(io::ErrorKind::WouldBlock.into())))
+    } else {
+        res
+    }
+}
+
+// Query the readiness without blocking
+fn poll_fd(fd: RawFd, events: libc::c_short) -> Option<io::Result<usize>> {
+    let mut pollfd = libc::pollfd {
+        fd,
+        events,
+        revents: 0,
+    };
+    match syscall!(poll(&mut pollfd, 1, 0)) {
+        Ok(0) => None,
+        Ok(_) => Some(Ok(pollfd.revents as u16 as usize)),
+        Err(e) => Some(Err(e)),
+    }
+}
+
+// The data moved between the pipes is written when the input is readable
+fn pipe_readiness(fd_in: RawFd, fd_out: RawFd) -> Readiness {
+    match poll_fd(fd_in, libc::POLLIN) {
+        Some(Ok(_)) => Readiness::Writable(fd_out),
+        _ => Readiness::Readable(fd_in),
+    }
+}
+
+// the offset of the regular file which position is not updated
+fn offset_ptr(offset: &mut Option<i64>) -> *mut i64 {
+    offset
+        .as_mut()
+        .map_or(std::ptr::null_mut(), |offset| offset as *mut _)
+}
+
 impl<'arena, T: IoBufMut<'arena>> OpCode for Read<'arena, T> {
     fn create_entry(&mut self) -> Entry {
         // SAFETY: slice into buffer is Unpin
@@ -34,6 +125,24 @@ impl<'arena, T: IoBufMut<'arena>> OpCode for Read<'arena, T> {
         apply_to_fd_or_fixed!(opcode::Read::new; self.fd, slice.as_mut_ptr() as _, slice.len() as _)
             .build()
     }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        // SAFETY: slice into buffer is Unpin
+        let slice = self.buffer.as_uninit_slice();
+        nonblocking(
+            fd,
+            || syscall!(maybe_block read(fd, slice.as_mut_ptr() as _, slice.len() as _)),
+        )
+    }
+
+    fn epoll_readiness(&self, files: &EpollFiles) -> Readiness {
+        Readiness::Readable(files.raw_fd(self.fd))
+    }
 }
 
 impl<'arena, T: IoBufMut<'arena>> OpCode for ReadAt<'arena, T> {
@@ -41,9 +150,193 @@ impl<'arena, T: IoBufMut<'arena>> OpCode for ReadAt<'arena, T> {
         // SAFETY: slice into buffer is Unpin
         let slice = self.buffer.as_uninit_slice();
         apply_to_fd_or_fixed!(opcode::Read::new; self.fd, slice.as_mut_ptr() as _, slice.len() as _)
+            .offset(self.offset as _)
+            .rw_flags(rw_flags(self.rw_flags))
+            .build()
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        // SAFETY: slice into buffer is Unpin
+        let slice = self.buffer.as_uninit_slice();
+        let flags = rw_flags(self.rw_flags);
+        let res = if flags == 0 {
+            syscall!(pread(
+                fd,
+                slice.as_mut_ptr() as _,
+                slice.len() as _,
+                self.offset as _
+            ))
+        } else {
+            let iovec = libc::iovec {
+                iov_base: slice.as_mut_ptr() as _,
+                iov_len: slice.len(),
+            };
+            syscall!(preadv2(fd, &iovec, 1, self.offset as _, flags))
+        };
+        Some(res.map(|read| read as _))
+    }
+}
+
+impl<'arena, T: AsIoSlicesMut<'arena>> OpCode for ReadVectoredAtImpl<'arena, T> {
+    fn create_entry(&mut self) -> Entry {
+        // SAFETY: IoSliceMut is Unpin
+        let slices = unsafe { self.buffer.as_io_slices_mut() };
+        apply_to_fd_or_fixed!(opcode::Readv::new; self.fd, slices.as_mut_ptr() as _, slices.len() as _)
+            .offset(self.offset as _)
+            .build()
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        // SAFETY: IoSliceMut is Unpin
+        let slices = unsafe { self.buffer.as_io_slices_mut() };
+        let res = syscall!(preadv(
+            fd,
+            slices.as_mut_ptr() as _,
+            slices.len() as _,
+            self.offset as _
+        ));
+        Some(res.map(|read| read as _))
+    }
+}
+
+fn rw_flags(flags: RwFlags) -> types::RwFlags {
+    let mut rw_flags = 0;
+    if flags.contains(RwFlags::DSYNC) {
+        rw_flags |= libc::RWF_DSYNC;
+    }
+    if flags.contains(RwFlags::HIPRI) {
+        rw_flags |= libc::RWF_HIPRI;
+    }
+    if flags.contains(RwFlags::NOWAIT) {
+        rw_flags |= libc::RWF_NOWAIT;
+    }
+    rw_flags as _
+}
+
+/// Read a file at specified position into a registered buffer.
+pub struct ReadAtFixed<'arena> {
+    fd: FdOrFixed,
+    offset: usize,
+    buffer: FixedBuf<'arena>,
+}
+
+impl<'arena> ReadAtFixed<'arena> {
+    /// Create [`ReadAtFixed`].
+    pub fn new(
+        fd: impl IntoFdOrFixed<Target = FdOrFixed>,
+        offset: usize,
+        buffer: FixedBuf<'arena>,
+    ) -> Self {
+        Self {
+            fd: fd.into(),
+            offset,
+            buffer,
+        }
+    }
+}
+
+impl<'arena> IntoInner for ReadAtFixed<'arena> {
+    type Inner = FixedBuf<'arena>;
+
+    fn into_inner(self) -> Self::Inner {
+        self.buffer
+    }
+}
+
+impl OpCode for ReadAtFixed<'_> {
+    fn create_entry(&mut self) -> Entry {
+        let buf_index = self.buffer.index();
+        // SAFETY: slice into registered buffer is Unpin
+        let slice = self.buffer.as_uninit_slice();
+        apply_to_fd_or_fixed!(opcode::ReadFixed::new; self.fd, slice.as_mut_ptr() as _, slice.len() as _, buf_index)
             .offset(self.offset as _)
             .build()
     }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        // the buffers are not registered in the kernel
+        let slice = self.buffer.as_uninit_slice();
+        let res = syscall!(pread(
+            fd,
+            slice.as_mut_ptr() as _,
+            slice.len() as _,
+            self.offset as _
+        ));
+        Some(res.map(|read| read as _))
+    }
+}
+
+/// Write a file at specified position from a registered buffer.
+pub struct WriteAtFixed<'arena> {
+    fd: FdOrFixed,
+    offset: usize,
+    buffer: FixedBuf<'arena>,
+}
+
+impl<'arena> WriteAtFixed<'arena> {
+    /// Create [`WriteAtFixed`].
+    pub fn new(
+        fd: impl IntoFdOrFixed<Target = FdOrFixed>,
+        offset: usize,
+        buffer: FixedBuf<'arena>,
+    ) -> Self {
+        Self {
+            fd: fd.into(),
+            offset,
+            buffer,
+        }
+    }
+}
+
+impl<'arena> IntoInner for WriteAtFixed<'arena> {
+    type Inner = FixedBuf<'arena>;
+
+    fn into_inner(self) -> Self::Inner {
+        self.buffer
+    }
+}
+
+impl OpCode for WriteAtFixed<'_> {
+    fn create_entry(&mut self) -> Entry {
+        let buf_index = self.buffer.index();
+        // SAFETY: slice into registered buffer is Unpin
+        let slice = self.buffer.as_slice();
+        apply_to_fd_or_fixed!(opcode::WriteFixed::new; self.fd, slice.as_ptr(), slice.len() as _, buf_index)
+            .offset(self.offset as _)
+            .build()
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        // the buffers are not registered in the kernel
+        let slice = self.buffer.as_slice();
+        let res = syscall!(pwrite(
+            fd,
+            slice.as_ptr() as _,
+            slice.len() as _,
+            self.offset as _
+        ));
+        Some(res.map(|written| written as _))
+    }
 }
 
 impl<'arena, T: IoBuf<'arena>> OpCode for Write<'arena, T> {
@@ -52,6 +345,24 @@ impl<'arena, T: IoBuf<'arena>> OpCode for Write<'arena, T> {
         let slice = self.buffer.as_slice();
         apply_to_fd_or_fixed!(opcode::Write::new; self.fd, slice.as_ptr(), slice.len() as _).build()
     }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        // SAFETY: slice into buffer is Unpin
+        let slice = self.buffer.as_slice();
+        nonblocking(
+            fd,
+            || syscall!(maybe_block write(fd, slice.as_ptr() as _, slice.len() as _)),
+        )
+    }
+
+    fn epoll_readiness(&self, files: &EpollFiles) -> Readiness {
+        Readiness::Writable(files.raw_fd(self.fd))
+    }
 }
 
 impl<'arena, T: IoBuf<'arena>> OpCode for WriteAt<'arena, T> {
@@ -59,9 +370,63 @@ impl<'arena, T: IoBuf<'arena>> OpCode for WriteAt<'arena, T> {
         // SAFETY: slice into buffer is Unpin
         let slice = self.buffer.as_slice();
         apply_to_fd_or_fixed!(opcode::Write::new; self.fd, slice.as_ptr(), slice.len() as _)
+            .offset(self.offset as _)
+            .rw_flags(rw_flags(self.rw_flags))
+            .build()
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        // SAFETY: slice into buffer is Unpin
+        let slice = self.buffer.as_slice();
+        let flags = rw_flags(self.rw_flags);
+        let res = if flags == 0 {
+            syscall!(pwrite(
+                fd,
+                slice.as_ptr() as _,
+                slice.len() as _,
+                self.offset as _
+            ))
+        } else {
+            let iovec = libc::iovec {
+                iov_base: slice.as_ptr() as _,
+                iov_len: slice.len(),
+            };
+            syscall!(pwritev2(fd, &iovec, 1, self.offset as _, flags))
+        };
+        Some(res.map(|written| written as _))
+    }
+}
+
+impl<'arena, T: AsIoSlices<'arena>> OpCode for WriteVectoredAtImpl<'arena, T> {
+    fn create_entry(&mut self) -> Entry {
+        // SAFETY: IoSlice is Unpin
+        let slices = unsafe { self.buffer.as_io_slices() };
+        apply_to_fd_or_fixed!(opcode::Writev::new; self.fd, slices.as_ptr() as _, slices.len() as _)
             .offset(self.offset as _)
             .build()
     }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        // SAFETY: IoSlice is Unpin
+        let slices = unsafe { self.buffer.as_io_slices() };
+        let res = syscall!(pwritev(
+            fd,
+            slices.as_ptr() as _,
+            slices.len() as _,
+            self.offset as _
+        ));
+        Some(res.map(|written| written as _))
+    }
 }
 
 impl OpCode for Sync {
@@ -74,13 +439,527 @@ impl OpCode for Sync {
             })
             .build()
     }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        let res = if self.datasync {
+            syscall!(fdatasync(fd))
+        } else {
+            syscall!(fsync(fd))
+        };
+        Some(res.map(|_| 0))
+    }
+}
+
+fn sync_range_flags(flags: SyncRangeFlags) -> u32 {
+    let mut sync_flags = 0;
+    if flags.contains(SyncRangeFlags::WAIT_BEFORE) {
+        sync_flags |= libc::SYNC_FILE_RANGE_WAIT_BEFORE;
+    }
+    if flags.contains(SyncRangeFlags::WRITE) {
+        sync_flags |= libc::SYNC_FILE_RANGE_WRITE;
+    }
+    if flags.contains(SyncRangeFlags::WAIT_AFTER) {
+        sync_flags |= libc::SYNC_FILE_RANGE_WAIT_AFTER;
+    }
+    sync_flags
+}
+
+impl OpCode for SyncFileRange {
+    fn create_entry(&mut self) -> Entry {
+        apply_to_fd_or_fixed!(opcode::SyncFileRange::new; self.fd, self.nbytes)
+            .offset(self.offset)
+            .flags(sync_range_flags(self.flags))
+            .build()
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        let flags = sync_range_flags(self.flags);
+        let res = syscall!(sync_file_range(
+            fd,
+            self.offset as _,
+            self.nbytes as _,
+            flags as _
+        ));
+        Some(res.map(|_| 0))
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+}
+
+fn accept_flags(flags: AcceptFlags) -> i32 {
+    let mut accept_flags = 0;
+    if flags.contains(AcceptFlags::CLOEXEC) {
+        accept_flags |= libc::SOCK_CLOEXEC;
+    }
+    if flags.contains(AcceptFlags::NONBLOCK) {
+        accept_flags |= libc::SOCK_NONBLOCK;
+    }
+    accept_flags
 }
 
 impl OpCode for Accept {
     fn create_entry(&mut self) -> Entry {
         // SAFETY: buffer is Unpin
         let buf_pointer = self.addr.as_ptr() as *mut sockaddr;
-        apply_to_fd_or_fixed!(opcode::Accept::new; self.fd, buf_pointer, &mut self.addr_len).build()
+        apply_to_fd_or_fixed!(opcode::Accept::new; self.fd, buf_pointer, &mut self.addr_len)
+            .flags(accept_flags(self.flags))
+            .build()
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        // SAFETY: buffer is Unpin
+        let buf_pointer = self.addr.as_ptr() as *mut sockaddr;
+        let addr_len = &mut self.addr_len;
+        let flags = accept_flags(self.flags);
+        nonblocking(
+            fd,
+            || syscall!(maybe_block accept4(fd, buf_pointer, addr_len, flags)),
+        )
+    }
+
+    fn epoll_readiness(&self, files: &EpollFiles) -> Readiness {
+        Readiness::Readable(files.raw_fd(self.fd))
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+}
+
+/// Whether the kernel supports multishot accept. It is probed when a driver is created.
+pub(super) static ACCEPT_MULTISHOT_SUPPORTED: AtomicBool = AtomicBool::new(false);
+
+/// Accept connections continuously.
+///
+/// Every accepted connection completes with a separate [`Entry`](crate::driver::Entry) which
+/// result is the accepted socket. The entries have
+/// [`Entry::has_more`](crate::driver::Entry::has_more) set while the operation keeps
+/// accepting. The operation runs until it is cancelled or fails. epoll driver completes it with
+/// [`io::ErrorKind::Unsupported`].
+pub struct AcceptMultishot {
+    fd: FdOrFixed,
+}
+
+impl AcceptMultishot {
+    /// Create [`AcceptMultishot`].
+    pub fn new(fd: impl IntoFdOrFixed<Target = FdOrFixed>) -> Self {
+        Self { fd: fd.into() }
+    }
+
+    /// Returns `true` if the kernel supports multishot accept.
+    ///
+    /// The support is known after a driver is created.
+    pub fn is_supported() -> bool {
+        ACCEPT_MULTISHOT_SUPPORTED.load(Ordering::Relaxed)
+    }
+}
+
+impl OpCode for AcceptMultishot {
+    fn create_entry(&mut self) -> Entry {
+        apply_to_fd_or_fixed!(opcode::AcceptMulti::new; self.fd).build()
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+}
+
+/// Whether the kernel supports `IORING_OP_SOCKET`. It is probed when a driver is created.
+pub(super) static SOCKET_SUPPORTED: AtomicBool = AtomicBool::new(false);
+
+impl OpCode for CreateSocket {
+    fn create_entry(&mut self) -> Entry {
+        opcode::Socket::new(
+            self.domain.into(),
+            i32::from(self.ty) | libc::SOCK_CLOEXEC,
+            self.protocol.map_or(0, Into::into),
+        )
+        .build()
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        if SOCKET_SUPPORTED.load(Ordering::Relaxed) {
+            None
+        } else {
+            Some(self.create().map(|socket| socket.into_raw_fd() as _))
+        }
+    }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        Some(self.create().map(|socket| socket.into_raw_fd() as _))
+    }
+}
+
+impl OpCode for Bind {
+    fn create_entry(&mut self) -> Entry {
+        unreachable!("Bind operation completes before submission")
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(self.bind())
+    }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        Some(self.bind())
+    }
+}
+
+impl OpCode for Listen {
+    fn create_entry(&mut self) -> Entry {
+        unreachable!("Listen operation completes before submission")
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(self.listen())
+    }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        Some(self.listen())
+    }
+}
+
+/// Whether the kernel supports futex operations. It is probed when a driver is created.
+pub(super) static FUTEX_SUPPORTED: AtomicBool = AtomicBool::new(false);
+
+// futex2 flags of a private 32-bit futex
+const FUTEX2_SIZE_U32: u32 = 0x02;
+const FUTEX2_PRIVATE: u32 = 128;
+
+#[inline]
+fn futex_unsupported() -> Option<io::Result<usize>> {
+    if FUTEX_SUPPORTED.load(Ordering::Relaxed) {
+        None
+    } else {
+        Some(Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "futex operations are available since Linux 6.7",
+        )))
+    }
+}
+
+/// Wait on a futex till it is woken.
+///
+/// The operation completes with `0` when it is woken and with `EAGAIN` when the futex value
+/// doesn't equal the expected one. The futex is private to the process. Available since
+/// Linux 6.7, older kernels and epoll driver complete it with [`io::ErrorKind::Unsupported`].
+pub struct FutexWait {
+    futex: *const AtomicU32,
+    val: u32,
+    mask: u32,
+}
+
+impl FutexWait {
+    /// Create [`FutexWait`] waiting while `futex` equals `val`.
+    ///
+    /// Only wakes with the intersecting `mask` wake the operation, `u32::MAX` matches any wake.
+    ///
+    /// # Safety
+    ///
+    /// `futex` should outlive the operation.
+    pub unsafe fn new(futex: &AtomicU32, val: u32, mask: u32) -> Self {
+        Self { futex, val, mask }
+    }
+
+    /// Returns `true` if the kernel supports futex operations.
+    ///
+    /// The support is known after a driver is created.
+    pub fn is_supported() -> bool {
+        FUTEX_SUPPORTED.load(Ordering::Relaxed)
+    }
+}
+
+impl OpCode for FutexWait {
+    fn create_entry(&mut self) -> Entry {
+        opcode::FutexWait::new(
+            self.futex.cast(),
+            self.val.into(),
+            self.mask.into(),
+            FUTEX2_SIZE_U32 | FUTEX2_PRIVATE,
+        )
+        .build()
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        futex_unsupported()
+    }
+}
+
+/// Wake waiters of a futex.
+///
+/// The operation completes with the number of woken waiters. The futex is private to the
+/// process. Available since Linux 6.7, older kernels and epoll driver complete it with
+/// [`io::ErrorKind::Unsupported`].
+pub struct FutexWake {
+    futex: *const AtomicU32,
+    count: u32,
+    mask: u32,
+}
+
+impl FutexWake {
+    /// Create [`FutexWake`] waking up to `count` waiters of `futex`.
+    ///
+    /// Only waiters with the intersecting `mask` are woken, `u32::MAX` matches any waiter.
+    ///
+    /// # Safety
+    ///
+    /// `futex` should outlive the operation.
+    pub unsafe fn new(futex: &AtomicU32, count: u32, mask: u32) -> Self {
+        Self { futex, count, mask }
+    }
+
+    /// Returns `true` if the kernel supports futex operations.
+    ///
+    /// The support is known after a driver is created.
+    pub fn is_supported() -> bool {
+        FUTEX_SUPPORTED.load(Ordering::Relaxed)
+    }
+}
+
+impl OpCode for FutexWake {
+    fn create_entry(&mut self) -> Entry {
+        opcode::FutexWake::new(
+            self.futex.cast(),
+            self.count.into(),
+            self.mask.into(),
+            FUTEX2_SIZE_U32 | FUTEX2_PRIVATE,
+        )
+        .build()
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        futex_unsupported()
+    }
+}
+
+/// A change of an epoll interest list.
+pub enum EpollCtlOp {
+    /// Register the file descriptor with the event.
+    Add(libc::epoll_event),
+    /// Change the event of the registered file descriptor.
+    Modify(libc::epoll_event),
+    /// Deregister the file descriptor.
+    Delete,
+}
+
+/// Change an epoll interest list, like `epoll_ctl` syscall.
+///
+/// The operation owns the event while it is in flight.
+pub struct EpollCtl {
+    epfd: FdOrFixed,
+    fd: Fd,
+    op: i32,
+    event: libc::epoll_event,
+}
+
+impl EpollCtl {
+    /// Create [`EpollCtl`] changing the registration of `fd` in the epoll instance `epfd`.
+    pub fn new(epfd: impl IntoFdOrFixed<Target = FdOrFixed>, fd: Fd, op: EpollCtlOp) -> Self {
+        let (op, event) = match op {
+            EpollCtlOp::Add(event) => (libc::EPOLL_CTL_ADD, event),
+            EpollCtlOp::Modify(event) => (libc::EPOLL_CTL_MOD, event),
+            EpollCtlOp::Delete => (libc::EPOLL_CTL_DEL, libc::epoll_event { events: 0, u64: 0 }),
+        };
+        Self {
+            epfd: epfd.into(),
+            fd,
+            op,
+            event,
+        }
+    }
+}
+
+impl OpCode for EpollCtl {
+    fn create_entry(&mut self) -> Entry {
+        let fd = types::Fd(self.fd.as_raw_fd());
+        let event = &self.event as *const libc::epoll_event;
+        apply_to_fd_or_fixed!(opcode::EpollCtl::new; self.epfd, fd, self.op, event.cast()).build()
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.epfd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let epfd = files.raw_fd(self.epfd);
+        let res = syscall!(epoll_ctl(
+            epfd,
+            self.op,
+            self.fd.as_raw_fd(),
+            &mut self.event
+        ));
+        Some(res.map(|_| 0))
+    }
+}
+
+/// Replace registered files of a [`FixedFdSlot`] asynchronously.
+///
+/// Unlike [`CompleteIo::register_files_update`](crate::driver::CompleteIo::register_files_update)
+/// it never falls back to the synchronous registration, so the submissions are not stalled. The
+/// operation completes with the number of updated files. The operations pushed before could use
+/// either the old or the new files.
+///
+/// The fixed fds got from the slot before the update are stale when it completes, the ones got
+/// after it is created are valid since then. The driver fails the operations using a stale fixed
+/// fd with [`io::ErrorKind::InvalidInput`].
+pub struct FilesUpdate {
+    offset: u32,
+    fds: Vec<RawFd>,
+    // the updated slot with the generation of its fixed fds after the update
+    slot: FixedFdSlot,
+}
+
+impl FilesUpdate {
+    /// Create [`FilesUpdate`] replacing the files of `slot` from `index` with `fds`. `-1`
+    /// removes the registered file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::ErrorKind::InvalidInput`] if the updated files are out of the slot.
+    pub fn new(slot: &mut FixedFdSlot, index: usize, fds: Vec<RawFd>) -> io::Result<Self> {
+        if index + fds.len() > slot.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "updated files are out of the slot",
+            ));
+        }
+        slot.generation = slot.generation.wrapping_add(1);
+        Ok(Self {
+            offset: slot.offset + index as u32,
+            fds,
+            slot: FixedFdSlot {
+                offset: slot.offset,
+                len: slot.len,
+                generation: slot.generation,
+            },
+        })
+    }
+}
+
+impl IntoInner for FilesUpdate {
+    type Inner = Vec<RawFd>;
+
+    fn into_inner(self) -> Self::Inner {
+        self.fds
+    }
+}
+
+impl OpCode for FilesUpdate {
+    fn create_entry(&mut self) -> Entry {
+        opcode::FilesUpdate::new(self.fds.as_ptr(), self.fds.len() as _)
+            .offset(self.offset as _)
+            .build()
+    }
+
+    fn files_update(&self) -> Option<&FixedFdSlot> {
+        Some(&self.slot)
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        Some(files.update(&self.slot, self.offset, &self.fds))
+    }
+}
+
+/// Pass a file or device specific command to the driver of `fd`, like `ioctl(2)`.
+///
+/// The operation completes with the result of the command. Available since Linux 5.19, with
+/// NVMe character devices and since Linux 6.7 with sockets.
+///
+/// The ring has 64-byte submission entries, so the command payload is limited to 16 bytes and
+/// the extra data of 32-byte completion entries is not available. epoll driver completes it with
+/// [`io::ErrorKind::Unsupported`].
+pub struct UringCmd<'arena, T: IoBufMut<'arena>> {
+    fd: FdOrFixed,
+    cmd_op: u32,
+    cmd: [u8; 16],
+    buffer: Option<T>,
+    _lifetime: PhantomData<&'arena ()>,
+}
+
+impl<'arena, T: IoBufMut<'arena>> UringCmd<'arena, T> {
+    /// Create [`UringCmd`] with the command opcode `cmd_op` and the command payload `cmd`.
+    ///
+    /// The operation owns `buffer` till it completes, the payload could refer the buffer memory
+    /// which is not moved with the buffer.
+    ///
+    /// # Safety
+    ///
+    /// * `cmd` should have the layout the target driver expects for `cmd_op`.
+    /// * The memory referred by `cmd` should be valid till the operation completes, the references
+    ///   into `buffer` should stay within its capacity.
+    pub unsafe fn new(
+        fd: impl IntoFdOrFixed<Target = FdOrFixed>,
+        cmd_op: u32,
+        cmd: [u8; 16],
+        buffer: Option<T>,
+    ) -> Self {
+        Self {
+            fd: fd.into(),
+            cmd_op,
+            cmd,
+            buffer,
+            _lifetime: PhantomData,
+        }
+    }
+}
+
+impl<'arena, T: IoBufMut<'arena>> IntoInner for UringCmd<'arena, T> {
+    type Inner = Option<T>;
+
+    fn into_inner(self) -> Self::Inner {
+        self.buffer
+    }
+}
+
+impl<'arena, T: IoBufMut<'arena>> OpCode for UringCmd<'arena, T> {
+    fn create_entry(&mut self) -> Entry {
+        apply_to_fd_or_fixed!(opcode::UringCmd16::new; self.fd, self.cmd_op)
+            .cmd(self.cmd)
+            .build()
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+}
+
+/// Receive data continuously into buffers selected from a [`BufRing`](crate::driver::BufRing).
+///
+/// Every received message completes with a separate [`Entry`](crate::driver::Entry) which
+/// result is the number of received bytes and
+/// [`Entry::buffer_id`](crate::driver::Entry::buffer_id) is the id of the filled buffer. The
+/// operation finishes when the ring runs out of buffers, fails or is cancelled. Available since
+/// Linux 6.0, epoll driver completes it with [`io::ErrorKind::Unsupported`].
+pub struct RecvMultishot {
+    fd: FdOrFixed,
+    group_id: u16,
+}
+
+impl RecvMultishot {
+    /// Create [`RecvMultishot`] selecting buffers from the buffer group `group_id`.
+    pub fn new(fd: impl IntoFdOrFixed<Target = FdOrFixed>, group_id: u16) -> Self {
+        Self {
+            fd: fd.into(),
+            group_id,
+        }
+    }
+}
+
+impl OpCode for RecvMultishot {
+    fn create_entry(&mut self) -> Entry {
+        apply_to_fd_or_fixed!(opcode::RecvMulti::new; self.fd, self.group_id).build()
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
     }
 }
 
@@ -90,6 +969,353 @@ impl OpCode for Connect {
         apply_to_fd_or_fixed!(opcode::Connect::new; self.fd, self.addr.as_ptr(), self.addr.len())
             .build()
     }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        if self.initiated {
+            // the socket is writable when the connection in progress completes
+            let mut err: libc::c_int = 0;
+            let mut err_len = std::mem::size_of::<libc::c_int>() as libc::socklen_t;
+            let res = syscall!(getsockopt(
+                fd,
+                libc::SOL_SOCKET,
+                libc::SO_ERROR,
+                &mut err as *mut _ as *mut _,
+                &mut err_len
+            ));
+            return Some(res.and_then(|_| match err {
+                0 => Ok(0),
+                err => Err(io::Error::from_raw_os_error(err)),
+            }));
+        }
+        self.initiated = true;
+        let addr = &self.addr;
+        nonblocking(
+            fd,
+            || syscall!(maybe_block connect(fd, addr.as_ptr(), addr.len())),
+        )
+    }
+
+    fn epoll_readiness(&self, files: &EpollFiles) -> Readiness {
+        Readiness::Writable(files.raw_fd(self.fd))
+    }
+}
+
+impl OpCode for ShutdownSocket {
+    fn create_entry(&mut self) -> Entry {
+        apply_to_fd_or_fixed!(opcode::Shutdown::new; self.fd, self.how()).build()
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        Some(syscall!(shutdown(fd, self.how())).map(|_| 0))
+    }
+}
+
+impl OpCode for PollReadable {
+    fn create_entry(&mut self) -> Entry {
+        apply_to_fd_or_fixed!(opcode::PollAdd::new; self.fd, libc::POLLIN as _).build()
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        poll_fd(files.raw_fd(self.fd), libc::POLLIN)
+    }
+
+    fn epoll_readiness(&self, files: &EpollFiles) -> Readiness {
+        Readiness::Readable(files.raw_fd(self.fd))
+    }
+}
+
+impl OpCode for PollWritable {
+    fn create_entry(&mut self) -> Entry {
+        apply_to_fd_or_fixed!(opcode::PollAdd::new; self.fd, libc::POLLOUT as _).build()
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        poll_fd(files.raw_fd(self.fd), libc::POLLOUT)
+    }
+
+    fn epoll_readiness(&self, files: &EpollFiles) -> Readiness {
+        Readiness::Writable(files.raw_fd(self.fd))
+    }
+}
+
+impl OpCode for OpenAt {
+    fn create_entry(&mut self) -> Entry {
+        // SAFETY: CString heap buffer doesn't move
+        opcode::OpenAt::new(types::Fd(self.dirfd), self.path.as_ptr())
+            .flags(self.flags)
+            .mode(self.mode)
+            .build()
+    }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let res = syscall!(openat(
+            self.dirfd,
+            self.path.as_ptr(),
+            self.flags,
+            self.mode as libc::c_uint
+        ));
+        Some(res.map(|fd| fd as _))
+    }
+}
+
+/// Get file status.
+///
+/// The kernel writes the status into the buffer owned by the operation.
+pub struct Statx {
+    dirfd: RawFd,
+    path: CString,
+    flags: i32,
+    mask: u32,
+    statx: libc::statx,
+}
+
+impl Statx {
+    /// Create [`Statx`].
+    ///
+    /// `dirfd`, `path`, `flags` and `mask` have the same meaning as in `statx(2)`. Empty `path`
+    /// with `AT_EMPTY_PATH` flag gets the status of `dirfd` itself.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_STATX` operation.
+    /// * epoll: synchronized `statx` syscall.
+    /// * kqueue: it is synchronized `fstatat` syscall, or `fstat` when `path` is empty. `mask` is
+    ///   ignored.
+    pub fn new(dirfd: RawFd, path: CString, flags: i32, mask: u32) -> Self {
+        Self {
+            dirfd,
+            path,
+            flags,
+            mask,
+            // SAFETY: statx is a plain C struct
+            statx: unsafe { std::mem::zeroed() },
+        }
+    }
+
+    /// Get the file status filled by the completed operation.
+    pub fn statx(&self) -> &libc::statx {
+        &self.statx
+    }
+}
+
+impl OpCode for Statx {
+    fn create_entry(&mut self) -> Entry {
+        // SAFETY: CString heap buffer doesn't move, statx buffer is owned by the operation
+        opcode::Statx::new(
+            types::Fd(self.dirfd),
+            self.path.as_ptr(),
+            &mut self.statx as *mut libc::statx as *mut _,
+        )
+        .flags(self.flags)
+        .mask(self.mask)
+        .build()
+    }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let res = syscall!(statx(
+            self.dirfd,
+            self.path.as_ptr(),
+            self.flags,
+            self.mask,
+            &mut self.statx
+        ));
+        Some(res.map(|_| 0))
+    }
+}
+
+impl OpCode for MkdirAt {
+    fn create_entry(&mut self) -> Entry {
+        // SAFETY: CString heap buffer doesn't move
+        opcode::MkDirAt::new(types::Fd(self.dirfd), self.path.as_ptr())
+            .mode(self.mode)
+            .build()
+    }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        Some(syscall!(mkdirat(self.dirfd, self.path.as_ptr(), self.mode)).map(|_| 0))
+    }
+}
+
+impl OpCode for UnlinkAt {
+    fn create_entry(&mut self) -> Entry {
+        // SAFETY: CString heap buffer doesn't move
+        opcode::UnlinkAt::new(types::Fd(self.dirfd), self.path.as_ptr())
+            .flags(self.flags)
+            .build()
+    }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        Some(syscall!(unlinkat(self.dirfd, self.path.as_ptr(), self.flags)).map(|_| 0))
+    }
+}
+
+fn rename_flags(flags: RenameFlags) -> u32 {
+    if flags.contains(RenameFlags::NOREPLACE) {
+        libc::RENAME_NOREPLACE
+    } else {
+        0
+    }
+}
+
+impl OpCode for RenameAt {
+    fn create_entry(&mut self) -> Entry {
+        let flags = rename_flags(self.flags);
+        // SAFETY: CString heap buffers don't move
+        opcode::RenameAt::new(
+            types::Fd(self.old_dirfd),
+            self.old_path.as_ptr(),
+            types::Fd(self.new_dirfd),
+            self.new_path.as_ptr(),
+        )
+        .flags(flags)
+        .build()
+    }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let res = syscall!(renameat2(
+            self.old_dirfd,
+            self.old_path.as_ptr(),
+            self.new_dirfd,
+            self.new_path.as_ptr(),
+            rename_flags(self.flags)
+        ));
+        Some(res.map(|_| 0))
+    }
+}
+
+impl OpCode for SymlinkAt {
+    fn create_entry(&mut self) -> Entry {
+        // SAFETY: CString heap buffers don't move
+        opcode::SymlinkAt::new(
+            types::Fd(self.linkdir_fd),
+            self.target.as_ptr(),
+            self.linkpath.as_ptr(),
+        )
+        .build()
+    }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let res = syscall!(symlinkat(
+            self.target.as_ptr(),
+            self.linkdir_fd,
+            self.linkpath.as_ptr()
+        ));
+        Some(res.map(|_| 0))
+    }
+}
+
+impl OpCode for LinkAt {
+    fn create_entry(&mut self) -> Entry {
+        // SAFETY: CString heap buffers don't move
+        opcode::LinkAt::new(
+            types::Fd(self.old_dirfd),
+            self.old_path.as_ptr(),
+            types::Fd(self.new_dirfd),
+            self.new_path.as_ptr(),
+        )
+        .flags(self.flags)
+        .build()
+    }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let res = syscall!(linkat(
+            self.old_dirfd,
+            self.old_path.as_ptr(),
+            self.new_dirfd,
+            self.new_path.as_ptr(),
+            self.flags
+        ));
+        Some(res.map(|_| 0))
+    }
+}
+
+impl OpCode for Close {
+    fn create_entry(&mut self) -> Entry {
+        opcode::Close::new(types::Fd(self.fd)).build()
+    }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        Some(syscall!(close(self.fd)).map(|_| 0))
+    }
+}
+
+impl OpCode for Cancel {
+    fn create_entry(&mut self) -> Entry {
+        match &self.target {
+            CancelTarget::UserData(user_data) => opcode::AsyncCancel::new(*user_data as _).build(),
+            CancelTarget::Fd(fd) => {
+                let builder = with_fd_or_fixed!(fd, |target| types::CancelBuilder::fd(target));
+                opcode::AsyncCancel2::new(builder.all()).build()
+            }
+        }
+    }
+
+    fn as_cancel(&self) -> Option<&crate::op::Cancel> {
+        Some(self)
+    }
+}
+
+impl OpCode for MsgRing {
+    fn create_entry(&mut self) -> Entry {
+        opcode::MsgRingData::new(
+            types::Fd(self.ring_fd),
+            self.result,
+            self.user_data as _,
+            None,
+        )
+        .build()
+    }
+}
+
+impl OpCode for Nop {
+    fn create_entry(&mut self) -> Entry {
+        opcode::Nop::new().build()
+    }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        Some(Ok(0))
+    }
+}
+
+impl OpCode for WaitId {
+    fn create_entry(&mut self) -> Entry {
+        opcode::PollAdd::new(types::Fd(self.pidfd()), libc::POLLIN as _).build()
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        // the process could have changed the state already
+        match self.try_collect() {
+            Ok(false) => None,
+            Ok(true) => Some(Ok(0)),
+            Err(e) => Some(Err(e)),
+        }
+    }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        self.operate()
+    }
+
+    fn epoll_readiness(&self, _files: &EpollFiles) -> Readiness {
+        Readiness::Readable(self.pidfd())
+    }
 }
 
 impl<'arena, T: IoBufMut<'arena>> OpCode for Recv<'arena, T> {
@@ -108,8 +1334,30 @@ impl<'arena, T: IoBufMut<'arena>> OpCode for Recv<'arena, T> {
         // though the behavior is functionally identical, there is a performance
         // gain to be had that shows up tests like this."
         apply_to_fd_or_fixed!(opcode::Recv::new; self.fd, slice.as_mut_ptr() as _, slice.len() as _)
+            .flags(self.flags)
             .build()
     }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        // SAFETY: slice into buffer is Unpin
+        let slice = self.buffer.as_uninit_slice();
+        let res = syscall!(maybe_block recv(
+            fd,
+            slice.as_mut_ptr() as _,
+            slice.len() as _,
+            self.flags | libc::MSG_DONTWAIT
+        ));
+        dont_wait(self.flags, res)
+    }
+
+    fn epoll_readiness(&self, files: &EpollFiles) -> Readiness {
+        Readiness::Readable(files.raw_fd(self.fd))
+    }
 }
 
 impl<'arena, T: AsIoSlicesMut<'arena>> OpCode for RecvVectoredImpl<'arena, T> {
@@ -118,14 +1366,88 @@ impl<'arena, T: AsIoSlicesMut<'arena>> OpCode for RecvVectoredImpl<'arena, T> {
         let slices = unsafe { self.buffer.as_io_slices_mut() };
         apply_to_fd_or_fixed!(opcode::Readv::new; self.fd, slices.as_mut_ptr() as _, slices.len() as _).build()
     }
-}
 
-impl<'arena, T: IoBuf<'arena>> OpCode for Send<'arena, T> {
-    fn create_entry(&mut self) -> Entry {
-        // SAFETY: IoSlice is Unpin
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        // SAFETY: IoSliceMut is Unpin
+        let slices = unsafe { self.buffer.as_io_slices_mut() };
+        nonblocking(
+            fd,
+            || syscall!(maybe_block readv(fd, slices.as_mut_ptr() as _, slices.len() as _)),
+        )
+    }
+
+    fn epoll_readiness(&self, files: &EpollFiles) -> Readiness {
+        Readiness::Readable(files.raw_fd(self.fd))
+    }
+}
+
+impl<'arena, T: IoBuf<'arena>> OpCode for Send<'arena, T> {
+    fn create_entry(&mut self) -> Entry {
+        // SAFETY: IoSlice is Unpin
+        let slice = self.buffer.as_slice();
+        apply_to_fd_or_fixed!(opcode::Send::new; self.fd, slice.as_ptr() as _, slice.len() as _)
+            .flags(self.flags)
+            .build()
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        // SAFETY: slice into buffer is Unpin
+        let slice = self.buffer.as_slice();
+        let res = syscall!(maybe_block send(
+            fd,
+            slice.as_ptr() as _,
+            slice.len() as _,
+            self.flags | libc::MSG_DONTWAIT
+        ));
+        dont_wait(self.flags, res)
+    }
+
+    fn epoll_readiness(&self, files: &EpollFiles) -> Readiness {
+        Readiness::Writable(files.raw_fd(self.fd))
+    }
+}
+
+impl<'arena, T: IoBuf<'arena>> OpCode for SendZc<'arena, T> {
+    fn create_entry(&mut self) -> Entry {
+        // SAFETY: IoBuf is Unpin
+        let slice = self.buffer.as_slice();
+        if self.zero_copy {
+            apply_to_fd_or_fixed!(opcode::SendZc::new; self.fd, slice.as_ptr() as _, slice.len() as _)
+                .build()
+        } else {
+            apply_to_fd_or_fixed!(opcode::Send::new; self.fd, slice.as_ptr() as _, slice.len() as _)
+                .build()
+        }
+    }
+
+    fn request_notification(&mut self, capabilities: &DriverCapabilities) -> bool {
+        self.zero_copy = capabilities.send_zc;
+        self.zero_copy
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        // the data is copied, there is no notification
         let slice = self.buffer.as_slice();
-        apply_to_fd_or_fixed!(opcode::Send::new; self.fd, slice.as_ptr() as _, slice.len() as _)
-            .build()
+        syscall!(maybe_block send(fd, slice.as_ptr() as _, slice.len() as _, libc::MSG_DONTWAIT))
+    }
+
+    fn epoll_readiness(&self, files: &EpollFiles) -> Readiness {
+        Readiness::Writable(files.raw_fd(self.fd))
     }
 }
 
@@ -136,6 +1458,24 @@ impl<'arena, T: AsIoSlices<'arena>> OpCode for SendVectoredImpl<'arena, T> {
         apply_to_fd_or_fixed!(opcode::Writev::new; self.fd, slices.as_ptr() as _, slices.len() as _)
             .build()
     }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        // SAFETY: IoSlice is Unpin
+        let slices = unsafe { self.buffer.as_io_slices() };
+        nonblocking(
+            fd,
+            || syscall!(maybe_block writev(fd, slices.as_ptr() as _, slices.len() as _)),
+        )
+    }
+
+    fn epoll_readiness(&self, files: &EpollFiles) -> Readiness {
+        Readiness::Writable(files.raw_fd(self.fd))
+    }
 }
 
 // SendTo/RecvFrom opcodes are in progress - https://github.com/axboe/liburing/issues/397
@@ -153,6 +1493,17 @@ impl<'arena, T: IoBufMut<'arena>> RecvFrom<'arena, T> {
             inner: RecvMsgImpl::new(fd, BufWrapperMut::from(buffer)),
         }
     }
+
+    /// Set `MSG_*` flags of the `recvfrom` call, see [`Recv::with_flags`].
+    pub fn with_flags(mut self, flags: i32) -> Self {
+        self.inner = self.inner.with_flags(flags);
+        self
+    }
+
+    /// See [`RecvMsgImpl::msg_flags`].
+    pub fn msg_flags(&self) -> i32 {
+        self.inner.msg_flags()
+    }
 }
 
 impl<'arena, T: IoBufMut<'arena>> IntoInner for RecvFrom<'arena, T> {
@@ -168,14 +1519,45 @@ impl<'arena, T: IoBufMut<'arena>> OpCode for RecvFrom<'arena, T> {
     fn create_entry(&mut self) -> Entry {
         self.inner.create_entry()
     }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        self.inner.fixed_fds()
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        self.inner.epoll_operate(files)
+    }
+
+    fn epoll_readiness(&self, files: &EpollFiles) -> Readiness {
+        self.inner.epoll_readiness(files)
+    }
 }
 
 impl<'arena, T: AsIoSlicesMut<'arena>> OpCode for RecvMsgImpl<'arena, T> {
     #[allow(clippy::no_effect)]
     fn create_entry(&mut self) -> Entry {
         let fd = self.fd;
+        let flags = self.flags as _;
+        let msg = self.set_msg();
+        apply_to_fd_or_fixed!(opcode::RecvMsg::new; fd, msg as *mut _)
+            .flags(flags)
+            .build()
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        let flags = self.flags;
         let msg = self.set_msg();
-        apply_to_fd_or_fixed!(opcode::RecvMsg::new; fd, msg as *mut _).build()
+        let res = syscall!(maybe_block recvmsg(fd, msg, flags | libc::MSG_DONTWAIT));
+        dont_wait(flags, res)
+    }
+
+    fn epoll_readiness(&self, files: &EpollFiles) -> Readiness {
+        Readiness::Readable(files.raw_fd(self.fd))
     }
 }
 
@@ -191,6 +1573,12 @@ impl<'arena, T: IoBuf<'arena>> SendTo<'arena, T> {
             inner: SendMsgImpl::new(fd, BufWrapper::from(buffer), addr),
         }
     }
+
+    /// Set `MSG_*` flags of the `sendto` call, see [`Send::with_flags`].
+    pub fn with_flags(mut self, flags: i32) -> Self {
+        self.inner = self.inner.with_flags(flags);
+        self
+    }
 }
 
 impl<'arena, T: IoBuf<'arena>> IntoInner for SendTo<'arena, T> {
@@ -205,33 +1593,513 @@ impl<'arena, T: IoBuf<'arena>> OpCode for SendTo<'arena, T> {
     fn create_entry(&mut self) -> Entry {
         self.inner.create_entry()
     }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        self.inner.fixed_fds()
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        self.inner.epoll_operate(files)
+    }
+
+    fn epoll_readiness(&self, files: &EpollFiles) -> Readiness {
+        self.inner.epoll_readiness(files)
+    }
 }
 
 impl<'arena, T: AsIoSlices<'arena>> OpCode for SendMsgImpl<'arena, T> {
+    #[allow(clippy::no_effect)]
+    fn create_entry(&mut self) -> Entry {
+        let fd = self.fd;
+        let flags = self.flags as _;
+        let msg = self.set_msg();
+        apply_to_fd_or_fixed!(opcode::SendMsg::new; fd, msg)
+            .flags(flags)
+            .build()
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        let flags = self.flags;
+        let msg = self.set_msg();
+        let res = syscall!(maybe_block sendmsg(fd, msg, flags | libc::MSG_DONTWAIT));
+        dont_wait(flags, res)
+    }
+
+    fn epoll_readiness(&self, files: &EpollFiles) -> Readiness {
+        Readiness::Writable(files.raw_fd(self.fd))
+    }
+}
+
+impl<'arena, T: AsIoSlicesMut<'arena>, C: IoBufMut<'arena>> OpCode for RecvMsg<'arena, T, C> {
+    #[allow(clippy::no_effect)]
+    fn create_entry(&mut self) -> Entry {
+        let fd = self.fd;
+        let flags = self.flags() as _;
+        let msg = self.set_msg();
+        apply_to_fd_or_fixed!(opcode::RecvMsg::new; fd, msg as *mut _)
+            .flags(flags)
+            .build()
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        let flags = self.flags();
+        let msg = self.set_msg();
+        syscall!(maybe_block recvmsg(fd, msg, flags | libc::MSG_DONTWAIT))
+    }
+
+    fn epoll_readiness(&self, files: &EpollFiles) -> Readiness {
+        Readiness::Readable(files.raw_fd(self.fd))
+    }
+}
+
+impl<'arena, T: AsIoSlices<'arena>, C: IoBuf<'arena>> OpCode for SendMsg<'arena, T, C> {
     #[allow(clippy::no_effect)]
     fn create_entry(&mut self) -> Entry {
         let fd = self.fd;
         let msg = self.set_msg();
         apply_to_fd_or_fixed!(opcode::SendMsg::new; fd, msg).build()
     }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        let msg = self.set_msg();
+        syscall!(maybe_block sendmsg(fd, msg, libc::MSG_DONTWAIT))
+    }
+
+    fn epoll_readiness(&self, files: &EpollFiles) -> Readiness {
+        Readiness::Writable(files.raw_fd(self.fd))
+    }
+}
+
+/// Move data between two file descriptors without copying between kernel address space and user
+/// address space.
+///
+/// At least one of the file descriptors should refer to a pipe.
+pub struct Splice {
+    fd_in: FdOrFixed,
+    off_in: Option<u64>,
+    fd_out: FdOrFixed,
+    off_out: Option<u64>,
+    len: u32,
+    flags: u32,
+}
+
+impl Splice {
+    /// Create [`Splice`].
+    ///
+    /// Offsets should be `None` for pipes. `flags` are `SPLICE_F_*` flags described in
+    /// `splice(2)`.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_SPLICE` operation.
+    /// * epoll: `splice` syscall with `SPLICE_F_NONBLOCK` flag, it waits till the input is readable
+    ///   and then till the output is writable.
+    /// * kqueue: it is emulated with read and write through an intermediate buffer. Flags are
+    ///   ignored.
+    pub fn new(
+        fd_in: impl IntoFdOrFixed<Target = FdOrFixed>,
+        off_in: Option<u64>,
+        fd_out: impl IntoFdOrFixed<Target = FdOrFixed>,
+        off_out: Option<u64>,
+        len: u32,
+        flags: u32,
+    ) -> Self {
+        Self {
+            fd_in: fd_in.into(),
+            off_in,
+            fd_out: fd_out.into(),
+            off_out,
+            len,
+            flags,
+        }
+    }
+}
+
+impl OpCode for Splice {
+    fn create_entry(&mut self) -> Entry {
+        const NO_OFFSET: i64 = -1;
+
+        let off_in = self.off_in.map_or(NO_OFFSET, |off| off as _);
+        let off_out = self.off_out.map_or(NO_OFFSET, |off| off as _);
+        with_fd_or_fixed!(self.fd_in, |fd_in| {
+            with_fd_or_fixed!(self.fd_out, |fd_out| {
+                opcode::Splice::new(fd_in, off_in, fd_out, off_out, self.len)
+            })
+        })
+        .flags(self.flags)
+        .build()
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd_in.fixed(), self.fd_out.fixed()]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let (fd_in, fd_out) = (files.raw_fd(self.fd_in), files.raw_fd(self.fd_out));
+        // the offsets are not updated as in io-uring
+        let mut off_in = self.off_in.map(|off| off as i64);
+        let mut off_out = self.off_out.map(|off| off as i64);
+        let (len, flags) = (self.len as usize, self.flags | libc::SPLICE_F_NONBLOCK);
+        nonblocking(fd_in, || {
+            nonblocking(fd_out, || {
+                syscall!(maybe_block splice(
+                    fd_in,
+                    offset_ptr(&mut off_in),
+                    fd_out,
+                    offset_ptr(&mut off_out),
+                    len,
+                    flags
+                ))
+            })
+        })
+    }
+
+    fn epoll_readiness(&self, files: &EpollFiles) -> Readiness {
+        pipe_readiness(files.raw_fd(self.fd_in), files.raw_fd(self.fd_out))
+    }
+}
+
+impl OpCode for Tee {
+    fn create_entry(&mut self) -> Entry {
+        with_fd_or_fixed!(self.fd_in, |fd_in| {
+            with_fd_or_fixed!(self.fd_out, |fd_out| {
+                opcode::Tee::new(fd_in, fd_out, self.len)
+            })
+        })
+        .flags(self.flags)
+        .build()
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd_in.fixed(), self.fd_out.fixed()]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let (fd_in, fd_out) = (files.raw_fd(self.fd_in), files.raw_fd(self.fd_out));
+        let flags = self.flags | libc::SPLICE_F_NONBLOCK;
+        syscall!(maybe_block tee(fd_in, fd_out, self.len as _, flags))
+    }
+
+    fn epoll_readiness(&self, files: &EpollFiles) -> Readiness {
+        pipe_readiness(files.raw_fd(self.fd_in), files.raw_fd(self.fd_out))
+    }
+}
+
+fn allocate_mode(mode: AllocateMode) -> i32 {
+    let mut allocate_mode = 0;
+    if mode.contains(AllocateMode::KEEP_SIZE) {
+        allocate_mode |= libc::FALLOC_FL_KEEP_SIZE;
+    }
+    if mode.contains(AllocateMode::PUNCH_HOLE) {
+        // Linux requires to combine punch hole with keep size
+        allocate_mode |= libc::FALLOC_FL_PUNCH_HOLE | libc::FALLOC_FL_KEEP_SIZE;
+    }
+    allocate_mode
+}
+
+impl OpCode for Fallocate {
+    fn create_entry(&mut self) -> Entry {
+        apply_to_fd_or_fixed!(opcode::Fallocate::new; self.fd, self.len)
+            .offset(self.offset)
+            .mode(allocate_mode(self.mode))
+            .build()
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        let res = syscall!(fallocate(
+            fd,
+            allocate_mode(self.mode),
+            self.offset as _,
+            self.len as _
+        ));
+        Some(res.map(|_| 0))
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+}
+
+impl OpCode for Fadvise {
+    fn create_entry(&mut self) -> Entry {
+        apply_to_fd_or_fixed!(opcode::Fadvise::new; self.fd, self.len as _, self.posix_advice())
+            .offset(self.offset)
+            .build()
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.fd.fixed(), None]
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let fd = files.raw_fd(self.fd);
+        // SAFETY: posix_fadvise doesn't access memory
+        let errno = unsafe {
+            libc::posix_fadvise(fd, self.offset as _, self.len as _, self.posix_advice())
+        };
+        // the error is returned instead of setting errno
+        Some(match errno {
+            0 => Ok(0),
+            errno => Err(io::Error::from_raw_os_error(errno)),
+        })
+    }
+}
+
+impl OpCode for Madvise {
+    fn create_entry(&mut self) -> Entry {
+        let advice = self.madvise_advice().expect("checked in operate");
+        opcode::Madvise::new(self.addr as *const _, self.len as _, advice).build()
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        // complete unsupported advice without submission
+        self.madvise_advice().err().map(Err)
+    }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let res = self
+            .madvise_advice()
+            .and_then(|advice| syscall!(madvise(self.addr as _, self.len, advice)));
+        Some(res.map(|_| 0))
+    }
+}
+
+impl OpCode for Truncate {
+    fn create_entry(&mut self) -> Entry {
+        unreachable!("Truncate operation completes before submission")
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(self.ftruncate())
+    }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        Some(self.ftruncate())
+    }
+}
+
+impl<'arena, T: IoBufMut<'arena>> OpCode for GetXattr<'arena, T> {
+    fn create_entry(&mut self) -> Entry {
+        unreachable!("GetXattr operation completes before submission")
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(self.get())
+    }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        Some(self.get())
+    }
+}
+
+impl<'arena, T: IoBuf<'arena>> OpCode for SetXattr<'arena, T> {
+    fn create_entry(&mut self) -> Entry {
+        unreachable!("SetXattr operation completes before submission")
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(self.set())
+    }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        Some(self.set())
+    }
+}
+
+impl OpCode for CopyFileRange {
+    fn create_entry(&mut self) -> Entry {
+        unreachable!("CopyFileRange operation completes before submission")
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(self.copy_file_range())
+    }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        Some(self.copy_file_range())
+    }
+}
+
+enum SendFileStage {
+    FileToPipe,
+    PipeToSocket { in_pipe: usize },
+    // epoll driver sends the file without the pipe
+    Direct,
+}
+
+/// Send a file region to a socket.
+///
+/// io-uring driver splices the file into an internal pipe and then the pipe into the socket, epoll
+/// driver uses `sendfile` syscall. The operation should be resubmitted till
+/// [`SendFile::on_transfer`] reports completion.
+pub struct SendFile {
+    file_fd: FdOrFixed,
+    socket_fd: FdOrFixed,
+    offset: u64,
+    len: usize,
+    sent: usize,
+    stage: SendFileStage,
+    pipe_rx: OwnedFd,
+    pipe_tx: OwnedFd,
+}
+
+impl SendFile {
+    /// Create [`SendFile`] to send `len` bytes of the file starting from `offset`.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: splices through an internal pipe owned by the operation.
+    /// * epoll: `sendfile` syscall, the pipe is not used.
+    /// * kqueue: `sendfile` syscall.
+    /// * IOCP: `TransmitFile`.
+    pub fn new(
+        file_fd: impl IntoFdOrFixed<Target = FdOrFixed>,
+        socket_fd: impl IntoFdOrFixed<Target = FdOrFixed>,
+        offset: u64,
+        len: usize,
+    ) -> io::Result<Self> {
+        let mut fds = [0; 2];
+        syscall!(pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC))?;
+        // SAFETY: pipe2 returned new file descriptors
+        let (pipe_rx, pipe_tx) =
+            unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+        Ok(Self {
+            file_fd: file_fd.into(),
+            socket_fd: socket_fd.into(),
+            offset,
+            len,
+            sent: 0,
+            stage: SendFileStage::FileToPipe,
+            pipe_rx,
+            pipe_tx,
+        })
+    }
+
+    /// Post operation handling.
+    ///
+    /// Returns `true` when `len` bytes were sent or the end of file is reached.
+    pub fn on_transfer(&mut self, result: io::Result<usize>) -> io::Result<bool> {
+        let transferred = result?;
+        match self.stage {
+            SendFileStage::FileToPipe => {
+                if transferred == 0 {
+                    return Ok(true);
+                }
+                self.stage = SendFileStage::PipeToSocket {
+                    in_pipe: transferred,
+                };
+            }
+            SendFileStage::PipeToSocket { in_pipe } => {
+                if transferred == 0 {
+                    return Err(io::ErrorKind::WriteZero.into());
+                }
+                self.sent += transferred;
+                let in_pipe = in_pipe - transferred;
+                self.stage = if in_pipe == 0 {
+                    SendFileStage::FileToPipe
+                } else {
+                    SendFileStage::PipeToSocket { in_pipe }
+                };
+            }
+            SendFileStage::Direct => {
+                if transferred == 0 {
+                    return Ok(true);
+                }
+                self.sent += transferred;
+            }
+        }
+        Ok(self.sent == self.len)
+    }
+
+    /// Bytes sent to the socket so far.
+    pub fn sent(&self) -> usize {
+        self.sent
+    }
+}
+
+impl OpCode for SendFile {
+    fn create_entry(&mut self) -> Entry {
+        const NO_OFFSET: i64 = -1;
+
+        match self.stage {
+            SendFileStage::FileToPipe => {
+                let len = u32::try_from(self.len - self.sent).unwrap_or(u32::MAX);
+                let offset = (self.offset + self.sent as u64) as i64;
+                let pipe_tx = types::Fd(self.pipe_tx.as_raw_fd());
+                with_fd_or_fixed!(self.file_fd, |file_fd| {
+                    opcode::Splice::new(file_fd, offset, pipe_tx, NO_OFFSET, len)
+                })
+                .build()
+            }
+            SendFileStage::PipeToSocket { in_pipe } => {
+                let pipe_rx = types::Fd(self.pipe_rx.as_raw_fd());
+                with_fd_or_fixed!(self.socket_fd, |socket_fd| {
+                    opcode::Splice::new(pipe_rx, NO_OFFSET, socket_fd, NO_OFFSET, in_pipe as _)
+                })
+                .build()
+            }
+            SendFileStage::Direct => unreachable!("only epoll driver sends the file directly"),
+        }
+    }
+
+    fn epoll_operate(&mut self, files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let (file_fd, socket_fd) = (files.raw_fd(self.file_fd), files.raw_fd(self.socket_fd));
+        self.stage = SendFileStage::Direct;
+        let mut offset = (self.offset + self.sent as u64) as libc::off_t;
+        let len = self.len - self.sent;
+        nonblocking(
+            socket_fd,
+            || syscall!(maybe_block sendfile(socket_fd, file_fd, &mut offset, len)),
+        )
+    }
+
+    fn epoll_readiness(&self, files: &EpollFiles) -> Readiness {
+        Readiness::Writable(files.raw_fd(self.socket_fd))
+    }
+
+    fn fixed_fds(&self) -> [Option<FixedFd>; 2] {
+        [self.file_fd.fixed(), self.socket_fd.fixed()]
+    }
 }
 
-/// Timeout operation completes after the given relative timeout duration.
+/// Timeout operation completes after the given relative timeout duration or at the absolute
+/// deadline.
 ///
 /// If supported by platform timeout operation will take into account the time
 /// spent in low power modes or suspend (CLOCK_BOOTTIME). Otherwise
 /// CLOCK_MONOTONIC is used.
 ///
 /// Only io_uring driver supports waiting using CLOCK_BOOTTIME clock.
+///
+/// The expired timeout completes with `Ok(0)`.
 #[cfg(feature = "time")]
-#[repr(transparent)]
 pub struct Timeout {
     timespec: Timespec,
+    flags: TimeoutFlags,
+    // epoll driver runs the timer in user space
+    delay: Duration,
+    deadline: Option<Instant>,
 }
 
 #[cfg(feature = "time")]
 impl Timeout {
-    // ETIME_SUCCESS seems not to work on Linux 5.15
+    // ETIME_SUCCESS seems not to work on Linux 5.15, the driver translates ETIME
     const FLAGS: TimeoutFlags = unsafe {
         TimeoutFlags::from_bits_unchecked(
             TimeoutFlags::BOOTTIME.bits() | TimeoutFlags::ETIME_SUCCESS.bits(),
@@ -241,7 +2109,33 @@ impl Timeout {
     /// Create `Timeout` with the provided duration.
     pub fn new(delay: Duration) -> Self {
         let timespec = Timespec::from(delay);
-        Self { timespec }
+        Self {
+            timespec,
+            flags: Self::FLAGS,
+            delay,
+            deadline: None,
+        }
+    }
+
+    /// Create `Timeout` completing at `deadline`.
+    ///
+    /// Unlike [`Timeout::new`] the time the operation waits for the submission is not added to
+    /// the timeout.
+    pub fn at(deadline: Instant) -> Self {
+        let delay = deadline.saturating_duration_since(Instant::now());
+        // `Instant` uses CLOCK_BOOTTIME on Linux
+        let mut now = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        syscall!(clock_gettime(libc::CLOCK_BOOTTIME, &mut now)).expect("boottime clock exists");
+        let now = Duration::new(now.tv_sec as _, now.tv_nsec as _);
+        Self {
+            timespec: Timespec::from(now + delay),
+            flags: Self::FLAGS | TimeoutFlags::ABS,
+            delay,
+            deadline: Some(deadline),
+        }
     }
 }
 
@@ -249,9 +2143,94 @@ impl Timeout {
 impl OpCode for Timeout {
     fn create_entry(&mut self) -> Entry {
         opcode::Timeout::new(&self.timespec as *const Timespec)
-            .flags(Self::FLAGS)
+            .flags(self.flags)
             .build()
     }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        Some(Ok(TIMER_PENDING))
+    }
+
+    fn timer_delay(&self) -> Duration {
+        self.deadline.map_or(self.delay, |deadline| {
+            deadline.saturating_duration_since(Instant::now())
+        })
+    }
+}
+
+/// Update the delay of the pending [`Timeout`] operation with `user_data`.
+///
+/// The operation completes with the not found error if the timeout has already completed.
+#[cfg(feature = "time")]
+pub struct TimeoutUpdate {
+    user_data: usize,
+    timespec: Timespec,
+    delay: Duration,
+}
+
+#[cfg(feature = "time")]
+impl TimeoutUpdate {
+    /// Create `TimeoutUpdate` with the new delay counted from the time it is submitted.
+    pub fn new(user_data: usize, delay: Duration) -> Self {
+        let timespec = Timespec::from(delay);
+        Self {
+            user_data,
+            timespec,
+            delay,
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+impl OpCode for TimeoutUpdate {
+    fn create_entry(&mut self) -> Entry {
+        // the updated timeout keeps its clock
+        opcode::TimeoutUpdate::new(self.user_data as _, &self.timespec as *const Timespec).build()
+    }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        Some(Ok(TIMER_UPDATE_PENDING))
+    }
+
+    fn timer_delay(&self) -> Duration {
+        self.delay
+    }
+
+    fn timer_target(&self) -> usize {
+        self.user_data
+    }
+}
+
+/// Remove the pending [`Timeout`] operation with `user_data`.
+///
+/// The removed timeout completes with the canceled error. The operation completes with the not
+/// found error if the timeout has already completed.
+#[cfg(feature = "time")]
+pub struct TimeoutRemove {
+    user_data: usize,
+}
+
+#[cfg(feature = "time")]
+impl TimeoutRemove {
+    /// Create `TimeoutRemove`.
+    pub fn new(user_data: usize) -> Self {
+        Self { user_data }
+    }
+}
+
+#[cfg(feature = "time")]
+impl OpCode for TimeoutRemove {
+    fn create_entry(&mut self) -> Entry {
+        opcode::TimeoutRemove::new(self.user_data as _).build()
+    }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        Some(Ok(TIMER_REMOVE_PENDING))
+    }
+
+    fn timer_target(&self) -> usize {
+        self.user_data
+    }
 }
 
 /// Close attached file descriptor.
@@ -259,6 +2238,10 @@ impl OpCode for Fd {
     fn create_entry(&mut self) -> Entry {
         opcode::Close::new(types::Fd(self.as_raw_fd())).build()
     }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        Some(syscall!(close(self.as_raw_fd())).map(|_| 0))
+    }
 }
 
 /// Close some file or socket and set it to None.
@@ -274,4 +2257,11 @@ impl<T: IntoRawFd> OpCode for Option<T> {
             panic!("Option<T: IntoRawFd> is None. Can't create Close operation.")
         }
     }
+
+    fn epoll_operate(&mut self, _files: &mut EpollFiles) -> Option<io::Result<usize>> {
+        let into_raw_fd = self
+            .take()
+            .expect("Option<T: IntoRawFd> is None. Can't close it.");
+        Some(syscall!(close(into_raw_fd.into_raw_fd())).map(|_| 0))
+    }
 }
@@ -0,0 +1,247 @@
+/// Thread pool performing the file operations which block instead of waiting for readiness
+use std::{
+    collections::VecDeque,
+    io,
+    os::fd::OwnedFd,
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    thread,
+    time::Instant,
+};
+
+use crate::driver::OpCode;
+
+// the ident of the user event triggered when the pool completes an operation
+#[cfg(any(
+    target_vendor = "apple",
+    target_os = "freebsd",
+    target_os = "dragonfly"
+))]
+pub(super) const POOL_IDENT: usize = usize::MAX - 1;
+
+// the operation borrowed by the driver till the pool returns its result
+struct OpPtr(*mut (dyn OpCode + 'static));
+
+// SAFETY: the driver doesn't access the operation while it's queued or performed by the pool
+unsafe impl Send for OpPtr {}
+
+struct Job {
+    user_data: usize,
+    op: OpPtr,
+}
+
+#[derive(Default)]
+struct State {
+    jobs: VecDeque<Job>,
+    // the result is `None` if the operation would block
+    completed: Vec<(usize, Option<io::Result<usize>>)>,
+    running: usize,
+    idle: usize,
+    shutdown: bool,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    job_queued: Condvar,
+    job_done: Condvar,
+    // the kqueue of the driver to wake it up
+    kqueue: OwnedFd,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // the operations are performed without the lock, so it isn't poisoned
+        self.state.lock().expect("not poisoned")
+    }
+}
+
+/// Threads performing the blocking operations of the driver.
+///
+/// The threads are spawned on demand up to the limit and live till the pool is dropped. The
+/// queued operations are performed in parallel, so the operations on the same file are not
+/// ordered.
+pub(super) struct BlockingPool {
+    shared: Arc<Shared>,
+    max_threads: usize,
+    threads: Vec<thread::JoinHandle<()>>,
+}
+
+impl BlockingPool {
+    /// The pool is disabled if there are no threads or kqueue doesn't support user events.
+    pub(super) fn new(kqueue: OwnedFd, max_threads: usize) -> Self {
+        #[cfg(not(any(
+            target_vendor = "apple",
+            target_os = "freebsd",
+            target_os = "dragonfly"
+        )))]
+        let max_threads = {
+            let _ = max_threads;
+            0
+        };
+        Self {
+            shared: Arc::new(Shared {
+                state: Mutex::default(),
+                job_queued: Condvar::new(),
+                job_done: Condvar::new(),
+                kqueue,
+            }),
+            max_threads,
+            threads: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub(super) fn is_enabled(&self) -> bool {
+        self.max_threads > 0
+    }
+
+    /// Queue the operation, returns `false` if there are no threads to perform it.
+    ///
+    /// # Safety
+    ///
+    /// The operation should be alive and not accessed till its result is taken.
+    pub(super) unsafe fn spawn(&mut self, user_data: usize, op: *mut (dyn OpCode + '_)) -> bool {
+        let mut state = self.shared.lock();
+        if state.jobs.len() >= state.idle && self.threads.len() < self.max_threads {
+            let shared = self.shared.clone();
+            match thread::Builder::new()
+                .name("completeio-blocking".into())
+                .spawn(move || work(&shared))
+            {
+                Ok(thread) => self.threads.push(thread),
+                Err(_) if self.threads.is_empty() => return false,
+                // the running threads perform the operation later
+                Err(_) => {}
+            }
+        }
+        // SAFETY: the caller guarantees the operation outlives the job
+        let op = std::mem::transmute::<*mut (dyn OpCode + '_), *mut (dyn OpCode + 'static)>(op);
+        state.jobs.push_back(Job {
+            user_data,
+            op: OpPtr(op),
+        });
+        drop(state);
+        self.shared.job_queued.notify_one();
+        true
+    }
+
+    /// Remove the queued operation, returns `false` if it's performed already.
+    pub(super) fn cancel(&self, user_data: usize) -> bool {
+        let mut state = self.shared.lock();
+        match state.jobs.iter().position(|job| job.user_data == user_data) {
+            Some(pos) => {
+                state.jobs.remove(pos);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Take the results of the performed operations.
+    pub(super) fn take_completed(&self) -> Vec<(usize, Option<io::Result<usize>>)> {
+        std::mem::take(&mut self.shared.lock().completed)
+    }
+
+    /// Remove the queued operations and wait for the running ones till `deadline`.
+    ///
+    /// The results of the operations are discarded.
+    pub(super) fn clear(&self, deadline: Option<Instant>) -> io::Result<()> {
+        let mut state = self.shared.lock();
+        state.jobs.clear();
+        while state.running > 0 {
+            state = match deadline {
+                Some(deadline) => {
+                    let left = deadline.saturating_duration_since(Instant::now());
+                    if left.is_zero() {
+                        return Err(io::ErrorKind::TimedOut.into());
+                    }
+                    self.shared
+                        .job_done
+                        .wait_timeout(state, left)
+                        .expect("not poisoned")
+                        .0
+                }
+                None => self.shared.job_done.wait(state).expect("not poisoned"),
+            };
+        }
+        state.completed.clear();
+        Ok(())
+    }
+}
+
+impl Drop for BlockingPool {
+    fn drop(&mut self) {
+        self.shared.lock().shutdown = true;
+        self.shared.job_queued.notify_all();
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn work(shared: &Shared) {
+    let mut state = shared.lock();
+    loop {
+        if let Some(job) = state.jobs.pop_front() {
+            state.running += 1;
+            drop(state);
+            // SAFETY: the driver keeps the operation alive till the result is taken
+            let res = catch_unwind(AssertUnwindSafe(|| unsafe { (*job.op.0).operate() }))
+                .unwrap_or_else(|_| Some(Err(io::Error::other("operation panicked"))));
+            state = shared.lock();
+            state.running -= 1;
+            state.completed.push((job.user_data, res));
+            drop(state);
+            shared.job_done.notify_all();
+            wake_driver(&shared.kqueue);
+            state = shared.lock();
+        } else if state.shutdown {
+            return;
+        } else {
+            state.idle += 1;
+            state = shared.job_queued.wait(state).expect("not poisoned");
+            state.idle -= 1;
+        }
+    }
+}
+
+#[cfg(any(
+    target_vendor = "apple",
+    target_os = "freebsd",
+    target_os = "dragonfly"
+))]
+fn wake_driver(kqueue: &OwnedFd) {
+    use std::{os::fd::AsFd, time::Duration};
+
+    use rustix::event::kqueue::{
+        kevent, Event, EventFilter, EventFlags, UserDefinedFlags, UserFlags,
+    };
+
+    let event = Event::new(
+        EventFilter::User {
+            ident: POOL_IDENT as _,
+            flags: UserFlags::TRIGGER,
+            user_flags: UserDefinedFlags::new(0),
+        },
+        EventFlags::ADD | EventFlags::CLEAR,
+        0,
+    );
+    // the driver takes the results on the next wait anyway
+    let _ = unsafe {
+        kevent(
+            kqueue.as_fd(),
+            &[event],
+            &mut Vec::new(),
+            Some(Duration::ZERO),
+        )
+    };
+}
+
+#[cfg(not(any(
+    target_vendor = "apple",
+    target_os = "freebsd",
+    target_os = "dragonfly"
+)))]
+fn wake_driver(_kqueue: &OwnedFd) {
+    unreachable!("the pool is disabled without user events")
+}
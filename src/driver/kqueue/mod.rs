@@ -2,7 +2,15 @@
 use std::alloc::Allocator;
 #[doc(no_inline)]
 pub use std::os::fd::{AsFd, AsRawFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
-use std::{collections::VecDeque, convert::identity, fmt, io, marker::PhantomData, time::Duration};
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::identity,
+    fmt,
+    io::{self, IoSliceMut},
+    marker::PhantomData,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use bit_set::BitSet;
 use rustix::event::kqueue::{kevent, kqueue, Event, EventFilter, EventFlags};
@@ -10,12 +18,24 @@ use rustix::event::kqueue::{kevent, kqueue, Event, EventFilter, EventFlags};
 #[cfg(feature = "time")]
 use crate::driver::time::TimerWheel;
 use crate::{
-    driver::{unix::IntoFdOrFixed, CompleteIo, Entry, OpObject, Operation},
+    driver::{
+        link::{canceled_entry, Links},
+        op::CancelTarget,
+        op_name,
+        op_timeout::OpTimeouts,
+        unix::IntoFdOrFixed,
+        BackendKind, CancelError, CompleteIo, CompletionIter, CountCompleted, DriverBuilder,
+        DriverCapabilities, DriverStats, Entry, FixedBufRegistry, Hooks, OpObject, Operation,
+        PushError, UserDataSet,
+    },
     vec_deque_alloc,
 };
 
+mod blocking;
 pub(crate) mod op;
 
+use blocking::BlockingPool;
+
 /// Attached file descriptor.
 ///
 /// Can't be moved between threads.
@@ -25,6 +45,87 @@ pub struct Fd {
     _not_send_not_sync: PhantomData<*const ()>,
 }
 
+/// A handle of [`Driver`] to post completions into it with [`MsgRing`](crate::op::MsgRing).
+///
+/// The handle owns a duplicate of the kqueue fd and could be sent to other threads.
+#[derive(Debug)]
+pub struct DriverHandle {
+    fd: OwnedFd,
+}
+
+impl DriverHandle {
+    pub(in crate::driver) fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+// the ident of the user event triggered by the wakers
+#[cfg(any(
+    target_vendor = "apple",
+    target_os = "freebsd",
+    target_os = "dragonfly"
+))]
+const WAKER_IDENT: usize = usize::MAX;
+
+/// A handle to wake up [`Driver`] waiting for completions from other threads.
+///
+/// The handle triggers a user event, the interrupted wait returns without entries.
+#[derive(Debug, Clone)]
+pub struct DriverWaker {
+    fd: Arc<OwnedFd>,
+}
+
+impl DriverWaker {
+    /// Wake up the driver.
+    ///
+    /// The wakeups before the driver waits are coalesced into one.
+    ///
+    /// ## Platform specific
+    ///
+    /// * NetBSD and OpenBSD: user events are not supported, the error is returned.
+    #[cfg(any(
+        target_vendor = "apple",
+        target_os = "freebsd",
+        target_os = "dragonfly"
+    ))]
+    pub fn wake(&self) -> io::Result<()> {
+        use rustix::event::kqueue::{UserDefinedFlags, UserFlags};
+
+        let event = Event::new(
+            EventFilter::User {
+                ident: WAKER_IDENT as _,
+                flags: UserFlags::TRIGGER,
+                user_flags: UserDefinedFlags::new(0),
+            },
+            EventFlags::ADD | EventFlags::CLEAR,
+            0,
+        );
+        unsafe {
+            kevent(
+                self.fd.as_fd(),
+                &[event],
+                &mut Vec::new(),
+                Some(Duration::ZERO),
+            )
+        }?;
+        Ok(())
+    }
+
+    /// Wake up the driver.
+    ///
+    /// ## Platform specific
+    ///
+    /// * NetBSD and OpenBSD: user events are not supported, the error is returned.
+    #[cfg(not(any(
+        target_vendor = "apple",
+        target_os = "freebsd",
+        target_os = "dragonfly"
+    )))]
+    pub fn wake(&self) -> io::Result<()> {
+        Err(io::Error::from(io::ErrorKind::Unsupported))
+    }
+}
+
 impl Fd {
     #[inline]
     const fn from_raw(raw_fd: RawFd) -> Self {
@@ -35,7 +136,7 @@ impl Fd {
     }
 
     #[inline]
-    fn as_raw_fd(&self) -> RawFd {
+    pub(in crate::driver) fn as_raw_fd(&self) -> RawFd {
         self.raw_fd
     }
 }
@@ -60,6 +161,30 @@ pub type FixedFd = Fd;
 /// FdOrFixed is aliased to attached fd
 pub type FdOrFixed = Fd;
 
+/// Registered files of
+/// [`CompleteIo::register_files`](crate::driver::CompleteIo::register_files).
+#[derive(Debug, PartialEq, Eq)]
+pub struct FixedFdSlot {
+    fds: Vec<FixedFd>,
+}
+
+impl FixedFdSlot {
+    /// The number of registered files in the slot.
+    pub fn len(&self) -> usize {
+        self.fds.len()
+    }
+
+    /// Returns `true` if the slot has no registered files.
+    pub fn is_empty(&self) -> bool {
+        self.fds.is_empty()
+    }
+
+    /// Get fixed fd of the registered file with `index` in the slot.
+    pub fn get(&self, index: usize) -> Option<FixedFd> {
+        self.fds.get(index).copied()
+    }
+}
+
 /// Invalid file descriptor value could be used as an initial value of uninitialized file descriptor
 pub const INVALID_FD: Fd = Fd::from_raw(-1);
 /// Invalid fixed file descriptor value could be used as an initial value of uninitialized fixed
@@ -75,15 +200,53 @@ pub trait OpCode {
     /// Construct kqueue Event for the operation with the provided user_data
     fn as_event(&self, user_data: usize) -> Event;
 
-    /// Only timers implement this method
+    /// Whether the operation blocks the thread instead of waiting for readiness, the driver
+    /// performs it on the blocking thread pool.
+    fn is_blocking(&self) -> bool {
+        false
+    }
+
+    /// The name of the operation reported to [`DriverHooks`](crate::driver::DriverHooks), it
+    /// defaults to the type name without the module path.
+    fn name(&self) -> &'static str {
+        op_name::<Self>()
+    }
+
+    /// Only [`Cancel`](crate::op::Cancel) implements this method, the driver completes it
+    /// instead of operating.
+    fn as_cancel(&self) -> Option<&crate::op::Cancel> {
+        None
+    }
+
+    /// Only timers and timer updates implement this method
     #[cfg(feature = "time")]
     fn timer_delay(&self) -> std::time::Duration {
         unimplemented!("operation is not a timer")
     }
+
+    /// Only timer updates and removals implement this method, returns user_data of the timer
+    #[cfg(feature = "time")]
+    fn timer_target(&self) -> usize {
+        unimplemented!("operation doesn't target a timer")
+    }
 }
 
 #[cfg(feature = "time")]
 const TIMER_PENDING: usize = usize::MAX - 2;
+// the driver updates or removes the timer instead of operating
+#[cfg(feature = "time")]
+const TIMER_UPDATE_PENDING: usize = usize::MAX - 3;
+#[cfg(feature = "time")]
+const TIMER_REMOVE_PENDING: usize = usize::MAX - 4;
+
+#[cfg(feature = "time")]
+fn timer_found(found: bool) -> io::Result<usize> {
+    if found {
+        Ok(0)
+    } else {
+        Err(io::Error::from_raw_os_error(libc::ENOENT))
+    }
+}
 
 /// Low-level driver based on kqueue.
 pub struct Driver<'arena> {
@@ -106,8 +269,31 @@ pub struct Driver<'arena> {
     to_change_fd_reads: BitSet,
     // The field is used to deduplicate changes to write filters using per fd bitset
     to_change_fd_writes: BitSet,
+    // linked operations waiting for their predecessors
+    links: Links<'arena>,
+    // timeouts of pushed operations
+    op_timeouts: OpTimeouts<()>,
+    // entries completed on push and the buffer used when there are linked operations or timeouts
+    completed: Vec<Entry>,
     #[cfg(feature = "time")]
     timers: TimerWheel,
+    // the blocking operations performed by the pool
+    blocking: HashMap<usize, OpObject<'arena>>,
+    pool: BlockingPool,
+    stats: DriverStats,
+    user_data: UserDataSet,
+    hooks: Hooks,
+}
+
+fn capabilities() -> DriverCapabilities {
+    DriverCapabilities {
+        kernel_version: crate::driver::capabilities::kernel_version(),
+        ..DriverCapabilities::default()
+    }
+}
+
+pub(crate) fn probe_capabilities() -> io::Result<DriverCapabilities> {
+    Ok(capabilities())
 }
 
 impl<'arena> Driver<'arena> {
@@ -116,25 +302,129 @@ impl<'arena> Driver<'arena> {
         Self::with(1024, 0)
     }
 
+    /// Create a handle to post completions into the driver from other threads.
+    pub fn handle(&self) -> io::Result<DriverHandle> {
+        Ok(DriverHandle {
+            fd: self.kqueue.try_clone()?,
+        })
+    }
+
+    /// Create a handle to wake up the driver from other threads.
+    pub fn waker(&mut self) -> io::Result<DriverWaker> {
+        Ok(DriverWaker {
+            fd: Arc::new(self.kqueue.try_clone()?),
+        })
+    }
+
     /// Create a new kqueue driver with specified entries.
     ///
     /// File registration is implemented as dummy operation.
     pub fn with(entries: u32, files_to_register: u32) -> io::Result<Self> {
-        let entries = entries as usize; // for the sake of consistency, use u32 like iour
-        let initial_fd_capacity = entries.max(files_to_register as usize);
+        Self::builder()
+            .sq_entries(entries)
+            .files_to_register(files_to_register)
+            .build()
+    }
+
+    /// Operations natively supported by the backend.
+    ///
+    /// kqueue driver emulates the operations in user space, so none of them is native.
+    pub fn capabilities(&self) -> DriverCapabilities {
+        capabilities()
+    }
+
+    /// The backend of the driver.
+    pub fn backend_kind(&self) -> BackendKind {
+        BackendKind::Kqueue
+    }
+
+    /// Counters of the driver operations.
+    pub fn stats(&self) -> DriverStats {
+        self.stats
+    }
+
+    /// Reset the counters of the driver operations.
+    pub fn reset_stats(&mut self) {
+        self.stats = DriverStats::default();
+    }
+
+    /// Cancel the pushed operations and remove their events from kqueue.
+    ///
+    /// The operations are performed in user space when their files are ready, so the driver
+    /// waits only for the blocking operations performed by the thread pool. The driver waits
+    /// without `timeout` till they complete, [`io::ErrorKind::TimedOut`] is returned if the timeout
+    /// passes first. The entries of the canceled operations are discarded. The driver is shut down
+    /// on drop.
+    pub fn shutdown(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        // the events are deleted in one call, the receipts report the events fired before
+        self.events_to_change.clear();
+        self.events_to_change
+            .extend(self.io_pending.iter().map(|op| {
+                Event::new(
+                    op.opcode_ref().as_event(0).filter(),
+                    EventFlags::DELETE | EventFlags::RECEIPT,
+                    0,
+                )
+            }));
+        self.ready_events.clear();
+        self.ready_events.reserve(self.events_to_change.len());
+        let res = unsafe {
+            kevent(
+                self.kqueue.as_fd(),
+                &self.events_to_change,
+                &mut self.ready_events,
+                Some(Duration::ZERO),
+            )
+        };
+        self.events_to_change.clear();
+        self.ready_events.clear();
+        self.squeue.clear();
+        self.io_pending.clear();
+        self.links = Links::default();
+        self.op_timeouts = OpTimeouts::default();
+        self.completed.clear();
+        self.user_data.clear();
+        #[cfg(feature = "time")]
+        {
+            self.timers = TimerWheel::with_capacity(16);
+        }
+        self.pool.clear(deadline)?;
+        self.blocking.clear();
+        Ok(res.map(|_| ())?)
+    }
+
+    /// Create a builder of kqueue driver.
+    pub fn builder() -> DriverBuilder {
+        DriverBuilder::new()
+    }
+
+    pub(crate) fn from_builder(builder: &DriverBuilder) -> io::Result<Self> {
+        let entries = builder.sq_entries as usize; // for the sake of consistency, use u32 like iour
+        let initial_fd_capacity = entries.max(builder.files_to_register as usize);
+        let kqueue = kqueue()?;
+        let pool = BlockingPool::new(kqueue.try_clone()?, builder.blocking_threads);
 
         Ok(Self {
-            kqueue: kqueue()?,
+            kqueue,
             squeue: Vec::with_capacity(entries),
             squeue_drained_till: entries,
             io_pending: VecDeque::with_capacity(entries),
             events_to_change: Vec::with_capacity(entries),
-            ready_events: Vec::with_capacity(entries),
+            ready_events: Vec::with_capacity(builder.event_capacity_or_default()),
             completed_events_indices: BitSet::with_capacity(entries),
             to_change_fd_reads: BitSet::with_capacity(initial_fd_capacity),
             to_change_fd_writes: BitSet::with_capacity(initial_fd_capacity),
+            links: Links::default(),
+            op_timeouts: OpTimeouts::default(),
+            completed: Vec::new(),
             #[cfg(feature = "time")]
             timers: TimerWheel::with_capacity(16),
+            blocking: HashMap::new(),
+            pool,
+            stats: DriverStats::default(),
+            user_data: UserDataSet::new(builder.check_user_data),
+            hooks: builder.hooks.clone(),
         })
     }
 
@@ -147,6 +437,13 @@ impl<'arena> Driver<'arena> {
                 .filter_map(|(idx, mut op)| {
                     let user_data = op.user_data();
                     let opcode = op.opcode();
+                    if self.pool.is_enabled() && opcode.is_blocking() {
+                        // SAFETY: the operation is kept by the driver till the pool performs it
+                        if unsafe { self.pool.spawn(user_data, opcode) } {
+                            self.blocking.insert(user_data, op);
+                            return None;
+                        }
+                    }
                     // io buffers are Unpin so no need to pin
                     match opcode.operate() {
                         // no result => io is pending
@@ -160,6 +457,20 @@ impl<'arena> Driver<'arena> {
                                 self.timers.insert(user_data, opcode.timer_delay());
                                 None
                             }
+                            #[cfg(feature = "time")]
+                            Ok(TIMER_UPDATE_PENDING) => {
+                                let updated = self
+                                    .timers
+                                    .update(opcode.timer_target(), opcode.timer_delay());
+                                self.squeue_drained_till = idx + 1;
+                                Some(Entry::new(user_data, timer_found(updated)))
+                            }
+                            #[cfg(feature = "time")]
+                            Ok(TIMER_REMOVE_PENDING) => {
+                                let removed = self.timers.cancel(opcode.timer_target());
+                                self.squeue_drained_till = idx + 1;
+                                Some(Entry::new(user_data, timer_found(removed)))
+                            }
                             res => {
                                 self.squeue_drained_till = idx + 1;
                                 Some(Entry::new(user_data, res))
@@ -181,6 +492,19 @@ impl<'arena> Driver<'arena> {
         self.completed_events_indices.clear();
 
         let completed_ops_iter = self.ready_events.drain(..).filter_map(|event| {
+            #[cfg(any(
+                target_vendor = "apple",
+                target_os = "freebsd",
+                target_os = "dragonfly"
+            ))]
+            if let EventFilter::User { ident, .. } = event.filter() {
+                if ident as usize == WAKER_IDENT || ident as usize == blocking::POOL_IDENT {
+                    // the wait is interrupted by the waker or the thread pool
+                    return None;
+                }
+                // the completion posted with MsgRing
+                return Some(Entry::new(ident as _, msg_result(event.udata() as _)));
+            }
             let index = event.udata() as usize;
             let op = self.io_pending.get_mut(index).expect("in range");
             let user_data = op.user_data();
@@ -247,7 +571,8 @@ impl<'arena> Driver<'arena> {
                         EventFilter::Write(raw_fd) => {
                             self.to_change_fd_writes.insert(raw_fd as usize)
                         }
-                        _ => unreachable!("only Read/Write filters are supported"),
+                        EventFilter::Proc { .. } => true,
+                        _ => unreachable!("only Read/Write/Proc filters are supported"),
                     };
                     let maybe_event = if fd_absent {
                         *events_to_change += 1;
@@ -285,26 +610,106 @@ impl<'arena> Driver<'arena> {
 
         Ok(res.map(|_| scanned_till)?)
     }
-}
 
-impl<'arena> CompleteIo<'arena> for Driver<'arena> {
     #[inline]
-    fn attach(&mut self, fd: RawFd) -> io::Result<Fd> {
-        Ok(Fd::from_raw(fd))
+    fn push_op(&mut self, op: OpObject<'arena>) {
+        self.stats.submitted += 1;
+        self.hooks.pushed(op.user_data(), || op.opcode_ref().name());
+        if let Some(cancel) = op.opcode_ref().as_cancel() {
+            let res = self.cancel(&cancel.target);
+            self.completed.push(Entry::new(op.user_data(), res));
+            return;
+        }
+        if let Some(timeout) = op.timeout() {
+            self.op_timeouts.insert(op.user_data(), timeout, ());
+        }
+        if let Some(op) = self.links.push(op) {
+            self.squeue.push(op);
+        }
     }
 
-    #[inline]
-    fn register_fd(&mut self, fd: RawFd, _id: u32) -> io::Result<FixedFd> {
-        Ok(FixedFd::from_raw(fd))
+    // cancel pushed operations matching the target
+    fn cancel(&mut self, target: &CancelTarget) -> io::Result<usize> {
+        let canceled = match target {
+            CancelTarget::UserData(user_data) => {
+                let user_data = *user_data;
+                let pushed = self.squeue.len() + self.io_pending.len();
+                self.squeue.retain(|op| op.user_data() != user_data);
+                self.io_pending.retain(|op| op.user_data() != user_data);
+                let removed = pushed != self.squeue.len() + self.io_pending.len();
+                let removed = self.remove_blocking(user_data)? || removed;
+                #[cfg(feature = "time")]
+                let removed = self.timers.remove(user_data) || removed;
+                if self.links.cancel(user_data) || removed {
+                    vec![user_data]
+                } else {
+                    Vec::new()
+                }
+            }
+            CancelTarget::Fd(fd) => {
+                let fd = fd.as_raw_fd();
+                let mut canceled = Vec::new();
+                self.io_pending.retain(|op| {
+                    let matches = match op.opcode_ref().as_event(0).filter() {
+                        EventFilter::Read(raw_fd) | EventFilter::Write(raw_fd) => raw_fd == fd,
+                        _ => false,
+                    };
+                    if matches {
+                        canceled.push(op.user_data());
+                    }
+                    !matches
+                });
+                for user_data in &canceled {
+                    self.links.cancel(*user_data);
+                }
+                canceled
+            }
+        };
+        if canceled.is_empty() {
+            return Err(io::Error::from_raw_os_error(libc::ENOENT));
+        }
+        for user_data in &canceled {
+            self.op_timeouts.remove(*user_data);
+            self.completed.push(canceled_entry(*user_data));
+        }
+        Ok(match target {
+            CancelTarget::UserData(_) => 0,
+            CancelTarget::Fd(_) => canceled.len(),
+        })
     }
 
-    #[inline]
-    fn unregister_fd(&mut self, _fixed_fd: FixedFd) -> io::Result<()> {
-        Ok(())
+    // remove the operation queued to the thread pool, the running operation can't be removed
+    fn remove_blocking(&mut self, user_data: usize) -> io::Result<bool> {
+        if !self.blocking.contains_key(&user_data) {
+            return Ok(false);
+        }
+        if !self.pool.cancel(user_data) {
+            return Err(io::Error::from_raw_os_error(libc::EALREADY));
+        }
+        self.blocking.remove(&user_data);
+        Ok(true)
     }
 
-    #[inline]
-    fn try_cancel(&mut self, user_data: usize) -> Result<(), ()> {
+    // the entries of the operations performed by the thread pool
+    fn receive_blocking(&mut self, entries: &mut impl Extend<Entry>) {
+        if self.blocking.is_empty() {
+            return;
+        }
+        for (user_data, res) in self.pool.take_completed() {
+            let op = self
+                .blocking
+                .remove(&user_data)
+                .expect("performed by the pool");
+            match res {
+                Some(res) => entries.extend(Some(Entry::new(user_data, res))),
+                // wait for readiness like other operations
+                None => self.io_pending.push_back(op),
+            }
+        }
+    }
+
+    // remove the operation canceled on timeout, it completes with the timed out error
+    fn discard_op(&mut self, user_data: usize) {
         // we assume cancellations are rare
         if let Some(pos) = self
             .squeue
@@ -320,30 +725,200 @@ impl<'arena> CompleteIo<'arena> for Driver<'arena> {
         {
             let _ = self.io_pending.remove(pos);
         }
+        self.links.cancel(user_data);
+        self.op_timeouts.remove(user_data);
+        #[cfg(feature = "time")]
+        self.timers.remove(user_data);
+    }
+
+    // timeouts of completed operations
+    fn remove_op_timeouts(&mut self, completed: &[Entry]) {
+        for entry in completed.iter().filter(|entry| !entry.has_more()) {
+            self.op_timeouts.remove(entry.user_data());
+        }
+    }
+
+    // cancel operations with expired deadlines
+    fn expire_op_timeouts(&mut self, completed: &mut Vec<Entry>) {
+        if self.op_timeouts.is_empty() {
+            return;
+        }
+        self.remove_op_timeouts(completed);
+        for (user_data, ()) in self.op_timeouts.expire() {
+            if self.remove_blocking(user_data).is_err() {
+                // the running blocking operation completes with its result
+                continue;
+            }
+            self.discard_op(user_data);
+            completed.push(Entry::new(user_data, Err(io::ErrorKind::TimedOut.into())));
+        }
+    }
+
+    unsafe fn submit_entries(
+        &mut self,
+        timeout: Option<Duration>,
+        entries: &mut impl Extend<Entry>,
+    ) -> io::Result<()> {
+        if self.links.is_empty() && self.op_timeouts.is_empty() && self.completed.is_empty() {
+            return self.submit_impl(timeout, entries);
+        }
+        // don't wait when there are operations completed on push
+        let timeout = if self.completed.is_empty() {
+            self.op_timeouts.till_next_deadline_or_timeout(timeout)
+        } else {
+            Some(Duration::ZERO)
+        };
+        // expire timeouts and push successors of completed linked operations
+        let mut completed = std::mem::take(&mut self.completed);
+        let res = self.submit_impl(timeout, &mut completed);
+        self.expire_op_timeouts(&mut completed);
+        self.links
+            .complete_entries(&mut completed, &mut self.squeue);
+        entries.extend(completed.drain(..));
+        self.completed = completed;
+        res
+    }
+
+    fn submit_impl(
+        &mut self,
+        timeout: Option<Duration>,
+        entries: &mut impl Extend<Entry>,
+    ) -> io::Result<()> {
+        let ops_pushed = self.squeue.len() > 0;
+
+        self.operate_squeue(entries);
+
+        // when io is pushed and completed and there is no pending io
+        // let the caller to process completed operations
+        if ops_pushed && self.io_pending.is_empty() {
+            #[cfg(feature = "time")]
+            self.timers.expire_timers(entries);
+            self.receive_blocking(entries);
+            return Ok(());
+        }
+        // either caller doesn't have new io or there is pending io
+
+        // on any error there is no ready events
+        let io_pending_scanned_till = self.check_readiness(timeout, entries)?;
+        self.operate_completed_and_requeue(io_pending_scanned_till, entries);
+        self.receive_blocking(entries);
+
+        Ok(())
+    }
+}
+
+impl<'arena> CompleteIo<'arena> for Driver<'arena> {
+    #[inline]
+    fn attach(&mut self, fd: RawFd) -> io::Result<Fd> {
+        Ok(Fd::from_raw(fd))
+    }
+
+    fn detach(&mut self, fd: RawFd) -> io::Result<()> {
+        let waiting = self.io_pending.iter().any(|op| {
+            matches!(
+                op.opcode_ref().as_event(0).filter(),
+                EventFilter::Read(raw_fd) | EventFilter::Write(raw_fd) if raw_fd == fd
+            )
+        });
+        if waiting {
+            Err(io::Error::from_raw_os_error(libc::EBUSY))
+        } else {
+            Ok(())
+        }
+    }
+
+    #[inline]
+    fn register_fd(&mut self, fd: RawFd, _id: u32) -> io::Result<FixedFd> {
+        Ok(FixedFd::from_raw(fd))
+    }
+
+    #[inline]
+    fn unregister_fd(&mut self, _fixed_fd: FixedFd) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn register_files(&mut self, fds: &[RawFd]) -> io::Result<FixedFdSlot> {
+        let fds = fds
+            .iter()
+            .map(|fd| self.register_fd(*fd, 0))
+            .collect::<io::Result<_>>()?;
+        Ok(FixedFdSlot { fds })
+    }
+
+    fn register_files_update(
+        &mut self,
+        slot: &mut FixedFdSlot,
+        index: usize,
+        fds: &[RawFd],
+    ) -> io::Result<()> {
+        if index + fds.len() > slot.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "updated files are out of the slot",
+            ));
+        }
+        for (fixed_fd, fd) in slot.fds[index..].iter_mut().zip(fds) {
+            *fixed_fd = self.register_fd(*fd, 0)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn unregister_files(&mut self, _slot: FixedFdSlot) -> io::Result<()> {
         Ok(())
     }
 
+    #[inline]
+    fn register_buffers<'a: 'arena>(
+        &mut self,
+        bufs: &'a mut [IoSliceMut<'a>],
+    ) -> io::Result<FixedBufRegistry<'a>> {
+        FixedBufRegistry::new(bufs)
+    }
+
+    #[inline]
+    fn unregister_buffers(&mut self, registry: &FixedBufRegistry<'_>) -> io::Result<()> {
+        registry.check_not_in_use()
+    }
+
+    #[inline]
+    fn try_cancel(&mut self, user_data: usize) -> Result<(), CancelError> {
+        match self.cancel(&CancelTarget::UserData(user_data)) {
+            Ok(_) => {
+                self.stats.canceled += 1;
+                self.hooks.canceled(user_data);
+                Ok(())
+            }
+            Err(e) if e.raw_os_error() == Some(libc::ENOENT) => Err(CancelError::NotFound),
+            Err(e) => Err(CancelError::Os(e)),
+        }
+    }
+
     #[inline]
     fn try_push<O: OpCode>(
         &mut self,
         op: Operation<'arena, O>,
-    ) -> Result<(), Operation<'arena, O>> {
-        if self.capacity_left() > 0 {
-            self.squeue.push(OpObject::from(op));
-            Ok(())
-        } else {
-            Err(op)
+    ) -> Result<(), PushError<Operation<'arena, O>>> {
+        if self.capacity_left() == 0 {
+            return Err(PushError::QueueFull(op));
+        }
+        if !self.user_data.insert(op.user_data()) {
+            return Err(PushError::DuplicateUserData(op));
         }
+        self.push_op(OpObject::from(op));
+        Ok(())
     }
 
     #[inline]
-    fn try_push_dyn(&mut self, op: OpObject<'arena>) -> Result<(), OpObject<'arena>> {
-        if self.capacity_left() > 0 {
-            self.squeue.push(op);
-            Ok(())
-        } else {
-            Err(op)
+    fn try_push_dyn(&mut self, op: OpObject<'arena>) -> Result<(), PushError<OpObject<'arena>>> {
+        if self.capacity_left() == 0 {
+            return Err(PushError::QueueFull(op));
         }
+        if !self.user_data.insert(op.user_data()) {
+            return Err(PushError::DuplicateUserData(op));
+        }
+        self.push_op(op);
+        Ok(())
     }
 
     #[inline]
@@ -351,8 +926,16 @@ impl<'arena> CompleteIo<'arena> for Driver<'arena> {
         &mut self,
         ops_queue: &mut vec_deque_alloc!(OpObject<'arena>, A),
     ) {
+        self.stats.max_queued = self.stats.max_queued.max(ops_queue.len());
         let till = self.capacity_left().min(ops_queue.len());
-        self.squeue.extend(ops_queue.drain(..till));
+        for _ in 0..till {
+            // the operation with the duplicate data stays in the queue
+            if !self.user_data.insert(ops_queue[0].user_data()) {
+                break;
+            }
+            let op = ops_queue.pop_front().expect("in range");
+            self.push_op(op);
+        }
     }
 
     #[inline]
@@ -365,24 +948,46 @@ impl<'arena> CompleteIo<'arena> for Driver<'arena> {
         timeout: Option<Duration>,
         entries: &mut impl Extend<Entry>,
     ) -> io::Result<()> {
-        let ops_pushed = self.squeue.len() > 0;
-
-        self.operate_squeue(entries);
+        self.hooks.submitted();
+        // the completed operations release the data when the entries are extended
+        let mut user_data = std::mem::take(&mut self.user_data);
+        let hooks = self.hooks.clone();
+        let mut entries = CountCompleted::new(entries, &mut user_data, &hooks);
+        let res = self.submit_entries(timeout, &mut entries);
+        self.stats.completed += entries.completed();
+        self.user_data = user_data;
+        res
+    }
 
-        // when io is pushed and completed and there is no pending io
-        // let the caller to process completed operations
-        if ops_pushed && self.io_pending.is_empty() {
-            #[cfg(feature = "time")]
-            self.timers.expire_timers(entries);
-            return Ok(());
-        }
-        // either caller doesn't have new io or there is pending io
+    fn peek_completed(&mut self) -> CompletionIter<'_, 'arena> {
+        // the entries are returned after the successors of linked operations are pushed
+        let mut completed = std::mem::take(&mut self.completed);
+        self.remove_op_timeouts(&completed);
+        self.links
+            .complete_entries(&mut completed, &mut self.squeue);
+        self.completed = completed;
+        CompletionIter::new(
+            &mut self.completed,
+            &mut self.stats,
+            &mut self.user_data,
+            &self.hooks,
+        )
+    }
 
-        // on any error there is no ready events
-        let io_pending_scanned_till = self.check_readiness(timeout, entries)?;
-        self.operate_completed_and_requeue(io_pending_scanned_till, entries);
+    unsafe fn flush(&mut self) -> io::Result<usize> {
+        self.hooks.submitted();
+        let submitted = self.squeue.len();
+        // the entries of completed operations are output by the next submit
+        let mut completed = std::mem::take(&mut self.completed);
+        self.operate_squeue(&mut completed);
+        self.completed = completed;
+        Ok(submitted)
+    }
+}
 
-        Ok(())
+impl Drop for Driver<'_> {
+    fn drop(&mut self) {
+        let _ = self.shutdown(None);
     }
 }
 
@@ -391,3 +996,18 @@ impl AsRawFd for Driver<'_> {
         self.kqueue.as_raw_fd()
     }
 }
+
+// negative result of MsgRing is the error code
+#[cfg(any(
+    target_vendor = "apple",
+    target_os = "freebsd",
+    target_os = "dragonfly"
+))]
+#[inline]
+fn msg_result(result: i32) -> io::Result<usize> {
+    if result < 0 {
+        Err(io::Error::from_raw_os_error(-result))
+    } else {
+        Ok(result as _)
+    }
+}
@@ -1,15 +1,16 @@
-use std::{io, marker::PhantomData, mem::size_of};
+use std::{ffi::CString, io, marker::PhantomData};
 
-use libc::{sockaddr, sockaddr_storage, socklen_t};
+use libc::sockaddr;
 use rustix::event::kqueue::{Event, EventFilter, EventFlags};
 use socket2::SockAddr;
 
 #[cfg(feature = "time")]
-pub use crate::driver::time::Timeout;
+pub use crate::driver::time::{Timeout, TimeoutRemove, TimeoutUpdate};
 pub use crate::driver::unix::op::*;
 use crate::{
-    buf::{AsIoSlices, AsIoSlicesMut, IntoInner, IoBuf, IoBufMut},
-    driver::{unix::IntoFdOrFixed, Fd, FdOrFixed, IntoRawFd, OpCode, RawFd},
+    buf::{AsIoSlices, AsIoSlicesMut, BufWrapperMut, IntoInner, IoBuf, IoBufMut},
+    driver::{unix::IntoFdOrFixed, Fd, FdOrFixed, FixedBuf, IntoRawFd, OpCode, RawFd},
+    op::{AllocateMode, RenameFlags, RwFlags},
     syscall,
 };
 
@@ -41,6 +42,27 @@ macro_rules! write_filter_event {
 }
 use write_filter_event;
 
+fn unsupported_nowait() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "RwFlags::NOWAIT is not supported on this platform",
+    )
+}
+
+/// Read a file at specified position into a registered buffer.
+///
+/// ## Platform specific
+///
+/// * kqueue: the buffers are not registered in the kernel, it is the same as [`ReadAt`].
+pub type ReadAtFixed<'arena> = ReadAt<'arena, FixedBuf<'arena>>;
+
+/// Write a file at specified position from a registered buffer.
+///
+/// ## Platform specific
+///
+/// * kqueue: the buffers are not registered in the kernel, it is the same as [`WriteAt`].
+pub type WriteAtFixed<'arena> = WriteAt<'arena, FixedBuf<'arena>>;
+
 impl<'arena, T: IoBufMut<'arena>> OpCode for Read<'arena, T> {
     fn operate(&mut self) -> Option<io::Result<usize>> {
         let fd = self.fd.as_raw_fd();
@@ -62,7 +84,14 @@ impl<'arena, T: IoBufMut<'arena>> OpCode for Read<'arena, T> {
 }
 
 impl<'arena, T: IoBufMut<'arena>> OpCode for ReadAt<'arena, T> {
+    fn is_blocking(&self) -> bool {
+        true
+    }
+
     fn operate(&mut self) -> Option<io::Result<usize>> {
+        if self.rw_flags.contains(RwFlags::NOWAIT) {
+            return Some(Err(unsupported_nowait()));
+        }
         let fd = self.fd.as_raw_fd();
         // SAFETY: slice into buffer is Unpin
         let slice = self.buffer.as_uninit_slice();
@@ -82,6 +111,30 @@ impl<'arena, T: IoBufMut<'arena>> OpCode for ReadAt<'arena, T> {
     }
 }
 
+impl<'arena, T: AsIoSlicesMut<'arena>> OpCode for ReadVectoredAtImpl<'arena, T> {
+    fn is_blocking(&self) -> bool {
+        true
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        let fd = self.fd.as_raw_fd();
+        // SAFETY: IoSliceMut is Unpin
+        let slices = unsafe { self.buffer.as_io_slices_mut() };
+        syscall!(
+            maybe_block preadv(
+                fd,
+                slices.as_mut_ptr() as _,
+                slices.len() as _,
+                self.offset as _
+            )
+        )
+    }
+
+    fn as_event(&self, user_data: usize) -> Event {
+        read_filter_event!(self, user_data)
+    }
+}
+
 impl<'arena, T: IoBuf<'arena>> OpCode for Write<'arena, T> {
     fn operate(&mut self) -> Option<io::Result<usize>> {
         // SAFETY: buffer is Unpin
@@ -102,17 +155,53 @@ impl<'arena, T: IoBuf<'arena>> OpCode for Write<'arena, T> {
 }
 
 impl<'arena, T: IoBuf<'arena>> OpCode for WriteAt<'arena, T> {
+    fn is_blocking(&self) -> bool {
+        true
+    }
+
     fn operate(&mut self) -> Option<io::Result<usize>> {
+        if self.rw_flags.contains(RwFlags::NOWAIT) {
+            return Some(Err(unsupported_nowait()));
+        }
         // SAFETY: buffer is Unpin
         let slice = self.buffer.as_slice();
 
-        syscall!(
+        let res = syscall!(
             maybe_block pwrite(
                 self.fd.as_raw_fd(),
                 slice.as_ptr() as _,
                 slice.len() as _,
                 self.offset as _
             )
+        );
+        if self.rw_flags.contains(RwFlags::DSYNC) {
+            if let Some(Ok(written)) = res {
+                return Some(syscall!(fsync(self.fd.as_raw_fd())).map(|_| written));
+            }
+        }
+        res
+    }
+
+    fn as_event(&self, user_data: usize) -> Event {
+        write_filter_event!(self, user_data)
+    }
+}
+
+impl<'arena, T: AsIoSlices<'arena>> OpCode for WriteVectoredAtImpl<'arena, T> {
+    fn is_blocking(&self) -> bool {
+        true
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        // SAFETY: IoSlice is Unpin
+        let slices = unsafe { self.buffer.as_io_slices() };
+        syscall!(
+            maybe_block pwritev(
+                self.fd.as_raw_fd(),
+                slices.as_ptr() as _,
+                slices.len() as _,
+                self.offset as _
+            )
         )
     }
 
@@ -122,6 +211,10 @@ impl<'arena, T: IoBuf<'arena>> OpCode for WriteAt<'arena, T> {
 }
 
 impl OpCode for Sync {
+    fn is_blocking(&self) -> bool {
+        true
+    }
+
     fn operate(&mut self) -> Option<io::Result<usize>> {
         Some(
             syscall!(fsync(self.fd.as_raw_fd()))
@@ -134,6 +227,101 @@ impl OpCode for Sync {
     }
 }
 
+impl Fallocate {
+    #[cfg(target_vendor = "apple")]
+    fn allocate(&self) -> io::Result<()> {
+        if self.mode.contains(AllocateMode::PUNCH_HOLE) {
+            return Err(unsupported_allocate_mode());
+        }
+        let fd = self.fd.as_raw_fd();
+        let end = self
+            .offset
+            .checked_add(self.len)
+            .and_then(|end| libc::off_t::try_from(end).ok())
+            .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidInput))?;
+        // allocate from the physical end of file, so the requested range is covered
+        let mut store = libc::fstore_t {
+            fst_flags: libc::F_ALLOCATECONTIG | libc::F_ALLOCATEALL,
+            fst_posmode: libc::F_PEOFPOSMODE,
+            fst_offset: 0,
+            fst_length: end,
+            fst_bytesalloc: 0,
+        };
+        if syscall!(fcntl(fd, libc::F_PREALLOCATE, &mut store)).is_err() {
+            // retry without requiring contiguous space
+            store.fst_flags = libc::F_ALLOCATEALL;
+            syscall!(fcntl(fd, libc::F_PREALLOCATE, &mut store))?;
+        }
+        if !self.mode.contains(AllocateMode::KEEP_SIZE) {
+            let mut stat = unsafe { std::mem::zeroed::<libc::stat>() };
+            syscall!(fstat(fd, &mut stat))?;
+            if stat.st_size < end {
+                syscall!(ftruncate(fd, end))?;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+    fn allocate(&self) -> io::Result<()> {
+        if self.mode != AllocateMode::empty() {
+            return Err(unsupported_allocate_mode());
+        }
+        let res =
+            unsafe { libc::posix_fallocate(self.fd.as_raw_fd(), self.offset as _, self.len as _) };
+        // posix_fallocate returns error number instead of setting errno
+        if res != 0 {
+            Err(io::Error::from_raw_os_error(res))
+        } else {
+            Ok(())
+        }
+    }
+
+    #[cfg(not(any(
+        target_vendor = "apple",
+        target_os = "freebsd",
+        target_os = "dragonfly"
+    )))]
+    fn allocate(&self) -> io::Result<()> {
+        Err(unsupported_allocate_mode())
+    }
+}
+
+fn unsupported_allocate_mode() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "allocate mode is not supported on this platform",
+    )
+}
+
+impl OpCode for Fallocate {
+    fn is_blocking(&self) -> bool {
+        true
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(self.allocate().map(|_| 0))
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("Fallocate operation should complete in one shot")
+    }
+}
+
+impl OpCode for SyncFileRange {
+    fn is_blocking(&self) -> bool {
+        true
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(syscall!(fsync(self.fd.as_raw_fd())).map(|_| 0))
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("SyncFileRange operation should complete in one shot")
+    }
+}
+
 impl OpCode for Accept {
     fn operate(&mut self) -> Option<io::Result<usize>> {
         // SAFETY: buffer is Unpin
@@ -185,12 +373,383 @@ impl OpCode for Connect {
     }
 }
 
+impl OpCode for ShutdownSocket {
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(syscall!(shutdown(self.fd.as_raw_fd(), self.how())).map(|_| 0))
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("ShutdownSocket operation should complete in one shot")
+    }
+}
+
+// Query the readiness without blocking
+fn poll_fd(fd: RawFd, events: libc::c_short) -> Option<io::Result<usize>> {
+    let mut pollfd = libc::pollfd {
+        fd,
+        events,
+        revents: 0,
+    };
+    match syscall!(poll(&mut pollfd, 1, 0)) {
+        Ok(0) => None,
+        Ok(_) => Some(Ok(pollfd.revents as u16 as usize)),
+        Err(e) => Some(Err(e)),
+    }
+}
+
+impl OpCode for PollReadable {
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        poll_fd(self.fd.as_raw_fd(), libc::POLLIN)
+    }
+
+    fn as_event(&self, user_data: usize) -> Event {
+        read_filter_event!(self, user_data)
+    }
+}
+
+impl OpCode for PollWritable {
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        poll_fd(self.fd.as_raw_fd(), libc::POLLOUT)
+    }
+
+    fn as_event(&self, user_data: usize) -> Event {
+        write_filter_event!(self, user_data)
+    }
+}
+
+impl OpCode for OpenAt {
+    fn is_blocking(&self) -> bool {
+        true
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(
+            syscall!(openat(
+                self.dirfd,
+                self.path.as_ptr(),
+                self.flags,
+                self.mode as libc::c_uint
+            ))
+            .map(|fd| usize::try_from(fd).expect("non negative")),
+        )
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("OpenAt operation should complete in one shot")
+    }
+}
+
+/// Get file status.
+///
+/// The status is written into the buffer owned by the operation.
+pub struct Statx {
+    dirfd: RawFd,
+    path: CString,
+    flags: i32,
+    stat: libc::stat,
+}
+
+impl Statx {
+    /// Create [`Statx`].
+    ///
+    /// `dirfd`, `path` and `flags` have the same meaning as in `fstatat(2)`. Empty `path` gets the
+    /// status of `dirfd` itself.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_STATX` operation.
+    /// * kqueue: it is synchronized `fstatat` syscall, or `fstat` when `path` is empty. `mask` is
+    ///   ignored.
+    pub fn new(dirfd: RawFd, path: CString, flags: i32, _mask: u32) -> Self {
+        Self {
+            dirfd,
+            path,
+            flags,
+            // SAFETY: stat is a plain C struct
+            stat: unsafe { std::mem::zeroed() },
+        }
+    }
+
+    /// Get the file status filled by the completed operation.
+    pub fn stat(&self) -> &libc::stat {
+        &self.stat
+    }
+}
+
+impl OpCode for Statx {
+    fn is_blocking(&self) -> bool {
+        true
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        let res = if self.path.as_bytes().is_empty() {
+            syscall!(fstat(self.dirfd, &mut self.stat))
+        } else {
+            syscall!(fstatat(
+                self.dirfd,
+                self.path.as_ptr(),
+                &mut self.stat,
+                self.flags
+            ))
+        };
+        Some(res.map(|_| 0))
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("Statx operation should complete in one shot")
+    }
+}
+
+impl OpCode for MkdirAt {
+    fn is_blocking(&self) -> bool {
+        true
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(syscall!(mkdirat(self.dirfd, self.path.as_ptr(), self.mode)).map(|_| 0))
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("MkdirAt operation should complete in one shot")
+    }
+}
+
+impl OpCode for UnlinkAt {
+    fn is_blocking(&self) -> bool {
+        true
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(syscall!(unlinkat(self.dirfd, self.path.as_ptr(), self.flags)).map(|_| 0))
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("UnlinkAt operation should complete in one shot")
+    }
+}
+
+impl OpCode for RenameAt {
+    fn is_blocking(&self) -> bool {
+        true
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        let noreplace = self.flags.contains(RenameFlags::NOREPLACE);
+        let res = if !noreplace {
+            syscall!(renameat(
+                self.old_dirfd,
+                self.old_path.as_ptr(),
+                self.new_dirfd,
+                self.new_path.as_ptr()
+            ))
+        } else {
+            #[cfg(target_vendor = "apple")]
+            {
+                syscall!(renameatx_np(
+                    self.old_dirfd,
+                    self.old_path.as_ptr(),
+                    self.new_dirfd,
+                    self.new_path.as_ptr(),
+                    libc::RENAME_EXCL
+                ))
+            }
+            #[cfg(not(target_vendor = "apple"))]
+            {
+                Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "rename without replace is not supported on this platform",
+                ))
+            }
+        };
+        Some(res.map(|_| 0))
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("RenameAt operation should complete in one shot")
+    }
+}
+
+impl OpCode for SymlinkAt {
+    fn is_blocking(&self) -> bool {
+        true
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(
+            syscall!(symlinkat(
+                self.target.as_ptr(),
+                self.linkdir_fd,
+                self.linkpath.as_ptr()
+            ))
+            .map(|_| 0),
+        )
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("SymlinkAt operation should complete in one shot")
+    }
+}
+
+impl OpCode for LinkAt {
+    fn is_blocking(&self) -> bool {
+        true
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(
+            syscall!(linkat(
+                self.old_dirfd,
+                self.old_path.as_ptr(),
+                self.new_dirfd,
+                self.new_path.as_ptr(),
+                self.flags
+            ))
+            .map(|_| 0),
+        )
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("LinkAt operation should complete in one shot")
+    }
+}
+
+impl OpCode for Close {
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(close_raw_fd(self.fd))
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("Close operation should complete in one shot")
+    }
+}
+
+impl OpCode for Cancel {
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        unreachable!("Cancel is completed by the driver")
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("Cancel is completed by the driver")
+    }
+
+    fn as_cancel(&self) -> Option<&Cancel> {
+        Some(self)
+    }
+}
+
+impl OpCode for Nop {
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(Ok(0))
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("Nop operation should complete in one shot")
+    }
+}
+
+impl OpCode for MsgRing {
+    #[cfg(any(
+        target_vendor = "apple",
+        target_os = "freebsd",
+        target_os = "dragonfly"
+    ))]
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        use std::{os::fd::BorrowedFd, time::Duration};
+
+        use rustix::event::kqueue::{kevent, UserDefinedFlags, UserFlags};
+
+        let event = Event::new(
+            EventFilter::User {
+                ident: self.user_data as _,
+                flags: UserFlags::TRIGGER,
+                user_flags: UserDefinedFlags::new(0),
+            },
+            EventFlags::ADD | EventFlags::ONESHOT,
+            self.result as _,
+        );
+        // SAFETY: the driver handle outlives the operation
+        let kqueue = unsafe { BorrowedFd::borrow_raw(self.ring_fd) };
+        let res = unsafe { kevent(kqueue, &[event], &mut Vec::new(), Some(Duration::ZERO)) };
+        Some(res.map(|_| 0).map_err(Into::into))
+    }
+
+    #[cfg(not(any(
+        target_vendor = "apple",
+        target_os = "freebsd",
+        target_os = "dragonfly"
+    )))]
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(Err(io::Error::from(io::ErrorKind::Unsupported)))
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("MsgRing operation should complete in one shot")
+    }
+}
+
+impl OpCode for CreateSocket {
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        let res = self.create().and_then(|socket| {
+            socket.set_nonblocking(true)?;
+            Ok(socket.into_raw_fd() as _)
+        });
+        Some(res)
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("CreateSocket operation should complete in one shot")
+    }
+}
+
+impl OpCode for Bind {
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(self.bind())
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("Bind operation should complete in one shot")
+    }
+}
+
+impl OpCode for Listen {
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(self.listen())
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("Listen operation should complete in one shot")
+    }
+}
+
+impl OpCode for WaitId {
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        match self.try_collect() {
+            Ok(false) => None,
+            Ok(true) => Some(Ok(0)),
+            Err(e) => Some(Err(e)),
+        }
+    }
+
+    fn as_event(&self, user_data: usize) -> Event {
+        use rustix::{event::kqueue::ProcessEvents, process::Pid};
+
+        Event::new(
+            EventFilter::Proc {
+                pid: Pid::from_raw(self.id as _).expect("positive pid"),
+                flags: ProcessEvents::EXIT,
+            },
+            add_event_flags!(),
+            user_data as isize,
+        )
+    }
+}
+
 impl<'arena, T: IoBufMut<'arena>> OpCode for Recv<'arena, T> {
     fn operate(&mut self) -> Option<io::Result<usize>> {
         let fd = self.fd;
         // SAFETY: IoBufMut is Unpin
         let slice = self.buffer.as_uninit_slice();
-        syscall!(maybe_block recv(fd.as_raw_fd(), slice.as_mut_ptr() as _, slice.len() as _, 0))
+        syscall!(maybe_block recv(fd.as_raw_fd(), slice.as_mut_ptr() as _, slice.len() as _, self.flags))
     }
 
     fn as_event(&self, user_data: usize) -> Event {
@@ -212,6 +771,18 @@ impl<'arena, T: AsIoSlicesMut<'arena>> OpCode for RecvVectoredImpl<'arena, T> {
 }
 
 impl<'arena, T: IoBuf<'arena>> OpCode for Send<'arena, T> {
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        // SAFETY: IoBuf is Unpin
+        let slice = self.buffer.as_slice();
+        syscall!(maybe_block send(self.fd.as_raw_fd(), slice.as_ptr() as _, slice.len() as _, self.flags))
+    }
+
+    fn as_event(&self, user_data: usize) -> Event {
+        write_filter_event!(self, user_data)
+    }
+}
+
+impl<'arena, T: IoBuf<'arena>> OpCode for SendZc<'arena, T> {
     fn operate(&mut self) -> Option<io::Result<usize>> {
         // SAFETY: IoBuf is Unpin
         let slice = self.buffer.as_slice();
@@ -236,56 +807,67 @@ impl<'arena, T: AsIoSlices<'arena>> OpCode for SendVectoredImpl<'arena, T> {
 }
 
 /// Receive a single piece of data and source address using a single buffer.
+///
+/// `recvmsg` is used rather than `recvfrom` to report the flags of the received message.
 pub struct RecvFrom<'arena, T: IoBufMut<'arena>> {
-    fd: FdOrFixed,
-    buffer: T,
-    addr: sockaddr_storage,
-    socklen: socklen_t,
-    _lifetime: PhantomData<&'arena ()>,
+    inner: RecvMsgImpl<'arena, BufWrapperMut<'arena, T>>,
 }
 
 impl<'arena, T: IoBufMut<'arena>> RecvFrom<'arena, T> {
     /// Create [`RecvFrom`].
     pub fn new(fd: impl IntoFdOrFixed<Target = FdOrFixed>, buffer: T) -> Self {
         Self {
-            fd: fd.into(),
-            buffer,
-            addr: unsafe { std::mem::zeroed() },
-            socklen: size_of::<sockaddr_storage>() as socklen_t,
-            _lifetime: PhantomData,
+            inner: RecvMsgImpl::new(fd, BufWrapperMut::from(buffer)),
         }
     }
+
+    /// Set `MSG_*` flags of the `recvfrom` call, see [`Recv::with_flags`].
+    ///
+    /// With `MSG_DONTWAIT` the operation fails with [`io::ErrorKind::WouldBlock`] instead of
+    /// waiting for readiness.
+    pub fn with_flags(mut self, flags: i32) -> Self {
+        self.inner = self.inner.with_flags(flags);
+        self
+    }
+
+    /// See [`RecvMsgImpl::msg_flags`].
+    pub fn msg_flags(&self) -> i32 {
+        self.inner.msg_flags()
+    }
 }
 
 impl<'arena, T: IoBufMut<'arena>> IntoInner for RecvFrom<'arena, T> {
     type Inner = (T, SockAddr);
 
     fn into_inner(self) -> Self::Inner {
-        (self.buffer, unsafe {
-            SockAddr::new(self.addr, self.socklen)
-        })
+        let (bufwrapper, sockaddr) = self.inner.into_inner();
+        (bufwrapper.into_inner(), sockaddr)
     }
 }
 
 impl<'arena, T: IoBufMut<'arena>> OpCode for RecvFrom<'arena, T> {
     fn operate(&mut self) -> Option<io::Result<usize>> {
-        let slice = self.buffer.as_uninit_slice();
-        syscall!(maybe_block recvfrom(self.fd.as_raw_fd(), slice.as_mut_ptr() as *mut libc::c_void, slice.len(), 0, &mut self.addr as *mut sockaddr_storage as *mut sockaddr, &mut self.socklen as *mut socklen_t))
+        self.inner.operate()
     }
 
     fn as_event(&self, user_data: usize) -> Event {
-        read_filter_event!(self, user_data)
+        self.inner.as_event(user_data)
     }
 }
+
 impl<'arena, T: AsIoSlicesMut<'arena>> OpCode for RecvMsgImpl<'arena, T> {
     fn operate(&mut self) -> Option<io::Result<usize>> {
         if self.msg.msg_namelen == 0 {
-            let fd = self.fd;
-            let msg = self.set_msg();
-            syscall!(maybe_block recvmsg(fd.as_raw_fd(), msg, 0))
-        } else {
-            syscall!(maybe_block recvmsg(self.fd.as_raw_fd(), &mut self.msg, 0))
+            self.set_msg();
+        }
+        // the receive without waiting completes with the would block error
+        if self.flags & libc::MSG_DONTWAIT != 0 {
+            return Some(
+                syscall!(recvmsg(self.fd.as_raw_fd(), &mut self.msg, self.flags))
+                    .map(|received| received as usize),
+            );
         }
+        syscall!(maybe_block recvmsg(self.fd.as_raw_fd(), &mut self.msg, self.flags))
     }
 
     fn as_event(&self, user_data: usize) -> Event {
@@ -298,6 +880,7 @@ pub struct SendTo<'arena, T: IoBuf<'arena>> {
     fd: FdOrFixed,
     buffer: T,
     addr: SockAddr,
+    flags: i32,
     _lifetime: PhantomData<&'arena ()>,
 }
 
@@ -308,9 +891,16 @@ impl<'arena, T: IoBuf<'arena>> SendTo<'arena, T> {
             fd: fd.into(),
             buffer,
             addr,
+            flags: 0,
             _lifetime: PhantomData,
         }
     }
+
+    /// Set `MSG_*` flags of the `sendto` call, see [`Send::with_flags`].
+    pub fn with_flags(mut self, flags: i32) -> Self {
+        self.flags = flags;
+        self
+    }
 }
 
 impl<'arena, T: IoBuf<'arena>> IntoInner for SendTo<'arena, T> {
@@ -324,7 +914,7 @@ impl<'arena, T: IoBuf<'arena>> IntoInner for SendTo<'arena, T> {
 impl<'arena, T: IoBuf<'arena>> OpCode for SendTo<'arena, T> {
     fn operate(&mut self) -> Option<io::Result<usize>> {
         let slice = self.buffer.as_slice();
-        syscall!(maybe_block sendto(self.fd.as_raw_fd(), slice.as_ptr() as *const libc::c_void, slice.len(), 0, self.addr.as_ptr(), self.addr.len()))
+        syscall!(maybe_block sendto(self.fd.as_raw_fd(), slice.as_ptr() as *const libc::c_void, slice.len(), self.flags, self.addr.as_ptr(), self.addr.len()))
     }
 
     fn as_event(&self, user_data: usize) -> Event {
@@ -336,10 +926,11 @@ impl<'arena, T: AsIoSlices<'arena>> OpCode for SendMsgImpl<'arena, T> {
     fn operate(&mut self) -> Option<io::Result<usize>> {
         if self.msg.msg_namelen == 0 {
             let fd = self.fd;
+            let flags = self.flags;
             let msg = self.set_msg();
-            syscall!(maybe_block sendmsg(fd.as_raw_fd(), msg, 0))
+            syscall!(maybe_block sendmsg(fd.as_raw_fd(), msg, flags))
         } else {
-            syscall!(maybe_block sendmsg(self.fd.as_raw_fd(), &self.msg, 0))
+            syscall!(maybe_block sendmsg(self.fd.as_raw_fd(), &self.msg, self.flags))
         }
     }
 
@@ -348,6 +939,360 @@ impl<'arena, T: AsIoSlices<'arena>> OpCode for SendMsgImpl<'arena, T> {
     }
 }
 
+impl<'arena, T: AsIoSlicesMut<'arena>, C: IoBufMut<'arena>> OpCode for RecvMsg<'arena, T, C> {
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        let fd = self.fd;
+        let flags = self.flags();
+        let msg = self.set_msg();
+        syscall!(maybe_block recvmsg(fd.as_raw_fd(), msg, flags))
+    }
+
+    fn as_event(&self, user_data: usize) -> Event {
+        read_filter_event!(self, user_data)
+    }
+}
+
+impl<'arena, T: AsIoSlices<'arena>, C: IoBuf<'arena>> OpCode for SendMsg<'arena, T, C> {
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        let fd = self.fd;
+        let msg = self.set_msg();
+        syscall!(maybe_block sendmsg(fd.as_raw_fd(), msg, 0))
+    }
+
+    fn as_event(&self, user_data: usize) -> Event {
+        write_filter_event!(self, user_data)
+    }
+}
+
+/// Move data between two file descriptors.
+///
+/// The kqueue driver emulates splice with read and write through an intermediate buffer.
+pub struct Splice {
+    fd_in: FdOrFixed,
+    off_in: Option<u64>,
+    fd_out: FdOrFixed,
+    off_out: Option<u64>,
+    buffer: Vec<u8>,
+    written: usize,
+}
+
+impl Splice {
+    /// Create [`Splice`].
+    ///
+    /// Offsets should be `None` for pipes. `flags` are `SPLICE_F_*` flags described in
+    /// `splice(2)`.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_SPLICE` operation.
+    /// * kqueue: it is emulated with read and write through an intermediate buffer. Flags are
+    ///   ignored.
+    pub fn new(
+        fd_in: impl IntoFdOrFixed<Target = FdOrFixed>,
+        off_in: Option<u64>,
+        fd_out: impl IntoFdOrFixed<Target = FdOrFixed>,
+        off_out: Option<u64>,
+        len: u32,
+        _flags: u32,
+    ) -> Self {
+        Self {
+            fd_in: fd_in.into(),
+            off_in,
+            fd_out: fd_out.into(),
+            off_out,
+            buffer: Vec::with_capacity(len as usize),
+            written: 0,
+        }
+    }
+
+    fn read(&mut self) -> Option<io::Result<usize>> {
+        let fd = self.fd_in.as_raw_fd();
+        let slice = self.buffer.spare_capacity_mut();
+        if let Some(offset) = self.off_in {
+            syscall!(maybe_block pread(fd, slice.as_mut_ptr() as _, slice.len() as _, offset as _))
+        } else {
+            syscall!(maybe_block read(fd, slice.as_mut_ptr() as _, slice.len() as _))
+        }
+    }
+
+    fn write(&mut self) -> Option<io::Result<usize>> {
+        let fd = self.fd_out.as_raw_fd();
+        let slice = &self.buffer[self.written..];
+        if let Some(offset) = self.off_out {
+            let offset = offset + self.written as u64;
+            syscall!(maybe_block pwrite(fd, slice.as_ptr() as _, slice.len() as _, offset as _))
+        } else {
+            syscall!(maybe_block write(fd, slice.as_ptr() as _, slice.len() as _))
+        }
+    }
+}
+
+impl OpCode for Splice {
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        if self.buffer.is_empty() {
+            match self.read()? {
+                Ok(0) => return Some(Ok(0)),
+                // SAFETY: `read` initialized `n` bytes
+                Ok(n) => unsafe { self.buffer.set_len(n) },
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        // all read data has to be written to not lose it
+        while self.written < self.buffer.len() {
+            match self.write()? {
+                Ok(n) => self.written += n,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        Some(Ok(self.buffer.len()))
+    }
+
+    fn as_event(&self, user_data: usize) -> Event {
+        if self.buffer.is_empty() {
+            Event::new(
+                EventFilter::Read(self.fd_in.as_raw_fd()),
+                add_event_flags!(),
+                user_data as isize,
+            )
+        } else {
+            Event::new(
+                EventFilter::Write(self.fd_out.as_raw_fd()),
+                add_event_flags!(),
+                user_data as isize,
+            )
+        }
+    }
+}
+
+impl OpCode for Tee {
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "tee is not supported by kqueue driver",
+        )))
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("Tee operation should complete in one shot")
+    }
+}
+
+impl OpCode for Fadvise {
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        #[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+        let res = {
+            let res = unsafe {
+                libc::posix_fadvise(
+                    self.fd.as_raw_fd(),
+                    self.offset as _,
+                    self.len as _,
+                    self.posix_advice(),
+                )
+            };
+            // posix_fadvise returns error number instead of setting errno
+            if res != 0 {
+                Err(io::Error::from_raw_os_error(res))
+            } else {
+                Ok(0)
+            }
+        };
+        #[cfg(not(any(target_os = "freebsd", target_os = "dragonfly")))]
+        let res = Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "fadvise is not supported on this platform",
+        ));
+        Some(res)
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("Fadvise operation should complete in one shot")
+    }
+}
+
+impl OpCode for Madvise {
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        let res = self
+            .madvise_advice()
+            .and_then(|advice| syscall!(madvise(self.addr as *mut _, self.len, advice)).map(|_| 0));
+        Some(res)
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("Madvise operation should complete in one shot")
+    }
+}
+
+impl OpCode for Truncate {
+    fn is_blocking(&self) -> bool {
+        true
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(self.ftruncate())
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("Truncate operation should complete in one shot")
+    }
+}
+
+impl<'arena, T: IoBufMut<'arena>> OpCode for GetXattr<'arena, T> {
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(self.get())
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("GetXattr operation should complete in one shot")
+    }
+}
+
+impl<'arena, T: IoBuf<'arena>> OpCode for SetXattr<'arena, T> {
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(self.set())
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("SetXattr operation should complete in one shot")
+    }
+}
+
+impl OpCode for CopyFileRange {
+    fn is_blocking(&self) -> bool {
+        cfg!(target_os = "freebsd")
+    }
+
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        #[cfg(target_os = "freebsd")]
+        let res = self.copy_file_range();
+        #[cfg(not(target_os = "freebsd"))]
+        let res = Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "copy_file_range is not supported on this platform",
+        ));
+        Some(res)
+    }
+
+    fn as_event(&self, _: usize) -> Event {
+        unreachable!("CopyFileRange operation should complete in one shot")
+    }
+}
+
+/// Send a file region to a socket.
+///
+/// The operation should be resubmitted till [`SendFile::on_transfer`] reports completion.
+pub struct SendFile {
+    file_fd: FdOrFixed,
+    socket_fd: FdOrFixed,
+    offset: u64,
+    len: usize,
+    sent: usize,
+}
+
+impl SendFile {
+    /// Create [`SendFile`] to send `len` bytes of the file starting from `offset`.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: splices through an internal pipe owned by the operation.
+    /// * kqueue: `sendfile` syscall. Platforms without `sendfile` fail with
+    ///   [`io::ErrorKind::Unsupported`].
+    /// * IOCP: `TransmitFile`.
+    pub fn new(
+        file_fd: impl IntoFdOrFixed<Target = FdOrFixed>,
+        socket_fd: impl IntoFdOrFixed<Target = FdOrFixed>,
+        offset: u64,
+        len: usize,
+    ) -> io::Result<Self> {
+        Ok(Self {
+            file_fd: file_fd.into(),
+            socket_fd: socket_fd.into(),
+            offset,
+            len,
+            sent: 0,
+        })
+    }
+
+    /// Post operation handling.
+    ///
+    /// Returns `true` when `len` bytes were sent or the end of file is reached.
+    pub fn on_transfer(&mut self, result: io::Result<usize>) -> io::Result<bool> {
+        let transferred = result?;
+        self.sent += transferred;
+        Ok(transferred == 0 || self.sent == self.len)
+    }
+
+    /// Bytes sent to the socket so far.
+    pub fn sent(&self) -> usize {
+        self.sent
+    }
+}
+
+impl OpCode for SendFile {
+    #[cfg(any(
+        target_vendor = "apple",
+        target_os = "freebsd",
+        target_os = "dragonfly"
+    ))]
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        let offset = (self.offset + self.sent as u64) as libc::off_t;
+        let remaining = self.len - self.sent;
+        #[cfg(target_vendor = "apple")]
+        let (res, sent) = {
+            let mut sent = remaining as libc::off_t;
+            let res = syscall!(sendfile(
+                self.file_fd.as_raw_fd(),
+                self.socket_fd.as_raw_fd(),
+                offset,
+                &mut sent,
+                std::ptr::null_mut(),
+                0
+            ));
+            (res, sent)
+        };
+        #[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+        let (res, sent) = {
+            let mut sent: libc::off_t = 0;
+            let res = syscall!(sendfile(
+                self.file_fd.as_raw_fd(),
+                self.socket_fd.as_raw_fd(),
+                offset,
+                remaining,
+                std::ptr::null_mut(),
+                &mut sent,
+                0
+            ));
+            (res, sent)
+        };
+        match res {
+            Ok(_) => Some(Ok(sent as usize)),
+            // partially sent before the socket buffer became full
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock && sent > 0 => Some(Ok(sent as usize)),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+
+    #[cfg(not(any(
+        target_vendor = "apple",
+        target_os = "freebsd",
+        target_os = "dragonfly"
+    )))]
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "sendfile is not supported on this platform",
+        )))
+    }
+
+    fn as_event(&self, user_data: usize) -> Event {
+        Event::new(
+            EventFilter::Write(self.socket_fd.as_raw_fd()),
+            add_event_flags!(),
+            user_data as isize,
+        )
+    }
+}
+
 #[cfg(feature = "time")]
 impl OpCode for Timeout {
     fn operate(&mut self) -> Option<io::Result<usize>> {
@@ -359,9 +1304,43 @@ impl OpCode for Timeout {
     }
 
     #[cfg(feature = "time")]
+    fn timer_delay(&self) -> std::time::Duration {
+        self.delay()
+    }
+}
+
+#[cfg(feature = "time")]
+impl OpCode for TimeoutUpdate {
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(Ok(super::TIMER_UPDATE_PENDING))
+    }
+
+    fn as_event(&self, _user_data: usize) -> Event {
+        unimplemented!("not relevant to timers")
+    }
+
     fn timer_delay(&self) -> std::time::Duration {
         self.delay
     }
+
+    fn timer_target(&self) -> usize {
+        self.user_data
+    }
+}
+
+#[cfg(feature = "time")]
+impl OpCode for TimeoutRemove {
+    fn operate(&mut self) -> Option<io::Result<usize>> {
+        Some(Ok(super::TIMER_REMOVE_PENDING))
+    }
+
+    fn as_event(&self, _user_data: usize) -> Event {
+        unimplemented!("not relevant to timers")
+    }
+
+    fn timer_target(&self) -> usize {
+        self.user_data
+    }
 }
 
 fn close_raw_fd(raw_fd: RawFd) -> io::Result<usize> {
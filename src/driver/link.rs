@@ -0,0 +1,167 @@
+/// Sequential execution of linked operations for drivers without native linking
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+};
+
+use crate::driver::{Entry, OpObject, CANCELED};
+
+#[derive(Default)]
+struct Chain<'arena> {
+    // successors of the running operation
+    waiting: VecDeque<OpObject<'arena>>,
+    // the running operation failed while the chain was open
+    canceled: bool,
+}
+
+/// Chains of linked operations.
+///
+/// Only the first operation of a chain is pushed to the driver. The successor is pushed when
+/// its predecessor completes successfully. Otherwise the successors complete with the canceled
+/// error.
+#[derive(Default)]
+pub(super) struct Links<'arena> {
+    // chains keyed by user_data of the running operation
+    chains: HashMap<usize, Chain<'arena>>,
+    // key of the chain accepting pushed operations
+    open: Option<usize>,
+    // entries of canceled successors
+    canceled: Vec<Entry>,
+}
+
+impl<'arena> Links<'arena> {
+    #[inline]
+    pub(super) fn is_empty(&self) -> bool {
+        self.chains.is_empty() && self.canceled.is_empty()
+    }
+
+    /// Returns the operation if it should be pushed to the driver now.
+    #[inline]
+    pub(super) fn push(&mut self, op: OpObject<'arena>) -> Option<OpObject<'arena>> {
+        match self.open {
+            None => {
+                if op.is_linked() {
+                    self.chains.insert(op.user_data(), Chain::default());
+                    self.open = Some(op.user_data());
+                }
+                Some(op)
+            }
+            Some(key) => {
+                if !op.is_linked() {
+                    // the last operation of the chain
+                    self.open = None;
+                }
+                let chain = self.chains.get_mut(&key).expect("open chain exists");
+                if chain.canceled {
+                    self.canceled.push(canceled_entry(op.user_data()));
+                    if self.open.is_none() {
+                        self.chains.remove(&key);
+                    }
+                } else {
+                    chain.waiting.push_back(op);
+                }
+                None
+            }
+        }
+    }
+
+    /// Returns the successor to push when the running operation completes.
+    fn complete(&mut self, entry: &Entry) -> Option<OpObject<'arena>> {
+        let key = entry.user_data();
+        if entry.has_more() {
+            return None;
+        }
+        let mut chain = self.chains.remove(&key)?;
+        if entry.result.is_err() {
+            self.cancel_chain(key, chain);
+            return None;
+        }
+        let next = chain.waiting.pop_front();
+        match &next {
+            Some(next) if next.is_linked() => {
+                if self.open == Some(key) {
+                    self.open = Some(next.user_data());
+                }
+                self.chains.insert(next.user_data(), chain);
+            }
+            Some(_) => {}
+            None if self.open == Some(key) => {
+                if chain.canceled {
+                    // the operations pushed later are canceled
+                    self.chains.insert(key, chain);
+                } else {
+                    // the predecessor succeeded, the next pushed operation runs immediately
+                    self.open = None;
+                }
+            }
+            None => {}
+        }
+        next
+    }
+
+    /// Cancel the successors of the canceled operation.
+    ///
+    /// The canceled operation itself is removed by the driver. Returns `true` if the operation
+    /// was waiting for its predecessor.
+    pub(super) fn cancel(&mut self, user_data: usize) -> bool {
+        if let Some(chain) = self.chains.remove(&user_data) {
+            self.cancel_chain(user_data, chain);
+            return false;
+        }
+        let found = self.chains.iter_mut().find_map(|(key, chain)| {
+            let pos = chain
+                .waiting
+                .iter()
+                .position(|op| op.user_data() == user_data)?;
+            Some((*key, pos))
+        });
+        if let Some((key, pos)) = found {
+            let chain = self.chains.get_mut(&key).expect("found");
+            let successors = chain.waiting.split_off(pos + 1);
+            chain.waiting.truncate(pos);
+            self.canceled.extend(
+                successors
+                    .into_iter()
+                    .map(|op| canceled_entry(op.user_data())),
+            );
+            if self.open == Some(key) {
+                chain.canceled = true;
+            }
+        }
+        found.is_some()
+    }
+
+    /// Push successors of the completed operations to `squeue` and append entries of the canceled
+    /// ones to `completed`.
+    pub(super) fn complete_entries(
+        &mut self,
+        completed: &mut Vec<Entry>,
+        squeue: &mut Vec<OpObject<'arena>>,
+    ) {
+        for entry in completed.iter() {
+            if let Some(op) = self.complete(entry) {
+                squeue.push(op);
+            }
+        }
+        completed.append(&mut self.canceled);
+    }
+
+    fn cancel_chain(&mut self, key: usize, mut chain: Chain<'arena>) {
+        self.canceled.extend(
+            chain
+                .waiting
+                .drain(..)
+                .map(|op| canceled_entry(op.user_data())),
+        );
+        if self.open == Some(key) {
+            // the operations pushed later are canceled too
+            chain.canceled = true;
+            self.chains.insert(key, chain);
+        }
+    }
+}
+
+#[inline]
+pub(super) fn canceled_entry(user_data: usize) -> Entry {
+    Entry::new(user_data, Err(io::Error::from_raw_os_error(CANCELED)))
+}
@@ -0,0 +1,455 @@
+#[cfg(feature = "allocator_api")]
+use std::alloc::Allocator;
+use std::{
+    io::{self, IoSliceMut},
+    os::fd::{AsRawFd, RawFd},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    driver::{
+        completion, epoll,
+        epoll::requires_io_uring,
+        iour::{self, CqueueIter},
+        BackendKind, BufRing, CancelError, CompleteIo, DriverBuilder, DriverCapabilities,
+        DriverHandle, DriverStats, DriverWaker, Entry, Fd, FixedBufRegistry, FixedFd, FixedFdSlot,
+        OpCode, OpObject, Operation, PushError, WorkQueueToken,
+    },
+    vec_deque_alloc,
+};
+
+// the driver is created once, boxing the larger one would add an indirection to every call
+#[allow(clippy::large_enum_variant)]
+enum Backend<'arena> {
+    IoUring(iour::Driver<'arena>),
+    Epoll(epoll::Driver<'arena>),
+}
+
+// call the method of the selected backend
+macro_rules! dispatch {
+    ($self:ident, | $driver:ident | $body:expr) => {
+        match &mut $self.backend {
+            Backend::IoUring($driver) => $body,
+            Backend::Epoll($driver) => $body,
+        }
+    };
+    (ref $self:ident, | $driver:ident | $body:expr) => {
+        match &$self.backend {
+            Backend::IoUring($driver) => $body,
+            Backend::Epoll($driver) => $body,
+        }
+    };
+}
+
+/// Low-level driver of io-uring.
+///
+/// The driver falls back to epoll when io-uring is unavailable, e.g. before Linux 5.1 or when
+/// seccomp filters out `io_uring_setup`. epoll driver performs the operations in user space when
+/// their files are ready, so [`Driver::backend_kind`] tells which one is used. It sets
+/// `O_NONBLOCK` on blocking files for the calls which could block and restores the flag after
+/// them, the operations on regular files block the thread. The operations which io-uring
+/// performs asynchronously without an equivalent syscall fail with
+/// [`io::ErrorKind::Unsupported`].
+pub struct Driver<'arena> {
+    backend: Backend<'arena>,
+}
+
+// the backend can't be selected on Linux
+fn unsupported_backend(kind: BackendKind) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{kind:?} backend is not available on Linux"),
+    )
+}
+
+pub(crate) fn probe_capabilities() -> io::Result<DriverCapabilities> {
+    match iour::probe_capabilities() {
+        Err(e) if e.kind() == io::ErrorKind::Unsupported => Ok(epoll::capabilities()),
+        res => res,
+    }
+}
+
+impl<'arena> Driver<'arena> {
+    /// Create a new driver with 1024 entries and without registered files.
+    pub fn new() -> io::Result<Self> {
+        Self::with(1024, 0)
+    }
+
+    /// Create a new driver with specified entries and files to register.
+    pub fn with(entries: u32, files_to_register: u32) -> io::Result<Self> {
+        Self::builder()
+            .sq_entries(entries)
+            .files_to_register(files_to_register)
+            .build()
+    }
+
+    /// Create a builder of the driver.
+    pub fn builder() -> DriverBuilder {
+        DriverBuilder::new()
+    }
+
+    pub(crate) fn from_builder(builder: &DriverBuilder) -> io::Result<Self> {
+        let backend = match builder.backend {
+            Some(BackendKind::IoUring) => Backend::IoUring(iour::Driver::from_builder(builder)?),
+            Some(BackendKind::Epoll) => Backend::Epoll(epoll::Driver::from_builder(builder)?),
+            Some(kind) => return Err(unsupported_backend(kind)),
+            None => match iour::Driver::from_builder(builder) {
+                Ok(driver) => Backend::IoUring(driver),
+                Err(e) if e.kind() == io::ErrorKind::Unsupported => {
+                    Backend::Epoll(epoll::Driver::from_builder(builder)?)
+                }
+                Err(e) => return Err(e),
+            },
+        };
+        Ok(Self { backend })
+    }
+
+    /// Operations natively supported by the kernel, probed when the driver is created.
+    ///
+    /// epoll driver emulates the operations in user space, so none of them is native.
+    pub fn capabilities(&self) -> DriverCapabilities {
+        match &self.backend {
+            Backend::IoUring(driver) => driver.capabilities(),
+            Backend::Epoll(_) => epoll::capabilities(),
+        }
+    }
+
+    /// The backend of the driver, epoll when io-uring is unavailable.
+    pub fn backend_kind(&self) -> BackendKind {
+        match &self.backend {
+            Backend::IoUring(_) => BackendKind::IoUring,
+            Backend::Epoll(_) => BackendKind::Epoll,
+        }
+    }
+
+    /// Counters of the driver operations.
+    pub fn stats(&self) -> DriverStats {
+        dispatch!(ref self, |driver| driver.stats())
+    }
+
+    /// Reset the counters of the driver operations.
+    pub fn reset_stats(&mut self) {
+        dispatch!(self, |driver| driver.reset_stats())
+    }
+
+    /// The number of entries which could be pushed to the submission queue.
+    pub fn sq_space_left(&self) -> usize {
+        match &self.backend {
+            Backend::IoUring(driver) => driver.sq_space_left(),
+            Backend::Epoll(driver) => driver.capacity_left(),
+        }
+    }
+
+    /// The number of entries ready in the completion queue.
+    pub fn cq_ready(&self) -> usize {
+        dispatch!(ref self, |driver| driver.cq_ready())
+    }
+
+    /// Whether the kernel reported the completion queue overflow with `IORING_SQ_CQ_OVERFLOW`
+    /// flag since the driver is created or the counters are reset.
+    ///
+    /// epoll driver has no completion queue, it never overflows.
+    pub fn cq_overflow_observed(&self) -> bool {
+        match &self.backend {
+            Backend::IoUring(driver) => driver.cq_overflow_observed(),
+            Backend::Epoll(_) => false,
+        }
+    }
+
+    /// Cancel the operations in flight and wait until the kernel completes them.
+    ///
+    /// The entries of the canceled operations are discarded. The driver waits without `timeout`
+    /// till all operations complete, [`io::ErrorKind::TimedOut`] is returned if the timeout
+    /// passes first. The driver is shut down on drop, so the kernel doesn't access the operation
+    /// buffers after the driver is gone.
+    ///
+    /// Linux before 5.19 can't cancel all operations with one request, the driver cancels them
+    /// one by one. epoll driver performs the operations in user space, so none of them is in
+    /// flight and the pushed ones are discarded.
+    ///
+    /// If the shutdown of io-uring driver on drop fails, the process is aborted: the kernel
+    /// could write to the buffers of the operations after they are released.
+    pub fn shutdown(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        match &mut self.backend {
+            Backend::IoUring(driver) => driver.shutdown(timeout),
+            Backend::Epoll(driver) => {
+                driver.shutdown();
+                Ok(())
+            }
+        }
+    }
+
+    /// Create a handle to post completions into the driver from other threads.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::ErrorKind::Unsupported`] if the driver falls back to epoll.
+    pub fn handle(&self) -> io::Result<DriverHandle> {
+        match &self.backend {
+            Backend::IoUring(driver) => driver.handle(),
+            Backend::Epoll(_) => Err(requires_io_uring("DriverHandle")),
+        }
+    }
+
+    /// Create a token to share the kernel worker pool of the driver with other drivers.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::ErrorKind::Unsupported`] if the driver falls back to epoll.
+    pub fn work_queue_token(&mut self) -> io::Result<WorkQueueToken> {
+        match &mut self.backend {
+            Backend::IoUring(driver) => driver.work_queue_token(),
+            Backend::Epoll(_) => Err(requires_io_uring("WorkQueueToken")),
+        }
+    }
+
+    /// Create a handle to wake up the driver from other threads.
+    ///
+    /// The first call creates an eventfd and pushes the multishot poll of it, the poll is not
+    /// reported as an operation. epoll driver adds the eventfd to its interest list.
+    pub fn waker(&mut self) -> io::Result<DriverWaker> {
+        dispatch!(self, |driver| driver.waker())
+    }
+
+    /// Register the ring of provided buffers.
+    ///
+    /// Operations select buffers from the ring by its group id. Available since Linux 5.19.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::ErrorKind::Unsupported`] if the driver falls back to epoll.
+    pub fn register_buf_ring(&mut self, ring: &'arena BufRing) -> io::Result<()> {
+        match &mut self.backend {
+            Backend::IoUring(driver) => driver.register_buf_ring(ring),
+            Backend::Epoll(_) => Err(requires_io_uring("BufRing")),
+        }
+    }
+
+    /// Unregister the ring of provided buffers with the group id.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::ErrorKind::Unsupported`] if the driver falls back to epoll.
+    pub fn unregister_buf_ring(&mut self, group_id: u16) -> io::Result<()> {
+        match &mut self.backend {
+            Backend::IoUring(driver) => driver.unregister_buf_ring(group_id),
+            Backend::Epoll(_) => Err(requires_io_uring("BufRing")),
+        }
+    }
+
+    /// Busy poll the network devices of the sockets for `busy_poll` when the driver waits for
+    /// completions, the previous registration is replaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::ErrorKind::Unsupported`] before Linux 6.9, if the kernel is built
+    /// without busy polling or the driver falls back to epoll.
+    pub fn register_napi(&mut self, busy_poll: Duration, prefer_busy_poll: bool) -> io::Result<()> {
+        match &mut self.backend {
+            Backend::IoUring(driver) => driver.register_napi(busy_poll, prefer_busy_poll),
+            Backend::Epoll(_) => Err(requires_io_uring("NAPI busy polling")),
+        }
+    }
+
+    /// Stop busy polling the network devices.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::ErrorKind::Unsupported`] before Linux 6.9, if the kernel is built
+    /// without busy polling or the driver falls back to epoll.
+    pub fn unregister_napi(&mut self) -> io::Result<()> {
+        match &mut self.backend {
+            Backend::IoUring(driver) => driver.unregister_napi(),
+            Backend::Epoll(_) => Err(requires_io_uring("NAPI busy polling")),
+        }
+    }
+}
+
+impl<'arena> CompleteIo<'arena> for Driver<'arena> {
+    #[inline]
+    fn attach(&mut self, fd: RawFd) -> io::Result<Fd> {
+        dispatch!(self, |driver| driver.attach(fd))
+    }
+
+    #[inline]
+    fn detach(&mut self, fd: RawFd) -> io::Result<()> {
+        dispatch!(self, |driver| driver.detach(fd))
+    }
+
+    #[inline]
+    fn register_fd(&mut self, fd: RawFd, id: u32) -> io::Result<FixedFd> {
+        dispatch!(self, |driver| driver.register_fd(fd, id))
+    }
+
+    #[inline]
+    fn unregister_fd(&mut self, fixed_fd: FixedFd) -> io::Result<()> {
+        dispatch!(self, |driver| driver.unregister_fd(fixed_fd))
+    }
+
+    #[inline]
+    fn register_files(&mut self, fds: &[RawFd]) -> io::Result<FixedFdSlot> {
+        dispatch!(self, |driver| driver.register_files(fds))
+    }
+
+    #[inline]
+    fn register_files_update(
+        &mut self,
+        slot: &mut FixedFdSlot,
+        index: usize,
+        fds: &[RawFd],
+    ) -> io::Result<()> {
+        dispatch!(self, |driver| driver
+            .register_files_update(slot, index, fds))
+    }
+
+    #[inline]
+    fn unregister_files(&mut self, slot: FixedFdSlot) -> io::Result<()> {
+        dispatch!(self, |driver| driver.unregister_files(slot))
+    }
+
+    #[inline]
+    fn register_buffers<'a: 'arena>(
+        &mut self,
+        bufs: &'a mut [IoSliceMut<'a>],
+    ) -> io::Result<FixedBufRegistry<'a>> {
+        dispatch!(self, |driver| driver.register_buffers(bufs))
+    }
+
+    #[inline]
+    fn unregister_buffers(&mut self, registry: &FixedBufRegistry<'_>) -> io::Result<()> {
+        dispatch!(self, |driver| driver.unregister_buffers(registry))
+    }
+
+    #[inline]
+    fn try_cancel(&mut self, user_data: usize) -> Result<(), CancelError> {
+        dispatch!(self, |driver| driver.try_cancel(user_data))
+    }
+
+    #[inline]
+    fn try_push<O: OpCode>(
+        &mut self,
+        op: Operation<'arena, O>,
+    ) -> Result<(), PushError<Operation<'arena, O>>> {
+        dispatch!(self, |driver| driver.try_push(op))
+    }
+
+    #[inline]
+    fn try_push_dyn(&mut self, op: OpObject<'arena>) -> Result<(), PushError<OpObject<'arena>>> {
+        dispatch!(self, |driver| driver.try_push_dyn(op))
+    }
+
+    #[inline]
+    fn push_queue<#[cfg(feature = "allocator_api")] A: Allocator + Unpin + 'arena>(
+        &mut self,
+        ops_queue: &mut vec_deque_alloc!(OpObject<'arena>, A),
+    ) {
+        dispatch!(self, |driver| driver.push_queue(ops_queue))
+    }
+
+    #[inline]
+    fn capacity_left(&self) -> usize {
+        dispatch!(ref self, |driver| driver.capacity_left())
+    }
+
+    #[inline]
+    fn is_saturated(&self) -> bool {
+        dispatch!(ref self, |driver| driver.is_saturated())
+    }
+
+    unsafe fn submit(
+        &mut self,
+        timeout: Option<Duration>,
+        completed: &mut impl Extend<Entry>,
+    ) -> io::Result<()> {
+        dispatch!(self, |driver| driver.submit(timeout, completed))
+    }
+
+    fn peek_completed(&mut self) -> CompletionIter<'_, 'arena> {
+        dispatch!(self, |driver| driver.peek_completed())
+    }
+
+    unsafe fn submit_and_wait_min(
+        &mut self,
+        timeout: Option<Duration>,
+        min_complete: usize,
+        completed: &mut impl Extend<Entry>,
+    ) -> io::Result<()> {
+        dispatch!(self, |driver| driver.submit_and_wait_min(
+            timeout,
+            min_complete,
+            completed
+        ))
+    }
+
+    unsafe fn submit_until(
+        &mut self,
+        deadline: Instant,
+        completed: &mut impl Extend<Entry>,
+    ) -> io::Result<bool> {
+        dispatch!(self, |driver| driver.submit_until(deadline, completed))
+    }
+
+    unsafe fn flush(&mut self) -> io::Result<usize> {
+        dispatch!(self, |driver| driver.flush())
+    }
+
+    unsafe fn wait_completed(
+        &mut self,
+        timeout: Option<Duration>,
+        completed: &mut impl Extend<Entry>,
+    ) -> io::Result<()> {
+        dispatch!(self, |driver| driver.wait_completed(timeout, completed))
+    }
+}
+
+impl AsRawFd for Driver<'_> {
+    fn as_raw_fd(&self) -> RawFd {
+        dispatch!(ref self, |driver| driver.as_raw_fd())
+    }
+}
+
+enum Completions<'a, 'arena> {
+    IoUring(CqueueIter<'a, 'arena>),
+    Epoll(completion::CompletionIter<'a, 'arena>),
+}
+
+/// Iterator of completed entries returned by
+/// [`CompleteIo::peek_completed`](crate::driver::CompleteIo::peek_completed).
+///
+/// Every entry is consumed from the completion queue when it is returned.
+pub struct CompletionIter<'a, 'arena> {
+    inner: Completions<'a, 'arena>,
+}
+
+impl<'a, 'arena> CompletionIter<'a, 'arena> {
+    pub(super) fn io_uring(iter: CqueueIter<'a, 'arena>) -> Self {
+        Self {
+            inner: Completions::IoUring(iter),
+        }
+    }
+
+    pub(super) fn epoll(iter: completion::CompletionIter<'a, 'arena>) -> Self {
+        Self {
+            inner: Completions::Epoll(iter),
+        }
+    }
+}
+
+impl Iterator for CompletionIter<'_, '_> {
+    type Item = Entry;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            Completions::IoUring(iter) => iter.next(),
+            Completions::Epoll(iter) => iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.inner {
+            Completions::IoUring(iter) => iter.size_hint(),
+            Completions::Epoll(iter) => iter.size_hint(),
+        }
+    }
+}
@@ -3,24 +3,62 @@
 
 #[cfg(feature = "allocator_api")]
 use std::alloc::Allocator;
-use std::{io, time::Duration};
+use std::{
+    fmt,
+    io::{self, IoSliceMut},
+    time::{Duration, Instant},
+};
 
 use crate::vec_deque_alloc;
 
+mod builder;
+pub use builder::*;
+mod capabilities;
+pub use capabilities::*;
+mod fixed_buf;
+pub use fixed_buf::*;
+mod hooks;
+#[cfg(feature = "hooks")]
+pub use hooks::DriverHooks;
+use hooks::{op_name, Hooks};
+mod stats;
+pub use stats::*;
+mod user_data;
+use user_data::UserDataSet;
 #[cfg(unix)]
 mod unix;
+#[cfg(all(target_os = "linux", feature = "runtime"))]
+pub(crate) use unix::op::copy_file_range;
 
 cfg_if::cfg_if! {
     if #[cfg(target_os = "windows")] {
         mod iocp;
+        mod completion;
+        pub use completion::*;
+        mod link;
+        mod op_timeout;
         #[cfg(feature="time")]
         mod time;
         pub use iocp::*;
     } else if #[cfg(target_os = "linux")] {
         mod iour;
         pub use iour::*;
+        mod epoll;
+        pub use epoll::{EpollFiles, Readiness};
+        mod completion;
+        mod link;
+        mod op_timeout;
+        #[cfg(feature="time")]
+        mod time;
+        mod linux;
+        pub use linux::{CompletionIter, Driver};
+        pub(crate) use linux::probe_capabilities;
     } else if #[cfg(any(target_vendor= "apple", target_os = "freebsd", target_os = "dragonfly", target_os = "openbsd", target_os = "netbsd"))] {
         mod kqueue;
+        mod completion;
+        pub use completion::*;
+        mod link;
+        mod op_timeout;
         #[cfg(feature="time")]
         mod time;
         pub use self::kqueue::*;
@@ -40,7 +78,7 @@ cfg_if::cfg_if! {
 /// # Examples
 ///
 /// ```
-/// use std::{collections::VecDeque, net::SocketAddr};
+/// use std::{collections::VecDeque, net::SocketAddr, time::Duration};
 ///
 /// use arrayvec::ArrayVec;
 /// use completeio::{
@@ -63,24 +101,29 @@ cfg_if::cfg_if! {
 /// socket.connect(second_addr).unwrap();
 /// other_socket.connect(first_addr).unwrap();
 ///
+/// // the operations outlive the driver
+/// let mut op_send;
+/// let mut op_recv;
 /// let mut driver = Driver::new().unwrap();
 /// let fd = driver.attach(socket.as_raw_fd()).unwrap();
 /// let other_fd = driver.attach(other_socket.as_raw_fd()).unwrap();
 ///
 /// // write data
-/// let mut op_send = op::Send::new(fd, "hello world");
+/// op_send = op::Send::new(fd, "hello world");
 ///
 /// // read data
 /// let buf = Vec::with_capacity(32);
-/// let mut op_recv = op::Recv::new(other_fd, buf);
+/// op_recv = op::Recv::new(other_fd, buf);
 ///
 /// let mut ops = VecDeque::from([(&mut op_send, 1).into(), (&mut op_recv, 2).into()]);
 /// driver.push_queue(&mut ops);
 /// let mut entries = ArrayVec::<Entry, 2>::new();
-/// unsafe { driver.submit(None, &mut entries).unwrap() };
-/// while entries.len() < 2 {
-///     unsafe { driver.submit(None, &mut entries).unwrap() };
-/// }
+/// unsafe {
+///     driver
+///         .submit_and_wait_min(Some(Duration::from_secs(5)), 2, &mut entries)
+///         .unwrap()
+/// };
+/// assert_eq!(entries.len(), 2);
 ///
 /// let mut n_bytes = 0;
 /// for entry in entries {
@@ -95,6 +138,15 @@ cfg_if::cfg_if! {
 ///     }
 /// }
 ///
+/// let stats = driver.stats();
+/// println!("{stats:?}");
+/// assert_eq!(stats.submitted, 2);
+/// assert_eq!(stats.completed, 2);
+/// assert_eq!(stats.in_flight(), 0);
+/// assert_eq!(stats.max_queued, 2);
+///
+/// // the driver borrows the operations until it's dropped
+/// drop(driver);
 /// let mut buf = op_recv.into_inner();
 /// unsafe { buf.set_len(n_bytes) };
 /// assert_eq!(buf, b"hello world");
@@ -111,6 +163,17 @@ pub trait CompleteIo<'arena> {
     /// To close fd issue `Close` operation using Fd as OpCode value.
     fn attach(&mut self, fd: RawFd) -> io::Result<Fd>;
 
+    /// Detach an fd from the driver, so it could be attached to another driver.
+    ///
+    /// The error is returned if the driver has pending operations of the fd.
+    ///
+    /// ## Platform specific
+    /// * IOCP: the handle is dissociated from the completion port, available since Windows 8.1. The
+    ///   duplicated handles share the association, so they are detached too.
+    /// * io-uring: it will do nothing and return `Ok(())`.
+    /// * kqueue: only the operations waiting for readiness are checked.
+    fn detach(&mut self, fd: RawFd) -> io::Result<()>;
+
     /// Attach fd to the driver and register it as fixed file descriptor with the provided fixed id.
     ///
     /// ## Platform specific
@@ -133,41 +196,117 @@ pub trait CompleteIo<'arena> {
     /// IOCP/kqueue: will do nothing
     fn unregister_fd(&mut self, fixed_fd: FixedFd) -> io::Result<()>;
 
+    /// Attach fds to the driver and register them as fixed file descriptors in a free slot.
+    ///
+    /// ## Platform specific
+    /// * io-uring: the files are registered in the first free range of `files_to_register`
+    ///   registered files the same way as [`CompleteIo::register_fd`] does. Fails if there is no
+    ///   free range.
+    /// * IOCP: the fds are attached to the completion port.
+    /// * kqueue: the fds are only stored in the slot.
+    fn register_files(&mut self, fds: &[RawFd]) -> io::Result<FixedFdSlot>;
+
+    /// Replace registered files in the slot starting from `index` with `fds`.
+    ///
+    /// Fails if the replaced files are out of the slot.
+    fn register_files_update(
+        &mut self,
+        slot: &mut FixedFdSlot,
+        index: usize,
+        fds: &[RawFd],
+    ) -> io::Result<()>;
+
+    /// Unregister files of the slot.
+    ///
+    /// ## Platform specific
+    /// * io-uring: the files could be used by pushed operations, so they are unregistered
+    ///   asynchronously after them. If another async registration is in progress, the removal is
+    ///   deferred till it completes. The slot becomes free after the removal completes.
+    /// * IOCP/kqueue: will do nothing
+    fn unregister_files(&mut self, slot: FixedFdSlot) -> io::Result<()>;
+
+    /// Register buffers for [`ReadAtFixed`](crate::op::ReadAtFixed) and
+    /// [`WriteAtFixed`](crate::op::WriteAtFixed) operations.
+    ///
+    /// ## Platform specific
+    /// * io-uring: the buffers are registered with `IORING_REGISTER_BUFFERS`, so the kernel maps
+    ///   them once instead of pinning pages on every operation. Only one set of buffers could be
+    ///   registered at a time.
+    /// * IOCP/kqueue: will do nothing, the operations are regular reads and writes.
+    fn register_buffers<'a: 'arena>(
+        &mut self,
+        bufs: &'a mut [IoSliceMut<'a>],
+    ) -> io::Result<FixedBufRegistry<'a>>;
+
+    /// Unregister buffers.
+    ///
+    /// Fails if a buffer is checked out from the registry, because an operation could use it.
+    fn unregister_buffers(&mut self, registry: &FixedBufRegistry<'_>) -> io::Result<()>;
+
     /// Try to cancel an operation with the pushed user-defined data.
     ///
-    /// If submission queue is full the error is returned. The caller should
-    /// queue the cancelation request or submit queued entries first.
+    /// If submission queue is full [`CancelError::QueueFull`] is returned. The caller should
+    /// queue the cancelation request and retry after submitting queued entries.
     ///
     /// If the cancellation is not possible the operation will run till
     /// completed.
     ///
     /// When an operation is cancelled or completed successfully
     /// `submit` will output it in `completed` iterator.
-    fn try_cancel(&mut self, user_data: usize) -> Result<(), ()>;
+    ///
+    /// Push [`Cancel`](crate::op::Cancel) to get the result of the cancellation itself.
+    ///
+    /// ## Platform specific
+    /// * io-uring: cancel request uses reserved `u64::MAX - 1` user_data key. The queued entries
+    ///   are submitted to make room for the request if the submission queue is full. The kernel
+    ///   looks up the operation asynchronously, so [`CancelError::NotFound`] is not returned.
+    /// * IOCP/kqueue: the operation is canceled immediately, [`CancelError::NotFound`] is returned
+    ///   if the operation has completed already or the user-defined data is unknown.
+    fn try_cancel(&mut self, user_data: usize) -> Result<(), CancelError>;
 
     /// Try to push operation into submission queue
     ///
     /// If the queue is full the submitted operation is returned as an error.
     /// Caller could use an external queue like VecDeque<OpObject<'a>> to keep
     /// unqueued operations.
-    fn try_push<O: OpCode>(&mut self, op: Operation<'arena, O>)
-    -> Result<(), Operation<'arena, O>>;
+    ///
+    /// The operation is rejected with [`PushError::DuplicateUserData`] if the driver checks the
+    /// user defined data, see [`DriverBuilder::check_user_data`].
+    fn try_push<O: OpCode>(
+        &mut self,
+        op: Operation<'arena, O>,
+    ) -> Result<(), PushError<Operation<'arena, O>>>;
 
     /// Try to push operation object into submission queue
-    fn try_push_dyn(&mut self, op: OpObject<'arena>) -> Result<(), OpObject<'arena>>;
+    fn try_push_dyn(&mut self, op: OpObject<'arena>) -> Result<(), PushError<OpObject<'arena>>>;
 
     /// Push multiple operations into submission queue from an external VecDeque
     ///
     /// After push the external queue could contain operations that didn't fit
-    /// into the submission queue
+    /// into the submission queue. The pushing stops at the operation with the user defined data
+    /// of an operation in flight if the driver checks it.
     fn push_queue<#[cfg(feature = "allocator_api")] A: Allocator + Unpin + 'arena>(
         &mut self,
         ops_queue: &mut vec_deque_alloc!(OpObject<'arena>, A),
     );
 
     /// Returns submission queue capacity left for pushing.
+    ///
+    /// io-uring counts the completion entries expected from the operations in flight against
+    /// the completion queue, so the pushed operations don't overflow it before their entries
+    /// are consumed. An operation with a timeout or a notification posts two entries, the
+    /// operations which complete multiple times are counted once, and a few entries are kept
+    /// for the internal requests. kqueue and IOCP keep the pending operations without a limit,
+    /// only the submission queue is counted.
     fn capacity_left(&self) -> usize;
 
+    /// Whether no operation could be pushed until the completed entries are consumed or the
+    /// queued operations are submitted.
+    #[inline]
+    fn is_saturated(&self) -> bool {
+        self.capacity_left() == 0
+    }
+
     /// Submit queued operations and wait for completed entries with an optional
     /// timeout.
     ///
@@ -188,18 +327,203 @@ pub trait CompleteIo<'arena> {
         timeout: Option<Duration>,
         completed: &mut impl Extend<Entry>,
     ) -> io::Result<()>;
+
+    /// Returns the iterator of entries which are completed already.
+    ///
+    /// The call doesn't block and doesn't enter the kernel. The entries are consumed as the
+    /// iterator returns them, the rest stays in the driver if the iterator is dropped early.
+    ///
+    /// ## Platform specific
+    /// * io-uring: the entries are read from the completion queue.
+    /// * IOCP/kqueue: only the entries of operations completed on push or [`CompleteIo::flush`] are
+    ///   available. Other completions are received by [`CompleteIo::submit`].
+    fn peek_completed(&mut self) -> CompletionIter<'_, 'arena>;
+
+    /// Submit queued operations and wait till at least `min_complete` entries are completed or
+    /// `timeout` passes.
+    ///
+    /// The timeout is handled like in [`CompleteIo::submit`] and wins over `min_complete`, so
+    /// fewer entries are output when it passes first. The wait returns early too if it's
+    /// interrupted by [`DriverWaker`].
+    ///
+    /// ## Platform specific
+    /// * io-uring: `min_complete` is passed to the kernel, which wakes up the driver once.
+    /// * IOCP/kqueue: the driver waits in a loop till the threshold.
+    ///
+    /// # Safety
+    ///
+    /// See [`CompleteIo::submit`].
+    unsafe fn submit_and_wait_min(
+        &mut self,
+        timeout: Option<Duration>,
+        min_complete: usize,
+        completed: &mut impl Extend<Entry>,
+    ) -> io::Result<()> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut completed = CountingExtend {
+            inner: completed,
+            count: 0,
+        };
+        loop {
+            let timeout =
+                deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+            let count = completed.count;
+            self.submit(timeout, &mut completed)?;
+            if completed.count >= min_complete
+                // the wait is interrupted
+                || completed.count == count
+                || timeout == Some(Duration::ZERO)
+            {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Submit queued operations and wait for completed entries till `deadline`.
+    ///
+    /// The wait interrupted by a signal is resumed with the time left, so the deadline doesn't
+    /// drift. Returns `true` if the deadline has passed without completed entries. The wait
+    /// woken up by [`DriverWaker`] returns `false` without entries.
+    ///
+    /// ## Platform specific
+    /// * io-uring: the kernel waits till the absolute deadline with `IORING_ENTER_ABS_TIMER`,
+    ///   available since Linux 6.12.
+    ///
+    /// # Safety
+    ///
+    /// See [`CompleteIo::submit`].
+    unsafe fn submit_until(
+        &mut self,
+        deadline: Instant,
+        completed: &mut impl Extend<Entry>,
+    ) -> io::Result<bool> {
+        let mut completed = CountingExtend {
+            inner: completed,
+            count: 0,
+        };
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match self.submit(Some(timeout), &mut completed) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {
+                    if completed.count == 0 {
+                        continue;
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+            return Ok(completed.count == 0 && Instant::now() >= deadline);
+        }
+    }
+
+    /// Submit queued operations without waiting for completed entries.
+    ///
+    /// Returns the number of submitted operations. The call doesn't block, the completed entries
+    /// are collected later with [`CompleteIo::wait_completed`].
+    ///
+    /// ## Platform specific
+    /// * io-uring: the submission queue is passed to the kernel.
+    /// * IOCP/kqueue: the operations are started. The entries of the operations completed
+    ///   immediately are output by the next wait.
+    ///
+    /// # Safety
+    ///
+    /// See [`CompleteIo::submit`].
+    unsafe fn flush(&mut self) -> io::Result<usize>;
+
+    /// Wait for completed entries with an optional timeout.
+    ///
+    /// The timeout is handled like in [`CompleteIo::submit`]. Operations pushed after the last
+    /// [`CompleteIo::flush`] are submitted too.
+    ///
+    /// # Safety
+    ///
+    /// See [`CompleteIo::submit`].
+    unsafe fn wait_completed(
+        &mut self,
+        timeout: Option<Duration>,
+        completed: &mut impl Extend<Entry>,
+    ) -> io::Result<()> {
+        self.submit(timeout, completed)
+    }
+}
+
+// counts the entries output by the driver
+struct CountingExtend<'a, E> {
+    inner: &'a mut E,
+    count: usize,
+}
+
+impl<E: Extend<Entry>> Extend<Entry> for CountingExtend<'_, E> {
+    fn extend<T: IntoIterator<Item = Entry>>(&mut self, iter: T) {
+        let count = &mut self.count;
+        self.inner.extend(iter.into_iter().inspect(|_| *count += 1));
+    }
 }
 
 /// An operation with a unique user defined data.
 pub struct Operation<'a, O: OpCode> {
     op: &'a mut O,
     user_data: usize,
+    link: bool,
+    timeout: Option<Duration>,
 }
 
 impl<'a, O: OpCode> Operation<'a, O> {
     /// Create [`Operation`].
     pub fn new(op: &'a mut O, user_data: usize) -> Self {
-        Self { op, user_data }
+        Self {
+            op,
+            user_data,
+            link: false,
+            timeout: None,
+        }
+    }
+
+    /// Create [`Operation`] which is canceled after `timeout` since it is pushed.
+    ///
+    /// The timed out operation completes with [`io::ErrorKind::TimedOut`].
+    ///
+    /// ## Platform specific
+    /// * io-uring: the operation is linked to `IORING_OP_LINK_TIMEOUT`, the timeout takes two
+    ///   submission entries. The timeout uses the `user_data` with the highest bit set, so it must
+    ///   be clear in the `user_data` of the operation. An operation canceled for another reason,
+    ///   e.g. the failed predecessor in the chain, completes with the canceled error.
+    /// * IOCP: the pending operation is canceled with `CancelIoEx`.
+    /// * kqueue: the pending operation is removed from the driver.
+    pub fn with_timeout(op: &'a mut O, user_data: usize, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..Self::new(op, user_data)
+        }
+    }
+
+    /// Link the next pushed operation to this one.
+    ///
+    /// The next operation starts only after this one completes successfully. Otherwise the
+    /// next operation completes with the canceled error, as well as the rest of the chain.
+    /// Completions of linked operations still arrive per operation.
+    ///
+    /// The chain ends with the first pushed operation without the link.
+    ///
+    /// ## Platform specific
+    /// * io-uring: the submission entry is flagged with `IOSQE_IO_LINK`, the chain should be pushed
+    ///   before the next `submit`. Operations completing before submission, like
+    ///   [`Truncate`](crate::op::Truncate), shouldn't be linked.
+    /// * IOCP/kqueue: the driver holds the successors and pushes them one by one.
+    pub fn link(mut self) -> Self {
+        self.link = true;
+        self
+    }
+
+    /// Whether the next pushed operation is linked to this one.
+    pub fn is_linked(&self) -> bool {
+        self.link
+    }
+
+    /// Get the timeout of the operation.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
     }
 
     /// Get the opcode.
@@ -228,12 +552,47 @@ impl<'a, O: OpCode> From<Operation<'a, O>> for (&'a mut O, usize) {
 pub struct OpObject<'a> {
     op: &'a mut dyn OpCode,
     user_data: usize,
+    link: bool,
+    timeout: Option<Duration>,
 }
 
 impl<'a> OpObject<'a> {
     /// Create [`Operation`].
     pub fn new(op: &'a mut dyn OpCode, user_data: usize) -> Self {
-        Self { op, user_data }
+        Self {
+            op,
+            user_data,
+            link: false,
+            timeout: None,
+        }
+    }
+
+    /// Create [`OpObject`] which is canceled after `timeout` since it is pushed.
+    ///
+    /// See [`Operation::with_timeout`].
+    pub fn with_timeout(op: &'a mut dyn OpCode, user_data: usize, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..Self::new(op, user_data)
+        }
+    }
+
+    /// Link the next pushed operation to this one.
+    ///
+    /// See [`Operation::link`].
+    pub fn link(mut self) -> Self {
+        self.link = true;
+        self
+    }
+
+    /// Whether the next pushed operation is linked to this one.
+    pub fn is_linked(&self) -> bool {
+        self.link
+    }
+
+    /// Get the timeout of the operation.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
     }
 
     /// Get the mut opcode.
@@ -266,7 +625,12 @@ impl<'a> From<(&'a mut dyn OpCode, usize)> for OpObject<'a> {
 
 impl<'a, O: OpCode> From<Operation<'a, O>> for OpObject<'a> {
     fn from(other: Operation<'a, O>) -> Self {
-        Self::new(other.op, other.user_data)
+        Self {
+            op: other.op,
+            user_data: other.user_data,
+            link: other.link,
+            timeout: other.timeout,
+        }
     }
 }
 
@@ -276,16 +640,185 @@ impl<'a> From<OpObject<'a>> for (&'a mut dyn OpCode, usize) {
     }
 }
 
+/// The error of [`CompleteIo::try_cancel`].
+#[derive(Debug)]
+pub enum CancelError {
+    /// The submission queue is full, the cancellation should be retried later.
+    QueueFull,
+    /// The operation is not found.
+    NotFound,
+    /// The cancellation failed with the OS error.
+    Os(io::Error),
+}
+
+impl fmt::Display for CancelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::QueueFull => f.write_str("submission queue is full"),
+            Self::NotFound => f.write_str("operation is not found"),
+            Self::Os(e) => write!(f, "operation cancellation failed: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for CancelError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Os(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<CancelError> for io::Error {
+    fn from(e: CancelError) -> Self {
+        match e {
+            CancelError::QueueFull => io::Error::new(io::ErrorKind::WouldBlock, e),
+            CancelError::NotFound => io::Error::new(io::ErrorKind::NotFound, e),
+            CancelError::Os(e) => e,
+        }
+    }
+}
+
+/// The error of [`CompleteIo::try_push`], the operation is returned back.
+pub enum PushError<T> {
+    /// The submission queue is full, the operation should be pushed later.
+    QueueFull(T),
+    /// An operation with the same user defined data is in flight.
+    DuplicateUserData(T),
+}
+
+impl<T> PushError<T> {
+    /// Get the rejected operation.
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::QueueFull(op) | Self::DuplicateUserData(op) => op,
+        }
+    }
+}
+
+impl<T> fmt::Debug for PushError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::QueueFull(_) => f.debug_tuple("QueueFull").finish_non_exhaustive(),
+            Self::DuplicateUserData(_) => {
+                f.debug_tuple("DuplicateUserData").finish_non_exhaustive()
+            }
+        }
+    }
+}
+
+impl<T> fmt::Display for PushError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::QueueFull(_) => f.write_str("submission queue is full"),
+            Self::DuplicateUserData(_) => f.write_str("user defined data is in flight"),
+        }
+    }
+}
+
+impl<T> std::error::Error for PushError<T> {}
+
+// the error code of the canceled operations
+#[cfg(unix)]
+const CANCELED: i32 = libc::ECANCELED;
+#[cfg(windows)]
+const CANCELED: i32 = windows_sys::Win32::Foundation::ERROR_OPERATION_ABORTED as _;
+
+/// The error of the operation canceled before it completed.
+///
+/// [`Entry::into_result`] reports the cancellation with the error of [`io::ErrorKind::Other`]
+/// kind wrapping this one, `ECANCELED` or `ERROR_OPERATION_ABORTED` is not returned as the raw
+/// OS error.
+///
+/// ```
+/// use std::io;
+///
+/// use completeio::CancelledError;
+///
+/// fn is_cancelled(res: &io::Result<usize>) -> bool {
+///     matches!(res, Err(e) if CancelledError::is_cancelled(e))
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CancelledError {
+    _private: (),
+}
+
+impl CancelledError {
+    /// Whether the error reports the canceled operation.
+    pub fn is_cancelled(error: &io::Error) -> bool {
+        error
+            .get_ref()
+            .is_some_and(|inner| inner.is::<CancelledError>())
+    }
+
+    /// The OS error code the canceled operations complete with.
+    pub fn raw_os_error(&self) -> i32 {
+        CANCELED
+    }
+}
+
+impl fmt::Display for CancelledError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("operation is canceled")
+    }
+}
+
+impl std::error::Error for CancelledError {}
+
 /// An completed entry returned from kernel.
 #[derive(Debug)]
 pub struct Entry {
     user_data: usize,
     result: io::Result<usize>,
+    // the signed result of the CQE
+    #[cfg(target_os = "linux")]
+    raw_result: i32,
+    more: bool,
+    buffer_id: Option<u16>,
+    notification: bool,
 }
 
 impl Entry {
     pub(crate) fn new(user_data: usize, result: io::Result<usize>) -> Self {
-        Self { user_data, result }
+        Self {
+            user_data,
+            #[cfg(target_os = "linux")]
+            raw_result: match &result {
+                Ok(res) => (*res).try_into().unwrap_or(i32::MAX),
+                Err(e) => -e.raw_os_error().unwrap_or(libc::EIO),
+            },
+            result,
+            more: false,
+            buffer_id: None,
+            notification: false,
+        }
+    }
+
+    // the entry result differs from the CQE result
+    #[cfg(target_os = "linux")]
+    pub(crate) fn with_raw_result(mut self, raw_result: i32) -> Self {
+        self.raw_result = raw_result;
+        self
+    }
+
+    #[cfg(target_os = "linux")]
+    pub(crate) fn with_more(mut self, more: bool) -> Self {
+        self.more = more;
+        self
+    }
+
+    #[cfg(target_os = "linux")]
+    pub(crate) fn with_buffer_id(mut self, buffer_id: Option<u16>) -> Self {
+        self.buffer_id = buffer_id;
+        self
+    }
+
+    #[cfg(target_os = "linux")]
+    pub(crate) fn with_notification(mut self, notification: bool) -> Self {
+        self.notification = notification;
+        self
     }
 
     /// The user-defined data passed to [`Operation`].
@@ -293,8 +826,58 @@ impl Entry {
         self.user_data
     }
 
+    /// Whether further entries are expected for the same user-defined data.
+    ///
+    /// Only multishot operations produce several entries. The last entry of
+    /// the operation doesn't have this flag set.
+    pub fn has_more(&self) -> bool {
+        self.more
+    }
+
+    /// The id of the provided buffer the kernel selected for the operation.
+    ///
+    /// Only io-uring operations with buffer selection set it. The buffer is
+    /// filled with the number of bytes in the result.
+    pub fn buffer_id(&self) -> Option<u16> {
+        self.buffer_id
+    }
+
+    /// Whether the kernel released the buffers of the zero-copy send.
+    ///
+    /// io-uring driver reports the result of [`SendZc`](crate::op::SendZc) when the buffer
+    /// release notification arrives and sets this flag. It is not set if the kernel doesn't
+    /// support zero-copy sends or other drivers copy the data.
+    pub fn is_notification(&self) -> bool {
+        self.notification
+    }
+
+    /// The OS error code of the failed operation.
+    ///
+    /// The code is kept for the canceled operations, [`Entry::into_result`] returns
+    /// [`CancelledError`] for them.
+    pub fn os_error(&self) -> Option<i32> {
+        self.result.as_ref().err().and_then(io::Error::raw_os_error)
+    }
+
+    /// The signed result of io-uring completion queue entry, the error code is negated.
+    ///
+    /// The result is kept when the driver reports another one, like the timed out error of the
+    /// operation canceled by its linked timeout. The errors of the operations completed before
+    /// submission without the OS error code are reported as `-EIO`.
+    #[cfg(target_os = "linux")]
+    pub fn raw_result(&self) -> i32 {
+        self.raw_result
+    }
+
     /// The result of the operation.
+    ///
+    /// The canceled operations complete with [`CancelledError`].
     pub fn into_result(self) -> io::Result<usize> {
-        self.result
+        match self.result {
+            Err(e) if e.raw_os_error() == Some(CANCELED) => {
+                Err(io::Error::other(CancelledError { _private: () }))
+            }
+            res => res,
+        }
     }
 }
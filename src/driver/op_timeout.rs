@@ -0,0 +1,83 @@
+/// Timeouts of pushed operations for drivers without linked timeouts
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    time::{Duration, Instant},
+};
+
+/// Deadlines of operations keyed by user_data.
+///
+/// `V` is the driver state required to cancel the pending operation.
+pub(super) struct OpTimeouts<V> {
+    // deadlines with the sequence number of the timeout
+    deadlines: BinaryHeap<Reverse<(Instant, u64, usize)>>,
+    // active timeouts, a stale deadline has another sequence number
+    active: HashMap<usize, (u64, V)>,
+    next_seq: u64,
+}
+
+impl<V> Default for OpTimeouts<V> {
+    fn default() -> Self {
+        Self {
+            deadlines: BinaryHeap::new(),
+            active: HashMap::new(),
+            next_seq: 0,
+        }
+    }
+}
+
+impl<V> OpTimeouts<V> {
+    #[inline]
+    pub(super) fn is_empty(&self) -> bool {
+        self.active.is_empty()
+    }
+
+    pub(super) fn insert(&mut self, user_data: usize, timeout: Duration, value: V) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.deadlines
+            .push(Reverse((Instant::now() + timeout, seq, user_data)));
+        self.active.insert(user_data, (seq, value));
+    }
+
+    /// Remove the timeout of the completed or canceled operation.
+    #[inline]
+    pub(super) fn remove(&mut self, user_data: usize) -> Option<V> {
+        let (_, value) = self.active.remove(&user_data)?;
+        if self.active.is_empty() {
+            self.deadlines.clear();
+        }
+        Some(value)
+    }
+
+    /// The smaller of `timeout` and the duration till the next deadline.
+    pub(super) fn till_next_deadline_or_timeout(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Option<Duration> {
+        match self.deadlines.peek() {
+            Some(Reverse((deadline, ..))) => {
+                let till_deadline = deadline.saturating_duration_since(Instant::now());
+                Some(timeout.map_or(till_deadline, |t| t.min(till_deadline)))
+            }
+            None => timeout,
+        }
+    }
+
+    /// Pop the operations with expired deadlines.
+    pub(super) fn expire(&mut self) -> Vec<(usize, V)> {
+        let now = Instant::now();
+        let mut expired = Vec::new();
+        while let Some(Reverse((deadline, seq, user_data))) = self.deadlines.peek().copied() {
+            if deadline > now {
+                break;
+            }
+            self.deadlines.pop();
+            if matches!(self.active.get(&user_data), Some((active_seq, _)) if *active_seq == seq) {
+                let (_, value) = self.active.remove(&user_data).expect("active");
+                expired.push((user_data, value));
+            }
+        }
+        expired
+    }
+}
@@ -0,0 +1,84 @@
+use crate::driver::{Entry, Hooks, UserDataSet};
+
+/// Counters of the driver operations returned by
+/// [`Driver::stats`](crate::driver::Driver::stats).
+///
+/// The counters are collected since the driver is created or
+/// [`Driver::reset_stats`](crate::driver::Driver::reset_stats) is called.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DriverStats {
+    /// Operations pushed to the driver.
+    pub submitted: u64,
+    /// Completed operations. A multishot operation is counted once by its last entry.
+    pub completed: u64,
+    /// Accepted cancellation requests of
+    /// [`CompleteIo::try_cancel`](crate::driver::CompleteIo::try_cancel).
+    pub canceled: u64,
+    /// Completion entries dropped by io-uring because the completion queue overflowed.
+    pub cq_overflows: u64,
+    /// Flushes of the completion entries which io-uring kept on the overflow list when the
+    /// completion queue was full.
+    pub cq_overflow_flushes: u64,
+    /// The high-water mark of the external queue passed to
+    /// [`CompleteIo::push_queue`](crate::driver::CompleteIo::push_queue).
+    pub max_queued: usize,
+}
+
+impl DriverStats {
+    /// Operations pushed but not completed yet.
+    ///
+    /// The value is not accurate after the counters are reset with operations in flight.
+    pub fn in_flight(&self) -> u64 {
+        self.submitted.saturating_sub(self.completed)
+    }
+
+    #[inline]
+    pub(crate) fn count_completed(&mut self, entry: &Entry) {
+        if !entry.has_more() {
+            self.completed += 1;
+        }
+    }
+}
+
+// collection counting the completed operations of extended entries
+pub(crate) struct CountCompleted<'a, E> {
+    entries: &'a mut E,
+    user_data: &'a mut UserDataSet,
+    hooks: &'a Hooks,
+    completed: u64,
+}
+
+impl<'a, E: Extend<Entry>> CountCompleted<'a, E> {
+    pub(crate) fn new(
+        entries: &'a mut E,
+        user_data: &'a mut UserDataSet,
+        hooks: &'a Hooks,
+    ) -> Self {
+        Self {
+            entries,
+            user_data,
+            hooks,
+            completed: 0,
+        }
+    }
+
+    pub(crate) fn completed(&self) -> u64 {
+        self.completed
+    }
+}
+
+impl<E: Extend<Entry>> Extend<Entry> for CountCompleted<'_, E> {
+    fn extend<T: IntoIterator<Item = Entry>>(&mut self, iter: T) {
+        let completed = &mut self.completed;
+        let user_data = &mut *self.user_data;
+        let hooks = self.hooks;
+        self.entries.extend(iter.into_iter().inspect(|entry| {
+            if !entry.has_more() {
+                *completed += 1;
+            }
+            user_data.release(entry);
+            hooks.completed(entry);
+        }));
+    }
+}
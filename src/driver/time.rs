@@ -4,7 +4,7 @@ use std::time::Duration;
 
 use boot_time::Instant;
 
-use crate::driver::Entry;
+use crate::driver::{link::canceled_entry, Entry};
 
 #[derive(Debug)]
 struct Timer {
@@ -33,22 +33,58 @@ impl Ord for Timer {
     }
 }
 
-#[repr(transparent)]
-pub(super) struct TimerWheel(BinaryHeap<Timer>);
+pub(super) struct TimerWheel {
+    timers: BinaryHeap<Timer>,
+    // keys of canceled timers to complete on the next expiration
+    canceled: Vec<usize>,
+}
 
 impl TimerWheel {
     pub(super) fn with_capacity(cap: usize) -> Self {
-        Self(BinaryHeap::with_capacity(cap))
+        Self {
+            timers: BinaryHeap::with_capacity(cap),
+            canceled: Vec::new(),
+        }
     }
 
     pub(super) fn insert(&mut self, key: usize, delay: Duration) {
         let deadline = Instant::now() + delay;
         let timer = Timer { key, deadline };
-        self.0.push(timer);
+        self.timers.push(timer);
+    }
+
+    /// Remove the timer, returns `true` if the timer was pending.
+    pub(super) fn remove(&mut self, key: usize) -> bool {
+        // linear scan, there are few pending timers
+        let len = self.timers.len();
+        self.timers.retain(|timer| timer.key != key);
+        len != self.timers.len()
+    }
+
+    /// Remove the timer and complete it with the canceled error on the next expiration.
+    pub(super) fn cancel(&mut self, key: usize) -> bool {
+        let removed = self.remove(key);
+        if removed {
+            self.canceled.push(key);
+        }
+        removed
+    }
+
+    /// Set the new delay of the pending timer.
+    pub(super) fn update(&mut self, key: usize, delay: Duration) -> bool {
+        let removed = self.remove(key);
+        if removed {
+            self.insert(key, delay);
+        }
+        removed
     }
 
     pub(super) fn duration_till_next_timer(&self) -> Option<Duration> {
-        self.0
+        if !self.canceled.is_empty() {
+            // canceled timers complete immediately
+            return Some(Duration::ZERO);
+        }
+        self.timers
             .peek()
             .map(|timer| timer.deadline.saturating_duration_since(Instant::now()))
     }
@@ -67,12 +103,13 @@ impl TimerWheel {
 
     /// Return expired flag and duration till the next timer
     pub(super) fn expire_timers(&mut self, entries: &mut impl Extend<Entry>) {
+        entries.extend(self.canceled.drain(..).map(canceled_entry));
         let now = Instant::now();
 
-        while let Some(timer) = self.0.peek() {
+        while let Some(timer) = self.timers.peek() {
             let duration_till_next_timer = timer.deadline.saturating_duration_since(now);
             if duration_till_next_timer == Duration::ZERO {
-                let timer = self.0.pop().expect("timer present");
+                let timer = self.timers.pop().expect("timer present");
                 entries.extend(Some(Entry::new(timer.key, Ok(0))));
             } else {
                 break;
@@ -87,28 +124,91 @@ impl Extend<(usize, Duration)> for TimerWheel {
         T: IntoIterator<Item = (usize, Duration)>,
     {
         let now = Instant::now();
-        self.0.extend(iter.into_iter().map(|(key, delay)| Timer {
-            key,
-            deadline: now + delay,
-        }));
+        self.timers
+            .extend(iter.into_iter().map(|(key, delay)| Timer {
+                key,
+                deadline: now + delay,
+            }));
     }
 }
 
-/// Timeout operation completes after the given relative timeout duration.
+/// Timeout operation completes after the given relative timeout duration or at the absolute
+/// deadline.
 ///
 /// If supported by platform timeout operation will take into account the time
 /// spent in low power modes or suspend (CLOCK_BOOTTIME). Otherwise
 /// CLOCK_MONOTONIC is used.
 ///
 /// Only io_uring driver supports waiting using CLOCK_BOOTTIME clock.
-#[repr(transparent)]
+///
+/// The expired timeout completes with `Ok(0)`.
+#[cfg(not(target_os = "linux"))]
 pub struct Timeout {
-    pub(crate) delay: std::time::Duration,
+    delay: std::time::Duration,
+    // the absolute deadline overrides the delay
+    deadline: Option<Instant>,
 }
 
+#[cfg(not(target_os = "linux"))]
 impl Timeout {
     /// Create `Timeout` with the provided duration.
     pub fn new(delay: std::time::Duration) -> Self {
-        Self { delay }
+        Self {
+            delay,
+            deadline: None,
+        }
+    }
+
+    /// Create `Timeout` completing at `deadline`.
+    ///
+    /// Unlike [`Timeout::new`] the time the operation waits for the submission is not added to
+    /// the timeout.
+    pub fn at(deadline: Instant) -> Self {
+        Self {
+            delay: Duration::ZERO,
+            deadline: Some(deadline),
+        }
+    }
+
+    // the delay counted from the time the driver starts the timer
+    pub(crate) fn delay(&self) -> std::time::Duration {
+        match self.deadline {
+            Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+            None => self.delay,
+        }
+    }
+}
+
+/// Update the delay of the pending [`Timeout`] operation with `user_data`.
+///
+/// The operation completes with the not found error if the timeout has already completed.
+#[cfg(not(target_os = "linux"))]
+pub struct TimeoutUpdate {
+    pub(crate) user_data: usize,
+    pub(crate) delay: std::time::Duration,
+}
+
+#[cfg(not(target_os = "linux"))]
+impl TimeoutUpdate {
+    /// Create `TimeoutUpdate` with the new delay counted from the time it is submitted.
+    pub fn new(user_data: usize, delay: std::time::Duration) -> Self {
+        Self { user_data, delay }
+    }
+}
+
+/// Remove the pending [`Timeout`] operation with `user_data`.
+///
+/// The removed timeout completes with the canceled error. The operation completes with the not
+/// found error if the timeout has already completed.
+#[cfg(not(target_os = "linux"))]
+pub struct TimeoutRemove {
+    pub(crate) user_data: usize,
+}
+
+#[cfg(not(target_os = "linux"))]
+impl TimeoutRemove {
+    /// Create `TimeoutRemove`.
+    pub fn new(user_data: usize) -> Self {
+        Self { user_data }
     }
 }
@@ -1,11 +1,18 @@
-use std::{io, marker::PhantomData};
+#[cfg(target_os = "linux")]
+use std::os::fd::OwnedFd;
+use std::{
+    ffi::CString, io, marker::PhantomData, net::Shutdown, os::unix::process::ExitStatusExt,
+    process::ExitStatus,
+};
 
-use libc::{sockaddr_storage, socklen_t};
+use libc::{mode_t, sockaddr_storage, socklen_t};
 use socket2::{Domain, Protocol, SockAddr, Socket, Type};
 
 use crate::{
     buf::{AsIoSlices, AsIoSlicesMut, IntoInner, IoBuf, IoBufMut},
-    driver::{unix::IntoFdOrFixed, FdOrFixed, FromRawFd, RawFd},
+    driver::{unix::IntoFdOrFixed, DriverHandle, Fd, FdOrFixed, FromRawFd, RawFd},
+    op::{AcceptFlags, Advice, AllocateMode, RenameFlags, RwFlags, SyncRangeFlags},
+    syscall,
 };
 
 /// Read a nonseekable file into specified buffer.
@@ -39,6 +46,7 @@ pub struct ReadAt<'arena, T: IoBufMut<'arena>> {
     pub(in crate::driver) fd: FdOrFixed,
     pub(in crate::driver) offset: usize,
     pub(in crate::driver) buffer: T,
+    pub(in crate::driver) rw_flags: RwFlags,
     _lifetime: PhantomData<&'arena ()>,
 }
 
@@ -49,9 +57,21 @@ impl<'arena, T: IoBufMut<'arena>> ReadAt<'arena, T> {
             fd: fd.into(),
             offset,
             buffer,
+            rw_flags: RwFlags::empty(),
             _lifetime: PhantomData,
         }
     }
+
+    /// Set [`RwFlags`] of the operation.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: the flags are passed to `IORING_OP_READ`.
+    /// * kqueue: `NOWAIT` fails with [`io::ErrorKind::Unsupported`], `HIPRI` is ignored.
+    pub fn with_rw_flags(mut self, rw_flags: RwFlags) -> Self {
+        self.rw_flags = rw_flags;
+        self
+    }
 }
 
 impl<'arena, T: IoBufMut<'arena>> IntoInner for ReadAt<'arena, T> {
@@ -93,6 +113,7 @@ pub struct WriteAt<'arena, T: IoBuf<'arena>> {
     pub(in crate::driver) fd: FdOrFixed,
     pub(in crate::driver) offset: usize,
     pub(in crate::driver) buffer: T,
+    pub(in crate::driver) rw_flags: RwFlags,
     _lifetime: PhantomData<&'arena ()>,
 }
 
@@ -103,9 +124,22 @@ impl<'arena, T: IoBuf<'arena>> WriteAt<'arena, T> {
             fd: fd.into(),
             offset,
             buffer,
+            rw_flags: RwFlags::empty(),
             _lifetime: PhantomData,
         }
     }
+
+    /// Set [`RwFlags`] of the operation.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: the flags are passed to `IORING_OP_WRITE`.
+    /// * kqueue: `DSYNC` is emulated with `fsync` syscall after the write, `NOWAIT` fails with
+    ///   [`io::ErrorKind::Unsupported`], `HIPRI` is ignored.
+    pub fn with_rw_flags(mut self, rw_flags: RwFlags) -> Self {
+        self.rw_flags = rw_flags;
+        self
+    }
 }
 
 impl<'arena, T: IoBuf<'arena>> IntoInner for WriteAt<'arena, T> {
@@ -116,32 +150,78 @@ impl<'arena, T: IoBuf<'arena>> IntoInner for WriteAt<'arena, T> {
     }
 }
 
+/// Read a file at specified position into scattered buffers.
+pub struct ReadVectoredAtImpl<'arena, T: AsIoSlicesMut<'arena>> {
+    pub(in crate::driver) fd: FdOrFixed,
+    pub(in crate::driver) offset: usize,
+    pub(in crate::driver) buffer: T,
+    _lifetime: PhantomData<&'arena ()>,
+}
+
+impl<'arena, T: AsIoSlicesMut<'arena>> ReadVectoredAtImpl<'arena, T> {
+    /// Create [`ReadVectoredAt`](crate::op::ReadVectoredAt).
+    pub fn new(fd: impl IntoFdOrFixed<Target = FdOrFixed>, offset: usize, buffer: T) -> Self {
+        Self {
+            fd: fd.into(),
+            offset,
+            buffer,
+            _lifetime: PhantomData,
+        }
+    }
+}
+
+impl<'arena, T: AsIoSlicesMut<'arena>> IntoInner for ReadVectoredAtImpl<'arena, T> {
+    type Inner = T;
+
+    fn into_inner(self) -> Self::Inner {
+        self.buffer
+    }
+}
+
+/// Write a file at specified position from scattered buffers.
+pub struct WriteVectoredAtImpl<'arena, T: AsIoSlices<'arena>> {
+    pub(in crate::driver) fd: FdOrFixed,
+    pub(in crate::driver) offset: usize,
+    pub(in crate::driver) buffer: T,
+    _lifetime: PhantomData<&'arena ()>,
+}
+
+impl<'arena, T: AsIoSlices<'arena>> WriteVectoredAtImpl<'arena, T> {
+    /// Create [`WriteVectoredAt`](crate::op::WriteVectoredAt).
+    pub fn new(fd: impl IntoFdOrFixed<Target = FdOrFixed>, offset: usize, buffer: T) -> Self {
+        Self {
+            fd: fd.into(),
+            offset,
+            buffer,
+            _lifetime: PhantomData,
+        }
+    }
+}
+
+impl<'arena, T: AsIoSlices<'arena>> IntoInner for WriteVectoredAtImpl<'arena, T> {
+    type Inner = T;
+
+    fn into_inner(self) -> Self::Inner {
+        self.buffer
+    }
+}
+
 /// Connect to a remote address.
 pub struct Connect {
     pub(in crate::driver) fd: FdOrFixed,
     pub(in crate::driver) addr: SockAddr,
-    #[cfg(not(target_os = "linux"))]
+    // the readiness drivers check the result of the connection in progress
     pub(in crate::driver) initiated: bool,
 }
 
 impl Connect {
     /// Create [`Connect`]. `fd` should be bound.
     pub fn new(fd: impl IntoFdOrFixed<Target = FdOrFixed>, addr: SockAddr) -> Self {
-        #[cfg(target_os = "linux")]
-        let this = Self {
+        Self {
             fd: fd.into(),
             addr,
-        };
-        #[cfg(not(target_os = "linux"))]
-        let this = {
-            let initiated = false;
-            Self {
-                fd: fd.into(),
-                addr,
-                initiated,
-            }
-        };
-        this
+            initiated: false,
+        }
     }
 
     /// Post operation socket handling.
@@ -152,6 +232,159 @@ impl Connect {
     }
 }
 
+/// Shut down the read, write, or both halves of a connected socket.
+pub struct ShutdownSocket {
+    pub(in crate::driver) fd: FdOrFixed,
+    pub(in crate::driver) how: Shutdown,
+}
+
+impl ShutdownSocket {
+    /// Create [`ShutdownSocket`].
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_SHUTDOWN` operation.
+    /// * kqueue: it is synchronized `shutdown` syscall.
+    pub fn new(fd: impl IntoFdOrFixed<Target = FdOrFixed>, how: Shutdown) -> Self {
+        Self { fd: fd.into(), how }
+    }
+
+    pub(in crate::driver) fn how(&self) -> i32 {
+        match self.how {
+            Shutdown::Read => libc::SHUT_RD,
+            Shutdown::Write => libc::SHUT_WR,
+            Shutdown::Both => libc::SHUT_RDWR,
+        }
+    }
+}
+
+/// Wait for a file descriptor to become readable.
+///
+/// The result is the returned poll mask, like `POLLIN` or `POLLHUP`.
+pub struct PollReadable {
+    pub(in crate::driver) fd: FdOrFixed,
+}
+
+impl PollReadable {
+    /// Create [`PollReadable`].
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_POLL_ADD` operation with `POLLIN` mask.
+    /// * kqueue: read filter event, the mask is queried by `poll` syscall.
+    pub fn new(fd: impl IntoFdOrFixed<Target = FdOrFixed>) -> Self {
+        Self { fd: fd.into() }
+    }
+}
+
+/// Wait for a file descriptor to become writable.
+///
+/// The result is the returned poll mask, like `POLLOUT` or `POLLERR`.
+pub struct PollWritable {
+    pub(in crate::driver) fd: FdOrFixed,
+}
+
+impl PollWritable {
+    /// Create [`PollWritable`].
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_POLL_ADD` operation with `POLLOUT` mask.
+    /// * kqueue: write filter event, the mask is queried by `poll` syscall.
+    pub fn new(fd: impl IntoFdOrFixed<Target = FdOrFixed>) -> Self {
+        Self { fd: fd.into() }
+    }
+}
+
+/// Create a socket.
+///
+/// The result is the new file descriptor, [`CreateSocket::on_create`] takes its ownership.
+pub struct CreateSocket {
+    pub(in crate::driver) domain: Domain,
+    pub(in crate::driver) ty: Type,
+    pub(in crate::driver) protocol: Option<Protocol>,
+}
+
+impl CreateSocket {
+    /// Create [`CreateSocket`].
+    ///
+    /// The socket is close-on-exec.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_SOCKET` operation, available since Linux 5.19. Older kernels create
+    ///   the socket with `socket` syscall before submission.
+    /// * kqueue: it is synchronized `socket` syscall, the socket is nonblocking.
+    pub fn new(domain: Domain, ty: Type, protocol: Option<Protocol>) -> Self {
+        Self {
+            domain,
+            ty,
+            protocol,
+        }
+    }
+
+    /// Post operation handling, returns the created socket.
+    pub fn on_create(&self, result: io::Result<usize>) -> io::Result<Socket> {
+        let fd = result?;
+        // SAFETY: the operation returned a new file descriptor
+        Ok(unsafe { Socket::from_raw_fd(fd as _) })
+    }
+
+    pub(in crate::driver) fn create(&self) -> io::Result<Socket> {
+        Socket::new(self.domain, self.ty, self.protocol)
+    }
+}
+
+/// Bind a socket to an address.
+pub struct Bind {
+    pub(in crate::driver) fd: Fd,
+    pub(in crate::driver) addr: SockAddr,
+}
+
+impl Bind {
+    /// Create [`Bind`].
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: there is no opcode, `bind` syscall is called before submission.
+    /// * kqueue: it is synchronized `bind` syscall.
+    pub fn new(fd: Fd, addr: SockAddr) -> Self {
+        Self { fd, addr }
+    }
+
+    pub(in crate::driver) fn bind(&self) -> io::Result<usize> {
+        syscall!(bind(
+            self.fd.as_raw_fd(),
+            self.addr.as_ptr(),
+            self.addr.len()
+        ))?;
+        Ok(0)
+    }
+}
+
+/// Mark a socket as accepting connections.
+pub struct Listen {
+    pub(in crate::driver) fd: Fd,
+    pub(in crate::driver) backlog: i32,
+}
+
+impl Listen {
+    /// Create [`Listen`].
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: there is no opcode, `listen` syscall is called before submission.
+    /// * kqueue: it is synchronized `listen` syscall.
+    pub fn new(fd: Fd, backlog: i32) -> Self {
+        Self { fd, backlog }
+    }
+
+    pub(in crate::driver) fn listen(&self) -> io::Result<usize> {
+        syscall!(listen(self.fd.as_raw_fd(), self.backlog))?;
+        Ok(0)
+    }
+}
+
 /// Accept a connection.
 ///
 /// It's possible to reinit the data structure and reuse it for the following accepts.
@@ -159,6 +392,7 @@ pub struct Accept {
     pub(in crate::driver) fd: FdOrFixed,
     pub(in crate::driver) addr: SockAddr,
     pub(in crate::driver) addr_len: socklen_t,
+    pub(in crate::driver) flags: AcceptFlags,
 }
 
 impl Accept {
@@ -185,9 +419,21 @@ impl Accept {
                 )
             },
             addr_len: std::mem::size_of::<sockaddr_storage>() as socklen_t,
+            flags: AcceptFlags::empty(),
         }
     }
 
+    /// Set flags of the accepted socket.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: the flags are passed to `IORING_OP_ACCEPT` like to `accept4`.
+    /// * kqueue: the flags are applied in [`Accept::on_accept`].
+    pub fn with_flags(mut self, flags: AcceptFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
     /// Init existing [`Accept`] for new accept operation.
     pub fn init_with_socket_opts(
         &mut self,
@@ -201,12 +447,19 @@ impl Accept {
 
     /// Post operation socket handling.
     ///
-    /// Set nonblocking for kqueue.
+    /// Apply the accept flags for kqueue.
     /// Get remote address.
     pub fn on_accept(&mut self, result: io::Result<usize>) -> io::Result<(Socket, &SockAddr)> {
         let accept_sock = unsafe { Socket::from_raw_fd(result? as RawFd) };
-        #[cfg(all(unix, not(target_os = "linux")))]
-        accept_sock.set_nonblocking(true)?;
+        #[cfg(not(target_os = "linux"))]
+        {
+            if self.flags.contains(AcceptFlags::CLOEXEC) {
+                accept_sock.set_cloexec(true)?;
+            }
+            if self.flags.contains(AcceptFlags::NONBLOCK) {
+                accept_sock.set_nonblocking(true)?;
+            }
+        }
         let addr = self.as_sockaddr();
         Ok((accept_sock, addr))
     }
@@ -243,116 +496,1075 @@ impl Sync {
     }
 }
 
-/// Receive a single piece of data in a single buffer from remote.
-pub struct Recv<'arena, T: IoBufMut<'arena>> {
+/// Sync a file range to the disk.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub struct SyncFileRange {
     pub(in crate::driver) fd: FdOrFixed,
-    pub(in crate::driver) buffer: T,
-    _lifetime: PhantomData<&'arena ()>,
+    pub(in crate::driver) offset: u64,
+    pub(in crate::driver) nbytes: u32,
+    pub(in crate::driver) flags: SyncRangeFlags,
 }
 
-impl<'arena, T: IoBufMut<'arena>> Recv<'arena, T> {
-    /// Create [`Recv`].
-    pub fn new(fd: impl IntoFdOrFixed<Target = FdOrFixed>, buffer: T) -> Self {
+impl SyncFileRange {
+    /// Create [`SyncFileRange`] for the range of `nbytes` bytes starting from `offset`.
+    ///
+    /// Zero `nbytes` means the range till the end of file.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_SYNC_FILE_RANGE` operation.
+    /// * kqueue: it falls back to synchronized `fsync` of the whole file.
+    pub fn new(
+        fd: impl IntoFdOrFixed<Target = FdOrFixed>,
+        offset: u64,
+        nbytes: u32,
+        flags: SyncRangeFlags,
+    ) -> Self {
         Self {
             fd: fd.into(),
-            buffer,
-            _lifetime: PhantomData,
+            offset,
+            nbytes,
+            flags,
         }
     }
 }
 
-impl<'arena, T: IoBufMut<'arena>> IntoInner for Recv<'arena, T> {
-    type Inner = T;
+/// Open or create a file relative to a directory file descriptor.
+///
+/// The completed operation returns the raw file descriptor of the opened file.
+pub struct OpenAt {
+    pub(in crate::driver) dirfd: RawFd,
+    pub(in crate::driver) path: CString,
+    pub(in crate::driver) flags: i32,
+    pub(in crate::driver) mode: mode_t,
+}
 
-    fn into_inner(self) -> Self::Inner {
-        self.buffer
+impl OpenAt {
+    /// Create [`OpenAt`].
+    ///
+    /// `dirfd` could be [`libc::AT_FDCWD`] to resolve relative `path` against the
+    /// current working directory. `flags` and `mode` have the same meaning as
+    /// for `openat(2)`.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_OPENAT` operation.
+    /// * kqueue: it is synchronized `openat` syscall.
+    pub fn new(dirfd: RawFd, path: CString, flags: i32, mode: mode_t) -> Self {
+        Self {
+            dirfd,
+            path,
+            flags,
+            mode,
+        }
     }
 }
 
-/// Receive a single piece of data into scattered buffers from remote.
-pub struct RecvVectoredImpl<'arena, T: AsIoSlicesMut<'arena>> {
-    pub(in crate::driver) fd: FdOrFixed,
-    pub(in crate::driver) buffer: T,
-    _lifetime: PhantomData<&'arena ()>,
+/// Create a directory relative to a directory file descriptor.
+pub struct MkdirAt {
+    pub(in crate::driver) dirfd: RawFd,
+    pub(in crate::driver) path: CString,
+    pub(in crate::driver) mode: mode_t,
 }
 
-impl<'arena, T: AsIoSlicesMut<'arena>> RecvVectoredImpl<'arena, T> {
-    /// Create [`RecvVectored`].
-    pub fn new(fd: impl IntoFdOrFixed<Target = FdOrFixed>, buffer: T) -> Self {
-        Self {
-            fd: fd.into(),
-            buffer,
-            _lifetime: PhantomData,
-        }
+impl MkdirAt {
+    /// Create [`MkdirAt`].
+    ///
+    /// `dirfd` could be [`libc::AT_FDCWD`] to resolve relative `path` against the
+    /// current working directory. `mode` has the same meaning as for `mkdirat(2)`.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_MKDIRAT` operation.
+    /// * kqueue: it is synchronized `mkdirat` syscall.
+    pub fn new(dirfd: RawFd, path: CString, mode: mode_t) -> Self {
+        Self { dirfd, path, mode }
     }
 }
 
-impl<'arena, T: AsIoSlicesMut<'arena>> IntoInner for RecvVectoredImpl<'arena, T> {
-    type Inner = T;
+/// Remove a directory entry relative to a directory file descriptor.
+pub struct UnlinkAt {
+    pub(in crate::driver) dirfd: RawFd,
+    pub(in crate::driver) path: CString,
+    pub(in crate::driver) flags: i32,
+}
 
-    fn into_inner(self) -> Self::Inner {
-        self.buffer
+impl UnlinkAt {
+    /// Create [`UnlinkAt`].
+    ///
+    /// `dirfd` could be [`libc::AT_FDCWD`] to resolve relative `path` against the
+    /// current working directory. `flags` could be [`libc::AT_REMOVEDIR`] to remove
+    /// a directory like `unlinkat(2)` does.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_UNLINKAT` operation.
+    /// * kqueue: it is synchronized `unlinkat` syscall.
+    pub fn new(dirfd: RawFd, path: CString, flags: i32) -> Self {
+        Self { dirfd, path, flags }
     }
 }
 
-/// Send a single piece of data from a single buffer to remote.
-pub struct Send<'arena, T: IoBuf<'arena>> {
-    pub(in crate::driver) fd: FdOrFixed,
-    pub(in crate::driver) buffer: T,
-    _lifetime: PhantomData<&'arena ()>,
+/// Rename a file relative to directory file descriptors.
+pub struct RenameAt {
+    pub(in crate::driver) old_dirfd: RawFd,
+    pub(in crate::driver) old_path: CString,
+    pub(in crate::driver) new_dirfd: RawFd,
+    pub(in crate::driver) new_path: CString,
+    pub(in crate::driver) flags: RenameFlags,
 }
 
-impl<'arena, T: IoBuf<'arena>> Send<'arena, T> {
-    /// Create [`Send`].
-    pub fn new(fd: impl IntoFdOrFixed<Target = FdOrFixed>, buffer: T) -> Self {
+impl RenameAt {
+    /// Create [`RenameAt`].
+    ///
+    /// Directory file descriptors could be [`libc::AT_FDCWD`] to resolve relative paths against
+    /// the current working directory.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_RENAMEAT` operation.
+    /// * kqueue: it is synchronized `renameat` syscall. [`RenameFlags::NOREPLACE`] is supported on
+    ///   Apple platforms with `renameatx_np` and fails with [`io::ErrorKind::Unsupported`] on
+    ///   others.
+    pub fn new(
+        old_dirfd: RawFd,
+        old_path: CString,
+        new_dirfd: RawFd,
+        new_path: CString,
+        flags: RenameFlags,
+    ) -> Self {
         Self {
-            fd: fd.into(),
-            buffer,
-            _lifetime: PhantomData,
+            old_dirfd,
+            old_path,
+            new_dirfd,
+            new_path,
+            flags,
         }
     }
 }
 
-impl<'arena, T: IoBuf<'arena>> IntoInner for Send<'arena, T> {
-    type Inner = T;
+/// Create a symbolic link relative to a directory file descriptor.
+pub struct SymlinkAt {
+    pub(in crate::driver) target: CString,
+    pub(in crate::driver) linkdir_fd: RawFd,
+    pub(in crate::driver) linkpath: CString,
+}
 
-    fn into_inner(self) -> Self::Inner {
-        self.buffer
+impl SymlinkAt {
+    /// Create [`SymlinkAt`].
+    ///
+    /// The link at `linkpath` points to `target`. `linkdir_fd` could be [`libc::AT_FDCWD`] to
+    /// resolve relative `linkpath` against the current working directory.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_SYMLINKAT` operation.
+    /// * kqueue: it is synchronized `symlinkat` syscall.
+    pub fn new(target: CString, linkdir_fd: RawFd, linkpath: CString) -> Self {
+        Self {
+            target,
+            linkdir_fd,
+            linkpath,
+        }
     }
 }
 
-/// Send a single piece of data to remote using scattered buffers.
-pub struct SendVectoredImpl<'arena, T: AsIoSlices<'arena>> {
-    pub(in crate::driver) fd: FdOrFixed,
-    pub(in crate::driver) buffer: T,
-    _lifetime: PhantomData<&'arena ()>,
+/// Create a hard link relative to directory file descriptors.
+pub struct LinkAt {
+    pub(in crate::driver) old_dirfd: RawFd,
+    pub(in crate::driver) old_path: CString,
+    pub(in crate::driver) new_dirfd: RawFd,
+    pub(in crate::driver) new_path: CString,
+    pub(in crate::driver) flags: i32,
 }
 
-impl<'arena, T: AsIoSlices<'arena>> SendVectoredImpl<'arena, T> {
-    /// Create [`SendVectored`].
-    pub fn new(fd: impl IntoFdOrFixed<Target = FdOrFixed>, buffer: T) -> Self {
+impl LinkAt {
+    /// Create [`LinkAt`].
+    ///
+    /// Directory file descriptors could be [`libc::AT_FDCWD`] to resolve relative paths against
+    /// the current working directory. `flags` could be [`libc::AT_SYMLINK_FOLLOW`] to
+    /// dereference `old_path` if it is a symbolic link like `linkat(2)` does.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_LINKAT` operation.
+    /// * kqueue: it is synchronized `linkat` syscall.
+    pub fn new(
+        old_dirfd: RawFd,
+        old_path: CString,
+        new_dirfd: RawFd,
+        new_path: CString,
+        flags: i32,
+    ) -> Self {
         Self {
-            fd: fd.into(),
-            buffer,
-            _lifetime: PhantomData,
+            old_dirfd,
+            old_path,
+            new_dirfd,
+            new_path,
+            flags,
         }
     }
 }
 
-impl<'arena, T: AsIoSlices<'arena>> IntoInner for SendVectoredImpl<'arena, T> {
-    type Inner = T;
+/// Close a file descriptor.
+pub struct Close {
+    pub(in crate::driver) fd: RawFd,
+}
 
-    fn into_inner(self) -> Self::Inner {
-        self.buffer
+impl Close {
+    /// Create [`Close`].
+    ///
+    /// The operation takes ownership of `fd`. The caller should not use it
+    /// after the operation is pushed.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_CLOSE` operation.
+    /// * kqueue: it is synchronized `close` syscall.
+    pub fn new(fd: RawFd) -> Self {
+        Self { fd }
     }
 }
 
-/// Receive a single piece of data and source address using scattered buffers.
-pub struct RecvMsgImpl<'arena, T: AsIoSlicesMut<'arena>> {
-    pub(in crate::driver) fd: FdOrFixed,
-    pub(in crate::driver) buffer: T,
-    pub(in crate::driver) addr: sockaddr_storage,
-    pub(in crate::driver) msg: libc::msghdr,
+/// Cancel pushed operations.
+///
+/// The canceled operations complete with `ECANCELED`.
+pub struct Cancel {
+    pub(in crate::driver) target: CancelTarget,
+}
+
+pub(in crate::driver) enum CancelTarget {
+    UserData(usize),
+    Fd(FdOrFixed),
+}
+
+impl Cancel {
+    /// Create [`Cancel`] of the operation with `user_data`.
+    ///
+    /// It completes with `0` when the target is canceled and with `ENOENT` when the target is
+    /// not found, e.g. it is already completed.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_ASYNC_CANCEL` operation. It completes with `EALREADY` when the target
+    ///   is already running and may not be interrupted.
+    /// * kqueue: the target is removed from the driver on push.
+    pub fn new(user_data: usize) -> Self {
+        Self {
+            target: CancelTarget::UserData(user_data),
+        }
+    }
+
+    /// Create [`Cancel`] of all operations on `fd`.
+    ///
+    /// It completes with the number of canceled operations and with `ENOENT` when nothing is
+    /// canceled.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_ASYNC_CANCEL` with `IORING_ASYNC_CANCEL_FD` and
+    ///   `IORING_ASYNC_CANCEL_ALL` flags, available since Linux 5.19.
+    /// * kqueue: the operations waiting for readiness of `fd` are removed from the driver on push.
+    /// * epoll: the same as kqueue.
+    pub fn fd(fd: impl IntoFdOrFixed<Target = FdOrFixed>) -> Self {
+        Self {
+            target: CancelTarget::Fd(fd.into()),
+        }
+    }
+}
+
+/// Post a completion into another driver.
+pub struct MsgRing {
+    pub(in crate::driver) ring_fd: RawFd,
+    pub(in crate::driver) user_data: usize,
+    pub(in crate::driver) result: i32,
+}
+
+impl MsgRing {
+    /// Create [`MsgRing`] posting into the driver of `handle`.
+    ///
+    /// The target driver outputs an [`Entry`](crate::driver::Entry) with `user_data` and
+    /// `result`, a negative `result` is the error code. The operation itself completes with `0`.
+    ///
+    /// The handle should outlive the operation.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_MSG_RING` operation, available since Linux 5.18.
+    /// * kqueue: the target is triggered with `EVFILT_USER` event identified by `user_data`, the
+    ///   messages with the same `user_data` are coalesced till the target receives them. NetBSD and
+    ///   OpenBSD are not supported.
+    pub fn new(handle: &DriverHandle, user_data: usize, result: i32) -> Self {
+        Self {
+            ring_fd: handle.as_raw_fd(),
+            user_data,
+            result,
+        }
+    }
+}
+
+/// No operation, it completes with `0`.
+///
+/// It measures the submission and completion overhead of a driver or checks that the driver is
+/// responsive.
+#[derive(Debug, Default)]
+pub struct Nop {}
+
+impl Nop {
+    /// Create [`Nop`].
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_NOP` operation.
+    /// * kqueue: it completes on submission.
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+/// Wait for a state change of a child process.
+///
+/// The operation should be resubmitted till [`WaitId::on_complete`] reports that the state is
+/// collected. The collected `siginfo` is available with [`WaitId::siginfo`].
+///
+/// SIGCHLD handler is not required.
+pub struct WaitId {
+    pub(in crate::driver) id: libc::id_t,
+    options: i32,
+    // owned pidfd opened for `P_PID`
+    #[cfg(target_os = "linux")]
+    pidfd: Option<OwnedFd>,
+    info: libc::siginfo_t,
+}
+
+impl WaitId {
+    /// Create [`WaitId`] waiting for the process identified by `idtype` and `id`.
+    ///
+    /// `options` are `waitid(2)` options like `WEXITED`, `WNOHANG` is added by the driver.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::ErrorKind::InvalidInput`] if the wait is not for a single process.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `P_PID` and `P_PIDFD` are supported, the driver polls the pidfd for readability
+    ///   and reaps the process with `waitid` syscall. The pidfd is opened by the operation for
+    ///   `P_PID`, available since Linux 5.4.
+    /// * kqueue: `P_PID` is supported, `EVFILT_PROC` event reports the process exit, so only
+    ///   `WEXITED` waits for the state change.
+    pub fn new(idtype: libc::idtype_t, id: libc::id_t, options: i32) -> io::Result<Self> {
+        #[cfg(target_os = "linux")]
+        let pidfd = match idtype {
+            libc::P_PID => {
+                let fd = syscall!(syscall(libc::SYS_pidfd_open, id, 0))?;
+                // SAFETY: pidfd_open returned a new file descriptor
+                Some(unsafe { OwnedFd::from_raw_fd(fd as _) })
+            }
+            libc::P_PIDFD => None,
+            _ => return Err(single_process_error()),
+        };
+        #[cfg(not(target_os = "linux"))]
+        if idtype != libc::P_PID {
+            return Err(single_process_error());
+        }
+        Ok(Self {
+            id,
+            options,
+            #[cfg(target_os = "linux")]
+            pidfd,
+            info: unsafe { std::mem::zeroed() },
+        })
+    }
+
+    /// Post operation handling.
+    ///
+    /// Returns `true` when the state change is collected.
+    pub fn on_complete(&mut self, result: io::Result<usize>) -> io::Result<bool> {
+        match result {
+            Ok(_) => self.try_collect(),
+            // kqueue can't watch the process which exited before the event registration
+            Err(e) if e.raw_os_error() == Some(libc::ESRCH) => self.try_collect(),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The collected state change.
+    pub fn siginfo(&self) -> &libc::siginfo_t {
+        &self.info
+    }
+
+    /// The exit status of the process if the state change is collected.
+    pub fn exit_status(&self) -> Option<ExitStatus> {
+        let (pid, status) = siginfo_pid_status(&self.info);
+        if pid == 0 {
+            return None;
+        }
+        // encode the status as `waitpid` does
+        let raw = match self.info.si_code {
+            libc::CLD_EXITED => (status & 0xff) << 8,
+            libc::CLD_KILLED => status,
+            libc::CLD_DUMPED => status | 0x80,
+            libc::CLD_STOPPED | libc::CLD_TRAPPED => (status << 8) | 0x7f,
+            libc::CLD_CONTINUED => 0xffff,
+            _ => return None,
+        };
+        Some(ExitStatus::from_raw(raw))
+    }
+
+    #[cfg(target_os = "linux")]
+    pub(in crate::driver) fn pidfd(&self) -> RawFd {
+        use std::os::fd::AsRawFd;
+
+        match &self.pidfd {
+            Some(fd) => fd.as_raw_fd(),
+            None => self.id as _,
+        }
+    }
+
+    /// Collect the state change without blocking, returns `true` if it is collected.
+    pub(in crate::driver) fn try_collect(&mut self) -> io::Result<bool> {
+        if siginfo_pid_status(&self.info).0 != 0 {
+            return Ok(true);
+        }
+        #[cfg(target_os = "linux")]
+        let (idtype, id) = (libc::P_PIDFD, self.pidfd() as libc::id_t);
+        #[cfg(not(target_os = "linux"))]
+        let (idtype, id) = (libc::P_PID, self.id);
+        syscall!(waitid(
+            idtype,
+            id,
+            &mut self.info,
+            self.options | libc::WNOHANG
+        ))?;
+        Ok(siginfo_pid_status(&self.info).0 != 0)
+    }
+}
+
+fn single_process_error() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "only a wait for a single process is supported",
+    )
+}
+
+#[cfg(target_os = "linux")]
+fn siginfo_pid_status(info: &libc::siginfo_t) -> (libc::pid_t, i32) {
+    // SAFETY: the fields are set by waitid or zeroed
+    unsafe { (info.si_pid(), info.si_status()) }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn siginfo_pid_status(info: &libc::siginfo_t) -> (libc::pid_t, i32) {
+    (info.si_pid, info.si_status)
+}
+
+/// Duplicate data from one pipe to another without consuming it.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub struct Tee {
+    pub(in crate::driver) fd_in: FdOrFixed,
+    pub(in crate::driver) fd_out: FdOrFixed,
+    pub(in crate::driver) len: u32,
+    pub(in crate::driver) flags: u32,
+}
+
+impl Tee {
+    /// Create [`Tee`].
+    ///
+    /// Both file descriptors should refer to pipes. `flags` are `SPLICE_F_*`
+    /// flags described in `tee(2)`.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_TEE` operation.
+    /// * epoll: `tee` syscall with `SPLICE_F_NONBLOCK` flag.
+    /// * kqueue: it can't be emulated and fails with [`io::ErrorKind::Unsupported`].
+    pub fn new(
+        fd_in: impl IntoFdOrFixed<Target = FdOrFixed>,
+        fd_out: impl IntoFdOrFixed<Target = FdOrFixed>,
+        len: u32,
+        flags: u32,
+    ) -> Self {
+        Self {
+            fd_in: fd_in.into(),
+            fd_out: fd_out.into(),
+            len,
+            flags,
+        }
+    }
+}
+
+/// Manipulate the allocated disk space of a file.
+pub struct Fallocate {
+    pub(in crate::driver) fd: FdOrFixed,
+    pub(in crate::driver) offset: u64,
+    pub(in crate::driver) len: u64,
+    pub(in crate::driver) mode: AllocateMode,
+}
+
+impl Fallocate {
+    /// Create [`Fallocate`] for the range of `len` bytes starting from `offset`.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_FALLOCATE` operation.
+    /// * kqueue: it is synchronized `fcntl(F_PREALLOCATE)` on Apple platforms and `posix_fallocate`
+    ///   on FreeBSD and DragonFly. Other platforms and modes unsupported by the syscall fail with
+    ///   [`io::ErrorKind::Unsupported`].
+    pub fn new(
+        fd: impl IntoFdOrFixed<Target = FdOrFixed>,
+        offset: u64,
+        len: u64,
+        mode: AllocateMode,
+    ) -> Self {
+        Self {
+            fd: fd.into(),
+            offset,
+            len,
+            mode,
+        }
+    }
+}
+
+/// Announce the expected access pattern of the file data.
+#[cfg_attr(target_vendor = "apple", allow(dead_code))]
+pub struct Fadvise {
+    pub(in crate::driver) fd: FdOrFixed,
+    pub(in crate::driver) offset: u64,
+    pub(in crate::driver) len: u64,
+    pub(in crate::driver) advice: Advice,
+}
+
+impl Fadvise {
+    /// Create [`Fadvise`] for the range of `len` bytes starting from `offset`.
+    ///
+    /// Zero `len` means the range till the end of file. Errors like `EINVAL` for unsupported advice
+    /// are returned as-is.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_FADVISE` operation.
+    /// * kqueue: it is synchronized `posix_fadvise` on FreeBSD and DragonFly. Other platforms fail
+    ///   with [`io::ErrorKind::Unsupported`].
+    pub fn new(
+        fd: impl IntoFdOrFixed<Target = FdOrFixed>,
+        offset: u64,
+        len: u64,
+        advice: Advice,
+    ) -> Self {
+        Self {
+            fd: fd.into(),
+            offset,
+            len,
+            advice,
+        }
+    }
+
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "dragonfly"
+    ))]
+    pub(in crate::driver) fn posix_advice(&self) -> i32 {
+        match self.advice {
+            Advice::Normal => libc::POSIX_FADV_NORMAL,
+            Advice::Sequential => libc::POSIX_FADV_SEQUENTIAL,
+            Advice::Random => libc::POSIX_FADV_RANDOM,
+            Advice::NoReuse => libc::POSIX_FADV_NOREUSE,
+            Advice::WillNeed => libc::POSIX_FADV_WILLNEED,
+            Advice::DontNeed => libc::POSIX_FADV_DONTNEED,
+        }
+    }
+}
+
+/// Announce the expected access pattern of the memory region.
+pub struct Madvise {
+    pub(in crate::driver) addr: *mut u8,
+    pub(in crate::driver) len: usize,
+    pub(in crate::driver) advice: Advice,
+}
+
+impl Madvise {
+    /// Create [`Madvise`] for the memory region of `len` bytes starting from `addr`.
+    ///
+    /// [`Advice::NoReuse`] has no memory counterpart and fails with
+    /// [`io::ErrorKind::InvalidInput`].
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_MADVISE` operation.
+    /// * kqueue: it is synchronized `madvise` syscall.
+    ///
+    /// # Safety
+    ///
+    /// `addr` should be page aligned and the region should stay mapped until the operation is
+    /// completed.
+    pub unsafe fn new(addr: *mut u8, len: usize, advice: Advice) -> Self {
+        Self { addr, len, advice }
+    }
+
+    pub(in crate::driver) fn madvise_advice(&self) -> io::Result<i32> {
+        match self.advice {
+            Advice::Normal => Ok(libc::MADV_NORMAL),
+            Advice::Sequential => Ok(libc::MADV_SEQUENTIAL),
+            Advice::Random => Ok(libc::MADV_RANDOM),
+            Advice::NoReuse => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "no reuse advice is not supported for memory regions",
+            )),
+            Advice::WillNeed => Ok(libc::MADV_WILLNEED),
+            Advice::DontNeed => Ok(libc::MADV_DONTNEED),
+        }
+    }
+}
+
+/// Truncate or extend a file to the specified length.
+pub struct Truncate {
+    pub(in crate::driver) fd: Fd,
+    pub(in crate::driver) len: u64,
+}
+
+impl Truncate {
+    /// Create [`Truncate`].
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: there is no opcode, `ftruncate` syscall is called before submission.
+    /// * kqueue: it is synchronized `ftruncate` syscall.
+    pub fn new(fd: Fd, len: u64) -> Self {
+        Self { fd, len }
+    }
+
+    pub(in crate::driver) fn ftruncate(&self) -> io::Result<usize> {
+        let len = libc::off_t::try_from(self.len)
+            .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+        crate::syscall!(ftruncate(self.fd.as_raw_fd(), len))?;
+        Ok(0)
+    }
+}
+
+/// Copy a range of data from one file to another.
+#[cfg_attr(not(any(target_os = "linux", target_os = "freebsd")), allow(dead_code))]
+pub struct CopyFileRange {
+    pub(in crate::driver) fd_in: Fd,
+    pub(in crate::driver) off_in: u64,
+    pub(in crate::driver) fd_out: Fd,
+    pub(in crate::driver) off_out: u64,
+    pub(in crate::driver) len: usize,
+}
+
+impl CopyFileRange {
+    /// Create [`CopyFileRange`] to copy `len` bytes from `fd_in` at `off_in` to `fd_out` at
+    /// `off_out`.
+    ///
+    /// The copy could be short, the number of copied bytes is returned. At most 16 MiB are
+    /// copied at once. Errors like `EXDEV` are returned as-is, so the caller could fall back to
+    /// reading and writing.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: there is no opcode, the blocking `copy_file_range` syscall is called on the
+    ///   driver thread before submission. [`copy_range`](crate::fs::copy_range) calls it on the
+    ///   blocking pool instead.
+    /// * kqueue: `copy_file_range` syscall on the blocking pool of the driver on FreeBSD. Other
+    ///   platforms fail with [`io::ErrorKind::Unsupported`].
+    pub fn new(fd_in: Fd, off_in: u64, fd_out: Fd, off_out: u64, len: usize) -> Self {
+        Self {
+            fd_in,
+            off_in,
+            fd_out,
+            off_out,
+            len,
+        }
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+    pub(in crate::driver) fn copy_file_range(&self) -> io::Result<usize> {
+        copy_file_range(
+            self.fd_in.as_raw_fd(),
+            self.off_in,
+            self.fd_out.as_raw_fd(),
+            self.off_out,
+            self.len,
+        )
+    }
+}
+
+// the longest range copied by one call, so the blocking syscall returns in bounded time
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+const MAX_COPY_LEN: usize = 1 << 24;
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+pub(crate) fn copy_file_range(
+    fd_in: RawFd,
+    off_in: u64,
+    fd_out: RawFd,
+    off_out: u64,
+    len: usize,
+) -> io::Result<usize> {
+    let mut off_in = off_in as _;
+    let mut off_out = off_out as _;
+    crate::syscall!(copy_file_range(
+        fd_in,
+        &mut off_in,
+        fd_out,
+        &mut off_out,
+        len.min(MAX_COPY_LEN),
+        0
+    ))
+    .map(|copied| usize::try_from(copied).expect("non negative"))
+}
+
+/// The file of an extended attribute operation.
+#[cfg_attr(
+    not(any(target_os = "linux", target_vendor = "apple")),
+    allow(dead_code)
+)]
+pub(in crate::driver) enum XattrTarget {
+    Fd(Fd),
+    Path(CString),
+}
+
+/// Get an extended attribute of a file into the buffer.
+///
+/// The result is the length of the value. The buffer too small for the value fails with
+/// `ERANGE`, so the caller could retry with a larger one.
+#[cfg_attr(
+    not(any(target_os = "linux", target_vendor = "apple")),
+    allow(dead_code)
+)]
+pub struct GetXattr<'arena, T: IoBufMut<'arena>> {
+    pub(in crate::driver) target: XattrTarget,
+    pub(in crate::driver) name: CString,
+    pub(in crate::driver) buffer: T,
+    _lifetime: PhantomData<&'arena ()>,
+}
+
+impl<'arena, T: IoBufMut<'arena>> GetXattr<'arena, T> {
+    /// Create [`GetXattr`] getting the attribute `name` of the open file.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: there is no opcode, `fgetxattr` syscall is called before submission.
+    /// * kqueue: it is synchronized `fgetxattr` syscall on macOS, other systems fail with
+    ///   [`io::ErrorKind::Unsupported`].
+    pub fn new(fd: Fd, name: CString, buffer: T) -> Self {
+        Self::with_target(XattrTarget::Fd(fd), name, buffer)
+    }
+
+    /// Create [`GetXattr`] getting the attribute `name` of the file at `path`.
+    ///
+    /// See [`GetXattr::new`] for the platform specific details, `getxattr` syscall is used.
+    pub fn with_path(path: CString, name: CString, buffer: T) -> Self {
+        Self::with_target(XattrTarget::Path(path), name, buffer)
+    }
+
+    fn with_target(target: XattrTarget, name: CString, buffer: T) -> Self {
+        Self {
+            target,
+            name,
+            buffer,
+            _lifetime: PhantomData,
+        }
+    }
+
+    #[cfg(any(target_os = "linux", target_vendor = "apple"))]
+    pub(in crate::driver) fn get(&mut self) -> io::Result<usize> {
+        // SAFETY: IoBufMut is Unpin
+        let slice = self.buffer.as_uninit_slice();
+        let (value, size) = (slice.as_mut_ptr() as *mut libc::c_void, slice.len());
+        let name = self.name.as_ptr();
+        #[cfg(target_os = "linux")]
+        let res = match &self.target {
+            XattrTarget::Fd(fd) => syscall!(fgetxattr(fd.as_raw_fd(), name, value, size)),
+            XattrTarget::Path(path) => syscall!(getxattr(path.as_ptr(), name, value, size)),
+        };
+        #[cfg(target_vendor = "apple")]
+        let res = match &self.target {
+            XattrTarget::Fd(fd) => syscall!(fgetxattr(fd.as_raw_fd(), name, value, size, 0, 0)),
+            XattrTarget::Path(path) => syscall!(getxattr(path.as_ptr(), name, value, size, 0, 0)),
+        };
+        let len = usize::try_from(res?).expect("non negative");
+        if size == 0 && len > 0 {
+            // the empty buffer returns the length of the value
+            return Err(io::Error::from_raw_os_error(libc::ERANGE));
+        }
+        Ok(len)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_vendor = "apple")))]
+    pub(in crate::driver) fn get(&mut self) -> io::Result<usize> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "extended attributes are not supported on this platform",
+        ))
+    }
+}
+
+impl<'arena, T: IoBufMut<'arena>> IntoInner for GetXattr<'arena, T> {
+    type Inner = T;
+
+    fn into_inner(self) -> Self::Inner {
+        self.buffer
+    }
+}
+
+/// Set an extended attribute of a file to the value from the buffer.
+#[cfg_attr(
+    not(any(target_os = "linux", target_vendor = "apple")),
+    allow(dead_code)
+)]
+pub struct SetXattr<'arena, T: IoBuf<'arena>> {
+    pub(in crate::driver) target: XattrTarget,
+    pub(in crate::driver) name: CString,
+    pub(in crate::driver) value: T,
+    pub(in crate::driver) flags: i32,
+    _lifetime: PhantomData<&'arena ()>,
+}
+
+impl<'arena, T: IoBuf<'arena>> SetXattr<'arena, T> {
+    /// Create [`SetXattr`] setting the attribute `name` of the open file.
+    ///
+    /// `flags` are `XATTR_CREATE` or `XATTR_REPLACE` described in `setxattr(2)`.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: there is no opcode, `fsetxattr` syscall is called before submission.
+    /// * kqueue: it is synchronized `fsetxattr` syscall on macOS, other systems fail with
+    ///   [`io::ErrorKind::Unsupported`].
+    pub fn new(fd: Fd, name: CString, value: T, flags: i32) -> Self {
+        Self::with_target(XattrTarget::Fd(fd), name, value, flags)
+    }
+
+    /// Create [`SetXattr`] setting the attribute `name` of the file at `path`.
+    ///
+    /// See [`SetXattr::new`] for the platform specific details, `setxattr` syscall is used.
+    pub fn with_path(path: CString, name: CString, value: T, flags: i32) -> Self {
+        Self::with_target(XattrTarget::Path(path), name, value, flags)
+    }
+
+    fn with_target(target: XattrTarget, name: CString, value: T, flags: i32) -> Self {
+        Self {
+            target,
+            name,
+            value,
+            flags,
+            _lifetime: PhantomData,
+        }
+    }
+
+    #[cfg(any(target_os = "linux", target_vendor = "apple"))]
+    pub(in crate::driver) fn set(&self) -> io::Result<usize> {
+        let slice = self.value.as_slice();
+        let (value, size) = (slice.as_ptr() as *const libc::c_void, slice.len());
+        let (name, flags) = (self.name.as_ptr(), self.flags);
+        #[cfg(target_os = "linux")]
+        let res = match &self.target {
+            XattrTarget::Fd(fd) => syscall!(fsetxattr(fd.as_raw_fd(), name, value, size, flags)),
+            XattrTarget::Path(path) => {
+                syscall!(setxattr(path.as_ptr(), name, value, size, flags))
+            }
+        };
+        #[cfg(target_vendor = "apple")]
+        let res = match &self.target {
+            XattrTarget::Fd(fd) => {
+                syscall!(fsetxattr(fd.as_raw_fd(), name, value, size, 0, flags))
+            }
+            XattrTarget::Path(path) => {
+                syscall!(setxattr(path.as_ptr(), name, value, size, 0, flags))
+            }
+        };
+        res.map(|_| 0)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_vendor = "apple")))]
+    pub(in crate::driver) fn set(&self) -> io::Result<usize> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "extended attributes are not supported on this platform",
+        ))
+    }
+}
+
+impl<'arena, T: IoBuf<'arena>> IntoInner for SetXattr<'arena, T> {
+    type Inner = T;
+
+    fn into_inner(self) -> Self::Inner {
+        self.value
+    }
+}
+
+/// Receive a single piece of data in a single buffer from remote.
+pub struct Recv<'arena, T: IoBufMut<'arena>> {
+    pub(in crate::driver) fd: FdOrFixed,
+    pub(in crate::driver) buffer: T,
+    pub(in crate::driver) flags: i32,
+    _lifetime: PhantomData<&'arena ()>,
+}
+
+impl<'arena, T: IoBufMut<'arena>> Recv<'arena, T> {
+    /// Create [`Recv`].
+    pub fn new(fd: impl IntoFdOrFixed<Target = FdOrFixed>, buffer: T) -> Self {
+        Self {
+            fd: fd.into(),
+            buffer,
+            flags: 0,
+            _lifetime: PhantomData,
+        }
+    }
+
+    /// Set `MSG_*` flags like `MSG_PEEK` or `MSG_WAITALL` of the `recv` call.
+    ///
+    /// With `MSG_TRUNC` Linux returns the real length of a datagram even if it is longer than
+    /// the buffer.
+    pub fn with_flags(mut self, flags: i32) -> Self {
+        self.flags = flags;
+        self
+    }
+}
+
+impl<'arena, T: IoBufMut<'arena>> IntoInner for Recv<'arena, T> {
+    type Inner = T;
+
+    fn into_inner(self) -> Self::Inner {
+        self.buffer
+    }
+}
+
+/// Receive a single piece of data into scattered buffers from remote.
+pub struct RecvVectoredImpl<'arena, T: AsIoSlicesMut<'arena>> {
+    pub(in crate::driver) fd: FdOrFixed,
+    pub(in crate::driver) buffer: T,
+    _lifetime: PhantomData<&'arena ()>,
+}
+
+impl<'arena, T: AsIoSlicesMut<'arena>> RecvVectoredImpl<'arena, T> {
+    /// Create [`RecvVectored`].
+    pub fn new(fd: impl IntoFdOrFixed<Target = FdOrFixed>, buffer: T) -> Self {
+        Self {
+            fd: fd.into(),
+            buffer,
+            _lifetime: PhantomData,
+        }
+    }
+}
+
+impl<'arena, T: AsIoSlicesMut<'arena>> IntoInner for RecvVectoredImpl<'arena, T> {
+    type Inner = T;
+
+    fn into_inner(self) -> Self::Inner {
+        self.buffer
+    }
+}
+
+/// Send a single piece of data from a single buffer to remote.
+pub struct Send<'arena, T: IoBuf<'arena>> {
+    pub(in crate::driver) fd: FdOrFixed,
+    pub(in crate::driver) buffer: T,
+    pub(in crate::driver) flags: i32,
+    _lifetime: PhantomData<&'arena ()>,
+}
+
+impl<'arena, T: IoBuf<'arena>> Send<'arena, T> {
+    /// Create [`Send`].
+    pub fn new(fd: impl IntoFdOrFixed<Target = FdOrFixed>, buffer: T) -> Self {
+        Self {
+            fd: fd.into(),
+            buffer,
+            flags: 0,
+            _lifetime: PhantomData,
+        }
+    }
+
+    /// Set `MSG_*` flags like `MSG_MORE` or `MSG_NOSIGNAL` of the `send` call.
+    pub fn with_flags(mut self, flags: i32) -> Self {
+        self.flags = flags;
+        self
+    }
+}
+
+impl<'arena, T: IoBuf<'arena>> IntoInner for Send<'arena, T> {
+    type Inner = T;
+
+    fn into_inner(self) -> Self::Inner {
+        self.buffer
+    }
+}
+
+/// Send a single piece of data from a single buffer to remote without copying it into the
+/// kernel.
+///
+/// The operation completes only when the kernel no longer references the buffer.
+pub struct SendZc<'arena, T: IoBuf<'arena>> {
+    pub(in crate::driver) fd: FdOrFixed,
+    pub(in crate::driver) buffer: T,
+    // chosen by the driver when the operation is pushed
+    #[cfg(target_os = "linux")]
+    pub(in crate::driver) zero_copy: bool,
+    _lifetime: PhantomData<&'arena ()>,
+}
+
+impl<'arena, T: IoBuf<'arena>> SendZc<'arena, T> {
+    /// Create [`SendZc`].
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_SEND_ZC` operation if the kernel supports it, otherwise
+    ///   `IORING_OP_SEND`.
+    /// * kqueue: the same as [`Send`].
+    pub fn new(fd: impl IntoFdOrFixed<Target = FdOrFixed>, buffer: T) -> Self {
+        Self {
+            fd: fd.into(),
+            buffer,
+            #[cfg(target_os = "linux")]
+            zero_copy: false,
+            _lifetime: PhantomData,
+        }
+    }
+}
+
+impl<'arena, T: IoBuf<'arena>> IntoInner for SendZc<'arena, T> {
+    type Inner = T;
+
+    fn into_inner(self) -> Self::Inner {
+        self.buffer
+    }
+}
+
+/// Send a single piece of data to remote using scattered buffers.
+pub struct SendVectoredImpl<'arena, T: AsIoSlices<'arena>> {
+    pub(in crate::driver) fd: FdOrFixed,
+    pub(in crate::driver) buffer: T,
+    _lifetime: PhantomData<&'arena ()>,
+}
+
+impl<'arena, T: AsIoSlices<'arena>> SendVectoredImpl<'arena, T> {
+    /// Create [`SendVectored`].
+    pub fn new(fd: impl IntoFdOrFixed<Target = FdOrFixed>, buffer: T) -> Self {
+        Self {
+            fd: fd.into(),
+            buffer,
+            _lifetime: PhantomData,
+        }
+    }
+}
+
+impl<'arena, T: AsIoSlices<'arena>> IntoInner for SendVectoredImpl<'arena, T> {
+    type Inner = T;
+
+    fn into_inner(self) -> Self::Inner {
+        self.buffer
+    }
+}
+
+/// Receive a single piece of data and source address using scattered buffers.
+pub struct RecvMsgImpl<'arena, T: AsIoSlicesMut<'arena>> {
+    pub(in crate::driver) fd: FdOrFixed,
+    pub(in crate::driver) buffer: T,
+    pub(in crate::driver) addr: sockaddr_storage,
+    pub(in crate::driver) msg: libc::msghdr,
+    pub(in crate::driver) flags: i32,
     _lifetime: PhantomData<&'arena ()>,
 }
 
@@ -364,10 +1576,23 @@ impl<'arena, T: AsIoSlicesMut<'arena>> RecvMsgImpl<'arena, T> {
             buffer,
             addr: unsafe { std::mem::zeroed() },
             msg: unsafe { std::mem::zeroed() },
+            flags: 0,
             _lifetime: PhantomData,
         }
     }
 
+    /// Set `MSG_*` flags of the `recvmsg` call, see [`Recv::with_flags`].
+    pub fn with_flags(mut self, flags: i32) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// The `MSG_*` flags of the received message, like `MSG_TRUNC` for a datagram truncated
+    /// to the buffer. The flags are set when the operation completes.
+    pub fn msg_flags(&self) -> i32 {
+        self.msg.msg_flags
+    }
+
     pub(in crate::driver) fn set_msg(&mut self) -> &mut libc::msghdr {
         // SAFETY: IoSliceMut is Unpin
         let (slices, len) = unsafe {
@@ -404,6 +1629,7 @@ pub struct SendMsgImpl<'arena, T: AsIoSlices<'arena>> {
     pub(in crate::driver) buffer: T,
     pub(in crate::driver) addr: SockAddr,
     pub(in crate::driver) msg: libc::msghdr,
+    pub(in crate::driver) flags: i32,
     _lifetime: PhantomData<&'arena ()>,
 }
 
@@ -415,10 +1641,17 @@ impl<'arena, T: AsIoSlices<'arena>> SendMsgImpl<'arena, T> {
             buffer,
             addr,
             msg: unsafe { std::mem::zeroed() },
+            flags: 0,
             _lifetime: PhantomData,
         }
     }
 
+    /// Set `MSG_*` flags of the `sendmsg` call, see [`Send::with_flags`].
+    pub fn with_flags(mut self, flags: i32) -> Self {
+        self.flags = flags;
+        self
+    }
+
     pub(in crate::driver) fn set_msg(&mut self) -> &libc::msghdr {
         // SAFETY: IoSlice is Unpin
         let (slices, len) = unsafe {
@@ -446,3 +1679,154 @@ impl<'arena, T: AsIoSlices<'arena>> IntoInner for SendMsgImpl<'arena, T> {
         self.buffer
     }
 }
+
+// received file descriptors shouldn't leak into child processes
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+const RECVMSG_FLAGS: i32 = libc::MSG_CMSG_CLOEXEC;
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+const RECVMSG_FLAGS: i32 = 0;
+
+/// Receive a single piece of data, source address and ancillary data using scattered buffers
+/// and a control buffer.
+pub struct RecvMsg<'arena, T: AsIoSlicesMut<'arena>, C: IoBufMut<'arena>> {
+    pub(in crate::driver) fd: FdOrFixed,
+    pub(in crate::driver) buffer: T,
+    pub(in crate::driver) control: C,
+    pub(in crate::driver) addr: sockaddr_storage,
+    pub(in crate::driver) msg: libc::msghdr,
+    _lifetime: PhantomData<&'arena ()>,
+}
+
+impl<'arena, T: AsIoSlicesMut<'arena>, C: IoBufMut<'arena>> RecvMsg<'arena, T, C> {
+    /// Create [`RecvMsg`].
+    ///
+    /// The whole capacity of `control` is available for control messages. Received file
+    /// descriptors are close-on-exec on the platforms supporting `MSG_CMSG_CLOEXEC`.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_RECVMSG` operation.
+    /// * kqueue: `recvmsg` syscall when the socket is readable.
+    pub fn new(fd: impl IntoFdOrFixed<Target = FdOrFixed>, buffer: T, control: C) -> Self {
+        Self {
+            fd: fd.into(),
+            buffer,
+            control,
+            addr: unsafe { std::mem::zeroed() },
+            msg: unsafe { std::mem::zeroed() },
+            _lifetime: PhantomData,
+        }
+    }
+
+    /// Flags of the received message like `MSG_TRUNC` or `MSG_CTRUNC`.
+    ///
+    /// The value is meaningful only after the operation completes successfully.
+    pub fn msg_flags(&self) -> i32 {
+        self.msg.msg_flags
+    }
+
+    /// Length of the received control data.
+    ///
+    /// The value is meaningful only after the operation completes successfully.
+    pub fn control_len(&self) -> usize {
+        self.msg.msg_controllen as _
+    }
+
+    pub(in crate::driver) fn flags(&self) -> i32 {
+        RECVMSG_FLAGS
+    }
+
+    pub(in crate::driver) fn set_msg(&mut self) -> &mut libc::msghdr {
+        // SAFETY: IoSliceMut and IoBufMut are Unpin
+        let (slices, len) = unsafe {
+            let slices = self.buffer.as_io_slices_mut();
+            let len = slices.len();
+            (slices.as_mut_ptr(), len)
+        };
+        let control = self.control.as_uninit_slice();
+        let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+        msg.msg_name = &mut self.addr as *mut _ as _;
+        msg.msg_namelen = std::mem::size_of_val(&self.addr) as _;
+        msg.msg_iov = slices as _;
+        msg.msg_iovlen = len as _;
+        msg.msg_control = control.as_mut_ptr() as _;
+        msg.msg_controllen = control.len() as _;
+        self.msg = msg;
+        &mut self.msg
+    }
+}
+
+impl<'arena, T: AsIoSlicesMut<'arena>, C: IoBufMut<'arena>> IntoInner for RecvMsg<'arena, T, C> {
+    type Inner = (T, C, SockAddr);
+
+    fn into_inner(self) -> Self::Inner {
+        (self.buffer, self.control, unsafe {
+            SockAddr::new(self.addr, self.msg.msg_namelen)
+        })
+    }
+}
+
+/// Send a single piece of data and ancillary data from scattered buffers and a control buffer.
+pub struct SendMsg<'arena, T: AsIoSlices<'arena>, C: IoBuf<'arena>> {
+    pub(in crate::driver) fd: FdOrFixed,
+    pub(in crate::driver) buffer: T,
+    pub(in crate::driver) control: C,
+    pub(in crate::driver) addr: Option<SockAddr>,
+    pub(in crate::driver) msg: libc::msghdr,
+    _lifetime: PhantomData<&'arena ()>,
+}
+
+impl<'arena, T: AsIoSlices<'arena>, C: IoBuf<'arena>> SendMsg<'arena, T, C> {
+    /// Create [`SendMsg`]. `addr` should be `None` for connected sockets.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_SENDMSG` operation.
+    /// * kqueue: `sendmsg` syscall when the socket is writable.
+    pub fn new(
+        fd: impl IntoFdOrFixed<Target = FdOrFixed>,
+        buffer: T,
+        control: C,
+        addr: Option<SockAddr>,
+    ) -> Self {
+        Self {
+            fd: fd.into(),
+            buffer,
+            control,
+            addr,
+            msg: unsafe { std::mem::zeroed() },
+            _lifetime: PhantomData,
+        }
+    }
+
+    pub(in crate::driver) fn set_msg(&mut self) -> &libc::msghdr {
+        // SAFETY: IoSlice and IoBuf are Unpin
+        let (slices, len) = unsafe {
+            let slices = self.buffer.as_io_slices();
+            let len = slices.len();
+            (slices.as_ptr(), len)
+        };
+        let control = self.control.as_slice();
+        let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+        if let Some(addr) = &self.addr {
+            msg.msg_name = addr.as_ptr() as _;
+            msg.msg_namelen = addr.len();
+        }
+        msg.msg_iov = slices as _;
+        msg.msg_iovlen = len as _;
+        if !control.is_empty() {
+            msg.msg_control = control.as_ptr() as _;
+            msg.msg_controllen = control.len() as _;
+        }
+        self.msg = msg;
+        &self.msg
+    }
+}
+
+impl<'arena, T: AsIoSlices<'arena>, C: IoBuf<'arena>> IntoInner for SendMsg<'arena, T, C> {
+    type Inner = (T, C);
+
+    fn into_inner(self) -> Self::Inner {
+        (self.buffer, self.control)
+    }
+}
@@ -0,0 +1,56 @@
+use std::collections::HashSet;
+
+use crate::driver::Entry;
+
+/// User defined data of the operations which are pushed and not completed yet.
+///
+/// The data is tracked only if the check is enabled with
+/// [`DriverBuilder::check_user_data`](crate::driver::DriverBuilder::check_user_data).
+#[derive(Debug, Default)]
+pub(crate) struct UserDataSet {
+    in_flight: Option<HashSet<usize>>,
+}
+
+impl UserDataSet {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            in_flight: enabled.then(HashSet::new),
+        }
+    }
+
+    /// Returns `false` if an operation with the same data is in flight.
+    #[inline]
+    pub(crate) fn insert(&mut self, user_data: usize) -> bool {
+        match &mut self.in_flight {
+            Some(in_flight) => in_flight.insert(user_data),
+            None => true,
+        }
+    }
+
+    /// Remove the data of the operation which is not pushed.
+    #[cfg(target_os = "linux")]
+    #[inline]
+    pub(crate) fn remove(&mut self, user_data: usize) {
+        if let Some(in_flight) = &mut self.in_flight {
+            in_flight.remove(&user_data);
+        }
+    }
+
+    /// Release the data of the operation completed with the entry.
+    ///
+    /// The operations completing multiple times keep the data till the last entry.
+    #[inline]
+    pub(crate) fn release(&mut self, entry: &Entry) {
+        if let Some(in_flight) = &mut self.in_flight {
+            if !entry.has_more() {
+                in_flight.remove(&entry.user_data());
+            }
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        if let Some(in_flight) = &mut self.in_flight {
+            in_flight.clear();
+        }
+    }
+}
@@ -5,13 +5,14 @@ use std::{
 
 use arrayvec::ArrayVec;
 
-use crate::{impl_raw_fd, op::Read, syscall, task::RUNTIME};
+use crate::{impl_raw_fd, op::Read, syscall, Attacher};
 
 /// An event that won't wake until [`EventHandle::notify`] is called
 /// successfully.
 #[derive(Debug)]
 pub struct Event {
     fd: OwnedFd,
+    attacher: Attacher,
 }
 
 impl Event {
@@ -19,7 +20,10 @@ impl Event {
     pub fn new() -> io::Result<Self> {
         let fd = syscall!(eventfd(0, 0))?;
         let fd = unsafe { OwnedFd::from_raw_fd(fd) };
-        Ok(Self { fd })
+        Ok(Self {
+            fd,
+            attacher: Attacher::new(),
+        })
     }
 
     /// Get a notify handle.
@@ -30,15 +34,15 @@ impl Event {
     /// Wait for [`EventHandle::notify`] called.
     pub async fn wait(&self) -> io::Result<()> {
         let buffer = ArrayVec::<u8, 8>::new();
-        let fd = RUNTIME.with(|runtime| runtime.attach(self.fd.as_raw_fd()))?;
+        let (fd, runtime) = self.attacher.attach_runtime(&self.fd)?;
         let op = Read::new(fd, buffer);
-        let (res, _) = RUNTIME.with(|runtime| runtime.submit(op)).await;
+        let (res, _) = runtime.submit(op).await;
         res?;
         Ok(())
     }
 }
 
-impl_raw_fd!(Event, fd);
+impl_raw_fd!(Event, fd, attacher);
 
 /// A handle to [`Event`].
 pub struct EventHandle {
@@ -7,7 +7,7 @@ use std::{
 
 use arrayvec::ArrayVec;
 
-use crate::{impl_raw_fd, op::Read, syscall, task::RUNTIME};
+use crate::{impl_raw_fd, op::Read, syscall, Attacher};
 
 /// An event that won't wake until [`EventHandle::notify`] is called
 /// successfully.
@@ -15,6 +15,7 @@ use crate::{impl_raw_fd, op::Read, syscall, task::RUNTIME};
 pub struct Event {
     sender: OwnedFd,
     receiver: OwnedFd,
+    attacher: Attacher,
 }
 
 impl Event {
@@ -24,7 +25,11 @@ impl Event {
         sender.set_nonblocking(false)?;
         let sender = unsafe { OwnedFd::from_raw_fd(sender.into_raw_fd()) };
         let receiver = unsafe { OwnedFd::from_raw_fd(receiver.into_raw_fd()) };
-        Ok(Self { sender, receiver })
+        Ok(Self {
+            sender,
+            receiver,
+            attacher: Attacher::new(),
+        })
     }
 
     /// Get a notify handle.
@@ -35,9 +40,9 @@ impl Event {
     /// Wait for [`EventHandle::notify`] called.
     pub async fn wait(&self) -> io::Result<()> {
         let buffer = ArrayVec::<u8, 1>::new();
-        let fd = RUNTIME.with(|runtime| runtime.attach(self.receiver.as_raw_fd()))?;
+        let (fd, runtime) = self.attacher.attach_runtime(&self.receiver)?;
         let op = Read::new(fd, buffer);
-        let (res, _) = RUNTIME.with(|runtime| runtime.submit(op)).await;
+        let (res, _) = runtime.submit(op).await;
         res?;
         Ok(())
     }
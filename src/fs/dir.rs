@@ -0,0 +1,176 @@
+#[cfg(unix)]
+use std::ffi::CString;
+use std::{io, path::Path};
+
+use crate::{
+    fs::metadata,
+    op::{LinkAt, MkdirAt, RenameAt, RenameFlags, SymlinkAt, UnlinkAt},
+    task::RUNTIME,
+};
+
+#[cfg(unix)]
+pub(crate) fn path_to_cstring(path: &Path) -> io::Result<CString> {
+    use std::os::unix::ffi::OsStrExt;
+
+    Ok(CString::new(path.as_os_str().as_bytes())?)
+}
+
+/// Creates a new, empty directory at the provided path.
+///
+/// ## Platform specific
+///
+/// * io-uring: `IORING_OP_MKDIRAT` operation.
+/// * kqueue: it is synchronized `mkdirat` syscall.
+/// * IOCP: it is synchronized `CreateDirectoryW` call.
+pub async fn create_dir(path: impl AsRef<Path>) -> io::Result<()> {
+    #[cfg(unix)]
+    let op = MkdirAt::new(libc::AT_FDCWD, path_to_cstring(path.as_ref())?, 0o777);
+    #[cfg(target_os = "windows")]
+    let op = MkdirAt::new(path.as_ref());
+    RUNTIME.with(|runtime| runtime.submit(op)).await.0?;
+    Ok(())
+}
+
+/// Recursively creates a directory and all of its parent components if they
+/// are missing.
+///
+/// Existing directories are skipped. If a component exists but is not a
+/// directory, an error is returned.
+pub async fn create_dir_all(path: impl AsRef<Path>) -> io::Result<()> {
+    // the path goes first, the root goes last
+    let ancestors = path
+        .as_ref()
+        .ancestors()
+        .filter(|ancestor| !ancestor.as_os_str().is_empty())
+        .collect::<Vec<_>>();
+    // find the deepest component with an existing parent
+    let mut created = 0;
+    while created < ancestors.len() {
+        match create_dir_if_missing(ancestors[created]).await {
+            Ok(()) => break,
+            Err(e) if e.kind() == io::ErrorKind::NotFound && created + 1 < ancestors.len() => {
+                created += 1
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    for ancestor in ancestors[..created].iter().rev() {
+        create_dir_if_missing(ancestor).await?;
+    }
+    Ok(())
+}
+
+async fn create_dir_if_missing(path: &Path) -> io::Result<()> {
+    match create_dir(path).await {
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+            if metadata(path).await?.is_dir() {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+        res => res,
+    }
+}
+
+/// Removes a file from the filesystem.
+///
+/// ## Platform specific
+///
+/// * io-uring: `IORING_OP_UNLINKAT` operation.
+/// * kqueue: it is synchronized `unlinkat` syscall.
+/// * IOCP: it is synchronized `DeleteFileW` call.
+pub async fn remove_file(path: impl AsRef<Path>) -> io::Result<()> {
+    #[cfg(unix)]
+    let op = UnlinkAt::new(libc::AT_FDCWD, path_to_cstring(path.as_ref())?, 0);
+    #[cfg(target_os = "windows")]
+    let op = UnlinkAt::new(path.as_ref(), false);
+    RUNTIME.with(|runtime| runtime.submit(op)).await.0?;
+    Ok(())
+}
+
+/// Renames a file or directory to a new name, replacing the original file if
+/// `to` already exists.
+///
+/// See [`rename_with_flags`] for the platform specific details.
+pub async fn rename(from: impl AsRef<Path>, to: impl AsRef<Path>) -> io::Result<()> {
+    rename_with_flags(from, to, RenameFlags::empty()).await
+}
+
+/// Renames a file or directory to a new name according to `flags`.
+///
+/// With [`RenameFlags::NOREPLACE`] it fails with
+/// [`io::ErrorKind::AlreadyExists`] if `to` already exists.
+///
+/// ## Platform specific
+///
+/// * io-uring: `IORING_OP_RENAMEAT` operation.
+/// * kqueue: it is synchronized `renameat` syscall. [`RenameFlags::NOREPLACE`] is supported on
+///   Apple platforms only.
+/// * IOCP: it is synchronized `MoveFileExW` call.
+pub async fn rename_with_flags(
+    from: impl AsRef<Path>,
+    to: impl AsRef<Path>,
+    flags: RenameFlags,
+) -> io::Result<()> {
+    #[cfg(unix)]
+    let op = RenameAt::new(
+        libc::AT_FDCWD,
+        path_to_cstring(from.as_ref())?,
+        libc::AT_FDCWD,
+        path_to_cstring(to.as_ref())?,
+        flags,
+    );
+    #[cfg(target_os = "windows")]
+    let op = RenameAt::new(from.as_ref(), to.as_ref(), flags);
+    RUNTIME.with(|runtime| runtime.submit(op)).await.0?;
+    Ok(())
+}
+
+/// Creates a new symbolic link at `link` pointing to `target`.
+///
+/// ## Platform specific
+///
+/// * io-uring: `IORING_OP_SYMLINKAT` operation.
+/// * kqueue: it is synchronized `symlinkat` syscall.
+/// * IOCP: it is synchronized `CreateSymbolicLinkW` call. A directory symbolic link is created if
+///   `target` is an existing directory; relative `target` is resolved against the parent of `link`.
+pub async fn symlink(target: impl AsRef<Path>, link: impl AsRef<Path>) -> io::Result<()> {
+    #[cfg(unix)]
+    let op = SymlinkAt::new(
+        path_to_cstring(target.as_ref())?,
+        libc::AT_FDCWD,
+        path_to_cstring(link.as_ref())?,
+    );
+    #[cfg(target_os = "windows")]
+    let op = {
+        let (target, link) = (target.as_ref(), link.as_ref());
+        let resolved = link.parent().unwrap_or(link).join(target);
+        let dir = metadata(resolved).await.is_ok_and(|m| m.is_dir());
+        SymlinkAt::new(target, link, dir)
+    };
+    RUNTIME.with(|runtime| runtime.submit(op)).await.0?;
+    Ok(())
+}
+
+/// Creates a new hard link at `dst` for the existing file at `src`.
+///
+/// ## Platform specific
+///
+/// * io-uring: `IORING_OP_LINKAT` operation.
+/// * kqueue: it is synchronized `linkat` syscall.
+/// * IOCP: it is synchronized `CreateHardLinkW` call.
+pub async fn hard_link(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<()> {
+    #[cfg(unix)]
+    let op = LinkAt::new(
+        libc::AT_FDCWD,
+        path_to_cstring(src.as_ref())?,
+        libc::AT_FDCWD,
+        path_to_cstring(dst.as_ref())?,
+        0,
+    );
+    #[cfg(target_os = "windows")]
+    let op = LinkAt::new(src.as_ref(), dst.as_ref());
+    RUNTIME.with(|runtime| runtime.submit(op)).await.0?;
+    Ok(())
+}
@@ -1,16 +1,29 @@
 #[cfg(all(feature = "allocator_api", feature = "runtime"))]
 use std::alloc::Allocator;
-use std::{fs::Metadata, io, path::Path};
+#[cfg(all(feature = "runtime", unix))]
+use std::ffi::CString;
+use std::{io, path::Path};
 
+#[cfg(all(feature = "runtime", unix, not(target_os = "linux")))]
+use crate::op::CopyFileRange;
 #[cfg(feature = "runtime")]
 use crate::{
-    buf::{IntoInner, IoBuf, IoBufMut},
+    buf::{IntoInner, IoBuf, IoBufMut, VectoredBufWrapper},
     buf_try,
-    driver::Fd,
-    op::{ReadAt, Sync, WriteAt},
-    task::RUNTIME,
+    driver::{Fd, IntoRawFd},
+    fs::Metadata,
+    op::{
+        AllocateMode, Close, Fallocate, ReadAt, ReadVectoredAt, Sync, SyncFileRange,
+        SyncRangeFlags, Truncate, WriteAt, WriteVectoredAt,
+    },
+    task::{runtime::Runtime, RUNTIME},
     vec_alloc, Attacher, BufResult,
 };
+#[cfg(all(feature = "runtime", unix))]
+use crate::{
+    driver::AsRawFd,
+    op::{Advice, Fadvise, GetXattr, SetXattr, Statx},
+};
 use crate::{fs::OpenOptions, impl_raw_fd};
 
 /// A reference to an open file on the filesystem.
@@ -26,31 +39,10 @@ pub struct File {
     attacher: Attacher,
 }
 
-#[cfg(target_os = "windows")]
-fn file_with_options(
-    path: impl AsRef<Path>,
-    mut options: std::fs::OpenOptions,
-) -> io::Result<std::fs::File> {
-    use std::os::windows::prelude::OpenOptionsExt;
-
-    use windows_sys::Win32::Storage::FileSystem::FILE_FLAG_OVERLAPPED;
-
-    options.custom_flags(FILE_FLAG_OVERLAPPED);
-    options.open(path)
-}
-
-#[cfg(not(target_os = "windows"))]
-fn file_with_options(
-    path: impl AsRef<Path>,
-    options: std::fs::OpenOptions,
-) -> io::Result<std::fs::File> {
-    options.open(path)
-}
-
 impl File {
     pub(crate) fn with_options(path: impl AsRef<Path>, options: OpenOptions) -> io::Result<Self> {
         let this = Self {
-            inner: file_with_options(path, options.0)?,
+            inner: options.to_std()?.open(path)?,
             #[cfg(feature = "runtime")]
             attacher: Attacher::new(),
         };
@@ -64,6 +56,14 @@ impl File {
         OpenOptions::new().read(true).open(path)
     }
 
+    /// Attempts to open a file in read-only mode through the driver.
+    ///
+    /// Unlike [`File::open`] it doesn't block the thread on io-uring.
+    #[cfg(feature = "runtime")]
+    pub async fn open_async(path: impl AsRef<Path>) -> io::Result<Self> {
+        OpenOptions::new().read(true).open_async(path).await
+    }
+
     /// Opens a file in write-only mode.
     ///
     /// This function will create a file if it does not exist,
@@ -83,23 +83,67 @@ impl File {
         self.attacher.attach(self)
     }
 
+    #[cfg(feature = "runtime")]
+    pub(crate) fn attach_runtime(&self) -> io::Result<(Fd, &Runtime)> {
+        self.attacher.attach_runtime(self)
+    }
+
     /// Creates a new `File` instance that shares the same underlying file
     /// handle as the existing `File` instance.
     ///
-    /// It does not clear the attach state.
+    /// The new instance submits operations using its own duplicated handle, so
+    /// it stays usable after the original is closed.
     pub fn try_clone(&self) -> io::Result<Self> {
+        let inner = self.inner.try_clone()?;
         Ok(Self {
-            inner: self.inner.try_clone()?,
             #[cfg(feature = "runtime")]
-            attacher: self.attacher.clone(),
+            attacher: self.attacher.duplicate(&inner),
+            inner,
         })
     }
 
+    /// Close the file through the driver.
+    ///
+    /// The file is consumed, so no other operation can be submitted using its
+    /// file descriptor while the close is in flight.
+    #[cfg(feature = "runtime")]
+    pub async fn close(self) -> io::Result<()> {
+        let op = Close::new(self.inner.into_raw_fd());
+        RUNTIME.with(|runtime| runtime.submit(op)).await.0?;
+        Ok(())
+    }
+
     /// Queries metadata about the underlying file.
-    pub fn metadata(&self) -> io::Result<Metadata> {
+    pub fn metadata(&self) -> io::Result<std::fs::Metadata> {
         self.inner.metadata()
     }
 
+    /// Queries metadata about the underlying file through the driver.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_STATX` operation.
+    /// * kqueue: it is synchronized `fstat` syscall.
+    /// * IOCP: it is synchronized [`std::fs::File::metadata`] call.
+    #[cfg(feature = "runtime")]
+    pub async fn metadata_async(&self) -> io::Result<Metadata> {
+        #[cfg(unix)]
+        {
+            use crate::fs::metadata::{statx, STATX_MASK};
+
+            #[cfg(target_os = "linux")]
+            let flags = libc::AT_EMPTY_PATH;
+            #[cfg(not(target_os = "linux"))]
+            let flags = 0;
+            let op = Statx::new(self.as_raw_fd(), CString::default(), flags, STATX_MASK);
+            statx(op).await
+        }
+        #[cfg(target_os = "windows")]
+        {
+            self.inner.metadata().map(Metadata::from)
+        }
+    }
+
     /// Read some bytes at the specified offset from the file into the specified
     /// buffer, returning how many bytes were read.
     ///
@@ -130,13 +174,9 @@ impl File {
     ) -> BufResult<usize, T> {
         use crate::op::UpdateBufferLen;
 
-        let (fd, buffer) = buf_try!(self.attach(), buffer);
+        let ((fd, runtime), buffer) = buf_try!(self.attach_runtime(), buffer);
         let op = ReadAt::new(fd, pos, buffer);
-        RUNTIME
-            .with(|runtime| runtime.submit(op))
-            .await
-            .into_inner()
-            .update_buffer_len()
+        runtime.submit(op).await.into_inner().update_buffer_len()
     }
 
     /// Read the exact number of bytes required to fill `buffer`.
@@ -195,6 +235,9 @@ impl File {
     ///
     /// If successful, this function will return the total number of bytes read.
     ///
+    /// The buffer grows as needed, its capacity is doubled when it's filled, so the capacity
+    /// of the passed buffer could be the expected size of the file or zero.
+    ///
     /// [`read_at()`]: File::read_at
     #[cfg(feature = "runtime")]
     pub async fn read_to_end_at<
@@ -207,14 +250,15 @@ impl File {
         let mut total_read = 0;
         let mut read;
         loop {
+            // the full buffer has no room to read into, including the empty one
+            if buffer.len() == buffer.capacity() {
+                buffer.reserve(buffer.capacity().max(32));
+            }
             (read, buffer) = buf_try!(self.read_at(buffer, pos + total_read).await);
             if read == 0 {
                 break;
             } else {
                 total_read += read;
-                if buffer.len() == buffer.capacity() {
-                    buffer.reserve(32);
-                }
             }
         }
         (Ok(total_read), buffer)
@@ -244,29 +288,34 @@ impl File {
     /// written to this writer.
     #[cfg(feature = "runtime")]
     pub async fn write_at<T: IoBuf<'static>>(&self, buffer: T, pos: usize) -> BufResult<usize, T> {
-        let (fd, buffer) = buf_try!(self.attach(), buffer);
+        let ((fd, runtime), buffer) = buf_try!(self.attach_runtime(), buffer);
         let op = WriteAt::new(fd, pos, buffer);
-        RUNTIME
-            .with(|runtime| runtime.submit(op))
-            .await
-            .into_inner()
+        runtime.submit(op).await.into_inner()
     }
 
-    /// Attempts to write an entire buffer into this writer.
+    /// Attempts to write an entire buffer into this writer at `offset`.
     ///
-    /// This method will continuously call [`write_at`] until there is no more
-    /// data to be written. This method will not return until the entire
-    /// buffer has been successfully written or such an error occurs.
+    /// This method will continuously call [`write_at`] from the advanced offset until there is
+    /// no more data to be written. This method will not return until the entire buffer has
+    /// been successfully written or such an error occurs.
     ///
     /// If the buffer contains no data, this will never call [`write_at`].
     ///
+    /// # Errors
+    ///
+    /// Returns [`io::ErrorKind::WriteZero`] if [`write_at`] writes no bytes, the number of the
+    /// written bytes is unspecified in this case. Returns [`io::ErrorKind::InvalidInput`] if
+    /// `offset` doesn't fit into `usize`.
+    ///
     /// [`write_at`]: File::write_at
     #[cfg(feature = "runtime")]
     pub async fn write_all_at<T: IoBuf<'static>>(
         &self,
+        offset: u64,
         mut buffer: T,
-        pos: usize,
-    ) -> BufResult<usize, T> {
+    ) -> BufResult<(), T> {
+        let pos;
+        (pos, buffer) = buf_try!(usize_offset(offset), buffer);
         let buf_len = buffer.buf_len();
         let mut total_written = 0;
         let mut written;
@@ -276,16 +325,100 @@ impl File {
                     .await
                     .into_inner()
             );
+            if written == 0 {
+                return (Err(write_zero()), buffer);
+            }
             total_written += written;
         }
-        (Ok(total_written), buffer)
+        (Ok(()), buffer)
+    }
+
+    /// Like [`read_at`], except that it reads into a slice of buffers.
+    ///
+    /// The buffers are filled in order up to their capacity, the last one could be filled
+    /// partially. Their lengths are updated with the read bytes.
+    ///
+    /// ## Platform specific
+    ///
+    /// * Windows: only the first buffer with the spare capacity is read into.
+    ///
+    /// [`read_at`]: File::read_at
+    #[cfg(feature = "runtime")]
+    pub async fn read_vectored_at<T: IoBufMut<'static>>(
+        &self,
+        buffer: VectoredBufWrapper<'static, T>,
+        pos: usize,
+    ) -> BufResult<usize, VectoredBufWrapper<'static, T>> {
+        use crate::op::UpdateBufferLen;
+
+        let ((fd, runtime), buffer) = buf_try!(self.attach_runtime(), buffer);
+        let op = ReadVectoredAt::new(fd, pos, buffer);
+        runtime.submit(op).await.into_inner().update_buffer_len()
+    }
+
+    /// Like [`write_at`], except that it writes from a slice of buffers.
+    ///
+    /// A short write could end in the middle of a buffer, see
+    /// [`write_all_vectored_at`](File::write_all_vectored_at) to write them entirely.
+    ///
+    /// ## Platform specific
+    ///
+    /// * Windows: only the first non-empty buffer is written.
+    ///
+    /// [`write_at`]: File::write_at
+    #[cfg(feature = "runtime")]
+    pub async fn write_vectored_at<T: IoBuf<'static>>(
+        &self,
+        buffer: VectoredBufWrapper<'static, T>,
+        pos: usize,
+    ) -> BufResult<usize, VectoredBufWrapper<'static, T>> {
+        let ((fd, runtime), buffer) = buf_try!(self.attach_runtime(), buffer);
+        let op = WriteVectoredAt::new(fd, pos, buffer);
+        runtime.submit(op).await.into_inner()
+    }
+
+    /// Attempts to write all the buffers, continuously calling
+    /// [`write_vectored_at`](File::write_vectored_at) until they are written or an error occurs.
+    ///
+    /// The next write after a short one starts at the first unwritten byte, see
+    /// [`VectoredBufWrapper::advance`]. The wrapper is rewound before it's returned and could
+    /// be written again.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::ErrorKind::WriteZero`] if a write writes no bytes, the number of the
+    /// written bytes is unspecified in case of an error.
+    #[cfg(feature = "runtime")]
+    pub async fn write_all_vectored_at<T: IoBuf<'static>>(
+        &self,
+        mut buffer: VectoredBufWrapper<'static, T>,
+        pos: usize,
+    ) -> BufResult<usize, VectoredBufWrapper<'static, T>> {
+        let mut total_written = 0;
+        let res = loop {
+            if buffer.remaining() == 0 {
+                break Ok(total_written);
+            }
+            let res;
+            (res, buffer) = self.write_vectored_at(buffer, pos + total_written).await;
+            match res {
+                Ok(0) => break Err(write_zero()),
+                Ok(written) => {
+                    buffer.advance(written);
+                    total_written += written;
+                }
+                Err(e) => break Err(e),
+            }
+        };
+        buffer.rewind();
+        (res, buffer)
     }
 
     #[cfg(feature = "runtime")]
     async fn sync_impl(&self, datasync: bool) -> io::Result<()> {
-        let fd = self.attach()?;
+        let (fd, runtime) = self.attach_runtime()?;
         let op = Sync::new(fd, datasync);
-        RUNTIME.with(|runtime| runtime.submit(op)).await.0?;
+        runtime.submit(op).await.0?;
         Ok(())
     }
 
@@ -313,6 +446,185 @@ impl File {
     pub async fn sync_data(&self) -> io::Result<()> {
         self.sync_impl(true).await
     }
+
+    /// Flushes the written data of the range of `len` bytes starting from
+    /// `offset` to disk.
+    ///
+    /// It initiates and waits for write-out of the dirty pages in the range.
+    /// Unlike [`sync_data`] it doesn't flush the file metadata, so an extended
+    /// file size may be lost after a crash. Ranges longer than `u32::MAX` are
+    /// synced till the end of file. See [`SyncFileRange`] for the platform
+    /// specific details.
+    ///
+    /// [`sync_data`]: File::sync_data
+    /// [`SyncFileRange`]: crate::op::SyncFileRange
+    #[cfg(feature = "runtime")]
+    pub async fn sync_range(&self, offset: u64, len: u64) -> io::Result<()> {
+        let (fd, runtime) = self.attach_runtime()?;
+        // zero length means the range till the end of file
+        let nbytes = u32::try_from(len).unwrap_or(0);
+        let flags =
+            SyncRangeFlags::WAIT_BEFORE | SyncRangeFlags::WRITE | SyncRangeFlags::WAIT_AFTER;
+        let op = SyncFileRange::new(fd, offset, nbytes, flags);
+        runtime.submit(op).await.0?;
+        Ok(())
+    }
+
+    /// Announces the expected access pattern for the range of `len` bytes
+    /// starting from `offset`.
+    ///
+    /// Zero `len` means the range till the end of file. See [`Fadvise`] for
+    /// the platform specific details.
+    ///
+    /// [`Fadvise`]: crate::op::Fadvise
+    #[cfg(all(feature = "runtime", unix))]
+    pub async fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        let (fd, runtime) = self.attach_runtime()?;
+        let op = Fadvise::new(fd, offset, len, advice);
+        runtime.submit(op).await.0?;
+        Ok(())
+    }
+
+    /// Reads the value of the extended attribute `name` into `buffer`,
+    /// returning the length of the value.
+    ///
+    /// # Errors
+    ///
+    /// If `buffer` is too small for the value, the error with `ERANGE` code is
+    /// returned, so the caller could retry with a larger buffer. See [`GetXattr`]
+    /// for the platform specific details.
+    #[cfg(all(feature = "runtime", unix))]
+    pub async fn get_xattr<T: IoBufMut<'static>>(
+        &self,
+        name: &str,
+        buffer: T,
+    ) -> BufResult<usize, T> {
+        use crate::op::UpdateBufferLen;
+
+        let ((fd, runtime), buffer) = buf_try!(self.attach_runtime(), buffer);
+        let (name, buffer) = buf_try!(CString::new(name).map_err(io::Error::from), buffer);
+        let op = GetXattr::new(fd, name, buffer);
+        runtime.submit(op).await.into_inner().update_buffer_len()
+    }
+
+    /// Sets the extended attribute `name` to the value from `value`.
+    ///
+    /// The attribute is created or replaced. See [`SetXattr`] for the platform
+    /// specific details.
+    #[cfg(all(feature = "runtime", unix))]
+    pub async fn set_xattr<T: IoBuf<'static>>(&self, name: &str, value: T) -> BufResult<(), T> {
+        let ((fd, runtime), value) = buf_try!(self.attach_runtime(), value);
+        let (name, value) = buf_try!(CString::new(name).map_err(io::Error::from), value);
+        let op = SetXattr::new(fd, name, value, 0);
+        let (res, value) = runtime.submit(op).await.into_inner();
+        (res.map(|_| ()), value)
+    }
+
+    /// Truncates or extends the underlying file, updating the size of this
+    /// file to become `len`.
+    ///
+    /// If `len` is less than the current file size, then the file will be
+    /// shrunk. If it is greater, the file will be extended and the extended
+    /// part will read as zeros.
+    #[cfg(feature = "runtime")]
+    pub async fn set_len(&self, len: u64) -> io::Result<()> {
+        let (fd, runtime) = self.attach_runtime()?;
+        let op = Truncate::new(fd, len);
+        runtime.submit(op).await.0?;
+        Ok(())
+    }
+
+    /// Allocates disk space for the range of `len` bytes starting from
+    /// `offset`.
+    ///
+    /// The file size is extended if the range is past the end of file.
+    /// Subsequent writes into the range don't fail because of lack of disk
+    /// space.
+    #[cfg(feature = "runtime")]
+    pub async fn allocate(&self, offset: u64, len: u64) -> io::Result<()> {
+        self.allocate_with_mode(offset, len, AllocateMode::empty())
+            .await
+    }
+
+    /// Manipulates the allocated disk space for the range of `len` bytes
+    /// starting from `offset` according to `mode`.
+    ///
+    /// See [`Fallocate`] for the platform specific details.
+    ///
+    /// [`Fallocate`]: crate::op::Fallocate
+    #[cfg(feature = "runtime")]
+    pub async fn allocate_with_mode(
+        &self,
+        offset: u64,
+        len: u64,
+        mode: AllocateMode,
+    ) -> io::Result<()> {
+        let (fd, runtime) = self.attach_runtime()?;
+        let op = Fallocate::new(fd, offset, len, mode);
+        runtime.submit(op).await.0?;
+        Ok(())
+    }
 }
 
 impl_raw_fd!(File, inner, attacher);
+
+/// Copy `len` bytes from `src` at `src_offset` to `dst` at `dst_offset`,
+/// returning how many bytes were copied.
+///
+/// The copy happens inside the kernel without transferring data to user space.
+/// It could be short, so the caller should check the returned length.
+///
+/// # Errors
+///
+/// Errors are returned unchanged, so the caller could fall back to a read and
+/// write loop on `EXDEV` or [`io::ErrorKind::Unsupported`].
+///
+/// At most 16 MiB are copied by one call.
+///
+/// ## Platform specific
+///
+/// * io-uring: there is no opcode, the blocking `copy_file_range` syscall runs on the pool of
+///   [`spawn_blocking`](crate::task::spawn_blocking).
+/// * kqueue: `copy_file_range` on FreeBSD, other platforms fail with
+///   [`io::ErrorKind::Unsupported`].
+#[cfg(all(feature = "runtime", unix))]
+pub async fn copy_range(
+    src: &File,
+    src_offset: u64,
+    dst: &File,
+    dst_offset: u64,
+    len: usize,
+) -> io::Result<usize> {
+    #[cfg(target_os = "linux")]
+    {
+        // the duplicates stay open till the syscall returns if the future is dropped
+        let (src, dst) = (src.inner.try_clone()?, dst.inner.try_clone()?);
+        crate::task::run_blocking(move || {
+            crate::driver::copy_file_range(
+                src.as_raw_fd(),
+                src_offset,
+                dst.as_raw_fd(),
+                dst_offset,
+                len,
+            )
+        })
+        .await
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let (src, runtime) = src.attach_runtime()?;
+        let op = CopyFileRange::new(src, src_offset, dst.attach()?, dst_offset, len);
+        runtime.submit(op).await.0
+    }
+}
+
+#[cfg(feature = "runtime")]
+fn write_zero() -> io::Error {
+    io::Error::new(io::ErrorKind::WriteZero, "failed to write whole buffer")
+}
+
+#[cfg(feature = "runtime")]
+fn usize_offset(offset: u64) -> io::Result<usize> {
+    usize::try_from(offset)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "offset overflows usize"))
+}
@@ -0,0 +1,183 @@
+#[cfg(unix)]
+use std::time::Duration;
+use std::{fs::Permissions, io, path::Path, time::SystemTime};
+
+#[cfg(unix)]
+use crate::{op::Statx, task::RUNTIME};
+
+/// The type of a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileType {
+    /// A regular file.
+    File,
+    /// A directory.
+    Dir,
+    /// A symbolic link.
+    Symlink,
+    /// Any other file type, like a socket or a device.
+    Other,
+}
+
+/// Metadata information about a file.
+///
+/// It is returned by [`metadata`] and [`File::metadata_async`].
+///
+/// [`File::metadata_async`]: crate::fs::File::metadata_async
+#[derive(Debug, Clone)]
+pub struct Metadata {
+    len: u64,
+    modified: Option<SystemTime>,
+    file_type: FileType,
+    permissions: Permissions,
+}
+
+impl Metadata {
+    /// Returns the size of the file, in bytes.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns `true` if the file size is zero.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the last modification time.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::ErrorKind::Unsupported`] if the filesystem doesn't provide
+    /// the modification time.
+    pub fn modified(&self) -> io::Result<SystemTime> {
+        self.modified.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                "modification time is not available",
+            )
+        })
+    }
+
+    /// Returns the file type.
+    pub fn file_type(&self) -> FileType {
+        self.file_type
+    }
+
+    /// Returns `true` if this metadata is for a regular file.
+    pub fn is_file(&self) -> bool {
+        self.file_type == FileType::File
+    }
+
+    /// Returns `true` if this metadata is for a directory.
+    pub fn is_dir(&self) -> bool {
+        self.file_type == FileType::Dir
+    }
+
+    /// Returns `true` if this metadata is for a symbolic link.
+    pub fn is_symlink(&self) -> bool {
+        self.file_type == FileType::Symlink
+    }
+
+    /// Returns the permissions of the file.
+    pub fn permissions(&self) -> Permissions {
+        self.permissions.clone()
+    }
+
+    #[cfg(unix)]
+    // mode_t is u16 on some platforms
+    #[allow(clippy::useless_conversion)]
+    fn from_mode(len: u64, modified: Option<SystemTime>, mode: libc::mode_t) -> Self {
+        use std::os::unix::fs::PermissionsExt;
+
+        let file_type = match mode & libc::S_IFMT {
+            libc::S_IFREG => FileType::File,
+            libc::S_IFDIR => FileType::Dir,
+            libc::S_IFLNK => FileType::Symlink,
+            _ => FileType::Other,
+        };
+        Self {
+            len,
+            modified,
+            file_type,
+            permissions: Permissions::from_mode(u32::from(mode)),
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn from_statx(statx: &libc::statx) -> Self {
+        let modified = (statx.stx_mask & libc::STATX_MTIME != 0)
+            .then(|| system_time(statx.stx_mtime.tv_sec, statx.stx_mtime.tv_nsec));
+        Self::from_mode(statx.stx_size, modified, statx.stx_mode.into())
+    }
+
+    #[cfg(all(unix, not(target_os = "linux")))]
+    fn from_stat(stat: &libc::stat) -> Self {
+        let modified = system_time(stat.st_mtime as _, stat.st_mtime_nsec as _);
+        Self::from_mode(stat.st_size as _, Some(modified), stat.st_mode)
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl From<std::fs::Metadata> for Metadata {
+    fn from(metadata: std::fs::Metadata) -> Self {
+        let file_type = metadata.file_type();
+        let file_type = if file_type.is_symlink() {
+            FileType::Symlink
+        } else if file_type.is_dir() {
+            FileType::Dir
+        } else if file_type.is_file() {
+            FileType::File
+        } else {
+            FileType::Other
+        };
+        Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            file_type,
+            permissions: metadata.permissions(),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn system_time(sec: i64, nsec: u32) -> SystemTime {
+    if sec >= 0 {
+        SystemTime::UNIX_EPOCH + Duration::new(sec as u64, nsec)
+    } else {
+        SystemTime::UNIX_EPOCH - Duration::from_secs(sec.unsigned_abs()) + Duration::new(0, nsec)
+    }
+}
+
+#[cfg(unix)]
+pub(crate) async fn statx(op: Statx) -> io::Result<Metadata> {
+    let (res, op) = RUNTIME.with(|runtime| runtime.submit(op)).await;
+    res?;
+    #[cfg(target_os = "linux")]
+    let metadata = Metadata::from_statx(op.statx());
+    #[cfg(not(target_os = "linux"))]
+    let metadata = Metadata::from_stat(op.stat());
+    Ok(metadata)
+}
+
+/// Queries the metadata about a file, following symlinks.
+///
+/// ## Platform specific
+///
+/// * io-uring: `IORING_OP_STATX` operation.
+/// * kqueue: it is synchronized `fstatat` syscall.
+/// * IOCP: it is synchronized [`std::fs::metadata`] call.
+pub async fn metadata(path: impl AsRef<Path>) -> io::Result<Metadata> {
+    #[cfg(unix)]
+    {
+        let path = crate::fs::dir::path_to_cstring(path.as_ref())?;
+        statx(Statx::new(libc::AT_FDCWD, path, 0, STATX_MASK)).await
+    }
+    #[cfg(target_os = "windows")]
+    {
+        std::fs::metadata(path).map(Metadata::from)
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) const STATX_MASK: u32 = libc::STATX_BASIC_STATS;
+#[cfg(all(unix, not(target_os = "linux")))]
+pub(crate) const STATX_MASK: u32 = 0;
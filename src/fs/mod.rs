@@ -1,7 +1,17 @@
 //! Filesystem manipulation operations.
 
+#[cfg(feature = "runtime")]
+mod dir;
+#[cfg(feature = "runtime")]
+pub use dir::*;
+
 mod file;
 pub use file::*;
 
+#[cfg(feature = "runtime")]
+mod metadata;
+#[cfg(feature = "runtime")]
+pub use metadata::*;
+
 mod open_options;
 pub use open_options::*;
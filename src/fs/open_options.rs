@@ -1,6 +1,8 @@
 use std::{fs::OpenOptions as StdOpenOptions, io, path::Path};
 
 use crate::fs::File;
+#[cfg(feature = "runtime")]
+use crate::{driver::FromRawFd, op::OpenAt, task::RUNTIME};
 
 /// Options and flags which can be used to configure how a file is opened.
 ///
@@ -38,15 +40,53 @@ use crate::fs::File;
 ///     .open("foo.txt")
 ///     .unwrap();
 /// ```
+///
+/// Creating a new file through the driver:
+///
+/// ```no_run
+/// use completeio::fs::OpenOptions;
+///
+/// completeio::task::block_on(async {
+///     let file = OpenOptions::new()
+///         .write(true)
+///         .create_new(true)
+///         .open_async("foo.txt")
+///         .await
+///         .unwrap();
+/// })
+/// ```
 #[derive(Debug, Clone)]
-pub struct OpenOptions(pub(crate) StdOpenOptions);
+pub struct OpenOptions {
+    read: bool,
+    write: bool,
+    append: bool,
+    truncate: bool,
+    create: bool,
+    create_new: bool,
+    #[cfg(unix)]
+    custom_flags: i32,
+    #[cfg(windows)]
+    custom_flags: u32,
+    #[cfg(unix)]
+    mode: u32,
+}
 
 impl OpenOptions {
     /// Creates a blank new set of options ready for configuration.
     #[allow(clippy::new_without_default)]
     #[must_use]
     pub fn new() -> Self {
-        Self(StdOpenOptions::new())
+        Self {
+            read: false,
+            write: false,
+            append: false,
+            truncate: false,
+            create: false,
+            create_new: false,
+            custom_flags: 0,
+            #[cfg(unix)]
+            mode: 0o666,
+        }
     }
 
     /// Sets the option for read access.
@@ -54,7 +94,7 @@ impl OpenOptions {
     /// This option, when true, will indicate that the file should be
     /// `read`-able if opened.
     pub fn read(mut self, read: bool) -> Self {
-        self.0.read(read);
+        self.read = read;
         self
     }
 
@@ -63,7 +103,18 @@ impl OpenOptions {
     /// This option, when true, will indicate that the file should be
     /// `write`-able if opened.
     pub fn write(mut self, write: bool) -> Self {
-        self.0.write(write);
+        self.write = write;
+        self
+    }
+
+    /// Sets the option for the append mode.
+    ///
+    /// The writes of [`File`] are positional, and the platforms differ in whether the offset
+    /// of a write to a file in the append mode is ignored. So opening a file with this option
+    /// fails with [`io::ErrorKind::Unsupported`], the end of the file could be written at the
+    /// offset of its length instead.
+    pub fn append(mut self, append: bool) -> Self {
+        self.append = append;
         self
     }
 
@@ -74,7 +125,7 @@ impl OpenOptions {
     ///
     /// The file must be opened with write access for truncate to work.
     pub fn truncate(mut self, truncate: bool) -> Self {
-        self.0.truncate(truncate);
+        self.truncate = truncate;
         self
     }
 
@@ -83,7 +134,7 @@ impl OpenOptions {
     /// In order for the file to be created, [`OpenOptions::write`] access must
     /// be used.
     pub fn create(mut self, create: bool) -> Self {
-        self.0.create(create);
+        self.create = create;
         self
     }
 
@@ -106,7 +157,33 @@ impl OpenOptions {
     /// [`.create()`]: OpenOptions::create
     /// [`.truncate()`]: OpenOptions::truncate
     pub fn create_new(mut self, create_new: bool) -> Self {
-        self.0.create_new(create_new);
+        self.create_new = create_new;
+        self
+    }
+
+    /// Pass custom `O_*` flags to the `open` call, the access mode bits are ignored.
+    ///
+    /// `O_CLOEXEC` is always set.
+    #[cfg(unix)]
+    pub fn custom_flags(mut self, flags: i32) -> Self {
+        self.custom_flags = flags;
+        self
+    }
+
+    /// Pass custom `FILE_FLAG_*` flags and `FILE_ATTRIBUTE_*` attributes to `CreateFileW`.
+    ///
+    /// `FILE_FLAG_OVERLAPPED` is always set, so the file can be used with IOCP.
+    #[cfg(windows)]
+    pub fn custom_flags(mut self, flags: u32) -> Self {
+        self.custom_flags = flags;
+        self
+    }
+
+    /// Sets the permission bits of the created file, `0o666` by default. The bits are masked
+    /// by the `umask` of the process.
+    #[cfg(unix)]
+    pub fn mode(mut self, mode: u32) -> Self {
+        self.mode = mode;
         self
     }
 
@@ -116,4 +193,88 @@ impl OpenOptions {
     pub fn open(self, path: impl AsRef<Path>) -> io::Result<File> {
         File::with_options(path, self)
     }
+
+    /// Opens a file at `path` with the options specified by `self` through the driver.
+    ///
+    /// Unlike [`OpenOptions::open`] it doesn't block the thread on io-uring.
+    #[cfg(feature = "runtime")]
+    pub async fn open_async(self, path: impl AsRef<Path>) -> io::Result<File> {
+        let op = self.open_at(path.as_ref())?;
+        let fd = RUNTIME.with(|runtime| runtime.submit(op)).await.0?;
+        // SAFETY: fd is a newly opened file
+        Ok(unsafe { File::from_raw_fd(fd as _) })
+    }
+
+    fn check_append(&self) -> io::Result<()> {
+        if self.append {
+            Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "the append mode is not supported by the positional writes",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn to_std(&self) -> io::Result<StdOpenOptions> {
+        self.check_append()?;
+        let mut options = StdOpenOptions::new();
+        options
+            .read(self.read)
+            .write(self.write)
+            .truncate(self.truncate)
+            .create(self.create)
+            .create_new(self.create_new);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+
+            options.custom_flags(self.custom_flags).mode(self.mode);
+        }
+        #[cfg(windows)]
+        {
+            use std::os::windows::fs::OpenOptionsExt;
+
+            use windows_sys::Win32::Storage::FileSystem::FILE_FLAG_OVERLAPPED;
+
+            options.custom_flags(self.custom_flags | FILE_FLAG_OVERLAPPED);
+        }
+        Ok(options)
+    }
+
+    #[cfg(all(feature = "runtime", unix))]
+    fn open_at(&self, path: &Path) -> io::Result<OpenAt> {
+        self.check_append()?;
+        let invalid = || io::Error::from_raw_os_error(libc::EINVAL);
+        let access = match (self.read, self.write) {
+            (true, false) => libc::O_RDONLY,
+            (false, true) => libc::O_WRONLY,
+            (true, true) => libc::O_RDWR,
+            (false, false) => return Err(invalid()),
+        };
+        // the same validation as `std::fs::OpenOptions`
+        if !self.write && (self.truncate || self.create || self.create_new) {
+            return Err(invalid());
+        }
+        let creation = match (self.create, self.truncate, self.create_new) {
+            (_, _, true) => libc::O_CREAT | libc::O_EXCL,
+            (true, true, false) => libc::O_CREAT | libc::O_TRUNC,
+            (true, false, false) => libc::O_CREAT,
+            (false, true, false) => libc::O_TRUNC,
+            (false, false, false) => 0,
+        };
+        let flags = libc::O_CLOEXEC | access | creation | (self.custom_flags & !libc::O_ACCMODE);
+        let path = crate::fs::dir::path_to_cstring(path)?;
+        Ok(OpenAt::new(
+            libc::AT_FDCWD,
+            path,
+            flags,
+            self.mode as libc::mode_t,
+        ))
+    }
+
+    #[cfg(all(feature = "runtime", windows))]
+    fn open_at(&self, path: &Path) -> io::Result<OpenAt> {
+        Ok(OpenAt::new(path, self.to_std()?))
+    }
 }
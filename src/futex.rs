@@ -0,0 +1,67 @@
+//! Futex waits and wakes parked in the driver.
+//!
+//! Available since Linux 6.7, older kernels return [`io::ErrorKind::Unsupported`].
+
+use std::{
+    io,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use crate::op::{FutexWait, FutexWake};
+
+/// Waits till `futex` is woken if it equals `val`.
+///
+/// Returns immediately if the value of `futex` differs. Spurious wakeups are possible, so
+/// the caller should check the value again.
+///
+/// Dropping the future cancels the wait, the cancellation completes in the background after
+/// the borrow of `futex` ends. The kernel never writes the futex, it only compares the value
+/// when the wait starts.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::atomic::{AtomicU32, Ordering};
+///
+/// use completeio::futex::wait_async;
+///
+/// static READY: AtomicU32 = AtomicU32::new(0);
+///
+/// completeio::task::block_on(async {
+///     while READY.load(Ordering::Acquire) == 0 {
+///         wait_async(&READY, 0).await.unwrap();
+///     }
+/// })
+/// ```
+pub async fn wait_async(futex: &AtomicU32, val: u32) -> io::Result<()> {
+    if futex.load(Ordering::Relaxed) != val {
+        return Ok(());
+    }
+    // SAFETY: the operation could outlive the borrow if the future is dropped, the submit task
+    // is detached. The kernel doesn't write the futex, the stale address is only read when the
+    // wait starts and hashed to find the waiters, so the memory of the process isn't touched.
+    let op = unsafe { FutexWait::new(futex, val, u32::MAX) };
+    let (res, _) = crate::task::RUNTIME
+        .with(|runtime| runtime.submit(op))
+        .await;
+    match res {
+        Ok(_) => Ok(()),
+        // the value is changed before the wait
+        Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Wakes up to `count` waiters of `futex`, returns the number of woken waiters.
+///
+/// The wake could still be issued after the future is dropped, the waiters see it as a
+/// spurious wakeup.
+pub async fn wake_async(futex: &AtomicU32, count: u32) -> io::Result<usize> {
+    // SAFETY: the wake only hashes the address, the stale one after the future is dropped wakes
+    // the unrelated waiters spuriously at worst
+    let op = unsafe { FutexWake::new(futex, count, u32::MAX) };
+    let (res, _) = crate::task::RUNTIME
+        .with(|runtime| runtime.submit(op))
+        .await;
+    res
+}
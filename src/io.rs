@@ -0,0 +1,116 @@
+//! Asynchronous IO helpers operating on raw file descriptors.
+
+use std::io;
+
+use crate::{
+    driver::AsRawFd,
+    op::{PollReadable, PollWritable, Splice, Tee},
+    task::RUNTIME,
+};
+
+/// The readiness to wait for with [`readiness`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interest {
+    /// The fd is readable.
+    Readable,
+    /// The fd is writable.
+    Writable,
+}
+
+/// Wait until `fd` is ready for the `interest`.
+///
+/// It drives third-party nonblocking code: wait for the readiness and retry
+/// the call that would block. The readiness could be spurious, so the call
+/// could still return `WouldBlock`.
+///
+/// ```
+/// use std::net::UdpSocket;
+///
+/// use completeio::io::{readiness, Interest};
+///
+/// completeio::task::block_on(async {
+///     let rx = UdpSocket::bind("127.0.0.1:0").unwrap();
+///     rx.set_nonblocking(true).unwrap();
+///     let tx = UdpSocket::bind("127.0.0.1:0").unwrap();
+///     tx.send_to(b"hello", rx.local_addr().unwrap()).unwrap();
+///
+///     readiness(&rx, Interest::Readable).await.unwrap();
+///
+///     let mut buf = [0; 5];
+///     assert_eq!(rx.recv(&mut buf).unwrap(), 5);
+///     assert_eq!(&buf, b"hello");
+/// });
+/// ```
+///
+/// ## Platform specific
+///
+/// * io-uring: `IORING_OP_POLL_ADD` operation.
+/// * kqueue: read or write filter event.
+pub async fn readiness(fd: &impl AsRawFd, interest: Interest) -> io::Result<()> {
+    let fd = RUNTIME.with(|runtime| runtime.attach(fd.as_raw_fd()))?;
+    match interest {
+        Interest::Readable => {
+            let op = PollReadable::new(fd);
+            RUNTIME.with(|runtime| runtime.submit(op)).await.0?;
+        }
+        Interest::Writable => {
+            let op = PollWritable::new(fd);
+            RUNTIME.with(|runtime| runtime.submit(op)).await.0?;
+        }
+    }
+    Ok(())
+}
+
+/// Move up to `len` bytes from `src` to `dst`, returning the number of bytes
+/// moved.
+///
+/// On Linux one of the file descriptors should refer to a pipe. Other unix
+/// platforms emulate splice with a read followed by a write.
+///
+/// It is not an error if fewer than `len` bytes were moved. `0` means that
+/// `src` reached end of file.
+///
+/// ```
+/// use std::io::{Read, Write};
+///
+/// completeio::task::block_on(async {
+///     let (src, mut src_tx) = std::io::pipe().unwrap();
+///     let (mut dst_rx, dst) = std::io::pipe().unwrap();
+///
+///     src_tx.write_all(b"hello").unwrap();
+///     let moved = completeio::io::splice(&src, &dst, 1024).await.unwrap();
+///     assert_eq!(moved, 5);
+///
+///     let mut buf = [0; 5];
+///     dst_rx.read_exact(&mut buf).unwrap();
+///     assert_eq!(&buf, b"hello");
+/// });
+/// ```
+pub async fn splice(src: &impl AsRawFd, dst: &impl AsRawFd, len: usize) -> io::Result<usize> {
+    let (fd_in, fd_out) = RUNTIME.with(|runtime| {
+        let fd_in = runtime.attach(src.as_raw_fd())?;
+        let fd_out = runtime.attach(dst.as_raw_fd())?;
+        io::Result::Ok((fd_in, fd_out))
+    })?;
+    let len = u32::try_from(len).unwrap_or(u32::MAX);
+    let op = Splice::new(fd_in, None, fd_out, None, len, 0);
+    RUNTIME.with(|runtime| runtime.submit(op)).await.0
+}
+
+/// Duplicate up to `len` bytes from the `src` pipe to the `dst` pipe without
+/// consuming them, returning the number of bytes duplicated.
+///
+/// ## Platform specific
+///
+/// * Linux: `tee(2)` semantics.
+/// * other unix platforms: fails with [`io::ErrorKind::Unsupported`].
+pub async fn tee(src: &impl AsRawFd, dst: &impl AsRawFd, len: usize) -> io::Result<usize> {
+    let (fd_in, fd_out) = RUNTIME.with(|runtime| {
+        let fd_in = runtime.attach(src.as_raw_fd())?;
+        let fd_out = runtime.attach(dst.as_raw_fd())?;
+        io::Result::Ok((fd_in, fd_out))
+    })?;
+    let len = u32::try_from(len).unwrap_or(u32::MAX);
+    let op = Tee::new(fd_in, fd_out, len, 0);
+    RUNTIME.with(|runtime| runtime.submit(op)).await.0
+}
@@ -12,11 +12,17 @@ pub mod fs;
 pub mod net;
 pub mod op;
 
+pub use driver::CancelledError;
+
 #[cfg(target_os = "windows")]
 pub mod named_pipe;
 
 #[cfg(feature = "event")]
 pub mod event;
+#[cfg(all(feature = "runtime", target_os = "linux"))]
+pub mod futex;
+#[cfg(all(feature = "runtime", unix))]
+pub mod io;
 #[cfg(feature = "runtime")]
 mod key;
 #[cfg(feature = "runtime")]
@@ -25,6 +31,9 @@ pub(crate) use key::Key;
 mod attacher;
 #[cfg(feature = "runtime")]
 pub(crate) use attacher::Attacher;
+pub mod pipe;
+#[cfg(all(feature = "runtime", unix))]
+pub mod process;
 #[cfg(feature = "signal")]
 pub mod signal;
 #[cfg(feature = "runtime")]
@@ -79,7 +88,14 @@ macro_rules! impl_raw_fd {
             }
         }
         impl crate::driver::IntoRawFd for $t {
-            fn into_raw_fd(self) -> crate::driver::RawFd {
+            #[allow(unused_mut)]
+            fn into_raw_fd(mut self) -> crate::driver::RawFd {
+                $(
+                    // the fd could be moved to another runtime, it stays attached if the
+                    // driver has pending operations on it
+                    #[cfg(feature = "runtime")]
+                    let _ = self.$attacher.reset(&self.$inner);
+                )?
                 self.$inner.into_raw_fd()
             }
         }
@@ -122,7 +138,7 @@ macro_rules! syscall {
             Ok(res)
         }
     }};
-    // The below branch are used by kqueue driver.
+    // The below branch are used by kqueue and epoll drivers.
     (maybe_block $fn: ident ( $($arg: expr),* $(,)* )) => {
         match $crate::syscall!( $fn ( $($arg, )* )) {
             Err(e) if e.kind() == ::std::io::ErrorKind::WouldBlock || e.raw_os_error() == Some(::libc::EINPROGRESS)
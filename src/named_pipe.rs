@@ -29,7 +29,7 @@ use windows_sys::Win32::{
 };
 
 #[cfg(feature = "runtime")]
-use crate::{buf::*, op::ConnectNamedPipe, task::RUNTIME, *};
+use crate::{buf::*, op::ConnectNamedPipe, *};
 use crate::{
     driver::{AsRawFd, FromRawFd, RawFd},
     fs::File,
@@ -174,9 +174,9 @@ impl NamedPipeServer {
     /// ```
     #[cfg(feature = "runtime")]
     pub async fn connect(&self) -> io::Result<()> {
-        let fd = self.handle.attach()?;
+        let (fd, runtime) = self.handle.attach_runtime()?;
         let op = ConnectNamedPipe::new(fd);
-        RUNTIME.with(|runtime| runtime.submit(op)).await.0?;
+        runtime.submit(op).await.0?;
         Ok(())
     }
 
@@ -233,7 +233,8 @@ impl NamedPipeServer {
     /// Write all bytes into the pipe.
     #[cfg(feature = "runtime")]
     pub async fn write_all<T: IoBuf<'static>>(&self, buffer: T) -> BufResult<usize, T> {
-        self.handle.write_all_at(buffer, 0).await
+        let (res, buffer) = self.handle.write_all_at(0, buffer).await;
+        (res.map(|()| buffer.buf_len()), buffer)
     }
 }
 
@@ -341,7 +342,8 @@ impl NamedPipeClient {
     /// Write all bytes into the pipe.
     #[cfg(feature = "runtime")]
     pub async fn write_all<T: IoBuf<'static>>(&self, buffer: T) -> BufResult<usize, T> {
-        self.handle.write_all_at(buffer, 0).await
+        let (res, buffer) = self.handle.write_all_at(0, buffer).await;
+        (res.map(|()| buffer.buf_len()), buffer)
     }
 }
 
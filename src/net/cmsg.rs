@@ -0,0 +1,137 @@
+use std::{
+    mem::size_of,
+    os::fd::{AsRawFd, BorrowedFd, RawFd},
+};
+
+// SAFETY: CMSG_LEN is a pure computation
+const HEADER_LEN: usize = unsafe { libc::CMSG_LEN(0) } as usize;
+
+/// Returns the number of bytes a control message with `data_len` bytes of
+/// data occupies in a control buffer, like `CMSG_SPACE` does.
+///
+/// # Panics
+///
+/// Panics if `data_len` doesn't fit into `u32`.
+pub const fn cmsg_space(data_len: usize) -> usize {
+    assert!(data_len <= u32::MAX as usize, "control message is too long");
+    // SAFETY: CMSG_SPACE is a pure computation
+    unsafe { libc::CMSG_SPACE(data_len as _) as usize }
+}
+
+/// Appends a `SCM_RIGHTS` control message passing `fds` to `control`.
+///
+/// The file descriptors should stay open until the message is sent.
+pub fn push_scm_rights(control: &mut Vec<u8>, fds: &[BorrowedFd<'_>]) {
+    let data_len = fds.len() * size_of::<RawFd>();
+    let start = control.len();
+    control.resize(start + cmsg_space(data_len), 0);
+
+    let mut header: libc::cmsghdr = unsafe { std::mem::zeroed() };
+    header.cmsg_len = (HEADER_LEN + data_len) as _;
+    header.cmsg_level = libc::SOL_SOCKET;
+    header.cmsg_type = libc::SCM_RIGHTS;
+    // SAFETY: the buffer is resized to fit the header and the data
+    unsafe {
+        let ptr = control.as_mut_ptr().add(start);
+        std::ptr::write_unaligned(ptr as *mut libc::cmsghdr, header);
+        let data = ptr.add(HEADER_LEN) as *mut RawFd;
+        for (i, fd) in fds.iter().enumerate() {
+            std::ptr::write_unaligned(data.add(i), fd.as_raw_fd());
+        }
+    }
+}
+
+/// A control message received along with the data.
+#[derive(Debug, Clone, Copy)]
+pub struct CMsg<'a> {
+    level: i32,
+    ty: i32,
+    data: &'a [u8],
+}
+
+impl<'a> CMsg<'a> {
+    /// Returns the originating protocol, like `SOL_SOCKET`.
+    pub fn level(&self) -> i32 {
+        self.level
+    }
+
+    /// Returns the protocol specific type, like `SCM_RIGHTS`.
+    pub fn ty(&self) -> i32 {
+        self.ty
+    }
+
+    /// Returns the message data.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Returns the passed file descriptors if it is a `SCM_RIGHTS` message.
+    pub fn scm_rights(&self) -> Option<ScmRights<'a>> {
+        (self.level == libc::SOL_SOCKET && self.ty == libc::SCM_RIGHTS)
+            .then_some(ScmRights { data: self.data })
+    }
+}
+
+/// An iterator over file descriptors of a `SCM_RIGHTS` control message.
+///
+/// The received file descriptors are owned by the caller and should be
+/// closed, e.g. by converting them into [`OwnedFd`](std::os::fd::OwnedFd).
+#[derive(Debug, Clone)]
+pub struct ScmRights<'a> {
+    data: &'a [u8],
+}
+
+impl Iterator for ScmRights<'_> {
+    type Item = RawFd;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.len() < size_of::<RawFd>() {
+            return None;
+        }
+        let (fd, rest) = self.data.split_at(size_of::<RawFd>());
+        self.data = rest;
+        // SAFETY: the slice has enough bytes
+        Some(unsafe { std::ptr::read_unaligned(fd.as_ptr() as *const RawFd) })
+    }
+}
+
+/// An iterator over control messages in a received control buffer.
+#[derive(Debug, Clone)]
+pub struct CMsgIter<'a> {
+    control: &'a [u8],
+}
+
+impl<'a> CMsgIter<'a> {
+    /// Create [`CMsgIter`] over the received part of a control buffer.
+    pub fn new(control: &'a [u8]) -> Self {
+        Self { control }
+    }
+}
+
+impl<'a> Iterator for CMsgIter<'a> {
+    type Item = CMsg<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.control.len() < size_of::<libc::cmsghdr>() {
+            return None;
+        }
+        // SAFETY: the slice has enough bytes for the header
+        let header =
+            unsafe { std::ptr::read_unaligned(self.control.as_ptr() as *const libc::cmsghdr) };
+        let len = header.cmsg_len as usize;
+        if len < HEADER_LEN || len > self.control.len() {
+            // malformed or truncated message
+            self.control = &[];
+            return None;
+        }
+        let data = &self.control[HEADER_LEN..len];
+        // the next header is aligned
+        let next = cmsg_space(len - HEADER_LEN).min(self.control.len());
+        self.control = &self.control[next..];
+        Some(CMsg {
+            level: header.cmsg_level,
+            ty: header.cmsg_type,
+            data,
+        })
+    }
+}
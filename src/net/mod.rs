@@ -1,24 +1,39 @@
 //! Network related.
 //!
-//! Currently, TCP/UDP/Unix socket are implemented.
+//! Currently, TCP/UDP/Unix and raw IP sockets are implemented.
 
+#[cfg(unix)]
+mod cmsg;
+mod options;
+mod raw;
+mod resolve;
 mod socket;
+mod split;
 mod tcp;
 mod udp;
 mod unix;
 
+#[cfg(feature = "runtime")]
+use std::future::Future;
 use std::{
-    future::Future,
     io,
     net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6, ToSocketAddrs},
 };
 
+#[cfg(unix)]
+pub use cmsg::*;
+pub use options::*;
+pub use raw::*;
+#[cfg(feature = "runtime")]
+pub use resolve::lookup_host;
 pub(crate) use socket::*;
 use socket2::SockAddr;
+pub use split::*;
 pub use tcp::*;
 pub use udp::*;
 pub use unix::*;
 
+#[cfg(feature = "runtime")]
 use crate::BufResult;
 
 /// A trait for objects which can be converted or resolved to one or more
@@ -31,11 +46,20 @@ pub trait ToSockAddrs {
 
     /// See [`ToSocketAddrs::to_socket_addrs`].
     fn to_sock_addrs(&self) -> io::Result<Self::Iter>;
+
+    /// Returns the `host:port` string the async operations resolve with `lookup_host` instead
+    /// of [`to_sock_addrs`](ToSockAddrs::to_sock_addrs), which blocks the thread on the name
+    /// resolution.
+    ///
+    /// [`None`] is returned by default, and for the numeric addresses.
+    fn host_to_resolve(&self) -> Option<String> {
+        None
+    }
 }
 
 // impl_to_sock_addrs_for_into_socket_addr
 macro_rules! itsafisa {
-    ($t:ty) => {
+    ($t:ty $(, $host:expr)?) => {
         impl ToSockAddrs for $t {
             type Iter =
                 std::iter::Map<<$t as std::net::ToSocketAddrs>::Iter, fn(SocketAddr) -> SockAddr>;
@@ -44,6 +68,10 @@ macro_rules! itsafisa {
                 std::net::ToSocketAddrs::to_socket_addrs(self)
                     .map(|iter| iter.map(SockAddr::from as _))
             }
+
+            $(fn host_to_resolve(&self) -> Option<String> {
+                $host(self)
+            })?
         }
     };
 }
@@ -51,12 +79,14 @@ macro_rules! itsafisa {
 itsafisa!(SocketAddr);
 itsafisa!(SocketAddrV4);
 itsafisa!(SocketAddrV6);
-itsafisa!(str);
-itsafisa!(String);
+itsafisa!(str, resolve::host_of_str);
+itsafisa!(String, |addr: &String| resolve::host_of_str(addr));
 itsafisa!((IpAddr, u16));
 itsafisa!((Ipv4Addr, u16));
 itsafisa!((Ipv6Addr, u16));
-itsafisa!((String, u16));
+itsafisa!((String, u16), |(host, port): &(String, u16)| {
+    resolve::host_of_pair(host, *port)
+});
 
 impl ToSockAddrs for (&str, u16) {
     type Iter = std::iter::Map<std::vec::IntoIter<SocketAddr>, fn(SocketAddr) -> SockAddr>;
@@ -64,6 +94,10 @@ impl ToSockAddrs for (&str, u16) {
     fn to_sock_addrs(&self) -> io::Result<Self::Iter> {
         ToSocketAddrs::to_socket_addrs(self).map(|iter| iter.map(SockAddr::from as _))
     }
+
+    fn host_to_resolve(&self) -> Option<String> {
+        resolve::host_of_pair(self.0, self.1)
+    }
 }
 
 impl ToSockAddrs for SockAddr {
@@ -88,6 +122,10 @@ impl<T: ToSockAddrs + ?Sized> ToSockAddrs for &T {
     fn to_sock_addrs(&self) -> io::Result<Self::Iter> {
         (**self).to_sock_addrs()
     }
+
+    fn host_to_resolve(&self) -> Option<String> {
+        (**self).host_to_resolve()
+    }
 }
 
 fn each_addr<T>(
@@ -110,12 +148,12 @@ fn each_addr<T>(
     }))
 }
 
-#[allow(dead_code)]
+#[cfg(feature = "runtime")]
 async fn each_addr_async<T, F: Future<Output = io::Result<T>>>(
     addr: impl ToSockAddrs,
     mut f: impl FnMut(SockAddr) -> F,
 ) -> io::Result<T> {
-    let addrs = addr.to_sock_addrs()?;
+    let addrs = resolve::resolve(&addr).await?;
     let mut last_err = None;
     for addr in addrs {
         match f(addr).await {
@@ -131,13 +169,13 @@ async fn each_addr_async<T, F: Future<Output = io::Result<T>>>(
     }))
 }
 
-#[allow(dead_code)]
+#[cfg(feature = "runtime")]
 async fn each_addr_async_buf<'arena, T, B: 'arena, F: Future<Output = BufResult<'arena, T, B>>>(
     addr: impl ToSockAddrs,
     mut buffer: B,
     mut f: impl FnMut(SockAddr, B) -> F,
 ) -> BufResult<'arena, T, B> {
-    match addr.to_sock_addrs() {
+    match resolve::resolve(&addr).await {
         Ok(addrs) => {
             let mut last_err = None;
             let mut res;
@@ -0,0 +1,406 @@
+use std::{io, time::Duration};
+
+use socket2::{SockAddr, Socket as Socket2, TcpKeepalive};
+
+/// Options applied to a socket after it's created and before it's bound, see
+/// [`TcpListener::bind_with_options`](crate::net::TcpListener::bind_with_options) and
+/// [`UdpSocket::bind_with_options`](crate::net::UdpSocket::bind_with_options).
+///
+/// ```
+/// use completeio::net::{BindOptions, TcpListener};
+///
+/// let listener =
+///     TcpListener::bind_with_options("127.0.0.1:0", BindOptions::new().reuse_address(true))
+///         .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct BindOptions {
+    reuse_address: bool,
+    reuse_port: bool,
+    only_v6: Option<bool>,
+    device: Option<String>,
+    freebind: bool,
+    pub(super) backlog: i32,
+}
+
+impl BindOptions {
+    /// Creates the options with the defaults of
+    /// [`TcpListener::bind`](crate::net::TcpListener::bind)
+    /// and [`UdpSocket::bind`](crate::net::UdpSocket::bind).
+    pub fn new() -> Self {
+        Self {
+            reuse_address: false,
+            reuse_port: false,
+            only_v6: None,
+            device: None,
+            freebind: false,
+            backlog: 128,
+        }
+    }
+
+    /// Sets the `SO_REUSEADDR` option, so the address is bound again while the sockets
+    /// previously bound to it are in the `TIME_WAIT` state.
+    pub fn reuse_address(&mut self, reuse: bool) -> &mut Self {
+        self.reuse_address = reuse;
+        self
+    }
+
+    /// Sets the `SO_REUSEPORT` option, so several sockets are bound to the same address and the
+    /// kernel distributes the incoming connections or datagrams between them.
+    ///
+    /// ## Platform specific
+    ///
+    /// * Windows, Solaris and illumos: the option isn't supported, it's ignored.
+    pub fn reuse_port(&mut self, reuse: bool) -> &mut Self {
+        self.reuse_port = reuse;
+        self
+    }
+
+    /// Sets the `IPV6_V6ONLY` option, so the socket bound to an IPv6 address doesn't accept
+    /// the IPv4-mapped connections or datagrams. The default of the OS is kept if it's not set.
+    ///
+    /// It's ignored by IPv4 sockets.
+    pub fn only_v6(&mut self, only_v6: bool) -> &mut Self {
+        self.only_v6 = Some(only_v6);
+        self
+    }
+
+    /// Sets the `SO_BINDTODEVICE` option, so the socket only uses the network interface named
+    /// `device`.
+    ///
+    /// ## Platform specific
+    ///
+    /// * Linux and Android: the option may require the `CAP_NET_RAW` capability, the bind fails
+    ///   with the permission error otherwise.
+    /// * Others: the bind fails with [`io::ErrorKind::Unsupported`].
+    pub fn device(&mut self, device: &str) -> &mut Self {
+        self.device = Some(device.to_owned());
+        self
+    }
+
+    /// Sets the `IP_FREEBIND` option, so the socket is bound to an address which isn't
+    /// assigned to any interface yet.
+    ///
+    /// ## Platform specific
+    ///
+    /// * Linux and Android: supported.
+    /// * Others: the bind fails with [`io::ErrorKind::Unsupported`].
+    pub fn freebind(&mut self, freebind: bool) -> &mut Self {
+        self.freebind = freebind;
+        self
+    }
+
+    /// Sets the maximum number of pending connections of a listener, 128 by default.
+    ///
+    /// It's ignored by UDP sockets.
+    pub fn backlog(&mut self, backlog: i32) -> &mut Self {
+        self.backlog = backlog;
+        self
+    }
+
+    pub(super) fn apply(&self, socket: &Socket2, addr: &SockAddr) -> io::Result<()> {
+        if let Some(only_v6) = self.only_v6.filter(|_| addr.is_ipv6()) {
+            socket.set_only_v6(only_v6)?;
+        }
+        if let Some(device) = &self.device {
+            set_device(socket, Some(device))?;
+        }
+        if self.freebind {
+            set_freebind(socket, true)?;
+        }
+        if self.reuse_address {
+            socket.set_reuse_address(true)?;
+        }
+        #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+        if self.reuse_port {
+            socket.set_reuse_port(true)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for BindOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// TCP keepalive parameters, see
+/// [`TcpStream::set_keepalive`](crate::net::TcpStream::set_keepalive).
+///
+/// The parameters left `None` keep the defaults of the OS.
+///
+/// ## Platform specific
+///
+/// * Windows: `retries` isn't supported, it's ignored. The applied `time` and `interval` can't be
+///   read back.
+/// * OpenBSD: the parameters can't be set per socket, they are ignored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeepAlive {
+    /// The time the connection is idle before the first probe is sent, `TCP_KEEPIDLE`.
+    pub time: Option<Duration>,
+    /// The interval between the probes, `TCP_KEEPINTVL`.
+    pub interval: Option<Duration>,
+    /// The number of unanswered probes before the connection is dropped, `TCP_KEEPCNT`.
+    pub retries: Option<u32>,
+}
+
+impl KeepAlive {
+    pub(super) fn apply(&self, socket: &Socket2) -> io::Result<()> {
+        let mut params = TcpKeepalive::new();
+        if let Some(time) = self.time {
+            params = params.with_time(time);
+        }
+        #[cfg(any(
+            target_os = "android",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "fuchsia",
+            target_os = "illumos",
+            target_vendor = "apple",
+            target_os = "linux",
+            target_os = "netbsd",
+            target_os = "windows",
+        ))]
+        if let Some(interval) = self.interval {
+            params = params.with_interval(interval);
+        }
+        #[cfg(any(
+            target_os = "android",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "fuchsia",
+            target_os = "illumos",
+            target_vendor = "apple",
+            target_os = "linux",
+            target_os = "netbsd",
+        ))]
+        if let Some(retries) = self.retries {
+            params = params.with_retries(retries);
+        }
+        socket.set_tcp_keepalive(&params)
+    }
+
+    // reads the parameters applied by the OS
+    pub(super) fn read(socket: &Socket2) -> io::Result<Option<Self>> {
+        if !socket.keepalive()? {
+            return Ok(None);
+        }
+        #[allow(unused_mut)]
+        let mut keepalive = Self::default();
+        #[cfg(not(any(windows, target_os = "haiku", target_os = "openbsd")))]
+        {
+            keepalive.time = Some(socket.keepalive_time()?);
+        }
+        #[cfg(any(
+            target_os = "android",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "fuchsia",
+            target_os = "illumos",
+            target_vendor = "apple",
+            target_os = "linux",
+            target_os = "netbsd",
+        ))]
+        {
+            keepalive.interval = Some(socket.keepalive_interval()?);
+            keepalive.retries = Some(socket.keepalive_retries()?);
+        }
+        Ok(Some(keepalive))
+    }
+}
+
+// SO_BINDTODEVICE and IP_FREEBIND are Linux options, the errors of the kernel are returned as is
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(super) fn set_device(socket: &Socket2, device: Option<&str>) -> io::Result<()> {
+    socket.bind_device(device.map(str::as_bytes))
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(super) fn device(socket: &Socket2) -> io::Result<Option<String>> {
+    Ok(socket
+        .device()?
+        .map(|device| String::from_utf8_lossy(&device).into_owned()))
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(super) fn set_freebind(socket: &Socket2, freebind: bool) -> io::Result<()> {
+    socket.set_freebind(freebind)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(super) fn freebind(socket: &Socket2) -> io::Result<bool> {
+    socket.freebind()
+}
+
+// TCP_FASTOPEN and TCP_DEFER_ACCEPT of listeners
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(super) fn set_tcp_fastopen(socket: &Socket2, queue_len: u32) -> io::Result<()> {
+    let queue_len = libc::c_int::try_from(queue_len).unwrap_or(libc::c_int::MAX);
+    set_tcp_option(socket, libc::TCP_FASTOPEN, queue_len)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(super) fn set_defer_accept(socket: &Socket2, timeout: Duration) -> io::Result<()> {
+    // the kernel counts whole seconds, the partial one is rounded up
+    let secs = timeout.as_secs() + u64::from(timeout.subsec_nanos() > 0);
+    let secs = libc::c_int::try_from(secs).unwrap_or(libc::c_int::MAX);
+    set_tcp_option(socket, libc::TCP_DEFER_ACCEPT, secs)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn set_tcp_option(socket: &Socket2, name: libc::c_int, value: libc::c_int) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    crate::syscall!(setsockopt(
+        socket.as_raw_fd(),
+        libc::IPPROTO_TCP,
+        name,
+        &value as *const libc::c_int as *const libc::c_void,
+        std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+    ))
+    .map(drop)
+}
+
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+pub(super) fn set_tcp_fastopen(_socket: &Socket2, _queue_len: u32) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+pub(super) fn set_defer_accept(_socket: &Socket2, _timeout: Duration) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+pub(super) fn set_device(_socket: &Socket2, _device: Option<&str>) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+pub(super) fn device(_socket: &Socket2) -> io::Result<Option<String>> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+pub(super) fn set_freebind(_socket: &Socket2, _freebind: bool) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+pub(super) fn freebind(_socket: &Socket2) -> io::Result<bool> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(super) fn set_user_timeout(socket: &Socket2, timeout: Option<Duration>) -> io::Result<()> {
+    socket.set_tcp_user_timeout(timeout)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(super) fn user_timeout(socket: &Socket2) -> io::Result<Option<Duration>> {
+    socket.tcp_user_timeout()
+}
+
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+pub(super) fn set_user_timeout(_socket: &Socket2, _timeout: Option<Duration>) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+pub(super) fn user_timeout(_socket: &Socket2) -> io::Result<Option<Duration>> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+// the length of the option value passed to the kernel
+fn option_len<T>() -> io::Result<i32> {
+    i32::try_from(std::mem::size_of::<T>())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "the option value is too large"))
+}
+
+/// Set the option `name` of the `level` to the bytes of `value`.
+///
+/// # Safety
+///
+/// `T` should be the type the kernel expects for the option.
+pub(super) unsafe fn set_raw_sockopt<T>(
+    socket: &Socket2,
+    level: i32,
+    name: i32,
+    value: &T,
+) -> io::Result<()> {
+    let len = option_len::<T>()?;
+    #[cfg(unix)]
+    crate::syscall!(setsockopt(
+        std::os::fd::AsRawFd::as_raw_fd(socket),
+        level,
+        name,
+        value as *const T as *const libc::c_void,
+        len as libc::socklen_t,
+    ))?;
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::Networking::WinSock::setsockopt;
+
+        crate::syscall!(
+            SOCKET,
+            setsockopt(
+                std::os::windows::io::AsRawSocket::as_raw_socket(socket) as _,
+                level,
+                name,
+                value as *const T as *const u8,
+                len,
+            )
+        )?;
+    }
+    Ok(())
+}
+
+/// Get the option `name` of the `level`, the length of the value returned by the kernel should
+/// be the size of `T`.
+///
+/// # Safety
+///
+/// `T` should be the type the kernel returns for the option, any bit pattern of its size should
+/// be valid.
+pub(super) unsafe fn get_raw_sockopt<T>(socket: &Socket2, level: i32, name: i32) -> io::Result<T> {
+    let mut value = std::mem::MaybeUninit::<T>::zeroed();
+    let mut len = option_len::<T>()?;
+    #[cfg(unix)]
+    {
+        let mut socklen = len as libc::socklen_t;
+        crate::syscall!(getsockopt(
+            std::os::fd::AsRawFd::as_raw_fd(socket),
+            level,
+            name,
+            value.as_mut_ptr() as *mut libc::c_void,
+            &mut socklen,
+        ))?;
+        len = socklen as i32;
+    }
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::Networking::WinSock::getsockopt;
+
+        crate::syscall!(
+            SOCKET,
+            getsockopt(
+                std::os::windows::io::AsRawSocket::as_raw_socket(socket) as _,
+                level,
+                name,
+                value.as_mut_ptr() as *mut u8,
+                &mut len,
+            )
+        )?;
+    }
+    if len != option_len::<T>()? {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "the option value has {len} bytes, expected {}",
+                std::mem::size_of::<T>()
+            ),
+        ));
+    }
+    Ok(value.assume_init())
+}
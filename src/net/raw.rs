@@ -0,0 +1,168 @@
+use std::io;
+
+use socket2::{Domain, Protocol, SockAddr, Type};
+
+#[cfg(feature = "runtime")]
+use crate::{
+    buf::{IoBuf, IoBufMut},
+    BufResult,
+};
+use crate::{
+    impl_raw_fd,
+    net::{Socket, ToSockAddrs},
+};
+
+/// A socket exchanging the packets of a protocol over IP, like ICMP for ping.
+///
+/// The socket is created with [`new`](RawSocket::new), preferring the unprivileged ICMP
+/// datagram socket where the platform has it, or with [`new_raw`](RawSocket::new_raw). The
+/// packets are exchanged with [`send_to`](RawSocket::send_to) and
+/// [`recv_from`](RawSocket::recv_from) like the datagrams of
+/// [`UdpSocket`](crate::net::UdpSocket).
+///
+/// The received packets of a [`Type::RAW`] IPv4 socket start with the IP header. The datagram
+/// socket receives only the ICMP message, and Linux replaces the identifier of the sent echo
+/// requests with the local port of the socket.
+///
+/// # Examples
+///
+/// ```no_run
+/// use completeio::net::RawSocket;
+/// use socket2::{Domain, Protocol};
+///
+/// completeio::task::block_on(async {
+///     let socket = RawSocket::new(Domain::IPV4, Protocol::ICMPV4).unwrap();
+///     // echo request with zero identifier and sequence number
+///     let request = vec![8, 0, 0xf7, 0xff, 0, 0, 0, 0];
+///     socket.send_to(request, "127.0.0.1:0").await.0.unwrap();
+///     let (res, reply) = socket.recv_from(Vec::with_capacity(1500)).await;
+///     let (len, addr) = res.unwrap();
+///     println!("{len} bytes from {:?}: {reply:?}", addr.as_socket());
+/// })
+/// ```
+pub struct RawSocket {
+    inner: Socket,
+}
+
+impl RawSocket {
+    /// Creates a socket of the protocol, the unprivileged datagram socket is preferred.
+    ///
+    /// ## Platform specific
+    ///
+    /// * Linux, Android: the ICMP datagram socket is allowed for the groups of the
+    ///   `net.ipv4.ping_group_range` sysctl, a raw socket is created otherwise.
+    /// * macOS, iOS: the ICMP datagram socket is created for any user.
+    /// * Others: a raw socket is created.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::ErrorKind::PermissionDenied`] if the process is not allowed to create
+    /// either socket.
+    pub fn new(domain: Domain, protocol: Protocol) -> io::Result<Self> {
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "macos",
+            target_os = "ios"
+        ))]
+        if let Ok(inner) = Socket::new(domain, Type::DGRAM, Some(protocol)) {
+            return Ok(Self { inner });
+        }
+        Self::new_raw(domain, protocol)
+    }
+
+    /// Creates a [`Type::RAW`] socket of the protocol.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::ErrorKind::PermissionDenied`] if the process lacks the privilege, e.g.
+    /// `CAP_NET_RAW` on Linux or the administrator rights on Windows.
+    pub fn new_raw(domain: Domain, protocol: Protocol) -> io::Result<Self> {
+        match Socket::new(domain, Type::RAW, Some(protocol)) {
+            Ok(inner) => Ok(Self { inner }),
+            Err(e) if e.kind() == io::ErrorKind::PermissionDenied => Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("not permitted to create a raw socket: {e}"),
+            )),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the type of the created socket, [`Type::DGRAM`] or [`Type::RAW`].
+    pub fn socket_type(&self) -> io::Result<Type> {
+        self.inner.socket_type()
+    }
+
+    /// Binds the socket to the local address, the port is ignored by raw sockets.
+    pub fn bind(&self, addr: impl ToSockAddrs) -> io::Result<()> {
+        super::each_addr(addr, |addr| self.inner.bind_to(&addr))
+    }
+
+    /// Connects the socket to the remote address, allowing [`send`](RawSocket::send) and
+    /// [`recv`](RawSocket::recv) to be used.
+    pub fn connect(&self, addr: impl ToSockAddrs) -> io::Result<()> {
+        super::each_addr(addr, |addr| self.inner.connect(&addr))
+    }
+
+    /// Close the socket through the driver.
+    #[cfg(feature = "runtime")]
+    pub async fn close(self) -> io::Result<()> {
+        self.inner.close().await
+    }
+
+    /// Returns the local address of the socket.
+    pub fn local_addr(&self) -> io::Result<SockAddr> {
+        self.inner.local_addr()
+    }
+
+    /// Returns the address of the remote peer the socket is connected to.
+    pub fn peer_addr(&self) -> io::Result<SockAddr> {
+        self.inner.peer_addr()
+    }
+
+    /// Sets the value for the `IP_TTL` option on this socket.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.inner.set_ttl(ttl)
+    }
+
+    /// Gets the value of the `IP_TTL` option for this socket.
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.inner.ttl()
+    }
+
+    /// Receives a packet from the connected peer.
+    #[cfg(feature = "runtime")]
+    pub async fn recv<T: IoBufMut<'static>>(&self, buffer: T) -> BufResult<usize, T> {
+        self.inner.recv(buffer).await
+    }
+
+    /// Sends a packet to the connected peer.
+    #[cfg(feature = "runtime")]
+    pub async fn send<T: IoBuf<'static>>(&self, buffer: T) -> BufResult<usize, T> {
+        self.inner.send(buffer).await
+    }
+
+    /// Receives a packet, returning its length and the origin.
+    #[cfg(feature = "runtime")]
+    pub async fn recv_from<T: IoBufMut<'static>>(
+        &self,
+        buffer: T,
+    ) -> BufResult<(usize, SockAddr), T> {
+        self.inner.recv_from(buffer).await
+    }
+
+    /// Sends a packet to the address, the port is ignored by raw sockets.
+    #[cfg(feature = "runtime")]
+    pub async fn send_to<T: IoBuf<'static>>(
+        &self,
+        buffer: T,
+        addr: impl ToSockAddrs,
+    ) -> BufResult<usize, T> {
+        super::each_addr_async_buf(addr, buffer, |addr, buffer| async move {
+            self.inner.send_to(buffer, &addr).await
+        })
+        .await
+    }
+}
+
+impl_raw_fd!(RawSocket, inner);
@@ -0,0 +1,135 @@
+#[cfg(feature = "runtime")]
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+
+#[cfg(feature = "runtime")]
+use socket2::SockAddr;
+
+#[cfg(feature = "runtime")]
+use crate::net::ToSockAddrs;
+
+/// Resolves the `host:port` string to the socket addresses without blocking the runtime thread.
+///
+/// A numeric address is parsed in place, a host name is resolved with `getaddrinfo` on the pool
+/// of [`spawn_blocking`](crate::task::spawn_blocking). The async operations accepting an
+/// address, like [`TcpStream::connect`](crate::net::TcpStream::connect), resolve host names
+/// with it.
+///
+/// # Errors
+///
+/// Returns [`io::ErrorKind::NotFound`] if the host doesn't exist or has no addresses, and
+/// [`io::ErrorKind::InvalidInput`] if the string isn't the host followed by the port.
+///
+/// ```
+/// completeio::task::block_on(async {
+///     let mut addrs = completeio::net::lookup_host("localhost:80").await.unwrap();
+///     assert!(addrs.any(|addr| addr.ip().is_loopback() && addr.port() == 80));
+/// })
+/// ```
+#[cfg(feature = "runtime")]
+pub async fn lookup_host(host: &str) -> io::Result<impl Iterator<Item = SocketAddr>> {
+    // numeric addresses don't need the blocking pool
+    if let Ok(addr) = host.parse::<SocketAddr>() {
+        return Ok(vec![addr].into_iter());
+    }
+    let (name, port) = host
+        .rsplit_once(':')
+        .and_then(|(name, port)| Some((name.to_owned(), port.parse::<u16>().ok()?)))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid socket address"))?;
+    let addrs = crate::task::run_blocking(move || getaddrinfo(&name, port)).await?;
+    Ok(addrs.into_iter())
+}
+
+// The name of the address to be resolved with `lookup_host`, `None` if the addresses are
+// numeric.
+pub(super) fn host_of_str(addr: &str) -> Option<String> {
+    addr.parse::<SocketAddr>().is_err().then(|| addr.to_owned())
+}
+
+pub(super) fn host_of_pair(host: &str, port: u16) -> Option<String> {
+    host.parse::<IpAddr>()
+        .is_err()
+        .then(|| format!("{host}:{port}"))
+}
+
+// Resolve the addresses of the async operations.
+#[cfg(feature = "runtime")]
+pub(super) async fn resolve(addr: &(impl ToSockAddrs + ?Sized)) -> io::Result<Vec<SockAddr>> {
+    match addr.host_to_resolve() {
+        Some(host) => Ok(lookup_host(&host).await?.map(SockAddr::from).collect()),
+        None => Ok(addr.to_sock_addrs()?.collect()),
+    }
+}
+
+#[cfg(all(unix, feature = "runtime"))]
+fn getaddrinfo(name: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+    use std::{ffi::CString, ptr};
+
+    let c_name = CString::new(name)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "host name contains nul"))?;
+    let mut hints: libc::addrinfo = unsafe { std::mem::zeroed() };
+    // one entry per address rather than per socket type
+    hints.ai_socktype = libc::SOCK_STREAM;
+    let mut res = ptr::null_mut();
+    let code = unsafe { libc::getaddrinfo(c_name.as_ptr(), ptr::null(), &hints, &mut res) };
+    match code {
+        0 => {}
+        libc::EAI_SYSTEM => return Err(io::Error::last_os_error()),
+        _ => {
+            let message = unsafe { std::ffi::CStr::from_ptr(libc::gai_strerror(code)) };
+            let message = format!(
+                "failed to lookup address of {name}: {}",
+                message.to_string_lossy()
+            );
+            let kind = match code {
+                libc::EAI_NONAME => io::ErrorKind::NotFound,
+                #[cfg(any(
+                    target_os = "linux",
+                    target_os = "android",
+                    target_os = "macos",
+                    target_os = "ios"
+                ))]
+                libc::EAI_NODATA => io::ErrorKind::NotFound,
+                _ => io::ErrorKind::Other,
+            };
+            return Err(io::Error::new(kind, message));
+        }
+    }
+    let mut addrs = Vec::new();
+    let mut entry = res;
+    while let Some(info) = unsafe { entry.as_ref() } {
+        let addr = unsafe {
+            SockAddr::try_init(|storage, len| {
+                ptr::copy_nonoverlapping(
+                    info.ai_addr.cast::<u8>(),
+                    storage.cast::<u8>(),
+                    info.ai_addrlen as usize,
+                );
+                *len = info.ai_addrlen;
+                Ok(())
+            })
+        };
+        if let Some(mut addr) = addr.ok().and_then(|(_, addr)| addr.as_socket()) {
+            addr.set_port(port);
+            addrs.push(addr);
+        }
+        entry = info.ai_next;
+    }
+    unsafe { libc::freeaddrinfo(res) };
+    Ok(addrs)
+}
+
+#[cfg(all(windows, feature = "runtime"))]
+fn getaddrinfo(name: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+    use std::net::ToSocketAddrs;
+
+    use windows_sys::Win32::Networking::WinSock::{WSAHOST_NOT_FOUND, WSANO_DATA};
+
+    match (name, port).to_socket_addrs() {
+        Ok(addrs) => Ok(addrs.collect()),
+        Err(e) if matches!(e.raw_os_error(), Some(WSAHOST_NOT_FOUND | WSANO_DATA)) => {
+            Err(io::Error::new(io::ErrorKind::NotFound, e))
+        }
+        Err(e) => Err(e),
+    }
+}
@@ -1,20 +1,33 @@
-use std::{io, net::Shutdown};
+use std::{io, net::Shutdown, time::Duration};
 
 use socket2::{Domain, Protocol, SockAddr, Socket as Socket2, Type};
 
-use crate::impl_raw_fd;
+#[cfg(all(unix, feature = "runtime"))]
+use crate::{
+    buf::{BufWrapper, BufWrapperMut},
+    op::{RecvMsg, SendMsg},
+};
 #[cfg(feature = "runtime")]
 use crate::{
     buf::{IntoInner, IoBuf, IoBufMut, VectoredBufWrapper},
     buf_try,
-    driver::Fd,
+    driver::{Fd, IntoRawFd, OpCode},
+    fs::File,
+    net::DatagramLen,
     op::{
-        Accept, Connect, Recv, RecvFrom, RecvFromVectored, RecvResultExt, RecvVectored, Send,
-        SendTo, SendToVectored, SendVectored, UpdateBufferLen,
+        Accept, AcceptFlags, Bind, Close, Connect, CreateSocket, Listen, Recv, RecvFrom,
+        RecvFromVectored, RecvResultExt, RecvVectored, Send, SendFile, SendTo, SendToVectored,
+        SendVectored, SendZc, ShutdownSocket, UpdateBufferLen,
     },
-    task::RUNTIME,
+    task::{op::OpFuture, runtime::Runtime, RUNTIME},
     Attacher, BufResult,
 };
+use crate::{
+    impl_raw_fd,
+    net::{options, BindOptions, KeepAlive},
+};
+#[cfg(all(target_os = "linux", feature = "runtime"))]
+use crate::{op::AcceptMultishot, task::op::OpStream};
 
 pub struct Socket {
     socket: Socket2,
@@ -32,18 +45,26 @@ impl Socket {
     }
 
     #[cfg(feature = "runtime")]
-    pub(crate) fn attach(&self) -> io::Result<Fd> {
-        self.attacher.attach(self)
+    pub(crate) fn attach_runtime(&self) -> io::Result<(Fd, &Runtime)> {
+        self.attacher.attach_runtime(self)
     }
 
     pub fn try_clone(&self) -> io::Result<Self> {
+        let socket = self.socket.try_clone()?;
         Ok(Self {
-            socket: self.socket.try_clone()?,
             #[cfg(feature = "runtime")]
-            attacher: self.attacher.clone(),
+            attacher: self.attacher.duplicate(&socket),
+            socket,
         })
     }
 
+    #[cfg(feature = "runtime")]
+    pub async fn close(self) -> io::Result<()> {
+        let op = Close::new(self.socket.into_raw_fd());
+        RUNTIME.with(|runtime| runtime.submit(op)).await.0?;
+        Ok(())
+    }
+
     pub fn peer_addr(&self) -> io::Result<SockAddr> {
         self.socket.peer_addr()
     }
@@ -52,6 +73,121 @@ impl Socket {
         self.socket.local_addr()
     }
 
+    pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        self.socket.set_nodelay(nodelay)
+    }
+
+    pub fn nodelay(&self) -> io::Result<bool> {
+        self.socket.nodelay()
+    }
+
+    pub fn set_oob_inline(&self, oob_inline: bool) -> io::Result<()> {
+        self.socket.set_out_of_band_inline(oob_inline)
+    }
+
+    pub fn oob_inline(&self) -> io::Result<bool> {
+        self.socket.out_of_band_inline()
+    }
+
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.socket.set_ttl(ttl)
+    }
+
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.socket.ttl()
+    }
+
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.socket.take_error()
+    }
+
+    pub fn only_v6(&self) -> io::Result<bool> {
+        self.socket.only_v6()
+    }
+
+    pub fn set_device(&self, device: Option<&str>) -> io::Result<()> {
+        options::set_device(&self.socket, device)
+    }
+
+    pub fn device(&self) -> io::Result<Option<String>> {
+        options::device(&self.socket)
+    }
+
+    pub fn set_freebind(&self, freebind: bool) -> io::Result<()> {
+        options::set_freebind(&self.socket, freebind)
+    }
+
+    pub fn freebind(&self) -> io::Result<bool> {
+        options::freebind(&self.socket)
+    }
+
+    pub fn set_tcp_fastopen(&self, queue_len: u32) -> io::Result<()> {
+        options::set_tcp_fastopen(&self.socket, queue_len)
+    }
+
+    pub fn set_defer_accept(&self, timeout: Duration) -> io::Result<()> {
+        options::set_defer_accept(&self.socket, timeout)
+    }
+
+    pub fn set_tos(&self, tos: u32) -> io::Result<()> {
+        self.socket.set_tos(tos)
+    }
+
+    pub fn tos(&self) -> io::Result<u32> {
+        self.socket.tos()
+    }
+
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.socket.set_recv_buffer_size(size)
+    }
+
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        self.socket.recv_buffer_size()
+    }
+
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.socket.set_send_buffer_size(size)
+    }
+
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        self.socket.send_buffer_size()
+    }
+
+    pub fn set_user_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        options::set_user_timeout(&self.socket, timeout)
+    }
+
+    pub fn user_timeout(&self) -> io::Result<Option<Duration>> {
+        options::user_timeout(&self.socket)
+    }
+
+    pub unsafe fn set_raw_sockopt<T>(&self, level: i32, name: i32, value: &T) -> io::Result<()> {
+        options::set_raw_sockopt(&self.socket, level, name, value)
+    }
+
+    pub unsafe fn get_raw_sockopt<T>(&self, level: i32, name: i32) -> io::Result<T> {
+        options::get_raw_sockopt(&self.socket, level, name)
+    }
+
+    pub fn set_keepalive(&self, keepalive: Option<KeepAlive>) -> io::Result<()> {
+        match keepalive {
+            Some(keepalive) => keepalive.apply(&self.socket),
+            None => self.socket.set_keepalive(false),
+        }
+    }
+
+    pub fn keepalive(&self) -> io::Result<Option<KeepAlive>> {
+        KeepAlive::read(&self.socket)
+    }
+
+    pub fn set_linger(&self, linger: Option<Duration>) -> io::Result<()> {
+        self.socket.set_linger(linger)
+    }
+
+    pub fn linger(&self) -> io::Result<Option<Duration>> {
+        self.socket.linger()
+    }
+
     pub fn new(domain: Domain, ty: Type, protocol: Option<Protocol>) -> io::Result<Self> {
         let socket = Socket2::new(domain, ty, protocol)?;
         // On Linux we use blocking socket
@@ -64,37 +200,155 @@ impl Socket {
         Ok(Self::from_socket2(socket))
     }
 
+    /// Wrap the socket created outside of the crate, setting the blocking mode the driver
+    /// expects.
+    pub fn from_std(socket: Socket2) -> io::Result<Self> {
+        // see `Socket::new` for the blocking sockets on Linux
+        #[cfg(target_os = "linux")]
+        socket.set_nonblocking(false)?;
+        #[cfg(all(unix, not(target_os = "linux")))]
+        socket.set_nonblocking(true)?;
+        Ok(Self::from_socket2(socket))
+    }
+
+    /// Detach the socket from the driver and return it in blocking mode.
+    ///
+    /// Fails if the driver still has the canceled operations of the socket in flight, the
+    /// socket is closed then.
+    #[allow(unused_mut)]
+    pub fn into_std(mut self) -> io::Result<Socket2> {
+        #[cfg(feature = "runtime")]
+        self.attacher.reset(&self.socket)?;
+        #[cfg(all(unix, not(target_os = "linux")))]
+        self.socket.set_nonblocking(false)?;
+        Ok(self.socket)
+    }
+
     pub fn bind(addr: &SockAddr, ty: Type, protocol: Option<Protocol>) -> io::Result<Self> {
         let socket = Self::new(addr.domain(), ty, protocol)?;
         socket.socket.bind(addr)?;
         Ok(socket)
     }
 
+    #[cfg(unix)]
+    pub fn pair(domain: Domain, ty: Type, protocol: Option<Protocol>) -> io::Result<(Self, Self)> {
+        let (first, second) = Socket2::pair(domain, ty, protocol)?;
+        // readiness based drivers require non-blocking sockets
+        #[cfg(not(target_os = "linux"))]
+        {
+            first.set_nonblocking(true)?;
+            second.set_nonblocking(true)?;
+        }
+        Ok((Self::from_socket2(first), Self::from_socket2(second)))
+    }
+
+    pub fn bind_with_options(
+        addr: &SockAddr,
+        ty: Type,
+        protocol: Option<Protocol>,
+        options: &BindOptions,
+    ) -> io::Result<Self> {
+        let socket = Self::new(addr.domain(), ty, protocol)?;
+        options.apply(&socket.socket, addr)?;
+        socket.socket.bind(addr)?;
+        Ok(socket)
+    }
+
+    #[cfg(feature = "runtime")]
+    pub async fn new_async(
+        domain: Domain,
+        ty: Type,
+        protocol: Option<Protocol>,
+    ) -> io::Result<Self> {
+        let op = CreateSocket::new(domain, ty, protocol);
+        let (res, op) = RUNTIME.with(|runtime| runtime.submit(op)).await;
+        Ok(Self::from_socket2(op.on_create(res)?))
+    }
+
+    #[cfg(feature = "runtime")]
+    pub async fn bind_async(
+        addr: &SockAddr,
+        ty: Type,
+        protocol: Option<Protocol>,
+    ) -> io::Result<Self> {
+        let socket = Self::new_async(addr.domain(), ty, protocol).await?;
+        let (fd, runtime) = socket.attach_runtime()?;
+        let op = Bind::new(fd, addr.clone());
+        runtime.submit(op).await.0?;
+        Ok(socket)
+    }
+
+    pub fn bind_to(&self, addr: &SockAddr) -> io::Result<()> {
+        self.socket.bind(addr)
+    }
+
+    pub fn socket_type(&self) -> io::Result<Type> {
+        self.socket.r#type()
+    }
+
     pub fn listen(&self, backlog: i32) -> io::Result<()> {
         self.socket.listen(backlog)
     }
 
+    #[cfg(feature = "runtime")]
+    pub async fn listen_async(&self, backlog: i32) -> io::Result<()> {
+        let (fd, runtime) = self.attach_runtime()?;
+        let op = Listen::new(fd, backlog);
+        runtime.submit(op).await.0?;
+        Ok(())
+    }
+
     pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
         self.socket.shutdown(how)
     }
 
+    #[cfg(feature = "runtime")]
+    pub async fn shutdown_async(&self, how: Shutdown) -> io::Result<()> {
+        let (fd, runtime) = self.attach_runtime()?;
+        let op = ShutdownSocket::new(fd, how);
+        runtime.submit(op).await.0?;
+        Ok(())
+    }
+
     pub fn connect(&self, addr: &SockAddr) -> io::Result<()> {
         self.socket.connect(addr)
     }
 
+    /// Connect with the optional timeout linked to the operation, the timed out connect fails
+    /// with [`io::ErrorKind::TimedOut`].
     #[cfg(feature = "runtime")]
-    pub async fn connect_async(&self, addr: &SockAddr) -> io::Result<()> {
-        let fd = self.attach()?;
+    pub async fn connect_async(
+        &self,
+        addr: &SockAddr,
+        timeout: Option<Duration>,
+    ) -> io::Result<()> {
+        let (fd, runtime) = self.attach_runtime()?;
         let op = Connect::new(fd, addr.clone());
-        let (res, op) = RUNTIME.with(|runtime| runtime.submit(op)).await;
+        // the timeout of the enclosing scope is not extended
+        let previous = runtime.replace_op_timeout(None);
+        let timeout = match (previous, timeout) {
+            (Some(previous), Some(timeout)) => Some(previous.min(timeout)),
+            (previous, timeout) => previous.or(timeout),
+        };
+        runtime.replace_op_timeout(timeout);
+        // dropping the future cancels the operation at once, before the socket is closed and its
+        // descriptor is reused
+        let future = runtime.submit_op(op);
+        runtime.replace_op_timeout(previous);
+        let (res, op) = future.await;
         op.on_connect(res)
     }
 
     #[cfg(feature = "runtime")]
     pub async fn accept(&self) -> io::Result<(Self, SockAddr)> {
-        let fd = self.attach()?;
+        let (fd, runtime) = self.attach_runtime()?;
+        // readiness based drivers require non-blocking sockets
+        #[cfg(all(unix, not(target_os = "linux")))]
+        let flags = AcceptFlags::CLOEXEC | AcceptFlags::NONBLOCK;
+        #[cfg(any(target_os = "linux", target_os = "windows"))]
+        let flags = AcceptFlags::CLOEXEC;
         #[cfg(unix)]
-        let op = Accept::new(fd);
+        let op = Accept::new(fd).with_flags(flags);
         #[cfg(target_os = "windows")]
         let op = {
             let local_addr = self.local_addr()?;
@@ -104,21 +358,50 @@ impl Socket {
                 self.socket.r#type()?,
                 self.socket.protocol()?,
             )
+            .with_flags(flags)
         };
-        let (res, mut op) = RUNTIME.with(|runtime| runtime.submit(op)).await;
+        let (res, mut op) = runtime.submit(op).await;
         let (accept_sock, addr) = op.on_accept(res)?;
         Ok((Self::from_socket2(accept_sock), addr.clone()))
     }
 
+    /// Starts accepting connections continuously.
+    ///
+    /// Returns `None` if the kernel doesn't support multishot accept. The
+    /// stream yields accepted sockets as raw file descriptors, it ends when
+    /// `capacity` sockets are buffered and not received.
+    #[cfg(all(target_os = "linux", feature = "runtime"))]
+    pub fn accept_multishot(
+        &self,
+        capacity: usize,
+    ) -> io::Result<Option<OpStream<AcceptMultishot>>> {
+        let (fd, runtime) = self.attach_runtime()?;
+        if !runtime.capabilities().accept_multishot {
+            return Ok(None);
+        }
+        let op = AcceptMultishot::new(fd);
+        Ok(Some(runtime.submit_multishot(op, capacity, |res| {
+            if let Ok(fd) = res {
+                // SAFETY: the accepted socket is owned by no one else
+                drop(unsafe { <Socket2 as crate::driver::FromRawFd>::from_raw_fd(fd as _) });
+            }
+        })))
+    }
+
     #[cfg(feature = "runtime")]
     pub async fn recv<T: IoBufMut<'static>>(&self, buffer: T) -> BufResult<usize, T> {
-        let (fd, buffer) = buf_try!(self.attach(), buffer);
-        let op = Recv::new(fd, buffer);
-        RUNTIME
-            .with(|runtime| runtime.submit(op))
-            .await
-            .into_inner()
-            .update_buffer_len()
+        self.recv_with_flags(buffer, 0).await
+    }
+
+    #[cfg(feature = "runtime")]
+    pub async fn recv_with_flags<T: IoBufMut<'static>>(
+        &self,
+        buffer: T,
+        flags: i32,
+    ) -> BufResult<usize, T> {
+        let ((fd, runtime), buffer) = buf_try!(self.attach_runtime(), buffer);
+        let op = Recv::new(fd, buffer).with_flags(flags);
+        runtime.submit(op).await.into_inner().update_buffer_len()
     }
 
     #[cfg(feature = "runtime")]
@@ -146,23 +429,25 @@ impl Socket {
         &self,
         buffer: VectoredBufWrapper<'static, T>,
     ) -> BufResult<usize, VectoredBufWrapper<'static, T>> {
-        let (fd, buffer) = buf_try!(self.attach(), buffer);
+        let ((fd, runtime), buffer) = buf_try!(self.attach_runtime(), buffer);
         let op = RecvVectored::new(fd, buffer);
-        RUNTIME
-            .with(|runtime| runtime.submit(op))
-            .await
-            .into_inner()
-            .update_buffer_len()
+        runtime.submit(op).await.into_inner().update_buffer_len()
     }
 
     #[cfg(feature = "runtime")]
     pub async fn send<T: IoBuf<'static>>(&self, buffer: T) -> BufResult<usize, T> {
-        let (fd, buffer) = buf_try!(self.attach(), buffer);
-        let op = Send::new(fd, buffer);
-        RUNTIME
-            .with(|runtime| runtime.submit(op))
-            .await
-            .into_inner()
+        self.send_with_flags(buffer, 0).await
+    }
+
+    #[cfg(feature = "runtime")]
+    pub async fn send_with_flags<T: IoBuf<'static>>(
+        &self,
+        buffer: T,
+        flags: i32,
+    ) -> BufResult<usize, T> {
+        let ((fd, runtime), buffer) = buf_try!(self.attach_runtime(), buffer);
+        let op = Send::new(fd, buffer).with_flags(flags);
+        runtime.submit(op).await.into_inner()
     }
 
     #[cfg(feature = "runtime")]
@@ -178,17 +463,86 @@ impl Socket {
         (Ok(total_written), buffer)
     }
 
+    #[cfg(feature = "runtime")]
+    pub async fn send_zc<T: IoBuf<'static>>(&self, buffer: T) -> BufResult<usize, T> {
+        let ((fd, runtime), buffer) = buf_try!(self.attach_runtime(), buffer);
+        let op = SendZc::new(fd, buffer);
+        runtime.submit(op).await.into_inner()
+    }
+
+    #[cfg(feature = "runtime")]
+    pub async fn send_zc_all<T: IoBuf<'static>>(&self, mut buffer: T) -> BufResult<usize, T> {
+        let buf_len = buffer.buf_len();
+        let mut total_written = 0;
+        let mut written;
+        while total_written < buf_len {
+            (written, buffer) = buf_try!(self
+                .send_zc(buffer.slice(total_written..))
+                .await
+                .into_inner());
+            total_written += written;
+        }
+        (Ok(total_written), buffer)
+    }
+
+    #[cfg(all(unix, feature = "runtime"))]
+    pub async fn recv_msg<T: IoBufMut<'static>, C: IoBufMut<'static>>(
+        &self,
+        buffer: T,
+        control: C,
+    ) -> BufResult<(usize, i32), (T, C)> {
+        let ((fd, runtime), (buffer, control)) = buf_try!(self.attach_runtime(), (buffer, control));
+        let op = RecvMsg::new(fd, BufWrapperMut::from(buffer), control);
+        let (res, op) = runtime.submit(op).await;
+        let (msg_flags, control_len) = (op.msg_flags(), op.control_len());
+        let (buffer, mut control, _) = op.into_inner();
+        let mut buffer = buffer.into_inner();
+        let res = res.map(|received| {
+            buffer.set_buf_init(received);
+            control.set_buf_init(control_len);
+            (received, msg_flags)
+        });
+        (res, (buffer, control))
+    }
+
+    #[cfg(all(unix, feature = "runtime"))]
+    pub async fn send_msg<T: IoBuf<'static>, C: IoBuf<'static>>(
+        &self,
+        buffer: T,
+        control: C,
+    ) -> BufResult<usize, (T, C)> {
+        let ((fd, runtime), (buffer, control)) = buf_try!(self.attach_runtime(), (buffer, control));
+        let op = SendMsg::new(fd, BufWrapper::from(buffer), control, None);
+        let (res, op) = runtime.submit(op).await;
+        let (buffer, control) = op.into_inner();
+        (res, (buffer.into_inner(), control))
+    }
+
+    #[cfg(feature = "runtime")]
+    pub async fn send_file(&self, file: &File, offset: u64, len: usize) -> io::Result<usize> {
+        if len == 0 {
+            return Ok(0);
+        }
+        let (fd, runtime) = self.attach_runtime()?;
+        let mut op = SendFile::new(file.attach()?, fd, offset, len)?;
+        loop {
+            let res;
+            (res, op) = runtime.submit(op).await;
+            if op.on_transfer(res)? {
+                break;
+            }
+        }
+        Ok(op.sent())
+    }
+
     #[cfg(feature = "runtime")]
     pub async fn send_vectored<T: IoBuf<'static>>(
         &self,
         buffer: VectoredBufWrapper<'static, T>,
     ) -> BufResult<usize, VectoredBufWrapper<'static, T>> {
-        let (fd, buffer) = buf_try!(self.attach(), buffer);
+        let ((fd, runtime), buffer) = buf_try!(self.attach_runtime(), buffer);
         let op = SendVectored::new(fd, buffer);
-        RUNTIME
-            .with(|runtime| runtime.submit(op))
-            .await
-            .into_inner()
+        runtime.submit(op).await.into_inner()
     }
 
     #[cfg(feature = "runtime")]
@@ -196,25 +550,62 @@ impl Socket {
         &self,
         buffer: T,
     ) -> BufResult<(usize, SockAddr), T> {
-        let (fd, buffer) = buf_try!(self.attach(), buffer);
-        let op = RecvFrom::new(fd, buffer);
-        RUNTIME
-            .with(|runtime| runtime.submit(op))
+        self.recv_from_with_flags(buffer, 0).await
+    }
+
+    #[cfg(feature = "runtime")]
+    pub async fn recv_from_with_flags<T: IoBufMut<'static>>(
+        &self,
+        buffer: T,
+        flags: i32,
+    ) -> BufResult<(usize, SockAddr), T> {
+        let ((fd, runtime), buffer) = buf_try!(self.attach_runtime(), buffer);
+        let op = RecvFrom::new(fd, buffer).with_flags(flags);
+        runtime
+            .submit(op)
             .await
             .into_inner()
             .map_addr()
             .update_buffer_len()
     }
 
+    #[cfg(feature = "runtime")]
+    pub async fn recv_from_full<T: IoBufMut<'static>>(
+        &self,
+        buffer: T,
+    ) -> BufResult<(DatagramLen, SockAddr), T> {
+        let ((fd, runtime), mut buffer) = buf_try!(self.attach_runtime(), buffer);
+        let capacity = buffer.as_uninit_slice().len();
+        let op = RecvFrom::new(fd, buffer).with_flags(TRUNC);
+        let (res, op) = runtime.submit(op).await;
+        #[cfg(unix)]
+        let truncated = op.msg_flags() & libc::MSG_TRUNC != 0;
+        let (mut buffer, addr) = op.into_inner();
+        let res = match res {
+            #[cfg(unix)]
+            Ok(received) => Ok(DatagramLen::new(received, capacity, truncated)),
+            #[cfg(windows)]
+            Ok(received) => Ok(DatagramLen::new(received, capacity, false)),
+            // the buffer is filled with the beginning of the datagram
+            #[cfg(windows)]
+            Err(e) if is_message_too_long(&e) => Ok(DatagramLen::new(capacity, capacity, true)),
+            Err(e) => Err(e),
+        };
+        if let Ok(len) = &res {
+            buffer.set_buf_init(len.copied);
+        }
+        (res.map(|len| (len, addr)), buffer)
+    }
+
     #[cfg(feature = "runtime")]
     pub async fn recv_from_vectored<T: IoBufMut<'static>>(
         &self,
         buffer: VectoredBufWrapper<'static, T>,
     ) -> BufResult<(usize, SockAddr), VectoredBufWrapper<'static, T>> {
-        let (fd, buffer) = buf_try!(self.attach(), buffer);
+        let ((fd, runtime), buffer) = buf_try!(self.attach_runtime(), buffer);
         let op = RecvFromVectored::new(fd, buffer);
-        RUNTIME
-            .with(|runtime| runtime.submit(op))
+        runtime
+            .submit(op)
             .await
             .into_inner()
             .map_addr()
@@ -227,12 +618,19 @@ impl Socket {
         buffer: T,
         addr: &SockAddr,
     ) -> BufResult<usize, T> {
-        let (fd, buffer) = buf_try!(self.attach(), buffer);
-        let op = SendTo::new(fd, buffer, addr.clone());
-        RUNTIME
-            .with(|runtime| runtime.submit(op))
-            .await
-            .into_inner()
+        self.send_to_with_flags(buffer, addr, 0).await
+    }
+
+    #[cfg(feature = "runtime")]
+    pub async fn send_to_with_flags<T: IoBuf<'static>>(
+        &self,
+        buffer: T,
+        addr: &SockAddr,
+        flags: i32,
+    ) -> BufResult<usize, T> {
+        let ((fd, runtime), buffer) = buf_try!(self.attach_runtime(), buffer);
+        let op = SendTo::new(fd, buffer, addr.clone()).with_flags(flags);
+        runtime.submit(op).await.into_inner()
     }
 
     #[cfg(feature = "runtime")]
@@ -241,12 +639,118 @@ impl Socket {
         buffer: VectoredBufWrapper<'static, T>,
         addr: &SockAddr,
     ) -> BufResult<usize, VectoredBufWrapper<'static, T>> {
-        let (fd, buffer) = buf_try!(self.attach(), buffer);
+        let ((fd, runtime), buffer) = buf_try!(self.attach_runtime(), buffer);
         let op = SendToVectored::new(fd, buffer, addr.clone());
-        RUNTIME
-            .with(|runtime| runtime.submit(op))
-            .await
-            .into_inner()
+        runtime.submit(op).await.into_inner()
+    }
+
+    /// Receive the datagrams with a chain of operations, the first one waits for a datagram and
+    /// the rest receive the queued ones with `MSG_DONTWAIT`.
+    #[cfg(feature = "runtime")]
+    pub async fn recv_from_batch<T: IoBufMut<'static>>(
+        &self,
+        buffers: Vec<T>,
+    ) -> BufResult<'static, Vec<(usize, SockAddr)>, Vec<T>> {
+        let ((fd, runtime), buffers) = buf_try!(self.attach_runtime(), buffers);
+        // IOCP always waits for a datagram, the rest of the buffers are not used
+        #[cfg(windows)]
+        let (buffers, unused) = {
+            let mut buffers = buffers;
+            let unused = buffers.split_off(buffers.len().min(1));
+            (buffers, unused)
+        };
+        #[cfg(unix)]
+        let unused = Vec::new();
+        let ops = buffers
+            .into_iter()
+            .enumerate()
+            .map(|(i, buffer)| {
+                let flags = if i == 0 { 0 } else { DONTWAIT };
+                RecvFrom::new(fd, buffer).with_flags(flags)
+            })
+            .collect();
+        let (res, mut buffers) = complete_batch(runtime.submit_linked(ops), |res| {
+            res.into_inner().map_addr().update_buffer_len()
+        })
+        .await;
+        buffers.extend(unused);
+        (res, buffers)
+    }
+
+    /// Send the datagrams with a chain of operations.
+    #[cfg(feature = "runtime")]
+    pub async fn send_to_batch<T: IoBuf<'static>>(
+        &self,
+        datagrams: Vec<(T, SockAddr)>,
+    ) -> BufResult<'static, Vec<usize>, Vec<(T, SockAddr)>> {
+        let ((fd, runtime), datagrams) = buf_try!(self.attach_runtime(), datagrams);
+        let (ops, addrs): (Vec<_>, Vec<_>) = datagrams
+            .into_iter()
+            .map(|(buffer, addr)| (SendTo::new(fd, buffer, addr.clone()), addr))
+            .unzip();
+        let mut addrs = addrs.into_iter();
+        complete_batch(runtime.submit_linked(ops), |res| {
+            let (res, buffer) = res.into_inner();
+            (
+                res,
+                (buffer, addrs.next().expect("an address per datagram")),
+            )
+        })
+        .await
+    }
+}
+
+// Linux reports the length of a truncated datagram
+#[cfg(all(any(target_os = "linux", target_os = "android"), feature = "runtime"))]
+const TRUNC: i32 = libc::MSG_TRUNC;
+#[cfg(all(
+    not(any(target_os = "linux", target_os = "android")),
+    feature = "runtime"
+))]
+const TRUNC: i32 = 0;
+
+// the overlapped receive fails with `ERROR_MORE_DATA`, the immediate one with `WSAEMSGSIZE`
+#[cfg(all(windows, feature = "runtime"))]
+fn is_message_too_long(e: &io::Error) -> bool {
+    use windows_sys::Win32::{Foundation::ERROR_MORE_DATA, Networking::WinSock::WSAEMSGSIZE};
+
+    matches!(e.raw_os_error(), Some(code) if code == ERROR_MORE_DATA as i32 || code == WSAEMSGSIZE)
+}
+
+// the successors of the first receive of a batch take only the queued datagrams, IOCP doesn't
+// chain the receives
+#[cfg(all(unix, feature = "runtime"))]
+const DONTWAIT: i32 = libc::MSG_DONTWAIT;
+#[cfg(all(windows, feature = "runtime"))]
+const DONTWAIT: i32 = 0;
+
+// Await the operations of a batch. The buffers of the completed operations come first, an error
+// is returned if none of them has completed.
+#[cfg(feature = "runtime")]
+async fn complete_batch<O: OpCode + 'static, R, B>(
+    futures: Vec<OpFuture<O>>,
+    mut complete: impl FnMut((io::Result<usize>, O)) -> BufResult<'static, R, B>,
+) -> BufResult<'static, Vec<R>, Vec<B>> {
+    let mut results = Vec::with_capacity(futures.len());
+    let mut buffers = Vec::with_capacity(futures.len());
+    let mut unused = Vec::new();
+    let mut error = None;
+    for future in futures {
+        match complete(future.await) {
+            (Ok(res), buffer) => {
+                results.push(res);
+                buffers.push(buffer);
+            }
+            (Err(e), buffer) => {
+                error.get_or_insert(e);
+                unused.push(buffer);
+            }
+        }
+    }
+    buffers.extend(unused);
+    match error {
+        Some(e) if results.is_empty() => (Err(e), buffers),
+        _ => (Ok(results), buffers),
     }
 }
 
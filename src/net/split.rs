@@ -0,0 +1,259 @@
+use std::{error::Error, fmt, io, net::Shutdown, rc::Rc};
+
+use socket2::SockAddr;
+
+use crate::net::TcpStream;
+#[cfg(feature = "runtime")]
+use crate::{
+    buf::{IoBuf, IoBufMut, VectoredBufWrapper},
+    fs::File,
+    BufResult,
+};
+
+// the methods shared by the borrowed and owned halves delegate to the stream
+macro_rules! impl_half_addrs {
+    () => {
+        /// Returns the socket address of the remote peer of the connection.
+        pub fn peer_addr(&self) -> io::Result<SockAddr> {
+            self.stream.peer_addr()
+        }
+
+        /// Returns the socket address of the local half of the connection.
+        pub fn local_addr(&self) -> io::Result<SockAddr> {
+            self.stream.local_addr()
+        }
+    };
+}
+
+macro_rules! impl_read_half {
+    () => {
+        impl_half_addrs!();
+
+        /// See [`TcpStream::recv`].
+        #[cfg(feature = "runtime")]
+        pub async fn recv<T: IoBufMut<'static>>(&self, buffer: T) -> BufResult<usize, T> {
+            self.stream.recv(buffer).await
+        }
+
+        /// See [`TcpStream::recv_with_flags`].
+        #[cfg(feature = "runtime")]
+        pub async fn recv_with_flags<T: IoBufMut<'static>>(
+            &self,
+            buffer: T,
+            flags: i32,
+        ) -> BufResult<usize, T> {
+            self.stream.recv_with_flags(buffer, flags).await
+        }
+
+        /// See [`TcpStream::peek`].
+        #[cfg(feature = "runtime")]
+        pub async fn peek<T: IoBufMut<'static>>(&self, buffer: T) -> BufResult<usize, T> {
+            self.stream.peek(buffer).await
+        }
+
+        /// See [`TcpStream::recv_oob`].
+        #[cfg(feature = "runtime")]
+        pub async fn recv_oob<T: IoBufMut<'static>>(&self, buffer: T) -> BufResult<usize, T> {
+            self.stream.recv_oob(buffer).await
+        }
+
+        /// See [`TcpStream::recv_exact`].
+        #[cfg(feature = "runtime")]
+        pub async fn recv_exact<T: IoBufMut<'static>>(&self, buffer: T) -> BufResult<usize, T> {
+            self.stream.recv_exact(buffer).await
+        }
+
+        /// See [`TcpStream::recv_vectored`].
+        #[cfg(feature = "runtime")]
+        pub async fn recv_vectored<T: IoBufMut<'static>>(
+            &self,
+            buffer: VectoredBufWrapper<'static, T>,
+        ) -> BufResult<usize, VectoredBufWrapper<'static, T>> {
+            self.stream.recv_vectored(buffer).await
+        }
+    };
+}
+
+macro_rules! impl_write_half {
+    () => {
+        impl_half_addrs!();
+
+        /// Shuts down the write half of the connection, the peer receives the end of the stream.
+        ///
+        /// The write half is not shut down when it's dropped.
+        pub fn shutdown(&self) -> io::Result<()> {
+            self.stream.shutdown(Shutdown::Write)
+        }
+
+        /// Shuts down the write half of the connection asynchronously.
+        #[cfg(feature = "runtime")]
+        pub async fn shutdown_async(&self) -> io::Result<()> {
+            self.stream.shutdown_async(Shutdown::Write).await
+        }
+
+        /// See [`TcpStream::send`].
+        #[cfg(feature = "runtime")]
+        pub async fn send<T: IoBuf<'static>>(&self, buffer: T) -> BufResult<usize, T> {
+            self.stream.send(buffer).await
+        }
+
+        /// See [`TcpStream::send_with_flags`].
+        #[cfg(feature = "runtime")]
+        pub async fn send_with_flags<T: IoBuf<'static>>(
+            &self,
+            buffer: T,
+            flags: i32,
+        ) -> BufResult<usize, T> {
+            self.stream.send_with_flags(buffer, flags).await
+        }
+
+        /// See [`TcpStream::send_oob`].
+        #[cfg(feature = "runtime")]
+        pub async fn send_oob(&self, byte: u8) -> io::Result<()> {
+            self.stream.send_oob(byte).await
+        }
+
+        /// See [`TcpStream::send_all`].
+        #[cfg(feature = "runtime")]
+        pub async fn send_all<T: IoBuf<'static>>(&self, buffer: T) -> BufResult<usize, T> {
+            self.stream.send_all(buffer).await
+        }
+
+        /// See [`TcpStream::send_zc`].
+        #[cfg(feature = "runtime")]
+        pub async fn send_zc<T: IoBuf<'static>>(&self, buffer: T) -> BufResult<usize, T> {
+            self.stream.send_zc(buffer).await
+        }
+
+        /// See [`TcpStream::send_zc_all`].
+        #[cfg(feature = "runtime")]
+        pub async fn send_zc_all<T: IoBuf<'static>>(&self, buffer: T) -> BufResult<usize, T> {
+            self.stream.send_zc_all(buffer).await
+        }
+
+        /// See [`TcpStream::send_file`].
+        #[cfg(feature = "runtime")]
+        pub async fn send_file(&self, file: &File, offset: u64, len: usize) -> io::Result<usize> {
+            self.stream.send_file(file, offset, len).await
+        }
+
+        /// See [`TcpStream::send_vectored`].
+        #[cfg(feature = "runtime")]
+        pub async fn send_vectored<T: IoBuf<'static>>(
+            &self,
+            buffer: VectoredBufWrapper<'static, T>,
+        ) -> BufResult<usize, VectoredBufWrapper<'static, T>> {
+            self.stream.send_vectored(buffer).await
+        }
+    };
+}
+
+/// The receiving half of a [`TcpStream`] borrowed by [`TcpStream::split`].
+pub struct ReadHalf<'a> {
+    stream: &'a TcpStream,
+}
+
+/// The sending half of a [`TcpStream`] borrowed by [`TcpStream::split`].
+pub struct WriteHalf<'a> {
+    stream: &'a TcpStream,
+}
+
+impl<'a> ReadHalf<'a> {
+    impl_read_half!();
+
+    pub(super) fn new(stream: &'a TcpStream) -> Self {
+        Self { stream }
+    }
+}
+
+impl<'a> WriteHalf<'a> {
+    impl_write_half!();
+
+    pub(super) fn new(stream: &'a TcpStream) -> Self {
+        Self { stream }
+    }
+}
+
+/// The receiving half of a [`TcpStream`] created by [`TcpStream::into_split`].
+///
+/// The halves share the socket, it's closed when both of them are dropped.
+pub struct OwnedReadHalf {
+    stream: Rc<TcpStream>,
+}
+
+/// The sending half of a [`TcpStream`] created by [`TcpStream::into_split`].
+///
+/// The halves share the socket, it's closed when both of them are dropped.
+pub struct OwnedWriteHalf {
+    stream: Rc<TcpStream>,
+}
+
+pub(super) fn into_split(stream: TcpStream) -> (OwnedReadHalf, OwnedWriteHalf) {
+    let stream = Rc::new(stream);
+    (
+        OwnedReadHalf {
+            stream: stream.clone(),
+        },
+        OwnedWriteHalf { stream },
+    )
+}
+
+impl OwnedReadHalf {
+    impl_read_half!();
+
+    /// Joins the halves back into the [`TcpStream`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the halves in [`ReuniteError`] if they are not created from the same stream.
+    pub fn reunite(self, other: OwnedWriteHalf) -> Result<TcpStream, ReuniteError> {
+        reunite(self, other)
+    }
+}
+
+impl OwnedWriteHalf {
+    impl_write_half!();
+
+    /// Joins the halves back into the [`TcpStream`], see [`OwnedReadHalf::reunite`].
+    pub fn reunite(self, other: OwnedReadHalf) -> Result<TcpStream, ReuniteError> {
+        reunite(other, self)
+    }
+}
+
+macro_rules! impl_as_ref {
+    ($($t:ty),*) => {
+        $(impl AsRef<TcpStream> for $t {
+            fn as_ref(&self) -> &TcpStream {
+                &self.stream
+            }
+        })*
+    };
+}
+
+impl_as_ref!(ReadHalf<'_>, WriteHalf<'_>, OwnedReadHalf, OwnedWriteHalf);
+
+fn reunite(read: OwnedReadHalf, write: OwnedWriteHalf) -> Result<TcpStream, ReuniteError> {
+    if !Rc::ptr_eq(&read.stream, &write.stream) {
+        return Err(ReuniteError(read, write));
+    }
+    drop(write);
+    Ok(Rc::into_inner(read.stream).expect("the halves are the only owners of the stream"))
+}
+
+/// An error returned by [`OwnedReadHalf::reunite`] when the halves are not created from the
+/// same stream.
+pub struct ReuniteError(pub OwnedReadHalf, pub OwnedWriteHalf);
+
+impl fmt::Debug for ReuniteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReuniteError").finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for ReuniteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("tried to reunite halves that are not from the same stream")
+    }
+}
+
+impl Error for ReuniteError {}
@@ -1,15 +1,36 @@
-use std::{io, net::Shutdown};
+#[cfg(feature = "runtime-time")]
+use std::net::SocketAddr;
+#[cfg(feature = "runtime")]
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use std::{io, net::Shutdown, time::Duration};
 
+#[cfg(feature = "runtime")]
+use futures_util::Stream;
+#[cfg(all(unix, feature = "runtime"))]
+use libc::MSG_OOB;
 use socket2::{Protocol, SockAddr, Type};
+#[cfg(all(windows, feature = "runtime"))]
+use windows_sys::Win32::Networking::WinSock::MSG_OOB;
 
 #[cfg(feature = "runtime")]
 use crate::{
     buf::{IoBuf, IoBufMut, VectoredBufWrapper},
+    buf_try,
+    fs::File,
     BufResult,
 };
+#[cfg(all(target_os = "linux", feature = "runtime"))]
+use crate::{driver::FromRawFd, op::AcceptMultishot, task::op::OpStream};
 use crate::{
     impl_raw_fd,
-    net::{Socket, ToSockAddrs},
+    net::{
+        BindOptions, KeepAlive, OwnedReadHalf, OwnedWriteHalf, ReadHalf, Socket, ToSockAddrs,
+        WriteHalf,
+    },
 };
 
 /// A TCP socket server, listening for connections.
@@ -65,15 +86,92 @@ impl TcpListener {
         })
     }
 
+    /// Creates a new `TcpListener` bound to the specified address with the options applied
+    /// before binding.
+    ///
+    /// ```
+    /// use completeio::net::{BindOptions, TcpListener};
+    ///
+    /// // the listeners share the incoming connections, e.g. one per thread
+    /// let first =
+    ///     TcpListener::bind_with_options("127.0.0.1:0", BindOptions::new().reuse_port(true)).unwrap();
+    /// # #[cfg(unix)]
+    /// let second = TcpListener::bind_with_options(
+    ///     first.local_addr().unwrap(),
+    ///     BindOptions::new().reuse_port(true),
+    /// )
+    /// .unwrap();
+    /// ```
+    pub fn bind_with_options(addr: impl ToSockAddrs, options: &BindOptions) -> io::Result<Self> {
+        super::each_addr(addr, |addr| {
+            let socket =
+                Socket::bind_with_options(&addr, Type::STREAM, Some(Protocol::TCP), options)?;
+            socket.listen(options.backlog)?;
+            Ok(Self { inner: socket })
+        })
+    }
+
+    /// Creates a new `TcpListener` bound to the specified address with the driver operations.
+    ///
+    /// It is the same as [`TcpListener::bind`] but the socket is set up without blocking
+    /// syscalls where the driver supports it.
+    #[cfg(feature = "runtime")]
+    pub async fn bind_async(addr: impl ToSockAddrs) -> io::Result<Self> {
+        super::each_addr_async(addr, |addr| async move {
+            let socket = Socket::bind_async(&addr, Type::STREAM, Some(Protocol::TCP)).await?;
+            socket.listen_async(128).await?;
+            Ok(Self { inner: socket })
+        })
+        .await
+    }
+
+    /// Creates new `TcpListener` from a standard library one, e.g. received with
+    /// the socket activation.
+    ///
+    /// The socket is switched to the blocking mode the driver of the platform
+    /// expects.
+    ///
+    /// ```
+    /// use completeio::net::TcpListener;
+    ///
+    /// let std_listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    /// let addr = std_listener.local_addr().unwrap();
+    /// let listener = TcpListener::from_std(std_listener).unwrap();
+    /// assert_eq!(listener.local_addr().unwrap().as_socket(), Some(addr));
+    /// ```
+    pub fn from_std(socket: std::net::TcpListener) -> io::Result<Self> {
+        Ok(Self {
+            inner: Socket::from_std(socket.into())?,
+        })
+    }
+
+    /// Converts the socket into a standard library one in blocking mode.
+    ///
+    /// The socket is detached from the driver, the conversion fails if the
+    /// driver still has canceled operations of the socket in flight.
+    pub fn into_std(self) -> io::Result<std::net::TcpListener> {
+        Ok(self.inner.into_std()?.into())
+    }
+
     /// Creates a new independently owned handle to the underlying socket.
     ///
-    /// It does not clear the attach state.
+    /// The new handle submits operations using its own duplicated socket, so
+    /// it stays usable after the original is closed.
     pub fn try_clone(&self) -> io::Result<Self> {
         Ok(Self {
             inner: self.inner.try_clone()?,
         })
     }
 
+    /// Close the socket through the driver.
+    ///
+    /// The socket is consumed, so no other operation can be submitted using
+    /// it while the close is in flight.
+    #[cfg(feature = "runtime")]
+    pub async fn close(self) -> io::Result<()> {
+        self.inner.close().await
+    }
+
     /// Accepts a new incoming connection from this listener.
     ///
     /// This function will yield once a new TCP connection is established. When
@@ -86,6 +184,39 @@ impl TcpListener {
         Ok((stream, addr))
     }
 
+    /// Returns a stream of incoming connections and the addresses of their
+    /// remote peers.
+    ///
+    /// The stream never ends. Dropping it stops accepting, the connections
+    /// accepted but not yet received are closed.
+    ///
+    /// ```
+    /// use completeio::net::{TcpListener, TcpStream};
+    /// use futures_util::StreamExt;
+    ///
+    /// completeio::task::block_on(async {
+    ///     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    ///     let addr = listener.local_addr().unwrap();
+    ///     let client = TcpStream::connect(&addr).await.unwrap();
+    ///     let (_stream, peer) = listener.incoming().next().await.unwrap().unwrap();
+    ///     assert_eq!(peer.as_socket(), client.local_addr().unwrap().as_socket());
+    /// })
+    /// ```
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: multishot `IORING_OP_ACCEPT` operation if the kernel supports it,
+    ///   [`accept`](Self::accept) in a loop otherwise. At most 64 connections are accepted ahead of
+    ///   the stream, the operation is submitted again after they are received.
+    /// * kqueue and IOCP: [`accept`](Self::accept) in a loop.
+    #[cfg(feature = "runtime")]
+    pub fn incoming(&self) -> Incoming<'_> {
+        Incoming {
+            listener: self,
+            state: IncomingState::Idle,
+        }
+    }
+
     /// Returns the local address that this listener is bound to.
     ///
     /// This can be useful, for example, when binding to port 0 to
@@ -113,10 +244,173 @@ impl TcpListener {
     pub fn local_addr(&self) -> io::Result<SockAddr> {
         self.inner.local_addr()
     }
+
+    /// Sets the value of the `TCP_NODELAY` option on the listening socket.
+    ///
+    /// See [`TcpStream::set_nodelay`] for the details.
+    pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        self.inner.set_nodelay(nodelay)
+    }
+
+    /// Gets the value of the `TCP_NODELAY` option on the listening socket.
+    pub fn nodelay(&self) -> io::Result<bool> {
+        self.inner.nodelay()
+    }
+
+    /// Sets the value for the `IP_TTL` option on this socket.
+    ///
+    /// This value sets the time-to-live field that is used in every packet sent
+    /// from this socket.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.inner.set_ttl(ttl)
+    }
+
+    /// Gets the value of the `IP_TTL` option for this socket.
+    ///
+    /// See [`set_ttl`](Self::set_ttl) for the details.
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.inner.ttl()
+    }
+
+    /// Gets the value of the `IPV6_V6ONLY` option for this socket.
+    ///
+    /// See [`BindOptions::only_v6`] for setting it before the socket is bound.
+    pub fn only_v6(&self) -> io::Result<bool> {
+        self.inner.only_v6()
+    }
+
+    /// Binds the socket to the network interface named `device`, or removes the binding with
+    /// `None`, the `SO_BINDTODEVICE` option.
+    ///
+    /// See [`BindOptions::device`] for the platform support, the option is set before the
+    /// socket is bound there.
+    pub fn set_device(&self, device: Option<&str>) -> io::Result<()> {
+        self.inner.set_device(device)
+    }
+
+    /// Gets the name of the network interface the socket is bound to.
+    pub fn device(&self) -> io::Result<Option<String>> {
+        self.inner.device()
+    }
+
+    /// Sets the value of the `IP_FREEBIND` option for this socket.
+    ///
+    /// See [`BindOptions::freebind`] for the platform support.
+    pub fn set_freebind(&self, freebind: bool) -> io::Result<()> {
+        self.inner.set_freebind(freebind)
+    }
+
+    /// Gets the value of the `IP_FREEBIND` option for this socket.
+    pub fn freebind(&self) -> io::Result<bool> {
+        self.inner.freebind()
+    }
+
+    /// Enables TCP Fast Open on the listener, the `TCP_FASTOPEN` option. `queue_len` limits the
+    /// number of the connections whose handshake is not completed but the data of the `SYN`
+    /// is accepted.
+    ///
+    /// The data of the clients is accepted only if the server side of TCP Fast Open is enabled
+    /// by `net.ipv4.tcp_fastopen`, see [`TcpStream::connect_with_data`] for the client side.
+    ///
+    /// ## Platform specific
+    ///
+    /// * Linux and Android: supported.
+    /// * Others: returns [`io::ErrorKind::Unsupported`].
+    pub fn set_tcp_fastopen(&self, queue_len: u32) -> io::Result<()> {
+        self.inner.set_tcp_fastopen(queue_len)
+    }
+
+    /// Delays the accept of the connections till their first data arrives or `timeout` passes,
+    /// the `TCP_DEFER_ACCEPT` option. The timeout is rounded up to seconds, zero disables the
+    /// option.
+    ///
+    /// ## Platform specific
+    ///
+    /// * Linux and Android: supported.
+    /// * Others: returns [`io::ErrorKind::Unsupported`].
+    pub fn set_defer_accept(&self, timeout: Duration) -> io::Result<()> {
+        self.inner.set_defer_accept(timeout)
+    }
+
+    /// Gets the value of the `SO_ERROR` option on this socket, clearing it.
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.inner.take_error()
+    }
 }
 
 impl_raw_fd!(TcpListener, inner);
 
+/// A stream of connections accepted by a [`TcpListener`].
+///
+/// It is created by [`TcpListener::incoming`].
+#[cfg(feature = "runtime")]
+pub struct Incoming<'a> {
+    listener: &'a TcpListener,
+    state: IncomingState<'a>,
+}
+
+// the connections accepted by the multishot operation and not received by the stream
+#[cfg(all(target_os = "linux", feature = "runtime"))]
+const INCOMING_CAPACITY: usize = 64;
+
+#[cfg(feature = "runtime")]
+type AcceptFuture<'a> = Pin<Box<dyn Future<Output = io::Result<(TcpStream, SockAddr)>> + 'a>>;
+
+#[cfg(feature = "runtime")]
+enum IncomingState<'a> {
+    Idle,
+    #[cfg(target_os = "linux")]
+    Multishot(OpStream<AcceptMultishot>),
+    Accept(AcceptFuture<'a>),
+}
+
+#[cfg(feature = "runtime")]
+impl Stream for Incoming<'_> {
+    type Item = io::Result<(TcpStream, SockAddr)>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match &mut this.state {
+                IncomingState::Idle => {
+                    #[cfg(target_os = "linux")]
+                    match this.listener.inner.accept_multishot(INCOMING_CAPACITY) {
+                        Ok(Some(stream)) => {
+                            this.state = IncomingState::Multishot(stream);
+                            continue;
+                        }
+                        Ok(None) => {}
+                        Err(e) => return Poll::Ready(Some(Err(e))),
+                    }
+                    this.state = IncomingState::Accept(Box::pin(this.listener.accept()));
+                }
+                #[cfg(target_os = "linux")]
+                IncomingState::Multishot(stream) => {
+                    match ready!(Pin::new(stream).poll_next(cx)) {
+                        Some(res) => {
+                            let res = res.and_then(|fd| {
+                                // SAFETY: the accepted socket is owned by no one else
+                                let stream = unsafe { TcpStream::from_raw_fd(fd as _) };
+                                let addr = stream.peer_addr()?;
+                                Ok((stream, addr))
+                            });
+                            return Poll::Ready(Some(res));
+                        }
+                        // the kernel or the full buffer stopped accepting, submit the operation
+                        // again
+                        None => this.state = IncomingState::Idle,
+                    }
+                }
+                IncomingState::Accept(accept) => {
+                    let res = ready!(accept.as_mut().poll(cx));
+                    this.state = IncomingState::Idle;
+                    return Poll::Ready(Some(res));
+                }
+            }
+        }
+    }
+}
+
 /// A TCP stream between a local and a remote socket.
 ///
 /// A TCP stream can either be created by connecting to an endpoint, via the
@@ -144,8 +438,179 @@ pub struct TcpStream {
 
 impl TcpStream {
     /// Opens a TCP connection to a remote host.
+    ///
+    /// A host name is resolved with [`lookup_host`](crate::net::lookup_host), the addresses are
+    /// tried one after another.
+    ///
+    /// Dropping the future cancels the connect in flight and closes the socket, so it can be
+    /// wrapped in [`time::timeout`](crate::time::timeout).
     #[cfg(feature = "runtime")]
     pub async fn connect(addr: impl ToSockAddrs) -> io::Result<Self> {
+        Self::connect_impl(addr, None).await
+    }
+
+    /// Opens a TCP connection to a remote host, failing with [`io::ErrorKind::TimedOut`] if
+    /// it's not established within `timeout`.
+    ///
+    /// The timeout is linked to the connect operation and applies to each of the resolved
+    /// addresses. The socket of the timed out connect is closed.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use completeio::net::{TcpListener, TcpStream};
+    ///
+    /// completeio::task::block_on(async {
+    ///     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    ///     let addr = listener.local_addr().unwrap();
+    ///     let (stream, _) = futures_util::try_join!(
+    ///         TcpStream::connect_timeout(&addr, Duration::from_secs(1)),
+    ///         listener.accept()
+    ///     )
+    ///     .unwrap();
+    ///     assert_eq!(stream.peer_addr().unwrap(), addr);
+    /// })
+    /// ```
+    #[cfg(feature = "runtime")]
+    pub async fn connect_timeout(addr: impl ToSockAddrs, timeout: Duration) -> io::Result<Self> {
+        Self::connect_impl(addr, Some(timeout)).await
+    }
+
+    /// Opens a TCP connection to the first of the addresses to accept it, racing the connects
+    /// like Happy Eyeballs of [RFC 8305](https://www.rfc-editor.org/rfc/rfc8305).
+    ///
+    /// The addresses are interleaved by family, starting with the family of the first one. A
+    /// connect is started every `attempt_delay` or as soon as the previous one fails, so an
+    /// unreachable address doesn't hold up the rest. When a connect succeeds the others are
+    /// cancelled and their sockets are closed. The error of the last failed connect is returned
+    /// if none of them succeeds.
+    ///
+    /// RFC 8305 recommends the delay of 250 milliseconds.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use completeio::net::{TcpListener, TcpStream};
+    ///
+    /// completeio::task::block_on(async {
+    ///     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    ///     let addr = listener.local_addr().unwrap().as_socket().unwrap();
+    ///     let unreachable = "[100::1]:80".parse().unwrap();
+    ///     let (stream, _) = futures_util::try_join!(
+    ///         TcpStream::connect_happy([unreachable, addr], Duration::from_millis(250)),
+    ///         listener.accept()
+    ///     )
+    ///     .unwrap();
+    ///     assert_eq!(stream.peer_addr().unwrap().as_socket(), Some(addr));
+    /// })
+    /// ```
+    #[cfg(feature = "runtime-time")]
+    pub async fn connect_happy(
+        addrs: impl IntoIterator<Item = SocketAddr>,
+        attempt_delay: Duration,
+    ) -> io::Result<Self> {
+        use futures_util::{
+            future::{select, Either},
+            stream::FuturesUnordered,
+            StreamExt,
+        };
+
+        let mut addrs = interleave_families(addrs).into_iter();
+        let mut attempts = FuturesUnordered::new();
+        let mut last_err = None;
+        loop {
+            if let Some(addr) = addrs.next() {
+                attempts.push(Self::connect_impl(addr, None));
+            }
+            let res = if addrs.len() > 0 {
+                let delay = std::pin::pin!(crate::time::sleep(attempt_delay));
+                match select(attempts.next(), delay).await {
+                    Either::Left((res, _)) => res,
+                    // the connects in flight are slow, the next one is started
+                    Either::Right(_) => continue,
+                }
+            } else {
+                attempts.next().await
+            };
+            match res {
+                // dropping the rest of the attempts cancels them
+                Some(Ok(stream)) => return Ok(stream),
+                Some(Err(e)) => last_err = Some(e),
+                None => break,
+            }
+        }
+        Err(last_err.unwrap_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "no addresses to connect to")
+        }))
+    }
+
+    /// Opens a TCP connection to a remote host, sending the data of the buffer with the
+    /// handshake. Returns the stream and the original buffer with the quantity of data sent.
+    ///
+    /// The data is sent in the `SYN` with TCP Fast Open if the client side of it is enabled
+    /// and the server has given the cookie to the host before. Otherwise the data is sent once
+    /// the connection is established, the whole buffer may not be sent in either case.
+    ///
+    /// ```
+    /// use completeio::net::{TcpListener, TcpStream};
+    ///
+    /// completeio::task::block_on(async {
+    ///     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    ///     let addr = listener.local_addr().unwrap();
+    ///     let connect = async {
+    ///         let (res, _) = TcpStream::connect_with_data(&addr, "hello").await;
+    ///         res
+    ///     };
+    ///     let ((_client, sent), (server, _)) =
+    ///         futures_util::try_join!(connect, listener.accept()).unwrap();
+    ///     let (res, buf) = server.recv_exact(Vec::with_capacity(sent)).await;
+    ///     res.unwrap();
+    ///     assert_eq!(buf, &b"hello"[..sent]);
+    /// })
+    /// ```
+    ///
+    /// ## Platform specific
+    ///
+    /// * Linux and Android: `sendmsg` with `MSG_FASTOPEN`, it falls back to
+    ///   [`connect`](Self::connect) and [`send`](Self::send) if TCP Fast Open is disabled.
+    /// * Others: [`connect`](Self::connect) and [`send`](Self::send).
+    #[cfg(feature = "runtime")]
+    pub async fn connect_with_data<T: IoBuf<'static>>(
+        addr: impl ToSockAddrs,
+        buffer: T,
+    ) -> BufResult<'static, (Self, usize), T> {
+        super::each_addr_async_buf(addr, buffer, |addr, buffer| async move {
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            let buffer = {
+                let (socket, buffer) = buf_try!(
+                    Socket::new(addr.domain(), Type::STREAM, Some(Protocol::TCP)),
+                    buffer
+                );
+                let (res, buffer) = socket
+                    .send_to_with_flags(buffer, &addr, libc::MSG_FASTOPEN)
+                    .await;
+                match res {
+                    Ok(sent) => return (Ok((Self { inner: socket }, sent)), buffer),
+                    // the handshake requests the cookie of the server, the data isn't sent
+                    Err(e) if e.raw_os_error() == Some(libc::EINPROGRESS) => {
+                        let stream = Self { inner: socket };
+                        let (res, buffer) = stream.send(buffer).await;
+                        return (res.map(|sent| (stream, sent)), buffer);
+                    }
+                    // the client side is disabled by net.ipv4.tcp_fastopen
+                    Err(e) if e.raw_os_error() == Some(libc::EOPNOTSUPP) => buffer,
+                    Err(e) => return (Err(e), buffer),
+                }
+            };
+            let (stream, buffer) = buf_try!(Self::connect_impl(addr, None).await, buffer);
+            let (res, buffer) = stream.send(buffer).await;
+            (res.map(|sent| (stream, sent)), buffer)
+        })
+        .await
+    }
+
+    #[cfg(feature = "runtime")]
+    async fn connect_impl(addr: impl ToSockAddrs, timeout: Option<Duration>) -> io::Result<Self> {
         use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
 
         super::each_addr_async(addr, |addr| async move {
@@ -164,21 +629,89 @@ impl TcpStream {
             } else {
                 Socket::new(addr.domain(), Type::STREAM, Some(Protocol::TCP))?
             };
-            socket.connect_async(&addr).await?;
+            socket.connect_async(&addr, timeout).await?;
             Ok(Self { inner: socket })
         })
         .await
     }
 
+    /// Creates new `TcpStream` from a standard library one.
+    ///
+    /// The socket is switched to the blocking mode the driver of the platform
+    /// expects.
+    pub fn from_std(stream: std::net::TcpStream) -> io::Result<Self> {
+        Ok(Self {
+            inner: Socket::from_std(stream.into())?,
+        })
+    }
+
+    /// Converts the socket into a standard library one in blocking mode.
+    ///
+    /// See [`TcpListener::into_std`].
+    pub fn into_std(self) -> io::Result<std::net::TcpStream> {
+        Ok(self.inner.into_std()?.into())
+    }
+
     /// Creates a new independently owned handle to the underlying socket.
     ///
-    /// It does not clear the attach state.
+    /// The new handle submits operations using its own duplicated socket, so
+    /// it stays usable after the original is closed.
     pub fn try_clone(&self) -> io::Result<Self> {
         Ok(Self {
             inner: self.inner.try_clone()?,
         })
     }
 
+    /// Splits the stream into the borrowed halves to receive and send concurrently.
+    pub fn split(&self) -> (ReadHalf<'_>, WriteHalf<'_>) {
+        (ReadHalf::new(self), WriteHalf::new(self))
+    }
+
+    /// Splits the stream into the owned halves, e.g. to receive and send in different
+    /// tasks.
+    ///
+    /// Unlike [`try_clone`](Self::try_clone), the halves share the socket and its attachment
+    /// to the driver. The halves are joined back with [`OwnedReadHalf::reunite`].
+    ///
+    /// ```
+    /// use completeio::net::{TcpListener, TcpStream};
+    ///
+    /// completeio::task::block_on(async {
+    ///     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    ///     let stream = TcpStream::connect(listener.local_addr().unwrap())
+    ///         .await
+    ///         .unwrap();
+    ///     let (server, _) = listener.accept().await.unwrap();
+    ///
+    ///     let (reader, writer) = stream.into_split();
+    ///     let writer = completeio::task::spawn(async move {
+    ///         writer.send_all("ping").await.0.unwrap();
+    ///         writer
+    ///     });
+    ///     let (res, buffer) = server.recv_exact(Vec::with_capacity(4)).await;
+    ///     res.unwrap();
+    ///     server.send_all(buffer).await.0.unwrap();
+    ///     let (res, buffer) = reader.recv_exact(Vec::with_capacity(4)).await;
+    ///     res.unwrap();
+    ///     assert_eq!(buffer, b"ping");
+    ///
+    ///     let writer = writer.await.unwrap();
+    ///     let _stream = reader.reunite(writer).unwrap();
+    /// })
+    /// ```
+    pub fn into_split(self) -> (OwnedReadHalf, OwnedWriteHalf) {
+        super::split::into_split(self)
+    }
+
+    /// Close the socket through the driver.
+    ///
+    /// The socket is consumed, so no other operation can be submitted using
+    /// it while the close is in flight.
+    #[cfg(feature = "runtime")]
+    pub async fn close(self) -> io::Result<()> {
+        self.inner.close().await
+    }
+
     /// Returns the socket address of the remote peer of this TCP connection.
     pub fn peer_addr(&self) -> io::Result<SockAddr> {
         self.inner.peer_addr()
@@ -189,6 +722,211 @@ impl TcpStream {
         self.inner.local_addr()
     }
 
+    /// Sets the value of the `TCP_NODELAY` option on this socket.
+    ///
+    /// If set, this option disables the Nagle algorithm. This means that
+    /// segments are always sent as soon as possible, even if there is only a
+    /// small amount of data. When not set, data is buffered until there is a
+    /// sufficient amount to send out, thereby avoiding the frequent sending of
+    /// small packets.
+    ///
+    /// The option is set on the socket itself, so it can be changed before or
+    /// after the stream is used with the driver.
+    pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        self.inner.set_nodelay(nodelay)
+    }
+
+    /// Gets the value of the `TCP_NODELAY` option on this socket.
+    ///
+    /// See [`set_nodelay`](Self::set_nodelay) for the details.
+    pub fn nodelay(&self) -> io::Result<bool> {
+        self.inner.nodelay()
+    }
+
+    /// Sets the value of the `SO_OOBINLINE` option on this socket.
+    ///
+    /// If set, the urgent data is received in the normal data stream rather than with
+    /// [`recv_oob`](Self::recv_oob).
+    pub fn set_oob_inline(&self, oob_inline: bool) -> io::Result<()> {
+        self.inner.set_oob_inline(oob_inline)
+    }
+
+    /// Gets the value of the `SO_OOBINLINE` option on this socket.
+    pub fn oob_inline(&self) -> io::Result<bool> {
+        self.inner.oob_inline()
+    }
+
+    /// Sets the value for the `IP_TTL` option on this socket.
+    ///
+    /// This value sets the time-to-live field that is used in every packet sent
+    /// from this socket.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.inner.set_ttl(ttl)
+    }
+
+    /// Gets the value of the `IP_TTL` option for this socket.
+    ///
+    /// See [`set_ttl`](Self::set_ttl) for the details.
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.inner.ttl()
+    }
+
+    /// Enables TCP keepalive with the parameters, or disables it with `None`.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use completeio::net::{KeepAlive, TcpListener, TcpStream};
+    ///
+    /// completeio::task::block_on(async {
+    ///     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    ///     let stream = TcpStream::connect(listener.local_addr().unwrap())
+    ///         .await
+    ///         .unwrap();
+    ///     stream
+    ///         .set_keepalive(Some(KeepAlive {
+    ///             time: Some(Duration::from_secs(60)),
+    ///             ..Default::default()
+    ///         }))
+    ///         .unwrap();
+    ///     assert!(stream.keepalive().unwrap().is_some());
+    /// })
+    /// ```
+    pub fn set_keepalive(&self, keepalive: Option<KeepAlive>) -> io::Result<()> {
+        self.inner.set_keepalive(keepalive)
+    }
+
+    /// Gets the keepalive parameters applied by the OS, `None` if keepalive is disabled.
+    ///
+    /// The parameters which can't be read on the platform are `None`, see [`KeepAlive`].
+    pub fn keepalive(&self) -> io::Result<Option<KeepAlive>> {
+        self.inner.keepalive()
+    }
+
+    /// Sets the value of the `SO_LINGER` option on this socket.
+    ///
+    /// With the timeout set, closing the socket blocks till the pending data is sent or the
+    /// timeout expires.
+    pub fn set_linger(&self, linger: Option<Duration>) -> io::Result<()> {
+        self.inner.set_linger(linger)
+    }
+
+    /// Gets the value of the `SO_LINGER` option on this socket.
+    pub fn linger(&self) -> io::Result<Option<Duration>> {
+        self.inner.linger()
+    }
+
+    /// Gets the value of the `SO_ERROR` option on this socket, clearing it.
+    ///
+    /// It returns the error deferred by the socket, e.g. of a failed
+    /// non-blocking connect.
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.inner.take_error()
+    }
+
+    /// Sets the value of the `IP_TOS` option on this IPv4 socket.
+    ///
+    /// The value is the type-of-service byte of the sent packets, the DSCP and ECN bits.
+    pub fn set_tos(&self, tos: u32) -> io::Result<()> {
+        self.inner.set_tos(tos)
+    }
+
+    /// Gets the value of the `IP_TOS` option for this socket.
+    pub fn tos(&self) -> io::Result<u32> {
+        self.inner.tos()
+    }
+
+    /// Sets the value of the `SO_RCVBUF` option on this socket.
+    ///
+    /// The kernel could adjust the size, e.g. Linux doubles it for the bookkeeping overhead.
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.inner.set_recv_buffer_size(size)
+    }
+
+    /// Gets the value of the `SO_RCVBUF` option on this socket.
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        self.inner.recv_buffer_size()
+    }
+
+    /// Sets the value of the `SO_SNDBUF` option on this socket.
+    ///
+    /// See [`set_recv_buffer_size`](Self::set_recv_buffer_size) for the adjusted size.
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.inner.set_send_buffer_size(size)
+    }
+
+    /// Gets the value of the `SO_SNDBUF` option on this socket.
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        self.inner.send_buffer_size()
+    }
+
+    /// Sets the value of the `TCP_USER_TIMEOUT` option on this socket, the time the sent data
+    /// could stay unacknowledged before the connection is dropped. `None` restores the default
+    /// of the system.
+    ///
+    /// The option is supported on Linux and Android, other platforms return
+    /// [`io::ErrorKind::Unsupported`].
+    pub fn set_user_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.inner.set_user_timeout(timeout)
+    }
+
+    /// Gets the value of the `TCP_USER_TIMEOUT` option on this socket.
+    pub fn user_timeout(&self) -> io::Result<Option<Duration>> {
+        self.inner.user_timeout()
+    }
+
+    /// Sets the socket option `name` of the protocol `level` to `value`, the options without
+    /// a wrapper could be set this way.
+    ///
+    /// The size of `T` is passed as the length of the value.
+    ///
+    /// # Safety
+    ///
+    /// `T` should have the layout the OS expects for the option, e.g. `c_int` for most of the
+    /// integer and boolean options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use completeio::net::{TcpListener, TcpStream};
+    ///
+    /// completeio::task::block_on(async {
+    ///     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    ///     let stream = TcpStream::connect(listener.local_addr().unwrap())
+    ///         .await
+    ///         .unwrap();
+    ///     # #[cfg(unix)]
+    ///     # {
+    ///     // SO_KEEPALIVE
+    ///     unsafe {
+    ///         stream
+    ///             .set_raw_sockopt(libc::SOL_SOCKET, libc::SO_KEEPALIVE, &1)
+    ///             .unwrap();
+    ///         let keepalive: libc::c_int = stream
+    ///             .get_raw_sockopt(libc::SOL_SOCKET, libc::SO_KEEPALIVE)
+    ///             .unwrap();
+    ///         assert_eq!(keepalive, 1);
+    ///     }
+    ///     # }
+    /// })
+    /// ```
+    pub unsafe fn set_raw_sockopt<T>(&self, level: i32, name: i32, value: &T) -> io::Result<()> {
+        self.inner.set_raw_sockopt(level, name, value)
+    }
+
+    /// Gets the socket option `name` of the protocol `level`.
+    ///
+    /// Fails with [`io::ErrorKind::InvalidData`] if the length of the value returned by the OS
+    /// differs from the size of `T`.
+    ///
+    /// # Safety
+    ///
+    /// `T` should have the layout the OS uses for the option, any bit pattern of its size
+    /// should be valid.
+    pub unsafe fn get_raw_sockopt<T>(&self, level: i32, name: i32) -> io::Result<T> {
+        self.inner.get_raw_sockopt(level, name)
+    }
+
     /// Shuts down the read, write, or both halves of this connection.
     ///
     /// This function will cause all pending and future I/O on the specified
@@ -198,6 +936,15 @@ impl TcpStream {
         self.inner.shutdown(how)
     }
 
+    /// Shuts down the read, write, or both halves of this connection
+    /// asynchronously.
+    ///
+    /// See [`shutdown`](Self::shutdown) for the details.
+    #[cfg(feature = "runtime")]
+    pub async fn shutdown_async(&self, how: Shutdown) -> io::Result<()> {
+        self.inner.shutdown_async(how).await
+    }
+
     /// Receives a packet of data from the socket into the buffer, returning the
     /// original buffer and quantity of data received.
     #[cfg(feature = "runtime")]
@@ -205,6 +952,38 @@ impl TcpStream {
         self.inner.recv(buffer).await
     }
 
+    /// Receives data with `MSG_*` flags like `MSG_WAITALL`.
+    #[cfg(feature = "runtime")]
+    pub async fn recv_with_flags<T: IoBufMut<'static>>(
+        &self,
+        buffer: T,
+        flags: i32,
+    ) -> BufResult<usize, T> {
+        self.inner.recv_with_flags(buffer, flags).await
+    }
+
+    /// Receives data without removing it from the queue, so the next receive returns the same
+    /// data.
+    #[cfg(feature = "runtime")]
+    pub async fn peek<T: IoBufMut<'static>>(&self, buffer: T) -> BufResult<usize, T> {
+        #[cfg(unix)]
+        use libc::MSG_PEEK;
+        #[cfg(windows)]
+        use windows_sys::Win32::Networking::WinSock::MSG_PEEK;
+
+        self.inner.recv_with_flags(buffer, MSG_PEEK).await
+    }
+
+    /// Receives the urgent byte sent with `MSG_OOB`.
+    ///
+    /// The operation fails if no urgent data is pending, or if it's received inline, see
+    /// [`set_oob_inline`](Self::set_oob_inline). The urgent byte is discarded when the data
+    /// sent after it is received.
+    #[cfg(feature = "runtime")]
+    pub async fn recv_oob<T: IoBufMut<'static>>(&self, buffer: T) -> BufResult<usize, T> {
+        self.inner.recv_with_flags(buffer, MSG_OOB).await
+    }
+
     /// Receives exact number of bytes from the socket.
     #[cfg(feature = "runtime")]
     pub async fn recv_exact<T: IoBufMut<'static>>(&self, buffer: T) -> BufResult<usize, T> {
@@ -228,12 +1007,64 @@ impl TcpStream {
         self.inner.send(buffer).await
     }
 
+    /// Sends data with `MSG_*` flags like `MSG_MORE` or `MSG_NOSIGNAL`.
+    #[cfg(feature = "runtime")]
+    pub async fn send_with_flags<T: IoBuf<'static>>(
+        &self,
+        buffer: T,
+        flags: i32,
+    ) -> BufResult<usize, T> {
+        self.inner.send_with_flags(buffer, flags).await
+    }
+
+    /// Sends the byte as the urgent data with `MSG_OOB`.
+    ///
+    /// The peer receives it with [`recv_oob`](Self::recv_oob) or inline with the rest of the
+    /// data. Only one urgent byte is kept, the pending one is replaced by the next.
+    #[cfg(feature = "runtime")]
+    pub async fn send_oob(&self, byte: u8) -> io::Result<()> {
+        let (res, _) = self.inner.send_with_flags(vec![byte], MSG_OOB).await;
+        res.map(|_| ())
+    }
+
     /// Sends all data to the socket.
     #[cfg(feature = "runtime")]
     pub async fn send_all<T: IoBuf<'static>>(&self, buffer: T) -> BufResult<usize, T> {
         self.inner.send_all(buffer).await
     }
 
+    /// Sends some data to the socket from the buffer without copying it into
+    /// the kernel, returning the original buffer and quantity of data sent.
+    ///
+    /// The buffer is returned only after the kernel releases it.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: `IORING_OP_SEND_ZC` operation is used if the kernel supports it, otherwise it is
+    ///   the same as [`send`](Self::send).
+    /// * kqueue/IOCP: the same as [`send`](Self::send).
+    #[cfg(feature = "runtime")]
+    pub async fn send_zc<T: IoBuf<'static>>(&self, buffer: T) -> BufResult<usize, T> {
+        self.inner.send_zc(buffer).await
+    }
+
+    /// Sends all data to the socket without copying it into the kernel.
+    ///
+    /// See [`send_zc`](Self::send_zc) for the details.
+    #[cfg(feature = "runtime")]
+    pub async fn send_zc_all<T: IoBuf<'static>>(&self, buffer: T) -> BufResult<usize, T> {
+        self.inner.send_zc_all(buffer).await
+    }
+
+    /// Sends `len` bytes of the file starting from `offset` without copying
+    /// them through a user space buffer, returning the number of bytes sent.
+    ///
+    /// Fewer than `len` bytes are sent only if the end of file is reached.
+    #[cfg(feature = "runtime")]
+    pub async fn send_file(&self, file: &File, offset: u64, len: usize) -> io::Result<usize> {
+        self.inner.send_file(file, offset, len).await
+    }
+
     /// Sends some data to the socket from the buffer, returning the original
     /// buffer and quantity of data sent.
     #[cfg(feature = "runtime")]
@@ -246,3 +1077,21 @@ impl TcpStream {
 }
 
 impl_raw_fd!(TcpStream, inner);
+
+// RFC 8305 alternates the address families, starting with the family of the first address
+#[cfg(feature = "runtime-time")]
+fn interleave_families(addrs: impl IntoIterator<Item = SocketAddr>) -> Vec<SocketAddr> {
+    let addrs = addrs.into_iter().collect::<Vec<_>>();
+    let len = addrs.len();
+    let first_ipv6 = addrs.first().is_some_and(SocketAddr::is_ipv6);
+    let (first, second): (Vec<_>, Vec<_>) = addrs
+        .into_iter()
+        .partition(|addr| addr.is_ipv6() == first_ipv6);
+    let (mut first, mut second) = (first.into_iter(), second.into_iter());
+    let mut interleaved = Vec::with_capacity(len);
+    while interleaved.len() < len {
+        interleaved.extend(first.next());
+        interleaved.extend(second.next());
+    }
+    interleaved
+}
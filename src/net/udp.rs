@@ -9,7 +9,7 @@ use crate::{
 };
 use crate::{
     impl_raw_fd,
-    net::{Socket, ToSockAddrs},
+    net::{BindOptions, Socket, ToSockAddrs},
 };
 
 /// A UDP socket.
@@ -109,6 +109,18 @@ impl UdpSocket {
         })
     }
 
+    /// Creates a new UDP socket bound to the addr provided with the options applied before
+    /// binding.
+    ///
+    /// The backlog of the options is ignored.
+    pub fn bind_with_options(addr: impl ToSockAddrs, options: &BindOptions) -> io::Result<Self> {
+        super::each_addr(addr, |addr| {
+            Ok(Self {
+                inner: Socket::bind_with_options(&addr, Type::DGRAM, Some(Protocol::UDP), options)?,
+            })
+        })
+    }
+
     /// Connects this UDP socket to a remote address, allowing the `send` and
     /// `recv` to be used to send data and also applies filters to only
     /// receive data from the specified address.
@@ -120,15 +132,42 @@ impl UdpSocket {
         super::each_addr(addr, |addr| self.inner.connect(&addr))
     }
 
+    /// Creates new `UdpSocket` from a standard library one.
+    ///
+    /// The socket is switched to the blocking mode the driver of the platform
+    /// expects.
+    pub fn from_std(socket: std::net::UdpSocket) -> io::Result<Self> {
+        Ok(Self {
+            inner: Socket::from_std(socket.into())?,
+        })
+    }
+
+    /// Converts the socket into a standard library one in blocking mode.
+    ///
+    /// See [`TcpListener::into_std`](crate::net::TcpListener::into_std).
+    pub fn into_std(self) -> io::Result<std::net::UdpSocket> {
+        Ok(self.inner.into_std()?.into())
+    }
+
     /// Creates a new independently owned handle to the underlying socket.
     ///
-    /// It does not clear the attach state.
+    /// The new handle submits operations using its own duplicated socket, so
+    /// it stays usable after the original is closed.
     pub fn try_clone(&self) -> io::Result<Self> {
         Ok(Self {
             inner: self.inner.try_clone()?,
         })
     }
 
+    /// Close the socket through the driver.
+    ///
+    /// The socket is consumed, so no other operation can be submitted using
+    /// it while the close is in flight.
+    #[cfg(feature = "runtime")]
+    pub async fn close(self) -> io::Result<()> {
+        self.inner.close().await
+    }
+
     /// Returns the socket address of the remote peer this socket was connected
     /// to.
     ///
@@ -176,6 +215,93 @@ impl UdpSocket {
         self.inner.local_addr()
     }
 
+    /// Gets the value of the `IPV6_V6ONLY` option for this socket.
+    ///
+    /// See [`BindOptions::only_v6`] for setting it before the socket is bound.
+    pub fn only_v6(&self) -> io::Result<bool> {
+        self.inner.only_v6()
+    }
+
+    /// Binds the socket to the network interface named `device`, or removes the binding with
+    /// `None`, the `SO_BINDTODEVICE` option.
+    ///
+    /// See [`BindOptions::device`] for the platform support, the option is set before the
+    /// socket is bound there.
+    pub fn set_device(&self, device: Option<&str>) -> io::Result<()> {
+        self.inner.set_device(device)
+    }
+
+    /// Gets the name of the network interface the socket is bound to.
+    pub fn device(&self) -> io::Result<Option<String>> {
+        self.inner.device()
+    }
+
+    /// Sets the value of the `IP_FREEBIND` option for this socket.
+    ///
+    /// See [`BindOptions::freebind`] for the platform support.
+    pub fn set_freebind(&self, freebind: bool) -> io::Result<()> {
+        self.inner.set_freebind(freebind)
+    }
+
+    /// Gets the value of the `IP_FREEBIND` option for this socket.
+    pub fn freebind(&self) -> io::Result<bool> {
+        self.inner.freebind()
+    }
+
+    /// Sets the value of the `IP_TOS` option on this IPv4 socket.
+    ///
+    /// See [`TcpStream::set_tos`](crate::net::TcpStream::set_tos).
+    pub fn set_tos(&self, tos: u32) -> io::Result<()> {
+        self.inner.set_tos(tos)
+    }
+
+    /// Gets the value of the `IP_TOS` option for this socket.
+    pub fn tos(&self) -> io::Result<u32> {
+        self.inner.tos()
+    }
+
+    /// Sets the value of the `SO_RCVBUF` option on this socket.
+    ///
+    /// See [`TcpStream::set_recv_buffer_size`](crate::net::TcpStream::set_recv_buffer_size).
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.inner.set_recv_buffer_size(size)
+    }
+
+    /// Gets the value of the `SO_RCVBUF` option on this socket.
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        self.inner.recv_buffer_size()
+    }
+
+    /// Sets the value of the `SO_SNDBUF` option on this socket.
+    ///
+    /// See [`TcpStream::set_recv_buffer_size`](crate::net::TcpStream::set_recv_buffer_size).
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.inner.set_send_buffer_size(size)
+    }
+
+    /// Gets the value of the `SO_SNDBUF` option on this socket.
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        self.inner.send_buffer_size()
+    }
+
+    /// Sets the socket option `name` of the protocol `level` to `value`.
+    ///
+    /// # Safety
+    ///
+    /// See [`TcpStream::set_raw_sockopt`](crate::net::TcpStream::set_raw_sockopt).
+    pub unsafe fn set_raw_sockopt<T>(&self, level: i32, name: i32, value: &T) -> io::Result<()> {
+        self.inner.set_raw_sockopt(level, name, value)
+    }
+
+    /// Gets the socket option `name` of the protocol `level`.
+    ///
+    /// # Safety
+    ///
+    /// See [`TcpStream::get_raw_sockopt`](crate::net::TcpStream::get_raw_sockopt).
+    pub unsafe fn get_raw_sockopt<T>(&self, level: i32, name: i32) -> io::Result<T> {
+        self.inner.get_raw_sockopt(level, name)
+    }
+
     /// Receives a packet of data from the socket into the buffer, returning the
     /// original buffer and quantity of data received.
     #[cfg(feature = "runtime")]
@@ -183,6 +309,25 @@ impl UdpSocket {
         self.inner.recv(buffer).await
     }
 
+    /// Receives a packet with `MSG_*` flags. With `MSG_TRUNC` Linux returns the real length of
+    /// the datagram even if it doesn't fit into the buffer.
+    #[cfg(feature = "runtime")]
+    pub async fn recv_with_flags<T: IoBufMut<'static>>(
+        &self,
+        buffer: T,
+        flags: i32,
+    ) -> BufResult<usize, T> {
+        self.inner.recv_with_flags(buffer, flags).await
+    }
+
+    /// Receives a packet from the connected peer, reporting whether it's truncated to the
+    /// buffer, see [`recv_from_full`](UdpSocket::recv_from_full).
+    #[cfg(feature = "runtime")]
+    pub async fn recv_full<T: IoBufMut<'static>>(&self, buffer: T) -> BufResult<DatagramLen, T> {
+        let (res, buffer) = self.inner.recv_from_full(buffer).await;
+        (res.map(|(len, _)| len), buffer)
+    }
+
     /// Receives a packet of data from the socket into the buffer, returning the
     /// original buffer and quantity of data received.
     #[cfg(feature = "runtime")]
@@ -200,6 +345,16 @@ impl UdpSocket {
         self.inner.send(buffer).await
     }
 
+    /// Sends a packet with `MSG_*` flags.
+    #[cfg(feature = "runtime")]
+    pub async fn send_with_flags<T: IoBuf<'static>>(
+        &self,
+        buffer: T,
+        flags: i32,
+    ) -> BufResult<usize, T> {
+        self.inner.send_with_flags(buffer, flags).await
+    }
+
     /// Sends some data to the socket from the buffer, returning the original
     /// buffer and quantity of data sent.
     #[cfg(feature = "runtime")]
@@ -220,6 +375,55 @@ impl UdpSocket {
         self.inner.recv_from(buffer).await
     }
 
+    /// Receives a single datagram message with `MSG_*` flags, see
+    /// [`recv_with_flags`](UdpSocket::recv_with_flags).
+    #[cfg(feature = "runtime")]
+    pub async fn recv_from_with_flags<T: IoBufMut<'static>>(
+        &self,
+        buffer: T,
+        flags: i32,
+    ) -> BufResult<(usize, SockAddr), T> {
+        self.inner.recv_from_with_flags(buffer, flags).await
+    }
+
+    /// Receives a single datagram message, returning the number of the copied bytes along
+    /// with the length of the datagram and the origin.
+    ///
+    /// The part of the datagram that doesn't fit into the buffer is discarded, which is reported
+    /// by [`DatagramLen::is_truncated`].
+    ///
+    /// ## Platform specific
+    ///
+    /// * Linux, Android: the length of a truncated datagram is reported with `MSG_TRUNC`.
+    /// * Others: the truncation is detected but the length of a truncated datagram is unknown.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use completeio::net::UdpSocket;
+    ///
+    /// completeio::task::block_on(async {
+    ///     let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    ///     let addr = socket.local_addr().unwrap();
+    ///     socket.send_to("hello world", addr).await.0.unwrap();
+    ///
+    ///     let (res, buffer) = socket.recv_from_full(Vec::with_capacity(5)).await;
+    ///     let (len, _) = res.unwrap();
+    ///     assert_eq!(buffer, b"hello");
+    ///     assert_eq!(len.copied, 5);
+    ///     assert!(len.is_truncated());
+    ///     # #[cfg(target_os = "linux")]
+    ///     assert_eq!(len.total, Some(11));
+    /// })
+    /// ```
+    #[cfg(feature = "runtime")]
+    pub async fn recv_from_full<T: IoBufMut<'static>>(
+        &self,
+        buffer: T,
+    ) -> BufResult<(DatagramLen, SockAddr), T> {
+        self.inner.recv_from_full(buffer).await
+    }
+
     /// Receives a single datagram message on the socket. On success, returns
     /// the number of bytes received and the origin.
     #[cfg(feature = "runtime")]
@@ -244,6 +448,20 @@ impl UdpSocket {
         .await
     }
 
+    /// Sends data on the socket to the given address with `MSG_*` flags.
+    #[cfg(feature = "runtime")]
+    pub async fn send_to_with_flags<T: IoBuf<'static>>(
+        &self,
+        buffer: T,
+        addr: impl ToSockAddrs,
+        flags: i32,
+    ) -> BufResult<usize, T> {
+        super::each_addr_async_buf(addr, buffer, |addr, buffer| async move {
+            self.inner.send_to_with_flags(buffer, &addr, flags).await
+        })
+        .await
+    }
+
     /// Sends data on the socket to the given address. On success, returns the
     /// number of bytes sent.
     #[cfg(feature = "runtime")]
@@ -257,6 +475,91 @@ impl UdpSocket {
         })
         .await
     }
+
+    /// Receives several datagrams with one submission, a datagram per buffer. On success,
+    /// returns the length and the origin of each received datagram.
+    ///
+    /// The call waits for the first datagram, the rest of the buffers receive the datagrams
+    /// already queued on the socket. The buffers are returned in the order of the results,
+    /// followed by the unused ones. An error is returned only if no datagram is received.
+    ///
+    /// ## Platform specific
+    ///
+    /// * io-uring: the receives are linked, the successors of the first one are flagged with
+    ///   `MSG_DONTWAIT`.
+    /// * kqueue: the driver runs the linked receives one by one.
+    /// * IOCP: only the first buffer is used.
+    ///
+    /// ```
+    /// use completeio::net::UdpSocket;
+    ///
+    /// completeio::task::block_on(async {
+    ///     let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+    ///     let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+    ///     let addr = receiver.local_addr().unwrap();
+    ///     let datagrams = vec![
+    ///         (b"first".to_vec(), addr.clone()),
+    ///         (b"second".to_vec(), addr),
+    ///     ];
+    ///     let (res, _) = sender.send_batch(datagrams).await;
+    ///     assert_eq!(res.unwrap(), [5, 6]);
+    ///
+    ///     let buffers = vec![Vec::with_capacity(16), Vec::with_capacity(16)];
+    ///     let (res, buffers) = receiver.recv_batch(buffers).await;
+    ///     let received = res.unwrap();
+    ///     assert_eq!(buffers[0], b"first");
+    ///     # #[cfg(unix)]
+    ///     assert_eq!(received.len(), 2);
+    /// })
+    /// ```
+    #[cfg(feature = "runtime")]
+    pub async fn recv_batch<T: IoBufMut<'static>>(
+        &self,
+        buffers: Vec<T>,
+    ) -> BufResult<Vec<(usize, SockAddr)>, Vec<T>> {
+        self.inner.recv_from_batch(buffers).await
+    }
+
+    /// Sends several datagrams with one submission. On success, returns the number of bytes
+    /// sent for each datagram in order.
+    ///
+    /// The datagrams are sent one after another, a failed send cancels the rest of them. The
+    /// datagrams are returned in the order of the results, followed by the unsent ones. An
+    /// error is returned only if no datagram is sent.
+    #[cfg(feature = "runtime")]
+    pub async fn send_batch<T: IoBuf<'static>>(
+        &self,
+        datagrams: Vec<(T, SockAddr)>,
+    ) -> BufResult<Vec<usize>, Vec<(T, SockAddr)>> {
+        self.inner.send_to_batch(datagrams).await
+    }
+}
+
+/// The length of a received datagram, see [`UdpSocket::recv_from_full`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DatagramLen {
+    /// The number of bytes copied into the buffer.
+    pub copied: usize,
+    /// The length of the datagram, [`None`] if it's truncated and the platform doesn't report
+    /// the length.
+    pub total: Option<usize>,
+}
+
+impl DatagramLen {
+    // `received` is the length of the datagram if the platform reports it, the copied bytes
+    // otherwise
+    #[cfg(feature = "runtime")]
+    pub(crate) fn new(received: usize, capacity: usize, truncated: bool) -> Self {
+        Self {
+            copied: received.min(capacity),
+            total: (!truncated || received > capacity).then_some(received),
+        }
+    }
+
+    /// Returns `true` if the datagram didn't fit into the buffer.
+    pub fn is_truncated(&self) -> bool {
+        self.total != Some(self.copied)
+    }
 }
 
 impl_raw_fd!(UdpSocket, inner);
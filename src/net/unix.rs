@@ -1,4 +1,9 @@
 use std::{io, net::Shutdown, path::Path};
+#[cfg(all(unix, feature = "runtime"))]
+use std::{
+    mem::size_of,
+    os::fd::{BorrowedFd, FromRawFd, OwnedFd, RawFd},
+};
 
 use socket2::{Domain, SockAddr, Type};
 
@@ -7,6 +12,11 @@ use crate::{
     buf::{IoBuf, IoBufMut, VectoredBufWrapper},
     BufResult,
 };
+#[cfg(all(unix, feature = "runtime"))]
+use crate::{
+    buf_try,
+    net::{cmsg_space, push_scm_rights, CMsgIter},
+};
 use crate::{
     impl_raw_fd,
     net::{Socket, ToSockAddrs},
@@ -65,13 +75,23 @@ impl UnixListener {
 
     /// Creates a new independently owned handle to the underlying socket.
     ///
-    /// It does not clear the attach state.
+    /// The new handle submits operations using its own duplicated socket, so
+    /// it stays usable after the original is closed.
     pub fn try_clone(&self) -> io::Result<Self> {
         Ok(Self {
             inner: self.inner.try_clone()?,
         })
     }
 
+    /// Close the socket through the driver.
+    ///
+    /// The socket is consumed, so no other operation can be submitted using
+    /// it while the close is in flight.
+    #[cfg(feature = "runtime")]
+    pub async fn close(self) -> io::Result<()> {
+        self.inner.close().await
+    }
+
     /// Accepts a new incoming connection from this listener.
     ///
     /// This function will yield once a new Unix domain socket connection
@@ -135,15 +155,46 @@ impl UnixStream {
         })
     }
 
+    /// Creates an unnamed pair of connected sockets.
+    ///
+    /// The sockets are created with `CLOEXEC`.
+    ///
+    /// ```
+    /// use completeio::net::UnixStream;
+    ///
+    /// completeio::task::block_on(async {
+    ///     let (first, second) = UnixStream::pair().unwrap();
+    ///     first.send_all("ping").await.0.unwrap();
+    ///     let (res, buf) = second.recv_exact(Vec::with_capacity(4)).await;
+    ///     res.unwrap();
+    ///     assert_eq!(buf, b"ping");
+    /// })
+    /// ```
+    #[cfg(unix)]
+    pub fn pair() -> io::Result<(Self, Self)> {
+        let (first, second) = Socket::pair(Domain::UNIX, Type::STREAM, None)?;
+        Ok((Self { inner: first }, Self { inner: second }))
+    }
+
     /// Creates a new independently owned handle to the underlying socket.
     ///
-    /// It does not clear the attach state.
+    /// The new handle submits operations using its own duplicated socket, so
+    /// it stays usable after the original is closed.
     pub fn try_clone(&self) -> io::Result<Self> {
         Ok(Self {
             inner: self.inner.try_clone()?,
         })
     }
 
+    /// Close the socket through the driver.
+    ///
+    /// The socket is consumed, so no other operation can be submitted using
+    /// it while the close is in flight.
+    #[cfg(feature = "runtime")]
+    pub async fn close(self) -> io::Result<()> {
+        self.inner.close().await
+    }
+
     /// Returns the socket path of the remote peer of this connection.
     pub fn peer_addr(&self) -> io::Result<SockAddr> {
         self.inner.peer_addr()
@@ -154,6 +205,48 @@ impl UnixStream {
         self.inner.local_addr()
     }
 
+    /// Sets the value of the `SO_RCVBUF` option on this socket.
+    ///
+    /// See [`TcpStream::set_recv_buffer_size`](crate::net::TcpStream::set_recv_buffer_size).
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.inner.set_recv_buffer_size(size)
+    }
+
+    /// Gets the value of the `SO_RCVBUF` option on this socket.
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        self.inner.recv_buffer_size()
+    }
+
+    /// Sets the value of the `SO_SNDBUF` option on this socket.
+    ///
+    /// See [`TcpStream::set_recv_buffer_size`](crate::net::TcpStream::set_recv_buffer_size).
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.inner.set_send_buffer_size(size)
+    }
+
+    /// Gets the value of the `SO_SNDBUF` option on this socket.
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        self.inner.send_buffer_size()
+    }
+
+    /// Sets the socket option `name` of the protocol `level` to `value`.
+    ///
+    /// # Safety
+    ///
+    /// See [`TcpStream::set_raw_sockopt`](crate::net::TcpStream::set_raw_sockopt).
+    pub unsafe fn set_raw_sockopt<T>(&self, level: i32, name: i32, value: &T) -> io::Result<()> {
+        self.inner.set_raw_sockopt(level, name, value)
+    }
+
+    /// Gets the socket option `name` of the protocol `level`.
+    ///
+    /// # Safety
+    ///
+    /// See [`TcpStream::get_raw_sockopt`](crate::net::TcpStream::get_raw_sockopt).
+    pub unsafe fn get_raw_sockopt<T>(&self, level: i32, name: i32) -> io::Result<T> {
+        self.inner.get_raw_sockopt(level, name)
+    }
+
     /// Shuts down the read, write, or both halves of this connection.
     ///
     /// This function will cause all pending and future I/O on the specified
@@ -163,6 +256,15 @@ impl UnixStream {
         self.inner.shutdown(how)
     }
 
+    /// Shuts down the read, write, or both halves of this connection
+    /// asynchronously.
+    ///
+    /// See [`shutdown`](Self::shutdown) for the details.
+    #[cfg(feature = "runtime")]
+    pub async fn shutdown_async(&self, how: Shutdown) -> io::Result<()> {
+        self.inner.shutdown_async(how).await
+    }
+
     /// Receives a packet of data from the socket into the buffer, returning the
     /// original buffer and quantity of data received.
     #[cfg(feature = "runtime")]
@@ -199,6 +301,94 @@ impl UnixStream {
         self.inner.send_all(buffer).await
     }
 
+    /// Sends some data together with file descriptors, returning the original
+    /// buffer and quantity of data sent.
+    ///
+    /// The file descriptors are passed in a `SCM_RIGHTS` control message and
+    /// are duplicated into the receiving process.
+    ///
+    /// ```
+    /// use std::os::fd::{AsRawFd, BorrowedFd};
+    ///
+    /// use completeio::{net::UnixStream, pipe::pipe};
+    ///
+    /// completeio::task::block_on(async {
+    ///     let (first, second) = UnixStream::pair().unwrap();
+    ///     let (reader, _writer) = pipe().unwrap();
+    ///     // SAFETY: the reader outlives the call
+    ///     let fd = unsafe { BorrowedFd::borrow_raw(reader.as_raw_fd()) };
+    ///     first.send_with_fds("fd", &[fd]).await.0.unwrap();
+    ///     let (res, _) = second.recv_with_fds(Vec::with_capacity(2), 1).await;
+    ///     let (_, fds) = res.unwrap();
+    ///     assert_eq!(fds.len(), 1);
+    /// })
+    /// ```
+    #[cfg(all(unix, feature = "runtime"))]
+    pub async fn send_with_fds<T: IoBuf<'static>>(
+        &self,
+        buffer: T,
+        fds: &[BorrowedFd<'_>],
+    ) -> BufResult<usize, T> {
+        let mut control = Vec::new();
+        push_scm_rights(&mut control, fds);
+        let (res, (buffer, _)) = self.inner.send_msg(buffer, control).await;
+        (res, buffer)
+    }
+
+    /// Receives some data together with file descriptors, returning the
+    /// original buffer, quantity of data received and the received file
+    /// descriptors.
+    ///
+    /// The control buffer has room for at least `fd_capacity` file descriptors.
+    /// If the peer passed more file descriptors than fit into it, the control
+    /// data is truncated and [`TruncatedFdsError`] is returned, the file
+    /// descriptors which did arrive are closed. The received data is still
+    /// available in the buffer and the error reports its length.
+    ///
+    /// The received file descriptors are owned right away, so they are closed
+    /// on any error. They are close-on-exec.
+    ///
+    /// ## Platform specific
+    ///
+    /// * Linux, Android and FreeBSD: `MSG_CMSG_CLOEXEC` sets close-on-exec atomically.
+    /// * Other Unix: `FD_CLOEXEC` is set after the descriptors are received, they could leak into a
+    ///   child process spawned concurrently by another thread.
+    #[cfg(all(unix, feature = "runtime"))]
+    pub async fn recv_with_fds<T: IoBufMut<'static>>(
+        &self,
+        buffer: T,
+        fd_capacity: usize,
+    ) -> BufResult<(usize, Vec<OwnedFd>), T> {
+        let control = Vec::with_capacity(cmsg_space(fd_capacity * size_of::<RawFd>()));
+        let (res, (buffer, control)) = self.inner.recv_msg(buffer, control).await;
+        let ((len, msg_flags), buffer) = buf_try!(res, buffer);
+        let fds = CMsgIter::new(&control)
+            .filter_map(|cmsg| cmsg.scm_rights())
+            .flatten()
+            // SAFETY: the received file descriptors are owned by the process
+            .map(|fd| unsafe { OwnedFd::from_raw_fd(fd) })
+            .collect::<Vec<_>>();
+        if msg_flags & libc::MSG_CTRUNC != 0 {
+            // the part of the passed file descriptors is useless, they are closed
+            drop(fds);
+            let error = io::Error::other(TruncatedFdsError { received: len });
+            return (Err(error), buffer);
+        }
+        // the flag of `recvmsg` isn't supported
+        #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+        {
+            use std::os::fd::AsRawFd;
+
+            let res = fds.iter().try_for_each(|fd| {
+                crate::syscall!(fcntl(fd.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC)).map(drop)
+            });
+            if let Err(e) = res {
+                return (Err(e), buffer);
+            }
+        }
+        (Ok((len, fds)), buffer)
+    }
+
     /// Sends some data to the socket from the buffer, returning the original
     /// buffer and quantity of data sent.
     #[cfg(feature = "runtime")]
@@ -211,3 +401,177 @@ impl UnixStream {
 }
 
 impl_raw_fd!(UnixStream, inner);
+
+/// The error of [`UnixStream::recv_with_fds`] when the peer passed more file descriptors than
+/// fit into the control buffer.
+///
+/// It is wrapped into [`io::Error`] with [`io::ErrorKind::Other`]. The data is received into the
+/// buffer, the arrived file descriptors are closed.
+///
+/// ```
+/// use std::io;
+///
+/// use completeio::net::TruncatedFdsError;
+///
+/// fn received_len(error: &io::Error) -> Option<usize> {
+///     TruncatedFdsError::from_io_error(error).map(TruncatedFdsError::received)
+/// }
+/// ```
+#[cfg(all(unix, feature = "runtime"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TruncatedFdsError {
+    received: usize,
+}
+
+#[cfg(all(unix, feature = "runtime"))]
+impl TruncatedFdsError {
+    /// Get the error wrapped into `error` if it reports the truncated file descriptors.
+    pub fn from_io_error(error: &io::Error) -> Option<&Self> {
+        error.get_ref().and_then(|inner| inner.downcast_ref())
+    }
+
+    /// The number of bytes received into the buffer.
+    pub fn received(&self) -> usize {
+        self.received
+    }
+}
+
+#[cfg(all(unix, feature = "runtime"))]
+impl std::fmt::Display for TruncatedFdsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "passed file descriptors are truncated, {} bytes are received",
+            self.received
+        )
+    }
+}
+
+#[cfg(all(unix, feature = "runtime"))]
+impl std::error::Error for TruncatedFdsError {}
+
+/// A Unix datagram socket.
+///
+/// # Examples
+///
+/// ```
+/// use completeio::net::UnixDatagram;
+///
+/// completeio::task::block_on(async {
+///     let (first, second) = UnixDatagram::pair().unwrap();
+///     first.send("hello").await.0.unwrap();
+///     let (res, buf) = second.recv(Vec::with_capacity(32)).await;
+///     assert_eq!(res.unwrap(), 5);
+///     assert_eq!(buf, b"hello");
+/// })
+/// ```
+#[cfg(unix)]
+pub struct UnixDatagram {
+    inner: Socket,
+}
+
+#[cfg(unix)]
+impl UnixDatagram {
+    /// Creates a Unix datagram socket bound to the given path.
+    pub fn bind(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::bind_addr(SockAddr::unix(path)?)
+    }
+
+    /// Creates a Unix datagram socket bound to the given address.
+    pub fn bind_addr(addr: impl ToSockAddrs) -> io::Result<Self> {
+        super::each_addr(addr, |addr| {
+            Ok(Self {
+                inner: Socket::bind(&addr, Type::DGRAM, None)?,
+            })
+        })
+    }
+
+    /// Creates a Unix datagram socket which is not bound to any address.
+    pub fn unbound() -> io::Result<Self> {
+        Ok(Self {
+            inner: Socket::new(Domain::UNIX, Type::DGRAM, None)?,
+        })
+    }
+
+    /// Creates an unnamed pair of connected sockets.
+    ///
+    /// The sockets are created with `CLOEXEC`.
+    pub fn pair() -> io::Result<(Self, Self)> {
+        let (first, second) = Socket::pair(Domain::UNIX, Type::DGRAM, None)?;
+        Ok((Self { inner: first }, Self { inner: second }))
+    }
+
+    /// Connects the socket to the specified path, so [`send`](Self::send) and
+    /// [`recv`](Self::recv) exchange datagrams with it only.
+    pub fn connect(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.inner.connect(&SockAddr::unix(path)?)
+    }
+
+    /// Creates a new independently owned handle to the underlying socket.
+    ///
+    /// The new handle submits operations using its own duplicated socket, so
+    /// it stays usable after the original is closed.
+    pub fn try_clone(&self) -> io::Result<Self> {
+        Ok(Self {
+            inner: self.inner.try_clone()?,
+        })
+    }
+
+    /// Close the socket through the driver.
+    ///
+    /// The socket is consumed, so no other operation can be submitted using
+    /// it while the close is in flight.
+    #[cfg(feature = "runtime")]
+    pub async fn close(self) -> io::Result<()> {
+        self.inner.close().await
+    }
+
+    /// Returns the address of the peer the socket is connected to.
+    pub fn peer_addr(&self) -> io::Result<SockAddr> {
+        self.inner.peer_addr()
+    }
+
+    /// Returns the address the socket is bound to.
+    pub fn local_addr(&self) -> io::Result<SockAddr> {
+        self.inner.local_addr()
+    }
+
+    /// Receives a datagram from the connected peer into the buffer, returning
+    /// the original buffer and quantity of data received.
+    #[cfg(feature = "runtime")]
+    pub async fn recv<T: IoBufMut<'static>>(&self, buffer: T) -> BufResult<usize, T> {
+        self.inner.recv(buffer).await
+    }
+
+    /// Sends a datagram to the connected peer from the buffer, returning the
+    /// original buffer and quantity of data sent.
+    #[cfg(feature = "runtime")]
+    pub async fn send<T: IoBuf<'static>>(&self, buffer: T) -> BufResult<usize, T> {
+        self.inner.send(buffer).await
+    }
+
+    /// Receives a datagram into the buffer, returning the original buffer,
+    /// quantity of data received and the address of the sender.
+    #[cfg(feature = "runtime")]
+    pub async fn recv_from<T: IoBufMut<'static>>(
+        &self,
+        buffer: T,
+    ) -> BufResult<(usize, SockAddr), T> {
+        self.inner.recv_from(buffer).await
+    }
+
+    /// Sends a datagram to the specified path from the buffer, returning the
+    /// original buffer and quantity of data sent.
+    #[cfg(feature = "runtime")]
+    pub async fn send_to<T: IoBuf<'static>>(
+        &self,
+        buffer: T,
+        path: impl AsRef<Path>,
+    ) -> BufResult<usize, T> {
+        let (addr, buffer) = buf_try!(SockAddr::unix(path), buffer);
+        self.inner.send_to(buffer, &addr).await
+    }
+}
+
+#[cfg(unix)]
+impl_raw_fd!(UnixDatagram, inner);
@@ -3,16 +3,31 @@
 //! The operation itself doesn't perform anything.
 //! You need to pass them to [`crate::driver::Driver`], and poll the driver.
 
+use std::ops::{BitOr, BitOrAssign};
+
 use socket2::SockAddr;
 
 #[cfg(target_os = "windows")]
 pub use crate::driver::op::ConnectNamedPipe;
-#[cfg(feature = "time")]
-pub use crate::driver::op::Timeout;
 pub use crate::driver::op::{
-    Accept, Connect, Read, ReadAt, Recv, RecvFrom, RecvMsgImpl, RecvVectoredImpl, Send,
-    SendMsgImpl, SendTo, SendVectoredImpl, Sync, Write, WriteAt,
+    Accept, Bind, Cancel, Close, Connect, CreateSocket, Fallocate, LinkAt, Listen, MkdirAt,
+    MsgRing, Nop, OpenAt, PollReadable, PollWritable, Read, ReadAt, ReadAtFixed,
+    ReadVectoredAtImpl, Recv, RecvFrom, RecvMsgImpl, RecvVectoredImpl, RenameAt, Send, SendFile,
+    SendMsgImpl, SendTo, SendVectoredImpl, SendZc, ShutdownSocket, SymlinkAt, Sync, SyncFileRange,
+    Truncate, UnlinkAt, Write, WriteAt, WriteAtFixed, WriteVectoredAtImpl,
+};
+#[cfg(target_os = "linux")]
+pub use crate::driver::op::{
+    AcceptMultishot, EpollCtl, EpollCtlOp, FilesUpdate, FutexWait, FutexWake, RecvMultishot,
+    UringCmd,
+};
+#[cfg(unix)]
+pub use crate::driver::op::{
+    CopyFileRange, Fadvise, GetXattr, Madvise, RecvMsg, SendMsg, SetXattr, Splice, Statx, Tee,
+    WaitId,
 };
+#[cfg(feature = "time")]
+pub use crate::driver::op::{Timeout, TimeoutRemove, TimeoutUpdate};
 use crate::{
     buf::{AsIoSlicesMut, BufWrapperMut, IoBufMut, VectoredBufWrapper},
     BufResult,
@@ -59,7 +74,9 @@ impl<'arena, T: IoBufMut<'arena>> UpdateBufferLen for BufResult<'arena, usize, T
     fn update_buffer_len(self) -> Self {
         let (res, mut buffer) = self;
         if let Ok(init) = &res {
-            buffer.set_buf_init(*init);
+            // `MSG_TRUNC` reports the length of the datagram rather than the received bytes
+            let init = (*init).min(buffer.as_uninit_slice().len());
+            buffer.set_buf_init(init);
         }
         (res, buffer)
     }
@@ -69,7 +86,8 @@ impl<'arena, T: IoBufMut<'arena>, O> UpdateBufferLen for BufResult<'arena, (usiz
     fn update_buffer_len(self) -> Self {
         let (res, mut buffer) = self;
         if let Ok((init, _)) = &res {
-            buffer.set_buf_init(*init);
+            let init = (*init).min(buffer.as_uninit_slice().len());
+            buffer.set_buf_init(init);
         }
         (res, buffer)
     }
@@ -95,7 +113,148 @@ pub type RecvVectored<'arena, T> = RecvVectoredImpl<'arena, VectoredBufWrapper<'
 /// Send a single piece of data with vectored buffer.
 pub type SendVectored<'arena, T> = SendVectoredImpl<'arena, VectoredBufWrapper<'arena, T>>;
 
+/// Read a file at specified position with vectored buffer.
+pub type ReadVectoredAt<'arena, T> = ReadVectoredAtImpl<'arena, VectoredBufWrapper<'arena, T>>;
+/// Write a file at specified position with vectored buffer.
+pub type WriteVectoredAt<'arena, T> = WriteVectoredAtImpl<'arena, VectoredBufWrapper<'arena, T>>;
+
 /// Receive a single piece of data and address with vectored buffer.
 pub type RecvFromVectored<'arena, T> = RecvMsgImpl<'arena, VectoredBufWrapper<'arena, T>>;
 /// Send a single piece of data to address with vectored buffer.
 pub type SendToVectored<'arena, T> = SendMsgImpl<'arena, VectoredBufWrapper<'arena, T>>;
+
+// a set of flags combined with `|`, the bits are the private details of the drivers
+macro_rules! flags {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident;
+        $(
+            $(#[$flag_meta:meta])*
+            const $flag:ident = $bit:expr;
+        )*
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct $name(u32);
+
+        impl $name {
+            $(
+                $(#[$flag_meta])*
+                pub const $flag: Self = Self($bit);
+            )*
+
+            /// Flags without any flag set.
+            pub const fn empty() -> Self {
+                Self(0)
+            }
+
+            /// Returns `true` if all flags of `other` are set.
+            pub const fn contains(self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            /// Returns `true` if no flag is set.
+            pub const fn is_empty(self) -> bool {
+                self.0 == 0
+            }
+        }
+
+        impl BitOr for $name {
+            type Output = Self;
+
+            fn bitor(self, rhs: Self) -> Self::Output {
+                Self(self.0 | rhs.0)
+            }
+        }
+
+        impl BitOrAssign for $name {
+            fn bitor_assign(&mut self, rhs: Self) {
+                self.0 |= rhs.0
+            }
+        }
+    };
+}
+
+flags! {
+    /// Flags of the socket accepted by [`Accept`] operation, like `SOCK_*` flags of `accept4(2)`.
+    ///
+    /// The flags could be combined with `|`.
+    pub struct AcceptFlags;
+
+    /// Don't leak the accepted socket into child processes.
+    const CLOEXEC = 1;
+    /// Put the accepted socket into non-blocking mode.
+    const NONBLOCK = 1 << 1;
+}
+
+flags! {
+    /// Mode of [`Fallocate`] operation.
+    ///
+    /// The flags could be combined with `|`. The default mode allocates the range and extends the
+    /// file size if the range is past the end of file.
+    pub struct AllocateMode;
+
+    /// Don't change the file size even if the range is past the end of file.
+    const KEEP_SIZE = 1;
+    /// Deallocate the range. The file size is never changed.
+    const PUNCH_HOLE = 1 << 1;
+}
+
+flags! {
+    /// Per-operation flags of [`ReadAt`] and [`WriteAt`] operations, like `RWF_*` flags of
+    /// `preadv2(2)`.
+    ///
+    /// The flags could be combined with `|`.
+    pub struct RwFlags;
+
+    /// Write the data with `O_DSYNC` semantics, the write completes after the data is flushed.
+    const DSYNC = 1 << 1;
+    /// High priority polled IO, it is a hint ignored by unsupported files.
+    const HIPRI = 1;
+    /// Fail with [`std::io::ErrorKind::WouldBlock`] instead of waiting, for example when the
+    /// data of a read is not in the page cache.
+    const NOWAIT = 1 << 3;
+}
+
+flags! {
+    /// Flags of [`SyncFileRange`] operation.
+    ///
+    /// The flags could be combined with `|`.
+    pub struct SyncRangeFlags;
+
+    /// Wait for write-out of the pages in the range after performing any write.
+    const WAIT_AFTER = 1 << 2;
+    /// Wait for write-out of the pages in the range that were already submitted.
+    const WAIT_BEFORE = 1;
+    /// Initiate write-out of the dirty pages in the range.
+    const WRITE = 1 << 1;
+}
+
+flags! {
+    /// Flags of [`RenameAt`] operation.
+    ///
+    /// The flags could be combined with `|`.
+    pub struct RenameFlags;
+
+    /// Don't overwrite the destination. The operation fails with
+    /// [`std::io::ErrorKind::AlreadyExists`] if the destination exists.
+    const NOREPLACE = 1;
+}
+
+/// Advice of [`Fadvise`] and [`Madvise`] operations about the expected access pattern.
+#[cfg(unix)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Advice {
+    /// No special treatment.
+    Normal,
+    /// Expect sequential access.
+    Sequential,
+    /// Expect random access.
+    Random,
+    /// Expect the data to be accessed only once.
+    NoReuse,
+    /// Expect access in the near future.
+    WillNeed,
+    /// Don't expect access in the near future.
+    DontNeed,
+}
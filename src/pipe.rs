@@ -0,0 +1,191 @@
+//! Anonymous pipes.
+//!
+//! ```
+//! use completeio::pipe::pipe;
+//!
+//! completeio::task::block_on(async {
+//!     let (reader, writer) = pipe().unwrap();
+//!     writer.write_all("hello").await.0.unwrap();
+//!     drop(writer);
+//!
+//!     let (res, buf) = reader.read_exact(Vec::with_capacity(5)).await;
+//!     res.unwrap();
+//!     assert_eq!(buf, b"hello");
+//!     // the writer is closed
+//!     let (res, _) = reader.read(Vec::with_capacity(1)).await;
+//!     assert_eq!(res.unwrap(), 0);
+//! })
+//! ```
+
+use std::io;
+
+#[cfg(feature = "runtime")]
+use crate::{
+    buf::{IntoInner, IoBuf, IoBufMut},
+    buf_try,
+    op::{Read, UpdateBufferLen, Write},
+    BufResult,
+};
+use crate::{driver::FromRawFd, fs::File, impl_raw_fd};
+
+/// Creates an anonymous pipe, returning its reading and writing ends.
+///
+/// The ends are not inherited by child processes unless they are converted to the standard IO
+/// of the child explicitly.
+///
+/// ## Platform specific
+///
+/// * Unix: `pipe2` with `O_CLOEXEC`, the ends are non-blocking for the readiness based drivers.
+/// * Windows: the anonymous pipes don't support overlapped IO, a named pipe with a unique name
+///   accepting a single local client is created instead.
+pub fn pipe() -> io::Result<(PipeReader, PipeWriter)> {
+    let (reader, writer) = sys::pipe()?;
+    // SAFETY: the ends are owned by no one else
+    unsafe {
+        Ok((
+            PipeReader::from_raw_fd(reader),
+            PipeWriter::from_raw_fd(writer),
+        ))
+    }
+}
+
+/// The reading end of a pipe created by [`pipe`].
+#[derive(Debug)]
+pub struct PipeReader {
+    inner: File,
+}
+
+impl PipeReader {
+    /// Reads some data from the pipe into the buffer, returning the original buffer and quantity
+    /// of data read, zero if the writing end is closed.
+    #[cfg(feature = "runtime")]
+    pub async fn read<T: IoBufMut<'static>>(&self, buffer: T) -> BufResult<usize, T> {
+        let ((fd, runtime), buffer) = buf_try!(self.inner.attach_runtime(), buffer);
+        let op = Read::new(fd, buffer);
+        runtime.submit(op).await.into_inner().update_buffer_len()
+    }
+
+    /// Reads the exact number of bytes required to fill the buffer.
+    #[cfg(feature = "runtime")]
+    pub async fn read_exact<T: IoBufMut<'static>>(&self, mut buffer: T) -> BufResult<usize, T> {
+        let need = buffer.as_uninit_slice().len();
+        let mut total_read = 0;
+        let mut read;
+        while total_read < need {
+            (read, buffer) = buf_try!(self.read(buffer).await);
+            if read == 0 {
+                return (
+                    Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "failed to fill whole buffer",
+                    )),
+                    buffer,
+                );
+            }
+            total_read += read;
+        }
+        (Ok(total_read), buffer)
+    }
+}
+
+impl_raw_fd!(PipeReader, inner);
+
+/// The writing end of a pipe created by [`pipe`].
+#[derive(Debug)]
+pub struct PipeWriter {
+    inner: File,
+}
+
+impl PipeWriter {
+    /// Writes some data from the buffer into the pipe, returning the original buffer and quantity
+    /// of data written.
+    #[cfg(feature = "runtime")]
+    pub async fn write<T: IoBuf<'static>>(&self, buffer: T) -> BufResult<usize, T> {
+        let ((fd, runtime), buffer) = buf_try!(self.inner.attach_runtime(), buffer);
+        let op = Write::new(fd, buffer);
+        runtime.submit(op).await.into_inner()
+    }
+
+    /// Writes all data from the buffer into the pipe.
+    #[cfg(feature = "runtime")]
+    pub async fn write_all<T: IoBuf<'static>>(&self, mut buffer: T) -> BufResult<usize, T> {
+        let buf_len = buffer.buf_len();
+        let mut total_written = 0;
+        let mut written;
+        while total_written < buf_len {
+            (written, buffer) =
+                buf_try!(self.write(buffer.slice(total_written..)).await.into_inner());
+            total_written += written;
+        }
+        (Ok(total_written), buffer)
+    }
+}
+
+impl_raw_fd!(PipeWriter, inner);
+
+#[cfg(unix)]
+mod sys {
+    use std::{
+        io,
+        os::fd::{FromRawFd, IntoRawFd, OwnedFd, RawFd},
+    };
+
+    use crate::syscall;
+
+    pub fn pipe() -> io::Result<(RawFd, RawFd)> {
+        let mut fds = [-1; 2];
+        #[cfg(not(target_vendor = "apple"))]
+        {
+            // readiness based drivers require non-blocking pipes
+            #[cfg(target_os = "linux")]
+            let flags = libc::O_CLOEXEC;
+            #[cfg(not(target_os = "linux"))]
+            let flags = libc::O_CLOEXEC | libc::O_NONBLOCK;
+            syscall!(pipe2(fds.as_mut_ptr(), flags))?;
+        }
+        #[cfg(target_vendor = "apple")]
+        syscall!(pipe(fds.as_mut_ptr()))?;
+        // SAFETY: the fds are created above
+        let (reader, writer) =
+            unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+        #[cfg(target_vendor = "apple")]
+        for fd in [&reader, &writer] {
+            use std::os::fd::AsRawFd;
+
+            syscall!(fcntl(fd.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC))?;
+            syscall!(fcntl(fd.as_raw_fd(), libc::F_SETFL, libc::O_NONBLOCK))?;
+        }
+        Ok((reader.into_raw_fd(), writer.into_raw_fd()))
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::{
+        io,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use crate::{
+        driver::{IntoRawFd, RawFd},
+        named_pipe::{ClientOptions, ServerOptions},
+    };
+
+    pub fn pipe() -> io::Result<(RawFd, RawFd)> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        let name = format!(
+            r"\\.\pipe\completeio-anonymous-{}-{}",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        );
+        let reader = ServerOptions::new()
+            .access_outbound(false)
+            .first_pipe_instance(true)
+            .max_instances(1)
+            .create(&name)?;
+        // the pipe is connected when the client is opened
+        let writer = ClientOptions::new().read(false).open(&name)?;
+        Ok((reader.into_raw_fd(), writer.into_raw_fd()))
+    }
+}
@@ -0,0 +1,82 @@
+//! Asynchronous waits for child processes.
+//!
+//! The driver waits for the process exit, SIGCHLD handler is not required.
+//!
+//! ```
+//! use std::process::Command;
+//!
+//! use completeio::process::Child;
+//!
+//! completeio::task::block_on(async {
+//!     let mut child = Child::from(Command::new("true").spawn().unwrap());
+//!     let status = child.wait().await.unwrap();
+//!     assert!(status.success());
+//! })
+//! ```
+
+use std::{io, process::ExitStatus};
+
+use crate::{op::WaitId, task::RUNTIME};
+
+/// A spawned child process.
+///
+/// [`Child::wait`] borrows the child mutably, so the process is waited by one task at a time.
+#[derive(Debug)]
+pub struct Child {
+    inner: std::process::Child,
+    status: Option<ExitStatus>,
+}
+
+impl Child {
+    /// The OS-assigned process identifier.
+    pub fn id(&self) -> u32 {
+        self.inner.id()
+    }
+
+    /// The spawned process, it could be used to take the handles of the child's stdio.
+    ///
+    /// The std waits don't know about the exit collected by [`Child::wait`].
+    pub fn as_std_mut(&mut self) -> &mut std::process::Child {
+        &mut self.inner
+    }
+
+    /// Forces the child process to exit.
+    ///
+    /// Does nothing if the exit status is already collected.
+    pub fn kill(&mut self) -> io::Result<()> {
+        if self.status.is_some() {
+            return Ok(());
+        }
+        self.inner.kill()
+    }
+
+    /// Waits for the child to exit and returns the exit status.
+    ///
+    /// The stdin handle of the child is closed before waiting.
+    pub async fn wait(&mut self) -> io::Result<ExitStatus> {
+        if let Some(status) = self.status {
+            return Ok(status);
+        }
+        drop(self.inner.stdin.take());
+        let mut op = WaitId::new(libc::P_PID, self.id() as _, libc::WEXITED)?;
+        loop {
+            let res;
+            (res, op) = RUNTIME.with(|runtime| runtime.submit(op)).await;
+            if op.on_complete(res)? {
+                break;
+            }
+        }
+        let status = op.exit_status().expect("the exit is collected");
+        self.status = Some(status);
+        Ok(status)
+    }
+}
+
+impl From<std::process::Child> for Child {
+    fn from(inner: std::process::Child) -> Self {
+        Self {
+            inner,
+            status: None,
+        }
+    }
+}
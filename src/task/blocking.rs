@@ -0,0 +1,209 @@
+use std::{
+    collections::VecDeque,
+    future::Future,
+    io,
+    pin::Pin,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    task::{Context, Poll},
+    thread,
+    time::Duration,
+};
+
+use async_task::Runnable;
+
+use crate::task::{join_future, JoinHandle};
+
+// the defaults of the pool of the thread-local runtime
+pub(crate) const DEFAULT_MAX_THREADS: usize = 512;
+pub(crate) const DEFAULT_KEEP_ALIVE: Duration = Duration::from_secs(10);
+
+#[derive(Default)]
+struct State {
+    queue: VecDeque<Runnable>,
+    threads: usize,
+    idle: usize,
+    shutdown: bool,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    queued: Condvar,
+    exited: Condvar,
+    max_threads: usize,
+    keep_alive: Duration,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // the closures run without the lock, so it isn't poisoned
+        self.state.lock().expect("not poisoned")
+    }
+
+    fn schedule(self: &Arc<Self>, runnable: Runnable) {
+        let mut state = self.lock();
+        if state.shutdown {
+            // the task is cancelled
+            drop(state);
+            drop(runnable);
+            return;
+        }
+        state.queue.push_back(runnable);
+        if state.queue.len() > state.idle && state.threads < self.max_threads {
+            let shared = self.clone();
+            match thread::Builder::new()
+                .name("completeio-blocking".into())
+                .spawn(move || work(&shared))
+            {
+                Ok(_) => state.threads += 1,
+                Err(_) if state.threads == 0 => {
+                    // there are no threads to run the closure, the task is cancelled
+                    let runnable = state.queue.pop_back();
+                    drop(state);
+                    drop(runnable);
+                    return;
+                }
+                // the running threads run the closure later
+                Err(_) => {}
+            }
+        }
+        drop(state);
+        self.queued.notify_one();
+    }
+}
+
+/// Threads running the blocking closures of [`spawn_blocking`].
+///
+/// The threads are spawned on demand up to the limit and exit after they are idle for the keep
+/// alive duration. The pool is shut down when it's dropped, it waits for the running closures
+/// and drops the queued ones.
+pub(crate) struct BlockingPool {
+    shared: Arc<Shared>,
+}
+
+impl BlockingPool {
+    pub fn new(max_threads: usize, keep_alive: Duration) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                state: Mutex::default(),
+                queued: Condvar::new(),
+                exited: Condvar::new(),
+                max_threads,
+                keep_alive,
+            }),
+        }
+    }
+
+    pub fn spawn<F, T>(&self, f: F) -> JoinHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (future, abort) = join_future(Blocking(Some(f)));
+        let shared = self.shared.clone();
+        let (runnable, task) = async_task::spawn(future, move |runnable| shared.schedule(runnable));
+        runnable.schedule();
+        JoinHandle::new(task, abort)
+    }
+}
+
+impl Drop for BlockingPool {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.shutdown = true;
+        let queue = std::mem::take(&mut state.queue);
+        drop(state);
+        // the dropped runnables cancel their tasks
+        drop(queue);
+        self.shared.queued.notify_all();
+        let mut state = self.shared.lock();
+        while state.threads > 0 {
+            state = self.shared.exited.wait(state).expect("not poisoned");
+        }
+    }
+}
+
+fn work(shared: &Shared) {
+    let mut state = shared.lock();
+    loop {
+        if let Some(runnable) = state.queue.pop_front() {
+            drop(state);
+            // the panic of the closure is caught by the task
+            runnable.run();
+            state = shared.lock();
+        } else if state.shutdown {
+            break;
+        } else {
+            state.idle += 1;
+            let (guard, timeout) = shared
+                .queued
+                .wait_timeout(state, shared.keep_alive)
+                .expect("not poisoned");
+            state = guard;
+            state.idle -= 1;
+            if timeout.timed_out() && state.queue.is_empty() {
+                break;
+            }
+        }
+    }
+    state.threads -= 1;
+    drop(state);
+    shared.exited.notify_all();
+}
+
+// the closure completing on the first poll
+struct Blocking<F>(Option<F>);
+
+impl<F> Unpin for Blocking<F> {}
+
+impl<F: FnOnce() -> T, T> Future for Blocking<F> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<T> {
+        let f = self
+            .0
+            .take()
+            .expect("`Blocking` is polled after completion");
+        Poll::Ready(f())
+    }
+}
+
+/// Runs the blocking closure on a thread pool, returning a [`JoinHandle`] for it.
+///
+/// The closure doesn't stall the tasks of the runtime, the awaiting task is woken up when it
+/// returns. The threads of the pool are spawned on demand up to the limit, the closures wait
+/// in a queue when all threads are busy. The limit and the keep alive duration of idle threads
+/// are set with [`Builder`](crate::task::Builder), the thread-local runtime uses the default
+/// ones.
+///
+/// The running closure is not interrupted by [`JoinHandle::abort`]. The runtime waits for the
+/// running closures when it's shut down, the queued ones are dropped and their handles return
+/// the cancelled [`JoinError`](crate::task::JoinError).
+///
+/// ```
+/// completeio::task::block_on(async {
+///     let sum = completeio::task::spawn_blocking(|| (1..=10).sum::<i32>()).await;
+///     assert_eq!(sum.unwrap(), 55);
+/// })
+/// ```
+pub fn spawn_blocking<F, T>(f: F) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    crate::task::RUNTIME.with(|runtime| runtime.blocking_pool().spawn(f))
+}
+
+// Runs the fallible blocking closure with `spawn_blocking`. The panic of the closure is resumed,
+// the closure dropped by the shut down runtime fails.
+pub(crate) async fn run_blocking<F, T>(f: F) -> io::Result<T>
+where
+    F: FnOnce() -> io::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    spawn_blocking(f).await.unwrap_or_else(|e| {
+        if e.is_panic() {
+            std::panic::resume_unwind(e.into_panic())
+        }
+        Err(io::Error::other(e.to_string()))
+    })
+}
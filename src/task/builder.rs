@@ -0,0 +1,322 @@
+use std::{
+    future::Future,
+    io,
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::{
+    driver::{DriverBuilder, DriverCapabilities, OpCode},
+    task::{
+        dump::trace, runtime::Remote, JoinHandle, RuntimeMetrics, SubmitHandle, TaskInfo, RUNTIME,
+    },
+};
+
+// the configuration of the runtimes created on the first use
+static DEFAULT: Mutex<Option<RuntimeBuilder>> = Mutex::new(None);
+
+/// Builder of the runtime of the current thread.
+///
+/// The runtime is created implicitly with the [default](RuntimeBuilder::set_default)
+/// configuration on the first use of the thread, e.g. by [`block_on`](crate::task::block_on).
+/// The builder creates it explicitly, so it should be called before any other API of the
+/// crate on the thread.
+///
+/// ```
+/// use completeio::task::RuntimeBuilder;
+///
+/// std::thread::spawn(|| {
+///     let runtime = RuntimeBuilder::new().sq_entries(64).build().unwrap();
+///     assert_eq!(runtime.block_on(async { 42 }), 42);
+///     // there is only one runtime in each thread
+///     assert!(RuntimeBuilder::new().build().is_err());
+/// })
+/// .join()
+/// .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RuntimeBuilder {
+    pub(crate) driver: DriverBuilder,
+    pub(crate) event_interval: u32,
+    pub(crate) max_tasks_per_poll: usize,
+    pub(crate) task_budget: Option<u32>,
+}
+
+impl Default for RuntimeBuilder {
+    fn default() -> Self {
+        Self {
+            // the runtime owns the driver on its thread, the setup flags are dropped if the
+            // kernel doesn't support them
+            driver: DriverBuilder::new()
+                .coop_taskrun(true)
+                .defer_taskrun(true)
+                .single_issuer(true),
+            event_interval: 61,
+            max_tasks_per_poll: usize::MAX,
+            task_budget: None,
+        }
+    }
+}
+
+impl RuntimeBuilder {
+    /// Create the builder with the default configuration of the driver.
+    ///
+    /// io-uring driver is set up with [`DriverBuilder::coop_taskrun`],
+    /// [`DriverBuilder::defer_taskrun`] and [`DriverBuilder::single_issuer`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the builder of the driver.
+    pub fn driver(mut self, driver: DriverBuilder) -> Self {
+        self.driver = driver;
+        self
+    }
+
+    /// See [`DriverBuilder::sq_entries`].
+    pub fn sq_entries(mut self, entries: u32) -> Self {
+        self.driver = self.driver.sq_entries(entries);
+        self
+    }
+
+    /// See [`DriverBuilder::sqpoll`].
+    pub fn sqpoll(mut self, idle: Duration) -> Self {
+        self.driver = self.driver.sqpoll(idle);
+        self
+    }
+
+    /// See [`DriverBuilder::coop_taskrun`].
+    pub fn coop_taskrun(mut self, enable: bool) -> Self {
+        self.driver = self.driver.coop_taskrun(enable);
+        self
+    }
+
+    /// Set the number of tasks run before the runtime reaps the completions without waiting,
+    /// 61 by default.
+    ///
+    /// The operations of the tasks are not delayed by the tasks which are always ready.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is zero.
+    pub fn event_interval(mut self, interval: u32) -> Self {
+        assert!(interval > 0, "the event interval should be positive");
+        self.event_interval = interval;
+        self
+    }
+
+    /// Set the number of tasks run before the runtime checks the future of
+    /// [`block_on`](Runtime::block_on) and the tasks woken up by other threads, unlimited by
+    /// default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tasks` is zero.
+    pub fn max_tasks_per_poll(mut self, tasks: usize) -> Self {
+        assert!(
+            tasks > 0,
+            "the runtime should run at least one task per poll"
+        );
+        self.max_tasks_per_poll = tasks;
+        self
+    }
+
+    /// Set the number of completed operations a task receives in a row before it's rescheduled
+    /// behind the other ready tasks, unlimited by default.
+    ///
+    /// The task looping on the operations which complete at once doesn't starve other tasks and
+    /// timers. The runtime rarely needs it, the task awaiting an operation usually yields till
+    /// the operation completes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `budget` is zero.
+    pub fn task_budget(mut self, budget: u32) -> Self {
+        assert!(budget > 0, "the task budget should be positive");
+        self.task_budget = Some(budget);
+        self
+    }
+
+    /// Create the runtime of the current thread.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::ErrorKind::AlreadyExists`] if the runtime of the thread is already created,
+    /// or the error of the driver creation.
+    pub fn build(&self) -> io::Result<Runtime> {
+        RUNTIME.init(self)?;
+        Ok(Runtime {
+            _not_send: PhantomData,
+        })
+    }
+
+    /// Set the configuration of the runtimes created implicitly on the first use of a thread,
+    /// including the workers of [`MultiThreadRuntime`](crate::task::MultiThreadRuntime).
+    ///
+    /// The runtimes created already are not changed.
+    pub fn set_default(&self) {
+        *DEFAULT.lock().unwrap_or_else(|e| e.into_inner()) = Some(self.clone());
+    }
+
+    pub(crate) fn default_config() -> Self {
+        DEFAULT
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+            .unwrap_or_default()
+    }
+}
+
+/// The runtime of the current thread created by [`RuntimeBuilder`].
+///
+/// The handle can't be sent to other threads. The runtime lives till the thread exits, the
+/// functions of [`task`](crate::task) use it on the thread as well.
+#[derive(Debug)]
+pub struct Runtime {
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Runtime {
+    /// Block on the future till it completes, see [`block_on`](crate::task::block_on).
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        crate::task::block_on(future)
+    }
+
+    /// Spawn a new task, see [`spawn`](crate::task::spawn).
+    pub fn spawn<F: Future + 'static>(&self, future: F) -> JoinHandle<F::Output> {
+        crate::task::spawn(future)
+    }
+
+    /// The capabilities of the driver of the runtime.
+    pub fn capabilities(&self) -> DriverCapabilities {
+        RUNTIME.with(|runtime| runtime.capabilities())
+    }
+
+    /// Read the metrics of the runtime.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use completeio::task::RuntimeBuilder;
+    ///
+    /// std::thread::spawn(|| {
+    ///     let runtime = RuntimeBuilder::new().build().unwrap();
+    ///     runtime.block_on(async {
+    ///         let task = runtime.spawn(completeio::time::sleep(Duration::from_millis(50)));
+    ///         let mut interval = completeio::time::interval(Duration::from_millis(10));
+    ///         for _ in 0..3 {
+    ///             interval.tick().await;
+    ///             let metrics = runtime.metrics();
+    ///             println!("{metrics:?}");
+    ///             assert_eq!(metrics.alive_tasks, 1);
+    ///         }
+    ///         task.await.unwrap();
+    ///         assert_eq!(runtime.metrics().alive_tasks, 0);
+    ///     })
+    /// })
+    /// .join()
+    /// .unwrap();
+    /// ```
+    pub fn metrics(&self) -> RuntimeMetrics {
+        RUNTIME.with(|runtime| runtime.metrics())
+    }
+
+    /// Submit the operation, returning the handle to wait for its completion or cancel it.
+    ///
+    /// Unlike the futures of the operations, which drop the operation with its buffer when
+    /// they are dropped, [`SubmitHandle::cancel`] returns the operation back.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use completeio::{op::Timeout, task::RuntimeBuilder};
+    ///
+    /// std::thread::spawn(|| {
+    ///     let runtime = RuntimeBuilder::new().build().unwrap();
+    ///     runtime.block_on(async {
+    ///         let handle = runtime.submit_cancellable(Timeout::new(Duration::from_secs(60)));
+    ///         completeio::time::sleep(Duration::from_millis(1)).await;
+    ///         // the timeout is returned after the driver cancels it
+    ///         let _timeout: Timeout = handle.cancel().await;
+    ///     })
+    /// })
+    /// .join()
+    /// .unwrap();
+    /// ```
+    pub fn submit_cancellable<O: OpCode + 'static>(&self, op: O) -> SubmitHandle<O> {
+        SubmitHandle::new(RUNTIME.with(|runtime| runtime.submit_op(op)))
+    }
+
+    /// List the alive tasks of the runtime, see [`dump_tasks`](crate::task::dump_tasks).
+    pub fn dump_tasks(&self) -> Vec<TaskInfo> {
+        crate::task::dump_tasks()
+    }
+
+    /// Get the handle to spawn tasks on the runtime from other threads.
+    pub fn handle(&self) -> Handle {
+        Handle::current()
+    }
+}
+
+/// The handle to spawn tasks on the runtime of a thread from other threads.
+///
+/// The tasks spawned from other threads are queued to the runtime, and its driver is woken up
+/// to run them. The tasks spawned on the thread of the runtime are not queued.
+///
+/// ```
+/// use completeio::task::Handle;
+///
+/// completeio::task::block_on(async {
+///     let handle = Handle::current();
+///     let task = std::thread::spawn(move || handle.spawn(async { 42 }))
+///         .join()
+///         .unwrap();
+///     assert_eq!(task.await.unwrap(), 42);
+/// })
+/// ```
+#[derive(Debug, Clone)]
+pub struct Handle {
+    remote: Arc<Remote>,
+}
+
+impl Handle {
+    /// Get the handle of the runtime of the current thread.
+    pub fn current() -> Self {
+        Self {
+            remote: RUNTIME.with(|runtime| runtime.remote()),
+        }
+    }
+
+    /// Spawn the future on the runtime, returning a [`JoinHandle`] for it.
+    ///
+    /// See [`Handle::spawn_fn`].
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.spawn_fn(move || future)
+    }
+
+    /// Spawn a task created by `f` on the runtime, returning a [`JoinHandle`] for it.
+    ///
+    /// The future is created on the thread of the runtime, so it doesn't need to be [`Send`].
+    /// The task is never run if the runtime has exited.
+    pub fn spawn_fn<F, Fut>(&self, f: F) -> JoinHandle<Fut::Output>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future + 'static,
+        Fut::Output: Send + 'static,
+    {
+        if self.remote.is_current() {
+            crate::task::spawn(f())
+        } else {
+            let alive = self.remote.alive_task();
+            self.remote.spawn_fn(move || async move {
+                let _alive = alive;
+                trace(None, f()).await
+            })
+        }
+    }
+}
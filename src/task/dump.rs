@@ -0,0 +1,146 @@
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    future::{poll_fn, Future},
+    pin::pin,
+    rc::Rc,
+};
+
+use slab::Slab;
+
+use crate::task::RUNTIME;
+
+/// The state of an alive task returned by [`dump_tasks`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TaskInfo {
+    /// The id of the task, unique among the alive tasks of the runtime.
+    pub id: usize,
+    /// The name of the task spawned with [`spawn_named`](crate::task::spawn_named).
+    pub name: Option<Cow<'static, str>>,
+    /// The user data of the operation the task waited for when it was polled last time.
+    pub waiting_on: Option<usize>,
+}
+
+struct TaskState {
+    name: Option<Cow<'static, str>>,
+    waiting_on: Option<usize>,
+}
+
+/// The alive tasks of a runtime spawned with [`spawn`](crate::task::spawn) and
+/// [`Handle`](crate::task::Handle).
+#[derive(Default)]
+pub(crate) struct TaskRegistry {
+    tasks: RefCell<Slab<TaskState>>,
+    // the task polled now
+    current: Cell<Option<usize>>,
+}
+
+impl TaskRegistry {
+    /// Wrap the future of the task to register it till it's dropped.
+    pub fn trace<F: Future>(
+        self: &Rc<Self>,
+        name: Option<Cow<'static, str>>,
+        future: F,
+    ) -> impl Future<Output = F::Output> {
+        let id = self.tasks.borrow_mut().insert(TaskState {
+            name,
+            waiting_on: None,
+        });
+        let entry = TaskEntry {
+            registry: self.clone(),
+            id,
+        };
+        async move {
+            let mut future = pin!(future);
+            poll_fn(|cx| entry.registry.enter(entry.id, || future.as_mut().poll(cx))).await
+        }
+    }
+
+    /// Record the operation the polled task waits for.
+    pub fn wait_for(&self, user_data: usize) {
+        if let Some(id) = self.current.get() {
+            if let Some(task) = self.tasks.borrow_mut().get_mut(id) {
+                task.waiting_on = Some(user_data);
+            }
+        }
+    }
+
+    pub fn dump(&self) -> Vec<TaskInfo> {
+        self.tasks
+            .borrow()
+            .iter()
+            .map(|(id, task)| TaskInfo {
+                id,
+                name: task.name.clone(),
+                waiting_on: task.waiting_on,
+            })
+            .collect()
+    }
+
+    fn enter<R>(&self, id: usize, f: impl FnOnce() -> R) -> R {
+        struct Restore<'a>(&'a Cell<Option<usize>>, Option<usize>);
+
+        impl Drop for Restore<'_> {
+            fn drop(&mut self) {
+                self.0.set(self.1);
+            }
+        }
+
+        if let Some(task) = self.tasks.borrow_mut().get_mut(id) {
+            task.waiting_on = None;
+        }
+        let _restore = Restore(&self.current, self.current.replace(Some(id)));
+        f()
+    }
+}
+
+// removes the task from the registry when its future is dropped
+struct TaskEntry {
+    registry: Rc<TaskRegistry>,
+    id: usize,
+}
+
+impl Drop for TaskEntry {
+    fn drop(&mut self) {
+        self.registry.tasks.borrow_mut().remove(self.id);
+    }
+}
+
+/// Wrap the future of a task spawned on the current thread for [`dump_tasks`].
+pub(crate) fn trace<F: Future>(
+    name: Option<Cow<'static, str>>,
+    future: F,
+) -> impl Future<Output = F::Output> {
+    RUNTIME
+        .with(|runtime| runtime.task_registry())
+        .trace(name, future)
+}
+
+/// List the alive tasks of the runtime of the current thread.
+///
+/// The tasks spawned with [`spawn`](crate::task::spawn), [`spawn_named`](crate::task::spawn_named)
+/// and [`Handle`](crate::task::Handle) are listed after they are created on the thread of the
+/// runtime. The future of [`block_on`](crate::task::block_on) is not a task.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// completeio::task::block_on(async {
+///     let task = completeio::task::spawn_named(
+///         "sleeper",
+///         completeio::time::sleep(Duration::from_millis(10)),
+///     );
+///     completeio::task::yield_now().await;
+///     let tasks = completeio::task::dump_tasks();
+///     let sleeper = tasks
+///         .iter()
+///         .find(|task| task.name.as_deref() == Some("sleeper"))
+///         .unwrap();
+///     assert!(sleeper.waiting_on.is_some());
+///     task.await.unwrap();
+/// })
+/// ```
+pub fn dump_tasks() -> Vec<TaskInfo> {
+    RUNTIME.with(|runtime| runtime.task_registry().dump())
+}
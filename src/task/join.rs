@@ -0,0 +1,182 @@
+use std::{
+    any::Any,
+    error::Error,
+    fmt,
+    future::Future,
+    panic::AssertUnwindSafe,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use async_task::{FallibleTask, Task};
+use futures_util::{
+    future::{AbortHandle, Abortable, Aborted, CatchUnwind},
+    FutureExt,
+};
+
+// the spawned future, the panic is caught and the future is dropped after the abort
+pub(crate) type JoinFuture<F> = Abortable<CatchUnwind<AssertUnwindSafe<F>>>;
+
+// the output of the spawned `JoinFuture`
+pub(crate) type JoinOutput<T> = Result<std::thread::Result<T>, Aborted>;
+
+/// Wrap the future to be spawned, returning the handle to abort it.
+pub(crate) fn join_future<F: Future>(future: F) -> (JoinFuture<F>, AbortHandle) {
+    let (abort, registration) = AbortHandle::new_pair();
+    (
+        Abortable::new(AssertUnwindSafe(future).catch_unwind(), registration),
+        abort,
+    )
+}
+
+/// An owned permission to join on a spawned task, awaiting its output.
+///
+/// The task is detached when the handle is dropped, it keeps running in the background.
+///
+/// ```
+/// completeio::task::block_on(async {
+///     let task = completeio::task::spawn(async { panic!("boom") });
+///
+///     let err = task.await.unwrap_err();
+///     assert!(err.is_panic());
+///     assert_eq!(*err.into_panic().downcast::<&str>().unwrap(), "boom");
+/// })
+/// ```
+#[derive(Debug)]
+#[must_use = "the task is detached when the handle is dropped"]
+pub struct JoinHandle<T> {
+    // `None` after the task is detached
+    task: Option<FallibleTask<JoinOutput<T>>>,
+    abort: AbortHandle,
+}
+
+impl<T> JoinHandle<T> {
+    pub(crate) fn new(task: Task<JoinOutput<T>>, abort: AbortHandle) -> Self {
+        Self {
+            task: Some(task.fallible()),
+            abort,
+        }
+    }
+
+    /// Abort the task.
+    ///
+    /// The task is woken up and dropped at its next await point, awaiting the handle then returns
+    /// the cancelled [`JoinError`]. The task which has already completed returns its output.
+    pub fn abort(&self) {
+        self.abort.abort()
+    }
+
+    /// Returns `true` if the task has completed, was aborted or panicked.
+    pub fn is_finished(&self) -> bool {
+        self.task.as_ref().is_none_or(FallibleTask::is_finished)
+    }
+
+    /// Detach the task to let it keep running in the background, the same as dropping the handle.
+    pub fn detach(self) {
+        drop(self)
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let task = self
+            .task
+            .as_mut()
+            .expect("`JoinHandle` is polled after completion");
+        let output = match Pin::new(task).poll(cx) {
+            Poll::Ready(output) => output,
+            Poll::Pending => return Poll::Pending,
+        };
+        self.task = None;
+        Poll::Ready(match output {
+            Some(Ok(Ok(output))) => Ok(output),
+            Some(Ok(Err(payload))) => Err(JoinError {
+                repr: Repr::Panic(payload),
+            }),
+            // the task is dropped by the runtime before it completes
+            Some(Err(Aborted)) | None => Err(JoinError {
+                repr: Repr::Cancelled,
+            }),
+        })
+    }
+}
+
+impl<T> Drop for JoinHandle<T> {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.detach();
+        }
+    }
+}
+
+/// The task failed to complete, returned by awaiting [`JoinHandle`].
+pub struct JoinError {
+    repr: Repr,
+}
+
+enum Repr {
+    Cancelled,
+    Panic(Box<dyn Any + Send + 'static>),
+}
+
+impl JoinError {
+    /// Returns `true` if the task was aborted or dropped by the runtime.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.repr, Repr::Cancelled)
+    }
+
+    /// Returns `true` if the task panicked.
+    pub fn is_panic(&self) -> bool {
+        matches!(self.repr, Repr::Panic(_))
+    }
+
+    /// Consume the error, returning the payload of the panic.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the task was cancelled.
+    pub fn into_panic(self) -> Box<dyn Any + Send + 'static> {
+        self.try_into_panic()
+            .expect("`JoinError` reason is not a panic")
+    }
+
+    /// Consume the error, returning the payload of the panic or the error itself if the task was
+    /// cancelled.
+    pub fn try_into_panic(self) -> Result<Box<dyn Any + Send + 'static>, JoinError> {
+        match self.repr {
+            Repr::Panic(payload) => Ok(payload),
+            Repr::Cancelled => Err(self),
+        }
+    }
+}
+
+impl fmt::Debug for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.repr {
+            Repr::Cancelled => f.write_str("JoinError::Cancelled"),
+            Repr::Panic(_) => f.write_str("JoinError::Panic(..)"),
+        }
+    }
+}
+
+impl fmt::Display for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.repr {
+            Repr::Cancelled => f.write_str("task was cancelled"),
+            Repr::Panic(payload) => {
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .copied()
+                    .or_else(|| payload.downcast_ref::<String>().map(String::as_str));
+                match message {
+                    Some(message) => write!(f, "task panicked: {message}"),
+                    None => f.write_str("task panicked"),
+                }
+            }
+        }
+    }
+}
+
+impl Error for JoinError {}
@@ -0,0 +1,174 @@
+use std::{
+    cell::RefCell,
+    error::Error,
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Declare new task-local storage keys of type [`LocalKey`].
+///
+/// The value of a key is set for a future with [`LocalKey::scope`], it's available while the
+/// future is polled.
+///
+/// ```
+/// completeio::task_local! {
+///     static REQUEST_ID: u32;
+/// }
+///
+/// completeio::task::block_on(async {
+///     REQUEST_ID
+///         .scope(42, async {
+///             assert_eq!(REQUEST_ID.get(), 42);
+///         })
+///         .await;
+///     assert!(REQUEST_ID.try_with(|_| ()).is_err());
+/// })
+/// ```
+#[macro_export]
+macro_rules! task_local {
+    () => {};
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty; $($rest:tt)*) => {
+        $crate::__task_local_inner!($(#[$attr])* $vis $name, $t);
+        $crate::task_local!($($rest)*);
+    };
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty) => {
+        $crate::__task_local_inner!($(#[$attr])* $vis $name, $t);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __task_local_inner {
+    ($(#[$attr:meta])* $vis:vis $name:ident, $t:ty) => {
+        $(#[$attr])*
+        $vis static $name: $crate::task::LocalKey<$t> = {
+            ::std::thread_local! {
+                static __KEY: ::std::cell::RefCell<::std::option::Option<$t>> =
+                    const { ::std::cell::RefCell::new(::std::option::Option::None) };
+            }
+            $crate::task::LocalKey { inner: __KEY }
+        };
+    };
+}
+
+/// A key for task-local data, declared with [`task_local!`](crate::task_local).
+///
+/// The value is stored in the future returned by [`LocalKey::scope`] and moved to the thread
+/// while the future is polled, so accessing it doesn't allocate.
+pub struct LocalKey<T: 'static> {
+    #[doc(hidden)]
+    pub inner: std::thread::LocalKey<RefCell<Option<T>>>,
+}
+
+impl<T: 'static> LocalKey<T> {
+    /// Set the value of the key for the future.
+    pub fn scope<F: Future>(&'static self, value: T, future: F) -> TaskLocalFuture<T, F> {
+        TaskLocalFuture {
+            local: self,
+            slot: Some(value),
+            future,
+        }
+    }
+
+    /// Set the value of the key while the closure runs.
+    pub fn sync_scope<R>(&'static self, value: T, f: impl FnOnce() -> R) -> R {
+        let mut slot = Some(value);
+        self.enter(&mut slot, f)
+    }
+
+    /// Access the value of the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not set by [`LocalKey::scope`].
+    pub fn with<R>(&'static self, f: impl FnOnce(&T) -> R) -> R {
+        self.try_with(f)
+            .expect("cannot access a task-local value without setting it")
+    }
+
+    /// Access the value of the key, returning an error if it's not set.
+    pub fn try_with<R>(&'static self, f: impl FnOnce(&T) -> R) -> Result<R, AccessError> {
+        self.inner.with(|inner| {
+            let value = inner.try_borrow().map_err(|_| AccessError)?;
+            value.as_ref().map(f).ok_or(AccessError)
+        })
+    }
+
+    /// Get a copy of the value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not set by [`LocalKey::scope`].
+    pub fn get(&'static self) -> T
+    where
+        T: Clone,
+    {
+        self.with(T::clone)
+    }
+
+    // swap the value of the scope with the one of the thread while `f` runs
+    fn enter<R>(&'static self, slot: &mut Option<T>, f: impl FnOnce() -> R) -> R {
+        struct Guard<'a, T: 'static> {
+            local: &'static LocalKey<T>,
+            slot: &'a mut Option<T>,
+        }
+
+        impl<T: 'static> Drop for Guard<'_, T> {
+            fn drop(&mut self) {
+                self.local
+                    .inner
+                    .with(|inner| std::mem::swap(self.slot, &mut *inner.borrow_mut()));
+            }
+        }
+
+        self.inner
+            .with(|inner| std::mem::swap(slot, &mut *inner.borrow_mut()));
+        let _guard = Guard { local: self, slot };
+        f()
+    }
+}
+
+impl<T: 'static> fmt::Debug for LocalKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalKey").finish_non_exhaustive()
+    }
+}
+
+/// Future returned by [`LocalKey::scope`].
+#[must_use = "futures do nothing unless polled"]
+pub struct TaskLocalFuture<T: 'static, F> {
+    local: &'static LocalKey<T>,
+    slot: Option<T>,
+    future: F,
+}
+
+impl<T: 'static, F: Future> Future for TaskLocalFuture<T, F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: the future is not moved out of the pinned wrapper, the value isn't pinned
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+        this.local.enter(&mut this.slot, || future.poll(cx))
+    }
+}
+
+impl<T: 'static, F> fmt::Debug for TaskLocalFuture<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TaskLocalFuture").finish_non_exhaustive()
+    }
+}
+
+/// An error returned by [`LocalKey::try_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccessError;
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("task-local value not set")
+    }
+}
+
+impl Error for AccessError {}
@@ -0,0 +1,22 @@
+use crate::driver::DriverStats;
+
+/// A snapshot of the counters of a runtime returned by
+/// [`Runtime::metrics`](crate::task::Runtime::metrics).
+///
+/// The snapshot is read from the state of the runtime on its thread, it's not
+/// synchronized with other threads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RuntimeMetrics {
+    /// Operations pushed to the driver which are not completed, including the cancelled ones.
+    pub in_flight_ops: usize,
+    /// Operations waiting for the space in the submission queue of the driver.
+    pub queued_ops: usize,
+    /// Tasks spawned with [`spawn`](crate::task::spawn) or [`Handle`](crate::task::Handle)
+    /// which are not completed or dropped.
+    pub alive_tasks: usize,
+    /// Operations completed since the runtime is created.
+    pub completed_ops: u64,
+    /// The counters of the driver.
+    pub driver: DriverStats,
+}
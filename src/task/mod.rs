@@ -1,6 +1,7 @@
 //! The runtime of completeio.
-//! We don't expose the runtime struct because there could be only one runtime
-//! in each thread.
+//! There could be only one runtime in each thread, it's created on the first
+//! use or explicitly with [`RuntimeBuilder`]. [`MultiThreadRuntime`] runs the
+//! tasks on the runtimes of its worker threads.
 //!
 //! ```
 //! let ans = completeio::task::block_on(async {
@@ -10,17 +11,80 @@
 //! assert_eq!(ans, 42);
 //! ```
 
-use std::future::Future;
-
-use async_task::Task;
+use std::{
+    borrow::Cow,
+    cell::OnceCell,
+    future::Future,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
 
 pub(crate) mod runtime;
-use runtime::Runtime;
 
 pub(crate) mod op;
+pub use op::SubmitHandle;
+
+mod blocking;
+pub use blocking::spawn_blocking;
+pub(crate) use blocking::{run_blocking, BlockingPool};
+
+mod builder;
+pub use builder::*;
+
+mod dump;
+pub use dump::{dump_tasks, TaskInfo};
+
+mod join;
+pub use join::*;
+
+mod local;
+pub use local::*;
+
+mod metrics;
+pub use metrics::*;
+
+mod multi_thread;
+pub use multi_thread::*;
 
 thread_local! {
-    pub(crate) static RUNTIME: Runtime = Runtime::new().expect("cannot create completeio runtime");
+    static CURRENT: OnceCell<runtime::Runtime> = const { OnceCell::new() };
+}
+
+/// The runtime of the current thread, created on the first use.
+pub(crate) static RUNTIME: CurrentRuntime = CurrentRuntime;
+
+pub(crate) struct CurrentRuntime;
+
+impl CurrentRuntime {
+    /// Acquire a reference to the runtime of the thread.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the runtime can't be created with the default configuration.
+    pub fn with<R>(&'static self, f: impl FnOnce(&runtime::Runtime) -> R) -> R {
+        CURRENT.with(|current| {
+            f(current.get_or_init(|| {
+                runtime::Runtime::new(&RuntimeBuilder::default_config())
+                    .expect("cannot create completeio runtime")
+            }))
+        })
+    }
+
+    fn init(&'static self, builder: &RuntimeBuilder) -> io::Result<()> {
+        CURRENT.with(|current| {
+            if current.get().is_some() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    "the runtime of the thread is already created",
+                ));
+            }
+            let runtime = runtime::Runtime::new(builder)?;
+            // the driver is created without the runtime
+            let _ = current.set(runtime);
+            Ok(())
+        })
+    }
 }
 
 /// Start a completeio runtime and block on the future till it completes.
@@ -46,10 +110,12 @@ pub fn block_on<F: Future>(future: F) -> F::Output {
     RUNTIME.with(|runtime| runtime.block_on(future))
 }
 
-/// Spawns a new asynchronous task, returning a [`Task`] for it.
+/// Spawns a new asynchronous task, returning a [`JoinHandle`] for it.
 ///
 /// Spawning a task enables the task to execute concurrently to other tasks.
 /// There is no guarantee that a spawned task will execute to completion.
+/// The panic of the task is returned by the handle, the task is detached
+/// when the handle is dropped.
 ///
 /// ```
 /// completeio::task::block_on(async {
@@ -58,9 +124,73 @@ pub fn block_on<F: Future>(future: F) -> F::Output {
 ///         42
 ///     });
 ///
-///     assert_eq!(task.await, 42);
+///     assert_eq!(task.await.unwrap(), 42);
+/// })
+/// ```
+pub fn spawn<F: Future + 'static>(future: F) -> JoinHandle<F::Output> {
+    spawn_traced(None, future)
+}
+
+/// Spawns a new asynchronous task with the name listed by [`dump_tasks`], returning a
+/// [`JoinHandle`] for it.
+///
+/// ```
+/// completeio::task::block_on(async {
+///     let task = completeio::task::spawn_named("answer", async { 42 });
+///     assert_eq!(task.await.unwrap(), 42);
 /// })
 /// ```
-pub fn spawn<F: Future + 'static>(future: F) -> Task<F::Output> {
-    RUNTIME.with(|runtime| runtime.spawn(future))
+pub fn spawn_named<F: Future + 'static>(
+    name: impl Into<Cow<'static, str>>,
+    future: F,
+) -> JoinHandle<F::Output> {
+    spawn_traced(Some(name.into()), future)
+}
+
+fn spawn_traced<F: Future + 'static>(
+    name: Option<Cow<'static, str>>,
+    future: F,
+) -> JoinHandle<F::Output> {
+    RUNTIME.with(|runtime| {
+        let alive = runtime.remote().alive_task();
+        let future = runtime.task_registry().trace(name, future);
+        let (future, abort) = join_future(async move {
+            let _alive = alive;
+            future.await
+        });
+        JoinHandle::new(runtime.spawn(future), abort)
+    })
+}
+
+/// Yields execution back to the runtime.
+///
+/// The task is rescheduled behind the other ready tasks, the runtime reaps the
+/// completions of the driver in the meantime.
+///
+/// ```
+/// completeio::task::block_on(async {
+///     let task = completeio::task::spawn(async { 42 });
+///     // the spawned task runs before the current one is resumed
+///     completeio::task::yield_now().await;
+///     assert!(task.is_finished());
+/// })
+/// ```
+pub async fn yield_now() {
+    YieldNow(false).await
+}
+
+// pending on the first poll
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
 }
@@ -0,0 +1,304 @@
+use std::{
+    future::Future,
+    io,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::task::{
+    blocking::{DEFAULT_KEEP_ALIVE, DEFAULT_MAX_THREADS},
+    dump::trace,
+    runtime::Remote,
+    BlockingPool, JoinHandle, RuntimeBuilder, RuntimeMetrics, RUNTIME,
+};
+
+/// Builder of [`MultiThreadRuntime`].
+///
+/// ```
+/// use completeio::task::Builder;
+///
+/// let runtime = Builder::new_multi_thread()
+///     .worker_threads(2)
+///     .build()
+///     .unwrap();
+/// let task = runtime.spawn(|| async { 42 });
+/// assert_eq!(completeio::task::block_on(task).unwrap(), 42);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Builder {
+    worker_threads: Option<usize>,
+    max_blocking_threads: usize,
+    thread_keep_alive: Duration,
+}
+
+impl Builder {
+    /// Create the builder of the runtime with one worker thread per CPU.
+    pub fn new_multi_thread() -> Self {
+        Self {
+            worker_threads: None,
+            max_blocking_threads: DEFAULT_MAX_THREADS,
+            thread_keep_alive: DEFAULT_KEEP_ALIVE,
+        }
+    }
+
+    /// Set the number of worker threads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threads` is zero.
+    pub fn worker_threads(mut self, threads: usize) -> Self {
+        assert!(threads > 0, "the runtime needs at least one worker thread");
+        self.worker_threads = Some(threads);
+        self
+    }
+
+    /// Set the limit of the threads running the closures of
+    /// [`spawn_blocking`](crate::task::spawn_blocking), 512 by default.
+    ///
+    /// The pool is shared by the workers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threads` is zero.
+    pub fn max_blocking_threads(mut self, threads: usize) -> Self {
+        assert!(threads > 0, "the blocking pool needs at least one thread");
+        self.max_blocking_threads = threads;
+        self
+    }
+
+    /// Set how long the idle blocking thread waits for closures before it exits, 10 seconds by
+    /// default.
+    pub fn thread_keep_alive(mut self, keep_alive: Duration) -> Self {
+        self.thread_keep_alive = keep_alive;
+        self
+    }
+
+    /// Start the worker threads, each worker creates its own driver.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the worker which can't create its runtime, the started workers are
+    /// stopped.
+    pub fn build(&self) -> io::Result<MultiThreadRuntime> {
+        let threads = match self.worker_threads {
+            Some(threads) => threads,
+            None => thread::available_parallelism()?.get(),
+        };
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let blocking_pool = Arc::new(BlockingPool::new(
+            self.max_blocking_threads,
+            self.thread_keep_alive,
+        ));
+        let mut runtime = MultiThreadRuntime {
+            workers: Vec::with_capacity(threads),
+            threads: Vec::with_capacity(threads),
+            next: AtomicUsize::new(0),
+        };
+        let mut states = Vec::with_capacity(threads);
+        for index in 0..threads {
+            let state = Arc::new(WorkerState::default());
+            let ready_tx = ready_tx.clone();
+            let worker_state = state.clone();
+            let blocking_pool = blocking_pool.clone();
+            let thread = thread::Builder::new()
+                .name(format!("completeio-worker-{index}"))
+                .spawn(move || run_worker(index, &worker_state, blocking_pool, ready_tx))?;
+            runtime.threads.push(thread);
+            states.push(state);
+        }
+        drop(ready_tx);
+        let mut remotes = vec![None; threads];
+        let mut error = None;
+        for (index, remote) in ready_rx {
+            match remote {
+                Ok(remote) => remotes[index] = Some(remote),
+                Err(e) => error = Some(e),
+            }
+        }
+        runtime.workers = states
+            .into_iter()
+            .zip(remotes)
+            .filter_map(|(state, remote)| {
+                Some(Worker {
+                    state,
+                    remote: remote?,
+                })
+            })
+            .collect();
+        match error {
+            // the dropped runtime stops the started workers
+            Some(e) => Err(e),
+            None => Ok(runtime),
+        }
+    }
+}
+
+/// The runtime running the tasks on several worker threads, each worker thread has its own
+/// driver.
+///
+/// The tasks are not moved between the workers. The task spawned from a worker thread runs on
+/// the same worker, other tasks are distributed over the workers in turn. The file and socket
+/// handles created by a task are attached to the driver of its worker, the operations on them
+/// fail with [`io::ErrorKind::InvalidInput`] on other threads.
+///
+/// The runtime is shut down when it's dropped, see [`MultiThreadRuntime::shutdown`].
+#[derive(Debug)]
+pub struct MultiThreadRuntime {
+    workers: Vec<Worker>,
+    threads: Vec<thread::JoinHandle<()>>,
+    // the worker of the next task spawned outside of the workers
+    next: AtomicUsize,
+}
+
+impl MultiThreadRuntime {
+    /// The number of worker threads.
+    pub fn worker_threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Spawn a task created by `f` on a worker thread, returning a [`JoinHandle`] for it.
+    ///
+    /// The future is created on the worker thread, so it doesn't need to be [`Send`]. The task
+    /// could be awaited on any thread, e.g. in [`block_on`](crate::task::block_on).
+    pub fn spawn<F, Fut>(&self, f: F) -> JoinHandle<Fut::Output>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future + 'static,
+        Fut::Output: Send + 'static,
+    {
+        let worker = self.current_worker().unwrap_or_else(|| {
+            &self.workers[self.next.fetch_add(1, Ordering::Relaxed) % self.workers.len()]
+        });
+        let guard = TaskGuard::new(worker.state.clone());
+        let alive = worker.remote.alive_task();
+        worker.remote.spawn_fn(move || async move {
+            let _guard = guard;
+            let _alive = alive;
+            trace(None, f()).await
+        })
+    }
+
+    /// Collect the [metrics](crate::task::Runtime::metrics) of the workers, in the order of
+    /// their threads.
+    ///
+    /// The metrics are read by a task on each worker, it's not counted in
+    /// [`RuntimeMetrics::alive_tasks`].
+    ///
+    /// ```
+    /// use completeio::task::Builder;
+    ///
+    /// let runtime = Builder::new_multi_thread()
+    ///     .worker_threads(2)
+    ///     .build()
+    ///     .unwrap();
+    /// let metrics = completeio::task::block_on(runtime.metrics());
+    /// assert_eq!(metrics.len(), 2);
+    /// assert!(metrics.iter().all(|metrics| metrics.alive_tasks == 0));
+    /// ```
+    pub async fn metrics(&self) -> Vec<RuntimeMetrics> {
+        let tasks = self
+            .workers
+            .iter()
+            .map(|worker| {
+                worker
+                    .remote
+                    .spawn_fn(|| async { RUNTIME.with(|runtime| runtime.metrics()) })
+            })
+            .collect::<Vec<_>>();
+        let mut metrics = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            metrics.push(
+                task.await
+                    .expect("the worker exits after the runtime is dropped"),
+            );
+        }
+        metrics
+    }
+
+    /// Wait for the spawned tasks to complete and stop the workers.
+    ///
+    /// The tasks are still able to spawn new tasks till they complete. The workers wait for
+    /// completions of the operations in flight before they exit, the tasks spawned with
+    /// [`spawn`](crate::task::spawn) are dropped. The last worker waits for the running closures
+    /// of [`spawn_blocking`](crate::task::spawn_blocking) and drops the queued ones.
+    pub fn shutdown(self) {
+        drop(self)
+    }
+
+    fn current_worker(&self) -> Option<&Worker> {
+        self.workers
+            .iter()
+            .find(|worker| worker.remote.is_current())
+    }
+}
+
+impl Drop for MultiThreadRuntime {
+    fn drop(&mut self) {
+        for worker in &self.workers {
+            worker.state.shutdown.store(true, Ordering::Release);
+            worker.remote.wake();
+        }
+        let current = thread::current().id();
+        for thread in self.threads.drain(..) {
+            // the worker dropping the runtime exits after its tasks
+            if thread.thread().id() != current {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Worker {
+    state: Arc<WorkerState>,
+    remote: Arc<Remote>,
+}
+
+#[derive(Debug, Default)]
+struct WorkerState {
+    // the spawned tasks which are not completed or dropped
+    tasks: AtomicUsize,
+    shutdown: AtomicBool,
+}
+
+impl WorkerState {
+    fn is_finished(&self) -> bool {
+        self.shutdown.load(Ordering::Acquire) && self.tasks.load(Ordering::Acquire) == 0
+    }
+}
+
+struct TaskGuard(Arc<WorkerState>);
+
+impl TaskGuard {
+    fn new(state: Arc<WorkerState>) -> Self {
+        state.tasks.fetch_add(1, Ordering::AcqRel);
+        Self(state)
+    }
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        self.0.tasks.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+type Ready = mpsc::Sender<(usize, io::Result<Arc<Remote>>)>;
+
+fn run_worker(index: usize, state: &WorkerState, blocking_pool: Arc<BlockingPool>, ready: Ready) {
+    let remote = RuntimeBuilder::default_config().build().map(|_runtime| {
+        RUNTIME.with(|runtime| {
+            runtime.set_blocking_pool(blocking_pool);
+            runtime.remote()
+        })
+    });
+    let started = remote.is_ok();
+    let _ = ready.send((index, remote));
+    drop(ready);
+    if started {
+        RUNTIME.with(|runtime| runtime.run_until(|| state.is_finished().then_some(())));
+    }
+}
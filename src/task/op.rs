@@ -1,4 +1,5 @@
 use std::{
+    collections::VecDeque,
     future::Future,
     io,
     marker::PhantomData,
@@ -6,6 +7,8 @@ use std::{
     task::{Context, Poll, Waker},
 };
 
+#[cfg(target_os = "linux")]
+use futures_util::Stream;
 use slab::Slab;
 
 use crate::{
@@ -22,6 +25,9 @@ pub(super) struct RegisteredOp {
     pub waker: Option<Waker>,
     pub result: Option<io::Result<usize>>,
     pub cancelled: bool,
+    // the cancellation is pushed, the result is still received by the future
+    pub cancel_requested: bool,
+    pub multishot: Option<Multishot>,
 }
 
 impl RegisteredOp {
@@ -31,13 +37,30 @@ impl RegisteredOp {
             waker: None,
             result: None,
             cancelled: false,
+            cancel_requested: false,
+            multishot: None,
         }
     }
 }
 
+/// The state of an operation that completes with several entries.
+pub(super) struct Multishot {
+    results: VecDeque<io::Result<usize>>,
+    // the operation is canceled when so many results are buffered
+    capacity: usize,
+    finished: bool,
+    // releases the resources of a result that no one is going to receive
+    discard: fn(io::Result<usize>),
+}
+
 #[derive(Default)]
 pub(super) struct OpRuntime {
     ops: Slab<RegisteredOp>,
+    // the operations which are not completed, dummy ones are not counted
+    pending: usize,
+    completed: u64,
+    // the multishot operations whose buffers are full, they should be canceled
+    overflowed: Vec<usize>,
 }
 
 impl OpRuntime {
@@ -45,10 +68,28 @@ impl OpRuntime {
         let op: &'static mut dyn OpCode = Box::leak(Box::new(op));
         let op_ptr = op as *mut dyn OpCode;
         let user_data = self.ops.insert(RegisteredOp::new(Some(op)));
+        self.pending += 1;
         // SAFETY: we leaked box and remove the allocation only during remove
         unsafe { (Key::new(user_data), &mut *op_ptr) }
     }
 
+    #[cfg(target_os = "linux")]
+    pub fn insert_multishot<T: OpCode + 'static>(
+        &mut self,
+        op: T,
+        capacity: usize,
+        discard: fn(io::Result<usize>),
+    ) -> (Key<T>, &'static mut dyn OpCode) {
+        let (key, op) = self.insert(op);
+        self.ops[*key].multishot = Some(Multishot {
+            results: VecDeque::new(),
+            capacity,
+            finished: false,
+            discard,
+        });
+        (key, op)
+    }
+
     pub fn insert_dummy(&mut self) -> Key<()> {
         Key::new_dummy(self.ops.insert(RegisteredOp::new(None)))
     }
@@ -59,18 +100,54 @@ impl OpRuntime {
         }
     }
 
-    pub fn update_result<T>(&mut self, key: Key<T>, result: io::Result<usize>) {
+    pub fn update_result<T>(&mut self, key: Key<T>, result: io::Result<usize>, more: bool) {
         if let Some(op) = self.ops.get_mut(*key) {
             if let Some(waker) = op.waker.take() {
                 waker.wake();
             }
+            if op.op.is_some() && !(more && op.multishot.is_some()) {
+                self.pending -= 1;
+                self.completed += 1;
+            }
+            if let Some(multishot) = op.multishot.as_mut() {
+                if op.cancelled {
+                    (multishot.discard)(result);
+                } else if op.cancel_requested && !more && result.is_err() {
+                    // the operation is stopped by the overflow, the stream ends without an error
+                } else {
+                    multishot.results.push_back(result);
+                    if more && !op.cancel_requested && multishot.results.len() >= multishot.capacity
+                    {
+                        op.cancel_requested = true;
+                        self.overflowed.push(*key);
+                    }
+                }
+                if !more {
+                    multishot.finished = true;
+                    if op.cancelled {
+                        self.remove_untyped(*key);
+                    }
+                }
+                return;
+            }
             op.result = Some(result);
             if op.cancelled {
-                self.remove(key);
+                // the key has no type of the operation
+                self.remove_untyped(*key);
             }
         }
     }
 
+    /// The operations which are not completed.
+    pub fn pending(&self) -> usize {
+        self.pending
+    }
+
+    /// The operations completed since the runtime is created.
+    pub fn completed(&self) -> u64 {
+        self.completed
+    }
+
     pub fn has_result<T>(&mut self, key: Key<T>) -> bool {
         self.ops
             .get_mut(*key)
@@ -78,14 +155,84 @@ impl OpRuntime {
             .unwrap_or_default()
     }
 
-    pub fn cancel<T>(&mut self, key: Key<T>) {
-        if let Some(ops) = self.ops.get_mut(*key) {
-            ops.cancelled = true;
+    /// Marks the operation cancelled, its result and buffer are dropped on
+    /// completion.
+    ///
+    /// Returns `true` if the operation is still running, the completed one
+    /// is removed at once.
+    pub fn cancel<T>(&mut self, key: Key<T>) -> bool {
+        let Some(op) = self.ops.get_mut(*key) else {
+            return false;
+        };
+        if op.result.is_some() {
+            self.remove_untyped(*key);
+            return false;
+        }
+        op.cancelled = true;
+        true
+    }
+
+    /// Marks the cancellation of the operation requested, the operation
+    /// is still received by its future on completion.
+    pub fn request_cancel<T>(&mut self, key: Key<T>) {
+        if let Some(op) = self.ops.get_mut(*key) {
+            op.cancel_requested = true;
+        }
+    }
+
+    /// Returns `true` if the cancellation of the operation in the slot is
+    /// requested, the slot of a completed operation could be reused.
+    pub fn is_cancel_requested(&self, user_data: usize) -> bool {
+        self.ops
+            .get(user_data)
+            .is_some_and(|op| op.cancelled || op.cancel_requested)
+    }
+
+    /// Pops the next result of a multishot operation.
+    ///
+    /// Returns `Some(None)` when the operation has finished and all its
+    /// results are received.
+    #[cfg(target_os = "linux")]
+    pub fn next_result<T>(&mut self, key: Key<T>) -> Option<Option<io::Result<usize>>> {
+        let multishot = self.ops.get_mut(*key)?.multishot.as_mut()?;
+        match multishot.results.pop_front() {
+            Some(result) => Some(Some(result)),
+            None if multishot.finished => Some(None),
+            None => None,
+        }
+    }
+
+    /// Marks a multishot operation cancelled and discards its buffered
+    /// results.
+    ///
+    /// Returns `true` if the operation is still running.
+    #[cfg(target_os = "linux")]
+    pub fn cancel_multishot<T>(&mut self, key: Key<T>) -> bool {
+        let Some(op) = self.ops.get_mut(*key) else {
+            return false;
+        };
+        op.cancelled = true;
+        let Some(multishot) = op.multishot.as_mut() else {
+            return true;
+        };
+        multishot.results.drain(..).for_each(multishot.discard);
+        if multishot.finished {
+            self.remove_untyped(*key);
+            false
+        } else {
+            true
         }
     }
 
     pub fn remove<T>(&mut self, key: Key<T>) -> (Option<io::Result<usize>>, Option<T>) {
         let registered_op = self.ops.remove(*key);
+        if registered_op.op.is_some()
+            && registered_op.result.is_none()
+            && registered_op.multishot.is_none()
+        {
+            // the operation is taken back before it's pushed to the driver
+            self.pending -= 1;
+        }
         let maybe_op = registered_op.op.map(|op| {
             let mut_ptr = op as *mut dyn OpCode;
             let ptr = mut_ptr.cast::<T>();
@@ -96,6 +243,18 @@ impl OpRuntime {
         (registered_op.result, maybe_op)
     }
 
+    fn remove_untyped(&mut self, user_data: usize) {
+        if let Some(op) = self.ops.remove(user_data).op {
+            // SAFETY: the allocation is leaked in `insert`
+            drop(unsafe { Box::from_raw(op as *mut dyn OpCode) });
+        }
+    }
+
+    /// Takes the multishot operations to cancel because their buffers are full.
+    pub fn take_overflowed(&mut self) -> Vec<usize> {
+        std::mem::take(&mut self.overflowed)
+    }
+
     pub fn completer(&mut self) -> &mut Self {
         self
     }
@@ -107,11 +266,16 @@ impl Extend<Entry> for OpRuntime {
         T: IntoIterator<Item = Entry>,
     {
         for entry in iter.into_iter() {
-            self.update_result(Key::new_dummy(entry.user_data()), entry.into_result());
+            let more = entry.has_more();
+            self.update_result(Key::new_dummy(entry.user_data()), entry.into_result(), more);
         }
     }
 }
 
+/// The future of a submitted operation.
+///
+/// Dropping the future before completion cancels the operation. The runtime
+/// keeps the operation and its buffer till the driver completes it.
 #[derive(Debug)]
 pub struct OpFuture<T: 'static> {
     user_data: Key<T>,
@@ -127,6 +291,10 @@ impl<T> OpFuture<T> {
             _p: PhantomData,
         }
     }
+
+    pub fn key(&self) -> Key<T> {
+        self.user_data
+    }
 }
 
 impl<T: OpCode + 'static> Future for OpFuture<T> {
@@ -153,6 +321,47 @@ impl Future for OpFuture<()> {
     }
 }
 
+/// The handle of an operation submitted with
+/// [`Runtime::submit_cancellable`](crate::task::Runtime::submit_cancellable).
+///
+/// Dropping the handle cancels the operation, the operation and its buffer are
+/// dropped on completion.
+#[derive(Debug)]
+#[must_use = "the operation is cancelled when the handle is dropped"]
+pub struct SubmitHandle<O: 'static> {
+    future: OpFuture<O>,
+}
+
+impl<O: OpCode + 'static> SubmitHandle<O> {
+    pub(crate) fn new(future: OpFuture<O>) -> Self {
+        Self { future }
+    }
+
+    /// Wait for the operation to complete, returning its result and the
+    /// operation.
+    pub async fn wait(self) -> (io::Result<usize>, O) {
+        self.future.await
+    }
+
+    /// Cancel the operation, returning it after it completes.
+    ///
+    /// The operation which is not pushed to the driver yet or has completed
+    /// already is returned at once. Otherwise the cancellation is pushed to
+    /// the driver and the handle waits for the last completion of the
+    /// operation. The result is discarded, e.g. the buffer of a read
+    /// completed before the cancellation holds the data.
+    pub async fn cancel(mut self) -> O {
+        let user_data = self.future.user_data;
+        match crate::task::RUNTIME.with(|runtime| runtime.cancel_op_and_take(user_data)) {
+            Some(op) => {
+                self.future.completed = true;
+                op
+            }
+            None => self.future.await.1,
+        }
+    }
+}
+
 impl<T> Drop for OpFuture<T> {
     fn drop(&mut self) {
         if !self.completed {
@@ -160,3 +369,50 @@ impl<T> Drop for OpFuture<T> {
         }
     }
 }
+
+/// A stream of the results of a multishot operation.
+///
+/// Dropping the stream cancels the operation.
+#[cfg(target_os = "linux")]
+#[derive(Debug)]
+pub struct OpStream<T: 'static> {
+    user_data: Key<T>,
+    completed: bool,
+    _p: PhantomData<&'static T>,
+}
+
+#[cfg(target_os = "linux")]
+impl<T> OpStream<T> {
+    pub fn new(user_data: Key<T>) -> Self {
+        Self {
+            user_data,
+            completed: false,
+            _p: PhantomData,
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl<T: OpCode + 'static> Stream for OpStream<T> {
+    type Item = io::Result<usize>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.completed {
+            return Poll::Ready(None);
+        }
+        let res = crate::task::RUNTIME.with(|runtime| runtime.poll_multishot(cx, self.user_data));
+        if let Poll::Ready(None) = res {
+            self.get_mut().completed = true;
+        }
+        res
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl<T> Drop for OpStream<T> {
+    fn drop(&mut self) {
+        if !self.completed {
+            crate::task::RUNTIME.with(|runtime| runtime.cancel_multishot(self.user_data))
+        }
+    }
+}
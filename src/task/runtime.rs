@@ -1,68 +1,326 @@
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     collections::VecDeque,
     future::Future,
     io,
+    pin::Pin,
+    ptr::NonNull,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
     task::{Context, Poll},
     time::Duration,
 };
 
 use async_task::{Runnable, Task};
 
+#[cfg(target_os = "linux")]
+use crate::task::op::OpStream;
 use crate::{
-    driver::{AsRawFd, CompleteIo, Driver, Fd, OpCode, OpObject, RawFd},
-    task::op::{OpFuture, OpRuntime},
+    driver::{
+        AsRawFd, CancelError, CompleteIo, Driver, DriverCapabilities, DriverWaker, Fd, OpCode,
+        OpObject, RawFd,
+    },
+    task::{
+        blocking::{DEFAULT_KEEP_ALIVE, DEFAULT_MAX_THREADS},
+        dump::TaskRegistry,
+        join_future,
+        op::{OpFuture, OpRuntime},
+        BlockingPool, JoinHandle, RuntimeBuilder, RuntimeMetrics,
+    },
     Key,
 };
 
+thread_local! {
+    // the id of the runtime created on the current thread, zero if it's not created yet
+    static CURRENT_ID: Cell<usize> = const { Cell::new(0) };
+}
+
+/// The tasks of [`Runtime`] scheduled from other threads.
+///
+/// The driver of the runtime is woken up to run them.
+#[derive(Debug)]
+pub(crate) struct Remote {
+    id: usize,
+    // `None` after the runtime is dropped
+    runnables: Mutex<Option<Vec<Runnable>>>,
+    // the spawned tasks which are not completed or dropped
+    alive_tasks: AtomicUsize,
+    // NetBSD and OpenBSD drivers don't support wakers, the runnables wait for the next
+    // completion
+    waker: Option<DriverWaker>,
+}
+
+impl Remote {
+    /// Returns `true` on the thread of the runtime.
+    #[inline]
+    pub fn is_current(&self) -> bool {
+        CURRENT_ID.with(Cell::get) == self.id
+    }
+
+    pub fn schedule(&self, runnable: Runnable) {
+        let mut runnables = self.runnables.lock().expect("not poisoned");
+        let Some(runnables) = runnables.as_mut() else {
+            // the future of the task can't be dropped on another thread
+            std::mem::forget(runnable);
+            return;
+        };
+        runnables.push(runnable);
+        if !self.is_current() {
+            if let Some(waker) = &self.waker {
+                // the driver runs the queued tasks after the next completion otherwise
+                let _ = waker.wake();
+            }
+        }
+    }
+
+    /// Spawn the task created by `f` on the thread of the runtime.
+    ///
+    /// The task is never run if the runtime is dropped.
+    pub fn spawn_fn<F, Fut>(self: &Arc<Self>, f: F) -> JoinHandle<Fut::Output>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future + 'static,
+        Fut::Output: Send + 'static,
+    {
+        let (future, abort) = join_future(async move { f().await });
+        let remote = self.clone();
+        // SAFETY: the future holds only `Send` values till it is polled. The runnable is
+        // scheduled to the runtime, so the future is polled and dropped on its thread.
+        let (runnable, task) = unsafe {
+            async_task::spawn_unchecked(future, move |runnable| remote.schedule(runnable))
+        };
+        runnable.schedule();
+        JoinHandle::new(task, abort)
+    }
+
+    /// Count a spawned task of the runtime till the returned guard is dropped.
+    pub fn alive_task(self: &Arc<Self>) -> AliveTask {
+        self.alive_tasks.fetch_add(1, Ordering::Relaxed);
+        AliveTask(self.clone())
+    }
+
+    /// Wake up the runtime waiting for completions.
+    pub fn wake(&self) {
+        if let Some(waker) = &self.waker {
+            let _ = waker.wake();
+        }
+    }
+
+    fn take_runnables(&self) -> Vec<Runnable> {
+        let mut runnables = self.runnables.lock().expect("not poisoned");
+        runnables.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    fn close(&self) -> Vec<Runnable> {
+        self.runnables
+            .lock()
+            .expect("not poisoned")
+            .take()
+            .unwrap_or_default()
+    }
+}
+
+pub(crate) struct AliveTask(Arc<Remote>);
+
+impl Drop for AliveTask {
+    fn drop(&mut self) {
+        self.0.alive_tasks.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// A copyable reference to the runtime of the current thread, which doesn't access the
+/// thread-local storage of the runtime.
+///
+/// It's cached by the handles attached to the runtime, so their operations are submitted
+/// without looking the runtime up.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RuntimeRef {
+    id: usize,
+    runtime: NonNull<Runtime>,
+}
+
+impl RuntimeRef {
+    /// Get the runtime on the thread which created it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::ErrorKind::InvalidInput`] on another thread, or after the runtime is
+    /// dropped.
+    #[inline]
+    pub fn get(&self) -> io::Result<&Runtime> {
+        if CURRENT_ID.with(Cell::get) == self.id {
+            // SAFETY: the runtime is not moved out of the thread-local storage, and the id of
+            // the thread is reset when it's dropped
+            Ok(unsafe { self.runtime.as_ref() })
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the handle is attached to the runtime of another thread",
+            ))
+        }
+    }
+}
+
 pub(crate) struct Runtime {
+    id: usize,
+    remote: Arc<Remote>,
     driver: RefCell<Driver<'static>>,
     runnables: RefCell<VecDeque<Runnable>>,
     unqueued_operations: RefCell<VecDeque<OpObject<'static>>>,
     unqueued_cancels: RefCell<VecDeque<usize>>,
     op_runtime: RefCell<OpRuntime>,
+    // timeout of the submitted operations
+    op_timeout: Cell<Option<Duration>>,
+    event_interval: u32,
+    max_tasks_per_poll: usize,
+    task_budget: Option<u32>,
+    // the completed operations the running task could receive before it's rescheduled
+    budget: Cell<Option<u32>>,
+    tasks: Rc<TaskRegistry>,
+    // created on the first blocking closure unless it's shared by the workers
+    blocking_pool: RefCell<Option<Arc<BlockingPool>>>,
 }
 
 impl Runtime {
-    pub fn new() -> io::Result<Self> {
+    pub fn new(builder: &RuntimeBuilder) -> io::Result<Self> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+        let mut driver = builder.driver.build()?;
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        CURRENT_ID.with(|current| current.set(id));
         Ok(Self {
-            driver: RefCell::new(Driver::new()?),
+            id,
+            remote: Arc::new(Remote {
+                id,
+                runnables: Mutex::new(Some(Vec::new())),
+                alive_tasks: AtomicUsize::new(0),
+                waker: driver.waker().ok(),
+            }),
+            driver: RefCell::new(driver),
             runnables: RefCell::default(),
             unqueued_operations: RefCell::default(),
             unqueued_cancels: RefCell::default(),
             op_runtime: RefCell::default(),
+            op_timeout: Cell::default(),
+            event_interval: builder.event_interval,
+            max_tasks_per_poll: builder.max_tasks_per_poll,
+            task_budget: builder.task_budget,
+            budget: Cell::new(builder.task_budget),
+            tasks: Rc::default(),
+            blocking_pool: RefCell::default(),
         })
     }
 
+    /// The reference to the runtime stored in the thread-local storage.
+    pub fn runtime_ref(&self) -> RuntimeRef {
+        RuntimeRef {
+            id: self.id,
+            runtime: NonNull::from(self),
+        }
+    }
+
+    /// The handle to schedule tasks from other threads.
+    pub fn remote(&self) -> Arc<Remote> {
+        self.remote.clone()
+    }
+
+    /// The alive tasks of the runtime.
+    pub fn task_registry(&self) -> Rc<TaskRegistry> {
+        self.tasks.clone()
+    }
+
+    /// The pool running the blocking closures.
+    pub fn blocking_pool(&self) -> Arc<BlockingPool> {
+        self.blocking_pool
+            .borrow_mut()
+            .get_or_insert_with(|| {
+                Arc::new(BlockingPool::new(DEFAULT_MAX_THREADS, DEFAULT_KEEP_ALIVE))
+            })
+            .clone()
+    }
+
+    /// Replace the pool running the blocking closures.
+    pub fn set_blocking_pool(&self, pool: Arc<BlockingPool>) {
+        *self.blocking_pool.borrow_mut() = Some(pool);
+    }
+
     #[allow(dead_code)]
     pub fn raw_driver(&self) -> RawFd {
         self.driver.borrow().as_raw_fd()
     }
 
+    pub fn capabilities(&self) -> DriverCapabilities {
+        self.driver.borrow().capabilities()
+    }
+
+    pub fn metrics(&self) -> RuntimeMetrics {
+        let op_runtime = self.op_runtime.borrow();
+        let queued_ops = self.unqueued_operations.borrow().len();
+        RuntimeMetrics {
+            in_flight_ops: op_runtime.pending().saturating_sub(queued_ops),
+            queued_ops,
+            alive_tasks: self.remote.alive_tasks.load(Ordering::Relaxed),
+            completed_ops: op_runtime.completed(),
+            driver: self.driver.borrow().stats(),
+        }
+    }
+
     // Safety: the return runnable should be scheduled.
     unsafe fn spawn_unchecked<F: Future>(&self, future: F) -> Task<F::Output> {
-        let schedule = move |runnable| self.runnables.borrow_mut().push_back(runnable);
+        let remote = self.remote.clone();
+        // the wakers of other threads pass the runnable to the runtime thread
+        let schedule = move |runnable| {
+            if remote.is_current() {
+                self.runnables.borrow_mut().push_back(runnable)
+            } else {
+                remote.schedule(runnable)
+            }
+        };
         let (runnable, task) = async_task::spawn_unchecked(future, schedule);
         runnable.schedule();
         task
     }
 
     pub fn block_on<F: Future>(&self, future: F) -> F::Output {
-        let mut result = None;
-        unsafe { self.spawn_unchecked(async { result = Some(future.await) }) }.detach();
+        // the output is shared with the task, it is not borrowed uniquely by either of them
+        let result = Cell::new(None);
+        unsafe { self.spawn_unchecked(async { result.set(Some(future.await)) }) }.detach();
+        self.run_until(|| result.take())
+    }
+
+    /// Run the tasks and wait for completions till `done` returns the result.
+    pub fn run_until<T>(&self, mut done: impl FnMut() -> Option<T>) -> T {
+        let mut ticks = 0;
         loop {
-            loop {
+            let mut tasks = 0;
+            while tasks < self.max_tasks_per_poll {
                 let next_task = self.runnables.borrow_mut().pop_front();
-                if let Some(task) = next_task {
-                    task.run();
-                } else {
+                let Some(task) = next_task else {
                     break;
+                };
+                self.budget.set(self.task_budget);
+                task.run();
+                tasks += 1;
+                ticks += 1;
+                if ticks == self.event_interval {
+                    ticks = 0;
+                    // the completed operations wake their tasks behind the ready ones
+                    self.poll(false);
                 }
             }
-            if let Some(result) = result.take() {
+            let remote = self.remote.take_runnables();
+            let idle = remote.is_empty() && self.runnables.borrow().is_empty();
+            self.runnables.borrow_mut().extend(remote);
+            if let Some(result) = done() {
                 return result;
             }
-            self.poll();
+            if idle {
+                ticks = 0;
+                self.poll(true);
+            }
         }
     }
 
@@ -74,17 +332,129 @@ impl Runtime {
         self.driver.borrow_mut().attach(fd)
     }
 
+    pub fn detach(&self, fd: RawFd) -> io::Result<()> {
+        self.driver.borrow_mut().detach(fd)
+    }
+
     pub fn submit<T: OpCode + 'static>(
         &self,
         op: T,
     ) -> impl Future<Output = (io::Result<usize>, T)> {
+        let future = self.submit_op(op);
+        let user_data = *future.key();
+        let mut task = self.spawn(future);
+        // the result is received by the awaiting task, not by the spawned one
+        std::future::poll_fn(move |cx| {
+            crate::task::RUNTIME.with(|runtime| {
+                if runtime.budget_exhausted(cx) {
+                    return Poll::Pending;
+                }
+                let res = Pin::new(&mut task).poll(cx);
+                if res.is_ready() {
+                    runtime.consume_budget();
+                } else {
+                    runtime.tasks.wait_for(user_data);
+                }
+                res
+            })
+        })
+    }
+
+    /// Submits an operation, the returned future exposes the key of the operation.
+    pub fn submit_op<T: OpCode + 'static>(&self, op: T) -> OpFuture<T> {
+        let mut op_runtime = self.op_runtime.borrow_mut();
+        let (user_data, op_mut) = op_runtime.insert(op);
+        let op_object = match self.op_timeout.get() {
+            Some(timeout) => OpObject::with_timeout(op_mut, *user_data, timeout),
+            None => OpObject::new(op_mut, *user_data),
+        };
+        if let Err(e) = self.driver.borrow_mut().try_push_dyn(op_object) {
+            self.unqueued_operations
+                .borrow_mut()
+                .push_back(e.into_inner());
+        };
+        OpFuture::new(user_data)
+    }
+
+    /// Submits the operations linked in a chain, each of them starts after the previous one
+    /// completes successfully.
+    ///
+    /// The chain is queued as a whole if it doesn't fit into the submission queue. The chain
+    /// longer than the submission queue is split.
+    pub fn submit_linked<T: OpCode + 'static>(&self, ops: Vec<T>) -> Vec<OpFuture<T>> {
+        let mut op_runtime = self.op_runtime.borrow_mut();
+        let mut driver = self.driver.borrow_mut();
+        let mut unqueued_operations = self.unqueued_operations.borrow_mut();
+        let timeout = self.op_timeout.get();
+        let required = ops.len() * if timeout.is_some() { 2 } else { 1 };
+        let mut queued = !unqueued_operations.is_empty() || driver.capacity_left() < required;
+        let last = ops.len().saturating_sub(1);
+        let mut futures = Vec::with_capacity(ops.len());
+        for (i, op) in ops.into_iter().enumerate() {
+            let (user_data, op_mut) = op_runtime.insert(op);
+            let mut op_object = match timeout {
+                Some(timeout) => OpObject::with_timeout(op_mut, *user_data, timeout),
+                None => OpObject::new(op_mut, *user_data),
+            };
+            if i < last {
+                op_object = op_object.link();
+            }
+            if queued {
+                unqueued_operations.push_back(op_object);
+            } else if let Err(e) = driver.try_push_dyn(op_object) {
+                queued = true;
+                unqueued_operations.push_back(e.into_inner());
+            }
+            futures.push(OpFuture::new(user_data));
+        }
+        futures
+    }
+
+    /// Submits an operation whose result is discarded on completion.
+    ///
+    /// The operation is not pushed ahead of the operations waiting for the submission queue
+    /// space, so it could target them.
+    #[cfg(feature = "runtime-time")]
+    pub fn submit_detached<T: OpCode + 'static>(&self, op: T) {
         let mut op_runtime = self.op_runtime.borrow_mut();
         let (user_data, op_mut) = op_runtime.insert(op);
+        op_runtime.cancel(user_data);
+        let op_object = OpObject::new(op_mut, *user_data);
+        let mut unqueued_operations = self.unqueued_operations.borrow_mut();
+        if !unqueued_operations.is_empty() {
+            unqueued_operations.push_back(op_object);
+        } else if let Err(e) = self.driver.borrow_mut().try_push_dyn(op_object) {
+            unqueued_operations.push_back(e.into_inner());
+        }
+    }
+
+    /// Submits an operation that completes with several entries.
+    ///
+    /// At most `capacity` results are buffered, the operation is canceled when the buffer is
+    /// full and the stream ends after the buffered results. `discard` releases the resources
+    /// of the results that are received after the stream is dropped.
+    #[cfg(target_os = "linux")]
+    pub fn submit_multishot<T: OpCode + 'static>(
+        &self,
+        op: T,
+        capacity: usize,
+        discard: fn(io::Result<usize>),
+    ) -> OpStream<T> {
+        let mut op_runtime = self.op_runtime.borrow_mut();
+        let (user_data, op_mut) = op_runtime.insert_multishot(op, capacity, discard);
         let op_object = OpObject::new(op_mut, *user_data);
-        if let Err(op_object) = self.driver.borrow_mut().try_push_dyn(op_object) {
-            self.unqueued_operations.borrow_mut().push_back(op_object);
+        if let Err(e) = self.driver.borrow_mut().try_push_dyn(op_object) {
+            self.unqueued_operations
+                .borrow_mut()
+                .push_back(e.into_inner());
         };
-        self.spawn(OpFuture::new(user_data))
+        OpStream::new(user_data)
+    }
+
+    /// Sets the timeout of the operations submitted with [`Runtime::submit`], returns the
+    /// previous one.
+    pub fn replace_op_timeout(&self, timeout: Option<Duration>) -> Option<Duration> {
+        self.op_timeout.replace(timeout)
     }
 
     #[allow(dead_code)]
@@ -93,10 +463,79 @@ impl Runtime {
     }
 
     pub fn cancel_op<T>(&self, user_data: Key<T>) {
-        if let Err(_) = self.driver.borrow_mut().try_cancel(*user_data) {
-            _ = self.unqueued_cancels.borrow_mut().push_back(*user_data)
+        // the operation waiting for the driver capacity is not pushed at all
+        let mut unqueued_operations = self.unqueued_operations.borrow_mut();
+        if let Some(pos) = unqueued_operations
+            .iter()
+            .position(|op| op.user_data() == *user_data)
+        {
+            unqueued_operations.remove(pos);
+            let mut op_runtime = self.op_runtime.borrow_mut();
+            op_runtime.cancel(user_data);
+            // the result of the canceled operation is discarded
+            op_runtime.update_result(user_data, Err(io::ErrorKind::Interrupted.into()), false);
+            return;
+        }
+        drop(unqueued_operations);
+        if !self.op_runtime.borrow_mut().cancel(user_data) {
+            return;
+        }
+        // the operation completes anyway if it is not found or can't be canceled
+        if let Err(CancelError::QueueFull) = self.driver.borrow_mut().try_cancel(*user_data) {
+            self.unqueued_cancels.borrow_mut().push_back(*user_data)
+        }
+    }
+
+    /// Cancel the operation keeping it for its future.
+    ///
+    /// Returns the operation at once if it's not pushed to the driver or has completed.
+    pub fn cancel_op_and_take<T: OpCode + 'static>(&self, user_data: Key<T>) -> Option<T> {
+        let mut unqueued_operations = self.unqueued_operations.borrow_mut();
+        if let Some(pos) = unqueued_operations
+            .iter()
+            .position(|op| op.user_data() == *user_data)
+        {
+            unqueued_operations.remove(pos);
+            return self.op_runtime.borrow_mut().remove(user_data).1;
+        }
+        drop(unqueued_operations);
+        let mut op_runtime = self.op_runtime.borrow_mut();
+        if op_runtime.has_result(user_data) {
+            return op_runtime.remove(user_data).1;
+        }
+        op_runtime.request_cancel(user_data);
+        drop(op_runtime);
+        if let Err(CancelError::QueueFull) = self.driver.borrow_mut().try_cancel(*user_data) {
+            self.unqueued_cancels.borrow_mut().push_back(*user_data)
+        }
+        None
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn cancel_multishot<T>(&self, user_data: Key<T>) {
+        if self.op_runtime.borrow_mut().cancel_multishot(user_data)
+            && matches!(
+                self.driver.borrow_mut().try_cancel(*user_data),
+                Err(CancelError::QueueFull)
+            )
+        {
+            self.unqueued_cancels.borrow_mut().push_back(*user_data)
+        }
+    }
+
+    // reschedule the task which has received the completed operations of its budget
+    fn budget_exhausted(&self, cx: &mut Context) -> bool {
+        if self.budget.get() == Some(0) {
+            cx.waker().wake_by_ref();
+            true
         } else {
-            self.op_runtime.borrow_mut().cancel(user_data);
+            false
+        }
+    }
+
+    fn consume_budget(&self) {
+        if let Some(budget) = self.budget.get() {
+            self.budget.set(Some(budget.saturating_sub(1)));
         }
     }
 
@@ -105,44 +544,89 @@ impl Runtime {
         cx: &mut Context,
         user_data: Key<T>,
     ) -> Poll<(io::Result<usize>, T)> {
+        if self.budget_exhausted(cx) {
+            return Poll::Pending;
+        }
         let mut op_runtime = self.op_runtime.borrow_mut();
         if op_runtime.has_result(user_data) {
             let (maybe_result, maybe_op) = op_runtime.remove(user_data);
             let result = maybe_result.unwrap();
             let operation = maybe_op.expect("`poll_task` is not called on dummy Op");
+            self.consume_budget();
             Poll::Ready((result, operation))
         } else {
             op_runtime.update_waker(user_data, cx.waker().clone());
+            self.tasks.wait_for(*user_data);
             Poll::Pending
         }
     }
 
+    #[cfg(target_os = "linux")]
+    pub fn poll_multishot<T: OpCode + 'static>(
+        &self,
+        cx: &mut Context,
+        user_data: Key<T>,
+    ) -> Poll<Option<io::Result<usize>>> {
+        if self.budget_exhausted(cx) {
+            return Poll::Pending;
+        }
+        let mut op_runtime = self.op_runtime.borrow_mut();
+        match op_runtime.next_result(user_data) {
+            Some(Some(result)) => {
+                self.consume_budget();
+                Poll::Ready(Some(result))
+            }
+            Some(None) => {
+                // all results are received, free the operation
+                op_runtime.remove(user_data);
+                Poll::Ready(None)
+            }
+            None => {
+                op_runtime.update_waker(user_data, cx.waker().clone());
+                self.tasks.wait_for(*user_data);
+                Poll::Pending
+            }
+        }
+    }
+
     #[allow(dead_code)]
     pub fn poll_dummy(&self, cx: &mut Context, user_data: Key<()>) -> Poll<io::Result<usize>> {
+        if self.budget_exhausted(cx) {
+            return Poll::Pending;
+        }
         let mut op_runtime = self.op_runtime.borrow_mut();
         if op_runtime.has_result(user_data) {
             let (maybe_result, _) = op_runtime.remove(user_data);
+            self.consume_budget();
             Poll::Ready(maybe_result.unwrap())
         } else {
             op_runtime.update_waker(user_data, cx.waker().clone());
+            self.tasks.wait_for(*user_data);
             Poll::Pending
         }
     }
 
-    fn poll(&self) {
+    // reap the completions, waiting for them if `wait` is set
+    fn poll(&self, wait: bool) {
         let mut unqueued_cancels = self.unqueued_cancels.borrow_mut();
         let mut driver = self.driver.borrow_mut();
+        let op_runtime = self.op_runtime.borrow();
         while let Some(user_data) = unqueued_cancels.pop_front() {
-            if let Err(_) = driver.try_cancel(user_data) {
+            // the operation has completed in the meantime
+            if !op_runtime.is_cancel_requested(user_data) {
+                continue;
+            }
+            if let Err(CancelError::QueueFull) = driver.try_cancel(user_data) {
                 unqueued_cancels.push_front(user_data);
                 break;
             }
         }
+        drop(op_runtime);
 
         let mut unqueued_operations = self.unqueued_operations.borrow_mut();
         driver.push_queue(&mut unqueued_operations);
 
-        let timeout = if unqueued_operations.len() > 0 {
+        let timeout = if !wait || unqueued_operations.len() > 0 || !unqueued_cancels.is_empty() {
             // busy loop to push outstanding work
             Some(Duration::ZERO)
         } else {
@@ -151,7 +635,10 @@ impl Runtime {
         let mut runtime_ref = self.op_runtime.borrow_mut();
         let completer = runtime_ref.completer();
 
-        if let Err(e) = unsafe { driver.submit(timeout, completer) } {
+        let res = unsafe { driver.submit(timeout, completer) };
+        // the cancels are pushed on the next poll
+        unqueued_cancels.extend(runtime_ref.take_overflowed());
+        if let Err(e) = res {
             if e.kind() == io::ErrorKind::TimedOut {
                 return;
             } else {
@@ -160,3 +647,12 @@ impl Runtime {
         }
     }
 }
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        // invalidate the cached references
+        CURRENT_ID.with(|current| current.set(0));
+        // the thread-local runtime is not accessible by the futures of the tasks anymore
+        self.remote.close().into_iter().for_each(std::mem::forget);
+    }
+}
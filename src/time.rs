@@ -1,11 +1,20 @@
 //! Utilities for tracking time.
 
-use std::{error::Error, fmt::Display, future::Future, time::Duration};
+use std::{
+    error::Error,
+    fmt::Display,
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+    time::Duration,
+};
 
 use boot_time::Instant;
-use futures_util::{select, FutureExt};
 
-use crate::op::Timeout;
+use crate::{
+    op::{self, TimeoutUpdate},
+    task::op::OpFuture,
+};
 
 /// Waits until `duration` has elapsed.
 ///
@@ -14,6 +23,9 @@ use crate::op::Timeout;
 ///
 /// To run something regularly on a schedule, see [`interval`].
 ///
+/// Concurrent sleeps use their own timeout operations. Dropping the future before the duration
+/// has elapsed cancels the operation.
+///
 /// # Examples
 ///
 /// Wait 100ms and print "100 ms have elapsed".
@@ -28,11 +40,8 @@ use crate::op::Timeout;
 ///     println!("100 ms have elapsed");
 /// })
 /// ```
-pub async fn sleep(duration: Duration) {
-    let (res, _) = crate::task::RUNTIME
-        .with(|runtime| runtime.submit(Timeout::new(duration)))
-        .await;
-    res.expect("timeout always succeeds");
+pub fn sleep(duration: Duration) -> Sleep {
+    sleep_until(Instant::now() + duration)
 }
 
 /// Waits until `deadline` is reached.
@@ -52,8 +61,90 @@ pub async fn sleep(duration: Duration) {
 ///     println!("100 ms have elapsed");
 /// })
 /// ```
-pub async fn sleep_until(deadline: Instant) {
-    sleep(deadline - Instant::now()).await
+pub fn sleep_until(deadline: Instant) -> Sleep {
+    Sleep {
+        deadline,
+        timer: None,
+    }
+}
+
+/// Future returned by [`sleep`] and [`sleep_until`].
+#[must_use = "futures do nothing unless polled"]
+pub struct Sleep {
+    deadline: Instant,
+    // the pending timeout operation
+    timer: Option<OpFuture<op::Timeout>>,
+}
+
+impl Sleep {
+    /// The instant at which the future completes.
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Reset the future to complete at `deadline`.
+    ///
+    /// The pending timeout operation is re-armed in place with [`TimeoutUpdate`] instead of
+    /// being canceled and submitted again. The completed future becomes pending again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use boot_time::{Duration, Instant};
+    /// use completeio::time::sleep;
+    ///
+    /// completeio::task::block_on(async {
+    ///     let mut sleep = sleep(Duration::from_secs(3600));
+    ///     sleep.reset(Instant::now() + Duration::from_millis(10));
+    ///     sleep.await;
+    /// })
+    /// ```
+    pub fn reset(&mut self, deadline: Instant) {
+        self.deadline = deadline;
+        if let Some(timer) = &self.timer {
+            let update = TimeoutUpdate::new(*timer.key(), self.remaining());
+            crate::task::RUNTIME.with(|runtime| runtime.submit_detached(update));
+        }
+    }
+
+    fn remaining(&self) -> Duration {
+        self.deadline.saturating_duration_since(Instant::now())
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            if self.timer.is_none() {
+                let timeout = op::Timeout::at(self.deadline);
+                self.timer = Some(crate::task::RUNTIME.with(|runtime| {
+                    // the timer of a sleep awaited in a timeout scope is not linked to it
+                    let previous = runtime.replace_op_timeout(None);
+                    let timer = runtime.submit_op(timeout);
+                    runtime.replace_op_timeout(previous);
+                    timer
+                }));
+            }
+            let timer = self.timer.as_mut().expect("timer is submitted");
+            let (res, _) = ready!(Pin::new(timer).poll(cx));
+            self.timer = None;
+            res.expect("timeout always succeeds");
+            // the timer could expire before the update of the reset deadline is applied
+            if self.remaining() == Duration::ZERO {
+                return Poll::Ready(());
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for Sleep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sleep")
+            .field("deadline", &self.deadline)
+            .finish_non_exhaustive()
+    }
 }
 
 /// Error returned by [`timeout`] or [`timeout_at`].
@@ -73,19 +164,180 @@ impl Error for Elapsed {}
 /// If the future completes before the duration has elapsed, then the completed
 /// value is returned. Otherwise, an error is returned and the future is
 /// canceled.
-pub async fn timeout<F: Future>(duration: Duration, future: F) -> Result<F::Output, Elapsed> {
-    select! {
-        res = future.fuse() => Ok(res),
-        _ = sleep(duration).fuse() => Err(Elapsed),
-    }
+///
+/// The operations submitted by the future are linked to the deadline, the driver cancels the
+/// ones in flight when it's reached and they complete with [`std::io::ErrorKind::TimedOut`].
+/// The future passed by reference is not dropped, awaiting it after the error returns the
+/// buffers of the cancelled operations.
+///
+/// # Examples
+///
+/// ```
+/// use std::{io, pin::pin, time::Duration};
+///
+/// use completeio::{net::UdpSocket, time::timeout};
+///
+/// completeio::task::block_on(async {
+///     let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+///     let mut recv = pin!(socket.recv(Vec::with_capacity(16)));
+///     assert!(timeout(Duration::from_millis(10), recv.as_mut())
+///         .await
+///         .is_err());
+///     // the canceled receive returns the buffer
+///     let (res, buf) = recv.await;
+///     assert_eq!(res.unwrap_err().kind(), io::ErrorKind::TimedOut);
+///     assert_eq!(buf.capacity(), 16);
+/// })
+/// ```
+pub fn timeout<F: Future>(duration: Duration, future: F) -> Timeout<F> {
+    timeout_at(Instant::now() + duration, future)
 }
 
 /// Require a [`Future`] to complete before the specified instant in time.
 ///
 /// If the future completes before the instant is reached, then the completed
 /// value is returned. Otherwise, an error is returned.
-pub async fn timeout_at<F: Future>(deadline: Instant, future: F) -> Result<F::Output, Elapsed> {
-    timeout(deadline - Instant::now(), future).await
+///
+/// See [`timeout`] for the cancellation of the operations.
+pub fn timeout_at<F: Future>(deadline: Instant, future: F) -> Timeout<F> {
+    Timeout {
+        future,
+        sleep: sleep_until(deadline),
+    }
+}
+
+/// Future returned by [`timeout`] and [`timeout_at`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Timeout<F> {
+    future: F,
+    sleep: Sleep,
+}
+
+impl<F> Timeout<F> {
+    /// Consume the timeout, returning the wrapped future.
+    pub fn into_inner(self) -> F {
+        self.future
+    }
+}
+
+impl<F: Future> Future for Timeout<F> {
+    type Output = Result<F::Output, Elapsed>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: the future is not moved out of the pinned wrapper
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+        let remaining = this.sleep.remaining();
+        let res = crate::task::RUNTIME.with(|runtime| {
+            // the nested deadline can't extend the outer one
+            let previous = runtime.replace_op_timeout(None);
+            let timeout = previous.map_or(remaining, |previous| previous.min(remaining));
+            runtime.replace_op_timeout(Some(timeout));
+            let res = future.poll(cx);
+            runtime.replace_op_timeout(previous);
+            res
+        });
+        if let Poll::Ready(res) = res {
+            return Poll::Ready(Ok(res));
+        }
+        ready!(Pin::new(&mut this.sleep).poll(cx));
+        Poll::Ready(Err(Elapsed))
+    }
+}
+
+/// Future returned by [`OpTimeoutExt::timeout`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct OpTimeout<F> {
+    future: F,
+    deadline: Instant,
+}
+
+impl<F: Future> Future for OpTimeout<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // the operations submitted on later polls get the rest of the timeout
+        let timeout = self.deadline.saturating_duration_since(Instant::now());
+        // SAFETY: the future is not moved out of the pinned wrapper
+        let future = unsafe { self.map_unchecked_mut(|this| &mut this.future) };
+        crate::task::RUNTIME.with(|runtime| {
+            let previous = runtime.replace_op_timeout(Some(timeout));
+            let res = future.poll(cx);
+            runtime.replace_op_timeout(previous);
+            res
+        })
+    }
+}
+
+/// Extension of futures submitting operations with per-operation timeout.
+pub trait OpTimeoutExt: Future + Sized {
+    /// Cancel every operation submitted by the future if it doesn't complete within `timeout`.
+    ///
+    /// The timeout starts when this method is called, the operations submitted later share the
+    /// rest of it.
+    ///
+    /// Unlike [`timeout`], the future isn't dropped. The driver cancels the in-flight operation
+    /// and it completes with [`std::io::ErrorKind::TimedOut`], so the buffer is returned with the
+    /// error. See [`Operation::with_timeout`](crate::driver::Operation::with_timeout).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{io, time::Duration};
+    ///
+    /// use completeio::{net::UdpSocket, time::OpTimeoutExt};
+    ///
+    /// completeio::task::block_on(async {
+    ///     let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    ///     let (res, _buf) = socket
+    ///         .recv(Vec::with_capacity(16))
+    ///         .timeout(Duration::from_millis(10))
+    ///         .await;
+    ///     assert_eq!(res.unwrap_err().kind(), io::ErrorKind::TimedOut);
+    /// })
+    /// ```
+    fn timeout(self, timeout: Duration) -> OpTimeout<Self> {
+        OpTimeout {
+            future: self,
+            deadline: Instant::now() + timeout,
+        }
+    }
+}
+
+impl<F: Future> OpTimeoutExt for F {}
+
+/// Defines the behavior of an [`Interval`] when it misses a tick.
+///
+/// The tick is missed when [`Interval::tick`] is called later than the period after the
+/// previous tick, e.g. the consumer of the ticks is slow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissedTickBehavior {
+    /// The missed ticks complete immediately one after another till the interval catches up,
+    /// the next ticks keep the original schedule.
+    Burst,
+    /// The missed tick completes immediately and the next ticks are scheduled from the time it
+    /// was called.
+    Delay,
+    /// The missed ticks are skipped, the next tick is scheduled to the next multiple of the
+    /// period from the start.
+    #[default]
+    Skip,
+}
+
+impl MissedTickBehavior {
+    // the instant of the next tick after the missed `timeout`
+    fn next_timeout(&self, timeout: Instant, now: Instant, period: Duration) -> Instant {
+        match self {
+            Self::Burst => timeout + period,
+            Self::Delay => now + period,
+            Self::Skip => {
+                now + period
+                    - Duration::from_nanos(((now - timeout).as_nanos() % period.as_nanos()) as _)
+            }
+        }
+    }
 }
 
 /// Interval returned by [`interval`] and [`interval_at`]
@@ -93,19 +345,23 @@ pub async fn timeout_at<F: Future>(deadline: Instant, future: F) -> Result<F::Ou
 /// This type allows you to wait on a sequence of instants with a certain
 /// duration between each instant. Unlike calling [`sleep`] in a loop, this lets
 /// you count the time spent between the calls to [`sleep`] as well.
+///
+/// The interval owns the timeout operation of the next tick, so the dropped [`Interval::tick`]
+/// future doesn't lose it. Dropping the interval cancels the operation.
 #[derive(Debug)]
 pub struct Interval {
-    first_ticked: bool,
-    start: Instant,
+    // the deadline is the instant of the next tick
+    sleep: Sleep,
     period: Duration,
+    missed_tick_behavior: MissedTickBehavior,
 }
 
 impl Interval {
     pub(crate) fn new(start: Instant, period: Duration) -> Self {
         Self {
-            first_ticked: false,
-            start,
+            sleep: sleep_until(start),
             period,
+            missed_tick_behavior: MissedTickBehavior::default(),
         }
     }
 
@@ -113,19 +369,45 @@ impl Interval {
     ///
     /// See [`interval`] and [`interval_at`].
     pub async fn tick(&mut self) -> Instant {
-        if !self.first_ticked {
-            sleep_until(self.start).await;
-            self.first_ticked = true;
-            self.start
+        std::future::poll_fn(|cx| self.poll_tick(cx)).await
+    }
+
+    /// Poll for the next instant in the interval to be reached.
+    pub fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<Instant> {
+        ready!(Pin::new(&mut self.sleep).poll(cx));
+        let timeout = self.sleep.deadline();
+        let now = Instant::now();
+        let next = if now > timeout + self.period {
+            self.missed_tick_behavior
+                .next_timeout(timeout, now, self.period)
         } else {
-            let now = Instant::now();
-            let next = now + self.period
-                - Duration::from_nanos(
-                    ((now - self.start).as_nanos() % self.period.as_nanos()) as _,
-                );
-            sleep_until(next).await;
-            next
-        }
+            timeout + self.period
+        };
+        // the timer is completed, the next poll submits the new one
+        self.sleep.reset(next);
+        Poll::Ready(timeout)
+    }
+
+    /// Reset the interval to complete one period after the current time.
+    ///
+    /// The pending timeout operation is re-armed in place, see [`Sleep::reset`].
+    pub fn reset(&mut self) {
+        self.sleep.reset(Instant::now() + self.period);
+    }
+
+    /// The period of the interval.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// The behavior of the interval when it misses a tick.
+    pub fn missed_tick_behavior(&self) -> MissedTickBehavior {
+        self.missed_tick_behavior
+    }
+
+    /// Set the behavior of the interval when it misses a tick.
+    pub fn set_missed_tick_behavior(&mut self, behavior: MissedTickBehavior) {
+        self.missed_tick_behavior = behavior;
     }
 }
 
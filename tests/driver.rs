@@ -9,14 +9,24 @@ use completeio::{
 
 #[test]
 fn cancel_before_poll() {
+    // the operations outlive the driver, it borrows them until it's dropped
+    let mut op;
     let mut driver = Driver::new().unwrap();
 
     let file = File::open("Cargo.toml").unwrap();
     let fd = driver.attach(file.as_raw_fd()).unwrap();
 
-    driver.try_cancel(0).unwrap();
+    let res = driver.try_cancel(0);
+    // io-uring looks up the operation asynchronously
+    #[cfg(target_os = "linux")]
+    res.unwrap();
+    #[cfg(not(target_os = "linux"))]
+    assert!(matches!(
+        res,
+        Err(completeio::driver::CancelError::NotFound)
+    ));
 
-    let mut op = ReadAt::new(fd, 0, Vec::with_capacity(8));
+    op = ReadAt::new(fd, 0, Vec::with_capacity(8));
     let mut ops = VecDeque::from([(&mut op, 0).into()]);
     driver.push_queue(&mut ops);
     let mut entries = ArrayVec::<Entry, 1>::new();
@@ -25,6 +35,130 @@ fn cancel_before_poll() {
     res.unwrap();
 }
 
+#[test]
+fn cancel_completed_and_unknown() {
+    use completeio::op::Nop;
+
+    let mut op = Nop::new();
+    let mut driver = Driver::new().unwrap();
+    driver
+        .try_push(Operation::new(&mut op, 0))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    let mut entries = ArrayVec::<Entry, 1>::new();
+    while entries.is_empty() {
+        unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    }
+    entries.pop().unwrap().into_result().unwrap();
+
+    for user_data in [0, 42] {
+        let res = driver.try_cancel(user_data);
+        #[cfg(target_os = "linux")]
+        res.unwrap();
+        #[cfg(not(target_os = "linux"))]
+        assert!(matches!(
+            res,
+            Err(completeio::driver::CancelError::NotFound)
+        ));
+    }
+    // the canceled operations don't complete again
+    unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    assert!(entries.is_empty());
+}
+
+#[test]
+fn cancel_with_full_queue() {
+    use completeio::op::Nop;
+
+    let mut ops = [Nop::new(), Nop::new()];
+    let mut driver = Driver::builder().sq_entries(2).build().unwrap();
+    for (i, op) in ops.iter_mut().enumerate() {
+        driver
+            .try_push(Operation::new(op, i))
+            .unwrap_or_else(|_| panic!("queue is full"));
+    }
+    // io-uring submits the queued operations to push the cancel request
+    driver.try_cancel(0).unwrap();
+
+    let mut entries = ArrayVec::<Entry, 2>::new();
+    while entries.len() < 2 {
+        unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    }
+}
+
+#[cfg(feature = "hooks")]
+#[test]
+fn hooks() {
+    use std::sync::{Arc, Mutex};
+
+    use completeio::{driver::DriverHooks, op::Nop};
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Push(usize, &'static str),
+        Submit,
+        Complete(usize),
+        Cancel(usize),
+    }
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<Event>>);
+
+    impl DriverHooks for Recorder {
+        fn on_push(&self, user_data: usize, name: &'static str) {
+            self.0.lock().unwrap().push(Event::Push(user_data, name));
+        }
+
+        fn on_submit(&self) {
+            self.0.lock().unwrap().push(Event::Submit);
+        }
+
+        fn on_complete(&self, entry: &Entry) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(Event::Complete(entry.user_data()));
+        }
+
+        fn on_cancel(&self, user_data: usize) {
+            self.0.lock().unwrap().push(Event::Cancel(user_data));
+        }
+    }
+
+    let recorder = Arc::new(Recorder::default());
+    let mut ops = [Nop::new(), Nop::new()];
+    let mut driver = Driver::builder().hooks(recorder.clone()).build().unwrap();
+    for (i, op) in ops.iter_mut().enumerate() {
+        driver
+            .try_push(Operation::new(op, i))
+            .unwrap_or_else(|_| panic!("queue is full"));
+    }
+    driver.try_cancel(0).unwrap();
+    let mut entries = ArrayVec::<Entry, 2>::new();
+    while entries.len() < 2 {
+        unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    }
+
+    let events = std::mem::take(&mut *recorder.0.lock().unwrap());
+    assert_eq!(
+        events[..3],
+        [
+            Event::Push(0, "Nop"),
+            Event::Push(1, "Nop"),
+            Event::Cancel(0)
+        ]
+    );
+    assert!(events.contains(&Event::Submit));
+    let mut completed = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Complete(user_data) => Some(*user_data),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    completed.sort();
+    assert_eq!(completed, [0, 1]);
+}
+
 #[test]
 fn timeout() {
     let mut driver = Driver::new().unwrap();
@@ -34,16 +168,702 @@ fn timeout() {
     res.unwrap();
 }
 
+#[test]
+fn builder() {
+    use completeio::op::Nop;
+
+    const ENTRIES: u32 = 64;
+
+    let err = Driver::builder()
+        .sq_entries(ENTRIES)
+        .cq_entries(ENTRIES / 2)
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(Driver::builder().sq_entries(0).build().is_err());
+
+    let mut ops: Vec<Nop> = (0..=ENTRIES).map(|_| Nop::new()).collect();
+    let mut driver = Driver::builder()
+        .sq_entries(ENTRIES)
+        .cq_entries(ENTRIES * 4)
+        .event_capacity(16)
+        .build()
+        .unwrap();
+    assert_eq!(driver.capacity_left(), ENTRIES as usize);
+
+    let (last, ops) = ops.split_last_mut().unwrap();
+    for (i, op) in ops.iter_mut().enumerate() {
+        driver
+            .try_push(Operation::new(op, i))
+            .unwrap_or_else(|_| panic!("queue is full"));
+    }
+    assert_eq!(driver.capacity_left(), 0);
+    assert!(driver.try_push(Operation::new(last, 0)).is_err());
+}
+
+#[test]
+fn flush_and_wait_completed() {
+    use completeio::op::Nop;
+
+    let mut ops = [Nop::new(), Nop::new()];
+    let mut driver = Driver::new().unwrap();
+    for (i, op) in ops.iter_mut().enumerate() {
+        driver
+            .try_push(Operation::new(op, i))
+            .unwrap_or_else(|_| panic!("queue is full"));
+    }
+
+    assert_eq!(unsafe { driver.flush() }.unwrap(), 2);
+    assert_eq!(unsafe { driver.flush() }.unwrap(), 0);
+
+    let mut entries = ArrayVec::<Entry, 2>::new();
+    while entries.len() < 2 {
+        unsafe { driver.wait_completed(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    }
+    entries.sort_by_key(|e| e.user_data());
+    for (i, entry) in entries.into_iter().enumerate() {
+        assert_eq!(entry.user_data(), i);
+        entry.into_result().unwrap();
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn peek_completed() {
+    use completeio::op::Nop;
+
+    let mut ops = [Nop::new(), Nop::new()];
+    let mut driver = Driver::new().unwrap();
+    for (i, op) in ops.iter_mut().enumerate() {
+        driver
+            .try_push(Operation::new(op, i))
+            .unwrap_or_else(|_| panic!("queue is full"));
+    }
+    assert!(driver.peek_completed().next().is_none());
+    assert_eq!(unsafe { driver.flush() }.unwrap(), 2);
+
+    // the entries which are not consumed stay in the driver
+    let first = driver.peek_completed().next().unwrap();
+    let rest = driver.peek_completed().collect::<Vec<_>>();
+    assert_eq!(rest.len(), 1);
+    let mut user_data = [first.user_data(), rest[0].user_data()];
+    user_data.sort();
+    assert_eq!(user_data, [0, 1]);
+    assert!(driver.peek_completed().next().is_none());
+
+    let mut entries = ArrayVec::<Entry, 1>::new();
+    unsafe { driver.submit(Some(Duration::ZERO), &mut entries) }.unwrap();
+    assert!(entries.is_empty());
+}
+
+#[test]
+fn stats() {
+    use completeio::op::Nop;
+
+    let mut ops = [Nop::new(), Nop::new(), Nop::new()];
+    let mut driver = Driver::builder().sq_entries(2).build().unwrap();
+    let mut queue = ops
+        .iter_mut()
+        .enumerate()
+        .map(|(i, op)| (op, i).into())
+        .collect::<VecDeque<_>>();
+    driver.push_queue(&mut queue);
+    assert_eq!(queue.len(), 1);
+
+    let stats = driver.stats();
+    assert_eq!(stats.submitted, 2);
+    assert_eq!(stats.in_flight(), 2);
+    assert_eq!(stats.max_queued, 3);
+
+    let mut entries = ArrayVec::<Entry, 3>::new();
+    while entries.len() < 2 {
+        unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    }
+    driver.push_queue(&mut queue);
+    while entries.len() < 3 {
+        unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    }
+    let stats = driver.stats();
+    assert_eq!((stats.submitted, stats.completed), (3, 3));
+    assert_eq!(stats.in_flight(), 0);
+    assert_eq!(stats.max_queued, 3);
+    assert_eq!(stats.cq_overflows, 0);
+
+    #[cfg(target_os = "linux")]
+    {
+        assert_eq!(driver.sq_space_left(), 2);
+        assert_eq!(driver.cq_ready(), 0);
+        assert!(!driver.cq_overflow_observed());
+    }
+
+    driver.reset_stats();
+    assert_eq!(driver.stats(), Default::default());
+}
+
+#[test]
+fn nops_do_not_overflow_completion_queue() {
+    use completeio::op::Nop;
+
+    const OPS: usize = 10_000;
+
+    let mut ops: Vec<Nop> = (0..OPS).map(|_| Nop::new()).collect();
+    let mut driver = Driver::builder().sq_entries(256).build().unwrap();
+    let mut queue = ops
+        .iter_mut()
+        .enumerate()
+        .map(|(i, op)| (op, i).into())
+        .collect::<VecDeque<_>>();
+
+    let mut completed = vec![false; OPS];
+    let mut entries = Vec::new();
+    let mut left = OPS;
+    while left > 0 {
+        driver.push_queue(&mut queue);
+        // the completions are not consumed until the driver is saturated
+        if !driver.is_saturated() && !queue.is_empty() {
+            unsafe { driver.flush() }.unwrap();
+            continue;
+        }
+        unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+        for entry in entries.drain(..) {
+            assert!(!std::mem::replace(&mut completed[entry.user_data()], true));
+            entry.into_result().unwrap();
+            left -= 1;
+        }
+    }
+    assert!(queue.is_empty());
+    let stats = driver.stats();
+    assert_eq!((stats.submitted, stats.completed), (OPS as u64, OPS as u64));
+    assert_eq!(stats.cq_overflows, 0);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn capacity_counts_unconsumed_completions() {
+    use completeio::op::Nop;
+
+    let mut ops: [Nop; 6] = std::array::from_fn(|_| Nop::new());
+    let mut driver = Driver::builder()
+        .sq_entries(4)
+        .cq_entries(7)
+        .build()
+        .unwrap();
+    let [first, ops @ .., last, rejected] = &mut ops;
+    // the operation with a linked timeout posts two completion entries
+    driver
+        .try_push(Operation::with_timeout(first, 0, Duration::from_secs(10)))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    unsafe { driver.flush() }.unwrap();
+    for (i, op) in ops.iter_mut().enumerate() {
+        driver
+            .try_push(Operation::new(op, i + 1))
+            .unwrap_or_else(|_| panic!("queue is full"));
+    }
+    unsafe { driver.flush() }.unwrap();
+    // the ring rounds the completion queue up to 8 entries, and 2 are reserved for the
+    // internal requests
+    assert_eq!(driver.sq_space_left(), 4);
+    assert_eq!(driver.capacity_left(), 1);
+    assert!(!driver.is_saturated());
+
+    assert!(driver
+        .try_push(Operation::with_timeout(
+            rejected,
+            4,
+            Duration::from_secs(10)
+        ))
+        .is_err());
+    driver
+        .try_push(Operation::new(last, 4))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    assert!(driver.is_saturated());
+
+    let mut entries = ArrayVec::<Entry, 5>::new();
+    while entries.len() < 5 {
+        unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    }
+    assert_eq!(driver.capacity_left(), 4);
+    assert!(!driver.cq_overflow_observed());
+}
+
+#[test]
+fn duplicate_user_data_is_rejected() {
+    use completeio::{driver::PushError, op::Nop};
+
+    let mut ops = [Nop::new(), Nop::new(), Nop::new()];
+    let mut driver = Driver::builder().check_user_data(true).build().unwrap();
+    let [first, second, third] = &mut ops;
+    driver
+        .try_push(Operation::new(first, 0))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    assert!(matches!(
+        driver.try_push(Operation::new(second, 0)),
+        Err(PushError::DuplicateUserData(_))
+    ));
+    // the queued operation waits for the data to be released
+    let mut queue = VecDeque::from([(third, 0).into()]);
+    driver.push_queue(&mut queue);
+    assert_eq!(queue.len(), 1);
+
+    let mut entries = ArrayVec::<Entry, 1>::new();
+    while entries.is_empty() {
+        unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    }
+    assert_eq!(entries.pop().unwrap().user_data(), 0);
+    driver.push_queue(&mut queue);
+    assert!(queue.is_empty());
+    while entries.is_empty() {
+        unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    }
+    assert_eq!(entries.pop().unwrap().user_data(), 0);
+
+    drop(driver);
+    let mut driver = Driver::builder().check_user_data(false).build().unwrap();
+    for op in &mut ops {
+        driver
+            .try_push(Operation::new(op, 0))
+            .unwrap_or_else(|_| panic!("queue is full"));
+    }
+}
+
+#[test]
+fn submit_until_deadline() {
+    use std::time::Instant;
+
+    use completeio::op::Nop;
+
+    let mut op = Nop::new();
+    let mut driver = Driver::new().unwrap();
+    let mut entries = ArrayVec::<Entry, 1>::new();
+
+    let deadline = Instant::now() + Duration::from_millis(50);
+    assert!(unsafe { driver.submit_until(deadline, &mut entries) }.unwrap());
+    assert!(Instant::now() >= deadline);
+    assert!(entries.is_empty());
+
+    driver
+        .try_push(Operation::new(&mut op, 0))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while entries.is_empty() {
+        assert!(!unsafe { driver.submit_until(deadline, &mut entries) }.unwrap());
+    }
+    assert!(Instant::now() < deadline);
+    assert_eq!(entries.pop().unwrap().user_data(), 0);
+
+    // the passed deadline doesn't block
+    assert!(unsafe { driver.submit_until(Instant::now(), &mut entries) }.unwrap());
+}
+
+#[test]
+fn capabilities() {
+    use completeio::driver::BackendKind;
+
+    let driver = Driver::new().unwrap();
+    let capabilities = driver.capabilities();
+    assert_eq!(capabilities, completeio::driver::probe().unwrap());
+    #[cfg(unix)]
+    assert!(capabilities.kernel_version.is_some());
+    let backend_kind = if cfg!(target_os = "linux") {
+        BackendKind::IoUring
+    } else if cfg!(windows) {
+        BackendKind::Iocp
+    } else {
+        BackendKind::Kqueue
+    };
+    assert_eq!(driver.backend_kind(), backend_kind);
+    #[cfg(target_os = "linux")]
+    {
+        // available since Linux 5.6 as well as the opcode probing
+        assert!(capabilities.open_at);
+        assert!(capabilities.fixed_buffers);
+    }
+    #[cfg(not(target_os = "linux"))]
+    assert!(
+        !capabilities.accept_multishot
+            && !capabilities.send_zc
+            && !capabilities.open_at
+            && !capabilities.fixed_buffers
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn epoll_backend() {
+    use std::{net::UdpSocket, thread, time::Instant};
+
+    use completeio::{
+        driver::{BackendKind, OpObject},
+        op::{Cancel, Recv, Send, Timeout},
+    };
+
+    let socket = completeio::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let other_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket.connect(other_socket.local_addr().unwrap()).unwrap();
+    other_socket
+        .connect(socket.local_addr().unwrap().as_socket().unwrap())
+        .unwrap();
+
+    let mut recv;
+    let mut send;
+    let mut canceled_recv;
+    let mut cancel;
+    let mut timeout;
+    let mut driver = Driver::builder()
+        .backend(BackendKind::Epoll)
+        .build()
+        .unwrap();
+    assert_eq!(driver.backend_kind(), BackendKind::Epoll);
+    assert!(!driver.capabilities().fixed_buffers);
+    let fd = driver.attach(socket.as_raw_fd()).unwrap();
+
+    let started = Instant::now();
+    let wait_entries = |driver: &mut Driver, entries: &mut ArrayVec<Entry, 2>, len| {
+        while entries.len() < len {
+            assert!(started.elapsed() < Duration::from_secs(5), "no completion");
+            unsafe { driver.submit(Some(Duration::from_millis(10)), entries) }.unwrap();
+        }
+        entries.sort_by_key(|e| e.user_data());
+    };
+
+    // the receive waits till the socket is readable
+    recv = Recv::new(fd, Vec::with_capacity(8));
+    driver
+        .try_push(Operation::new(&mut recv, 0))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    let mut entries = ArrayVec::<Entry, 2>::new();
+    unsafe { driver.submit(Some(Duration::ZERO), &mut entries) }.unwrap();
+    assert!(entries.is_empty());
+    other_socket.send(b"epoll").unwrap();
+    wait_entries(&mut driver, &mut entries, 1);
+    let received = entries.pop().unwrap();
+    assert_eq!(received.user_data(), 0);
+    assert_eq!(received.into_result().unwrap(), 5);
+
+    send = Send::new(fd, b"reply".to_vec());
+    driver
+        .try_push(Operation::new(&mut send, 1))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    wait_entries(&mut driver, &mut entries, 1);
+    let sent = entries.pop().unwrap();
+    assert_eq!(sent.into_result().unwrap(), 5);
+    let mut buffer = [0; 8];
+    let n = other_socket.recv(&mut buffer).unwrap();
+    assert_eq!(&buffer[..n], b"reply");
+
+    // the waiting receive is canceled
+    canceled_recv = Recv::new(fd, Vec::with_capacity(8));
+    cancel = Cancel::new(2);
+    for op in [
+        Operation::new(&mut canceled_recv, 2).into(),
+        OpObject::from(Operation::new(&mut cancel, 3)),
+    ] {
+        driver
+            .try_push_dyn(op)
+            .unwrap_or_else(|_| panic!("queue is full"));
+    }
+    wait_entries(&mut driver, &mut entries, 2);
+    let [canceled, cancel] = entries.take().into_inner().unwrap();
+    assert!(completeio::CancelledError::is_cancelled(
+        &canceled.into_result().unwrap_err()
+    ));
+    assert_eq!(cancel.into_result().unwrap(), 0);
+
+    // the timers run in user space
+    timeout = Timeout::new(Duration::from_millis(10));
+    driver
+        .try_push(Operation::new(&mut timeout, 4))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    wait_entries(&mut driver, &mut entries, 1);
+    let fired = entries.pop().unwrap();
+    assert_eq!(fired.user_data(), 4);
+    assert_eq!(fired.into_result().unwrap(), 0);
+
+    let waker = driver.waker().unwrap();
+    let waker_thread = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        waker.wake().unwrap();
+    });
+    let started = Instant::now();
+    unsafe { driver.submit(Some(Duration::from_secs(10)), &mut entries) }.unwrap();
+    assert!(entries.is_empty());
+    assert!(started.elapsed() < Duration::from_secs(5));
+    waker_thread.join().unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn sqpoll() {
+    use completeio::op::Nop;
+
+    let mut ops = [Nop::new(), Nop::new()];
+    let mut driver = match Driver::builder()
+        .sq_entries(8)
+        .sqpoll(Duration::from_millis(10))
+        .build()
+    {
+        Ok(driver) => driver,
+        // the kernel refuses the mode without privileges
+        Err(e) if matches!(e.raw_os_error(), Some(libc::EPERM | libc::EINVAL)) => return,
+        Err(e) => panic!("{e}"),
+    };
+
+    let mut entries = ArrayVec::<Entry, 1>::new();
+    // the second submission wakes up the sleeping thread
+    for (op, wait) in ops
+        .iter_mut()
+        .zip([Duration::ZERO, Duration::from_millis(50)])
+    {
+        std::thread::sleep(wait);
+        driver
+            .try_push(Operation::new(op, 0))
+            .unwrap_or_else(|_| panic!("queue is full"));
+        while entries.is_empty() {
+            unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+        }
+        assert_eq!(entries.pop().unwrap().into_result().unwrap(), 0);
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn taskrun_setup_flags() {
+    use std::{io::Write, os::unix::net::UnixStream};
+
+    use completeio::op::Read;
+
+    let (socket, mut peer) = UnixStream::pair().unwrap();
+    socket.set_nonblocking(true).unwrap();
+
+    let mut read;
+    let mut driver = Driver::builder()
+        .coop_taskrun(true)
+        .defer_taskrun(true)
+        .build()
+        .unwrap();
+    let capabilities = driver.capabilities();
+    // the flags are dropped on older kernels
+    if capabilities.kernel_version >= Some((6, 1, 0)) {
+        assert!(capabilities.coop_taskrun && capabilities.defer_taskrun);
+    }
+    // the single issuer is required by the deferred completions
+    assert!(!capabilities.defer_taskrun || capabilities.single_issuer);
+    let fd = driver.attach(socket.as_raw_fd()).unwrap();
+
+    read = Read::new(fd, Vec::with_capacity(8));
+    driver
+        .try_push(Operation::new(&mut read, 0))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    let mut entries = ArrayVec::<Entry, 1>::new();
+    unsafe { driver.submit(Some(Duration::ZERO), &mut entries) }.unwrap();
+    assert!(entries.is_empty());
+
+    // the deferred completion is received without waiting
+    peer.write_all(b"hello").unwrap();
+    while entries.is_empty() {
+        unsafe { driver.submit(Some(Duration::ZERO), &mut entries) }.unwrap();
+    }
+    assert_eq!(entries.pop().unwrap().into_result().unwrap(), 5);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn registered_ring_fd() {
+    use completeio::op::Nop;
+
+    fn submit_nop(driver: &mut Driver<'static>, user_data: usize) {
+        let op = Box::leak(Box::new(Nop::new()));
+        driver
+            .try_push(Operation::new(op, user_data))
+            .unwrap_or_else(|_| panic!("queue is full"));
+        let mut entries = ArrayVec::<Entry, 1>::new();
+        unsafe { driver.submit(None, &mut entries) }.unwrap();
+        let entry = entries.pop().unwrap();
+        assert_eq!(entry.user_data(), user_data);
+        assert_eq!(entry.into_result().unwrap(), 0);
+    }
+
+    let mut driver = Driver::new().unwrap();
+    let capabilities = driver.capabilities();
+    if capabilities.kernel_version >= Some((5, 18, 0)) {
+        assert!(capabilities.registered_ring);
+    }
+    submit_nop(&mut driver, 0);
+    // other threads enter with the plain fd
+    let mut driver = std::thread::spawn(move || {
+        submit_nop(&mut driver, 1);
+        driver
+    })
+    .join()
+    .unwrap();
+    submit_nop(&mut driver, 2);
+
+    let mut driver = Driver::builder().register_ring_fd(false).build().unwrap();
+    assert!(!driver.capabilities().registered_ring);
+    submit_nop(&mut driver, 3);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn napi_busy_poll() {
+    let busy_poll = Duration::from_micros(50);
+    let mut driver = Driver::builder()
+        .napi_busy_poll(busy_poll)
+        .napi_prefer_busy_poll(true)
+        .build()
+        .unwrap();
+    let capabilities = driver.capabilities();
+    match capabilities.napi_busy_poll {
+        Some(timeout) => {
+            assert_eq!(timeout, busy_poll);
+            assert!(capabilities.napi_prefer_busy_poll);
+            driver.unregister_napi().unwrap();
+            assert_eq!(driver.capabilities().napi_busy_poll, None);
+            driver.register_napi(busy_poll, false).unwrap();
+            assert!(!driver.capabilities().napi_prefer_busy_poll);
+        }
+        // the kernel is older than 6.9 or built without busy polling
+        None => {
+            let err = driver.register_napi(busy_poll, false).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn attach_wq() {
+    let mut driver = Driver::new().unwrap();
+    let token = driver.work_queue_token().unwrap();
+    let other_driver = Driver::builder().attach_wq(&token).build().unwrap();
+    drop(other_driver);
+
+    drop(driver);
+    let err = Driver::builder().attach_wq(&token).build().err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn nop() {
+    use completeio::op::Nop;
+
+    const TASK_LEN: usize = 16;
+
+    let mut ops: [Nop; TASK_LEN] = std::array::from_fn(|_| Nop::new());
+    let mut driver = Driver::new().unwrap();
+    for (i, nop) in ops.iter_mut().enumerate() {
+        driver
+            .try_push(Operation::new(nop, i))
+            .unwrap_or_else(|_| panic!("queue is full"));
+    }
+
+    let mut entries = ArrayVec::<Entry, TASK_LEN>::new();
+    while entries.len() < TASK_LEN {
+        unsafe { driver.submit(Some(Duration::from_secs(1)), &mut entries) }.unwrap();
+    }
+    let mut completed = entries
+        .into_iter()
+        .map(|entry| {
+            let user_data = entry.user_data();
+            assert_eq!(entry.into_result().unwrap(), 0);
+            user_data
+        })
+        .collect::<Vec<_>>();
+    completed.sort_unstable();
+    assert_eq!(completed, (0..TASK_LEN).collect::<Vec<_>>());
+}
+
+#[test]
+fn timeout_op() {
+    use boot_time::Instant;
+    use completeio::op::Timeout;
+
+    const DELAY: Duration = Duration::from_millis(50);
+
+    let start = Instant::now();
+    let mut relative = Timeout::new(DELAY);
+    let mut absolute = Timeout::at(start + 2 * DELAY);
+
+    let mut driver = Driver::new().unwrap();
+    let mut entries = ArrayVec::<Entry, 1>::new();
+    for (op, deadline) in [
+        (&mut relative, start + DELAY),
+        (&mut absolute, start + 2 * DELAY),
+    ] {
+        driver
+            .try_push(Operation::new(op, 0))
+            .unwrap_or_else(|_| panic!("queue is full"));
+        while entries.is_empty() {
+            unsafe { driver.submit(Some(Duration::from_millis(100)), &mut entries) }.unwrap();
+        }
+        assert!(Instant::now() >= deadline);
+        // the expired timeout is not an error
+        assert_eq!(entries.pop().unwrap().into_result().unwrap(), 0);
+    }
+}
+
+#[test]
+fn timeout_update_and_remove() {
+    use completeio::op::{Timeout, TimeoutRemove, TimeoutUpdate};
+
+    let mut timeout = Timeout::new(Duration::from_secs(3600));
+    let mut update = TimeoutUpdate::new(0, Duration::from_millis(10));
+    let mut removed_timeout = Timeout::new(Duration::from_secs(3600));
+    let mut remove = TimeoutRemove::new(2);
+
+    let mut driver = Driver::new().unwrap();
+    let mut entries = ArrayVec::<Entry, 2>::new();
+    for op in [
+        Operation::new(&mut timeout, 0).into(),
+        Operation::new(&mut update, 1).into(),
+    ] {
+        driver
+            .try_push_dyn(op)
+            .unwrap_or_else(|_| panic!("queue is full"));
+    }
+    while entries.len() < 2 {
+        unsafe { driver.submit(Some(Duration::from_millis(100)), &mut entries) }.unwrap();
+    }
+    // the updated timeout fires after the update completes
+    let [updated, fired] = entries.take().into_inner().unwrap();
+    assert_eq!(updated.user_data(), 1);
+    assert_eq!(updated.into_result().unwrap(), 0);
+    assert_eq!(fired.user_data(), 0);
+    assert_eq!(fired.into_result().unwrap(), 0);
+
+    for op in [
+        Operation::new(&mut removed_timeout, 2).into(),
+        Operation::new(&mut remove, 3).into(),
+    ] {
+        driver
+            .try_push_dyn(op)
+            .unwrap_or_else(|_| panic!("queue is full"));
+    }
+    while entries.len() < 2 {
+        unsafe { driver.submit(Some(Duration::from_millis(100)), &mut entries) }.unwrap();
+    }
+    for entry in entries.drain(..) {
+        match entry.user_data() {
+            2 => assert!(entry.into_result().is_err()),
+            3 => assert_eq!(entry.into_result().unwrap(), 0),
+            _ => unreachable!(),
+        }
+    }
+}
+
 #[test]
 fn attach_read_multiple_and_close_attached() {
     const TASK_LEN: usize = 3;
 
+    let mut ops;
+    let mut attached_fd;
     let mut driver = Driver::new().unwrap();
 
     let file = File::open("Cargo.toml").unwrap();
     let fd = driver.attach(file.as_raw_fd()).unwrap();
 
-    let mut ops = [
+    ops = [
         ReadAt::new(fd, 0, Vec::with_capacity(1024)),
         ReadAt::new(fd, 0, Vec::with_capacity(1024)),
         ReadAt::new(fd, 0, Vec::with_capacity(1024)),
@@ -65,9 +885,9 @@ fn attach_read_multiple_and_close_attached() {
     }
 
     // close attached fd
-    let mut fd = fd;
+    attached_fd = fd;
     driver
-        .try_push(Operation::new(&mut fd, 42))
+        .try_push(Operation::new(&mut attached_fd, 42))
         .unwrap_or_else(|_| panic!("queue is full"));
 
     let mut entries = ArrayVec::<Entry, 1>::new();
@@ -86,12 +906,14 @@ fn register_read_multiple_and_close_unregister() {
     const ENTRIES: u32 = 1024;
     const FILES_TO_REGISTER: u32 = 64;
 
+    let mut ops;
+    let mut file_to_close;
     let mut driver = Driver::with(ENTRIES, FILES_TO_REGISTER).unwrap();
 
     let file = File::open("Cargo.toml").unwrap();
     let fixed_fd = driver.register_fd(file.as_raw_fd(), 1).unwrap();
 
-    let mut ops = [
+    ops = [
         ReadAt::new(fixed_fd, 0, Vec::with_capacity(1024)),
         ReadAt::new(fixed_fd, 0, Vec::with_capacity(1024)),
         ReadAt::new(fixed_fd, 0, Vec::with_capacity(1024)),
@@ -115,7 +937,7 @@ fn register_read_multiple_and_close_unregister() {
     // Close and unregister file using async interface
 
     // Close operation will replace Some value to None
-    let mut file_to_close = Some(file);
+    file_to_close = Some(file);
     let close_op = Operation::new(&mut file_to_close, 7);
 
     driver
@@ -134,3 +956,1047 @@ fn register_read_multiple_and_close_unregister() {
         e.into_result().unwrap();
     }
 }
+
+#[cfg(target_os = "linux")]
+#[test]
+fn register_files_slot() {
+    const FILES_TO_REGISTER: u32 = 4;
+
+    let mut op;
+    let mut driver = Driver::with(1024, FILES_TO_REGISTER).unwrap();
+
+    let file = std::fs::File::open("Cargo.toml").unwrap();
+    let other_file = std::fs::File::open("Cargo.toml").unwrap();
+    let fds = [file.as_raw_fd(), other_file.as_raw_fd()];
+
+    let slot = driver.register_files(&fds).unwrap();
+    assert_eq!(slot.len(), 2);
+    assert!(slot.get(2).is_none());
+    let other_slot = driver.register_files(&fds).unwrap();
+    // all registered files are in use
+    assert!(driver.register_files(&fds[..1]).is_err());
+
+    op = ReadAt::new(slot.get(1).unwrap(), 0, Vec::with_capacity(9));
+    driver
+        .try_push(Operation::new(&mut op, 0))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    let mut entries = ArrayVec::<Entry, 1>::new();
+    while entries.is_empty() {
+        unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    }
+    assert_eq!(entries.pop().unwrap().into_result().unwrap(), 9);
+
+    // the slot is reused after the async removal completes
+    let fixed_fd = slot.get(0);
+    driver.unregister_files(slot).unwrap();
+    let slot = loop {
+        unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+        if let Ok(slot) = driver.register_files(&fds) {
+            break slot;
+        }
+    };
+    assert_eq!(slot.get(0), fixed_fd);
+
+    driver.unregister_files(slot).unwrap();
+    driver.unregister_files(other_slot).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn files_update_replaces_socket() {
+    use std::net::UdpSocket;
+
+    use completeio::{
+        buf::{IntoInner, IoBufMut},
+        op::{FilesUpdate, Recv},
+    };
+
+    let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let first = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let second = UdpSocket::bind("127.0.0.1:0").unwrap();
+    sender
+        .send_to(b"first", first.local_addr().unwrap())
+        .unwrap();
+    sender
+        .send_to(b"second", second.local_addr().unwrap())
+        .unwrap();
+
+    let mut first_recv;
+    let mut update;
+    let mut stale_recv;
+    let mut second_recv;
+    let mut driver = Driver::with(1024, 2).unwrap();
+    let mut slot = driver.register_files(&[first.as_raw_fd()]).unwrap();
+    let fixed_fd = slot.get(0).unwrap();
+    assert!(FilesUpdate::new(&mut slot, 1, vec![second.as_raw_fd()]).is_err());
+
+    first_recv = Recv::new(fixed_fd, Vec::with_capacity(8));
+    update = FilesUpdate::new(&mut slot, 0, vec![second.as_raw_fd()]).unwrap();
+    // the fixed fd got before the update is stale
+    stale_recv = Recv::new(fixed_fd, Vec::with_capacity(8));
+    second_recv = Recv::new(slot.get(0).unwrap(), Vec::with_capacity(8));
+
+    let mut entries = ArrayVec::<Entry, 1>::new();
+    let mut results = Vec::new();
+    for op in [
+        Operation::new(&mut first_recv, 0).into(),
+        Operation::new(&mut update, 1).into(),
+        Operation::new(&mut stale_recv, 2).into(),
+        Operation::new(&mut second_recv, 3).into(),
+    ] {
+        driver
+            .try_push_dyn(op)
+            .unwrap_or_else(|_| panic!("queue is full"));
+        while entries.is_empty() {
+            unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+        }
+        results.push(entries.pop().unwrap().into_result());
+    }
+    // the number of updated files
+    assert_eq!(results[1].as_ref().unwrap(), &1);
+    assert_eq!(
+        results[2].as_ref().unwrap_err().kind(),
+        std::io::ErrorKind::InvalidInput
+    );
+    driver.unregister_files(slot).unwrap();
+    drop(driver);
+
+    let mut buffer = first_recv.into_inner();
+    buffer.set_buf_init(*results[0].as_ref().unwrap());
+    assert_eq!(buffer, b"first");
+    // the read with the new fixed fd hits the new socket
+    let mut buffer = second_recv.into_inner();
+    buffer.set_buf_init(*results[3].as_ref().unwrap());
+    assert_eq!(buffer, b"second");
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn unregistered_files_are_released() {
+    use std::net::UdpSocket;
+
+    let first = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let second = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    let mut driver = Driver::with(1024, 2).unwrap();
+    let mut slot = driver
+        .register_files(&[first.as_raw_fd(), second.as_raw_fd()])
+        .unwrap();
+    // the file removed from the live slot keeps it allocated
+    driver.register_files_update(&mut slot, 1, &[-1]).unwrap();
+    // the wait returns when the update completes
+    let mut entries = ArrayVec::<Entry, 1>::new();
+    unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    assert!(driver.register_files(&[second.as_raw_fd()]).is_err());
+
+    driver.unregister_files(slot).unwrap();
+    unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    let slot = driver
+        .register_files(&[first.as_raw_fd(), second.as_raw_fd()])
+        .unwrap();
+    driver.unregister_files(slot).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn linked_write_and_sync() {
+    use completeio::{
+        driver::OpObject,
+        op::{Sync, WriteAt},
+    };
+
+    const CONTENT: &[u8] = b"linked";
+
+    let dir = tempfile::tempdir().unwrap();
+    let file = std::fs::File::create(dir.path().join("linked")).unwrap();
+    let read_only_file = std::fs::File::open("Cargo.toml").unwrap();
+
+    let mut write;
+    let mut sync;
+    let mut failed_write;
+    let mut canceled_sync;
+    let mut canceled_write;
+    let mut driver = Driver::new().unwrap();
+    let fd = driver.attach(file.as_raw_fd()).unwrap();
+    let read_only_fd = driver.attach(read_only_file.as_raw_fd()).unwrap();
+
+    write = WriteAt::new(fd, 0, CONTENT);
+    sync = Sync::new(fd, false);
+    // the write fails, so its successors are canceled
+    failed_write = WriteAt::new(read_only_fd, 0, CONTENT);
+    canceled_sync = Sync::new(read_only_fd, false);
+    canceled_write = WriteAt::new(read_only_fd, 0, CONTENT);
+    for op in [
+        Operation::new(&mut write, 0).link().into(),
+        Operation::new(&mut sync, 1).into(),
+        Operation::new(&mut failed_write, 2).link().into(),
+        Operation::new(&mut canceled_sync, 3).link().into(),
+        OpObject::from(Operation::new(&mut canceled_write, 4)),
+    ] {
+        driver
+            .try_push_dyn(op)
+            .unwrap_or_else(|_| panic!("queue is full"));
+    }
+
+    let mut entries = ArrayVec::<Entry, 5>::new();
+    while entries.len() < 5 {
+        unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    }
+    entries.sort_by_key(|e| e.user_data());
+    let mut results = entries.into_iter().map(Entry::into_result);
+    assert_eq!(results.next().unwrap().unwrap(), CONTENT.len());
+    assert_eq!(results.next().unwrap().unwrap(), 0);
+    assert_eq!(
+        results.next().unwrap().unwrap_err().raw_os_error(),
+        Some(libc::EBADF)
+    );
+    for result in results {
+        assert!(completeio::CancelledError::is_cancelled(
+            &result.unwrap_err()
+        ));
+    }
+}
+
+#[test]
+fn read_file_on_blocking_threads() {
+    use completeio::buf::IntoInner;
+
+    let file = File::open("Cargo.toml").unwrap();
+    let expected = std::fs::read("Cargo.toml").unwrap();
+
+    // the reads outnumber the threads of the pool
+    let mut ops;
+    let mut driver = Driver::builder().blocking_threads(2).build().unwrap();
+    let fd = driver.attach(file.as_raw_fd()).unwrap();
+    ops = [0, 1, 2].map(|offset| ReadAt::new(fd, offset, Vec::with_capacity(4)));
+    for (i, op) in ops.iter_mut().enumerate() {
+        driver
+            .try_push(Operation::new(op, i))
+            .unwrap_or_else(|_| panic!("queue is full"));
+    }
+
+    let mut entries = ArrayVec::<Entry, 3>::new();
+    while entries.len() < 3 {
+        unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    }
+    for entry in entries {
+        assert_eq!(entry.into_result().unwrap(), 4);
+    }
+    drop(driver);
+    for (offset, op) in ops.into_iter().enumerate() {
+        let mut buffer = op.into_inner();
+        unsafe { buffer.set_len(4) };
+        assert_eq!(buffer, expected[offset..offset + 4]);
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn nowait_read_of_empty_pipe() {
+    use std::{io::Write, os::fd::AsRawFd as _};
+
+    use completeio::op::RwFlags;
+
+    fn complete_one(driver: &mut Driver<'_>) -> std::io::Result<usize> {
+        let mut entries = ArrayVec::<Entry, 1>::new();
+        while entries.is_empty() {
+            unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+        }
+        entries.pop().unwrap().into_result()
+    }
+
+    let (receiver, mut sender) = std::io::pipe().unwrap();
+
+    let mut empty_read;
+    let mut read;
+    let mut driver = Driver::new().unwrap();
+    let fd = driver.attach(receiver.as_raw_fd()).unwrap();
+
+    // the offset -1 reads from the current position of the nonseekable pipe
+    empty_read = ReadAt::new(fd, usize::MAX, Vec::with_capacity(8)).with_rw_flags(RwFlags::NOWAIT);
+    read = ReadAt::new(fd, usize::MAX, Vec::with_capacity(8)).with_rw_flags(RwFlags::NOWAIT);
+
+    // without the flag the read would wait for the data
+    driver
+        .try_push(Operation::new(&mut empty_read, 0))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    let err = complete_one(&mut driver).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock);
+
+    sender.write_all(b"ready").unwrap();
+    driver
+        .try_push(Operation::new(&mut read, 1))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    assert_eq!(complete_one(&mut driver).unwrap(), 5);
+}
+
+#[cfg(unix)]
+#[test]
+fn madvise_mapped_file() {
+    use completeio::op::{Advice, Madvise};
+
+    let file = std::fs::File::open("Cargo.toml").unwrap();
+    let map = unsafe { memmap2::Mmap::map(&file) }.unwrap();
+
+    // SAFETY: the mapping outlives the operation
+    let mut ops = [
+        unsafe { Madvise::new(map.as_ptr() as *mut _, map.len(), Advice::WillNeed) },
+        unsafe { Madvise::new(map.as_ptr() as *mut _, map.len(), Advice::NoReuse) },
+    ];
+    let mut driver = Driver::new().unwrap();
+    for (i, madvise) in ops.iter_mut().enumerate() {
+        driver
+            .try_push(Operation::new(madvise, i))
+            .unwrap_or_else(|_| panic!("queue is full"));
+    }
+
+    let mut entries = ArrayVec::<Entry, 2>::new();
+    while entries.len() < 2 {
+        unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    }
+    entries.sort_by_key(|e| e.user_data());
+    let mut entries = entries.into_iter();
+    assert_eq!(entries.next().unwrap().into_result().unwrap(), 0);
+    let err = entries.next().unwrap().into_result().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    assert_eq!(&map[..9], b"[package]");
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn recv_multishot_buf_ring() {
+    use std::net::UdpSocket;
+
+    use completeio::{driver::BufRing, op::RecvMultishot};
+
+    const GROUP_ID: u16 = 1;
+
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let other_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    other_socket.connect(socket.local_addr().unwrap()).unwrap();
+
+    let ring = BufRing::new(GROUP_ID, 4, 16).unwrap();
+    let mut op;
+    let mut driver = Driver::new().unwrap();
+    driver.register_buf_ring(&ring).unwrap();
+    let fd = driver.attach(socket.as_raw_fd()).unwrap();
+
+    op = RecvMultishot::new(fd, GROUP_ID);
+    driver
+        .try_push(Operation::new(&mut op, 0))
+        .unwrap_or_else(|_| panic!("queue is full"));
+
+    // more datagrams than buffers, the taken buffers are given back to the ring
+    let mut received = Vec::new();
+    for i in 0..8u8 {
+        other_socket.send(&[i; 3]).unwrap();
+        let mut entries = ArrayVec::<Entry, 1>::new();
+        while entries.is_empty() {
+            unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+        }
+        let entry = entries.pop().unwrap();
+        assert!(entry.has_more());
+        let buffer_id = entry.buffer_id().expect("buffer is selected");
+        let len = entry.into_result().unwrap();
+        let buffer = unsafe { ring.take(buffer_id, len) };
+        assert_eq!(&*buffer, &[i; 3]);
+        received.push(buffer_id);
+    }
+    assert!(received.iter().all(|&buffer_id| buffer_id < 4));
+
+    driver.try_cancel(0).unwrap();
+    let mut entries = ArrayVec::<Entry, 1>::new();
+    while entries.is_empty() {
+        unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    }
+    let entry = entries.pop().unwrap();
+    assert!(!entry.has_more());
+    assert_eq!(entry.os_error(), Some(libc::ECANCELED));
+    assert_eq!(entry.raw_result(), -libc::ECANCELED);
+    assert!(completeio::CancelledError::is_cancelled(
+        &entry.into_result().unwrap_err()
+    ));
+    driver.unregister_buf_ring(GROUP_ID).unwrap();
+}
+
+#[test]
+fn send_zc_notification() {
+    use std::net::UdpSocket;
+
+    use completeio::op::SendZc;
+
+    let socket = completeio::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let other_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket.connect(other_socket.local_addr().unwrap()).unwrap();
+
+    let mut op;
+    let mut driver = Driver::new().unwrap();
+    let fd = driver.attach(socket.as_raw_fd()).unwrap();
+
+    op = SendZc::new(fd, b"hello".to_vec());
+    driver
+        .try_push(Operation::new(&mut op, 0))
+        .unwrap_or_else(|_| panic!("queue is full"));
+
+    let mut entries = ArrayVec::<Entry, 1>::new();
+    while entries.is_empty() {
+        unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    }
+    let entry = entries.pop().unwrap();
+    assert_eq!(entry.is_notification(), driver.capabilities().send_zc);
+    assert!(!entry.has_more());
+    assert_eq!(entry.buffer_id(), None);
+    assert_eq!(entry.into_result().unwrap(), 5);
+
+    let mut buffer = [0; 8];
+    let n = other_socket.recv(&mut buffer).unwrap();
+    assert_eq!(&buffer[..n], b"hello");
+}
+
+#[cfg(unix)]
+#[test]
+fn send_zc_with_timeout() {
+    use std::{
+        io::Write,
+        net::{TcpListener, TcpStream},
+    };
+
+    use completeio::op::SendZc;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (_peer, _) = listener.accept().unwrap();
+    // the peer doesn't read, so the send waits when the socket buffers are full
+    socket2::SockRef::from(&stream)
+        .set_send_buffer_size(4096)
+        .unwrap();
+    stream.set_nonblocking(true).unwrap();
+    while stream.write(&[0; 4096]).is_ok() {}
+    stream.set_nonblocking(false).unwrap();
+
+    let mut op;
+    let mut driver = Driver::new().unwrap();
+    let fd = driver.attach(stream.as_raw_fd()).unwrap();
+
+    op = SendZc::new(fd, vec![0; 4096]);
+    driver
+        .try_push(Operation::with_timeout(
+            &mut op,
+            0,
+            Duration::from_millis(10),
+        ))
+        .unwrap_or_else(|_| panic!("queue is full"));
+
+    let mut entries = ArrayVec::<Entry, 1>::new();
+    while entries.is_empty() {
+        unsafe { driver.submit(Some(Duration::from_secs(1)), &mut entries) }.unwrap();
+    }
+    let entry = entries.pop().unwrap();
+    assert_eq!(entry.user_data(), 0);
+    // the linked timeout cancels the operation before or after the send is issued
+    #[cfg(target_os = "linux")]
+    assert_eq!(entry.raw_result(), -libc::ECANCELED);
+    assert_eq!(
+        entry.into_result().unwrap_err().kind(),
+        std::io::ErrorKind::TimedOut
+    );
+    // no CQE of the operation is left
+    unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    assert!(entries.is_empty());
+}
+
+#[test]
+fn recv_pending_and_completed_on_push() {
+    use std::{net::UdpSocket, thread, time::Instant};
+
+    use completeio::{buf::IntoInner, op::Recv};
+
+    let socket = completeio::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let other_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket.connect(other_socket.local_addr().unwrap()).unwrap();
+    other_socket
+        .connect(socket.local_addr().unwrap().as_socket().unwrap())
+        .unwrap();
+
+    let mut pending_recv;
+    let mut ready_recv;
+    let mut driver = Driver::new().unwrap();
+    let fd = driver.attach(socket.as_raw_fd()).unwrap();
+
+    // IOCP receives the packet of the pending operation
+    pending_recv = Recv::new(fd, Vec::with_capacity(8));
+    driver
+        .try_push(Operation::new(&mut pending_recv, 0))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    let mut entries = ArrayVec::<Entry, 1>::new();
+    unsafe { driver.submit(Some(Duration::ZERO), &mut entries) }.unwrap();
+    assert!(entries.is_empty());
+    other_socket.send(b"first").unwrap();
+
+    // the data is received already, IOCP doesn't post the packet on success
+    let started = Instant::now();
+    let wait_entry = |driver: &mut Driver, entries: &mut ArrayVec<Entry, 1>| {
+        while entries.is_empty() {
+            assert!(started.elapsed() < Duration::from_secs(5), "no completion");
+            unsafe { driver.submit(Some(Duration::from_millis(10)), entries) }.unwrap();
+        }
+        entries.pop().unwrap()
+    };
+    let entry = wait_entry(&mut driver, &mut entries);
+    assert_eq!(entry.user_data(), 0);
+    assert_eq!(entry.into_result().unwrap(), 5);
+    other_socket.send(b"second").unwrap();
+    thread::sleep(Duration::from_millis(50));
+    ready_recv = Recv::new(fd, Vec::with_capacity(8));
+    driver
+        .try_push(Operation::new(&mut ready_recv, 1))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    let entry = wait_entry(&mut driver, &mut entries);
+    assert_eq!(entry.user_data(), 1);
+    assert_eq!(entry.into_result().unwrap(), 6);
+
+    drop(driver);
+    let mut buffer = ready_recv.into_inner();
+    unsafe { buffer.set_len(6) };
+    assert_eq!(buffer, b"second");
+}
+
+#[test]
+fn detach_and_attach_to_another_driver() {
+    use std::{net::UdpSocket, time::Instant};
+
+    use completeio::op::Recv;
+
+    let socket = completeio::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let other_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket.connect(other_socket.local_addr().unwrap()).unwrap();
+    other_socket
+        .connect(socket.local_addr().unwrap().as_socket().unwrap())
+        .unwrap();
+
+    let mut cancelled_recv;
+    let mut recv;
+    let mut driver = Driver::new().unwrap();
+    let mut other_driver = Driver::new().unwrap();
+    let fd = driver.attach(socket.as_raw_fd()).unwrap();
+
+    let started = Instant::now();
+    let wait_entry = |driver: &mut Driver, entries: &mut ArrayVec<Entry, 1>| {
+        while entries.is_empty() {
+            assert!(started.elapsed() < Duration::from_secs(5), "no completion");
+            unsafe { driver.submit(Some(Duration::from_millis(10)), entries) }.unwrap();
+        }
+        entries.pop().unwrap()
+    };
+
+    cancelled_recv = Recv::new(fd, Vec::with_capacity(8));
+    driver
+        .try_push(Operation::new(&mut cancelled_recv, 0))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    let mut entries = ArrayVec::<Entry, 1>::new();
+    unsafe { driver.submit(Some(Duration::ZERO), &mut entries) }.unwrap();
+    assert!(entries.is_empty());
+    // io-uring operations don't depend on the attachment
+    #[cfg(not(target_os = "linux"))]
+    driver.detach(socket.as_raw_fd()).unwrap_err();
+    driver.try_cancel(0).unwrap();
+    let entry = wait_entry(&mut driver, &mut entries);
+    assert_eq!(entry.user_data(), 0);
+    entry.into_result().unwrap_err();
+    driver.detach(socket.as_raw_fd()).unwrap();
+
+    let fd = other_driver.attach(socket.as_raw_fd()).unwrap();
+    recv = Recv::new(fd, Vec::with_capacity(8));
+    other_driver
+        .try_push(Operation::new(&mut recv, 1))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    other_socket.send(b"moved").unwrap();
+    let entry = wait_entry(&mut other_driver, &mut entries);
+    assert_eq!(entry.user_data(), 1);
+    assert_eq!(entry.into_result().unwrap(), 5);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn epoll_ctl_eventfd() {
+    use completeio::op::{EpollCtl, EpollCtlOp};
+
+    // the data of the registered event
+    const TOKEN: u64 = 7;
+
+    fn epoll_wait(epfd: i32) -> Vec<u64> {
+        let mut events = [libc::epoll_event { events: 0, u64: 0 }; 2];
+        let n = unsafe { libc::epoll_wait(epfd, events.as_mut_ptr(), 2, 0) };
+        assert!(n >= 0);
+        events[..n as usize].iter().map(|e| e.u64).collect()
+    }
+
+    let epfd = unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) };
+    assert!(epfd >= 0);
+    let efd = unsafe { libc::eventfd(0, libc::EFD_CLOEXEC) };
+    assert!(efd >= 0);
+
+    let mut add;
+    let mut delete;
+    let mut driver = Driver::new().unwrap();
+    let epoll = driver.attach(epfd).unwrap();
+    let event_fd = driver.attach(efd).unwrap();
+    let event = libc::epoll_event {
+        events: libc::EPOLLIN as _,
+        u64: TOKEN,
+    };
+    add = EpollCtl::new(epoll, event_fd, EpollCtlOp::Add(event));
+    delete = EpollCtl::new(epoll, event_fd, EpollCtlOp::Delete);
+
+    for (user_data, op) in [&mut add, &mut delete].into_iter().enumerate() {
+        driver
+            .try_push(Operation::new(op, user_data))
+            .unwrap_or_else(|_| panic!("queue is full"));
+        let mut entries = ArrayVec::<Entry, 1>::new();
+        while entries.is_empty() {
+            unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+        }
+        let entry = entries.pop().unwrap();
+        assert_eq!(entry.user_data(), user_data);
+        entry.into_result().unwrap();
+
+        if user_data == 0 {
+            assert!(epoll_wait(epfd).is_empty());
+            assert_eq!(unsafe { libc::eventfd_write(efd, 1) }, 0);
+            assert_eq!(epoll_wait(epfd), [TOKEN]);
+        } else {
+            assert!(epoll_wait(epfd).is_empty());
+        }
+    }
+    unsafe {
+        libc::close(efd);
+        libc::close(epfd);
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn uring_cmd_socket_inq() {
+    use std::net::UdpSocket;
+
+    use completeio::op::UringCmd;
+
+    // the number of bytes in the receive queue
+    const SOCKET_URING_OP_SIOCINQ: u32 = 0;
+
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket
+        .send_to(b"hello", socket.local_addr().unwrap())
+        .unwrap();
+
+    let mut op;
+    let mut driver = Driver::new().unwrap();
+    let fd = driver.attach(socket.as_raw_fd()).unwrap();
+    op = unsafe { UringCmd::<Vec<u8>>::new(fd, SOCKET_URING_OP_SIOCINQ, [0; 16], None) };
+    driver
+        .try_push(Operation::new(&mut op, 0))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    let mut entries = ArrayVec::<Entry, 1>::new();
+    while entries.is_empty() {
+        unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    }
+    match entries.pop().unwrap().into_result() {
+        Ok(inq) => assert_eq!(inq, 5),
+        // socket commands are available since Linux 6.7
+        Err(e) if matches!(e.raw_os_error(), Some(libc::EOPNOTSUPP | libc::EINVAL)) => {}
+        Err(e) => panic!("{e}"),
+    }
+}
+
+#[test]
+fn write_read_registered_buffers() {
+    use std::io::IoSliceMut;
+
+    use completeio::{
+        buf::{IntoInner, IoBufMut},
+        op::{ReadAtFixed, WriteAtFixed},
+    };
+
+    const CONTENT: &[u8] = b"registered buffers";
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fixed");
+    let file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+        .unwrap();
+
+    let mut write_memory = [0u8; 32];
+    let mut read_memory = [0u8; 32];
+    let mut bufs = [
+        IoSliceMut::new(&mut write_memory),
+        IoSliceMut::new(&mut read_memory),
+    ];
+
+    // the registry and the operations outlive the driver
+    #[allow(clippy::needless_late_init)]
+    let registry;
+    let mut write;
+    let mut read;
+    let mut driver = Driver::new().unwrap();
+    registry = driver.register_buffers(&mut bufs).unwrap();
+    assert_eq!(registry.len(), 2);
+    let fd = driver.attach(file.as_raw_fd()).unwrap();
+
+    let mut write_buf = registry.check_out(0).unwrap();
+    // the buffer is owned by a single operation
+    assert!(registry.check_out(0).is_none());
+    assert_eq!(write_buf.extend_from_slice(CONTENT), CONTENT.len());
+    write = WriteAtFixed::new(fd, 0, write_buf);
+    let read_buf = registry.check_out(1).unwrap();
+    read = ReadAtFixed::new(fd, 0, read_buf);
+
+    driver
+        .try_push(Operation::new(&mut write, 0))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    let mut entries = ArrayVec::<Entry, 1>::new();
+    while entries.is_empty() {
+        unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    }
+    assert_eq!(entries.pop().unwrap().into_result().unwrap(), CONTENT.len());
+
+    driver
+        .try_push(Operation::new(&mut read, 1))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    while entries.is_empty() {
+        unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    }
+    let n = entries.pop().unwrap().into_result().unwrap();
+    assert_eq!(n, CONTENT.len());
+
+    // buffers in use can't be unregistered
+    assert!(driver.unregister_buffers(&registry).is_err());
+
+    // the operations are borrowed by the driver until it's dropped
+    drop(driver);
+    let mut read_buf = read.into_inner();
+    read_buf.set_buf_init(n);
+    assert_eq!(&*read_buf, CONTENT);
+    drop(read_buf);
+    drop(write);
+    // the buffers are returned to the registry
+    assert!(registry.check_out(0).is_some());
+    assert!(registry.check_out(1).is_some());
+}
+
+#[test]
+fn recv_with_timeout() {
+    use std::net::UdpSocket;
+
+    use completeio::op::Recv;
+
+    let socket = socket2::Socket::from(UdpSocket::bind("127.0.0.1:0").unwrap());
+
+    let mut op;
+    let mut driver = Driver::new().unwrap();
+    let fd = driver.attach(socket.as_raw_fd()).unwrap();
+
+    // nothing is sent to the socket
+    op = Recv::new(fd, Vec::with_capacity(8));
+    driver
+        .try_push(Operation::with_timeout(
+            &mut op,
+            0,
+            Duration::from_millis(10),
+        ))
+        .unwrap_or_else(|_| panic!("queue is full"));
+
+    let mut entries = ArrayVec::<Entry, 1>::new();
+    while entries.is_empty() {
+        unsafe { driver.submit(Some(Duration::from_secs(1)), &mut entries) }.unwrap();
+    }
+    let entry = entries.pop().unwrap();
+    assert_eq!(entry.user_data(), 0);
+    // the linked timeout cancels the operation
+    #[cfg(target_os = "linux")]
+    assert_eq!(entry.raw_result(), -libc::ECANCELED);
+    assert_eq!(
+        entry.into_result().unwrap_err().kind(),
+        std::io::ErrorKind::TimedOut
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn canceled_successor_with_timeout() {
+    use completeio::op::{Sync, WriteAt};
+
+    let read_only_file = std::fs::File::open("Cargo.toml").unwrap();
+
+    let mut failed_write;
+    let mut canceled_sync;
+    let mut driver = Driver::new().unwrap();
+    let fd = driver.attach(read_only_file.as_raw_fd()).unwrap();
+
+    failed_write = WriteAt::new(fd, 0, &b"linked"[..]);
+    canceled_sync = Sync::new(fd, false);
+    driver
+        .try_push(Operation::new(&mut failed_write, 0).link())
+        .unwrap_or_else(|_| panic!("queue is full"));
+    driver
+        .try_push(Operation::with_timeout(
+            &mut canceled_sync,
+            1,
+            Duration::from_secs(5),
+        ))
+        .unwrap_or_else(|_| panic!("queue is full"));
+
+    let mut entries = ArrayVec::<Entry, 2>::new();
+    while entries.len() < 2 {
+        unsafe { driver.submit(Some(Duration::from_secs(1)), &mut entries) }.unwrap();
+    }
+    entries.sort_by_key(|e| e.user_data());
+    // the failed predecessor cancels the operation before its timeout fires
+    let err = entries.pop().unwrap().into_result().unwrap_err();
+    assert_ne!(err.kind(), std::io::ErrorKind::TimedOut);
+    assert!(completeio::CancelledError::is_cancelled(&err));
+}
+
+#[cfg(unix)]
+#[test]
+fn cancel_recv() {
+    use std::net::UdpSocket;
+
+    use completeio::op::{Cancel, Recv};
+
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    let mut recv;
+    let mut other_recv;
+    let mut cancel;
+    let mut cancel_missing;
+    let mut cancel_fd;
+    let mut driver = Driver::new().unwrap();
+    let fd = driver.attach(socket.as_raw_fd()).unwrap();
+
+    recv = Recv::new(fd, Vec::with_capacity(8));
+    other_recv = Recv::new(fd, Vec::with_capacity(8));
+    driver
+        .try_push(Operation::new(&mut recv, 0))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    driver
+        .try_push(Operation::new(&mut other_recv, 1))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    let mut entries = ArrayVec::<Entry, 3>::new();
+    unsafe { driver.submit(Some(Duration::ZERO), &mut entries) }.unwrap();
+    assert!(entries.is_empty());
+
+    cancel = Cancel::new(0);
+    cancel_missing = Cancel::new(42);
+    for (op, user_data) in [(&mut cancel, 2), (&mut cancel_missing, 3)] {
+        driver
+            .try_push(Operation::new(op, user_data))
+            .unwrap_or_else(|_| panic!("queue is full"));
+    }
+    let mut entries = ArrayVec::<Entry, 3>::new();
+    while entries.len() < 3 {
+        unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    }
+    entries.sort_by_key(|e| e.user_data());
+    let mut results = entries.into_iter().map(Entry::into_result);
+    assert!(completeio::CancelledError::is_cancelled(
+        &results.next().unwrap().unwrap_err()
+    ));
+    assert_eq!(results.next().unwrap().unwrap(), 0);
+    assert_eq!(
+        results.next().unwrap().unwrap_err().raw_os_error(),
+        Some(libc::ENOENT)
+    );
+
+    // the rest of operations on the socket
+    cancel_fd = Cancel::fd(fd);
+    driver
+        .try_push(Operation::new(&mut cancel_fd, 4))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    let mut entries = ArrayVec::<Entry, 2>::new();
+    while entries.len() < 2 {
+        unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    }
+    entries.sort_by_key(|e| e.user_data());
+    let mut results = entries.into_iter().map(Entry::into_result);
+    assert!(completeio::CancelledError::is_cancelled(
+        &results.next().unwrap().unwrap_err()
+    ));
+    assert_eq!(results.next().unwrap().unwrap(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn shutdown_and_drop_with_pending_read() {
+    use std::os::unix::net::UnixStream;
+
+    use completeio::op::Read;
+
+    // nothing is written to the peer
+    let (socket, _peer) = UnixStream::pair().unwrap();
+    socket.set_nonblocking(true).unwrap();
+
+    let mut read;
+    let mut other_read;
+    let mut driver = Driver::new().unwrap();
+    let fd = driver.attach(socket.as_raw_fd()).unwrap();
+
+    read = Read::new(fd, Vec::with_capacity(8));
+    driver
+        .try_push(Operation::new(&mut read, 0))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    let mut entries = ArrayVec::<Entry, 1>::new();
+    unsafe { driver.submit(Some(Duration::ZERO), &mut entries) }.unwrap();
+    assert!(entries.is_empty());
+
+    // the canceled read is not reported
+    driver.shutdown(Some(Duration::from_secs(1))).unwrap();
+    unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+    assert!(entries.is_empty());
+
+    // the driver waits for the pending read when it's dropped
+    other_read = Read::new(fd, Vec::with_capacity(8));
+    driver
+        .try_push(Operation::new(&mut other_read, 1))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    unsafe { driver.submit(Some(Duration::ZERO), &mut entries) }.unwrap();
+    assert!(entries.is_empty());
+    drop(driver);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn shutdown_cancels_one_by_one() {
+    use std::os::unix::net::UnixStream;
+
+    use completeio::op::Read;
+
+    // nothing is written to the peer
+    let (socket, _peer) = UnixStream::pair().unwrap();
+    socket.set_nonblocking(true).unwrap();
+
+    let mut read;
+    let mut other_read;
+    // cancel the operations as before Linux 5.19 without tracking the duplicates
+    let mut driver = Driver::builder()
+        .check_user_data(false)
+        .cancel_any(false)
+        .build()
+        .unwrap();
+    let fd = driver.attach(socket.as_raw_fd()).unwrap();
+    // the operations share the user data
+    read = Read::new(fd, Vec::with_capacity(8));
+    driver
+        .try_push(Operation::new(&mut read, 0))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    other_read = Read::new(fd, Vec::with_capacity(8));
+    driver
+        .try_push(Operation::new(&mut other_read, 0))
+        .unwrap_or_else(|_| panic!("queue is full"));
+    let mut entries = ArrayVec::<Entry, 2>::new();
+    unsafe { driver.submit(Some(Duration::ZERO), &mut entries) }.unwrap();
+    assert!(entries.is_empty());
+
+    driver.shutdown(Some(Duration::from_secs(1))).unwrap();
+    drop(driver);
+}
+
+#[test]
+fn msg_ring_counter() {
+    use std::{sync::mpsc, thread};
+
+    use completeio::op::MsgRing;
+
+    // the user data of the posted counter
+    const COUNTER: usize = 0;
+
+    // post the counters from `msgs` in turn with the received ones
+    fn pass_counter<'a>(
+        driver: &mut Driver<'a>,
+        msgs: &'a mut [MsgRing],
+        mut send_next: bool,
+    ) -> Vec<usize> {
+        let rounds = msgs.len();
+        let mut msgs = msgs.iter_mut();
+        let mut sent = 0;
+        let mut received = Vec::new();
+        let mut entries = ArrayVec::<Entry, 2>::new();
+        while sent < rounds || received.len() < rounds {
+            if send_next {
+                if let Some(msg) = msgs.next() {
+                    driver
+                        .try_push(Operation::new(msg, 1))
+                        .unwrap_or_else(|_| panic!("queue is full"));
+                }
+                send_next = false;
+            }
+            unsafe { driver.submit(Some(Duration::from_millis(10)), &mut entries) }.unwrap();
+            for entry in entries.drain(..) {
+                if entry.user_data() == COUNTER {
+                    received.push(entry.into_result().unwrap());
+                    send_next = true;
+                } else {
+                    assert_eq!(entry.into_result().unwrap(), 0);
+                    sent += 1;
+                }
+            }
+        }
+        received
+    }
+
+    let (handle_tx, handle_rx) = mpsc::channel();
+    let (peer_tx, peer_rx) = mpsc::channel();
+    let peer_thread = thread::spawn(move || {
+        let mut msgs;
+        let mut driver = Driver::new().unwrap();
+        handle_tx.send(driver.handle().unwrap()).unwrap();
+        let peer = peer_rx.recv().unwrap();
+        msgs = [2, 4, 6, 8, 10].map(|counter| MsgRing::new(&peer, COUNTER, counter));
+        pass_counter(&mut driver, &mut msgs, false)
+    });
+
+    let peer = handle_rx.recv().unwrap();
+    let mut msgs = [1, 3, 5, 7, 9].map(|counter| MsgRing::new(&peer, COUNTER, counter));
+    let mut driver = Driver::new().unwrap();
+    peer_tx.send(driver.handle().unwrap()).unwrap();
+    assert_eq!(pass_counter(&mut driver, &mut msgs, true), [2, 4, 6, 8, 10]);
+    assert_eq!(peer_thread.join().unwrap(), [1, 3, 5, 7, 9]);
+}
+
+#[test]
+#[cfg(not(any(target_os = "netbsd", target_os = "openbsd")))]
+fn waker_interrupts_wait() {
+    use std::{thread, time::Instant};
+
+    let mut driver = Driver::new().unwrap();
+    let waker = driver.waker().unwrap();
+    let waker_thread = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        waker.wake().unwrap();
+    });
+
+    let mut entries = ArrayVec::<Entry, 1>::new();
+    let started = Instant::now();
+    unsafe { driver.submit(Some(Duration::from_secs(10)), &mut entries) }.unwrap();
+    assert!(entries.is_empty());
+    assert!(started.elapsed() < Duration::from_secs(5));
+    waker_thread.join().unwrap();
+
+    // the coalesced wakeups interrupt the next wait once, IOCP queues every posted packet
+    #[cfg(unix)]
+    {
+        let waker = driver.waker().unwrap();
+        waker.wake().unwrap();
+        waker.clone().wake().unwrap();
+        unsafe { driver.submit(Some(Duration::from_secs(10)), &mut entries) }.unwrap();
+        assert!(entries.is_empty());
+        let started = Instant::now();
+        unsafe { driver.submit(Some(Duration::from_millis(100)), &mut entries) }.unwrap();
+        assert!(entries.is_empty());
+        assert!(started.elapsed() >= Duration::from_millis(50));
+    }
+}
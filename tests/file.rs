@@ -1,6 +1,9 @@
 use std::io::prelude::*;
 
-use completeio::fs::File;
+use completeio::{
+    buf::{IntoInner, VectoredBufWrapper},
+    fs::{File, OpenOptions},
+};
 use tempfile::NamedTempFile;
 
 const HELLO: &[u8] = b"hello world...";
@@ -25,6 +28,127 @@ fn basic_read() {
     });
 }
 
+#[test]
+fn async_open_read() {
+    completeio::task::block_on(async {
+        let mut tempfile = tempfile();
+        tempfile.write_all(HELLO).unwrap();
+
+        let file = File::open_async(tempfile.path()).await.unwrap();
+        read_hello(&file).await;
+    });
+}
+
+#[test]
+fn async_open_not_found() {
+    completeio::task::block_on(async {
+        let err = File::open_async("file-does-not-exist").await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    });
+}
+
+#[test]
+fn open_options_create_new() {
+    completeio::task::block_on(async {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("new");
+        let options = OpenOptions::new().write(true).create_new(true);
+
+        let file = options.clone().open_async(&path).await.unwrap();
+        file.write_all_at(0, HELLO).await.0.unwrap();
+        let err = options.clone().open_async(&path).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+        let err = options.open(&path).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+        assert_eq!(std::fs::read(&path).unwrap(), HELLO);
+    });
+}
+
+#[test]
+fn open_options_truncate() {
+    completeio::task::block_on(async {
+        let mut tempfile = tempfile();
+        tempfile.write_all(HELLO).unwrap();
+
+        // the file is kept without truncate
+        let file = OpenOptions::new()
+            .write(true)
+            .open_async(tempfile.path())
+            .await
+            .unwrap();
+        file.write_all_at(0, &b"HELLO"[..]).await.0.unwrap();
+        assert_eq!(std::fs::read(tempfile.path()).unwrap(), b"HELLO world...");
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .truncate(true)
+            .open_async(tempfile.path())
+            .await
+            .unwrap();
+        assert_eq!(file.metadata().unwrap().len(), 0);
+        file.write_all_at(0, HELLO).await.0.unwrap();
+        read_hello(&file).await;
+
+        // truncate requires write access
+        let err = OpenOptions::new()
+            .read(true)
+            .truncate(true)
+            .open_async(tempfile.path())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    });
+}
+
+#[test]
+fn open_options_append() {
+    completeio::task::block_on(async {
+        let tempfile = tempfile();
+        let options = OpenOptions::new().write(true).append(true);
+        let err = options.clone().open(tempfile.path()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+        let err = options.open_async(tempfile.path()).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn open_options_mode() {
+    use std::os::unix::fs::PermissionsExt;
+
+    completeio::task::block_on(async {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mode");
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .mode(0o600)
+            .custom_flags(libc::O_NOFOLLOW)
+            .open_async(&path)
+            .await
+            .unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    });
+}
+
+#[test]
+fn close_and_read_clone() {
+    completeio::task::block_on(async {
+        let mut tempfile = tempfile();
+        tempfile.write_all(HELLO).unwrap();
+
+        let file = File::open(tempfile.path()).unwrap();
+        read_hello(&file).await;
+
+        let cloned = file.try_clone().unwrap();
+        file.close().await.unwrap();
+        read_hello(&cloned).await;
+    });
+}
+
 #[test]
 fn basic_write() {
     completeio::task::block_on(async {
@@ -32,7 +156,7 @@ fn basic_write() {
 
         let file = File::create(tempfile.path()).unwrap();
 
-        file.write_all_at(HELLO, 0).await.0.unwrap();
+        file.write_all_at(0, HELLO).await.0.unwrap();
         file.sync_all().await.unwrap();
 
         let file = std::fs::read(tempfile.path()).unwrap();
@@ -40,6 +164,487 @@ fn basic_write() {
     });
 }
 
+#[test]
+fn sync_range() {
+    completeio::task::block_on(async {
+        let tempfile = tempfile();
+        let file = File::create(tempfile.path()).unwrap();
+
+        file.write_all_at(0, HELLO).await.0.unwrap();
+        file.sync_range(0, HELLO.len() as u64).await.unwrap();
+        // the whole file
+        file.sync_range(0, u64::MAX).await.unwrap();
+
+        let file = std::fs::read(tempfile.path()).unwrap();
+        assert_eq!(file, HELLO);
+    });
+}
+
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+#[test]
+fn copy_range() {
+    completeio::task::block_on(async {
+        let mut src_tempfile = tempfile();
+        src_tempfile.write_all(HELLO).unwrap();
+        let dst_tempfile = tempfile();
+
+        let src = File::open(src_tempfile.path()).unwrap();
+        let dst = File::create(dst_tempfile.path()).unwrap();
+
+        let mut copied = 0;
+        while copied < HELLO.len() {
+            let n = completeio::fs::copy_range(
+                &src,
+                copied as u64,
+                &dst,
+                copied as u64,
+                HELLO.len() - copied,
+            )
+            .await
+            .unwrap();
+            assert!(n > 0);
+            copied += n;
+        }
+
+        let file = std::fs::read(dst_tempfile.path()).unwrap();
+        assert_eq!(file, HELLO);
+    });
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn copy_range_is_capped() {
+    const LEN: u64 = 1 << 25;
+
+    completeio::task::block_on(async {
+        let src_tempfile = tempfile();
+        src_tempfile.as_file().set_len(LEN).unwrap();
+        let dst_tempfile = tempfile();
+
+        let src = File::open(src_tempfile.path()).unwrap();
+        let dst = File::create(dst_tempfile.path()).unwrap();
+        let copied = completeio::fs::copy_range(&src, 0, &dst, 0, LEN as usize)
+            .await
+            .unwrap();
+        assert!(copied > 0 && copied <= 1 << 24, "copied {copied} bytes");
+    });
+}
+
+#[cfg(any(target_os = "linux", target_vendor = "apple", target_os = "windows"))]
+#[test]
+fn allocate() {
+    use completeio::op::AllocateMode;
+
+    const LEN: u64 = 1 << 20;
+
+    completeio::task::block_on(async {
+        let tempfile = tempfile();
+        let file = File::create(tempfile.path()).unwrap();
+
+        file.allocate_with_mode(0, LEN, AllocateMode::KEEP_SIZE)
+            .await
+            .unwrap();
+        let metadata = file.metadata().unwrap();
+        assert_eq!(metadata.len(), 0);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            assert!(metadata.blocks() * 512 >= LEN);
+        }
+
+        file.allocate(0, LEN).await.unwrap();
+        assert_eq!(file.metadata().unwrap().len(), LEN);
+
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::unix::fs::MetadataExt;
+
+            file.allocate_with_mode(0, LEN, AllocateMode::PUNCH_HOLE)
+                .await
+                .unwrap();
+            let metadata = file.metadata().unwrap();
+            assert_eq!(metadata.len(), LEN);
+            assert!(metadata.blocks() * 512 < LEN);
+        }
+    });
+}
+
+#[test]
+fn set_len() {
+    completeio::task::block_on(async {
+        let tempfile = tempfile();
+        let file = File::create(tempfile.path()).unwrap();
+
+        file.write_all_at(0, HELLO).await.0.unwrap();
+        file.set_len(5).await.unwrap();
+        assert_eq!(std::fs::read(tempfile.path()).unwrap(), &HELLO[..5]);
+
+        assert_eq!(file.metadata().unwrap().len(), 5);
+
+        // the extended part reads as zeros
+        file.set_len(8).await.unwrap();
+        assert_eq!(file.metadata().unwrap().len(), 8);
+        assert_eq!(std::fs::read(tempfile.path()).unwrap(), b"hello\0\0\0");
+    });
+}
+
+#[test]
+fn metadata_async() {
+    completeio::task::block_on(async {
+        let mut tempfile = tempfile();
+        tempfile.write_all(HELLO).unwrap();
+
+        let file = File::open(tempfile.path()).unwrap();
+        let metadata = file.metadata_async().await.unwrap();
+        let std_metadata = file.metadata().unwrap();
+        assert_eq!(metadata.len(), HELLO.len() as u64);
+        assert!(metadata.is_file());
+        assert_eq!(
+            metadata.modified().unwrap(),
+            std_metadata.modified().unwrap()
+        );
+        assert_eq!(metadata.permissions(), std_metadata.permissions());
+
+        let metadata = completeio::fs::metadata(tempfile.path()).await.unwrap();
+        assert_eq!(metadata.len(), HELLO.len() as u64);
+
+        let dir = tempfile.path().parent().unwrap();
+        let metadata = completeio::fs::metadata(dir).await.unwrap();
+        assert!(metadata.is_dir());
+
+        let err = completeio::fs::metadata("file-does-not-exist")
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    });
+}
+
+#[test]
+fn read_to_end_grows_buffer() {
+    completeio::task::block_on(async {
+        let data = HELLO.repeat(1 << 12);
+        let mut tempfile = tempfile();
+        tempfile.write_all(&data).unwrap();
+
+        let file = File::open(tempfile.path()).unwrap();
+        let (res, buf) = file.read_to_end_at(Vec::new(), 0).await;
+        assert_eq!(res.unwrap(), data.len());
+        assert_eq!(buf, data);
+
+        // the read data is appended to the initialized part
+        let (res, buf) = file.read_to_end_at(b"prefix".to_vec(), HELLO.len()).await;
+        assert_eq!(res.unwrap(), data.len() - HELLO.len());
+        assert_eq!(&buf[..6], b"prefix");
+        assert_eq!(&buf[6..], &data[HELLO.len()..]);
+
+        // the offset past the end reads nothing
+        let (res, buf) = file
+            .read_to_end_at(Vec::with_capacity(8), data.len() + 1)
+            .await;
+        assert_eq!(res.unwrap(), 0);
+        assert!(buf.is_empty());
+    });
+}
+
+#[test]
+fn read_exact_short_file() {
+    completeio::task::block_on(async {
+        let mut tempfile = tempfile();
+        tempfile.write_all(HELLO).unwrap();
+
+        let file = File::open(tempfile.path()).unwrap();
+        let (res, buf) = file.read_exact_at(Vec::with_capacity(5), 6).await;
+        assert_eq!(res.unwrap(), 5);
+        assert_eq!(buf, b"world");
+
+        let (res, _) = file
+            .read_exact_at(Vec::with_capacity(HELLO.len() + 1), 0)
+            .await;
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::UnexpectedEof);
+    });
+}
+
+fn vectored(buffers: &[&[u8]]) -> VectoredBufWrapper<'static, Vec<u8>> {
+    let buffers: Vec<Vec<u8>> = buffers.iter().map(|buf| buf.to_vec()).collect();
+    VectoredBufWrapper::from(buffers.into_boxed_slice())
+}
+
+#[test]
+fn vectored_at() {
+    completeio::task::block_on(async {
+        let tempfile = tempfile();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(tempfile.path())
+            .unwrap();
+
+        let (res, _) = file
+            .write_all_vectored_at(vectored(&[b"hello", b"", b" world..."]), 0)
+            .await;
+        assert_eq!(res.unwrap(), HELLO.len());
+
+        let buffers = vec![Vec::with_capacity(3), Vec::new(), Vec::with_capacity(16)];
+        let buffer = VectoredBufWrapper::from(buffers.into_boxed_slice());
+        let (res, buffer) = file.read_vectored_at(buffer, 0).await;
+        let read = res.unwrap();
+        assert!(read > 0);
+        let buffers = buffer.into_inner();
+        assert_eq!(buffers.concat(), &HELLO[..read]);
+        assert!(buffers[0].len() == 3 || read < 3);
+        assert!(buffers[1].is_empty());
+    });
+}
+
+#[test]
+fn write_all_vectored_at_resumes_mid_buffer() {
+    completeio::task::block_on(async {
+        let tempfile = tempfile();
+        let file = OpenOptions::new()
+            .write(true)
+            .open(tempfile.path())
+            .unwrap();
+
+        let mut buffer = vectored(&[b"hello", b"", b" world", b"..."]);
+        assert_eq!(buffer.remaining(), HELLO.len());
+        // a short write stopped in the middle of the first buffer
+        file.write_all_at(0, b"hel".to_vec()).await.0.unwrap();
+        buffer.advance(3);
+        assert_eq!(buffer.remaining(), HELLO.len() - 3);
+        let (res, mut buffer) = file.write_all_vectored_at(buffer, 3).await;
+        assert_eq!(res.unwrap(), HELLO.len() - 3);
+        assert_eq!(buffer.remaining(), HELLO.len());
+
+        // a short write stopped at the end of the buffer before the empty one
+        let pos = HELLO.len();
+        file.write_all_at(pos as u64, b"hello".to_vec())
+            .await
+            .0
+            .unwrap();
+        buffer.advance(5);
+        let (res, mut buffer) = file.write_all_vectored_at(buffer, pos + 5).await;
+        assert_eq!(res.unwrap(), HELLO.len() - 5);
+
+        // nothing is left after the whole buffers are skipped
+        buffer.advance(HELLO.len());
+        assert_eq!(buffer.remaining(), 0);
+        let (res, buffer) = file.write_all_vectored_at(buffer, 0).await;
+        assert_eq!(res.unwrap(), 0);
+        assert_eq!(buffer.remaining(), HELLO.len());
+
+        assert_eq!(std::fs::read(tempfile.path()).unwrap(), HELLO.repeat(2));
+    });
+}
+
+// A blocking pipe of the minimal capacity with the reader draining it after a delay. The first
+// write fills the pipe and returns a short count, the next one blocks till the pipe is drained.
+#[cfg(target_os = "linux")]
+fn short_write_pipe() -> (File, usize, std::thread::JoinHandle<Vec<u8>>) {
+    use completeio::{
+        driver::{AsRawFd, FromRawFd, IntoRawFd},
+        pipe::pipe,
+    };
+
+    let (reader, writer) = pipe().unwrap();
+    // SAFETY: the ends are moved out of the pipe
+    let mut reader = unsafe { std::fs::File::from_raw_fd(reader.into_raw_fd()) };
+    let writer = unsafe { File::from_raw_fd(writer.into_raw_fd()) };
+    let capacity = unsafe { libc::fcntl(writer.as_raw_fd(), libc::F_SETPIPE_SZ, 4096) };
+    assert!(capacity > 0);
+    let thread = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).unwrap();
+        data
+    });
+    (writer, capacity as usize, thread)
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn write_all_at_resumes_short_pipe_write() {
+    let (writer, capacity, thread) = short_write_pipe();
+    let data = (0..capacity * 2).map(|i| i as u8).collect::<Vec<_>>();
+    completeio::task::block_on(async {
+        let (res, buffer) = writer.write_all_at(0, data.clone()).await;
+        res.unwrap();
+        assert_eq!(buffer, data);
+    });
+    drop(writer);
+    assert_eq!(thread.join().unwrap(), data);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn write_all_vectored_at_resumes_short_pipe_write() {
+    let (writer, capacity, thread) = short_write_pipe();
+    // the short write stops in the middle of the second buffer
+    let buffers = [b'a', b'b', b'c'].map(|byte| vec![byte; capacity * 2 / 3]);
+    let expected = buffers.concat();
+    completeio::task::block_on(async {
+        let buffer = VectoredBufWrapper::from(buffers.to_vec().into_boxed_slice());
+        let (res, buffer) = writer.write_all_vectored_at(buffer, 0).await;
+        assert_eq!(res.unwrap(), expected.len());
+        assert_eq!(buffer.remaining(), expected.len());
+    });
+    drop(writer);
+    assert_eq!(thread.join().unwrap(), expected);
+}
+
+#[test]
+#[should_panic = "advancing past the end of the buffers"]
+fn advance_past_end() {
+    vectored(&[b"hello", b"world"]).advance(11);
+}
+
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+#[test]
+fn advise_dontneed() {
+    use completeio::op::Advice;
+
+    completeio::task::block_on(async {
+        let data = HELLO.repeat(1 << 16);
+        let mut tempfile = tempfile();
+        tempfile.write_all(&data).unwrap();
+
+        let file = File::open(tempfile.path()).unwrap();
+        file.advise(0, 0, Advice::Sequential).await.unwrap();
+        let (res, buf) = file.read_to_end_at(Vec::with_capacity(data.len()), 0).await;
+        assert_eq!(res.unwrap(), data.len());
+        assert_eq!(buf, data);
+
+        file.advise(0, 0, Advice::DontNeed).await.unwrap();
+        let (res, buf) = file.read_to_end_at(Vec::with_capacity(data.len()), 0).await;
+        assert_eq!(res.unwrap(), data.len());
+        assert_eq!(buf, data);
+    });
+}
+
+#[test]
+fn create_dir_all() {
+    completeio::task::block_on(async {
+        let tempdir = tempfile::tempdir_in(".").unwrap();
+        // relative path
+        let dir = tempdir
+            .path()
+            .strip_prefix(std::env::current_dir().unwrap())
+            .unwrap();
+
+        completeio::fs::create_dir_all(dir.join("a/b/c"))
+            .await
+            .unwrap();
+        assert!(dir.join("a/b/c").is_dir());
+        // existing directories are skipped
+        completeio::fs::create_dir_all(dir.join("a/b/d"))
+            .await
+            .unwrap();
+        assert!(dir.join("a/b/d").is_dir());
+
+        let err = completeio::fs::create_dir(dir.join("a/b"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+
+        // a component exists as a file
+        std::fs::write(dir.join("a/file"), HELLO).unwrap();
+        let err = completeio::fs::create_dir_all(dir.join("a/file"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+        completeio::fs::create_dir_all(dir.join("a/file/e"))
+            .await
+            .unwrap_err();
+    });
+}
+
+#[test]
+fn remove_file() {
+    completeio::task::block_on(async {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+        std::fs::write(&path, HELLO).unwrap();
+
+        completeio::fs::remove_file(&path).await.unwrap();
+        assert!(!path.exists());
+
+        let err = completeio::fs::remove_file(&path).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    });
+}
+
+#[test]
+fn rename() {
+    completeio::task::block_on(async {
+        let dir = tempfile::tempdir().unwrap();
+        let from = dir.path().join("from");
+        let to = dir.path().join("to");
+        std::fs::write(&from, HELLO).unwrap();
+        std::fs::write(&to, b"replaced").unwrap();
+
+        completeio::fs::rename(&from, &to).await.unwrap();
+        assert!(!from.exists());
+        assert_eq!(std::fs::read(&to).unwrap(), HELLO);
+    });
+}
+
+#[cfg(any(target_os = "linux", target_vendor = "apple", target_os = "windows"))]
+#[test]
+fn rename_noreplace() {
+    use completeio::op::RenameFlags;
+
+    completeio::task::block_on(async {
+        let dir = tempfile::tempdir().unwrap();
+        let from = dir.path().join("from");
+        let to = dir.path().join("to");
+        std::fs::write(&from, HELLO).unwrap();
+        std::fs::write(&to, b"kept").unwrap();
+
+        let err = completeio::fs::rename_with_flags(&from, &to, RenameFlags::NOREPLACE)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+        assert_eq!(std::fs::read(&to).unwrap(), b"kept");
+
+        std::fs::remove_file(&to).unwrap();
+        completeio::fs::rename_with_flags(&from, &to, RenameFlags::NOREPLACE)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&to).unwrap(), HELLO);
+    });
+}
+
+#[test]
+fn symlink_and_hard_link() {
+    completeio::task::block_on(async {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target");
+        std::fs::write(&target, HELLO).unwrap();
+
+        let link = dir.path().join("link");
+        completeio::fs::symlink(&target, &link).await.unwrap();
+        assert!(std::fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+
+        // read through the link
+        let file = File::open(&link).unwrap();
+        read_hello(&file).await;
+
+        let hard_link = dir.path().join("hard_link");
+        completeio::fs::hard_link(&target, &hard_link)
+            .await
+            .unwrap();
+        std::fs::remove_file(&target).unwrap();
+        assert_eq!(std::fs::read(&hard_link).unwrap(), HELLO);
+
+        let err = completeio::fs::hard_link(&target, dir.path().join("dangling"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    });
+}
+
 #[test]
 fn cancel_read() {
     completeio::task::block_on(async {
@@ -64,13 +669,41 @@ fn drop_open() {
         // Do something else
         let file = File::create(tempfile.path()).unwrap();
 
-        file.write_all_at(HELLO, 0).await.0.unwrap();
+        file.write_all_at(0, HELLO).await.0.unwrap();
 
         let file = std::fs::read(tempfile.path()).unwrap();
         assert_eq!(file, HELLO);
     });
 }
 
+#[cfg(target_os = "linux")]
+#[test]
+fn xattr() {
+    const NAME: &str = "user.completeio";
+    const VALUE: &[u8] = b"tagged";
+
+    completeio::task::block_on(async {
+        let tempfile = tempfile();
+        let file = File::open(tempfile.path()).unwrap();
+
+        let (res, _) = file.set_xattr(NAME, VALUE).await;
+        match res {
+            Ok(()) => {}
+            // the file system doesn't support user attributes
+            Err(e) if e.raw_os_error() == Some(libc::EOPNOTSUPP) => return,
+            Err(e) => panic!("{e}"),
+        }
+
+        let (res, buf) = file.get_xattr(NAME, Vec::with_capacity(2)).await;
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::ERANGE));
+        assert!(buf.is_empty());
+
+        let (res, buf) = file.get_xattr(NAME, Vec::with_capacity(16)).await;
+        assert_eq!(res.unwrap(), VALUE.len());
+        assert_eq!(buf, VALUE);
+    });
+}
+
 fn tempfile() -> NamedTempFile {
     NamedTempFile::new().unwrap()
 }
@@ -0,0 +1,106 @@
+#![cfg(target_os = "linux")]
+
+use std::{
+    sync::atomic::{AtomicU32, Ordering},
+    thread,
+    time::Duration,
+};
+
+use completeio::{
+    futex::{wait_async, wake_async},
+    op::FutexWait,
+};
+
+// the kernel support is known after the driver of the runtime is created
+fn skip_unsupported() -> bool {
+    if FutexWait::is_supported() {
+        return false;
+    }
+    eprintln!("skipped: futex operations are available since Linux 6.7");
+    true
+}
+
+fn futex_wait(futex: &AtomicU32, val: u32) {
+    unsafe {
+        libc::syscall(
+            libc::SYS_futex,
+            futex.as_ptr(),
+            libc::FUTEX_WAIT | libc::FUTEX_PRIVATE_FLAG,
+            val,
+            std::ptr::null::<libc::timespec>(),
+        )
+    };
+}
+
+#[test]
+fn wait_woken_by_thread() {
+    static FUTEX: AtomicU32 = AtomicU32::new(0);
+
+    completeio::task::block_on(async {
+        if skip_unsupported() {
+            return;
+        }
+        let waker = thread::spawn(|| {
+            thread::sleep(Duration::from_millis(10));
+            FUTEX.store(1, Ordering::Release);
+            unsafe {
+                libc::syscall(
+                    libc::SYS_futex,
+                    FUTEX.as_ptr(),
+                    libc::FUTEX_WAKE | libc::FUTEX_PRIVATE_FLAG,
+                    i32::MAX,
+                )
+            };
+        });
+        while FUTEX.load(Ordering::Acquire) == 0 {
+            wait_async(&FUTEX, 0).await.unwrap();
+        }
+        waker.join().unwrap();
+    });
+}
+
+#[test]
+fn wake_parked_thread() {
+    static FUTEX: AtomicU32 = AtomicU32::new(0);
+
+    completeio::task::block_on(async {
+        if skip_unsupported() {
+            return;
+        }
+        let waiter = thread::spawn(|| {
+            while FUTEX.load(Ordering::Acquire) == 0 {
+                futex_wait(&FUTEX, 0);
+            }
+        });
+        // the waiter parks again after the wake till the value changes
+        while wake_async(&FUTEX, 1).await.unwrap() == 0 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        FUTEX.store(1, Ordering::Release);
+        wake_async(&FUTEX, u32::MAX).await.unwrap();
+        waiter.join().unwrap();
+    });
+}
+
+#[test]
+fn wait_woken_by_task() {
+    static FUTEX: AtomicU32 = AtomicU32::new(0);
+
+    completeio::task::block_on(async {
+        if skip_unsupported() {
+            return;
+        }
+        let waiter = completeio::task::spawn(async {
+            while FUTEX.load(Ordering::Acquire) == 0 {
+                wait_async(&FUTEX, 0).await.unwrap();
+            }
+        });
+        // the waiting task submits the wait when the runtime polls it
+        while wake_async(&FUTEX, 1).await.unwrap() == 0 {
+            completeio::task::yield_now().await;
+        }
+        FUTEX.store(1, Ordering::Release);
+        wake_async(&FUTEX, u32::MAX).await.unwrap();
+        waiter.await.unwrap();
+    });
+}
@@ -0,0 +1,43 @@
+use std::{io, time::Duration};
+
+use completeio::{net::RawSocket, time::timeout};
+use socket2::{Domain, Protocol, Type};
+
+#[test]
+fn echo_localhost() {
+    completeio::task::block_on(async {
+        let socket = match RawSocket::new(Domain::IPV4, Protocol::ICMPV4) {
+            Ok(socket) => socket,
+            // without CAP_NET_RAW or the ping group
+            Err(e) if e.kind() == io::ErrorKind::PermissionDenied => return,
+            Err(e) => panic!("{e}"),
+        };
+        let ty = socket.socket_type().unwrap();
+        assert!(ty == Type::RAW || ty == Type::DGRAM);
+
+        // echo request with the identifier and sequence number 0x1234
+        let request = vec![8, 0, 0xd3, 0x97, 0x12, 0x34, 0x12, 0x34];
+        let (res, _) = socket.send_to(request, "127.0.0.1:0").await;
+        assert_eq!(res.unwrap(), 8);
+
+        let reply = timeout(Duration::from_secs(5), async {
+            loop {
+                let (res, packet) = socket.recv_from(Vec::with_capacity(128)).await;
+                let (len, addr) = res.unwrap();
+                assert_eq!(addr.as_socket_ipv4().unwrap().ip().octets(), [127, 0, 0, 1]);
+                let offset = if ty == Type::RAW {
+                    usize::from(packet[0] & 0x0f) * 4
+                } else {
+                    0
+                };
+                // raw sockets also receive the request sent to the loopback
+                if len >= offset + 8 && packet[offset] == 0 {
+                    break packet[offset + 6..offset + 8].to_vec();
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(reply, [0x12, 0x34]);
+    })
+}
@@ -0,0 +1,77 @@
+#![cfg(unix)]
+
+use std::io::{Read, Write};
+
+use completeio::io::{readiness, splice, tee, Interest};
+
+const HELLO: &[u8] = b"hello world...";
+
+#[test]
+fn splice_pipes() {
+    completeio::task::block_on(async {
+        let (src, mut src_tx) = std::io::pipe().unwrap();
+        let (mut dst_rx, dst) = std::io::pipe().unwrap();
+        src_tx.write_all(HELLO).unwrap();
+
+        let moved = splice(&src, &dst, 4).await.unwrap();
+        assert_eq!(moved, 4);
+        let moved = splice(&src, &dst, 1024).await.unwrap();
+        assert_eq!(moved, HELLO.len() - 4);
+
+        let mut buf = [0; HELLO.len()];
+        dst_rx.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, HELLO);
+    });
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn tee_does_not_consume() {
+    completeio::task::block_on(async {
+        let (src, mut src_tx) = std::io::pipe().unwrap();
+        let (mut copy_rx, copy) = std::io::pipe().unwrap();
+        let (mut dst_rx, dst) = std::io::pipe().unwrap();
+        src_tx.write_all(HELLO).unwrap();
+
+        let duplicated = tee(&src, &copy, 1024).await.unwrap();
+        assert_eq!(duplicated, HELLO.len());
+        let moved = splice(&src, &dst, 1024).await.unwrap();
+        assert_eq!(moved, HELLO.len());
+
+        let mut buf = [0; HELLO.len()];
+        copy_rx.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, HELLO);
+        dst_rx.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, HELLO);
+    });
+}
+
+#[test]
+#[cfg(not(target_os = "linux"))]
+fn tee_unsupported() {
+    completeio::task::block_on(async {
+        let (src, _src_tx) = std::io::pipe().unwrap();
+        let (_copy_rx, copy) = std::io::pipe().unwrap();
+
+        let err = tee(&src, &copy, 1024).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+    });
+}
+
+#[test]
+fn readiness_of_pipe() {
+    completeio::task::block_on(async {
+        let (mut rx, mut tx) = std::io::pipe().unwrap();
+
+        let wait = completeio::task::spawn(async move {
+            readiness(&rx, Interest::Readable).await.unwrap();
+            let mut buf = [0; HELLO.len()];
+            rx.read_exact(&mut buf).unwrap();
+            buf
+        });
+        // the reader starts waiting while the writer waits for the readiness
+        readiness(&tx, Interest::Writable).await.unwrap();
+        tx.write_all(HELLO).unwrap();
+        assert_eq!(&wait.await.unwrap(), HELLO);
+    });
+}
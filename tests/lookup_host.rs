@@ -0,0 +1,56 @@
+use std::{io, net::SocketAddr};
+
+use completeio::net::{lookup_host, TcpListener, TcpStream};
+
+#[test]
+fn localhost() {
+    completeio::task::block_on(async {
+        let addrs = lookup_host("localhost:80")
+            .await
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert!(!addrs.is_empty());
+        assert!(addrs
+            .iter()
+            .all(|addr| addr.ip().is_loopback() && addr.port() == 80));
+
+        let addr = "[::1]:443".parse::<SocketAddr>().unwrap();
+        let addrs = lookup_host("[::1]:443").await.unwrap().collect::<Vec<_>>();
+        assert_eq!(addrs, [addr]);
+    })
+}
+
+#[test]
+fn invalid_tld() {
+    completeio::task::block_on(async {
+        let err = lookup_host("completeio.invalid:80").await.err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+        let err = lookup_host("localhost").await.err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    })
+}
+
+#[test]
+fn connect_by_name() {
+    completeio::task::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().as_socket().unwrap().port();
+        let (stream, _) =
+            futures_util::try_join!(TcpStream::connect(("localhost", port)), listener.accept())
+                .unwrap();
+        assert!(stream
+            .peer_addr()
+            .unwrap()
+            .as_socket()
+            .unwrap()
+            .ip()
+            .is_loopback());
+
+        let err = TcpStream::connect("completeio.invalid:80")
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    })
+}
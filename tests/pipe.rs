@@ -0,0 +1,28 @@
+use completeio::{
+    driver::{FromRawFd, IntoRawFd},
+    pipe::{pipe, PipeReader},
+};
+
+#[test]
+fn pipe_across_threads() {
+    let (reader, writer) = pipe().unwrap();
+    // the end is not attached yet, it's attached to the runtime of the thread
+    let reader = reader.into_raw_fd() as usize;
+    let thread = std::thread::spawn(move || {
+        // SAFETY: the end is moved to the thread
+        let reader = unsafe { PipeReader::from_raw_fd(reader as _) };
+        completeio::task::block_on(async {
+            let (res, buf) = reader.read_exact(Vec::with_capacity(11)).await;
+            res.unwrap();
+            assert_eq!(buf, b"hello world");
+            let (res, _) = reader.read(Vec::with_capacity(1)).await;
+            assert_eq!(res.unwrap(), 0);
+        })
+    });
+    completeio::task::block_on(async {
+        writer.write_all("hello ").await.0.unwrap();
+        writer.write_all("world").await.0.unwrap();
+    });
+    drop(writer);
+    thread.join().unwrap();
+}
@@ -0,0 +1,51 @@
+#![cfg(unix)]
+
+use std::process::Command;
+
+use completeio::process::Child;
+
+#[test]
+fn wait_exit_code() {
+    completeio::task::block_on(async {
+        let mut child = Child::from(Command::new("sh").args(["-c", "exit 3"]).spawn().unwrap());
+        let status = child.wait().await.unwrap();
+        assert_eq!(status.code(), Some(3));
+        // the collected status is cached
+        assert_eq!(child.wait().await.unwrap().code(), Some(3));
+    });
+}
+
+#[test]
+fn wait_killed() {
+    use std::os::unix::process::ExitStatusExt;
+
+    completeio::task::block_on(async {
+        let mut child = Child::from(Command::new("sleep").arg("10").spawn().unwrap());
+        child.kill().unwrap();
+        let status = child.wait().await.unwrap();
+        assert_eq!(status.signal(), Some(libc::SIGKILL));
+    });
+}
+
+#[test]
+fn wait_with_blocked_sigchld() {
+    // the wait doesn't rely on SIGCHLD delivery
+    unsafe {
+        let mut set = std::mem::zeroed();
+        libc::sigemptyset(&mut set);
+        libc::sigaddset(&mut set, libc::SIGCHLD);
+        assert_eq!(
+            libc::pthread_sigmask(libc::SIG_BLOCK, &set, std::ptr::null_mut()),
+            0
+        );
+    }
+    completeio::task::block_on(async {
+        let mut child = Child::from(
+            Command::new("sh")
+                .args(["-c", "sleep 0.05"])
+                .spawn()
+                .unwrap(),
+        );
+        assert!(child.wait().await.unwrap().success());
+    });
+}
@@ -68,6 +68,47 @@ fn try_clone() {
     });
 }
 
+#[test]
+fn send_zc() {
+    const CHUNK_LEN: usize = 1024 * 1024;
+    const CHUNKS: u8 = 4;
+
+    fn chunk(i: u8) -> Vec<u8> {
+        (0..CHUNK_LEN).map(|j| (j as u8).wrapping_add(i)).collect()
+    }
+
+    completeio::task::block_on(async {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let (tx, (rx, _)) =
+            futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+
+        let send = async {
+            let mut returned = Vec::new();
+            for i in 0..CHUNKS {
+                let buffer = chunk(i);
+                let ptr = buffer.as_ptr();
+                let (n, buffer) = tx.send_zc_all(buffer).await;
+                assert_eq!(n.unwrap(), CHUNK_LEN);
+                // the same buffer is handed back untouched
+                assert_eq!(buffer.as_ptr(), ptr);
+                assert_eq!(buffer, chunk(i));
+                returned.push(i);
+            }
+            returned
+        };
+        let recv = rx.recv_exact(Vec::with_capacity(CHUNKS as usize * CHUNK_LEN));
+        let (returned, (n, buffer)) = futures_util::join!(send, recv);
+
+        assert_eq!(returned, (0..CHUNKS).collect::<Vec<_>>());
+        assert_eq!(n.unwrap(), buffer.len());
+        for (i, received) in buffer.chunks(CHUNK_LEN).enumerate() {
+            assert_eq!(received, chunk(i as u8));
+        }
+    });
+}
+
 #[test]
 fn drop_on_complete() {
     use std::sync::Arc;
@@ -145,6 +186,628 @@ fn too_many_submissions() {
     });
 }
 
+#[test]
+fn dropped_ops_release_buffers() {
+    use std::{
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+
+    use completeio::net::UdpSocket;
+
+    struct CountedBuf {
+        data: Vec<u8>,
+        _ref_cnt: Arc<()>,
+    }
+
+    unsafe impl IoBuf<'static> for CountedBuf {
+        fn as_buf_ptr(&self) -> *const u8 {
+            self.data.as_buf_ptr()
+        }
+
+        fn buf_len(&self) -> usize {
+            self.data.buf_len()
+        }
+
+        fn buf_capacity(&self) -> usize {
+            self.data.buf_capacity()
+        }
+    }
+
+    unsafe impl IoBufMut<'static> for CountedBuf {
+        fn as_buf_mut_ptr(&mut self) -> *mut u8 {
+            self.data.as_buf_mut_ptr()
+        }
+
+        fn set_buf_init(&mut self, pos: usize) {
+            self.data.set_buf_init(pos);
+        }
+    }
+
+    let ref_cnt = Arc::new(());
+    completeio::task::block_on(async {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = socket.local_addr().unwrap();
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        for round in 0..4 {
+            // more receives than the queue entries, some of them complete before the drop
+            for i in 0..4096 {
+                if i % 64 == round {
+                    sender.send_to("hello", &addr).await.0.unwrap();
+                }
+                let buffer = CountedBuf {
+                    data: Vec::with_capacity(16),
+                    _ref_cnt: ref_cnt.clone(),
+                };
+                if i % 2 == 0 {
+                    poll_once(socket.recv(buffer)).await;
+                } else {
+                    futures_util::select! {
+                        _ = futures_util::FutureExt::fuse(socket.recv(buffer)) => {}
+                        default => {}
+                    }
+                }
+            }
+        }
+
+        // the canceled operations complete and release the buffers
+        let start = Instant::now();
+        while Arc::strong_count(&ref_cnt) > 1 {
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "{} buffers are not released",
+                Arc::strong_count(&ref_cnt) - 1
+            );
+            completeio::time::sleep(Duration::from_millis(1)).await;
+        }
+
+        // the socket still receives
+        sender.send_to("world", &addr).await.0.unwrap();
+        let (res, buffer) = socket.recv(Vec::with_capacity(16)).await;
+        res.unwrap();
+        assert!(buffer == b"hello" || buffer == b"world");
+    });
+}
+
+#[test]
+fn join_handle() {
+    use std::{cell::Cell, rc::Rc};
+
+    struct DropFlag(Rc<Cell<bool>>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.set(true);
+        }
+    }
+
+    completeio::task::block_on(async {
+        // the output of the task
+        let task = completeio::task::spawn(async { 42 });
+        assert_eq!(task.await.unwrap(), 42);
+
+        // the panic of the task
+        let task = completeio::task::spawn(async {
+            if true {
+                panic!("boom");
+            }
+        });
+        let err = task.await.unwrap_err();
+        assert!(err.is_panic());
+        assert!(!err.is_cancelled());
+        assert_eq!(*err.into_panic().downcast::<&str>().unwrap(), "boom");
+
+        // the aborted task is dropped at its await point
+        let dropped = Rc::new(Cell::new(false));
+        let flag = DropFlag(dropped.clone());
+        let (started_tx, started_rx) = futures_channel::oneshot::channel();
+        let (tx, rx) = futures_channel::oneshot::channel::<()>();
+        let task = completeio::task::spawn(async move {
+            let _flag = flag;
+            started_tx.send(()).unwrap();
+            rx.await.unwrap_err();
+        });
+        started_rx.await.unwrap();
+        assert!(!task.is_finished());
+        task.abort();
+        let err = task.await.unwrap_err();
+        assert!(err.is_cancelled());
+        assert!(err.try_into_panic().is_err());
+        assert!(dropped.get());
+        drop(tx);
+
+        // the abort of the completed task has no effect
+        let (tx, rx) = futures_channel::oneshot::channel();
+        let task = completeio::task::spawn(async move {
+            tx.send(()).unwrap();
+            42
+        });
+        rx.await.unwrap();
+        assert!(task.is_finished());
+        task.abort();
+        assert_eq!(task.await.unwrap(), 42);
+
+        // the dropped handle detaches the task
+        let (tx, rx) = futures_channel::oneshot::channel();
+        drop(completeio::task::spawn(async move {
+            tx.send(42).unwrap();
+        }));
+        assert_eq!(rx.await.unwrap(), 42);
+    });
+}
+
+#[test]
+fn spawn_blocking() {
+    use std::sync::{Arc, Barrier};
+
+    completeio::task::block_on(async {
+        let task =
+            completeio::task::spawn_blocking(|| std::thread::current().name().map(String::from));
+        assert_eq!(task.await.unwrap().as_deref(), Some("completeio-blocking"));
+
+        let task = completeio::task::spawn_blocking(|| {
+            if true {
+                panic!("boom");
+            }
+        });
+        assert!(task.await.unwrap_err().is_panic());
+
+        // the pool spawns another thread for the closure waiting on the busy one
+        let barrier = Arc::new(Barrier::new(2));
+        let tasks = (0..2)
+            .map(|_| {
+                let barrier = barrier.clone();
+                completeio::task::spawn_blocking(move || barrier.wait().is_leader())
+            })
+            .collect::<Vec<_>>();
+        let leaders = futures_util::future::try_join_all(tasks).await.unwrap();
+        assert_eq!(leaders.iter().filter(|leader| **leader).count(), 1);
+    });
+}
+
+#[test]
+fn spawn_blocking_shutdown() {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            mpsc, Arc,
+        },
+        time::Duration,
+    };
+
+    use completeio::task::Builder;
+
+    let runtime = Builder::new_multi_thread()
+        .worker_threads(1)
+        .max_blocking_threads(1)
+        .build()
+        .unwrap();
+    let running_done = Arc::new(AtomicBool::new(false));
+    let queued_run = Arc::new(AtomicBool::new(false));
+    let (started_tx, started_rx) = mpsc::channel();
+    let (running, queued) = {
+        let running_done = running_done.clone();
+        let queued_run = queued_run.clone();
+        completeio::task::block_on(runtime.spawn(move || async move {
+            let running = completeio::task::spawn_blocking(move || {
+                started_tx.send(()).unwrap();
+                std::thread::sleep(Duration::from_millis(100));
+                running_done.store(true, Ordering::Release);
+            });
+            // the only thread of the pool is busy
+            let queued = completeio::task::spawn_blocking(move || {
+                queued_run.store(true, Ordering::Release);
+            });
+            (running, queued)
+        }))
+        .unwrap()
+    };
+    started_rx.recv().unwrap();
+    // the shutdown waits for the running closure and drops the queued one
+    runtime.shutdown();
+    assert!(running_done.load(Ordering::Acquire));
+    assert!(!queued_run.load(Ordering::Acquire));
+    completeio::task::block_on(async {
+        running.await.unwrap();
+        assert!(queued.await.unwrap_err().is_cancelled());
+    });
+}
+
+#[test]
+fn runtime_builder() {
+    use std::{io::ErrorKind, thread};
+
+    use completeio::task::RuntimeBuilder;
+
+    thread::spawn(|| {
+        let runtime = RuntimeBuilder::new()
+            .sq_entries(8)
+            .coop_taskrun(false)
+            .event_interval(1)
+            .max_tasks_per_poll(1)
+            .build()
+            .unwrap();
+        assert!(!runtime.capabilities().coop_taskrun);
+        let read = runtime.block_on(async {
+            // more operations than the queue entries
+            let tasks = (0..32)
+                .map(|_| {
+                    completeio::task::spawn(async {
+                        let file = File::open("Cargo.toml").unwrap();
+                        file.read_at(Vec::with_capacity(8), 0).await.0.unwrap()
+                    })
+                })
+                .collect::<Vec<_>>();
+            futures_util::future::try_join_all(tasks).await.unwrap()
+        });
+        assert_eq!(read, vec![8; 32]);
+
+        let err = RuntimeBuilder::new().build().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+    })
+    .join()
+    .unwrap();
+
+    // the runtime is already created on the first use
+    thread::spawn(|| {
+        completeio::task::block_on(async {});
+        let err = RuntimeBuilder::new().build().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn runtime_handle() {
+    use std::thread;
+
+    use completeio::task::RuntimeBuilder;
+
+    // the runtime of the test thread could be created by another test
+    thread::spawn(|| {
+        let runtime = RuntimeBuilder::new().build().unwrap();
+        let handle = runtime.handle();
+        let runtime_thread = thread::current().id();
+        runtime.block_on(async {
+            let (tx, rx) = futures_channel::oneshot::channel();
+            // the thread without the runtime hands the work to the runtime thread
+            let legacy = thread::spawn(move || {
+                let read = handle.spawn_fn(|| async {
+                    let file = File::open("Cargo.toml").unwrap();
+                    let (read, _) = file.read_at(Vec::with_capacity(8), 0).await;
+                    (read.unwrap(), thread::current().id())
+                });
+                let thread_id = handle.spawn(async { thread::current().id() });
+                tx.send((read, thread_id)).unwrap();
+            });
+            let (read, thread_id) = rx.await.unwrap();
+            assert_eq!(read.await.unwrap(), (8, runtime_thread));
+            assert_eq!(thread_id.await.unwrap(), runtime_thread);
+            legacy.join().unwrap();
+
+            // the tasks spawned on the runtime thread are not queued
+            let handle = completeio::task::Handle::current();
+            let task = handle.spawn_fn(|| async { thread::current().id() });
+            assert_eq!(task.await.unwrap(), runtime_thread);
+        });
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn runtime_metrics() {
+    use std::thread;
+
+    use completeio::task::RuntimeBuilder;
+
+    // the runtime of the test thread could be created by another test
+    thread::spawn(|| {
+        let runtime = RuntimeBuilder::new().build().unwrap();
+        runtime.block_on(async {
+            let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+            let addr = listener.local_addr().unwrap();
+            let (tx, (rx, _)) =
+                futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+            let before = runtime.metrics();
+            assert_eq!(before.alive_tasks, 0);
+            assert_eq!(before.in_flight_ops, 0);
+            assert_eq!(before.queued_ops, 0);
+
+            let (started_tx, started_rx) = futures_channel::oneshot::channel();
+            let task = runtime.spawn(async move {
+                let recv = rx.recv(Vec::with_capacity(8));
+                started_tx.send(()).unwrap();
+                recv.await
+            });
+            started_rx.await.unwrap();
+            let metrics = runtime.metrics();
+            assert_eq!(metrics.alive_tasks, 1);
+            assert_eq!(metrics.in_flight_ops + metrics.queued_ops, 1);
+
+            tx.send_all("hello").await.0.unwrap();
+            let (res, _) = task.await.unwrap();
+            assert_eq!(res.unwrap(), 5);
+            let after = runtime.metrics();
+            assert_eq!(after.alive_tasks, 0);
+            assert_eq!(after.in_flight_ops, 0);
+            assert_eq!(after.queued_ops, 0);
+            assert_eq!(after.completed_ops, before.completed_ops + 2);
+            assert!(after.driver.completed >= after.completed_ops);
+        });
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn task_budget() {
+    use std::{
+        cell::Cell,
+        future::{poll_fn, Future},
+        rc::Rc,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use completeio::task::RuntimeBuilder;
+
+    // the runtime of the test thread could be created by another test
+    thread::spawn(|| {
+        let runtime = RuntimeBuilder::new().task_budget(4).build().unwrap();
+        runtime.block_on(async {
+            let done = Rc::new(Cell::new(false));
+            let hot = runtime.spawn({
+                let done = done.clone();
+                async move {
+                    let mut spins = 0u64;
+                    while !done.get() {
+                        completeio::task::yield_now().await;
+                        spins += 1;
+                    }
+                    spins
+                }
+            });
+            let timer = runtime.spawn(async move {
+                let start = Instant::now();
+                completeio::time::sleep(Duration::from_millis(10)).await;
+                done.set(true);
+                start.elapsed()
+            });
+            assert!(timer.await.unwrap() < Duration::from_millis(500));
+            assert!(hot.await.unwrap() > 0);
+
+            let file = File::open("Cargo.toml").unwrap();
+            let reads = futures_util::future::join_all(
+                (0..16).map(|_| file.read_at(Vec::with_capacity(8), 0)),
+            );
+            futures_util::pin_mut!(reads);
+            let mut polls = 0;
+            let results = poll_fn(|cx| {
+                polls += 1;
+                reads.as_mut().poll(cx)
+            })
+            .await;
+            assert!(results.iter().all(|(res, _)| matches!(res, Ok(8))));
+            // the task receives at most 4 completed reads per poll
+            assert!(polls > 16 / 4);
+        });
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn submit_handle() {
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use completeio::{
+        op::{Nop, Timeout},
+        task::RuntimeBuilder,
+    };
+
+    // the runtime of the test thread could be created by another test
+    thread::spawn(|| {
+        let runtime = RuntimeBuilder::new().sq_entries(2).build().unwrap();
+        runtime.block_on(async {
+            let start = Instant::now();
+            let (res, _) = runtime.submit_cancellable(Nop::new()).wait().await;
+            res.unwrap();
+
+            // the queue holds two operations, the others are not pushed to the driver
+            let handles = (0..4)
+                .map(|_| runtime.submit_cancellable(Timeout::new(Duration::from_secs(60))))
+                .collect::<Vec<_>>();
+            assert_eq!(runtime.metrics().queued_ops, 2);
+            // the last ones are cancelled before they start, the cancellations of the first
+            // ones wait for the queue space
+            for handle in handles.into_iter().rev() {
+                let _: Timeout = handle.cancel().await;
+            }
+
+            // cancel the running operation
+            let handle = runtime.submit_cancellable(Timeout::new(Duration::from_secs(60)));
+            completeio::time::sleep(Duration::from_millis(5)).await;
+            let _: Timeout = handle.cancel().await;
+
+            // cancel the completed operation
+            let handle = runtime.submit_cancellable(Nop::new());
+            completeio::time::sleep(Duration::from_millis(5)).await;
+            let _: Nop = handle.cancel().await;
+
+            assert!(start.elapsed() < Duration::from_secs(10));
+            let metrics = runtime.metrics();
+            assert_eq!(metrics.in_flight_ops, 0);
+            assert_eq!(metrics.queued_ops, 0);
+        });
+    })
+    .join()
+    .unwrap();
+}
+
+completeio::task_local! {
+    static REQUEST_ID: u32;
+    static NAME: String;
+}
+
+#[test]
+fn task_local() {
+    completeio::task::block_on(async {
+        let tasks = (0..4)
+            .map(|id| {
+                completeio::task::spawn(REQUEST_ID.scope(id, async move {
+                    for _ in 0..4 {
+                        // other tasks run with their values in the meantime
+                        completeio::task::yield_now().await;
+                        assert_eq!(REQUEST_ID.get(), id);
+                    }
+                    id
+                }))
+            })
+            .collect::<Vec<_>>();
+        for (id, task) in tasks.into_iter().enumerate() {
+            assert_eq!(task.await.unwrap(), id as u32);
+        }
+
+        let len = NAME
+            .scope("outer".into(), async {
+                NAME.sync_scope("inner".into(), || assert_eq!(NAME.get(), "inner"));
+                NAME.with(|name| name.len())
+            })
+            .await;
+        assert_eq!(len, 5);
+        assert!(NAME.try_with(|_| ()).is_err());
+        assert!(REQUEST_ID.try_with(|_| ()).is_err());
+    });
+}
+
+#[test]
+fn dump_tasks() {
+    use std::thread;
+
+    use completeio::task::RuntimeBuilder;
+
+    // the runtime of the test thread could be created by another test
+    thread::spawn(|| {
+        let runtime = RuntimeBuilder::new().build().unwrap();
+        runtime.block_on(async {
+            let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+            let addr = listener.local_addr().unwrap();
+            let (tx, (rx, _)) =
+                futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+            assert!(runtime.dump_tasks().is_empty());
+
+            let (done_tx, done_rx) = futures_channel::oneshot::channel::<()>();
+            let reader = completeio::task::spawn_named("reader", async move {
+                rx.recv(Vec::with_capacity(8)).await.0.unwrap()
+            });
+            let waiter = completeio::task::spawn_named(format!("waiter-{}", 1), async move {
+                done_rx.await.unwrap();
+            });
+            let unnamed = completeio::task::spawn(async {});
+            completeio::task::yield_now().await;
+            unnamed.await.unwrap();
+
+            let tasks = runtime.dump_tasks();
+            assert_eq!(tasks.len(), 2);
+            let reader_info = tasks
+                .iter()
+                .find(|task| task.name.as_deref() == Some("reader"))
+                .unwrap();
+            // the reader waits for the receive, the waiter for the channel
+            assert!(reader_info.waiting_on.is_some());
+            let waiter_info = tasks
+                .iter()
+                .find(|task| task.name.as_deref() == Some("waiter-1"))
+                .unwrap();
+            assert_eq!(waiter_info.waiting_on, None);
+
+            tx.send_all("hello").await.0.unwrap();
+            assert_eq!(reader.await.unwrap(), 5);
+            done_tx.send(()).unwrap();
+            waiter.await.unwrap();
+            assert!(runtime.dump_tasks().is_empty());
+        });
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn multi_thread_runtime() {
+    use std::{
+        io::ErrorKind,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+    };
+
+    use completeio::task::Builder;
+
+    fn thread_name() -> String {
+        std::thread::current().name().unwrap().to_owned()
+    }
+
+    let runtime = Arc::new(
+        Builder::new_multi_thread()
+            .worker_threads(2)
+            .build()
+            .unwrap(),
+    );
+    assert_eq!(runtime.worker_threads(), 2);
+
+    // the tasks spawned outside of the workers are distributed in turn
+    let tasks = (0..4)
+        .map(|_| runtime.spawn(|| async { thread_name() }))
+        .collect::<Vec<_>>();
+    let names = completeio::task::block_on(futures_util::future::try_join_all(tasks)).unwrap();
+    assert_ne!(names[0], names[1]);
+    assert_eq!(names[0], names[2]);
+    assert_eq!(names[1], names[3]);
+
+    // the tasks spawned from a worker stay on it
+    let handle = runtime.clone();
+    let (outer, inner) = completeio::task::block_on(runtime.spawn(move || async move {
+        let inner = handle.spawn(|| async { thread_name() }).await.unwrap();
+        (thread_name(), inner)
+    }))
+    .unwrap();
+    assert_eq!(outer, inner);
+
+    // the handles are attached to the driver of the worker
+    let file = completeio::task::block_on(runtime.spawn(|| async {
+        let file = File::open("Cargo.toml").unwrap();
+        let (read, _) = file.read_at(Vec::with_capacity(8), 0).await;
+        read.unwrap();
+        SendWrapper(file)
+    }))
+    .unwrap();
+    let err = completeio::task::block_on(file.read_at(Vec::with_capacity(8), 0))
+        .0
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+    // the shutdown waits for the spawned tasks
+    let completed = Arc::new(AtomicBool::new(false));
+    let task_completed = completed.clone();
+    runtime
+        .spawn(move || async move {
+            let file = File::open("Cargo.toml").unwrap();
+            file.read_at(Vec::with_capacity(8), 0).await.0.unwrap();
+            task_completed.store(true, Ordering::Release);
+        })
+        .detach();
+    Arc::into_inner(runtime).unwrap().shutdown();
+    assert!(completed.load(Ordering::Acquire));
+    // the file is closed on the thread of the main runtime
+    drop(file);
+}
+
 #[test]
 #[cfg(feature = "allocator_api")]
 fn arena() {
@@ -174,6 +837,7 @@ fn arena() {
         let (read, buffer) = file.read_to_end_at(Vec::new_in(ArenaAllocator), 0).await;
         let read = read.unwrap();
         assert_eq!(buffer.len(), read);
+        assert_eq!(buffer[..], std::fs::read("Cargo.toml").unwrap()[..]);
     })
 }
 
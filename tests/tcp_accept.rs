@@ -1,4 +1,7 @@
-use completeio::net::{TcpListener, TcpStream, ToSockAddrs};
+use completeio::{
+    driver::AsRawFd,
+    net::{TcpListener, TcpStream, ToSockAddrs},
+};
 
 async fn test_impl(addr: impl ToSockAddrs) {
     let listener = TcpListener::bind(addr).unwrap();
@@ -36,3 +39,214 @@ test_accept! {
     (str_port_tuple, ("127.0.0.1", 0)),
     (ip_port_tuple, ("127.0.0.1".parse::<std::net::IpAddr>().unwrap(), 0)),
 }
+
+#[test]
+fn bind_async() {
+    completeio::task::block_on(async {
+        let listener = TcpListener::bind_async("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let cli = TcpStream::connect(&addr).await.unwrap();
+        let (srv, _) = listener.accept().await.unwrap();
+        assert_eq!(cli.local_addr().unwrap(), srv.peer_addr().unwrap());
+    })
+}
+
+#[test]
+fn incoming() {
+    use futures_util::StreamExt;
+
+    completeio::task::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let mut incoming = listener.incoming();
+        let mut clients = Vec::new();
+        for _ in 0..3 {
+            let cli = TcpStream::connect(&addr).await.unwrap();
+            let (srv, peer) = incoming.next().await.unwrap().unwrap();
+            assert_eq!(cli.local_addr().unwrap(), srv.peer_addr().unwrap());
+            assert_eq!(cli.local_addr().unwrap(), peer);
+            clients.push(cli);
+        }
+        // connections accepted after the last poll are closed on drop
+        let _pending = TcpStream::connect(&addr).await.unwrap();
+        drop(incoming);
+
+        let cli = TcpStream::connect(&addr).await.unwrap();
+        let (srv, _) = listener.accept().await.unwrap();
+        assert_eq!(cli.local_addr().unwrap(), srv.peer_addr().unwrap());
+    })
+}
+
+#[test]
+fn incoming_buffered() {
+    use futures_util::StreamExt;
+
+    completeio::task::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let mut incoming = listener.incoming();
+        let cli = TcpStream::connect(&addr).await.unwrap();
+        let (srv, _) = incoming.next().await.unwrap().unwrap();
+        assert_eq!(cli.local_addr().unwrap(), srv.peer_addr().unwrap());
+
+        // the connections accepted ahead of the stream overflow its buffer
+        let mut clients = Vec::new();
+        for _ in 0..100 {
+            clients.push(TcpStream::connect(&addr).await.unwrap());
+        }
+        completeio::task::yield_now().await;
+        let mut peers = Vec::new();
+        for _ in 0..clients.len() {
+            let (_, peer) = incoming.next().await.unwrap().unwrap();
+            peers.push(peer.as_socket().unwrap());
+        }
+        let mut addrs = clients
+            .iter()
+            .map(|cli| cli.local_addr().unwrap().as_socket().unwrap())
+            .collect::<Vec<_>>();
+        addrs.sort();
+        peers.sort();
+        assert_eq!(peers, addrs);
+    })
+}
+
+#[test]
+fn accept_cloexec() {
+    completeio::task::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (accepted, _cli) = futures_util::join!(listener.accept(), TcpStream::connect(&addr));
+        let (srv, _) = accepted.unwrap();
+
+        #[cfg(unix)]
+        {
+            let flags = unsafe { libc::fcntl(srv.as_raw_fd(), libc::F_GETFD) };
+            assert!(flags != -1);
+            assert!(flags & libc::FD_CLOEXEC != 0);
+        }
+        #[cfg(windows)]
+        {
+            use windows_sys::Win32::Foundation::{GetHandleInformation, HANDLE_FLAG_INHERIT};
+
+            let mut flags = 0;
+            let res = unsafe { GetHandleInformation(srv.as_raw_fd() as _, &mut flags) };
+            assert!(res != 0);
+            assert!(flags & HANDLE_FLAG_INHERIT == 0);
+        }
+    })
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn reuse_port() {
+    use completeio::net::BindOptions;
+
+    completeio::task::block_on(async {
+        let mut options = BindOptions::new();
+        options.reuse_port(true);
+        let first = TcpListener::bind_with_options("127.0.0.1:0", &options).unwrap();
+        let addr = first.local_addr().unwrap();
+        let second = TcpListener::bind_with_options(&addr, &options).unwrap();
+        assert!(TcpListener::bind(&addr).is_err());
+
+        let accept = |listener: TcpListener| {
+            completeio::task::spawn(async move { listener.accept().await.unwrap() })
+        };
+        let (first, second) = (accept(first), accept(second));
+        // the kernel distributes the connections between the listeners by the client address
+        let mut clients = Vec::new();
+        while !(first.is_finished() && second.is_finished()) {
+            assert!(clients.len() < 256, "the connections are not distributed");
+            clients.push(TcpStream::connect(&addr).await.unwrap());
+            completeio::task::yield_now().await;
+        }
+        let (first, _) = first.await.unwrap();
+        let (second, _) = second.await.unwrap();
+        assert_ne!(first.peer_addr().unwrap(), second.peer_addr().unwrap());
+    })
+}
+
+#[test]
+fn std_round_trip() {
+    completeio::task::block_on(async {
+        let std_listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = std_listener.local_addr().unwrap();
+        let listener = TcpListener::from_std(std_listener).unwrap();
+        assert_eq!(listener.local_addr().unwrap().as_socket(), Some(addr));
+        let (cli, (srv, _)) =
+            futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+        assert_eq!(cli.local_addr().unwrap(), srv.peer_addr().unwrap());
+
+        // the listener is used in blocking mode again
+        let std_listener = listener.into_std().unwrap();
+        assert_eq!(std_listener.local_addr().unwrap(), addr);
+        let cli = std::net::TcpStream::connect(addr).unwrap();
+        let (_, peer) = std_listener.accept().unwrap();
+        assert_eq!(cli.local_addr().unwrap(), peer);
+    })
+}
+
+#[test]
+fn dual_stack() {
+    use std::net::{Ipv4Addr, SocketAddr};
+
+    use completeio::net::{BindOptions, UdpSocket};
+
+    completeio::task::block_on(async {
+        let Ok(listener) =
+            TcpListener::bind_with_options("[::]:0", BindOptions::new().only_v6(false))
+        else {
+            // IPv6 is not available
+            return;
+        };
+        assert!(!listener.only_v6().unwrap());
+        let port = listener.local_addr().unwrap().as_socket().unwrap().port();
+        let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+        let (cli, (_, peer)) =
+            futures_util::try_join!(TcpStream::connect(addr), listener.accept()).unwrap();
+        // the IPv4 client is mapped to an IPv6 address
+        let mapped = cli.local_addr().unwrap().as_socket_ipv4().unwrap();
+        let peer = peer.as_socket_ipv6().unwrap();
+        assert_eq!(peer.ip().to_ipv4_mapped(), Some(*mapped.ip()));
+        assert_eq!(peer.port(), mapped.port());
+
+        let listener =
+            TcpListener::bind_with_options("[::]:0", BindOptions::new().only_v6(true)).unwrap();
+        assert!(listener.only_v6().unwrap());
+        let port = listener.local_addr().unwrap().as_socket().unwrap().port();
+        assert!(TcpStream::connect((Ipv4Addr::LOCALHOST, port))
+            .await
+            .is_err());
+
+        let socket =
+            UdpSocket::bind_with_options("[::]:0", BindOptions::new().only_v6(true)).unwrap();
+        assert!(socket.only_v6().unwrap());
+    })
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn device_and_freebind() {
+    use std::io;
+
+    use completeio::net::BindOptions;
+
+    // the address isn't assigned to any interface
+    let listener =
+        TcpListener::bind_with_options("198.51.100.7:0", BindOptions::new().freebind(true))
+            .unwrap();
+    assert!(listener.freebind().unwrap());
+    assert!(TcpListener::bind("198.51.100.7:0").is_err());
+
+    match TcpListener::bind_with_options("127.0.0.1:0", BindOptions::new().device("lo")) {
+        Ok(listener) => {
+            assert_eq!(listener.device().unwrap().as_deref(), Some("lo"));
+            listener.set_device(None).unwrap();
+            assert_eq!(listener.device().unwrap(), None);
+        }
+        // the option requires CAP_NET_RAW on older kernels
+        Err(e) => assert_eq!(e.kind(), io::ErrorKind::PermissionDenied),
+    }
+}
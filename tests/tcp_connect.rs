@@ -101,3 +101,378 @@ fn connect_invalid_dst() {
         assert!(TcpStream::connect("127.0.0.1:1").await.is_err());
     })
 }
+
+#[test]
+fn close_stream() {
+    completeio::task::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let (accepted, client) = futures_util::join!(listener.accept(), TcpStream::connect(&addr));
+        let (server, _) = accepted.unwrap();
+        client.unwrap().close().await.unwrap();
+
+        let (res, _) = server.recv(Vec::with_capacity(8)).await;
+        assert_eq!(res.unwrap(), 0);
+    });
+}
+
+#[test]
+fn send_file() {
+    use std::io::Write;
+
+    completeio::task::block_on(async {
+        let mut tempfile = tempfile::NamedTempFile::new().unwrap();
+        tempfile.write_all(b"hello world").unwrap();
+        let file = completeio::fs::File::open(tempfile.path()).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (accepted, client) = futures_util::join!(listener.accept(), TcpStream::connect(&addr));
+        let (server, _) = accepted.unwrap();
+        let client = client.unwrap();
+
+        let sent = client.send_file(&file, 6, 5).await.unwrap();
+        assert_eq!(sent, 5);
+        // end of file is reached
+        let sent = client.send_file(&file, 9, 5).await.unwrap();
+        assert_eq!(sent, 2);
+
+        let (res, buf) = server.recv_exact(Vec::with_capacity(7)).await;
+        res.unwrap();
+        assert_eq!(buf, b"worldld");
+    });
+}
+
+#[test]
+fn peek() {
+    completeio::task::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (accepted, client) = futures_util::join!(listener.accept(), TcpStream::connect(&addr));
+        let (server, _) = accepted.unwrap();
+        client.unwrap().send_all("hello").await.0.unwrap();
+
+        let (res, buf) = server.peek(Vec::with_capacity(5)).await;
+        assert_eq!(res.unwrap(), 5);
+        assert_eq!(buf, b"hello");
+        // the peeked data is received again
+        let (res, buf) = server.recv_exact(Vec::with_capacity(5)).await;
+        res.unwrap();
+        assert_eq!(buf, b"hello");
+    });
+}
+
+#[test]
+fn urgent_data() {
+    completeio::task::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (accepted, client) = futures_util::join!(listener.accept(), TcpStream::connect(&addr));
+        let (server, _) = accepted.unwrap();
+        let client = client.unwrap();
+
+        // the urgent byte skips the data stream, it fails to be received before it arrives
+        assert!(!server.oob_inline().unwrap());
+        client.send_all("a").await.0.unwrap();
+        client.send_oob(b'!').await.unwrap();
+        let mut attempts = 0;
+        let buf = loop {
+            match server.recv_oob(Vec::with_capacity(1)).await {
+                (Ok(_), buf) => break buf,
+                (Err(e), _) if e.kind() == std::io::ErrorKind::InvalidInput && attempts < 100 => {
+                    attempts += 1;
+                    completeio::time::sleep(std::time::Duration::from_millis(10)).await;
+                }
+                (Err(e), _) => panic!("{e}"),
+            }
+        };
+        assert_eq!(buf, b"!");
+        client.send_all("b").await.0.unwrap();
+        let (res, buf) = server.recv_exact(Vec::with_capacity(2)).await;
+        res.unwrap();
+        assert_eq!(buf, b"ab");
+
+        server.set_oob_inline(true).unwrap();
+        assert!(server.oob_inline().unwrap());
+        client.send_oob(b'?').await.unwrap();
+        client.send_all("c").await.0.unwrap();
+        let (res, buf) = server.recv_exact(Vec::with_capacity(2)).await;
+        res.unwrap();
+        assert_eq!(buf, b"?c");
+    });
+}
+
+#[test]
+fn socket_options() {
+    completeio::task::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_ttl(42).unwrap();
+        assert_eq!(listener.ttl().unwrap(), 42);
+        let addr = listener.local_addr().unwrap();
+        let (accepted, client) = futures_util::join!(listener.accept(), TcpStream::connect(&addr));
+        let (server, _) = accepted.unwrap();
+        let client = client.unwrap();
+
+        for stream in [&client, &server] {
+            stream.set_nodelay(true).unwrap();
+            assert!(stream.nodelay().unwrap());
+            stream.set_ttl(7).unwrap();
+            assert_eq!(stream.ttl().unwrap(), 7);
+            assert!(stream.take_error().unwrap().is_none());
+        }
+        // the options are kept after the streams are attached to the driver
+        client.send_all("ping").await.0.unwrap();
+        server.recv_exact(Vec::with_capacity(4)).await.0.unwrap();
+        client.set_nodelay(false).unwrap();
+        assert!(!client.nodelay().unwrap());
+        assert!(server.nodelay().unwrap());
+    });
+}
+
+#[test]
+fn keepalive_and_linger() {
+    use std::time::Duration;
+
+    use completeio::net::KeepAlive;
+
+    completeio::task::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(stream.keepalive().unwrap(), None);
+        let keepalive = KeepAlive {
+            time: Some(Duration::from_secs(120)),
+            interval: Some(Duration::from_secs(15)),
+            retries: Some(4),
+        };
+        stream.set_keepalive(Some(keepalive)).unwrap();
+        let applied = stream.keepalive().unwrap().unwrap();
+        // the parameters the platform can't report are None
+        for (applied, set) in [
+            (applied.time, keepalive.time),
+            (applied.interval, keepalive.interval),
+        ] {
+            if applied.is_some() {
+                assert_eq!(applied, set);
+            }
+        }
+        if applied.retries.is_some() {
+            assert_eq!(applied.retries, keepalive.retries);
+        }
+        stream.set_keepalive(None).unwrap();
+        assert_eq!(stream.keepalive().unwrap(), None);
+
+        assert_eq!(stream.linger().unwrap(), None);
+        stream.set_linger(Some(Duration::from_secs(3))).unwrap();
+        assert_eq!(stream.linger().unwrap(), Some(Duration::from_secs(3)));
+        stream.set_linger(None).unwrap();
+        assert_eq!(stream.linger().unwrap(), None);
+    });
+}
+
+#[test]
+fn connect_timeout() {
+    use std::time::Duration;
+
+    #[cfg(target_os = "linux")]
+    use completeio::net::BindOptions;
+
+    completeio::task::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (stream, _) = futures_util::try_join!(
+            TcpStream::connect_timeout(&addr, Duration::from_secs(10)),
+            listener.accept()
+        )
+        .unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), addr);
+
+        // the listener with the full backlog drops the handshakes like a black-holed address
+        #[cfg(target_os = "linux")]
+        {
+            let listener =
+                TcpListener::bind_with_options("127.0.0.1:0", BindOptions::new().backlog(0))
+                    .unwrap();
+            let addr = listener.local_addr().unwrap();
+            let mut streams = Vec::new();
+            let err = loop {
+                assert!(streams.len() < 16, "the backlog is not filled");
+                let start = std::time::Instant::now();
+                match TcpStream::connect_timeout(&addr, Duration::from_millis(100)).await {
+                    Ok(stream) => streams.push(stream),
+                    Err(e) => {
+                        assert!(start.elapsed() < Duration::from_secs(1));
+                        break e;
+                    }
+                }
+            };
+            assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+        }
+    })
+}
+
+#[test]
+fn connect_happy() {
+    use std::time::{Duration, Instant};
+
+    #[cfg(target_os = "linux")]
+    use completeio::net::BindOptions;
+
+    completeio::task::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().as_socket().unwrap();
+        let refused = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .as_socket()
+            .unwrap();
+
+        // the failed connect starts the next one without waiting for the delay
+        let start = Instant::now();
+        let (stream, _) = futures_util::try_join!(
+            TcpStream::connect_happy([refused, addr], Duration::from_secs(10)),
+            listener.accept()
+        )
+        .unwrap();
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(stream.peer_addr().unwrap().as_socket(), Some(addr));
+
+        let err = TcpStream::connect_happy([refused], Duration::from_millis(10))
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::ConnectionRefused);
+
+        // the connect to the black-holed address is overtaken after the delay
+        #[cfg(target_os = "linux")]
+        {
+            let black_hole =
+                TcpListener::bind_with_options("127.0.0.1:0", BindOptions::new().backlog(0))
+                    .unwrap();
+            let black_hole_addr = black_hole.local_addr().unwrap();
+            // fill the backlog
+            let mut streams = Vec::new();
+            while let Ok(stream) =
+                TcpStream::connect_timeout(&black_hole_addr, Duration::from_millis(100)).await
+            {
+                assert!(streams.len() < 16, "the backlog is not filled");
+                streams.push(stream);
+            }
+            let black_hole_addr = black_hole_addr.as_socket().unwrap();
+            let (stream, _) = futures_util::try_join!(
+                TcpStream::connect_happy([black_hole_addr, addr], Duration::from_millis(50)),
+                listener.accept()
+            )
+            .unwrap();
+            assert_eq!(stream.peer_addr().unwrap().as_socket(), Some(addr));
+        }
+    })
+}
+
+#[test]
+fn std_round_trip() {
+    use std::io::{Read, Write};
+
+    completeio::task::block_on(async {
+        let std_listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = std_listener.local_addr().unwrap();
+        let mut std_cli = std::net::TcpStream::connect(addr).unwrap();
+        let (std_srv, _) = std_listener.accept().unwrap();
+        let local_addr = std_srv.local_addr().unwrap();
+
+        let srv = TcpStream::from_std(std_srv).unwrap();
+        assert_eq!(srv.local_addr().unwrap().as_socket(), Some(local_addr));
+        std_cli.write_all(b"ping").unwrap();
+        let (res, buf) = srv.recv_exact(Vec::with_capacity(4)).await;
+        res.unwrap();
+        assert_eq!(buf, b"ping");
+
+        let mut std_srv = srv.into_std().unwrap();
+        assert_eq!(std_srv.local_addr().unwrap(), local_addr);
+        std_srv.write_all(b"pong").unwrap();
+        let mut buf = [0; 4];
+        std_cli.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"pong");
+    })
+}
+
+#[test]
+fn connect_with_data() {
+    use std::time::Duration;
+
+    completeio::task::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        if cfg!(target_os = "linux") {
+            listener.set_tcp_fastopen(16).unwrap();
+            listener.set_defer_accept(Duration::from_secs(1)).unwrap();
+        }
+        let addr = listener.local_addr().unwrap();
+        // the second connect carries the data in the SYN if the server gave the cookie
+        for _ in 0..2 {
+            let connect = async {
+                let (res, _) = TcpStream::connect_with_data(&addr, "hello").await;
+                res
+            };
+            let ((client, sent), (server, _)) =
+                futures_util::try_join!(connect, listener.accept()).unwrap();
+            assert_eq!(sent, 5);
+            let (res, buf) = server.recv_exact(Vec::with_capacity(5)).await;
+            res.unwrap();
+            assert_eq!(buf, b"hello");
+            assert_eq!(client.local_addr().unwrap(), server.peer_addr().unwrap());
+        }
+    })
+}
+
+#[test]
+fn buffer_tos_and_raw_options() {
+    completeio::task::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+
+        // the kernel could round the sizes up
+        stream.set_recv_buffer_size(64 * 1024).unwrap();
+        assert!(stream.recv_buffer_size().unwrap() >= 64 * 1024);
+        stream.set_send_buffer_size(32 * 1024).unwrap();
+        assert!(stream.send_buffer_size().unwrap() >= 32 * 1024);
+
+        // CS1 DSCP
+        stream.set_tos(0x20).unwrap();
+        assert_eq!(stream.tos().unwrap(), 0x20);
+
+        #[cfg(target_os = "linux")]
+        {
+            use std::time::Duration;
+
+            stream
+                .set_user_timeout(Some(Duration::from_secs(10)))
+                .unwrap();
+            assert_eq!(
+                stream.user_timeout().unwrap(),
+                Some(Duration::from_secs(10))
+            );
+
+            // the raw option is read back by the wrapper and the other way around
+            unsafe {
+                stream
+                    .set_raw_sockopt(libc::IPPROTO_TCP, libc::TCP_USER_TIMEOUT, &5000i32)
+                    .unwrap();
+                let timeout: i32 = stream
+                    .get_raw_sockopt(libc::IPPROTO_TCP, libc::TCP_USER_TIMEOUT)
+                    .unwrap();
+                assert_eq!(timeout, 5000);
+                assert_eq!(stream.user_timeout().unwrap(), Some(Duration::from_secs(5)));
+                let err = stream
+                    .get_raw_sockopt::<u64>(libc::IPPROTO_TCP, libc::TCP_USER_TIMEOUT)
+                    .unwrap_err();
+                assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+            }
+        }
+    })
+}
@@ -0,0 +1,109 @@
+use completeio::net::{TcpListener, TcpStream};
+
+const CHUNK: usize = 1024;
+const CHUNKS: usize = 64;
+
+// echoes the stream till the peer shuts down its write half
+async fn echo(stream: TcpStream) {
+    let mut buffer = Vec::with_capacity(CHUNK);
+    loop {
+        let (res, received) = stream.recv(buffer).await;
+        if res.unwrap() == 0 {
+            break;
+        }
+        let (res, mut sent) = stream.send_all(received).await;
+        res.unwrap();
+        sent.clear();
+        buffer = sent;
+    }
+    stream.shutdown(std::net::Shutdown::Write).unwrap();
+}
+
+#[test]
+fn echo_into_split() {
+    completeio::task::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = completeio::task::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            echo(stream).await;
+        });
+
+        let stream = TcpStream::connect(&addr).await.unwrap();
+        let local_addr = stream.local_addr().unwrap();
+        let (reader, writer) = stream.into_split();
+        // the receive is in flight while the chunks are sent on the same socket
+        let reader = completeio::task::spawn(async move {
+            let mut received = Vec::new();
+            loop {
+                let (res, buffer) = reader.recv(Vec::with_capacity(CHUNK)).await;
+                if res.unwrap() == 0 {
+                    break;
+                }
+                received.extend_from_slice(&buffer);
+            }
+            (reader, received)
+        });
+        let writer = completeio::task::spawn(async move {
+            for i in 0..CHUNKS {
+                let (res, _) = writer.send_all(vec![i as u8; CHUNK]).await;
+                res.unwrap();
+            }
+            writer.shutdown().unwrap();
+            writer
+        });
+
+        let writer = writer.await.unwrap();
+        let (reader, received) = reader.await.unwrap();
+        server.await.unwrap();
+        assert_eq!(received.len(), CHUNK * CHUNKS);
+        for (i, chunk) in received.chunks(CHUNK).enumerate() {
+            assert!(chunk.iter().all(|&byte| byte == i as u8));
+        }
+
+        let stream = reader.reunite(writer).unwrap();
+        assert_eq!(stream.local_addr().unwrap(), local_addr);
+    })
+}
+
+#[test]
+fn echo_split() {
+    completeio::task::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = completeio::task::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            echo(stream).await;
+        });
+
+        let stream = TcpStream::connect(&addr).await.unwrap();
+        let (reader, writer) = stream.split();
+        let ((res, buffer), sent) =
+            futures_util::join!(reader.recv_exact(Vec::with_capacity(5)), async {
+                let res = writer.send_all("hello").await.0;
+                writer.shutdown().unwrap();
+                res
+            });
+        assert_eq!(sent.unwrap(), 5);
+        res.unwrap();
+        assert_eq!(buffer, b"hello");
+        server.await.unwrap();
+    })
+}
+
+#[test]
+fn reunite_other_stream() {
+    completeio::task::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let first = TcpStream::connect(&addr).await.unwrap();
+        let second = TcpStream::connect(&addr).await.unwrap();
+        let (first_reader, first_writer) = first.into_split();
+        let (_second_reader, second_writer) = second.into_split();
+
+        let Err(err) = first_reader.reunite(second_writer) else {
+            panic!("the halves of different streams are reunited");
+        };
+        assert!(err.0.reunite(first_writer).is_ok());
+    })
+}
@@ -0,0 +1,269 @@
+use std::{
+    future::{poll_fn, Future},
+    pin::{pin, Pin},
+    task::Poll,
+    time::Duration,
+};
+
+use boot_time::Instant;
+use completeio::time::{sleep, sleep_until};
+
+// the accepted delay of the timer wake up
+const TOLERANCE: Duration = Duration::from_millis(50);
+
+#[test]
+fn concurrent_sleeps() {
+    completeio::task::block_on(async {
+        let start = Instant::now();
+        let tasks = (1..=100)
+            .map(|i| {
+                completeio::task::spawn(async move {
+                    let duration = Duration::from_millis(10 + i % 3 * 10);
+                    sleep(duration).await;
+                    (duration, start.elapsed())
+                })
+            })
+            .collect::<Vec<_>>();
+        for task in tasks {
+            let (duration, elapsed) = task.await.unwrap();
+            assert!(elapsed >= duration, "{elapsed:?} < {duration:?}");
+            assert!(
+                elapsed < duration + TOLERANCE,
+                "{elapsed:?} >= {duration:?} + {TOLERANCE:?}"
+            );
+        }
+    })
+}
+
+#[test]
+fn sleep_until_deadline() {
+    completeio::task::block_on(async {
+        let deadline = Instant::now() + Duration::from_millis(20);
+        sleep_until(deadline).await;
+        let now = Instant::now();
+        assert!(now >= deadline);
+        assert!(now < deadline + TOLERANCE);
+
+        // the passed deadline completes immediately
+        sleep_until(deadline).await;
+        assert!(Instant::now() < now + TOLERANCE);
+    })
+}
+
+#[test]
+fn dropped_sleeps_are_canceled() {
+    completeio::task::block_on(async {
+        // more timers than the submission queue entries
+        for _ in 0..4096 {
+            let mut sleep = pin!(sleep(Duration::from_secs(3600)));
+            poll_fn(|cx| {
+                assert!(sleep.as_mut().poll(cx).is_pending());
+                Poll::Ready(())
+            })
+            .await;
+        }
+        let start = Instant::now();
+        sleep(Duration::from_millis(10)).await;
+        assert!(start.elapsed() < Duration::from_millis(10) + TOLERANCE);
+    })
+}
+
+#[test]
+fn reset_sleep() {
+    completeio::task::block_on(async {
+        let mut sleep = sleep(Duration::from_millis(10));
+        poll_fn(|cx| {
+            assert!(Pin::new(&mut sleep).poll(cx).is_pending());
+            Poll::Ready(())
+        })
+        .await;
+        // re-arm the pending timer more times than the submission queue entries
+        for _ in 0..4096 {
+            sleep.reset(Instant::now() + Duration::from_millis(10));
+        }
+        let deadline = Instant::now() + Duration::from_millis(50);
+        sleep.reset(deadline);
+        assert_eq!(sleep.deadline(), deadline);
+        (&mut sleep).await;
+        // only the final deadline fires
+        let now = Instant::now();
+        assert!(now >= deadline);
+        assert!(now < deadline + TOLERANCE);
+
+        // the completed sleep is pending again
+        let deadline = Instant::now() + Duration::from_millis(10);
+        sleep.reset(deadline);
+        sleep.await;
+        assert!(Instant::now() >= deadline);
+    })
+}
+
+#[test]
+fn reset_sleep_to_earlier_deadline() {
+    completeio::task::block_on(async {
+        let mut sleep = sleep(Duration::from_secs(3600));
+        poll_fn(|cx| {
+            assert!(Pin::new(&mut sleep).poll(cx).is_pending());
+            Poll::Ready(())
+        })
+        .await;
+        let deadline = Instant::now() + Duration::from_millis(10);
+        sleep.reset(deadline);
+        sleep.await;
+        let now = Instant::now();
+        assert!(now >= deadline);
+        assert!(now < deadline + TOLERANCE);
+    })
+}
+
+#[test]
+fn timeout_cancels_operations() {
+    use completeio::{
+        net::UdpSocket,
+        time::{timeout, Elapsed},
+    };
+
+    completeio::task::block_on(async {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        // the receive on the silent socket is canceled at the deadline
+        let start = Instant::now();
+        let mut recv = pin!(socket.recv(Vec::with_capacity(16)));
+        let res = timeout(Duration::from_millis(50), recv.as_mut()).await;
+        assert!(matches!(res, Err(Elapsed)));
+        let (res, buffer) = recv.await;
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::TimedOut);
+        assert_eq!(buffer.capacity(), 16);
+        assert!(start.elapsed() < Duration::from_millis(50) + TOLERANCE);
+
+        // the socket still receives
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        sender
+            .send_to("hello", socket.local_addr().unwrap())
+            .await
+            .0
+            .unwrap();
+        let (res, buffer) = timeout(Duration::from_secs(5), socket.recv(buffer))
+            .await
+            .unwrap();
+        assert_eq!(res.unwrap(), 5);
+        assert_eq!(buffer, b"hello");
+
+        // the inner deadline doesn't extend the outer one, the sleeps are not canceled
+        let res = timeout(
+            Duration::from_millis(10),
+            timeout(Duration::from_secs(5), sleep(Duration::from_secs(5))),
+        )
+        .await;
+        assert!(matches!(res, Err(Elapsed)));
+        let res = timeout(Duration::from_secs(5), sleep(Duration::from_millis(10))).await;
+        assert!(res.is_ok());
+    })
+}
+
+#[test]
+fn op_timeout_keeps_deadline() {
+    use completeio::{net::UdpSocket, time::OpTimeoutExt};
+
+    completeio::task::block_on(async {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        // the receive submitted after the sleep gets the rest of the timeout
+        let start = Instant::now();
+        let (res, _) = async {
+            sleep(Duration::from_millis(100)).await;
+            socket.recv(Vec::with_capacity(16)).await
+        }
+        .timeout(Duration::from_millis(150))
+        .await;
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::TimedOut);
+        let elapsed = start.elapsed();
+        assert!(
+            elapsed < Duration::from_millis(150) + TOLERANCE,
+            "{elapsed:?}"
+        );
+    })
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn timeout_cancels_connect() {
+    use completeio::{
+        net::{BindOptions, TcpListener, TcpStream},
+        time::{timeout, Elapsed},
+    };
+
+    completeio::task::block_on(async {
+        let listener =
+            TcpListener::bind_with_options("127.0.0.1:0", BindOptions::new().backlog(0)).unwrap();
+        let addr = listener.local_addr().unwrap();
+        // the handshakes beyond the full backlog are dropped
+        let mut streams = Vec::new();
+        while let Ok(stream) = TcpStream::connect_timeout(&addr, Duration::from_millis(50)).await {
+            assert!(streams.len() < 16, "the backlog is not filled");
+            streams.push(stream);
+        }
+
+        let start = Instant::now();
+        let res = timeout(Duration::from_millis(50), TcpStream::connect(&addr)).await;
+        // the linked timeout of the connect could fire before the sleep
+        match res {
+            Ok(Err(e)) => assert_eq!(e.kind(), std::io::ErrorKind::TimedOut),
+            res => assert!(matches!(res, Err(Elapsed))),
+        }
+        assert!(start.elapsed() < Duration::from_millis(50) + TOLERANCE);
+    })
+}
+
+#[test]
+fn interval_ticks() {
+    use completeio::time::{interval, interval_at, MissedTickBehavior};
+
+    const PERIOD: Duration = Duration::from_millis(20);
+
+    completeio::task::block_on(async {
+        let mut interval = interval(PERIOD);
+        let start = interval.tick().await;
+        for i in 1..=2 {
+            let tick = interval.tick().await;
+            assert_eq!(tick, start + PERIOD * i);
+            let elapsed = start.elapsed();
+            assert!(elapsed >= PERIOD * i, "{elapsed:?} < {:?}", PERIOD * i);
+            assert!(elapsed < PERIOD * i + TOLERANCE);
+        }
+
+        // the consumer misses three ticks
+        let expected = [
+            (MissedTickBehavior::Burst, [1, 2, 3, 4]),
+            (MissedTickBehavior::Delay, [1, 4, 5, 6]),
+            (MissedTickBehavior::Skip, [1, 4, 5, 6]),
+        ];
+        for (behavior, ticks) in expected {
+            let start = Instant::now();
+            let mut interval = interval_at(start, PERIOD);
+            interval.set_missed_tick_behavior(behavior);
+            assert_eq!(interval.tick().await, start);
+            sleep(PERIOD * 3 + PERIOD / 2).await;
+            let late = Instant::now();
+            let mut instants = Vec::new();
+            for _ in ticks {
+                instants.push(interval.tick().await);
+            }
+            match behavior {
+                MissedTickBehavior::Delay => {
+                    // the next ticks are scheduled from the late call
+                    assert_eq!(instants[0], start + PERIOD);
+                    for (i, pair) in instants[1..].windows(2).enumerate() {
+                        assert_eq!(pair[1] - pair[0], PERIOD, "tick {i}");
+                    }
+                    assert!(instants[1] >= late + PERIOD);
+                    assert!(instants[1] < late + PERIOD + TOLERANCE);
+                }
+                _ => {
+                    let scheduled = ticks.map(|i| start + PERIOD * i).to_vec();
+                    assert_eq!(instants, scheduled, "{behavior:?}");
+                }
+            }
+        }
+    })
+}
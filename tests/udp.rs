@@ -69,3 +69,184 @@ fn send_to() {
         );
     })
 }
+
+#[cfg(target_os = "linux")]
+#[test]
+fn recv_truncated() {
+    completeio::task::block_on(async {
+        const MSG: &str = "foo bar baz";
+
+        let passive = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let passive_addr = passive.local_addr().unwrap();
+
+        let active = UdpSocket::bind("127.0.0.1:0").unwrap();
+        active.send_to(MSG, &passive_addr).await.0.unwrap();
+
+        let (res, buffer) = passive
+            .recv_with_flags(Vec::with_capacity(3), libc::MSG_TRUNC)
+            .await;
+        assert_eq!(res.unwrap(), MSG.len());
+        assert_eq!(buffer, b"foo");
+    })
+}
+
+#[test]
+fn recv_full() {
+    completeio::task::block_on(async {
+        const MSG: &str = "foo bar baz";
+
+        let passive = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let passive_addr = passive.local_addr().unwrap();
+
+        let active = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let active_addr = active.local_addr().unwrap();
+        active.send_to(MSG, &passive_addr).await.0.unwrap();
+        active.send_to(MSG, &passive_addr).await.0.unwrap();
+
+        let (res, buffer) = passive.recv_from_full(Vec::with_capacity(3)).await;
+        let (len, addr) = res.unwrap();
+        assert_eq!(addr, active_addr);
+        assert_eq!(buffer, b"foo");
+        assert_eq!(len.copied, 3);
+        assert!(len.is_truncated());
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        assert_eq!(len.total, Some(MSG.len()));
+
+        passive.connect(&active_addr).unwrap();
+        let (res, buffer) = passive.recv_full(Vec::with_capacity(20)).await;
+        let len = res.unwrap();
+        assert_eq!(buffer, MSG.as_bytes());
+        assert_eq!(len.copied, MSG.len());
+        assert_eq!(len.total, Some(MSG.len()));
+        assert!(!len.is_truncated());
+    })
+}
+
+#[test]
+fn move_to_another_runtime() {
+    use completeio::driver::{FromRawFd, IntoRawFd};
+
+    const MSG: &str = "foo bar baz";
+
+    let passive = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let passive_addr = passive.local_addr().unwrap();
+
+    let fd = completeio::task::block_on(async {
+        let active = UdpSocket::bind("127.0.0.1:0").unwrap();
+        active.send_to(MSG, &passive_addr).await.0.unwrap();
+        active.into_raw_fd()
+    });
+    // the fd is sent to the thread as a number, the socket is !Send
+    let fd = fd as usize;
+    std::thread::spawn(move || {
+        completeio::task::block_on(async {
+            let active = unsafe { UdpSocket::from_raw_fd(fd as _) };
+            active.send_to(MSG, &passive_addr).await.0.unwrap();
+        })
+    })
+    .join()
+    .unwrap();
+
+    let mut buffer = [0; 20];
+    for _ in 0..2 {
+        let len = passive.recv(&mut buffer).unwrap();
+        assert_eq!(MSG.as_bytes(), &buffer[..len]);
+    }
+}
+
+#[test]
+fn std_round_trip() {
+    completeio::task::block_on(async {
+        let std_socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = std_socket.local_addr().unwrap();
+        let socket = UdpSocket::from_std(std_socket).unwrap();
+        assert_eq!(socket.local_addr().unwrap().as_socket(), Some(addr));
+
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        peer.send_to(b"ping", addr).unwrap();
+        let (res, buf) = socket.recv(Vec::with_capacity(4)).await;
+        assert_eq!(res.unwrap(), 4);
+        assert_eq!(buf, b"ping");
+
+        let std_socket = socket.into_std().unwrap();
+        assert_eq!(std_socket.local_addr().unwrap(), addr);
+        std_socket
+            .send_to(b"pong", peer.local_addr().unwrap())
+            .unwrap();
+        let mut buf = [0; 4];
+        assert_eq!(peer.recv(&mut buf).unwrap(), 4);
+        assert_eq!(&buf, b"pong");
+    })
+}
+
+#[test]
+fn batch() {
+    completeio::task::block_on(async {
+        const COUNT: usize = 64;
+
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let receiver_addr = receiver.local_addr().unwrap();
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let sender_addr = sender.local_addr().unwrap();
+
+        let datagrams = (0..COUNT)
+            .map(|i| (vec![i as u8; i + 1], receiver_addr.clone()))
+            .collect();
+        let (res, datagrams) = sender.send_batch(datagrams).await;
+        let sent = res.unwrap();
+        assert_eq!(sent, (1..=COUNT).collect::<Vec<_>>());
+        assert_eq!(datagrams.len(), COUNT);
+
+        let mut received = Vec::new();
+        let mut calls = 0;
+        while received.len() < COUNT {
+            let buffers = (received.len()..COUNT)
+                .map(|_| Vec::with_capacity(128))
+                .collect();
+            let (res, buffers) = receiver.recv_batch(buffers).await;
+            let results = res.unwrap();
+            for ((len, addr), buffer) in results.iter().zip(&buffers) {
+                assert_eq!(*len, buffer.len());
+                assert_eq!(*addr, sender_addr);
+            }
+            received.extend(buffers.into_iter().take(results.len()));
+            calls += 1;
+        }
+        for (i, buffer) in received.iter().enumerate() {
+            assert_eq!(buffer, &vec![i as u8; i + 1]);
+        }
+        // the queued datagrams are received at once
+        #[cfg(unix)]
+        assert_eq!(calls, 1);
+        #[cfg(windows)]
+        let _ = calls;
+
+        // the batch ends at the last queued datagram
+        let datagrams = (0..3).map(|_| ("ping", receiver_addr.clone())).collect();
+        assert_eq!(sender.send_batch(datagrams).await.0.unwrap(), [4; 3]);
+        let (res, buffers) = receiver
+            .recv_batch((0..COUNT).map(|_| Vec::with_capacity(8)).collect())
+            .await;
+        let results = res.unwrap();
+        assert!(!results.is_empty());
+        #[cfg(unix)]
+        assert_eq!(results.len(), 3);
+        assert_eq!(buffers.len(), COUNT);
+        assert_eq!(buffers[0], b"ping");
+        assert!(buffers[results.len()..]
+            .iter()
+            .all(|buffer| buffer.is_empty()));
+    })
+}
+
+#[test]
+fn socket_options() {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket.set_recv_buffer_size(256 * 1024).unwrap();
+    assert!(socket.recv_buffer_size().unwrap() >= 256 * 1024);
+    socket.set_send_buffer_size(64 * 1024).unwrap();
+    assert!(socket.send_buffer_size().unwrap() >= 64 * 1024);
+    // EF DSCP
+    socket.set_tos(0xb8).unwrap();
+    assert_eq!(socket.tos().unwrap(), 0xb8);
+}
@@ -52,3 +52,166 @@ fn shutdown() -> std::io::Result<()> {
         Ok(())
     })
 }
+
+#[test]
+fn shutdown_async() -> std::io::Result<()> {
+    completeio::task::block_on(async {
+        let dir = tempfile::Builder::new()
+            .prefix("completeio-uds-tests")
+            .tempdir()
+            .unwrap();
+        let sock_path = dir.path().join("connect.sock");
+
+        let listener = UnixListener::bind(&sock_path)?;
+
+        let client = UnixStream::connect(&sock_path)?;
+        let (server, _) = listener.accept().await?;
+
+        client.send_all("hello").await.0?;
+        client.shutdown_async(Shutdown::Write).await?;
+
+        let (n, buf) = server.recv_exact(Vec::with_capacity(5)).await;
+        assert_eq!(n?, 5);
+        assert_eq!(buf, b"hello");
+        // the peer sees the end of stream after the async shutdown
+        let n = server.recv(Vec::with_capacity(1)).await.0?;
+        assert_eq!(n, 0);
+        Ok(())
+    })
+}
+
+#[cfg(unix)]
+#[test]
+fn send_recv_with_fds() -> std::io::Result<()> {
+    use std::{
+        io::{Read, Seek, Write},
+        os::fd::AsFd,
+    };
+
+    use completeio::net::TruncatedFdsError;
+
+    completeio::task::block_on(async {
+        let dir = tempfile::Builder::new()
+            .prefix("completeio-uds-tests")
+            .tempdir()
+            .unwrap();
+        let sock_path = dir.path().join("connect.sock");
+
+        let listener = UnixListener::bind(&sock_path)?;
+
+        let client = UnixStream::connect(&sock_path)?;
+        let (server, _) = listener.accept().await?;
+
+        let mut file = tempfile::tempfile()?;
+        file.write_all(b"passed")?;
+        let other_file = tempfile::tempfile()?;
+
+        let sent = client
+            .send_with_fds("hello", &[file.as_fd(), other_file.as_fd()])
+            .await
+            .0?;
+        assert_eq!(sent, 5);
+
+        let ((len, fds), buf) = {
+            let (res, buf) = server.recv_with_fds(Vec::with_capacity(5), 2).await;
+            (res?, buf)
+        };
+        assert_eq!(len, 5);
+        assert_eq!(buf, b"hello");
+        assert_eq!(fds.len(), 2);
+
+        // the received descriptor refers to the same open file
+        let mut received_file = std::fs::File::from(fds.into_iter().next().unwrap());
+        received_file.rewind()?;
+        let mut content = String::new();
+        received_file.read_to_string(&mut content)?;
+        assert_eq!(content, "passed");
+
+        // the control buffer doesn't fit the descriptors
+        client.send_with_fds("world", &[file.as_fd()]).await.0?;
+        let (res, buf) = server.recv_with_fds(Vec::with_capacity(5), 0).await;
+        let error = res.unwrap_err();
+        let truncated = TruncatedFdsError::from_io_error(&error).unwrap();
+        assert_eq!(truncated.received(), 5);
+        assert_eq!(buf, b"world");
+        Ok(())
+    })
+}
+
+#[cfg(unix)]
+#[test]
+fn pair_across_threads() {
+    use completeio::driver::{FromRawFd, IntoRawFd};
+
+    let (first, second) = UnixStream::pair().unwrap();
+    let second = second.into_raw_fd();
+    let thread = std::thread::spawn(move || {
+        // SAFETY: the socket is moved to the thread
+        let second = unsafe { UnixStream::from_raw_fd(second) };
+        completeio::task::block_on(async {
+            let (res, buf) = second.recv_exact(Vec::with_capacity(4)).await;
+            res.unwrap();
+            second.send_all(buf).await.0.unwrap();
+        })
+    });
+    completeio::task::block_on(async {
+        first.send_all("ping").await.0.unwrap();
+        let (res, buf) = first.recv_exact(Vec::with_capacity(4)).await;
+        res.unwrap();
+        assert_eq!(buf, b"ping");
+    });
+    thread.join().unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn datagram_pair() {
+    use completeio::net::UnixDatagram;
+
+    completeio::task::block_on(async {
+        let (first, second) = UnixDatagram::pair().unwrap();
+        first.send("first").await.0.unwrap();
+        first.send("second").await.0.unwrap();
+        let (res, buf) = second.recv(Vec::with_capacity(16)).await;
+        assert_eq!(res.unwrap(), 5);
+        assert_eq!(buf, b"first");
+        let (res, buf) = second.recv(Vec::with_capacity(16)).await;
+        assert_eq!(res.unwrap(), 6);
+        assert_eq!(buf, b"second");
+    })
+}
+
+#[cfg(unix)]
+#[test]
+fn pass_pipe_to_task() {
+    use completeio::{
+        driver::{AsRawFd, FromRawFd, IntoRawFd},
+        pipe::{pipe, PipeWriter},
+    };
+
+    completeio::task::block_on(async {
+        let (parent, child) = UnixStream::pair().unwrap();
+        let child = completeio::task::spawn(async move {
+            let (res, _) = child.recv_with_fds(Vec::with_capacity(1), 1).await;
+            let (_, mut fds) = res.unwrap();
+            assert_eq!(fds.len(), 1);
+            // SAFETY: the received descriptor is owned by the task
+            let writer = unsafe { PipeWriter::from_raw_fd(fds.pop().unwrap().into_raw_fd()) };
+            writer.write_all("from child").await.0.unwrap();
+        });
+
+        let (reader, writer) = pipe().unwrap();
+        // SAFETY: the writer is dropped after the call
+        let fd = unsafe { std::os::fd::BorrowedFd::borrow_raw(writer.as_raw_fd()) };
+        parent.send_with_fds("w", &[fd]).await.0.unwrap();
+        // the child holds the only writing end
+        drop(writer);
+        child.await.unwrap();
+
+        let (res, buf) = reader.read_exact(Vec::with_capacity(10)).await;
+        res.unwrap();
+        assert_eq!(buf, b"from child");
+        let (res, _) = reader.read(Vec::with_capacity(1)).await;
+        assert_eq!(res.unwrap(), 0);
+    })
+}
@@ -0,0 +1,25 @@
+//! The default runtime configuration is global, the tests changing it run in their own binary.
+
+use std::io::ErrorKind;
+
+use completeio::{
+    driver::DriverBuilder,
+    task::{Builder, RuntimeBuilder},
+};
+
+#[test]
+fn worker_runtime_error() {
+    RuntimeBuilder::new()
+        .driver(DriverBuilder::new().sq_entries(0))
+        .set_default();
+    let err = Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap_err();
+    // the error of the driver creation is returned as is
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert_eq!(
+        err.to_string(),
+        "submission queue entries should be nonzero"
+    );
+}